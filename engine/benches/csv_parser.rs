@@ -0,0 +1,45 @@
+// Benchmarks for loading candles from B3 CSV exports
+use std::io::Write;
+
+use chrono::{Duration, NaiveDate};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use engine::data::csv_parser::BrazilianCsvParser;
+use tempfile::NamedTempFile;
+
+/// `value` the way B3 writes prices: "30,15".
+fn price(value: f64) -> String {
+    format!("{:.2}", value).replace('.', ",")
+}
+
+/// A CSV file of `rows` one-minute bars in the B3 export format.
+fn csv_file(rows: usize) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(file, "Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade").unwrap();
+    let start = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap().and_hms_opt(10, 0, 0).unwrap();
+    for i in 0..rows {
+        let at = start + Duration::minutes(i as i64);
+        let close = 30.0 + (i % 100) as f64 / 100.0;
+        writeln!(
+            file, "PETR4;{};{};{};{};{};{};1.234.567,89;{}",
+            at.format("%d/%m/%Y"), at.format("%H:%M:%S"), price(close), price(close + 0.05), price(close - 0.05), price(close), i % 500
+        ).unwrap();
+    }
+    file.flush().unwrap();
+    file
+}
+
+fn load_csv(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load_candles_from_csv");
+    for rows in [10_000, 100_000] {
+        let file = csv_file(rows);
+        let path = file.path().to_str().unwrap().to_string();
+        group.throughput(Throughput::Elements(rows as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(rows), &path, |b, path| {
+            b.iter(|| BrazilianCsvParser::load_candles_from_csv(path, "PETR4").unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, load_csv);
+criterion_main!(benches);
@@ -0,0 +1,39 @@
+// Benchmarks for indicator calculation, from candles and from a series' cached columns
+use chrono::{Duration, TimeZone, Utc};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use engine::indicators::{Atr, Ema, IndicatorCalculator, Rsi, Sma, Vwap};
+use shared::models::{to_price, Candle, CandleSeries};
+
+fn series(candles: usize) -> CandleSeries {
+    let start = Utc.with_ymd_and_hms(2024, 1, 2, 13, 0, 0).unwrap();
+    CandleSeries::new((0..candles).map(|i| {
+        let close = 30.0 + ((i as f64) / 50.0).sin();
+        Candle {
+            symbol: "PETR4".to_string(),
+            timestamp: start + Duration::minutes(i as i64),
+            open: to_price(close - 0.02), high: to_price(close + 0.05), low: to_price(close - 0.05), close: to_price(close),
+            volume: 1_000.0, trades: 10,
+        }
+    }).collect())
+}
+
+fn indicators(c: &mut Criterion) {
+    let series = series(100_000);
+    series.columns(); // Extracted once, as the first request on a stored series would
+    let calculators: Vec<Box<dyn IndicatorCalculator>> =
+        vec![Box::new(Sma::new(20).unwrap()), Box::new(Ema::new(20).unwrap()), Box::new(Rsi::new(14).unwrap()), Box::new(Atr::new(14).unwrap()), Box::new(Vwap::new())];
+
+    let mut group = c.benchmark_group("indicators");
+    for calculator in &calculators {
+        group.bench_with_input(BenchmarkId::new("candles", calculator.name()), &series, |b, series| {
+            b.iter(|| calculator.calculate(series))
+        });
+        group.bench_with_input(BenchmarkId::new("columns", calculator.name()), &series, |b, series| {
+            b.iter(|| calculator.calculate_columns(series.columns()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, indicators);
+criterion_main!(benches);
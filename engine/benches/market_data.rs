@@ -0,0 +1,48 @@
+// Benchmarks for range queries on the market data store
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use engine::data::market_data::MarketDataStore;
+use shared::models::{to_price, Candle, TimeFrame};
+
+fn minute(i: usize) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 2, 13, 0, 0).unwrap() + Duration::minutes(i as i64)
+}
+
+fn store_with(candles: usize) -> MarketDataStore {
+    let mut store = MarketDataStore::new();
+    let series = (0..candles).map(|i| {
+        let close = to_price(30.0 + (i % 100) as f64 / 100.0);
+        Candle { symbol: "PETR4".to_string(), timestamp: minute(i), open: close, high: close, low: close, close, volume: 1_000.0, trades: 10 }
+    }).collect();
+    store.add_candles("PETR4", TimeFrame::Minute1, series).unwrap();
+    store
+}
+
+/// A day's worth of bars from the middle of a long series: the shape of a chart or export request.
+fn range_queries(c: &mut Criterion) {
+    let mut group = c.benchmark_group("range_query");
+    for candles in [10_000, 1_000_000] {
+        let store = store_with(candles);
+        let (from, to) = (Some(minute(candles / 2)), Some(minute(candles / 2 + 1_440)));
+        group.bench_with_input(BenchmarkId::new("count_candles", candles), &store, |b, store| {
+            b.iter(|| store.count_candles("PETR4", TimeFrame::Minute1, black_box(from), black_box(to)))
+        });
+        group.bench_with_input(BenchmarkId::new("get_candles", candles), &store, |b, store| {
+            b.iter(|| store.get_candles("PETR4", TimeFrame::Minute1, black_box(from), black_box(to)))
+        });
+        group.bench_with_input(BenchmarkId::new("get_candles_page", candles), &store, |b, store| {
+            b.iter(|| store.get_candles_page("PETR4", TimeFrame::Minute1, black_box(from), black_box(to), 0, 5_000))
+        });
+        // What the store used to do: filter every candle of the series.
+        group.bench_with_input(BenchmarkId::new("linear_filter", candles), &store, |b, store| {
+            b.iter(|| {
+                let all = store.get_candles("PETR4", TimeFrame::Minute1, None, None).unwrap().unwrap();
+                all.iter().filter(|c| Some(c.timestamp) >= from && Some(c.timestamp) <= to).cloned().collect::<Vec<_>>()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, range_queries);
+criterion_main!(benches);
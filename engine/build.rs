@@ -1,11 +1,17 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("cargo:rerun-if-changed=proto/trading.proto"); // Rerun if .proto file changes
+    println!("cargo:rerun-if-changed=proto"); // Rerun if a .proto file changes
+    // Reported by GetEngineInfo.
+    println!("cargo:rustc-env=BUILD_TARGET={}", std::env::var("TARGET")?);
     tonic_build::configure()
         .build_server(true) // Generate server code
         .build_client(true) // Generate client code (optional, but can be useful for tests or if engine itself calls other gRPC services)
+        // Messages can be logged or cached as JSON with their proto field names; enum fields stay numbers.
+        // Only the trading package: google.rpc's messages hold prost_types::Any, which has no serde support.
+        .type_attribute(".trading", "#[derive(serde::Serialize, serde::Deserialize)]")
         // .out_dir("src/services/generated") // Output directory for generated Rust code - Let tonic_build use default OUT_DIR
         .compile(
-            &["proto/trading.proto"], // Path to .proto files relative to engine crate root
+            // Paths to .proto files relative to engine crate root; google/rpc holds the standard error details
+            &["proto/trading.proto", "proto/google/rpc/status.proto", "proto/google/rpc/error_details.proto"],
             &["proto"], // Include path for .proto files
         )?;
     Ok(())
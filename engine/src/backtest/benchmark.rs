@@ -0,0 +1,135 @@
+// Benchmark comparison
+// Puts a strategy's equity curve next to buying and holding a benchmark (the traded
+// symbol itself, or an index such as IBOV) with the same starting capital, and
+// relates their per-bar returns (alpha, beta, correlation).
+use chrono::{DateTime, Utc};
+use shared::models::Candle;
+
+use super::metrics::{mean, period_returns, PERIODS_PER_YEAR};
+use super::report::EquityPoint;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkComparison {
+    pub symbol: String,
+    /// Buy and hold of the benchmark, on the strategy curve's timestamps.
+    pub equity_curve: Vec<EquityPoint>,
+    pub benchmark_return_pct: f64,
+    pub strategy_return_pct: f64,
+    /// Strategy return minus benchmark return, in percentage points.
+    pub excess_return_pct: f64,
+    /// Annualized intercept of strategy returns regressed on benchmark returns (a fraction, 0.05 = 5%).
+    pub alpha: f64,
+    pub beta: f64,
+    pub correlation: f64,
+}
+
+/// Compares `strategy_curve` with buying `benchmark` at the first close at or before the
+/// curve's start. Benchmark closes are carried forward over bars it has no candle for.
+/// `None` when the benchmark has no price at the start of the curve.
+pub fn compare_to_benchmark(symbol: &str, strategy_curve: &[EquityPoint], benchmark: &[Candle]) -> Option<BenchmarkComparison> {
+    let first = strategy_curve.first()?;
+    let start_price = close_at(benchmark, first.timestamp)?;
+    let initial = first.equity;
+
+    let equity_curve: Vec<EquityPoint> = strategy_curve.iter()
+        .map(|p| EquityPoint {
+            timestamp: p.timestamp,
+            equity: initial * close_at(benchmark, p.timestamp).unwrap_or(start_price) / start_price,
+        })
+        .collect();
+
+    let return_pct = |curve: &[EquityPoint]| match (curve.first(), curve.last()) {
+        (Some(a), Some(b)) if a.equity != 0.0 => (b.equity / a.equity - 1.0) * 100.0,
+        _ => 0.0,
+    };
+    let strategy_return_pct = return_pct(strategy_curve);
+    let benchmark_return_pct = return_pct(&equity_curve);
+
+    let (alpha, beta, correlation) = regression(&period_returns(strategy_curve), &period_returns(&equity_curve));
+    Some(BenchmarkComparison {
+        symbol: symbol.to_string(),
+        equity_curve,
+        benchmark_return_pct,
+        strategy_return_pct,
+        excess_return_pct: strategy_return_pct - benchmark_return_pct,
+        alpha,
+        beta,
+        correlation,
+    })
+}
+
+// Latest close at or before `timestamp` (candles are chronological).
+fn close_at(candles: &[Candle], timestamp: DateTime<Utc>) -> Option<f64> {
+    let index = candles.partition_point(|c| c.timestamp <= timestamp);
+    index.checked_sub(1).map(|i| candles[i].close_f64())
+}
+
+// (annualized alpha, beta, correlation) of `y` on `x`; zeros when either series is flat.
+fn regression(y: &[f64], x: &[f64]) -> (f64, f64, f64) {
+    let n = y.len().min(x.len());
+    if n < 2 {
+        return (0.0, 0.0, 0.0);
+    }
+    let (y, x) = (&y[..n], &x[..n]);
+    let (mean_y, mean_x) = (mean(y), mean(x));
+    let covariance: f64 = y.iter().zip(x).map(|(a, b)| (a - mean_y) * (b - mean_x)).sum();
+    let var_x: f64 = x.iter().map(|b| (b - mean_x).powi(2)).sum();
+    let var_y: f64 = y.iter().map(|a| (a - mean_y).powi(2)).sum();
+    if var_x == 0.0 {
+        return (mean_y * PERIODS_PER_YEAR, 0.0, 0.0);
+    }
+    let beta = covariance / var_x;
+    let correlation = if var_y == 0.0 { 0.0 } else { covariance / (var_x * var_y).sqrt() };
+    ((mean_y - beta * mean_x) * PERIODS_PER_YEAR, beta, correlation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::{to_price, Price};
+    use chrono::{Duration, TimeZone};
+
+    fn at(day: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(day)
+    }
+
+    fn benchmark(closes: &[f64]) -> Vec<Candle> {
+        closes.iter().enumerate().map(|(i, &close)| Candle {
+            symbol: "IBOV".to_string(), timestamp: at(i as i64), open: to_price(close), high: to_price(close), low: to_price(close), close: to_price(close), volume: 0.0, trades: 0,
+        }).collect()
+    }
+
+    #[test]
+    fn test_leveraged_copy_has_beta_two() {
+        let closes = [100.0, 102.0, 99.0, 103.0, 101.0];
+        // A strategy whose every return is twice the benchmark's.
+        let mut equity = 1_000.0;
+        let mut curve = vec![EquityPoint { timestamp: at(0), equity }];
+        for i in 1..closes.len() {
+            equity *= 1.0 + 2.0 * (closes[i] / closes[i - 1] - 1.0);
+            curve.push(EquityPoint { timestamp: at(i as i64), equity });
+        }
+
+        let comparison = compare_to_benchmark("IBOV", &curve, &benchmark(&closes)).unwrap();
+        assert!((comparison.beta - 2.0).abs() < 1e-9);
+        assert!((comparison.correlation - 1.0).abs() < 1e-9);
+        assert!(comparison.alpha.abs() < 1e-9);
+        assert!((comparison.benchmark_return_pct - 1.0).abs() < 1e-9);
+        assert!((comparison.equity_curve[3].equity - 1_030.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_missing_benchmark_bars_carry_forward() {
+        let curve: Vec<EquityPoint> = (0..3).map(|d| EquityPoint { timestamp: at(d), equity: 1_000.0 }).collect();
+        let sparse = vec![benchmark(&[100.0])[0].clone(), Candle { timestamp: at(2), close: Price::from(110), ..benchmark(&[110.0])[0].clone() }];
+        let comparison = compare_to_benchmark("IBOV", &curve, &sparse).unwrap();
+        assert_eq!(comparison.equity_curve[1].equity, 1_000.0);
+        assert!((comparison.equity_curve[2].equity - 1_100.0).abs() < 1e-9);
+        // A flat strategy neither tracks nor beats the benchmark.
+        assert_eq!(comparison.beta, 0.0);
+        assert!((comparison.excess_return_pct + 10.0).abs() < 1e-9);
+
+        let late = vec![Candle { timestamp: at(5), ..benchmark(&[100.0])[0].clone() }];
+        assert!(compare_to_benchmark("IBOV", &curve, &late).is_none());
+    }
+}
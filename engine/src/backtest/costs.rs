@@ -0,0 +1,183 @@
+// Carrying costs for multi-day backtests: overnight financing/borrow fees and
+// futures rollovers at contract expiry. Charges are collected while the runner
+// walks the bars and then deducted from the report (equity curve, trades, totals).
+use chrono::{DateTime, NaiveDate, Utc};
+use shared::models::{to_f64, to_price, Candle};
+
+use super::report::PnlReport;
+use crate::data::calendar::trading_days_between;
+use crate::simulation::{contract_expiry, front_contract, next_contract, ContractSpec, Contracts};
+
+/// Business days per year used to accrue the annual overnight rate.
+pub const BUSINESS_DAYS_PER_YEAR: f64 = 252.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CarryCosts {
+    /// Annual rate on the notional of equity positions held overnight (margin financing
+    /// for longs, borrow fee for shorts), accrued per business day.
+    pub overnight_rate: f64,
+    /// Flat fee per share/contract per business day held overnight.
+    pub overnight_fee: f64,
+    /// Fee per contract each time a futures position is rolled to the next contract.
+    pub rollover_fee: f64,
+    /// Calendar spread paid per contract on each roll, in points.
+    pub rollover_spread: f64,
+}
+
+impl CarryCosts {
+    pub fn is_zero(&self) -> bool {
+        *self == CarryCosts::default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CostKind {
+    Overnight,
+    Rollover { from: String, to: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostCharge {
+    pub symbol: String,
+    pub timestamp: DateTime<Utc>,
+    pub kind: CostKind,
+    /// In currency; positive is a cost.
+    pub amount: f64,
+}
+
+/// Walks a single-symbol series and works out what holding a position costs.
+pub struct CarryModel {
+    costs: CarryCosts,
+    symbol: String,
+    spec: Option<ContractSpec>,
+    /// Futures contract currently held; for the generic FUT series, the front month.
+    contract: Option<String>,
+}
+
+impl CarryModel {
+    pub fn new(costs: CarryCosts, symbol: &str, first_date: Option<NaiveDate>, contracts: &Contracts) -> Self {
+        let spec = contracts.spec(symbol);
+        let contract = spec.and_then(|spec| {
+            if contract_expiry(symbol).is_some() {
+                Some(symbol.trim().to_uppercase())
+            } else {
+                first_date.and_then(|date| front_contract(spec.root, date))
+            }
+        });
+        Self { costs, symbol: symbol.to_string(), spec, contract }
+    }
+
+    /// Charges for holding `position` (signed) from the close of `prev` to `candle`:
+    /// one overnight charge per B3 trading day crossed, and a roll for each contract that
+    /// expired in between. The contract is tracked even while flat.
+    pub fn charges(&mut self, prev: &Candle, candle: &Candle, position: f64) -> Vec<CostCharge> {
+        let mut charges = Vec::new();
+        let (from, to) = (prev.timestamp.date_naive(), candle.timestamp.date_naive());
+        if to <= from {
+            return charges;
+        }
+
+        if position != 0.0 {
+            let nights = trading_days_between(from, to).max(1) as f64;
+            let financing = match self.spec {
+                None => position.abs() * prev.close_f64() * self.costs.overnight_rate / BUSINESS_DAYS_PER_YEAR,
+                Some(_) => 0.0, // Futures are not financed: only the flat fee applies
+            };
+            let amount = nights * (financing + position.abs() * self.costs.overnight_fee);
+            if amount != 0.0 {
+                charges.push(CostCharge { symbol: self.symbol.clone(), timestamp: candle.timestamp, kind: CostKind::Overnight, amount });
+            }
+        }
+
+        if let Some(spec) = self.spec {
+            while let Some(current) = self.contract.clone() {
+                match contract_expiry(&current) {
+                    Some(expiry) if expiry <= to => {}
+                    _ => break,
+                }
+                let Some(next) = next_contract(&current) else { break };
+                if position != 0.0 {
+                    let amount = position.abs() * (self.costs.rollover_fee + self.costs.rollover_spread * spec.point_value);
+                    tracing::debug!(symbol = %self.symbol, from = %current, to = %next, amount, "Rolling futures position");
+                    charges.push(CostCharge {
+                        symbol: self.symbol.clone(),
+                        timestamp: candle.timestamp,
+                        kind: CostKind::Rollover { from: current.clone(), to: next.clone() },
+                        amount,
+                    });
+                }
+                self.contract = Some(next);
+            }
+        }
+        charges
+    }
+}
+
+/// Deducts carrying costs from a report built from fills alone. Each charge lowers the
+/// equity from its timestamp on, and the P&L of the round trip that was open at the time.
+pub fn apply_charges(report: &mut PnlReport, charges: &[CostCharge], contracts: &Contracts) {
+    if charges.is_empty() {
+        return;
+    }
+    for point in report.equity_curve.iter_mut() {
+        point.equity -= charges.iter().filter(|c| c.timestamp <= point.timestamp).map(|c| c.amount).sum::<f64>();
+    }
+    for trade in report.trades.iter_mut() {
+        let cost: f64 = charges.iter()
+            .filter(|c| c.symbol == trade.symbol && c.timestamp > trade.entry_time && c.timestamp <= trade.exit_time)
+            .map(|c| c.amount)
+            .sum();
+        if cost != 0.0 {
+            let point_value = contracts.point_value(&trade.symbol);
+            let notional = to_f64(trade.entry_price) * trade.quantity * point_value;
+            trade.pnl -= to_price(cost);
+            trade.return_pct = if notional != 0.0 { to_f64(trade.pnl) / notional * 100.0 } else { 0.0 };
+        }
+    }
+    let total: f64 = charges.iter().map(|c| c.amount).sum();
+    report.total_costs += total;
+    report.realized_pnl -= total; // Costs are paid in cash as they accrue
+    report.final_equity -= total;
+    report.total_pnl -= total;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::to_price;
+    use chrono::TimeZone;
+
+    fn candle(symbol: &str, y: i32, m: u32, d: u32, close: f64) -> Candle {
+        Candle {
+            symbol: symbol.to_string(),
+            timestamp: Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap(),
+            open: to_price(close), high: to_price(close), low: to_price(close), close: to_price(close), volume: 0.0, trades: 0,
+        }
+    }
+
+    #[test]
+    fn test_overnight_financing_counts_business_days() {
+        let costs = CarryCosts { overnight_rate: 0.252, ..Default::default() };
+        let mut model = CarryModel::new(costs, "PETR4", None, &Contracts::default());
+        // Friday 2024-01-05 to Monday 2024-01-08: one business day on 100 x R$10.
+        let charges = model.charges(&candle("PETR4", 2024, 1, 5, 10.0), &candle("PETR4", 2024, 1, 8, 10.0), -100.0);
+        assert_eq!(charges.len(), 1);
+        assert!((charges[0].amount - 1.0).abs() < 1e-9);
+        assert!(model.charges(&candle("PETR4", 2024, 1, 8, 10.0), &candle("PETR4", 2024, 1, 9, 10.0), 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_futures_roll_at_expiry() {
+        let costs = CarryCosts { rollover_fee: 2.0, rollover_spread: 100.0, ..Default::default() };
+        let mut model = CarryModel::new(costs, "WINFUT", NaiveDate::from_ymd_opt(2024, 12, 16), &Contracts::default());
+        // WINZ24 expires on Wednesday 2024-12-18.
+        assert!(model.charges(&candle("WINFUT", 2024, 12, 16, 1.0), &candle("WINFUT", 2024, 12, 17, 1.0), 2.0).is_empty());
+        let charges = model.charges(&candle("WINFUT", 2024, 12, 17, 1.0), &candle("WINFUT", 2024, 12, 18, 1.0), 2.0);
+        assert_eq!(charges.len(), 1);
+        assert_eq!(charges[0].kind, CostKind::Rollover { from: "WINZ24".to_string(), to: "WING25".to_string() });
+        // 2 contracts x (R$2 + 100 points x R$0.20).
+        assert!((charges[0].amount - 44.0).abs() < 1e-9);
+        // Already on WING25: no second roll.
+        assert!(model.charges(&candle("WINFUT", 2024, 12, 18, 1.0), &candle("WINFUT", 2024, 12, 19, 1.0), 2.0).is_empty());
+    }
+}
@@ -0,0 +1,295 @@
+// Declarative strategies
+// A strategy described as data (JSON or TOML) instead of Rust code, e.g.:
+//
+//   name = "EMA cross"
+//   direction = "long"
+//   entry = ["EMA(9) crosses above EMA(21)", "RSI(14) < 70"]
+//   exit = ["EMA(9) crosses below EMA(21)"]
+//
+// All conditions of a list must hold on the same bar. Operands are indicators
+// (SMA(n), EMA(n), RSI(n)), price fields (OPEN, HIGH, LOW, CLOSE, VOLUME) or numbers.
+use serde::Deserialize;
+use shared::models::Candle;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::strategy::{Signal, Strategy};
+use crate::error::EngineError;
+use crate::indicators::{Ema, IndicatorCalculator, Rsi, Sma};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SpecDirection {
+    #[default]
+    Long,
+    Short,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StrategySpec {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub direction: SpecDirection,
+    pub entry: Vec<String>,
+    /// Without exit conditions a position is held until the end of the data.
+    #[serde(default)]
+    pub exit: Vec<String>,
+}
+
+impl StrategySpec {
+    pub fn from_json(text: &str) -> Result<Self, EngineError> {
+        serde_json::from_str(text).map_err(|e| EngineError::InvalidRequest(format!("Invalid strategy spec (JSON): {}", e)))
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, EngineError> {
+        toml::from_str(text).map_err(|e| EngineError::InvalidRequest(format!("Invalid strategy spec (TOML): {}", e)))
+    }
+
+    /// Loads a spec file; the format is picked from the extension (`.toml`, anything else is JSON).
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, EngineError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Self::from_toml(&text),
+            _ => Self::from_json(&text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Price(String), // OPEN, HIGH, LOW, CLOSE, VOLUME
+    Indicator(String, usize), // (kind, period)
+    Constant(f64),
+}
+
+impl Operand {
+    fn parse(text: &str) -> Result<Self, EngineError> {
+        let text = text.trim().to_uppercase();
+        if let Ok(value) = text.parse::<f64>() {
+            return Ok(Operand::Constant(value));
+        }
+        if matches!(text.as_str(), "OPEN" | "HIGH" | "LOW" | "CLOSE" | "VOLUME") {
+            return Ok(Operand::Price(text));
+        }
+        if let Some((kind, rest)) = text.split_once('(') {
+            let kind = kind.trim();
+            let period = rest.strip_suffix(')').and_then(|p| p.trim().parse::<usize>().ok());
+            if let (true, Some(period)) = (matches!(kind, "SMA" | "EMA" | "RSI"), period) {
+                if period == 0 {
+                    return Err(EngineError::InvalidRequest(format!("Indicator period cannot be 0 in '{}'", text)));
+                }
+                return Ok(Operand::Indicator(kind.to_string(), period));
+            }
+        }
+        Err(EngineError::InvalidRequest(format!(
+            "Unknown operand '{}'. Use SMA(n), EMA(n), RSI(n), OPEN, HIGH, LOW, CLOSE, VOLUME or a number.",
+            text
+        )))
+    }
+
+    fn key(&self) -> String {
+        match self {
+            Operand::Price(field) => field.clone(),
+            Operand::Indicator(kind, period) => format!("{}({})", kind, period),
+            Operand::Constant(value) => value.to_string(),
+        }
+    }
+
+    fn series(&self, candles: &[Candle]) -> Vec<f64> {
+        match self {
+            Operand::Price(field) => candles.iter().map(|c| match field.as_str() {
+                "OPEN" => c.open_f64(),
+                "HIGH" => c.high_f64(),
+                "LOW" => c.low_f64(),
+                "VOLUME" => c.volume,
+                _ => c.close_f64(),
+            }).collect(),
+            // `parse` rejects a period of 0, the only one the indicators refuse.
+            Operand::Indicator(kind, period) => match kind.as_str() {
+                "SMA" => Sma::new(*period).map(|sma| sma.calculate(candles)),
+                "EMA" => Ema::new(*period).map(|ema| ema.calculate(candles)),
+                _ => Rsi::new(*period).map(|rsi| rsi.calculate(candles)),
+            }.unwrap_or_else(|_| vec![f64::NAN; candles.len()]),
+            Operand::Constant(value) => vec![*value; candles.len()],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    CrossesAbove,
+    CrossesBelow,
+    GreaterOrEqual,
+    LessOrEqual,
+    Greater,
+    Less,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    left: Operand,
+    comparison: Comparison,
+    right: Operand,
+}
+
+// Longest operators first so ">=" is not read as ">".
+const OPERATORS: [(&str, Comparison); 6] = [
+    (" CROSSES ABOVE ", Comparison::CrossesAbove),
+    (" CROSSES BELOW ", Comparison::CrossesBelow),
+    (">=", Comparison::GreaterOrEqual),
+    ("<=", Comparison::LessOrEqual),
+    (">", Comparison::Greater),
+    ("<", Comparison::Less),
+];
+
+impl Condition {
+    fn parse(text: &str) -> Result<Self, EngineError> {
+        let upper = format!(" {} ", text.trim().to_uppercase());
+        for (token, comparison) in OPERATORS {
+            if let Some((left, right)) = upper.split_once(token) {
+                return Ok(Condition { left: Operand::parse(left)?, comparison, right: Operand::parse(right)? });
+            }
+        }
+        Err(EngineError::InvalidRequest(format!(
+            "Cannot parse condition '{}'. Expected '<operand> <op> <operand>' with op one of: crosses above, crosses below, >, <, >=, <=",
+            text.trim()
+        )))
+    }
+
+    fn holds(&self, series: &HashMap<String, Vec<f64>>, index: usize) -> bool {
+        let (left, right) = (&series[&self.left.key()], &series[&self.right.key()]);
+        let (l, r) = (left[index], right[index]);
+        if l.is_nan() || r.is_nan() {
+            return false;
+        }
+        match self.comparison {
+            Comparison::Greater => l > r,
+            Comparison::Less => l < r,
+            Comparison::GreaterOrEqual => l >= r,
+            Comparison::LessOrEqual => l <= r,
+            Comparison::CrossesAbove | Comparison::CrossesBelow => {
+                if index == 0 || left[index - 1].is_nan() || right[index - 1].is_nan() {
+                    return false;
+                }
+                let (prev_l, prev_r) = (left[index - 1], right[index - 1]);
+                if self.comparison == Comparison::CrossesAbove {
+                    prev_l <= prev_r && l > r
+                } else {
+                    prev_l >= prev_r && l < r
+                }
+            }
+        }
+    }
+}
+
+pub struct DeclarativeStrategy {
+    name: String,
+    direction: SpecDirection,
+    entry: Vec<Condition>,
+    exit: Vec<Condition>,
+    series: HashMap<String, Vec<f64>>,
+    in_position: bool,
+}
+
+impl DeclarativeStrategy {
+    pub fn from_spec(spec: &StrategySpec) -> Result<Self, EngineError> {
+        if spec.entry.is_empty() {
+            return Err(EngineError::InvalidRequest("Strategy spec needs at least one entry condition".to_string()));
+        }
+        let parse_all = |conditions: &[String]| conditions.iter().map(|c| Condition::parse(c)).collect::<Result<Vec<_>, _>>();
+        Ok(Self {
+            name: if spec.name.is_empty() { "Declarative".to_string() } else { spec.name.clone() },
+            direction: spec.direction,
+            entry: parse_all(&spec.entry)?,
+            exit: parse_all(&spec.exit)?,
+            series: HashMap::new(),
+            in_position: false,
+        })
+    }
+}
+
+impl Strategy for DeclarativeStrategy {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn prepare(&mut self, candles: &[Candle]) {
+        self.series.clear();
+        self.in_position = false;
+        for condition in self.entry.iter().chain(self.exit.iter()) {
+            for operand in [&condition.left, &condition.right] {
+                self.series.entry(operand.key()).or_insert_with(|| operand.series(candles));
+            }
+        }
+    }
+
+    fn on_bar(&mut self, _candles: &[Candle], index: usize) -> Signal {
+        let all_hold = |conditions: &[Condition]| conditions.iter().all(|c| c.holds(&self.series, index));
+        if !self.in_position && all_hold(&self.entry) {
+            self.in_position = true;
+            return match self.direction {
+                SpecDirection::Long => Signal::EnterLong,
+                SpecDirection::Short => Signal::EnterShort,
+            };
+        }
+        if self.in_position && !self.exit.is_empty() && all_hold(&self.exit) {
+            self.in_position = false;
+            return Signal::Exit;
+        }
+        Signal::Hold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::to_price;
+    use chrono::Utc;
+
+    fn create_candle(close: f64) -> Candle {
+        Candle { symbol: "TEST".to_string(), timestamp: Utc::now(), open: to_price(close), high: to_price(close), low: to_price(close), close: to_price(close), volume: 0.0, trades: 0 }
+    }
+
+    #[test]
+    fn test_parse_conditions() {
+        let c = Condition::parse("EMA(9) crosses above ema(21)").unwrap();
+        assert_eq!(c.left, Operand::Indicator("EMA".to_string(), 9));
+        assert_eq!(c.comparison, Comparison::CrossesAbove);
+        assert_eq!(Condition::parse("RSI(14) >= 70").unwrap().comparison, Comparison::GreaterOrEqual);
+        assert_eq!(Condition::parse("close < 10.5").unwrap().right, Operand::Constant(10.5));
+        assert!(Condition::parse("EMA(9) above EMA(21)").is_err());
+        assert!(Condition::parse("MACD(9) > 0").is_err());
+        assert!(Condition::parse("SMA(0) > 0").is_err());
+    }
+
+    #[test]
+    fn test_spec_formats_and_signals() {
+        let toml_spec = StrategySpec::from_toml(r#"
+            name = "SMA cross"
+            entry = ["SMA(2) crosses above SMA(3)"]
+            exit = ["SMA(2) crosses below SMA(3)"]
+        "#).unwrap();
+        let json_spec = StrategySpec::from_json(r#"{"name": "SMA cross", "entry": ["SMA(2) crosses above SMA(3)"], "exit": ["SMA(2) crosses below SMA(3)"]}"#).unwrap();
+        assert_eq!(toml_spec.entry, json_spec.entry);
+        assert_eq!(toml_spec.direction, SpecDirection::Long);
+
+        let closes = [10.0, 9.0, 8.0, 7.0, 9.0, 11.0, 12.0, 8.0, 5.0];
+        let candles: Vec<Candle> = closes.iter().map(|&c| create_candle(c)).collect();
+        let mut strategy = DeclarativeStrategy::from_spec(&toml_spec).unwrap();
+        strategy.prepare(&candles);
+        let signals: Vec<Signal> = (0..candles.len()).map(|i| strategy.on_bar(&candles, i)).collect();
+        let entry = signals.iter().position(|s| *s == Signal::EnterLong).unwrap();
+        let exit = signals.iter().position(|s| *s == Signal::Exit).unwrap();
+        assert!(entry < exit);
+        assert_eq!(strategy.name(), "SMA cross");
+    }
+
+    #[test]
+    fn test_spec_requires_entry() {
+        let spec = StrategySpec::from_json(r#"{"entry": []}"#).unwrap();
+        assert!(DeclarativeStrategy::from_spec(&spec).is_err());
+        assert!(StrategySpec::from_json(r#"{"exit": ["CLOSE > 1"]}"#).is_err());
+    }
+}
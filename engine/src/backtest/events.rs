@@ -0,0 +1,32 @@
+// Events of the backtest event loop.
+// The runner turns each bar into a candle event; handling it may raise cost, order,
+// fill and position events, which are handled (and reported to an observer, e.g. the
+// StreamBacktest RPC) in the order they occur.
+use chrono::{DateTime, Utc};
+use shared::models::{Candle, Price};
+
+use super::costs::CostCharge;
+use crate::simulation::{Fill, OrderSide};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BacktestEvent {
+    /// Bar `index` of its symbol's series is being processed.
+    Candle { index: usize, candle: Candle },
+    /// The strategy asked for `target` (signed) at the next open; `quantity` is the change needed.
+    Order { symbol: String, timestamp: DateTime<Utc>, side: OrderSide, quantity: f64, target: f64 },
+    Fill(Fill),
+    /// Position after a fill.
+    Position { symbol: String, timestamp: DateTime<Utc>, quantity: f64, average_price: Price, realized_pnl: Price },
+    Cost(CostCharge),
+}
+
+impl BacktestEvent {
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            BacktestEvent::Candle { candle, .. } => candle.timestamp,
+            BacktestEvent::Order { timestamp, .. } | BacktestEvent::Position { timestamp, .. } => *timestamp,
+            BacktestEvent::Fill(fill) => fill.timestamp,
+            BacktestEvent::Cost(charge) => charge.timestamp,
+        }
+    }
+}
@@ -0,0 +1,150 @@
+// Risk and performance metrics computed from a trade list and an equity curve
+use super::report::EquityPoint;
+use shared::models::{to_f64, Trade};
+
+/// Bars per year used to annualize Sharpe/Sortino (daily bars, B3 trading days).
+pub const PERIODS_PER_YEAR: f64 = 252.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PerformanceMetrics {
+    pub total_trades: usize,
+    pub sharpe_ratio: f64,
+    pub sortino_ratio: f64,
+    pub max_drawdown: f64,     // In currency, from the equity curve peak
+    pub max_drawdown_pct: f64, // Percentage of the peak equity
+    pub win_rate: f64,         // Percentage of trades with positive P&L
+    /// Gross profit / gross loss. Infinite when there are winners but no losers, 0 without trades.
+    pub profit_factor: f64,
+    pub expectancy: f64, // Average P&L per trade
+    pub avg_mae: f64,
+    pub avg_mfe: f64,
+}
+
+/// Per-bar simple returns of an equity curve.
+pub(crate) fn period_returns(equity_curve: &[EquityPoint]) -> Vec<f64> {
+    equity_curve.windows(2)
+        .filter(|w| w[0].equity != 0.0)
+        .map(|w| (w[1].equity - w[0].equity) / w[0].equity)
+        .collect()
+}
+
+pub(crate) fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() { 0.0 } else { values.iter().sum::<f64>() / values.len() as f64 }
+}
+
+/// Annualized Sharpe ratio (risk-free rate of 0) of per-bar returns.
+pub fn sharpe_ratio(returns: &[f64]) -> f64 {
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let avg = mean(returns);
+    let variance = returns.iter().map(|r| (r - avg).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    let std_dev = variance.sqrt();
+    if std_dev == 0.0 { 0.0 } else { avg / std_dev * PERIODS_PER_YEAR.sqrt() }
+}
+
+/// Annualized Sortino ratio: like Sharpe, but only downside deviation counts as risk.
+pub fn sortino_ratio(returns: &[f64]) -> f64 {
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let downside = (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / returns.len() as f64).sqrt();
+    if downside == 0.0 { 0.0 } else { mean(returns) / downside * PERIODS_PER_YEAR.sqrt() }
+}
+
+/// Largest peak-to-trough decline of the equity curve, as (amount, percentage of peak).
+pub fn max_drawdown(equity_curve: &[EquityPoint]) -> (f64, f64) {
+    let mut peak = f64::NEG_INFINITY;
+    let (mut max_dd, mut max_dd_pct) = (0.0_f64, 0.0_f64);
+    for point in equity_curve {
+        peak = peak.max(point.equity);
+        let drawdown = peak - point.equity;
+        max_dd = max_dd.max(drawdown);
+        if peak > 0.0 {
+            max_dd_pct = max_dd_pct.max(drawdown / peak * 100.0);
+        }
+    }
+    (max_dd, max_dd_pct)
+}
+
+pub fn compute_metrics(trades: &[Trade], equity_curve: &[EquityPoint]) -> PerformanceMetrics {
+    let returns = period_returns(equity_curve);
+    let (max_drawdown, max_drawdown_pct) = max_drawdown(equity_curve);
+
+    let pnls: Vec<f64> = trades.iter().map(|t| to_f64(t.pnl)).collect();
+    let gross_profit: f64 = pnls.iter().filter(|p| **p > 0.0).sum();
+    let gross_loss: f64 = -pnls.iter().filter(|p| **p < 0.0).sum::<f64>();
+    let winners = pnls.iter().filter(|p| **p > 0.0).count();
+
+    let profit_factor = if gross_loss > 0.0 {
+        gross_profit / gross_loss
+    } else if gross_profit > 0.0 {
+        f64::INFINITY
+    } else {
+        0.0
+    };
+
+    PerformanceMetrics {
+        total_trades: trades.len(),
+        sharpe_ratio: sharpe_ratio(&returns),
+        sortino_ratio: sortino_ratio(&returns),
+        max_drawdown,
+        max_drawdown_pct,
+        win_rate: if trades.is_empty() { 0.0 } else { winners as f64 / trades.len() as f64 * 100.0 },
+        profit_factor,
+        expectancy: mean(&pnls),
+        avg_mae: mean(&trades.iter().map(|t| to_f64(t.mae)).collect::<Vec<_>>()),
+        avg_mfe: mean(&trades.iter().map(|t| to_f64(t.mfe)).collect::<Vec<_>>()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::TradeDirection;
+    use shared::models::{to_price, Price};
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn curve(values: &[f64]) -> Vec<EquityPoint> {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        values.iter().enumerate().map(|(i, &equity)| EquityPoint { timestamp: t0 + Duration::days(i as i64), equity }).collect()
+    }
+
+    fn trade(pnl: f64, mae: f64, mfe: f64) -> Trade {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        Trade {
+            symbol: "TEST".to_string(), direction: TradeDirection::Long, entry_time: t0, exit_time: t0,
+            entry_price: Price::from(10), exit_price: Price::from(10), quantity: 1.0, pnl: to_price(pnl), return_pct: 0.0, mae: to_price(mae), mfe: to_price(mfe),
+        }
+    }
+
+    #[test]
+    fn test_max_drawdown() {
+        let (dd, dd_pct) = max_drawdown(&curve(&[100.0, 120.0, 90.0, 110.0, 80.0, 130.0]));
+        assert!((dd - 40.0).abs() < 1e-9);
+        assert!((dd_pct - 40.0 / 120.0 * 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_trade_statistics() {
+        let trades = vec![trade(30.0, -5.0, 40.0), trade(-10.0, -15.0, 5.0), trade(20.0, -1.0, 21.0), trade(-20.0, -25.0, 0.0)];
+        let metrics = compute_metrics(&trades, &curve(&[100.0, 130.0, 120.0, 140.0, 120.0]));
+        assert_eq!(metrics.total_trades, 4);
+        assert!((metrics.win_rate - 50.0).abs() < 1e-9);
+        assert!((metrics.profit_factor - 50.0 / 30.0).abs() < 1e-9);
+        assert!((metrics.expectancy - 5.0).abs() < 1e-9);
+        assert!((metrics.avg_mae + 11.5).abs() < 1e-9);
+        assert!((metrics.avg_mfe - 16.5).abs() < 1e-9);
+        assert!(metrics.sharpe_ratio > 0.0);
+        assert!(metrics.sortino_ratio > 0.0);
+    }
+
+    #[test]
+    fn test_degenerate_inputs() {
+        let metrics = compute_metrics(&[], &curve(&[100.0]));
+        assert_eq!(metrics, PerformanceMetrics::default());
+        assert_eq!(compute_metrics(&[trade(5.0, 0.0, 5.0)], &[]).profit_factor, f64::INFINITY);
+        // Flat equity has no volatility: ratios are reported as 0 rather than NaN.
+        assert_eq!(sharpe_ratio(&[0.0, 0.0, 0.0]), 0.0);
+    }
+}
@@ -0,0 +1,25 @@
+// Backtesting module
+// Runs a strategy over stored candles against a simulated portfolio and
+// summarizes the outcome (trade list, P&L, equity curve).
+pub mod benchmark;
+pub mod costs;
+pub mod declarative;
+pub mod events;
+pub mod metrics;
+pub mod monte_carlo;
+pub mod report;
+pub mod runner;
+pub mod strategy;
+pub mod stress;
+
+pub use benchmark::{compare_to_benchmark, BenchmarkComparison};
+pub use costs::{apply_charges, CarryCosts, CarryModel, CostCharge, CostKind};
+pub use declarative::{DeclarativeStrategy, StrategySpec};
+pub use events::BacktestEvent;
+pub use metrics::{compute_metrics, PerformanceMetrics};
+pub use monte_carlo::{run_monte_carlo, MonteCarloConfig, MonteCarloMethod, MonteCarloSummary, Percentiles};
+pub use report::{build_report, EquityPoint, PnlReport};
+pub use shared::models::{Trade, TradeDirection};
+pub use runner::{run_backtest, run_backtest_with_events, run_portfolio_backtest, run_portfolio_backtest_with_events, BacktestConfig, BacktestLeg, BacktestOutcome};
+pub use strategy::{create_strategy, Signal, SmaCrossover, Strategy};
+pub use stress::{run_stress_test, run_stress_test_with_progress, StressResult, StressScenario};
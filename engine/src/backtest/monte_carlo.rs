@@ -0,0 +1,193 @@
+// Monte Carlo robustness analysis
+// Re-orders (shuffle) or resamples with replacement (bootstrap) the P&L of a
+// backtest's trades to see how much of its result depends on trade sequence,
+// and summarizes the simulated return and drawdown distributions.
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::error::EngineError;
+
+pub const DEFAULT_MONTE_CARLO_ITERATIONS: usize = 1_000;
+pub const MAX_MONTE_CARLO_ITERATIONS: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonteCarloMethod {
+    /// Same trades, random order: final return is fixed, drawdown varies.
+    Shuffle,
+    /// Trades drawn with replacement: both return and drawdown vary.
+    Bootstrap,
+}
+
+impl MonteCarloMethod {
+    /// Parses the `method` field of a `MonteCarloRequest`; empty means SHUFFLE.
+    pub fn parse(method: &str) -> Result<Self, EngineError> {
+        match method.trim().to_uppercase().as_str() {
+            "" | "SHUFFLE" => Ok(MonteCarloMethod::Shuffle),
+            "BOOTSTRAP" => Ok(MonteCarloMethod::Bootstrap),
+            other => Err(EngineError::InvalidRequest(format!(
+                "Unsupported Monte Carlo method: '{}'. Use 'SHUFFLE' or 'BOOTSTRAP'.",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MonteCarloConfig {
+    pub iterations: usize,
+    pub method: MonteCarloMethod,
+    /// Fixed seed for reproducible runs.
+    pub seed: Option<u64>,
+}
+
+impl Default for MonteCarloConfig {
+    fn default() -> Self {
+        Self { iterations: DEFAULT_MONTE_CARLO_ITERATIONS, method: MonteCarloMethod::Shuffle, seed: None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Percentiles {
+    pub p5: f64,
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p95: f64,
+}
+
+impl Percentiles {
+    /// Nearest-rank percentiles; `values` is sorted in place.
+    pub fn from_values(values: &mut [f64]) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        let at = |p: f64| values[((p / 100.0) * (values.len() - 1) as f64).round() as usize];
+        Self { p5: at(5.0), p25: at(25.0), p50: at(50.0), p75: at(75.0), p95: at(95.0) }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MonteCarloSummary {
+    pub iterations: usize,
+    pub return_pct: Percentiles,
+    pub max_drawdown_pct: Percentiles,
+    /// Share of simulations (percentage) that ended below the initial capital.
+    pub probability_of_loss: f64,
+}
+
+/// Return and max drawdown (both in % of capital) of one trade sequence.
+fn simulate_path(initial_capital: f64, pnls: impl Iterator<Item = f64>) -> (f64, f64) {
+    let mut equity = initial_capital;
+    let mut peak = initial_capital;
+    let mut max_dd_pct = 0.0_f64;
+    for pnl in pnls {
+        equity += pnl;
+        peak = peak.max(equity);
+        if peak > 0.0 {
+            max_dd_pct = max_dd_pct.max((peak - equity) / peak * 100.0);
+        }
+    }
+    ((equity - initial_capital) / initial_capital * 100.0, max_dd_pct)
+}
+
+/// Runs the simulation, calling `on_progress(completed, total)` periodically (about
+/// every 5%). Returning `false` from the callback cancels the run.
+pub fn run_monte_carlo(
+    trade_pnls: &[f64],
+    initial_capital: f64,
+    config: MonteCarloConfig,
+    mut on_progress: impl FnMut(usize, usize) -> bool,
+) -> Result<MonteCarloSummary, EngineError> {
+    if trade_pnls.is_empty() {
+        return Err(EngineError::InvalidRequest("Monte Carlo analysis needs at least one closed trade".to_string()));
+    }
+    if config.iterations == 0 || config.iterations > MAX_MONTE_CARLO_ITERATIONS {
+        return Err(EngineError::InvalidRequest(format!(
+            "Monte Carlo iterations must be between 1 and {}, got {}",
+            MAX_MONTE_CARLO_ITERATIONS, config.iterations
+        )));
+    }
+    if initial_capital <= 0.0 {
+        return Err(EngineError::InvalidRequest("Initial capital must be positive".to_string()));
+    }
+
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let report_every = (config.iterations / 20).max(1);
+    let mut returns = Vec::with_capacity(config.iterations);
+    let mut drawdowns = Vec::with_capacity(config.iterations);
+    let mut sequence = trade_pnls.to_vec();
+
+    for i in 0..config.iterations {
+        let (ret, dd) = match config.method {
+            MonteCarloMethod::Shuffle => {
+                sequence.shuffle(&mut rng);
+                simulate_path(initial_capital, sequence.iter().copied())
+            }
+            MonteCarloMethod::Bootstrap => {
+                let draws = (0..trade_pnls.len()).map(|_| trade_pnls[rng.gen_range(0..trade_pnls.len())]);
+                simulate_path(initial_capital, draws)
+            }
+        };
+        returns.push(ret);
+        drawdowns.push(dd);
+
+        let completed = i + 1;
+        if (completed % report_every == 0 || completed == config.iterations) && !on_progress(completed, config.iterations) {
+            return Err(EngineError::ProcessingError("Monte Carlo analysis cancelled".to_string()));
+        }
+    }
+
+    let losses = returns.iter().filter(|r| **r < 0.0).count();
+    Ok(MonteCarloSummary {
+        iterations: config.iterations,
+        probability_of_loss: losses as f64 / config.iterations as f64 * 100.0,
+        return_pct: Percentiles::from_values(&mut returns),
+        max_drawdown_pct: Percentiles::from_values(&mut drawdowns),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shuffle_keeps_final_return() {
+        let pnls = [100.0, -50.0, 200.0, -150.0, 80.0];
+        let config = MonteCarloConfig { iterations: 200, method: MonteCarloMethod::Shuffle, seed: Some(7) };
+        let mut progress = Vec::new();
+        let summary = run_monte_carlo(&pnls, 1_000.0, config, |done, total| { progress.push((done, total)); true }).unwrap();
+
+        // Same trades in any order always add up to +180 on 1000.
+        assert!((summary.return_pct.p5 - 18.0).abs() < 1e-9);
+        assert!((summary.return_pct.p95 - 18.0).abs() < 1e-9);
+        assert_eq!(summary.probability_of_loss, 0.0);
+        assert!(summary.max_drawdown_pct.p5 <= summary.max_drawdown_pct.p95);
+        assert_eq!(progress.len(), 20);
+        assert_eq!(*progress.last().unwrap(), (200, 200));
+    }
+
+    #[test]
+    fn test_bootstrap_is_reproducible_with_seed() {
+        let pnls = [100.0, -120.0, 30.0, -10.0];
+        let config = MonteCarloConfig { iterations: 500, method: MonteCarloMethod::Bootstrap, seed: Some(42) };
+        let a = run_monte_carlo(&pnls, 1_000.0, config, |_, _| true).unwrap();
+        let b = run_monte_carlo(&pnls, 1_000.0, config, |_, _| true).unwrap();
+        assert_eq!(a, b);
+        assert!(a.return_pct.p5 < a.return_pct.p95);
+        assert!(a.probability_of_loss > 0.0);
+    }
+
+    #[test]
+    fn test_invalid_input_and_cancellation() {
+        let config = MonteCarloConfig::default();
+        assert!(run_monte_carlo(&[], 1_000.0, config, |_, _| true).is_err());
+        assert!(run_monte_carlo(&[1.0], 1_000.0, MonteCarloConfig { iterations: 0, ..config }, |_, _| true).is_err());
+        assert!(run_monte_carlo(&[1.0], 1_000.0, config, |_, _| false).is_err());
+        assert!(MonteCarloMethod::parse("jackknife").is_err());
+    }
+}
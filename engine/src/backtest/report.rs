@@ -0,0 +1,257 @@
+// P&L report: closed trades (round trips) and the equity curve built from a fill journal
+use chrono::{DateTime, Utc};
+use shared::models::{to_f64, to_price, Candle, Price, Trade, TradeDirection, PRICE_SCALE};
+use std::collections::HashMap;
+
+use crate::simulation::{Contracts, Fill, Portfolio, Position};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquityPoint {
+    pub timestamp: DateTime<Utc>,
+    pub equity: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PnlReport {
+    pub initial_capital: f64,
+    pub final_equity: f64,
+    pub total_pnl: f64,
+    pub realized_pnl: f64,
+    /// Carrying costs (overnight fees, rollovers) already deducted from the figures above.
+    pub total_costs: f64,
+    pub trades: Vec<Trade>,
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+// Round trip being accumulated while the position is open.
+struct OpenTrade {
+    direction: TradeDirection,
+    entry_time: DateTime<Utc>,
+    entry_qty: f64,
+    entry_notional: Price,
+    exit_qty: f64,
+    exit_notional: Price,
+    pnl: Price,
+}
+
+impl OpenTrade {
+    fn new(direction: TradeDirection, fill: &Fill, quantity: f64) -> Self {
+        Self {
+            direction,
+            entry_time: fill.timestamp,
+            entry_qty: quantity,
+            entry_notional: to_price(quantity) * fill.price,
+            exit_qty: 0.0,
+            exit_notional: Price::ZERO,
+            pnl: Price::ZERO,
+        }
+    }
+
+    // `point_value` converts the price-unit notional into currency for the return.
+    fn close(self, symbol: &str, exit_time: DateTime<Utc>, point_value: f64) -> Trade {
+        let notional = to_f64(self.entry_notional) * point_value;
+        let average = |notional: Price, quantity: f64| notional.checked_div(to_price(quantity)).unwrap_or_default().round_dp(PRICE_SCALE);
+        Trade {
+            symbol: symbol.to_string(),
+            direction: self.direction,
+            entry_time: self.entry_time,
+            exit_time,
+            entry_price: average(self.entry_notional, self.entry_qty),
+            exit_price: average(self.exit_notional, self.exit_qty),
+            quantity: self.entry_qty,
+            pnl: self.pnl,
+            return_pct: if notional != 0.0 { to_f64(self.pnl) / notional * 100.0 } else { 0.0 },
+            mae: Price::ZERO,
+            mfe: Price::ZERO,
+        }
+    }
+}
+
+/// Groups fills into closed round trips, ordered by exit time. Open trades are not listed.
+pub fn round_trips(fills: &[Fill], contracts: &Contracts) -> Vec<Trade> {
+    let mut sorted: Vec<&Fill> = fills.iter().collect();
+    sorted.sort_by_key(|f| f.timestamp); // Stable: fills at the same instant keep journal order
+
+    let mut positions: HashMap<&str, Position> = HashMap::new();
+    let mut open: HashMap<&str, OpenTrade> = HashMap::new();
+    let mut trades = Vec::new();
+
+    for fill in sorted {
+        let symbol = fill.symbol.as_str();
+        let position = positions.entry(symbol).or_insert_with(|| Position::new(symbol, contracts.point_value(symbol)));
+        let before = position.quantity;
+        let realized = position.apply(fill.side, fill.quantity, fill.price);
+        let after = position.quantity;
+        let point_value = position.point_value;
+
+        match open.remove(symbol) {
+            None => {
+                open.insert(symbol, OpenTrade::new(TradeDirection::of(fill.side), fill, fill.quantity));
+            }
+            Some(mut trade) if TradeDirection::of(fill.side) == trade.direction => {
+                trade.entry_qty += fill.quantity;
+                trade.entry_notional += to_price(fill.quantity) * fill.price;
+                open.insert(symbol, trade);
+            }
+            Some(mut trade) => {
+                let closing = fill.quantity.min(before.abs());
+                trade.exit_qty += closing;
+                trade.exit_notional += to_price(closing) * fill.price;
+                trade.pnl += realized;
+                if after == 0.0 {
+                    trades.push(trade.close(symbol, fill.timestamp, point_value));
+                } else if after.signum() != before.signum() {
+                    trades.push(trade.close(symbol, fill.timestamp, point_value));
+                    open.insert(symbol, OpenTrade::new(TradeDirection::of(fill.side), fill, fill.quantity - closing));
+                } else {
+                    open.insert(symbol, trade);
+                }
+            }
+        }
+    }
+    trades
+}
+
+/// Equity at every candle timestamp in `marks` (positions marked at the close),
+/// replaying fills up to and including each timestamp.
+pub fn equity_curve(initial_capital: f64, fills: &[Fill], marks: &HashMap<String, Vec<Candle>>, contracts: &Contracts) -> Vec<EquityPoint> {
+    let mut sorted_fills: Vec<&Fill> = fills.iter().collect();
+    sorted_fills.sort_by_key(|f| f.timestamp);
+
+    let mut closes: Vec<(DateTime<Utc>, &str, f64)> = marks.iter()
+        .flat_map(|(symbol, candles)| candles.iter().map(move |c| (c.timestamp, symbol.as_str(), c.close_f64())))
+        .collect();
+    closes.sort_by_key(|(ts, _, _)| *ts);
+
+    let mut portfolio = Portfolio::new(initial_capital);
+    portfolio.set_contracts(contracts.clone());
+    let mut prices: HashMap<String, f64> = HashMap::new();
+    let mut curve: Vec<EquityPoint> = Vec::new();
+    let mut next_fill = 0;
+
+    for (timestamp, symbol, close) in closes {
+        while next_fill < sorted_fills.len() && sorted_fills[next_fill].timestamp <= timestamp {
+            portfolio.apply_fill(sorted_fills[next_fill].clone());
+            next_fill += 1;
+        }
+        prices.insert(symbol.to_string(), close);
+        let equity = portfolio.equity(&prices);
+        // Several symbols can share a timestamp: keep one point per instant.
+        match curve.last_mut() {
+            Some(last) if last.timestamp == timestamp => last.equity = equity,
+            _ => curve.push(EquityPoint { timestamp, equity }),
+        }
+    }
+
+    // Fills after the last known candle still count towards the final equity.
+    if next_fill < sorted_fills.len() {
+        let last_ts = sorted_fills[sorted_fills.len() - 1].timestamp;
+        for fill in &sorted_fills[next_fill..] {
+            portfolio.apply_fill((*fill).clone());
+        }
+        curve.push(EquityPoint { timestamp: last_ts, equity: portfolio.equity(&prices) });
+    }
+    curve
+}
+
+/// Fills in MAE/MFE for each trade from the candles traded through between entry and exit,
+/// measured against the average entry price for the full quantity, in currency.
+pub fn annotate_excursions(trades: &mut [Trade], marks: &HashMap<String, Vec<Candle>>, contracts: &Contracts) {
+    for trade in trades.iter_mut() {
+        let Some(candles) = marks.get(&trade.symbol) else { continue };
+        let size = to_price(trade.quantity) * to_price(contracts.point_value(&trade.symbol));
+        let (mut mae, mut mfe) = (Price::ZERO, Price::ZERO);
+        for candle in candles.iter().filter(|c| c.timestamp >= trade.entry_time && c.timestamp <= trade.exit_time) {
+            let (worst, best) = match trade.direction {
+                TradeDirection::Long => (candle.low - trade.entry_price, candle.high - trade.entry_price),
+                TradeDirection::Short => (trade.entry_price - candle.high, trade.entry_price - candle.low),
+            };
+            mae = mae.min(worst * size);
+            mfe = mfe.max(best * size);
+        }
+        trade.mae = mae;
+        trade.mfe = mfe;
+    }
+}
+
+/// Builds the full report for a fill journal, marking open positions with `marks`.
+pub fn build_report(initial_capital: f64, fills: &[Fill], marks: &HashMap<String, Vec<Candle>>, contracts: &Contracts) -> PnlReport {
+    let mut trades = round_trips(fills, contracts);
+    annotate_excursions(&mut trades, marks, contracts);
+    let equity_curve = equity_curve(initial_capital, fills, marks, contracts);
+
+    let mut portfolio = Portfolio::new(initial_capital);
+    portfolio.set_contracts(contracts.clone());
+    portfolio.apply_fills(fills.to_vec());
+    let final_equity = equity_curve.last().map(|p| p.equity).unwrap_or(initial_capital);
+
+    PnlReport {
+        initial_capital,
+        final_equity,
+        total_pnl: final_equity - initial_capital,
+        realized_pnl: to_f64(portfolio.realized_pnl()),
+        total_costs: 0.0,
+        trades,
+        equity_curve,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::{to_price, OrderSide};
+    use chrono::{Duration, TimeZone};
+
+    fn t(day: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(day)
+    }
+
+    fn fill(day: i64, side: OrderSide, quantity: f64, price: f64) -> Fill {
+        Fill { order_id: format!("o{}", day), symbol: "TEST".to_string(), side, price: to_price(price), quantity, timestamp: t(day) }
+    }
+
+    fn candle(day: i64, close: f64) -> Candle {
+        Candle { symbol: "TEST".to_string(), timestamp: t(day), open: to_price(close), high: to_price(close), low: to_price(close), close: to_price(close), volume: 0.0, trades: 0 }
+    }
+
+    #[test]
+    fn test_round_trips_with_scale_in_and_flip() {
+        let fills = vec![
+            fill(0, OrderSide::Buy, 10.0, 10.0),
+            fill(1, OrderSide::Buy, 10.0, 12.0),
+            fill(2, OrderSide::Sell, 30.0, 15.0), // Closes 20 long, opens 10 short
+            fill(3, OrderSide::Buy, 10.0, 14.0),
+        ];
+        let trades = round_trips(&fills, &Contracts::default());
+        assert_eq!(trades.len(), 2);
+
+        assert_eq!(trades[0].direction, TradeDirection::Long);
+        assert_eq!(trades[0].quantity, 20.0);
+        assert_eq!((trades[0].entry_price, trades[0].exit_price), (Price::from(11), Price::from(15)));
+        assert_eq!(trades[0].pnl, Price::from(80));
+        let json = serde_json::to_value(&trades[0]).unwrap();
+        assert_eq!((json["entry_price"].as_str(), json["pnl"].as_str()), (Some("11"), Some("80")));
+
+        assert_eq!(trades[1].direction, TradeDirection::Short);
+        assert_eq!(trades[1].entry_time, t(2));
+        assert_eq!(trades[1].pnl, Price::from(10));
+    }
+
+    #[test]
+    fn test_build_report_equity_curve() {
+        let fills = vec![fill(1, OrderSide::Buy, 10.0, 10.0), fill(3, OrderSide::Sell, 10.0, 13.0)];
+        let marks = HashMap::from([(
+            "TEST".to_string(),
+            vec![candle(0, 9.0), candle(1, 10.0), candle(2, 12.0), candle(3, 13.0), candle(4, 11.0)],
+        )]);
+        let report = build_report(1_000.0, &fills, &marks, &Contracts::default());
+
+        let equity: Vec<f64> = report.equity_curve.iter().map(|p| p.equity).collect();
+        assert_eq!(equity, vec![1_000.0, 1_000.0, 1_020.0, 1_030.0, 1_030.0]);
+        assert_eq!(report.trades.len(), 1);
+        assert!((report.total_pnl - 30.0).abs() < 1e-9);
+        assert!((report.realized_pnl - 30.0).abs() < 1e-9);
+        // Flat candles: the excursions follow the closes while the trade was open (10 -> 13).
+        assert_eq!((report.trades[0].mae, report.trades[0].mfe), (Price::ZERO, Price::from(30)));
+    }
+}
@@ -0,0 +1,471 @@
+// Bar-by-bar backtest runner
+use shared::models::{to_f64, to_price, Candle, Price};
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+use super::benchmark::{compare_to_benchmark, BenchmarkComparison};
+use super::costs::{apply_charges, CarryCosts, CarryModel, CostCharge};
+use super::events::BacktestEvent;
+use super::metrics::{compute_metrics, PerformanceMetrics};
+use super::report::{build_report, PnlReport};
+use super::strategy::{Signal, Strategy};
+use crate::error::EngineError;
+use crate::indicators::{Atr, IndicatorCalculator};
+use crate::simulation::portfolio::DEFAULT_INITIAL_CAPITAL;
+use crate::simulation::{position_size, Contracts, Fill, OrderSide, Position, SizingInput, SizingMethod};
+
+/// ATR period used when sizing entries with `SizingMethod::AtrRisk`.
+pub const SIZING_ATR_PERIOD: usize = 14;
+
+#[derive(Debug, Clone)]
+pub struct BacktestConfig {
+    pub initial_capital: f64,
+    /// Fixed number of shares/contracts per entry, also the fallback when `sizing` cannot size a trade.
+    pub quantity: f64,
+    /// Sizes each new entry from the equity at the signal bar instead of using `quantity`.
+    pub sizing: Option<SizingMethod>,
+    /// Overnight and rollover costs charged while a position is held across days.
+    pub costs: CarryCosts,
+    /// Bid/ask spread as a percentage of the price: buys fill half of it above the open, sells below.
+    pub spread_pct: f64,
+    /// When the run must give up, e.g. the deadline of the request that started it.
+    pub deadline: Option<Instant>,
+    /// Tick sizes and point values of the traded symbols.
+    pub contracts: Contracts,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self { initial_capital: DEFAULT_INITIAL_CAPITAL, quantity: 100.0, sizing: None, costs: CarryCosts::default(), spread_pct: 0.0, deadline: None, contracts: Contracts::default() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestOutcome {
+    pub strategy_name: String,
+    pub fills: Vec<Fill>,
+    /// Carrying costs deducted from `report`, in the order they were charged.
+    pub charges: Vec<CostCharge>,
+    pub report: PnlReport,
+    pub metrics: PerformanceMetrics,
+    /// Against buying and holding the traded symbol, unless replaced with another benchmark.
+    pub benchmark: Option<BenchmarkComparison>,
+}
+
+/// One symbol of a backtest, traded by its own strategy instance.
+pub struct BacktestLeg<'a> {
+    pub strategy: &'a mut dyn Strategy,
+    pub symbol: &'a str,
+    /// Chronological candles of `symbol`.
+    pub candles: &'a [Candle],
+}
+
+/// Runs `strategy` over `candles` (assumed chronological). A signal raised at the
+/// close of bar N is executed at the open of bar N+1, so a strategy never trades
+/// on a price it could not have seen. Runs with a `deadline` should use `run_backtest_with_events`.
+pub fn run_backtest(strategy: &mut dyn Strategy, symbol: &str, candles: &[Candle], config: BacktestConfig) -> BacktestOutcome {
+    run_backtest_with_events(strategy, symbol, candles, config, |_| true)
+        .expect("a backtest whose observer never stops it always completes")
+}
+
+/// Event-loop form of `run_backtest`: every event is passed to `on_event` as it is
+/// handled. Returning `false` stops the run (e.g. the client of a stream went away), and so
+/// does reaching `config.deadline`, with `EngineError::DeadlineExceeded`.
+pub fn run_backtest_with_events(
+    strategy: &mut dyn Strategy,
+    symbol: &str,
+    candles: &[Candle],
+    config: BacktestConfig,
+    on_event: impl FnMut(&BacktestEvent) -> bool,
+) -> Result<BacktestOutcome, EngineError> {
+    run_portfolio_backtest_with_events(vec![BacktestLeg { strategy, symbol, candles }], config, on_event)
+}
+
+/// Runs a basket of symbols against one account: every leg sizes its entries from the
+/// combined equity and the report has a single equity curve. Bars are processed in
+/// timestamp order across the legs; at each instant all opens are handled before any close.
+/// Runs with a `deadline` should use `run_portfolio_backtest_with_events`.
+pub fn run_portfolio_backtest(legs: Vec<BacktestLeg<'_>>, config: BacktestConfig) -> BacktestOutcome {
+    run_portfolio_backtest_with_events(legs, config, |_| true)
+        .expect("a backtest whose observer never stops it always completes")
+}
+
+/// Event-loop form of `run_portfolio_backtest`, see `run_backtest_with_events`.
+pub fn run_portfolio_backtest_with_events(
+    legs: Vec<BacktestLeg<'_>>,
+    config: BacktestConfig,
+    mut on_event: impl FnMut(&BacktestEvent) -> bool,
+) -> Result<BacktestOutcome, EngineError> {
+    let mut strategies = Vec::with_capacity(legs.len());
+    let mut books = Vec::with_capacity(legs.len());
+    for BacktestLeg { strategy, symbol, candles } in legs {
+        strategy.prepare(candles);
+        books.push(LegBook {
+            symbol,
+            candles,
+            carry: CarryModel::new(config.costs, symbol, candles.first().map(|c| c.timestamp.date_naive()), &config.contracts),
+            atr: match config.sizing {
+                Some(SizingMethod::AtrRisk { .. }) => Atr::new(SIZING_ATR_PERIOD)?.calculate(candles),
+                _ => Vec::new(),
+            },
+            book: Position::new(symbol, config.contracts.point_value(symbol)),
+            pending: None,
+            next: 0,
+            mark: None,
+        });
+        strategies.push(strategy);
+    }
+    let strategy_name = strategies.first().map(|s| s.name()).unwrap_or_default();
+    let mut event_loop = EventLoop {
+        legs: books,
+        costs: config.costs,
+        spread_pct: config.spread_pct,
+        contracts: config.contracts.clone(),
+        charges: Vec::new(),
+        fills: Vec::new(),
+        queue: VecDeque::new(),
+    };
+
+    // Each step takes the earliest pending bar across the legs and every leg with a bar at that instant.
+    while let Some(now) = event_loop.legs.iter().filter_map(|leg| leg.candles.get(leg.next).map(|c| c.timestamp)).min() {
+        let due: Vec<usize> = (0..event_loop.legs.len())
+            .filter(|&l| event_loop.legs[l].candles.get(event_loop.legs[l].next).is_some_and(|c| c.timestamp == now))
+            .collect();
+        if config.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err(deadline_exceeded(&strategy_name, &event_loop.legs));
+        }
+
+        // The open: carrying costs, then the orders signalled at the previous close.
+        for &l in &due {
+            let leg = &mut event_loop.legs[l];
+            let index = leg.next;
+            leg.mark = Some(leg.candles[index].close_f64());
+            event_loop.queue.push_back((l, BacktestEvent::Candle { index, candle: leg.candles[index].clone() }));
+            if !event_loop.drain(&mut on_event) {
+                return Err(stopped(&strategy_name, &event_loop.legs));
+            }
+        }
+
+        // The close: each strategy sees its bar with this open's fills already booked.
+        for &l in &due {
+            let index = event_loop.legs[l].next;
+            event_loop.legs[l].next += 1;
+            let leg = &event_loop.legs[l];
+            let position = leg.book.quantity;
+            let target = match strategies[l].on_bar(leg.candles, index) {
+                Signal::Hold => None,
+                // An entry signal while already positioned that way keeps the current size.
+                Signal::EnterLong if position > 0.0 => None,
+                Signal::EnterShort if position < 0.0 => None,
+                Signal::EnterLong => Some(entry_quantity(&*strategies[l], leg, index, &config, event_loop.equity(config.initial_capital))),
+                Signal::EnterShort => Some(-entry_quantity(&*strategies[l], leg, index, &config, event_loop.equity(config.initial_capital))),
+                Signal::Exit => Some(0.0),
+            };
+            if let Some(target) = target.filter(|t| *t != position) {
+                let delta = target - position;
+                event_loop.queue.push_back((l, BacktestEvent::Order {
+                    symbol: leg.symbol.to_string(),
+                    timestamp: now,
+                    side: if delta > 0.0 { OrderSide::Buy } else { OrderSide::Sell },
+                    quantity: delta.abs(),
+                    target,
+                }));
+                if !event_loop.drain(&mut on_event) {
+                    return Err(stopped(&strategy_name, &event_loop.legs));
+                }
+            }
+        }
+    }
+
+    let EventLoop { legs, fills, charges, .. } = event_loop;
+    let marks: HashMap<String, Vec<Candle>> = legs.iter().map(|leg| (leg.symbol.to_string(), leg.candles.to_vec())).collect();
+    let mut report = build_report(config.initial_capital, &fills, &marks, &config.contracts);
+    apply_charges(&mut report, &charges, &config.contracts);
+    let metrics = compute_metrics(&report.trades, &report.equity_curve);
+    let symbols: Vec<&str> = legs.iter().map(|leg| leg.symbol).collect();
+    tracing::info!(strategy = %strategy_name, symbols = ?symbols, fills = fills.len(), total_pnl = report.total_pnl, "Backtest finished");
+    // A basket has no single symbol to hold; callers can compare it with an index instead.
+    let benchmark = match legs.as_slice() {
+        [leg] => compare_to_benchmark(leg.symbol, &report.equity_curve, leg.candles),
+        _ => None,
+    };
+    Ok(BacktestOutcome { strategy_name, fills, charges, report, metrics, benchmark })
+}
+
+fn stopped(strategy_name: &str, legs: &[LegBook<'_>]) -> EngineError {
+    let symbols: Vec<&str> = legs.iter().map(|leg| leg.symbol).collect();
+    EngineError::ProcessingError(format!("Backtest of {} on {} was stopped", strategy_name, symbols.join(", ")))
+}
+
+fn deadline_exceeded(strategy_name: &str, legs: &[LegBook<'_>]) -> EngineError {
+    let symbols: Vec<&str> = legs.iter().map(|leg| leg.symbol).collect();
+    let (done, total) = legs.iter().fold((0, 0), |(done, total), leg| (done + leg.next, total + leg.candles.len()));
+    EngineError::DeadlineExceeded(format!(
+        "Backtest of {} on {} gave up after {} of {} bars", strategy_name, symbols.join(", "), done, total
+    ))
+}
+
+// Per-symbol state of a run.
+struct LegBook<'a> {
+    symbol: &'a str,
+    candles: &'a [Candle],
+    carry: CarryModel,
+    atr: Vec<f64>, // Only for `SizingMethod::AtrRisk`
+    book: Position, // Position and realized P&L
+    pending: Option<f64>, // Target position to reach at the next open
+    next: usize, // Index of the next bar to process
+    mark: Option<f64>, // Close of the latest bar processed
+}
+
+// State of a run; events queued while handling one are handled after it.
+// Each queued event is tagged with the index of the leg it belongs to.
+struct EventLoop<'a> {
+    legs: Vec<LegBook<'a>>,
+    costs: CarryCosts,
+    spread_pct: f64,
+    contracts: Contracts,
+    charges: Vec<CostCharge>,
+    fills: Vec<Fill>,
+    queue: VecDeque<(usize, BacktestEvent)>,
+}
+
+impl EventLoop<'_> {
+    /// Handles queued events until none are left; `false` if the observer stopped the run.
+    fn drain(&mut self, on_event: &mut impl FnMut(&BacktestEvent) -> bool) -> bool {
+        while let Some((leg, event)) = self.queue.pop_front() {
+            if !on_event(&event) {
+                return false;
+            }
+            self.handle(leg, event);
+        }
+        true
+    }
+
+    /// Account equity with every leg marked at its latest close, the sizing equity.
+    fn equity(&self, initial_capital: f64) -> f64 {
+        initial_capital + self.legs.iter().map(|leg| {
+            to_f64(leg.book.realized_pnl) + leg.mark.map(|close| leg.book.unrealized_pnl(close)).unwrap_or(0.0)
+        }).sum::<f64>()
+    }
+
+    // The open, moved by half the spread against the order and rounded to a tick that is not better.
+    fn fill_price(&self, symbol: &str, is_buy: bool, open: Price) -> Price {
+        if self.spread_pct == 0.0 {
+            return self.contracts.round_to_tick(symbol, open);
+        }
+        let half_spread = open * to_price(self.spread_pct) / Price::from(200);
+        let price = if is_buy { open + half_spread } else { open - half_spread };
+        self.contracts.round_limit_price(symbol, !is_buy, price)
+    }
+
+    fn handle(&mut self, l: usize, event: BacktestEvent) {
+        match event {
+            BacktestEvent::Candle { index, candle } => {
+                let leg = &mut self.legs[l];
+                // `book` still holds what was carried overnight.
+                if index > 0 && !self.costs.is_zero() {
+                    let charges = leg.carry.charges(&leg.candles[index - 1], &candle, leg.book.quantity);
+                    self.queue.extend(charges.into_iter().map(|charge| (l, BacktestEvent::Cost(charge))));
+                }
+                if let Some(target) = leg.pending.take() {
+                    let delta = target - leg.book.quantity;
+                    if delta != 0.0 {
+                        let symbol = leg.symbol;
+                        let fill = Fill {
+                            order_id: format!("bt-{}", self.fills.len() + 1),
+                            symbol: symbol.to_string(),
+                            side: if delta > 0.0 { OrderSide::Buy } else { OrderSide::Sell },
+                            price: self.fill_price(symbol, delta > 0.0, candle.open),
+                            quantity: delta.abs(),
+                            timestamp: candle.timestamp,
+                        };
+                        self.queue.push_back((l, BacktestEvent::Fill(fill)));
+                    }
+                }
+            }
+            BacktestEvent::Order { target, .. } => self.legs[l].pending = Some(target),
+            BacktestEvent::Fill(fill) => {
+                let book = &mut self.legs[l].book;
+                book.apply(fill.side, fill.quantity, fill.price);
+                self.queue.push_back((l, BacktestEvent::Position {
+                    symbol: fill.symbol.clone(),
+                    timestamp: fill.timestamp,
+                    quantity: book.quantity,
+                    average_price: book.average_price,
+                    realized_pnl: book.realized_pnl,
+                }));
+                self.fills.push(fill);
+            }
+            BacktestEvent::Position { .. } => {}
+            BacktestEvent::Cost(charge) => self.charges.push(charge),
+        }
+    }
+}
+
+/// Quantity for a new entry on `leg` signalled at the close of bar `index`, sized from the account `equity`.
+fn entry_quantity(strategy: &dyn Strategy, leg: &LegBook<'_>, index: usize, config: &BacktestConfig, equity: f64) -> f64 {
+    let Some(method) = config.sizing else {
+        return config.quantity;
+    };
+    let (symbol, candles) = (leg.symbol, leg.candles);
+    let mut input = SizingInput::for_symbol(symbol, equity, candles[index].close_f64(), &config.contracts);
+    input.stop_distance = strategy.stop_distance(candles, index);
+    input.atr = leg.atr.get(index).copied().filter(|a| !a.is_nan());
+    match position_size(method, &input) {
+        Ok(result) => result.quantity,
+        Err(e) => {
+            tracing::debug!(symbol, index, error = %e, "Position sizing failed; using the fixed backtest quantity");
+            config.quantity
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    // Goes long on bar 1, exits on bar 3.
+    struct Scripted;
+
+    impl Strategy for Scripted {
+        fn name(&self) -> String {
+            "Scripted".to_string()
+        }
+
+        fn on_bar(&mut self, _candles: &[Candle], index: usize) -> Signal {
+            match index {
+                1 => Signal::EnterLong,
+                3 => Signal::Exit,
+                _ => Signal::Hold,
+            }
+        }
+
+        fn stop_distance(&self, _candles: &[Candle], _index: usize) -> Option<f64> {
+            Some(2.0)
+        }
+    }
+
+    fn rising_candles() -> Vec<Candle> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        (0..6).map(|i| {
+            let open = 10.0 + i as f64;
+            Candle { symbol: "TEST".to_string(), timestamp: start + Duration::days(i), open: to_price(open), high: to_price(open + 1.0), low: to_price(open - 1.0), close: to_price(open + 0.5), volume: 0.0, trades: 0 }
+        }).collect()
+    }
+
+    #[test]
+    fn test_signals_fill_at_next_open() {
+        let candles = rising_candles();
+        let outcome = run_backtest(&mut Scripted, "TEST", &candles, BacktestConfig { initial_capital: 1_000.0, quantity: 10.0, ..Default::default() });
+        assert_eq!(outcome.fills.len(), 2);
+        assert_eq!(outcome.fills[0].price, Price::from(12)); // Open of bar 2
+        assert_eq!(outcome.fills[1].price, Price::from(14)); // Open of bar 4
+        assert_eq!(outcome.report.trades.len(), 1);
+        assert!((outcome.report.total_pnl - 20.0).abs() < 1e-9);
+        assert_eq!(outcome.report.equity_curve.len(), candles.len());
+        assert_eq!(outcome.metrics.total_trades, 1);
+        assert_eq!(outcome.metrics.win_rate, 100.0);
+        let benchmark = outcome.benchmark.unwrap();
+        assert_eq!(benchmark.symbol, "TEST");
+        // Buy and hold from the first close (10.5) to the last (15.5).
+        assert!((benchmark.benchmark_return_pct - (15.5 / 10.5 - 1.0) * 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_entries_sized_from_stop_distance() {
+        // Risking 1% of 1000 with a 2-point stop buys 5 shares instead of the fixed 10.
+        let config = BacktestConfig { initial_capital: 1_000.0, quantity: 10.0, sizing: Some(SizingMethod::FixedFractional { risk_fraction: 0.01 }), ..Default::default() };
+        let outcome = run_backtest(&mut Scripted, "TEST", &rising_candles(), config);
+        assert_eq!(outcome.fills[0].quantity, 5.0);
+        assert_eq!(outcome.fills[1].quantity, 5.0);
+        assert!((outcome.report.total_pnl - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_overnight_costs_reduce_pnl() {
+        // Held from the open of bar 2 to the open of bar 4: charged on bars 3 and 4.
+        let config = BacktestConfig { initial_capital: 1_000.0, quantity: 10.0, costs: CarryCosts { overnight_fee: 0.5, ..Default::default() }, ..Default::default() };
+        let outcome = run_backtest(&mut Scripted, "TEST", &rising_candles(), config);
+        assert_eq!(outcome.charges.len(), 2);
+        assert!((outcome.report.total_costs - 10.0).abs() < 1e-9);
+        assert!((outcome.report.total_pnl - 10.0).abs() < 1e-9);
+        assert_eq!(outcome.report.trades[0].pnl, Price::from(10));
+        assert!((outcome.report.equity_curve.last().unwrap().equity - 1_010.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_event_stream_order() {
+        let mut events = Vec::new();
+        let outcome = run_backtest_with_events(&mut Scripted, "TEST", &rising_candles(), BacktestConfig::default(), |event| {
+            events.push(event.clone());
+            true
+        }).unwrap();
+        let kinds: Vec<&str> = events.iter().map(|e| match e {
+            BacktestEvent::Candle { .. } => "candle",
+            BacktestEvent::Order { .. } => "order",
+            BacktestEvent::Fill(_) => "fill",
+            BacktestEvent::Position { .. } => "position",
+            BacktestEvent::Cost(_) => "cost",
+        }).collect();
+        assert_eq!(kinds, [
+            "candle", "candle", "order", "candle", "fill", "position", "candle", "order", "candle", "fill", "position", "candle",
+        ]);
+        assert!(matches!(events[5], BacktestEvent::Position { quantity, .. } if quantity == 100.0));
+        assert_eq!(outcome.fills.len(), 2);
+
+        // The observer can stop the run.
+        let mut seen = 0;
+        let stopped = run_backtest_with_events(&mut Scripted, "TEST", &rising_candles(), BacktestConfig::default(), |_| {
+            seen += 1;
+            seen < 3
+        });
+        assert!(stopped.is_err());
+
+        // So can a deadline that has passed, before the first bar.
+        let config = BacktestConfig { deadline: Some(std::time::Instant::now()), ..Default::default() };
+        let late = run_backtest_with_events(&mut Scripted, "TEST", &rising_candles(), config, |_| true);
+        assert!(matches!(late, Err(EngineError::DeadlineExceeded(msg)) if msg.contains("after 0 of 6 bars")));
+    }
+
+    #[test]
+    fn test_portfolio_backtest_interleaves_symbols() {
+        let first = rising_candles();
+        // Same prices one day later under another symbol: the calendars overlap on days 1-5.
+        let second: Vec<Candle> = rising_candles().into_iter().map(|mut c| {
+            c.symbol = "OTHER".to_string();
+            c.timestamp += Duration::days(1);
+            c
+        }).collect();
+        let (mut a, mut b) = (Scripted, Scripted);
+        let legs = vec![
+            BacktestLeg { strategy: &mut a, symbol: "TEST", candles: &first },
+            BacktestLeg { strategy: &mut b, symbol: "OTHER", candles: &second },
+        ];
+        let mut events = Vec::new();
+        let config = BacktestConfig { initial_capital: 1_000.0, quantity: 10.0, ..Default::default() };
+        let outcome = run_portfolio_backtest_with_events(legs, config, |event| {
+            events.push(event.clone());
+            true
+        }).unwrap();
+
+        let tagged: Vec<(&str, &str)> = events.iter().take(9).map(|e| match e {
+            BacktestEvent::Candle { candle, .. } => ("candle", candle.symbol.as_str()),
+            BacktestEvent::Order { symbol, .. } => ("order", symbol.as_str()),
+            BacktestEvent::Fill(fill) => ("fill", fill.symbol.as_str()),
+            BacktestEvent::Position { symbol, .. } => ("position", symbol.as_str()),
+            BacktestEvent::Cost(charge) => ("cost", charge.symbol.as_str()),
+        }).collect();
+        // Both opens of day 1 come before the first close's order.
+        assert_eq!(tagged, [
+            ("candle", "TEST"), ("candle", "TEST"), ("candle", "OTHER"), ("order", "TEST"),
+            ("candle", "TEST"), ("fill", "TEST"), ("position", "TEST"), ("candle", "OTHER"), ("order", "OTHER"),
+        ]);
+
+        assert_eq!(outcome.fills.len(), 4);
+        assert_eq!(outcome.report.trades.len(), 2);
+        assert!((outcome.report.total_pnl - 40.0).abs() < 1e-9);
+        assert_eq!(outcome.report.equity_curve.len(), 7); // Days 0-6
+        assert!(outcome.benchmark.is_none());
+    }
+}
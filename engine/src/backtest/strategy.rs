@@ -0,0 +1,149 @@
+// Strategy trait and built-in strategies for the backtester
+use serde_json::Value;
+use shared::models::Candle;
+
+use super::declarative::{DeclarativeStrategy, StrategySpec};
+use crate::error::EngineError;
+use crate::indicators::{IndicatorCalculator, Sma};
+
+/// What a strategy wants its position to be after a bar closes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Hold,
+    EnterLong,
+    EnterShort,
+    Exit,
+}
+
+pub trait Strategy: Send {
+    fn name(&self) -> String;
+    /// Called once with the full series before the run, e.g. to precompute indicators.
+    /// Implementations must only use values at or before `index` when answering `on_bar`.
+    fn prepare(&mut self, _candles: &[Candle]) {}
+    /// Signal at the close of bar `index`.
+    fn on_bar(&mut self, candles: &[Candle], index: usize) -> Signal;
+    /// Distance in points from an entry at bar `index` to its protective stop, used by
+    /// risk-based position sizing. `None` when the strategy has no stop.
+    fn stop_distance(&self, _candles: &[Candle], _index: usize) -> Option<f64> {
+        None
+    }
+}
+
+/// Long when the fast SMA crosses above the slow SMA, flat when it crosses back below.
+pub struct SmaCrossover {
+    fast_period: usize,
+    slow_period: usize,
+    fast_sma: Sma,
+    slow_sma: Sma,
+    fast: Vec<f64>,
+    slow: Vec<f64>,
+}
+
+impl SmaCrossover {
+    pub fn new(fast_period: usize, slow_period: usize) -> Result<Self, EngineError> {
+        if fast_period == 0 || slow_period == 0 {
+            return Err(EngineError::InvalidRequest("SMA crossover periods must be greater than 0".to_string()));
+        }
+        if fast_period >= slow_period {
+            return Err(EngineError::InvalidRequest(format!(
+                "SMA crossover fast period ({}) must be shorter than the slow period ({})",
+                fast_period, slow_period
+            )));
+        }
+        Ok(Self {
+            fast_period,
+            slow_period,
+            fast_sma: Sma::new(fast_period)?,
+            slow_sma: Sma::new(slow_period)?,
+            fast: Vec::new(),
+            slow: Vec::new(),
+        })
+    }
+}
+
+impl Strategy for SmaCrossover {
+    fn name(&self) -> String {
+        format!("SMA Crossover({}, {})", self.fast_period, self.slow_period)
+    }
+
+    fn prepare(&mut self, candles: &[Candle]) {
+        self.fast = self.fast_sma.calculate(candles);
+        self.slow = self.slow_sma.calculate(candles);
+    }
+
+    fn on_bar(&mut self, _candles: &[Candle], index: usize) -> Signal {
+        if index == 0 || index >= self.fast.len() {
+            return Signal::Hold;
+        }
+        let (prev_fast, prev_slow) = (self.fast[index - 1], self.slow[index - 1]);
+        let (fast, slow) = (self.fast[index], self.slow[index]);
+        if [prev_fast, prev_slow, fast, slow].iter().any(|v| v.is_nan()) {
+            return Signal::Hold;
+        }
+        if prev_fast <= prev_slow && fast > slow {
+            Signal::EnterLong
+        } else if prev_fast >= prev_slow && fast < slow {
+            Signal::Exit
+        } else {
+            Signal::Hold
+        }
+    }
+}
+
+/// Builds a strategy from its name and JSON parameters.
+/// For "declarative", the parameters are either the spec itself or `{"file": "<path to .json/.toml spec>"}`.
+pub fn create_strategy(name: &str, params: &Value) -> Result<Box<dyn Strategy>, EngineError> {
+    match name.to_lowercase().as_str() {
+        "sma_crossover" | "" => {
+            let fast = params.get("fast_period").and_then(|v| v.as_u64()).unwrap_or(9) as usize;
+            let slow = params.get("slow_period").and_then(|v| v.as_u64()).unwrap_or(21) as usize;
+            Ok(Box::new(SmaCrossover::new(fast, slow)?))
+        }
+        "declarative" => {
+            let spec = match params.get("file").and_then(|v| v.as_str()) {
+                Some(path) => StrategySpec::from_file(path)?,
+                None => serde_json::from_value(params.clone())
+                    .map_err(|e| EngineError::InvalidRequest(format!("Invalid strategy spec: {}", e)))?,
+            };
+            Ok(Box::new(DeclarativeStrategy::from_spec(&spec)?))
+        }
+        _ => Err(EngineError::InvalidField { field: "strategy".to_string(), message: format!("Unknown strategy: {}", name) }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::to_price;
+    use chrono::Utc;
+
+    fn create_candle(close: f64) -> Candle {
+        Candle { symbol: "TEST".to_string(), timestamp: Utc::now(), open: to_price(close), high: to_price(close), low: to_price(close), close: to_price(close), volume: 0.0, trades: 0 }
+    }
+
+    #[test]
+    fn test_sma_crossover_signals() {
+        // Falling then rising: the 2-bar SMA crosses above the 3-bar SMA once, then back below.
+        let closes = [10.0, 9.0, 8.0, 7.0, 9.0, 11.0, 12.0, 8.0, 5.0];
+        let candles: Vec<Candle> = closes.iter().map(|&c| create_candle(c)).collect();
+        let mut strategy = SmaCrossover::new(2, 3).unwrap();
+        strategy.prepare(&candles);
+        let signals: Vec<Signal> = (0..candles.len()).map(|i| strategy.on_bar(&candles, i)).collect();
+        assert_eq!(signals.iter().filter(|s| **s == Signal::EnterLong).count(), 1);
+        assert_eq!(signals.iter().filter(|s| **s == Signal::Exit).count(), 1);
+        let entry = signals.iter().position(|s| *s == Signal::EnterLong).unwrap();
+        let exit = signals.iter().position(|s| *s == Signal::Exit).unwrap();
+        assert!(entry < exit);
+    }
+
+    #[test]
+    fn test_create_strategy_validation() {
+        assert!(create_strategy("sma_crossover", &serde_json::json!({"fast_period": 5, "slow_period": 20})).is_ok());
+        assert!(create_strategy("sma_crossover", &serde_json::json!({"fast_period": 20, "slow_period": 5})).is_err());
+        assert!(create_strategy("sma_crossover", &serde_json::json!({"fast_period": 0})).is_err());
+        assert!(create_strategy("martingale", &Value::Null).is_err());
+        let spec = serde_json::json!({"name": "RSI dip", "entry": ["RSI(14) < 30"], "exit": ["RSI(14) > 50"]});
+        assert_eq!(create_strategy("declarative", &spec).unwrap().name(), "RSI dip");
+        assert!(create_strategy("declarative", &serde_json::json!({"file": "no_such_spec.toml"})).is_err());
+    }
+}
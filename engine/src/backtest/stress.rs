@@ -0,0 +1,218 @@
+// Scenario / stress testing
+// Re-runs a backtest on shocked copies of the historical candles (a price gap,
+// amplified volatility) and with wider spreads, and compares each outcome with the
+// unshocked baseline to show how much of the strategy's P&L survives.
+use shared::models::{to_f64, to_price, Candle, Price, PRICE_SCALE};
+
+use super::metrics::PerformanceMetrics;
+use super::runner::{run_backtest_with_events, BacktestConfig};
+use super::strategy::Strategy;
+use crate::error::EngineError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StressScenario {
+    pub name: String,
+    /// Gap applied once, at `gap_at` (a bar index; `None` = the middle bar): that bar and every
+    /// later price is moved by this percentage (e.g. -5 for a 5% gap down).
+    pub gap_pct: f64,
+    pub gap_at: Option<usize>,
+    /// Multiplies close-to-close returns and each bar's range around its close (1 = unchanged).
+    pub volatility_multiplier: f64,
+    /// Bid/ask spread as a percentage of the price; each fill pays half of it.
+    pub spread_pct: f64,
+}
+
+impl StressScenario {
+    pub fn gap(pct: f64) -> Self {
+        Self { name: format!("Gap {:+}%", pct), gap_pct: pct, ..Self::identity() }
+    }
+
+    pub fn volatility(multiplier: f64) -> Self {
+        Self { name: format!("Volatility x{}", multiplier), volatility_multiplier: multiplier, ..Self::identity() }
+    }
+
+    pub fn spread(pct: f64) -> Self {
+        Self { name: format!("Spread {}%", pct), spread_pct: pct, ..Self::identity() }
+    }
+
+    fn identity() -> Self {
+        Self { name: String::new(), gap_pct: 0.0, gap_at: None, volatility_multiplier: 1.0, spread_pct: 0.0 }
+    }
+
+    /// Scenarios run when the request does not name any.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::gap(-5.0),
+            Self::gap(5.0),
+            Self::volatility(2.0),
+            Self::spread(0.2),
+        ]
+    }
+
+    pub fn validate(&self) -> Result<(), EngineError> {
+        if !self.gap_pct.is_finite() || self.gap_pct <= -100.0 {
+            return Err(EngineError::InvalidRequest(format!("Scenario '{}': gap must be above -100%", self.name)));
+        }
+        if !self.volatility_multiplier.is_finite() || self.volatility_multiplier <= 0.0 {
+            return Err(EngineError::InvalidRequest(format!("Scenario '{}': volatility multiplier must be positive", self.name)));
+        }
+        if !self.spread_pct.is_finite() || self.spread_pct < 0.0 {
+            return Err(EngineError::InvalidRequest(format!("Scenario '{}': spread must not be negative", self.name)));
+        }
+        Ok(())
+    }
+
+    /// Shocked copy of `candles`. Prices stay positive however large the shock.
+    pub fn apply(&self, candles: &[Candle]) -> Vec<Candle> {
+        let m = self.volatility_multiplier;
+        let gap_at = self.gap_at.unwrap_or(candles.len() / 2);
+        let gap_factor = 1.0 + self.gap_pct / 100.0;
+        // The smallest price a decimal candle holds
+        let floor = |price: f64| price.max(to_f64(Price::new(1, PRICE_SCALE)));
+
+        let mut shocked: Vec<Candle> = Vec::with_capacity(candles.len());
+        let mut previous_close = 0.0;
+        for (i, candle) in candles.iter().enumerate() {
+            let close = match i {
+                0 => candle.close_f64(),
+                _ => floor(previous_close * (1.0 + m * (candle.close_f64() / candles[i - 1].close_f64() - 1.0))),
+            };
+            // The gap hits this bar's open too, not just the later closes.
+            let close = if i == gap_at { close * gap_factor } else { close };
+            let around_close = |price: f64| to_price(floor(close * (1.0 + m * (price / candle.close_f64() - 1.0))));
+            let mut bar = candle.clone();
+            bar.open = around_close(candle.open_f64());
+            bar.high = around_close(candle.high_f64());
+            bar.low = around_close(candle.low_f64());
+            bar.close = to_price(close);
+            shocked.push(bar);
+            previous_close = close;
+        }
+        shocked
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StressResult {
+    pub scenario: StressScenario,
+    pub total_pnl: f64,
+    /// Change against the baseline P&L, in currency.
+    pub pnl_change: f64,
+    /// The same change as a percentage of the baseline P&L magnitude (0 when the baseline is flat).
+    pub pnl_change_pct: f64,
+    pub metrics: PerformanceMetrics,
+}
+
+/// Runs the baseline and every scenario; `make_strategy` builds a fresh strategy per run.
+/// Returns the baseline first.
+pub fn run_stress_test(
+    make_strategy: impl Fn() -> Result<Box<dyn Strategy>, EngineError>,
+    symbol: &str,
+    candles: &[Candle],
+    config: BacktestConfig,
+    scenarios: &[StressScenario],
+) -> Result<(StressResult, Vec<StressResult>), EngineError> {
+    run_stress_test_with_progress(make_strategy, symbol, candles, config, scenarios, |_, _| true)
+}
+
+/// `run_stress_test`, calling `on_progress(completed, total)` after each run (the baseline
+/// counts as one). Returning `false` from the callback stops the test.
+pub fn run_stress_test_with_progress(
+    make_strategy: impl Fn() -> Result<Box<dyn Strategy>, EngineError>,
+    symbol: &str,
+    candles: &[Candle],
+    config: BacktestConfig,
+    scenarios: &[StressScenario],
+    mut on_progress: impl FnMut(usize, usize) -> bool,
+) -> Result<(StressResult, Vec<StressResult>), EngineError> {
+    for scenario in scenarios {
+        scenario.validate()?;
+    }
+    let mut completed = 0;
+    let mut run = |scenario: &StressScenario| -> Result<(f64, PerformanceMetrics), EngineError> {
+        let mut strategy = make_strategy()?;
+        let config = BacktestConfig { spread_pct: config.spread_pct + scenario.spread_pct, ..config.clone() };
+        let outcome = run_backtest_with_events(strategy.as_mut(), symbol, &scenario.apply(candles), config, |_| true)?;
+        completed += 1;
+        if !on_progress(completed, scenarios.len() + 1) {
+            return Err(EngineError::ProcessingError(format!("Stress test of {} was stopped", symbol)));
+        }
+        Ok((outcome.report.total_pnl, outcome.metrics))
+    };
+
+    let baseline_scenario = StressScenario { name: "Baseline".to_string(), ..StressScenario::identity() };
+    let (baseline_pnl, baseline_metrics) = run(&baseline_scenario)?;
+    let results = scenarios.iter().map(|scenario| {
+        let (total_pnl, metrics) = run(scenario)?;
+        tracing::debug!(scenario = %scenario.name, total_pnl, baseline_pnl, "Stress scenario finished");
+        let pnl_change = total_pnl - baseline_pnl;
+        let pnl_change_pct = if baseline_pnl != 0.0 { pnl_change / baseline_pnl.abs() * 100.0 } else { 0.0 };
+        Ok(StressResult { scenario: scenario.clone(), total_pnl, pnl_change, pnl_change_pct, metrics })
+    }).collect::<Result<Vec<_>, EngineError>>()?;
+
+    let baseline = StressResult { scenario: baseline_scenario, total_pnl: baseline_pnl, pnl_change: 0.0, pnl_change_pct: 0.0, metrics: baseline_metrics };
+    Ok((baseline, results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::Signal;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn candles() -> Vec<Candle> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        (0..6).map(|i| {
+            let close = 100.0 + i as f64;
+            Candle { symbol: "TEST".to_string(), timestamp: start + Duration::days(i), open: to_price(close - 0.5), high: to_price(close + 1.0), low: to_price(close - 1.0), close: to_price(close), volume: 0.0, trades: 0 }
+        }).collect()
+    }
+
+    // Long from the open of bar 1 to the open of bar 5.
+    struct HoldLong;
+
+    impl Strategy for HoldLong {
+        fn name(&self) -> String {
+            "HoldLong".to_string()
+        }
+
+        fn on_bar(&mut self, _candles: &[Candle], index: usize) -> Signal {
+            match index {
+                0 => Signal::EnterLong,
+                4 => Signal::Exit,
+                _ => Signal::Hold,
+            }
+        }
+    }
+
+    #[test]
+    fn test_shocks_reshape_prices() {
+        let data = candles();
+        let gapped = StressScenario { gap_at: Some(2), ..StressScenario::gap(-10.0) }.apply(&data);
+        assert_eq!(gapped[1].close, data[1].close);
+        // Shocked prices are rounded to the price scale, 1e-8
+        assert!((gapped[2].close_f64() - data[2].close_f64() * 0.9).abs() < 1e-7);
+        assert!((gapped[3].close_f64() - gapped[2].close_f64() * data[3].close_f64() / data[2].close_f64()).abs() < 1e-7);
+
+        let volatile = StressScenario::volatility(2.0).apply(&data);
+        // 100 -> 101 (+1%) becomes 100 -> 102; the 2-point range around the close doubles.
+        assert_eq!(volatile[1].close, Price::from(102));
+        assert!((volatile[1].high_f64() - volatile[1].low_f64() - 4.0 * 102.0 / 101.0).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_scenarios_compared_to_baseline() {
+        let config = BacktestConfig { quantity: 10.0, ..Default::default() };
+        let scenarios = [StressScenario { gap_at: Some(3), ..StressScenario::gap(-10.0) }, StressScenario::spread(1.0)];
+        let (baseline, results) = run_stress_test(|| Ok(Box::new(HoldLong)), "TEST", &candles(), config.clone(), &scenarios).unwrap();
+        // Bought at 100.5, sold at 104.5.
+        assert!((baseline.total_pnl - 40.0).abs() < 1e-9);
+        assert!(results[0].pnl_change < -90.0);
+        assert!(results[0].pnl_change_pct < -225.0);
+        // Half of a 1% spread on each fill, rounded against us: bought at 101.01, sold at 103.97.
+        assert!((results[1].pnl_change + 10.4).abs() < 1e-6);
+
+        let bad = [StressScenario::volatility(0.0)];
+        assert!(run_stress_test(|| Ok(Box::new(HoldLong)), "TEST", &candles(), config, &bad).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+// Engine logging: human-readable lines on the console (or JSON, `engine.log_format`) and, with
+// `engine.log_file`, JSON lines in a log file rotated hourly or daily, for long-running sessions.
+// What gets logged is `engine.log_level`, a level or per-module filter (`info,engine::data=debug`)
+// that the settings can change while the engine runs; `RUST_LOG`, when set, takes its place.
+use std::path::Path;
+use std::sync::OnceLock;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+use super::settings::{EngineSettings, LogFormat, LogRotation};
+use crate::error::EngineError;
+
+/// Changes the filter of the logging set up by `init`.
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Logs as `settings` say until `set_filter` changes what is logged. The returned guard flushes the
+/// log file when dropped, so it must be kept until the engine exits.
+pub fn init(settings: &EngineSettings) -> Result<Option<WorkerGuard>, EngineError> {
+    let filter = match rust_log() {
+        Some(directives) => EnvFilter::try_new(&directives)
+            .map_err(|e| EngineError::ConfigError(format!("RUST_LOG '{}' is not a valid filter: {}", directives, e)))?,
+        None => settings.log_filter()?,
+    };
+    let (filter, handle) = reload::Layer::new(filter);
+    let console = match settings.log_format {
+        LogFormat::Pretty => fmt::layer().boxed(),
+        LogFormat::Json => fmt::layer().json().boxed(),
+    };
+    let (file, guard) = match &settings.log_file {
+        Some(path) => {
+            let (writer, guard) = tracing_appender::non_blocking(file_appender(Path::new(path), settings.log_rotation, settings.log_max_files)?);
+            (Some(fmt::layer().json().with_ansi(false).with_writer(writer)), Some(guard))
+        }
+        None => (None, None),
+    };
+    tracing_subscriber::registry().with(filter).with(console).with(file).try_init()
+        .map_err(|e| EngineError::ConfigError(format!("Logging is already set up: {}", e)))?;
+    let _ = FILTER.set(handle);
+    Ok(guard)
+}
+
+/// Logs what `filter` lets through from now on. Does nothing when the logging was not set up by
+/// `init`, as in an engine embedded in the GUI, or when `RUST_LOG` chose the filter.
+pub fn set_filter(filter: EnvFilter) {
+    if rust_log().is_some() {
+        tracing::info!("RUST_LOG is set; ignoring engine.log_level");
+        return;
+    }
+    if let Some(handle) = FILTER.get() {
+        if let Err(e) = handle.reload(filter) {
+            tracing::warn!(error = %e, "Failed to change the log filter");
+        }
+    }
+}
+
+/// The filter directives of `RUST_LOG`, if set.
+fn rust_log() -> Option<String> {
+    std::env::var(EnvFilter::DEFAULT_ENV).ok().filter(|directives| !directives.trim().is_empty())
+}
+
+/// Writer to `path`, starting a new file each hour or day: `logs/engine.log` is written as
+/// `logs/engine.2024-01-02.log`. Keeps the newest `max_files`, or every file with 0.
+fn file_appender(path: &Path, rotation: LogRotation, max_files: usize) -> Result<RollingFileAppender, EngineError> {
+    let directory = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let prefix = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| "engine".to_string());
+    let mut builder = RollingFileAppender::builder()
+        .rotation(match rotation {
+            LogRotation::Hourly => Rotation::HOURLY,
+            LogRotation::Daily => Rotation::DAILY,
+            LogRotation::Never => Rotation::NEVER,
+        })
+        .filename_prefix(prefix);
+    if let Some(extension) = path.extension() {
+        builder = builder.filename_suffix(extension.to_string_lossy());
+    }
+    if max_files > 0 {
+        builder = builder.max_log_files(max_files);
+    }
+    let cannot_write = |e: &dyn std::fmt::Display| EngineError::ConfigError(format!("Cannot write the log file {}: {}", path.display(), e));
+    // Made now rather than with the first file, as old files are looked for in it right away
+    std::fs::create_dir_all(directory).map_err(|e| cannot_write(&e))?;
+    builder.build(directory).map_err(|e| cannot_write(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_log_files_are_named_after_the_configured_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut appender = file_appender(&dir.path().join("logs/engine.log"), LogRotation::Daily, 3).unwrap();
+        appender.write_all(b"{\"message\":\"hello\"}\n").unwrap();
+        appender.flush().unwrap();
+
+        let names: Vec<String> = std::fs::read_dir(dir.path().join("logs")).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names.len(), 1);
+        assert!(names[0].starts_with("engine.") && names[0].ends_with(".log"), "{:?}", names);
+    }
+}
@@ -1,2 +1,5 @@
 // Engine configuration module
+pub mod logging;
 pub mod settings;
+pub mod symbols;
+pub mod watch;
@@ -1,62 +1,343 @@
 // Engine settings, loaded from a config file or environment variables
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path; // Removed PathBuf
+use std::path::{Path, PathBuf};
 use anyhow::{Context, Result}; // Ensure anyhow is in Cargo.toml for engine
+use figment::providers::{Env, Format, Json, Toml};
+use figment::Figment;
+use tonic::codec::CompressionEncoding;
+use tonic::transport::{Identity, ServerTlsConfig};
 use tracing::warn;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::EnvFilter;
+
+use super::symbols::{self, SymbolSettings};
+use crate::data::csv_parser::CsvFormat;
+use crate::data::market_data::CandleStoreSettings;
+use crate::error::EngineError;
+use crate::services::limits::RequestLimits;
+use crate::simulation::RiskLimits;
 
 const DEFAULT_CONFIG_PATH_FROM_WORKSPACE_ROOT: &str = "gui/assets/config/default.json";
 
+/// Prefix of the environment variables overriding the config file. Sections are separated by a
+/// double underscore: `HOME_TRADER_ENGINE__PORT=50052` sets `engine.port`.
+pub const ENV_PREFIX: &str = "HOME_TRADER_";
+
+/// Environment variable naming the config file, instead of the development default.
+pub const CONFIG_PATH_VAR: &str = "HOME_TRADER_CONFIG";
+
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct AppSettings {
+    #[serde(default)]
     pub engine: EngineSettings,
-    // We can add other sections like `app`, `chart`, `data` from the spec's JSON if needed by the engine.
-    // For now, only `engine` settings are actively used by the engine's core startup.
-    // Example:
-    // pub chart: serde_json::Value, // Or a strongly typed struct
-    // pub data: DataSettings,
+    /// How the CSV files the engine imports are written.
+    #[serde(default)]
+    pub data: CsvFormat,
+    /// Settings of single symbols, or of every contract of a futures root, over the defaults.
+    #[serde(default)]
+    pub symbols: HashMap<String, SymbolSettings>,
+    // We can add other sections like `app` or `chart` from the spec's JSON if needed by the engine.
+}
+
+/// Settings a reload found changed: those now in effect, and those that take a restart.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SettingsChanges {
+    pub applied: Vec<String>,
+    pub restart_required: Vec<String>,
 }
 
 impl AppSettings {
-    /// Loads application settings from a specified JSON file.
+    /// Loads application settings from a specified JSON file, or TOML if its extension is `.toml`,
+    /// with the `HOME_TRADER_*` environment variables laid over it.
     /// If the path is relative, it's resolved from the current working directory.
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path_ref = path.as_ref();
         let file_content = fs::read_to_string(path_ref)
             .with_context(|| format!("Failed to read configuration file from {:?}", path_ref))?;
 
-        let app_settings: AppSettings = serde_json::from_str(&file_content)
-            .with_context(|| format!("Failed to parse configuration file: {}", file_content))?;
+        let file = if is_toml(path_ref) { Figment::from(Toml::string(&file_content)) } else { Figment::from(Json::string(&file_content)) };
+        Self::extract(file.merge(env_overrides(ENV_PREFIX)))
+            .with_context(|| format!("Invalid configuration in {:?} or the {}* environment variables", path_ref, ENV_PREFIX))
+    }
+
+    /// The default settings with the `HOME_TRADER_*` environment variables laid over them, for an
+    /// engine started without a config file (e.g. in a container).
+    pub fn load_from_env() -> Result<Self> {
+        Self::extract(Figment::from(env_overrides(ENV_PREFIX)))
+    }
 
+    /// The settings in `figment`, failing with every problem found in them, each naming its setting.
+    fn extract(figment: Figment) -> Result<Self> {
+        let app_settings: AppSettings = figment.extract()
+            .map_err(|e| EngineError::ConfigError(e.into_iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; ")))?;
+        let problems = app_settings.validate();
+        if !problems.is_empty() {
+            return Err(EngineError::ConfigError(problems.join("; ")).into());
+        }
         Ok(app_settings)
     }
 
-    /// Attempts to load settings from a default path, typically for development.
-    /// This path is relative to the workspace root.
+    /// Problems that would keep the engine from starting or make it misbehave, one message each,
+    /// naming the setting as in the config file; empty when the settings are fine.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, problem: String| {
+            if !ok {
+                problems.push(problem);
+            }
+        };
+        let engine = &self.engine;
+        check(!engine.host.trim().is_empty(), "engine.host is empty".to_string());
+        check(engine.port != 0, "engine.port must be between 1 and 65535".to_string());
+        if let Err(EngineError::ConfigError(e)) = engine.log_filter() {
+            check(false, e);
+        }
+        for (name, path) in [("account_file", &engine.account_file), ("unix_socket", &engine.unix_socket), ("log_file", &engine.log_file), ("auth_token", &engine.auth_token)] {
+            check(path.as_ref().is_none_or(|path| !path.trim().is_empty()), format!("engine.{} is empty; leave it out or set it to null instead", name));
+        }
+        if let Some(candle_store) = &engine.candle_store {
+            check(!candle_store.file.trim().is_empty(), "engine.candle_store.file is empty".to_string());
+            check(candle_store.recent_candles > 0, "engine.candle_store.recent_candles must be at least 1".to_string());
+            check(candle_store.cached_ranges > 0, "engine.candle_store.cached_ranges must be at least 1".to_string());
+        }
+        if let Some(tls) = &engine.tls {
+            check(!tls.cert_file.trim().is_empty(), "engine.tls.cert_file is empty".to_string());
+            check(!tls.key_file.trim().is_empty(), "engine.tls.key_file is empty".to_string());
+        }
+        let limits = &engine.request_limits;
+        check(limits.requests_per_second.is_finite() && limits.requests_per_second >= 0.0,
+            format!("engine.request_limits.requests_per_second must be 0 (no limit) or more, got {}", limits.requests_per_second));
+        check(limits.requests_per_second == 0.0 || limits.burst > 0, "engine.request_limits.burst must be at least 1 when requests are rate limited".to_string());
+        check(limits.max_concurrent_requests > 0, "engine.request_limits.max_concurrent_requests must be at least 1".to_string());
+        if let Err(e) = engine.risk_limits.validate() {
+            check(false, format!("engine.risk_limits: {}", e));
+        }
+        if let Err(EngineError::ConfigError(e)) = self.data.validate() {
+            check(false, format!("data.{}", e));
+        }
+        let mut names: Vec<&String> = self.symbols.keys().collect();
+        names.sort();
+        for name in names {
+            problems.extend(self.symbols[name].validate(name));
+        }
+        problems
+    }
+
+    /// What changed since `previous`, named as in the config file (e.g. "engine.port").
+    /// The log level, risk limits, data format and symbol settings apply to a running engine; the rest is read at startup.
+    pub fn changes_from(&self, previous: &AppSettings) -> SettingsChanges {
+        let mut changes = SettingsChanges::default();
+        let (engine, before) = (&self.engine, &previous.engine);
+        let mut restart = |name: &str, changed: bool| if changed { changes.restart_required.push(format!("engine.{}", name)) };
+        restart("host", engine.host != before.host);
+        restart("port", engine.port != before.port);
+        restart("max_connections", engine.max_connections != before.max_connections);
+        restart("thread_pool_size", engine.thread_pool_size != before.thread_pool_size);
+        restart("account_file", engine.account_file != before.account_file);
+        restart("candle_store", engine.candle_store != before.candle_store);
+        restart("compression", engine.compression != before.compression);
+        restart("request_limits", engine.request_limits != before.request_limits);
+        restart("unix_socket", engine.unix_socket != before.unix_socket);
+        restart("tls", engine.tls != before.tls);
+        restart("auth_token", engine.auth_token != before.auth_token);
+        restart("log_format", engine.log_format != before.log_format);
+        restart("log_file", engine.log_file != before.log_file);
+        restart("log_rotation", engine.log_rotation != before.log_rotation);
+        restart("log_max_files", engine.log_max_files != before.log_max_files);
+
+        let mut apply = |name: &str, changed: bool| if changed { changes.applied.push(name.to_string()) };
+        apply("engine.log_level", engine.log_level != before.log_level);
+        apply("engine.risk_limits", engine.risk_limits != before.risk_limits);
+        let (data, before) = (&self.data, &previous.data);
+        apply("data.csv_delimiter", data.csv_delimiter != before.csv_delimiter);
+        apply("data.decimal_separator", data.decimal_separator != before.decimal_separator);
+        apply("data.thousand_separator", data.thousand_separator != before.thousand_separator);
+        apply("data.date_format", data.date_format != before.date_format);
+        apply("data.time_format", data.time_format != before.time_format);
+        apply("symbols", self.symbols != previous.symbols);
+        changes
+    }
+
+    /// How the CSV files of `symbol` are written: its `symbols` entry's format, or else the `data` section.
+    pub fn csv_format(&self, symbol: &str) -> CsvFormat {
+        symbols::lookup(&self.symbols, symbol).and_then(|settings| settings.csv_format.clone()).unwrap_or_else(|| self.data.clone())
+    }
+
+    /// Attempts to load settings from `config_path`: `HOME_TRADER_CONFIG` if set, otherwise a
+    /// default path relative to the workspace root, typically for development.
     pub fn load_default_dev() -> Result<Self> {
-        // Try to find the workspace root. This is a common pattern.
-        // CARGO_MANIFEST_DIR is set by Cargo when running tests or `cargo run`.
-        // For a deployed binary, this path needs to be determined differently (e.g., relative to executable).
-        let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
-        let workspace_root = Path::new(&manifest_dir).parent().unwrap_or_else(|| Path::new(".")); // Assumes engine is one level down
-        let config_path = workspace_root.join(DEFAULT_CONFIG_PATH_FROM_WORKSPACE_ROOT);
+        Self::load_from_file(config_path())
+    }
+}
 
-        Self::load_from_file(&config_path)
+/// The config file the engine reads: `HOME_TRADER_CONFIG`, or the default one of the workspace.
+pub fn config_path() -> PathBuf {
+    if let Some(path) = std::env::var_os(CONFIG_PATH_VAR) {
+        return PathBuf::from(path);
     }
+    // Try to find the workspace root. This is a common pattern.
+    // CARGO_MANIFEST_DIR is set by Cargo when running tests or `cargo run`.
+    // For a deployed binary, set HOME_TRADER_CONFIG instead.
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+    let workspace_root = Path::new(&manifest_dir).parent().unwrap_or_else(|| Path::new(".")); // Assumes engine is one level down
+    workspace_root.join(DEFAULT_CONFIG_PATH_FROM_WORKSPACE_ROOT)
+}
+
+/// Whether the config file at `path` is TOML (by its extension) rather than JSON.
+pub fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("toml"))
+}
+
+/// The environment variables starting with `prefix`, as config keys: `ENGINE__ACCOUNT_FILE` is
+/// `engine.account_file`. `HOME_TRADER_CONFIG` names the file and is not a setting.
+fn env_overrides(prefix: &str) -> Env {
+    Env::prefixed(prefix).split("__").ignore(&["config"])
 }
 
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[serde(default)] // This will apply EngineSettings::default() if "engine" key is missing or for missing fields
 pub struct EngineSettings {
     pub host: String,
     pub port: u16,
     pub max_connections: usize,
     pub thread_pool_size: usize,
+    /// JSON file holding the paper-trading account between restarts; unset keeps the account in memory only.
+    pub account_file: Option<String>,
+    /// SQLite file every loaded candle is written to, with the latest candles of each series kept in
+    /// memory; unset keeps every candle in memory only, until the engine stops.
+    pub candle_store: Option<CandleStoreSettings>,
+    /// Limits enforced on simulated orders at startup; `SetRiskLimits` replaces them until the next restart or settings reload.
+    pub risk_limits: RiskLimits,
+    /// Compression of the messages the server sends; compressed requests are accepted either way.
+    pub compression: Compression,
+    /// Per-client request rate and the global cap on requests handled at once.
+    pub request_limits: RequestLimits,
+    /// Unix domain socket to listen on besides `host:port`, for lower-latency local clients (Unix only).
+    pub unix_socket: Option<String>,
+    /// Certificate and key to serve `host:port` over TLS with; unset serves plaintext.
+    pub tls: Option<TlsSettings>,
+    /// Token every client must send as `authorization: Bearer <token>`; unset lets any client in.
+    pub auth_token: Option<String>,
+    /// Most detailed level logged: `error`, `warn`, `info`, `debug`, `trace` or `off`, optionally
+    /// followed by levels of single modules, e.g. `info,engine::data=debug`. `RUST_LOG` overrides it.
+    pub log_level: String,
+    /// How log lines are written to the console.
+    pub log_format: LogFormat,
+    /// File the log is also written to, as JSON lines; unset logs to the console only.
+    pub log_file: Option<String>,
+    /// How often a new log file is started.
+    pub log_rotation: LogRotation,
+    /// Log files kept, the oldest being deleted; 0 keeps them all.
+    pub log_max_files: usize,
     // Add other engine-specific settings here
 }
 
+/// PEM files of the certificate the engine presents and of its private key.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct TlsSettings {
+    pub cert_file: String,
+    pub key_file: String,
+}
+
+impl TlsSettings {
+    /// The server's TLS config, read from both files.
+    pub fn server_config(&self) -> Result<ServerTlsConfig> {
+        let cert = fs::read(&self.cert_file).with_context(|| format!("Failed to read engine.tls.cert_file {:?}", self.cert_file))?;
+        let key = fs::read(&self.key_file).with_context(|| format!("Failed to read engine.tls.key_file {:?}", self.key_file))?;
+        Ok(ServerTlsConfig::new().identity(Identity::from_pem(cert, key)))
+    }
+}
+
+/// gRPC message compression, `"none"`, `"gzip"` or `"zstd"` in the config file.
+/// Worth it for large candle and indicator payloads over a network; on localhost it mostly costs CPU.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+/// Console log lines: `"pretty"` (human-readable, colored) or `"json"` (one object per line).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+/// When the log file is rotated: `"hourly"`, `"daily"` or `"never"`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    Hourly,
+    #[default]
+    Daily,
+    Never,
+}
+
+impl Compression {
+    pub fn encoding(self) -> Option<CompressionEncoding> {
+        match self {
+            Compression::None => None,
+            Compression::Gzip => Some(CompressionEncoding::Gzip),
+            Compression::Zstd => Some(CompressionEncoding::Zstd),
+        }
+    }
+}
+
+impl EngineSettings {
+    /// `log_level` as a filter. Each directive not naming a module (`engine::data=debug`) must be a level,
+    /// so a misspelled level is not taken for a module name.
+    pub fn log_filter(&self) -> Result<EnvFilter, EngineError> {
+        let invalid = || EngineError::ConfigError(format!(
+            "engine.log_level '{}' must be error, warn, info, debug, trace or off, optionally followed by module levels such as engine::data=debug",
+            self.log_level
+        ));
+        let bare_levels_valid = self.log_level.split(',')
+            .map(str::trim)
+            .filter(|directive| !directive.contains('=') && !directive.contains('['))
+            .all(|level| level.parse::<LevelFilter>().is_ok());
+        if !bare_levels_valid {
+            return Err(invalid());
+        }
+        EnvFilter::try_new(&self.log_level).map_err(|_| invalid())
+    }
+
+    /// Optional capabilities these settings turn on, as reported by GetEngineInfo.
+    pub fn features(&self) -> Vec<String> {
+        let mut features = vec!["grpc-web".to_string(), "health".to_string()];
+        if let Some(encoding) = self.compression.encoding() {
+            features.push(format!("compression:{}", encoding));
+        }
+        if self.request_limits.requests_per_second > 0.0 {
+            features.push("rate-limits".to_string());
+        }
+        if self.account_file.is_some() {
+            features.push("account-file".to_string());
+        }
+        if self.candle_store.is_some() {
+            features.push("candle-store".to_string());
+        }
+        if self.unix_socket.is_some() {
+            features.push("unix-socket".to_string());
+        }
+        if self.tls.is_some() {
+            features.push("tls".to_string());
+        }
+        if self.auth_token.is_some() {
+            features.push("auth-token".to_string());
+        }
+        features
+    }
+}
+
 impl Default for EngineSettings {
     fn default() -> Self {
         // Default values as per spec's JSON example
@@ -65,26 +346,175 @@ impl Default for EngineSettings {
             port: 50051,
             max_connections: 10,
             thread_pool_size: 4, // Note: Tokio manages its own thread pool. This is more for custom pools.
+            account_file: None,
+            candle_store: None,
+            risk_limits: RiskLimits::default(),
+            compression: Compression::None,
+            request_limits: RequestLimits::default(),
+            unix_socket: None,
+            tls: None,
+            auth_token: None,
+            log_level: "info".to_string(),
+            log_format: LogFormat::Pretty,
+            log_file: None,
+            log_rotation: LogRotation::Daily,
+            log_max_files: 7,
         }
     }
 }
 
 /// Utility function to get engine settings:
-/// 1. Tries to load from `gui/assets/config/default.json` (relative to workspace root for dev).
-/// 2. Falls back to `EngineSettings::default()` if loading fails.
-pub fn get_engine_settings() -> EngineSettings {
-    match AppSettings::load_default_dev() {
-        Ok(app_settings) => {
-            tracing::info!(path = %DEFAULT_CONFIG_PATH_FROM_WORKSPACE_ROOT, "Successfully loaded configuration.");
-            app_settings.engine
-        }
-        Err(e) => {
-            warn!(
-                path = %DEFAULT_CONFIG_PATH_FROM_WORKSPACE_ROOT,
-                error = ?e, // Using debug formatting for the error object
-                "Failed to load configuration. Using default engine settings."
-            );
-            EngineSettings::default()
+/// 1. Loads them from `config_path()` (`gui/assets/config/default.json` relative to workspace root for dev).
+/// 2. Falls back to `EngineSettings::default()` if there is no such file.
+///
+/// Either way, the `HOME_TRADER_*` environment variables override what they name. A file that cannot
+/// be read or holds invalid settings is an error listing what is wrong, rather than a silent fallback.
+pub fn get_engine_settings() -> Result<EngineSettings> {
+    Ok(get_app_settings()?.engine)
+}
+
+/// Same as `get_engine_settings`, with the other sections the engine reads.
+pub fn get_app_settings() -> Result<AppSettings> {
+    let path = config_path();
+    if !path.exists() {
+        warn!(path = %path.display(), "No configuration file. Using default engine settings.");
+        return AppSettings::load_from_env();
+    }
+    let app_settings = AppSettings::load_from_file(&path)?;
+    tracing::info!(path = %path.display(), "Successfully loaded configuration.");
+    Ok(app_settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::TimeFrame;
+
+    #[test]
+    fn test_environment_variables_override_the_file() {
+        // A prefix of its own, as tests share the process environment
+        let prefix = "HOME_TRADER_TEST_OVERRIDE_";
+        std::env::set_var(format!("{}ENGINE__PORT", prefix), "6000");
+        std::env::set_var(format!("{}ENGINE__ACCOUNT_FILE", prefix), "/var/lib/home-trader/account.json");
+        std::env::set_var(format!("{}ENGINE__REQUEST_LIMITS__BURST", prefix), "7");
+        std::env::set_var(format!("{}ENGINE__CANDLE_STORE__FILE", prefix), "/var/lib/home-trader/candles.db");
+        let file = r#"{"engine": {"host": "0.0.0.0", "port": 50051, "max_connections": 3}, "chart": {"theme": "dark"}}"#;
+
+        let settings = AppSettings::extract(Figment::from(Json::string(file)).merge(env_overrides(prefix))).unwrap();
+
+        assert_eq!(settings.engine.host, "0.0.0.0");
+        assert_eq!(settings.engine.port, 6000);
+        assert_eq!(settings.engine.max_connections, 3);
+        assert_eq!(settings.engine.account_file.as_deref(), Some("/var/lib/home-trader/account.json"));
+        assert_eq!(settings.engine.request_limits.burst, 7);
+        let candle_store = settings.engine.candle_store.as_ref().unwrap();
+        assert_eq!((candle_store.file.as_str(), candle_store.recent_candles), ("/var/lib/home-trader/candles.db", crate::data::market_data::DEFAULT_RECENT_CANDLES));
+    }
+
+    #[test]
+    fn test_tls_and_auth_token_come_from_the_environment() {
+        let prefix = "HOME_TRADER_TEST_SECURITY_";
+        std::env::set_var(format!("{}ENGINE__TLS__CERT_FILE", prefix), "/run/secrets/engine.crt");
+        std::env::set_var(format!("{}ENGINE__TLS__KEY_FILE", prefix), "/run/secrets/engine.key");
+        std::env::set_var(format!("{}ENGINE__AUTH_TOKEN", prefix), "s3cret");
+
+        let settings = AppSettings::extract(Figment::from(env_overrides(prefix))).unwrap();
+
+        let tls = settings.engine.tls.as_ref().unwrap();
+        assert_eq!((tls.cert_file.as_str(), tls.key_file.as_str()), ("/run/secrets/engine.crt", "/run/secrets/engine.key"));
+        assert_eq!(settings.engine.auth_token.as_deref(), Some("s3cret"));
+        assert!(settings.engine.features().contains(&"tls".to_string()));
+        assert_eq!(settings.changes_from(&AppSettings::default()).restart_required, vec!["engine.tls", "engine.auth_token"]);
+        // Reading the files happens at startup, naming the one missing
+        let error = tls.server_config().unwrap_err().to_string();
+        assert!(error.contains("engine.tls.cert_file"), "{}", error);
+
+        // A certificate without its key is incomplete
+        let prefix = "HOME_TRADER_TEST_SECURITY_PARTIAL_";
+        std::env::set_var(format!("{}ENGINE__TLS__CERT_FILE", prefix), "/run/secrets/engine.crt");
+        let error = AppSettings::extract(Figment::from(env_overrides(prefix))).unwrap_err();
+        assert!(format!("{:#}", error).contains("key_file"), "{:#}", error);
+    }
+
+    #[test]
+    fn test_toml_files_are_read_by_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("engine.TOML");
+        let file = "# Hand-edited\n[engine]\nport = 50060\nlog_level = \"debug\"\n\n[engine.request_limits]\nburst = 5\n\n[data]\ncsv_delimiter = \",\"\ndecimal_separator = \".\"\nthousand_separator = \"_\"\n";
+        std::fs::write(&path, file).unwrap();
+
+        let settings = AppSettings::load_from_file(&path).unwrap();
+
+        assert_eq!(settings.engine.port, 50060);
+        assert_eq!(settings.engine.log_level, "debug");
+        assert_eq!(settings.engine.request_limits.burst, 5);
+        assert_eq!(settings.data.csv_delimiter, ',');
+        // The same text is not JSON
+        std::fs::write(dir.path().join("engine.json"), file).unwrap();
+        assert!(AppSettings::load_from_file(dir.path().join("engine.json")).is_err());
+    }
+
+    #[test]
+    fn test_environment_variables_apply_without_a_file() {
+        let prefix = "HOME_TRADER_TEST_NO_FILE_";
+        std::env::set_var(format!("{}ENGINE__HOST", prefix), "0.0.0.0");
+        std::env::set_var(format!("{}DATA__CSV_DELIMITER", prefix), ",");
+        std::env::set_var(format!("{}DATA__DECIMAL_SEPARATOR", prefix), ".");
+        std::env::set_var(format!("{}DATA__THOUSAND_SEPARATOR", prefix), "_");
+
+        let settings = AppSettings::extract(Figment::from(env_overrides(prefix))).unwrap();
+
+        assert_eq!(settings.engine.host, "0.0.0.0");
+        assert_eq!(settings.engine.port, EngineSettings::default().port);
+        assert_eq!(settings.data.csv_delimiter, ',');
+        assert_eq!(settings.data.decimal_separator, '.');
+    }
+
+    #[test]
+    fn test_symbols_have_settings_of_their_own() {
+        let file = r#"{"data": {"csv_delimiter": ";"}, "symbols": {
+            "WIN": {"decimals": 0, "timeframe": "Minute5", "csv_format": {"csv_delimiter": ",", "decimal_separator": ".", "thousand_separator": "_"}},
+            "PETR4": {"tick_size": 0.01, "point_value": 1.0}}}"#;
+
+        let settings = AppSettings::extract(Figment::from(Json::string(file))).unwrap();
+
+        assert_eq!(settings.symbols["WIN"].timeframe, Some(TimeFrame::Minute5));
+        assert_eq!(settings.csv_format("WINM25").csv_delimiter, ',');
+        assert_eq!(settings.csv_format("PETR4").csv_delimiter, ';');
+        let mut previous = settings.clone();
+        previous.symbols.remove("PETR4");
+        assert_eq!(settings.changes_from(&previous).applied, vec!["symbols".to_string()]);
+        let invalid = r#"{"symbols": {"PETR4": {"tick_size": -1}}}"#;
+        let error = AppSettings::extract(Figment::from(Json::string(invalid))).unwrap_err().to_string();
+        assert!(error.contains("symbols.PETR4.tick_size"), "{}", error);
+    }
+
+    #[test]
+    fn test_every_invalid_setting_is_reported() {
+        let file = r#"{"engine": {"host": " ", "port": 0, "log_level": "loud", "account_file": "", "auth_token": "", "tls": {"cert_file": "", "key_file": "engine.key"},
+            "candle_store": {"file": "candles.db", "cached_ranges": 0},
+            "request_limits": {"requests_per_second": 10, "burst": 0}, "risk_limits": {"max_position": -1}},
+            "data": {"decimal_separator": ".", "thousand_separator": "."}}"#;
+
+        let error = AppSettings::extract(Figment::from(Json::string(file))).unwrap_err().to_string();
+
+        for setting in ["engine.host", "engine.port", "engine.log_level 'loud'", "engine.account_file", "engine.auth_token", "engine.tls.cert_file", "engine.candle_store.cached_ranges", "engine.request_limits.burst", "engine.risk_limits: Risk limit max_position", "data.decimal_separator"] {
+            assert!(error.contains(setting), "{} not in: {}", setting, error);
         }
+        assert!(AppSettings::default().validate().is_empty());
+        assert!(AppSettings::load_default_dev().unwrap().validate().is_empty());
+        let engine = |log_level: &str| EngineSettings { log_level: log_level.to_string(), ..Default::default() };
+        assert!(engine("warn,engine::data=debug,tonic=off").log_filter().is_ok());
+        assert!(engine("info,lod").log_filter().is_err());
+    }
+
+    #[test]
+    fn test_invalid_environment_values_name_the_variable() {
+        let prefix = "HOME_TRADER_TEST_INVALID_";
+        std::env::set_var(format!("{}ENGINE__PORT", prefix), "not-a-port");
+
+        let error = AppSettings::extract(Figment::from(env_overrides(prefix))).unwrap_err();
+
+        assert!(format!("{:#}", error).to_lowercase().contains("engine.port"), "{:#}", error);
     }
 }
@@ -0,0 +1,98 @@
+// Per-symbol settings: the `symbols` section of the config file
+// Keyed by symbol (PETR4) or by futures root (WIN, for WINFUT and every WIN contract); a symbol's own
+// entry wins over its root's. Only the settings given override the built-in ones: the contract specs
+// of `simulation::contracts` for futures, R$0.01 ticks and a point value of 1 for equities, and the
+// `data` section's CSV format. The simulator looks tick sizes and point values up through the
+// `simulation::Contracts` of its engine.
+use serde::{Deserialize, Serialize};
+use shared::models::{to_price, TimeFrame};
+use std::collections::HashMap;
+
+use crate::data::csv_parser::CsvFormat;
+use crate::error::EngineError;
+use crate::simulation::contracts::futures_root;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SymbolSettings {
+    /// Minimum price increment, in price units.
+    pub tick_size: Option<f64>,
+    /// Decimals prices are shown with.
+    pub decimals: Option<u32>,
+    /// Currency value of a one-point price move, per share or contract.
+    pub point_value: Option<f64>,
+    /// Timeframe of the symbol's data, offered first when loading it.
+    pub timeframe: Option<TimeFrame>,
+    /// How the symbol's CSV files are written, instead of the `data` section.
+    pub csv_format: Option<CsvFormat>,
+}
+
+impl SymbolSettings {
+    /// Problems with these settings, one message each, naming them as `symbols.<name>.<setting>`.
+    pub fn validate(&self, name: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (setting, value) in [("tick_size", self.tick_size), ("point_value", self.point_value)] {
+            if value.is_some_and(|value| !value.is_finite() || value <= 0.0) {
+                problems.push(format!("symbols.{}.{} must be a positive number, got {}", name, setting, value.unwrap_or_default()));
+            }
+        }
+        // Prices keep `PRICE_SCALE` decimals: a smaller tick would be zero, and nothing could be rounded to it.
+        if self.tick_size.is_some_and(|tick| tick > 0.0 && to_price(tick).is_zero()) {
+            problems.push(format!("symbols.{}.tick_size is below the smallest price step, 0.00000001", name));
+        }
+        if self.decimals.is_some_and(|decimals| decimals > MAX_DECIMALS) {
+            problems.push(format!("symbols.{}.decimals must be at most {}", name, MAX_DECIMALS));
+        }
+        if let Some(Err(EngineError::ConfigError(e))) = self.csv_format.as_ref().map(CsvFormat::validate) {
+            problems.push(format!("symbols.{}.csv_format: {}", name, e));
+        }
+        problems
+    }
+}
+
+const MAX_DECIMALS: u32 = 8;
+
+/// The settings of `symbol` in `symbols`: its own entry, or else its futures root's. Names are
+/// compared without regard to case.
+pub fn lookup<'a>(symbols: &'a HashMap<String, SymbolSettings>, symbol: &str) -> Option<&'a SymbolSettings> {
+    let find = |name: &str| symbols.iter().find(|(key, _)| key.trim().eq_ignore_ascii_case(name)).map(|(_, settings)| settings);
+    find(symbol.trim()).or_else(|| futures_root(symbol).and_then(find))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbols_fall_back_to_their_futures_root() {
+        let symbols = HashMap::from([
+            ("win".to_string(), SymbolSettings { decimals: Some(0), ..Default::default() }),
+            ("WINFUT".to_string(), SymbolSettings { decimals: Some(1), ..Default::default() }),
+            ("PETR4".to_string(), SymbolSettings { tick_size: Some(0.05), ..Default::default() }),
+        ]);
+        assert_eq!(lookup(&symbols, "WINFUT").unwrap().decimals, Some(1));
+        assert_eq!(lookup(&symbols, "winm25").unwrap().decimals, Some(0));
+        assert_eq!(lookup(&symbols, "petr4").unwrap().tick_size, Some(0.05));
+        assert!(lookup(&symbols, "WINE3").is_none());
+        assert!(lookup(&symbols, "VALE3").is_none());
+    }
+
+    #[test]
+    fn test_invalid_symbol_settings_are_named() {
+        let settings = SymbolSettings {
+            tick_size: Some(0.0),
+            decimals: Some(12),
+            csv_format: Some(CsvFormat { thousand_separator: ',', ..CsvFormat::default() }),
+            ..Default::default()
+        };
+        let problems = settings.validate("PETR4");
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        assert!(problems[0].starts_with("symbols.PETR4.tick_size"));
+        assert!(SymbolSettings::default().validate("PETR4").is_empty());
+
+        let too_fine = SymbolSettings { tick_size: Some(1e-9), ..Default::default() }.validate("PETR4");
+        assert_eq!(too_fine.len(), 1, "{:?}", too_fine);
+        assert!(too_fine[0].contains("smallest price step"));
+        assert!(SymbolSettings { tick_size: Some(1e-8), ..Default::default() }.validate("PETR4").is_empty());
+    }
+}
@@ -0,0 +1,64 @@
+// Watching the config file, so saving it reloads the settings
+// Editors save either by writing the file in place or by writing a new file and renaming it over
+// the old one, in several events each: the file's directory is watched, events about other files
+// are dropped, and a change is only reported once the save has settled.
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// How long after a change to wait for the rest of the save's events.
+pub const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// Marks `changed` whenever the file at `path` is written, created, renamed or removed. Watching
+/// stops when the returned watcher is dropped.
+pub fn watch_file(path: &Path, changed: watch::Sender<()>) -> notify::Result<RecommendedWatcher> {
+    let file_name = path.file_name().map(|name| name.to_os_string());
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+        Ok(event) if !event.kind.is_access() && event.paths.iter().any(|path| path.file_name() == file_name.as_deref()) => {
+            changed.send_replace(());
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!(error = %e, "Error watching the config file"),
+    })?;
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
+
+/// Waits for the next change marked by `watch_file` and for it to settle; `None` once the watcher
+/// is dropped.
+pub async fn next_change(changed: &mut watch::Receiver<()>) -> Option<()> {
+    changed.changed().await.ok()?;
+    tokio::time::sleep(SETTLE_TIME).await;
+    changed.borrow_and_update();
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_saving_the_file_is_one_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, "{}").unwrap();
+        let (changed, mut changes) = watch::channel(());
+        let watcher = watch_file(&path, changed).unwrap();
+
+        // Other files in the directory are not the config file
+        std::fs::write(dir.path().join("other.json"), "{}").unwrap();
+        // A save writing a new file and renaming it over the config file
+        std::fs::write(dir.path().join("config.json.tmp"), r#"{"engine": {}}"#).unwrap();
+        std::fs::rename(dir.path().join("config.json.tmp"), &path).unwrap();
+
+        let change = tokio::time::timeout(Duration::from_secs(5), next_change(&mut changes)).await.unwrap();
+        assert_eq!(change, Some(()));
+        assert!(!changes.has_changed().unwrap());
+
+        drop(watcher);
+        let after_drop = tokio::time::timeout(Duration::from_secs(5), next_change(&mut changes)).await.unwrap();
+        assert_eq!(after_drop, None);
+    }
+}
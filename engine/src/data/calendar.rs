@@ -0,0 +1,229 @@
+// B3 trading calendar
+// Trading days (weekends and exchange holidays excluded) and the daily session of the
+// cash equities and futures markets. Candle timestamps carry B3 local (São Paulo) wall
+// clock time, as the CSV parser stores them, so no time zone conversion happens here.
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+
+use crate::simulation::contract_spec;
+
+/// Market a symbol trades in: futures (WIN, WDO, ...) keep longer hours than the cash market.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Market {
+    Equities,
+    Futures,
+}
+
+impl Market {
+    pub fn of(symbol: &str) -> Self {
+        if contract_spec(symbol).is_some() { Market::Futures } else { Market::Equities }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionPhase {
+    /// No trading: outside the session, or not a trading day.
+    Closed,
+    /// Pre-opening call: orders are accepted and matched in an auction at the open.
+    OpeningAuction,
+    Continuous,
+    /// Closing call of the cash market.
+    ClosingAuction,
+}
+
+impl SessionPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionPhase::Closed => "CLOSED",
+            SessionPhase::OpeningAuction => "OPENING_AUCTION",
+            SessionPhase::Continuous => "CONTINUOUS",
+            SessionPhase::ClosingAuction => "CLOSING_AUCTION",
+        }
+    }
+}
+
+/// Regular session of one trading day, in local time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Session {
+    pub date: NaiveDate,
+    pub opening_auction: NaiveTime,
+    pub open: NaiveTime,
+    /// Start of the closing call; equal to `close` for futures, which have none.
+    pub closing_auction: NaiveTime,
+    pub close: NaiveTime,
+}
+
+impl Session {
+    pub fn phase_at(&self, time: NaiveTime) -> SessionPhase {
+        if time < self.opening_auction || time >= self.close {
+            SessionPhase::Closed
+        } else if time < self.open {
+            SessionPhase::OpeningAuction
+        } else if time < self.closing_auction {
+            SessionPhase::Continuous
+        } else {
+            SessionPhase::ClosingAuction
+        }
+    }
+
+    pub fn open_at(&self) -> DateTime<Utc> {
+        self.date.and_time(self.open).and_utc()
+    }
+
+    pub fn close_at(&self) -> DateTime<Utc> {
+        self.date.and_time(self.close).and_utc()
+    }
+}
+
+fn hm(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).expect("valid session time")
+}
+
+/// Easter Sunday (anonymous Gregorian algorithm); Carnival, Good Friday and Corpus Christi follow it.
+pub fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).expect("Easter falls in March or April")
+}
+
+/// Whether B3 is closed on `date` for a holiday (weekends aside).
+pub fn is_holiday(date: NaiveDate) -> bool {
+    let year = date.year();
+    let fixed = match (date.month(), date.day()) {
+        (1, 1) | (4, 21) | (5, 1) | (9, 7) | (10, 12) | (11, 2) | (11, 15) | (12, 24) | (12, 25) | (12, 31) => true,
+        // São Paulo's city and state holidays: B3 trades through them since 2022.
+        (1, 25) | (7, 9) => year < 2022,
+        // Black Consciousness Day: a São Paulo holiday until 2021, national since 2024.
+        (11, 20) => !(2022..2024).contains(&year),
+        _ => false,
+    };
+    if fixed {
+        return true;
+    }
+    let easter = easter_sunday(year);
+    [-48, -47, -2, 60].iter().any(|offset| date == easter + Duration::days(*offset))
+}
+
+pub fn is_trading_day(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !is_holiday(date)
+}
+
+/// First trading day after `date`.
+pub fn next_trading_day(date: NaiveDate) -> NaiveDate {
+    let mut day = date + Duration::days(1);
+    while !is_trading_day(day) {
+        day += Duration::days(1);
+    }
+    day
+}
+
+/// `date` itself if B3 trades on it, otherwise the next trading day.
+pub fn trading_day_on_or_after(date: NaiveDate) -> NaiveDate {
+    if is_trading_day(date) { date } else { next_trading_day(date) }
+}
+
+/// Trading days in (from, to]: a Friday-to-Monday hold is one, a hold over Carnival skips two more.
+pub fn trading_days_between(from: NaiveDate, to: NaiveDate) -> u32 {
+    from.iter_days().skip(1).take_while(|d| *d <= to).filter(|d| is_trading_day(*d)).count() as u32
+}
+
+// US daylight saving time, second Sunday of March to first Sunday of November: B3 moves
+// the cash market's close an hour earlier to keep its overlap with New York.
+fn us_daylight_saving(date: NaiveDate) -> bool {
+    let nth_sunday = |month: u32, n: u8| NaiveDate::from_weekday_of_month_opt(date.year(), month, Weekday::Sun, n);
+    match (nth_sunday(3, 2), nth_sunday(11, 1)) {
+        (Some(start), Some(end)) => date >= start && date < end,
+        _ => false,
+    }
+}
+
+/// Regular session of `symbol`'s market on `date`, `None` if B3 does not trade that day.
+/// Ash Wednesday opens at 13:00. After-market and extended sessions are not modelled.
+pub fn session(symbol: &str, date: NaiveDate) -> Option<Session> {
+    if !is_trading_day(date) {
+        return None;
+    }
+    let ash_wednesday = date == easter_sunday(date.year()) - Duration::days(46);
+    let session = match Market::of(symbol) {
+        Market::Equities => {
+            let close = if us_daylight_saving(date) { hm(17, 0) } else { hm(18, 0) };
+            let open = if ash_wednesday { hm(13, 0) } else { hm(10, 0) };
+            Session { date, opening_auction: open - Duration::minutes(15), open, closing_auction: close - Duration::minutes(5), close }
+        }
+        Market::Futures => {
+            let open = if ash_wednesday { hm(13, 0) } else { hm(9, 0) };
+            Session { date, opening_auction: open - Duration::minutes(5), open, closing_auction: hm(18, 25), close: hm(18, 25) }
+        }
+    };
+    Some(session)
+}
+
+/// Phase of `symbol`'s market at `timestamp`.
+pub fn session_phase(symbol: &str, timestamp: DateTime<Utc>) -> SessionPhase {
+    match session(symbol, timestamp.date_naive()) {
+        Some(session) => session.phase_at(timestamp.time()),
+        None => SessionPhase::Closed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_moveable_holidays() {
+        assert_eq!(easter_sunday(2024), date(2024, 3, 31));
+        assert_eq!(easter_sunday(2025), date(2025, 4, 20));
+        // 2025: Carnival on March 3-4, Good Friday April 18, Corpus Christi June 19.
+        for holiday in [date(2025, 3, 3), date(2025, 3, 4), date(2025, 4, 18), date(2025, 6, 19)] {
+            assert!(is_holiday(holiday), "{} should be a holiday", holiday);
+        }
+        assert!(is_trading_day(date(2025, 3, 5))); // Ash Wednesday trades in the afternoon
+        assert!(!is_holiday(date(2025, 1, 25)));
+        assert!(is_holiday(date(2021, 1, 25)));
+        assert!(!is_holiday(date(2023, 11, 20)));
+        assert!(is_holiday(date(2024, 11, 20)));
+    }
+
+    #[test]
+    fn test_trading_days_skip_weekends_and_holidays() {
+        assert_eq!(next_trading_day(date(2024, 12, 23)), date(2024, 12, 26));
+        assert_eq!(next_trading_day(date(2025, 2, 28)), date(2025, 3, 5)); // Friday before Carnival
+        assert_eq!(trading_days_between(date(2025, 2, 28), date(2025, 3, 5)), 1);
+        assert_eq!(trading_days_between(date(2025, 3, 5), date(2025, 3, 10)), 3);
+        assert_eq!(trading_day_on_or_after(date(2025, 1, 1)), date(2025, 1, 2));
+    }
+
+    #[test]
+    fn test_session_hours_and_phases() {
+        // November 2024 is outside US daylight saving time: the cash market closes at 18:00.
+        let petr = session("PETR4", date(2024, 11, 26)).unwrap();
+        assert_eq!((petr.open, petr.close), (hm(10, 0), hm(18, 0)));
+        assert_eq!(session("PETR4", date(2024, 7, 1)).unwrap().close, hm(17, 0));
+        assert_eq!(session("WINZ24", date(2024, 11, 26)).unwrap().open, hm(9, 0));
+        assert_eq!(session("PETR4", date(2025, 3, 5)).unwrap().open, hm(13, 0));
+        assert!(session("PETR4", date(2024, 12, 25)).is_none());
+
+        let at = |h, m| date(2024, 11, 26).and_time(hm(h, m)).and_utc();
+        assert_eq!(session_phase("PETR4", at(9, 50)), SessionPhase::OpeningAuction);
+        assert_eq!(session_phase("PETR4", at(12, 0)), SessionPhase::Continuous);
+        assert_eq!(session_phase("PETR4", at(17, 57)), SessionPhase::ClosingAuction);
+        assert_eq!(session_phase("PETR4", at(18, 10)), SessionPhase::Closed);
+        assert_eq!(session_phase("WINZ24", at(18, 10)), SessionPhase::Continuous);
+    }
+}
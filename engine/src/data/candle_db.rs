@@ -0,0 +1,178 @@
+// SQLite file of candles.
+// Every candle loaded into the engine is written here when `engine.candle_store` is set, so
+// series survive a restart and ranges older than the candles kept in memory can be read back.
+// Prices are stored as decimal text, exactly as loaded.
+use std::fs;
+use std::path::Path;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use shared::models::{Candle, Price, TimeFrame};
+
+use super::market_data::SeriesSummary;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS candles (
+        symbol TEXT NOT NULL,
+        timeframe TEXT NOT NULL,
+        timestamp INTEGER NOT NULL, -- Milliseconds since the epoch, UTC
+        open TEXT NOT NULL,
+        high TEXT NOT NULL,
+        low TEXT NOT NULL,
+        close TEXT NOT NULL,
+        volume REAL NOT NULL,
+        trades INTEGER NOT NULL,
+        PRIMARY KEY (symbol, timeframe, timestamp)
+    ) WITHOUT ROWID;
+";
+
+const COLUMNS: &str = "timestamp, open, high, low, close, volume, trades";
+
+pub struct CandleDb {
+    connection: Connection,
+}
+
+impl CandleDb {
+    /// Opens the file at `path`, creating it (and its directory) if needed.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create the directory of the candle store {:?}", path))?;
+        }
+        let connection = Connection::open(path).with_context(|| format!("Failed to open the candle store {:?}", path))?;
+        connection.execute_batch(SCHEMA).with_context(|| format!("Failed to set up the candle store {:?}", path))?;
+        Ok(CandleDb { connection })
+    }
+
+    /// Writes `candles` to the series; one at the timestamp of a stored candle replaces it.
+    pub fn save(&mut self, symbol: &str, timeframe: TimeFrame, candles: &[Candle]) -> Result<()> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut insert = transaction.prepare_cached(&format!("INSERT OR REPLACE INTO candles (symbol, timeframe, {}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)", COLUMNS))?;
+            for c in candles {
+                insert.execute(params![
+                    symbol, timeframe.as_str(), c.timestamp.timestamp_millis(),
+                    c.open.to_string(), c.high.to_string(), c.low.to_string(), c.close.to_string(), c.volume, c.trades,
+                ])?;
+            }
+        }
+        transaction.commit().with_context(|| format!("Failed to save candles of {} {}", symbol, timeframe))
+    }
+
+    /// The stored candles in [from, to], oldest first.
+    pub fn range(&self, symbol: &str, timeframe: TimeFrame, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Result<Vec<Candle>> {
+        let mut select = self.connection.prepare_cached(&format!(
+            "SELECT {} FROM candles WHERE symbol = ?1 AND timeframe = ?2 AND timestamp BETWEEN ?3 AND ?4 ORDER BY timestamp", COLUMNS
+        ))?;
+        let (from, to) = bounds(from, to);
+        let candles = select.query_map(params![symbol, timeframe.as_str(), from, to], |row| candle(symbol, row))?
+            .collect::<rusqlite::Result<Vec<_>>>();
+        candles.with_context(|| format!("Failed to read candles of {} {} from the candle store", symbol, timeframe))
+    }
+
+    /// At most `max` of the stored candles in [from, to], oldest first, after skipping the first `skip`.
+    /// Only the page is read, so paging through a range resumes from the last timestamp sent.
+    pub fn page(&self, symbol: &str, timeframe: TimeFrame, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>, skip: usize, max: usize) -> Result<Vec<Candle>> {
+        let mut select = self.connection.prepare_cached(&format!(
+            "SELECT {} FROM candles WHERE symbol = ?1 AND timeframe = ?2 AND timestamp >= ?3 AND timestamp <= ?4 ORDER BY timestamp LIMIT ?5 OFFSET ?6", COLUMNS
+        ))?;
+        let (from, to) = bounds(from, to);
+        let (max, skip) = (i64::try_from(max).unwrap_or(i64::MAX), i64::try_from(skip).unwrap_or(i64::MAX));
+        let candles = select.query_map(params![symbol, timeframe.as_str(), from, to, max, skip], |row| candle(symbol, row))?
+            .collect::<rusqlite::Result<Vec<_>>>();
+        candles.with_context(|| format!("Failed to read candles of {} {} from the candle store", symbol, timeframe))
+    }
+
+    /// Number of stored candles in [from, to].
+    pub fn count(&self, symbol: &str, timeframe: TimeFrame, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Result<usize> {
+        let mut select = self.connection.prepare_cached(
+            "SELECT COUNT(*) FROM candles WHERE symbol = ?1 AND timeframe = ?2 AND timestamp BETWEEN ?3 AND ?4"
+        )?;
+        let (from, to) = bounds(from, to);
+        let count = select.query_row(params![symbol, timeframe.as_str(), from, to], |row| row.get::<_, i64>(0));
+        Ok(count.with_context(|| format!("Failed to count candles of {} {} in the candle store", symbol, timeframe))? as usize)
+    }
+
+    /// The last `max` candles of the series, oldest first.
+    pub fn latest(&self, symbol: &str, timeframe: TimeFrame, max: usize) -> Result<Vec<Candle>> {
+        let mut select = self.connection.prepare_cached(&format!(
+            "SELECT {} FROM candles WHERE symbol = ?1 AND timeframe = ?2 ORDER BY timestamp DESC LIMIT ?3", COLUMNS
+        ))?;
+        let mut candles = select.query_map(params![symbol, timeframe.as_str(), max as i64], |row| candle(symbol, row))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .with_context(|| format!("Failed to read candles of {} {} from the candle store", symbol, timeframe))?;
+        candles.reverse();
+        Ok(candles)
+    }
+
+    /// The latest stored candle at or before `timestamp`.
+    pub fn at_or_before(&self, symbol: &str, timeframe: TimeFrame, timestamp: DateTime<Utc>) -> Result<Option<Candle>> {
+        let mut select = self.connection.prepare_cached(&format!(
+            "SELECT {} FROM candles WHERE symbol = ?1 AND timeframe = ?2 AND timestamp <= ?3 ORDER BY timestamp DESC LIMIT 1", COLUMNS
+        ))?;
+        select.query_row(params![symbol, timeframe.as_str(), timestamp.timestamp_millis()], |row| candle(symbol, row))
+            .optional()
+            .with_context(|| format!("Failed to read a candle of {} {} from the candle store", symbol, timeframe))
+    }
+
+    /// Extent of one series, with no candles if none are stored.
+    pub fn summary(&self, symbol: &str, timeframe: TimeFrame) -> Result<SeriesSummary> {
+        let mut select = self.connection.prepare_cached(
+            "SELECT COUNT(*), MIN(timestamp), MAX(timestamp) FROM candles WHERE symbol = ?1 AND timeframe = ?2"
+        )?;
+        let summary = select.query_row(params![symbol, timeframe.as_str()], |row| {
+            let bound = |index| -> rusqlite::Result<Option<DateTime<Utc>>> {
+                row.get::<_, Option<i64>>(index)?.map(|_| timestamp(row, index)).transpose()
+            };
+            Ok(SeriesSummary { symbol: symbol.to_string(), timeframe, candles: row.get::<_, i64>(0)? as usize, first: bound(1)?, last: bound(2)? })
+        });
+        summary.with_context(|| format!("Failed to read the extent of {} {} from the candle store", symbol, timeframe))
+    }
+
+    /// Extent of every stored series.
+    pub fn summaries(&self) -> Result<Vec<SeriesSummary>> {
+        let mut select = self.connection.prepare(
+            "SELECT symbol, timeframe, COUNT(*), MIN(timestamp), MAX(timestamp) FROM candles GROUP BY symbol, timeframe"
+        )?;
+        let summaries = select.query_map([], |row| {
+            let timeframe: String = row.get(1)?;
+            Ok(SeriesSummary {
+                symbol: row.get(0)?,
+                timeframe: timeframe.parse().map_err(|e: String| rusqlite::Error::FromSqlConversionFailure(1, Type::Text, e.into()))?,
+                candles: row.get::<_, i64>(2)? as usize,
+                first: Some(timestamp(row, 3)?),
+                last: Some(timestamp(row, 4)?),
+            })
+        })?.collect::<rusqlite::Result<Vec<_>>>();
+        summaries.context("Failed to list the series of the candle store")
+    }
+}
+
+/// Milliseconds bounding [from, to]: a `from` between two milliseconds starts at the later one.
+fn bounds(from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> (i64, i64) {
+    let from = from.map_or(i64::MIN, |t| t.timestamp_millis() + i64::from(t.timestamp_subsec_nanos() % 1_000_000 != 0));
+    (from, to.map_or(i64::MAX, |t| t.timestamp_millis()))
+}
+
+fn candle(symbol: &str, row: &Row) -> rusqlite::Result<Candle> {
+    Ok(Candle {
+        symbol: symbol.to_string(),
+        timestamp: timestamp(row, 0)?,
+        open: price(row, 1)?,
+        high: price(row, 2)?,
+        low: price(row, 3)?,
+        close: price(row, 4)?,
+        volume: row.get(5)?,
+        trades: row.get(6)?,
+    })
+}
+
+fn timestamp(row: &Row, index: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let millis: i64 = row.get(index)?;
+    DateTime::from_timestamp_millis(millis).ok_or(rusqlite::Error::IntegralValueOutOfRange(index, millis))
+}
+
+fn price(row: &Row, index: usize) -> rusqlite::Result<Price> {
+    let text: String = row.get(index)?;
+    text.parse().map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
@@ -1,44 +1,131 @@
 use crate::error::EngineError; // Import EngineError
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use csv::{ReaderBuilder, StringRecord};
-use shared::models::Candle;
+use serde::{Deserialize, Serialize};
+use shared::models::{Candle, Price};
 use std::fs::File;
 use std::io::BufReader;
+use std::time::Instant;
+
+// Records parsed between two calls of a load's check (deadline, job progress).
+const CHECK_INTERVAL: usize = 1024;
+
+// Columns every file must have; "Ativo" may be left out for the requested symbol.
+const REQUIRED_COLUMNS: [&str; 8] = ["Data", "Hora", "Abertura", "Máximo", "Mínimo", "Fechamento", "Volume", "Quantidade"];
+
+/// How CSV files are written: the `data` section of the config file. Defaults to the
+/// Brazilian format of spec section 7.1 ("1.234,56", "30/12/2024", "18:20:00").
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvFormat {
+    pub csv_delimiter: char,
+    pub decimal_separator: char,
+    pub thousand_separator: char,
+    /// chrono format of the `Data` column.
+    pub date_format: String,
+    /// chrono format of the `Hora` column.
+    pub time_format: String,
+}
 
-// Module for Brazilian number and date/time format handling, as per spec section 7.1
-pub mod brazilian_format {
-    use crate::error::EngineError; // For returning CsvDataFormatError
-    use std::str::FromStr;
-    // Using anyhow::Error for internal error propagation within this module, then map to EngineError if needed.
-    // Or directly use EngineError if preferred. For now, keeping anyhow for internal detailed errors.
-    use anyhow::Result; // Removed unused 'anyhow' macro import
-    use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+impl Default for CsvFormat {
+    fn default() -> Self {
+        CsvFormat {
+            csv_delimiter: ';',
+            decimal_separator: ',',
+            thousand_separator: '.',
+            date_format: shared::utils::brazilian_format::DATE_FORMAT.to_string(),
+            time_format: shared::utils::brazilian_format::TIME_FORMAT.to_string(),
+        }
+    }
+}
+
+impl CsvFormat {
+    /// Checks the format can be used to read files: single-byte delimiter, distinct separators,
+    /// valid date and time formats.
+    pub fn validate(&self) -> Result<(), EngineError> {
+        if !self.csv_delimiter.is_ascii() {
+            return Err(EngineError::ConfigError(format!("csv_delimiter '{}' must be a single ASCII character", self.csv_delimiter)));
+        }
+        if self.decimal_separator == self.thousand_separator {
+            return Err(EngineError::ConfigError(format!("decimal_separator and thousand_separator are both '{}'", self.decimal_separator)));
+        }
+        for (name, format) in [("date_format", &self.date_format), ("time_format", &self.time_format)] {
+            if format.is_empty() || StrftimeItems::new(format).any(|item| item == Item::Error) {
+                return Err(EngineError::ConfigError(format!("{} '{}' is not a valid strftime format", name, format)));
+            }
+        }
+        Ok(())
+    }
 
     // Parses decimals like "1.234,56" or "123,45" into f64
-    pub fn parse_decimal(s: &str) -> Result<f64, EngineError> { // Changed to Result<_, EngineError>
-        let normalized = s.trim()
-            .replace('.', "")  // Remove thousand separators
-            .replace(',', "."); // Replace decimal separator
+    pub fn parse_decimal(&self, s: &str) -> Result<f64, EngineError> {
+        self.normalize(s).parse::<f64>()
+            .map_err(|e| EngineError::CsvDataFormatError(format!("Failed to parse decimal '{}': {}", s, e)))
+    }
 
-        f64::from_str(&normalized)
+    // Parses prices like "1.234,56" exactly, without going through f64
+    pub fn parse_price(&self, s: &str) -> Result<Price, EngineError> {
+        self.normalize(s).parse::<Price>()
+            .map(|price| price.normalize())
             .map_err(|e| EngineError::CsvDataFormatError(format!("Failed to parse decimal '{}': {}", s, e)))
     }
 
-    // Specifically for volume fields that might have a different thousand separator rule or be just a large number.
-    pub fn parse_volume(s: &str) -> Result<f64, EngineError> { // Changed to Result<_, EngineError>
-        parse_decimal(s) // Reuses parse_decimal which now returns Result<_, EngineError>
+    // "1.234,56" as "1234.56"
+    fn normalize(&self, s: &str) -> String {
+        s.trim().chars()
+            .filter(|c| *c != self.thousand_separator)
+            .map(|c| if c == self.decimal_separator { '.' } else { c })
+            .collect()
     }
 
-    // Parses date "dd/mm/yyyy" and time "HH:MM:SS" into DateTime<Utc>
-    pub fn parse_datetime(date_str: &str, time_str: &str) -> Result<DateTime<Utc>, EngineError> { // Changed
-        let date = NaiveDate::parse_from_str(date_str, "%d/%m/%Y")
-            .map_err(|e| EngineError::CsvDataFormatError(format!("Failed to parse date '{}': {}", date_str, e)))?;
-        let time = NaiveTime::parse_from_str(time_str, "%H:%M:%S")
-            .map_err(|e| EngineError::CsvDataFormatError(format!("Failed to parse time '{}': {}", time_str, e)))?;
+    // Parses whole counts like "24.228" into u32
+    pub fn parse_count(&self, s: &str) -> Result<u32, EngineError> {
+        s.trim().replace(self.thousand_separator, "").parse::<u32>()
+            .map_err(|e| EngineError::CsvDataFormatError(format!("Failed to parse count '{}': {}", s, e)))
+    }
+
+    pub fn parse_date(&self, date_str: &str) -> Result<NaiveDate, EngineError> {
+        NaiveDate::parse_from_str(date_str, &self.date_format)
+            .map_err(|e| EngineError::CsvDataFormatError(format!("Failed to parse date '{}': {}", date_str, e)))
+    }
+
+    pub fn parse_time(&self, time_str: &str) -> Result<NaiveTime, EngineError> {
+        NaiveTime::parse_from_str(time_str, &self.time_format)
+            .map_err(|e| EngineError::CsvDataFormatError(format!("Failed to parse time '{}': {}", time_str, e)))
+    }
+
+    // Parses the date and time columns into DateTime<Utc>
+    pub fn parse_datetime(&self, date_str: &str, time_str: &str) -> Result<DateTime<Utc>, EngineError> {
+        let (date, time) = (self.parse_date(date_str)?, self.parse_time(time_str)?);
 
         // Combine date and time, and assume it's in UTC.
         // If the CSV times are local, timezone conversion would be needed here.
         Ok(DateTime::from_naive_utc_and_offset(date.and_time(time), Utc))
     }
+}
+
+// Module for Brazilian number and date/time format handling, as per spec section 7.1:
+// `shared::utils::brazilian_format`, with its errors as CSV data format errors.
+pub mod brazilian_format {
+    use crate::error::EngineError; // For returning CsvDataFormatError
+    use chrono::{DateTime, Utc};
+    use shared::utils::brazilian_format as shared_format;
+
+    // Parses decimals like "1.234,56" or "123,45" into f64
+    pub fn parse_decimal(s: &str) -> Result<f64, EngineError> {
+        shared_format::parse_decimal(s).map_err(|e| EngineError::CsvDataFormatError(e.to_string()))
+    }
+
+    // Specifically for volume fields that might have a different thousand separator rule or be just a large number.
+    pub fn parse_volume(s: &str) -> Result<f64, EngineError> {
+        shared_format::parse_volume(s).map_err(|e| EngineError::CsvDataFormatError(e.to_string()))
+    }
+
+    // Parses date "dd/mm/yyyy" and time "HH:MM:SS" into DateTime<Utc>
+    pub fn parse_datetime(date_str: &str, time_str: &str) -> Result<DateTime<Utc>, EngineError> {
+        shared_format::parse_datetime(date_str, time_str).map_err(|e| EngineError::CsvDataFormatError(e.to_string()))
+    }
 
     #[cfg(test)]
     mod tests {
@@ -90,76 +177,172 @@ pub mod brazilian_format {
 
 pub struct BrazilianCsvParser;
 
+/// A record a lenient load left out, and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedRow {
+    /// Line of the record, counting the header as line 1.
+    pub line: usize,
+    /// Column whose value was bad; empty when the whole record was (e.g. a missing field separator).
+    pub column: String,
+    pub reason: String,
+}
+
+/// The candles read from a file and, if its bad rows were skipped, the rows left out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CsvLoad {
+    pub candles: Vec<Candle>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+/// The error for `column` of the record at `line`, from the error parsing its value.
+fn record_error(line: usize, column: &str, error: EngineError) -> EngineError {
+    let message = match error {
+        EngineError::CsvDataFormatError(message) => message,
+        other => other.to_string(),
+    };
+    EngineError::CsvRecordError { line, column: column.to_string(), message: format!("Error parsing '{}': {}", column, message) }
+}
+
 impl BrazilianCsvParser {
     // CSV Header: Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade
     // Example Row: WINFUT;30/12/2024;18:20:00;124.080;124.090;123.938;123.983;600.822.115,84;24.228
     pub fn load_candles_from_csv(file_path: &str, default_symbol: &str) -> Result<Vec<Candle>, EngineError> {
+        Self::load_candles_from_csv_until(file_path, default_symbol, &CsvFormat::default(), false, None).map(|load| load.candles)
+    }
+
+    // Same as `load_candles_from_csv` for files written in `format`, giving up with DeadlineExceeded once `deadline` passes.
+    // With `skip_bad_rows`, a record that cannot be read is left out instead of failing the load.
+    pub fn load_candles_from_csv_until(
+        file_path: &str,
+        default_symbol: &str,
+        format: &CsvFormat,
+        skip_bad_rows: bool,
+        deadline: Option<Instant>,
+    ) -> Result<CsvLoad, EngineError> {
+        Self::load_candles_from_csv_checked(file_path, default_symbol, format, skip_bad_rows, |line_num, _| match deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                Err(EngineError::DeadlineExceeded(format!("Loading {} gave up at line {}", file_path, line_num)))
+            }
+            _ => Ok(()),
+        })
+    }
+
+    // Same as `load_candles_from_csv_until`, calling `check(line_num, fraction_read)` every few records instead,
+    // with the share of the file read so far (0 to 1). An error from `check` stops the load.
+    pub fn load_candles_from_csv_checked(
+        file_path: &str,
+        default_symbol: &str,
+        format: &CsvFormat,
+        skip_bad_rows: bool,
+        mut check: impl FnMut(usize, f64) -> Result<(), EngineError>,
+    ) -> Result<CsvLoad, EngineError> {
         let file = File::open(file_path).map_err(|e| EngineError::IoError{ source: e })?;
+        let file_len = file.metadata().map_err(|e| EngineError::IoError{ source: e })?.len().max(1);
         let mut rdr = ReaderBuilder::new()
-            .delimiter(b';')
+            .delimiter(format.csv_delimiter as u8)
             .has_headers(true) // Assuming the first row is a header
             .from_reader(BufReader::new(file));
 
-        let mut candles = Vec::new();
+        let mut load = CsvLoad::default();
         // Map csv::Error to EngineError::CsvSystemError
         let headers = rdr.headers().map_err(|e| EngineError::CsvSystemError{ source: e })?.clone();
+        // A missing column is the file's fault, not each row's: a lenient load must not skip them all.
+        if let Some(missing) = REQUIRED_COLUMNS.iter().find(|name| !headers.iter().any(|header| header == **name)) {
+            return Err(EngineError::CsvDataFormatError(format!("Missing '{}' field in the CSV header", missing)));
+        }
+        let mut read = 0.0;
 
         for (idx, result) in rdr.records().enumerate() {
-            // Map csv::Error to EngineError::CsvSystemError
-            let record = result.map_err(|e| EngineError::CsvSystemError{ source: e })?;
             let line_num = idx + 2; // For user-friendly error messages (1-based index + header)
+            if let Some(position) = result.as_ref().ok().and_then(|record| record.position()) {
+                read = position.byte() as f64 / file_len as f64;
+            }
+            if idx % CHECK_INTERVAL == 0 {
+                check(line_num, read)?;
+            }
 
-            let get_field_or_err = |name: &str| {
-                Self::get_field(&record, &headers, name)
-                    .map_err(EngineError::from) // Convert anyhow::Error from get_field to EngineError
-                    .and_then(|opt_val| {
-                        opt_val.ok_or_else(|| EngineError::CsvDataFormatError(format!("Missing '{}' field in CSV record at line {}", name, line_num)))
+            let candle = result
+                .map_err(|e| EngineError::CsvSystemError{ source: e })
+                .and_then(|record| Self::parse_record(&record, &headers, line_num, default_symbol, format));
+            match candle {
+                Ok(candle) => load.candles.push(candle),
+                Err(e) if skip_bad_rows => match Self::skipped_row(line_num, e) {
+                    Ok(row) => load.skipped.push(row),
+                    Err(e) => return Err(e),
+                },
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(load)
+    }
+
+    /// The candle in `record`, the one at `line_num` of the file.
+    fn parse_record(record: &StringRecord, headers: &StringRecord, line_num: usize, default_symbol: &str, format: &CsvFormat) -> Result<Candle, EngineError> {
+        let get_field_or_err = |name: &str| {
+            Self::get_field(record, headers, name)
+                .and_then(|opt_val| {
+                    opt_val.ok_or_else(|| EngineError::CsvRecordError {
+                        line: line_num,
+                        column: name.to_string(),
+                        message: format!("Missing '{}' field in CSV record", name),
                     })
-            };
-
-            let symbol_str = Self::get_field(&record, &headers, "Ativo")?.unwrap_or(default_symbol); // get_field can return anyhow error
-            let date_str = get_field_or_err("Data")?;
-            let time_str = get_field_or_err("Hora")?;
-
-            let open_str = get_field_or_err("Abertura")?;
-            let high_str = get_field_or_err("Máximo")?;
-            let low_str = get_field_or_err("Mínimo")?;
-            let close_str = get_field_or_err("Fechamento")?;
-
-            let volume_str = get_field_or_err("Volume")?;
-            let trades_str = get_field_or_err("Quantidade")?;
-
-            // brazilian_format functions now return Result<_, EngineError>
-            let timestamp = brazilian_format::parse_datetime(date_str, time_str)
-                .map_err(|e| EngineError::CsvDataFormatError(format!("{} at line {}", e, line_num)))?;
-
-            let open = brazilian_format::parse_decimal(open_str)
-                .map_err(|e| EngineError::CsvDataFormatError(format!("Error parsing 'Abertura': {} at line {}", e, line_num)))?;
-            let high = brazilian_format::parse_decimal(high_str)
-                .map_err(|e| EngineError::CsvDataFormatError(format!("Error parsing 'Máximo': {} at line {}", e, line_num)))?;
-            let low = brazilian_format::parse_decimal(low_str)
-                .map_err(|e| EngineError::CsvDataFormatError(format!("Error parsing 'Mínimo': {} at line {}", e, line_num)))?;
-            let close = brazilian_format::parse_decimal(close_str)
-                .map_err(|e| EngineError::CsvDataFormatError(format!("Error parsing 'Fechamento': {} at line {}", e, line_num)))?;
-
-            let volume = brazilian_format::parse_volume(volume_str)
-                .map_err(|e| EngineError::CsvDataFormatError(format!("Error parsing 'Volume': {} at line {}", e, line_num)))?;
-
-            let trades = trades_str.replace('.', "").parse::<u32>()
-                .map_err(|e| EngineError::CsvDataFormatError(format!("Error parsing 'Quantidade' {} as u32: {} at line {}", trades_str, e, line_num)))?;
-
-            candles.push(Candle {
-                symbol: symbol_str.to_string(),
-                timestamp,
-                open,
-                high,
-                low,
-                close,
-                volume,
-                trades,
+                })
+        };
+
+        let symbol_str = Self::get_field(record, headers, "Ativo")?.unwrap_or(default_symbol); // get_field can return anyhow error
+        let date_str = get_field_or_err("Data")?;
+        let time_str = get_field_or_err("Hora")?;
+
+        let open_str = get_field_or_err("Abertura")?;
+        let high_str = get_field_or_err("Máximo")?;
+        let low_str = get_field_or_err("Mínimo")?;
+        let close_str = get_field_or_err("Fechamento")?;
+
+        let volume_str = get_field_or_err("Volume")?;
+        let trades_str = get_field_or_err("Quantidade")?;
+
+        let date = format.parse_date(date_str).map_err(|e| record_error(line_num, "Data", e))?;
+        let time = format.parse_time(time_str).map_err(|e| record_error(line_num, "Hora", e))?;
+        // Combine date and time, and assume it's in UTC (see `CsvFormat::parse_datetime`).
+        let timestamp = DateTime::from_naive_utc_and_offset(date.and_time(time), Utc);
+
+        let open = format.parse_price(open_str).map_err(|e| record_error(line_num, "Abertura", e))?;
+        let high = format.parse_price(high_str).map_err(|e| record_error(line_num, "Máximo", e))?;
+        let low = format.parse_price(low_str).map_err(|e| record_error(line_num, "Mínimo", e))?;
+        let close = format.parse_price(close_str).map_err(|e| record_error(line_num, "Fechamento", e))?;
+        let volume = format.parse_decimal(volume_str).map_err(|e| record_error(line_num, "Volume", e))?;
+        let trades = format.parse_count(trades_str).map_err(|e| record_error(line_num, "Quantidade", e))?;
+
+        let candle = Candle {
+            symbol: symbol_str.to_string(),
+            timestamp,
+            open,
+            high,
+            low,
+            close,
+            volume,
+            trades,
+        };
+        if !candle.is_valid() {
+            return Err(EngineError::CsvRecordError {
+                line: line_num,
+                column: String::new(),
+                message: format!("Inconsistent candle: open {} and close {} must lie within low {} and high {}, and volume {} must not be negative", open, close, low, high, volume),
             });
         }
-        Ok(candles)
+        Ok(candle)
+    }
+
+    /// The row a lenient load skips for `error` on the record at `line_num`; the error itself when
+    /// it is not about that record (reading the file failed), which stops the load.
+    fn skipped_row(line_num: usize, error: EngineError) -> Result<SkippedRow, EngineError> {
+        match error {
+            EngineError::CsvRecordError { line, column, message } => Ok(SkippedRow { line, column, reason: message }),
+            EngineError::CsvSystemError { source } if !matches!(source.kind(), csv::ErrorKind::Io(_)) => {
+                Ok(SkippedRow { line: line_num, column: String::new(), reason: source.to_string() })
+            }
+            other => Err(other),
+        }
     }
 
     // Helper to get field by header name.
@@ -204,7 +387,7 @@ PETR4;02/01/2023;10:00:00;23,50;23,80;23,40;23,75;1.000.000,00;1000";
 
         assert_eq!(candles[0].symbol, "WINFUT");
         assert_eq!(candles[0].timestamp, brazilian_format::parse_datetime("30/12/2024", "18:20:00").unwrap());
-        assert_eq!(candles[0].open, 124080.0); // Assuming "124.080" is 124080, not 124.080 - brazilian_format::parse_decimal interprets "." as thousand sep.
+        assert_eq!(candles[0].open, Price::from(124080)); // Assuming "124.080" is 124080, not 124.080 - brazilian_format::parse_decimal interprets "." as thousand sep.
                                             // If "124.080" means 124 point 080, the parse_decimal needs adjustment for this specific field
                                             // The spec example "124.080" vs "600.822.115,84" is ambiguous.
                                             // Current parse_decimal: "124.080" -> 124080.0. "124,080" -> 124.080
@@ -228,18 +411,27 @@ PETR4;02/01/2023;10:00:00;23,50;23,80;23,40;23,75;1.000.000,00;1000";
                                             // where '.' is thousands and ',' is decimal, then `parse_decimal` is correct.
                                             // The ambiguity is "124.080" vs "123.938". If these are mini-indice points, they are like this.
                                             // WINFUT (Mini Indice) prices are typically like 124080 points. So parse_decimal is correct.
-        assert_eq!(candles[0].high, 124090.0);
-        assert_eq!(candles[0].low, 123938.0);
-        assert_eq!(candles[0].close, 123983.0);
+        assert_eq!(candles[0].high, Price::from(124090));
+        assert_eq!(candles[0].low, Price::from(123938));
+        assert_eq!(candles[0].close, Price::from(123983));
         assert_eq!(candles[0].volume, 600822115.84);
         assert_eq!(candles[0].trades, 24228);
 
         assert_eq!(candles[1].symbol, "PETR4");
-        assert_eq!(candles[1].open, 23.50); // "23,50" -> 23.50. This is fine.
+        assert_eq!(candles[1].open, Price::new(2350, 2)); // "23,50" -> 23.50. This is fine.
         assert_eq!(candles[1].volume, 1000000.00); // "1.000.000,00" -> 1000000.0. Fine.
         assert_eq!(candles[1].trades, 1000); // "1000" -> 1000. Fine.
     }
 
+    #[test]
+    fn test_prices_are_parsed_exactly() {
+        let format = CsvFormat::default();
+        let prices: Vec<Price> = ["0,1", "0,2", "1.234,56"].iter().map(|s| format.parse_price(s).unwrap()).collect();
+        assert_eq!(prices[0] + prices[1], Price::new(3, 1));
+        assert_eq!(prices[2].to_string(), "1234.56");
+        assert!(format.parse_price("12,3x").is_err());
+    }
+
     #[test]
     fn test_load_candles_from_csv_empty_file() {
         let csv_content = "Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade"; // Only header
@@ -267,6 +459,57 @@ WINFUT;30/12/2024;18:20:00;invalid;124.090;123.938;123.983;600.822.115,84;24.228
         let tmp_file = create_test_csv(csv_content);
         let result = BrazilianCsvParser::load_candles_from_csv(tmp_file.path().to_str().unwrap(), "FALLBACK");
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("Error parsing 'Abertura'"));
+        let error = result.unwrap_err();
+        assert!(error.to_string().contains("Error parsing 'Abertura'"));
+        assert!(matches!(error, EngineError::CsvRecordError { line: 2, ref column, .. } if column == "Abertura"));
+    }
+
+    #[test]
+    fn test_load_candles_in_a_configured_format() {
+        let csv_content = "\
+Ativo|Data|Hora|Abertura|Máximo|Mínimo|Fechamento|Volume|Quantidade
+PETR4|2023-01-02|10:00|1,023.50|1,023.80|1,023.40|1,023.75|1,000,000.00|1,000";
+        let tmp_file = create_test_csv(csv_content);
+        let format = CsvFormat {
+            csv_delimiter: '|',
+            decimal_separator: '.',
+            thousand_separator: ',',
+            date_format: "%Y-%m-%d".to_string(),
+            time_format: "%H:%M".to_string(),
+        };
+        let candles = BrazilianCsvParser::load_candles_from_csv_until(tmp_file.path().to_str().unwrap(), "FALLBACK", &format, false, None).unwrap().candles;
+        assert_eq!((candles[0].open, candles[0].close, candles[0].volume, candles[0].trades), (Price::new(10235, 1), Price::new(102375, 2), 1_000_000.0, 1000));
+        assert_eq!(candles[0].timestamp, brazilian_format::parse_datetime("02/01/2023", "10:00:00").unwrap());
+
+        assert!(CsvFormat { thousand_separator: ',', ..CsvFormat::default() }.validate().is_err());
+        assert!(CsvFormat { csv_delimiter: '¦', ..CsvFormat::default() }.validate().is_err());
+        assert!(CsvFormat { date_format: "%d/%m/%Q".to_string(), ..CsvFormat::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_bad_rows_are_skipped_and_reported() {
+        let csv_content = "\
+Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade
+PETR4;02/01/2024;00:00:00;37,00;37,50;36,80;37,20;1.000,00;100
+PETR4;03/01/2024;00:00:00;37,20;abc;36,90;37,40;1.000,00;100
+PETR4;04/01/2024;00:00:00;37,40
+PETR4;05/01/2024;00:00:00;37,40;37,90;37,10;37,80;1.000,00;100
+PETR4;08/01/2024;00:00:00;38,00;37,90;37,10;37,80;1.000,00;100";
+        let tmp_file = create_test_csv(csv_content);
+        let path = tmp_file.path().to_str().unwrap();
+
+        let strict = BrazilianCsvParser::load_candles_from_csv_until(path, "FALLBACK", &CsvFormat::default(), false, None);
+        assert!(matches!(strict, Err(EngineError::CsvRecordError { line: 3, .. })));
+
+        let load = BrazilianCsvParser::load_candles_from_csv_until(path, "FALLBACK", &CsvFormat::default(), true, None).unwrap();
+        assert_eq!(load.candles.iter().map(|c| c.close).collect::<Vec<_>>(), vec![Price::new(3720, 2), Price::new(3780, 2)]);
+        assert_eq!(load.skipped.iter().map(|row| (row.line, row.column.as_str())).collect::<Vec<_>>(), vec![(3, "Máximo"), (4, ""), (6, "")]);
+        assert!(load.skipped[0].reason.contains("'abc'"));
+        assert!(load.skipped[2].reason.contains("Inconsistent candle")); // Opens above the high
+
+        let no_trades = "Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume\nPETR4;02/01/2024;00:00:00;37,00;37,50;36,80;37,20;1.000,00";
+        let tmp_file = create_test_csv(no_trades);
+        let missing_column = BrazilianCsvParser::load_candles_from_csv_until(tmp_file.path().to_str().unwrap(), "FALLBACK", &CsvFormat::default(), true, None);
+        assert!(matches!(missing_column, Err(EngineError::CsvDataFormatError(_))));
     }
 }
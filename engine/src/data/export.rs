@@ -0,0 +1,157 @@
+// Candle export
+// Writes a series, with indicator values computed over it, as CSV or JSON: one row per candle with
+// its timestamp, prices, volume and trades, then one value per indicator. Values an indicator has
+// none for (e.g. before its period is filled) are left empty, or null in JSON.
+// CSV files are in the B3 layout LoadCsvData reads ("Ativo;Data;Hora;...", "1.234,56"), so an export
+// loads back as it was; JSON has RFC 3339 timestamps and plain numbers.
+use serde_json::{json, Map, Value};
+use shared::models::{Candle, TimeFrame};
+use shared::utils::brazilian_format::{format_date, format_decimal, format_price, format_time, format_volume};
+
+use crate::error::EngineError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    /// The format of a request: "CSV" (also when empty) or "JSON", in any case.
+    pub fn parse(value: &str) -> Result<Self, EngineError> {
+        match value.trim().to_uppercase().as_str() {
+            "" | "CSV" => Ok(ExportFormat::Csv),
+            "JSON" => Ok(ExportFormat::Json),
+            _ => Err(EngineError::InvalidField { field: "format".to_string(), message: format!("Unknown export format '{}'", value) }),
+        }
+    }
+}
+
+/// An indicator's values, one per exported candle, under its name (e.g. "SMA(20)").
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedSeries {
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+impl ExportedSeries {
+    fn value(&self, index: usize) -> Option<f64> {
+        self.values.get(index).copied().filter(|value| value.is_finite())
+    }
+}
+
+/// Decimal places of indicator values in CSV files.
+const INDICATOR_DECIMALS: usize = 4;
+
+/// The file contents of `candles` of `symbol` and `series` in `format`.
+pub fn export_candles(symbol: &str, timeframe: TimeFrame, candles: &[Candle], series: &[ExportedSeries], format: ExportFormat) -> Result<String, EngineError> {
+    match format {
+        ExportFormat::Csv => to_csv(symbol, candles, series),
+        ExportFormat::Json => to_json(symbol, timeframe, candles, series),
+    }
+}
+
+fn to_csv(symbol: &str, candles: &[Candle], series: &[ExportedSeries]) -> Result<String, EngineError> {
+    let mut writer = csv::WriterBuilder::new().delimiter(b';').from_writer(Vec::new());
+    let mut header: Vec<&str> = vec!["Ativo", "Data", "Hora", "Abertura", "Máximo", "Mínimo", "Fechamento", "Volume", "Quantidade"];
+    header.extend(series.iter().map(|series| series.name.as_str()));
+    writer.write_record(&header)?;
+    for (i, candle) in candles.iter().enumerate() {
+        let mut record = vec![
+            symbol.to_string(),
+            format_date(candle.timestamp),
+            format_time(candle.timestamp),
+            format_price(candle.open),
+            format_price(candle.high),
+            format_price(candle.low),
+            format_price(candle.close),
+            format_volume(candle.volume),
+            format_decimal(candle.trades as f64, 0),
+        ];
+        record.extend(series.iter().map(|series| series.value(i).map_or_else(String::new, |value| format_decimal(value, INDICATOR_DECIMALS))));
+        writer.write_record(&record)?;
+    }
+    let bytes = writer.into_inner().map_err(|e| EngineError::ProcessingError(format!("Failed to write the CSV export: {}", e)))?;
+    String::from_utf8(bytes).map_err(|e| EngineError::ProcessingError(format!("The CSV export is not UTF-8: {}", e)))
+}
+
+fn to_json(symbol: &str, timeframe: TimeFrame, candles: &[Candle], series: &[ExportedSeries]) -> Result<String, EngineError> {
+    let rows: Vec<Value> = candles.iter().enumerate().map(|(i, candle)| {
+        let indicators: Map<String, Value> = series.iter().map(|series| (series.name.clone(), series.value(i).map_or(Value::Null, Value::from))).collect();
+        json!({
+            "timestamp": candle.timestamp.to_rfc3339(),
+            "open": candle.open,
+            "high": candle.high,
+            "low": candle.low,
+            "close": candle.close,
+            "volume": candle.volume,
+            "trades": candle.trades,
+            "indicators": indicators,
+        })
+    }).collect();
+    serde_json::to_string_pretty(&json!({ "symbol": symbol, "timeframe": timeframe, "candles": rows }))
+        .map_err(|e| EngineError::ProcessingError(format!("Failed to write the JSON export: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::to_price;
+    use chrono::{TimeZone, Utc};
+
+    fn candles() -> Vec<Candle> {
+        (0..2).map(|day| Candle {
+            symbol: "PETR4".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 11, 25 + day, 13, 0, 0).unwrap(),
+            open: to_price(37.0),
+            high: to_price(38.5),
+            low: to_price(36.5),
+            close: to_price(37.25 + day as f64),
+            volume: 1000.0,
+            trades: 12,
+        }).collect()
+    }
+
+    fn sma() -> Vec<ExportedSeries> {
+        vec![ExportedSeries { name: "SMA(2)".to_string(), values: vec![f64::NAN, 37.75] }]
+    }
+
+    #[test]
+    fn test_csv_has_a_column_per_indicator() {
+        let csv = export_candles("PETR4", TimeFrame::Day1, &candles(), &sma(), ExportFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines, vec![
+            "Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade;SMA(2)",
+            "PETR4;25/11/2024;13:00:00;37;38,5;36,5;37,25;1.000,00;12;",
+            "PETR4;26/11/2024;13:00:00;37;38,5;36,5;38,25;1.000,00;12;37,7500",
+        ]);
+    }
+
+    #[test]
+    fn test_csv_loads_back() {
+        use crate::data::csv_parser::BrazilianCsvParser;
+        use std::io::Write;
+        let csv = export_candles("PETR4", TimeFrame::Day1, &candles(), &sma(), ExportFormat::Csv).unwrap();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(csv.as_bytes()).unwrap();
+        assert_eq!(BrazilianCsvParser::load_candles_from_csv(file.path().to_str().unwrap(), "OTHER").unwrap(), candles());
+    }
+
+    #[test]
+    fn test_json_leaves_missing_values_null() {
+        let json = export_candles("PETR4", TimeFrame::Day1, &candles(), &sma(), ExportFormat::Json).unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["symbol"], "PETR4");
+        assert_eq!(value["candles"].as_array().unwrap().len(), 2);
+        assert_eq!(value["candles"][0]["indicators"]["SMA(2)"], Value::Null);
+        assert_eq!(value["candles"][1]["indicators"]["SMA(2)"], 37.75);
+        assert_eq!(value["candles"][1]["close"], "38.25"); // Exact, like the account file
+    }
+
+    #[test]
+    fn test_format_parsing() {
+        assert_eq!(ExportFormat::parse("").unwrap(), ExportFormat::Csv);
+        assert_eq!(ExportFormat::parse("json").unwrap(), ExportFormat::Json);
+        assert!(ExportFormat::parse("xlsx").is_err());
+    }
+}
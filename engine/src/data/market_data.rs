@@ -1,44 +1,305 @@
 // Manages market data, including candles and potentially other data types
-use shared::models::{Candle, TimeFrame};
+use crate::data::calendar::trading_days_between;
+use crate::data::candle_db::CandleDb;
+use shared::models::{Candle, CandleSeries, TimeFrame};
 use std::collections::HashMap;
-use anyhow::Result;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use lru::LruCache;
+use serde::Deserialize;
+use tokio::sync::broadcast;
+
+const UPDATES_CHANNEL_CAPACITY: usize = 1024;
+
+pub const DEFAULT_RECENT_CANDLES: usize = 5_000;
+pub const DEFAULT_CACHED_RANGES: usize = 16;
+
+/// A candle added to (or replaced in) the store, as broadcast to subscribers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandleUpdate {
+    pub symbol: String,
+    pub timeframe: TimeFrame,
+    pub candle: Candle,
+}
+
+/// Extent of one stored series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesSummary {
+    pub symbol: String,
+    pub timeframe: TimeFrame,
+    pub candles: usize,
+    /// `None` for an empty series.
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+}
+
+/// Bars missing from a series between two stored ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gap {
+    pub after: DateTime<Utc>,
+    pub before: DateTime<Utc>,
+    pub missing: u32,
+}
+
+/// The `engine.candle_store` section: the SQLite file every loaded candle is written to, and how
+/// much of it stays in memory.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CandleStoreSettings {
+    pub file: String,
+    /// Latest candles of each series kept in memory; older ones are read from the file when asked for.
+    #[serde(default = "default_recent_candles")]
+    pub recent_candles: usize,
+    /// Ranges read from the file kept in memory, the least recently used dropped first.
+    #[serde(default = "default_cached_ranges")]
+    pub cached_ranges: usize,
+}
+
+fn default_recent_candles() -> usize {
+    DEFAULT_RECENT_CANDLES
+}
+
+fn default_cached_ranges() -> usize {
+    DEFAULT_CACHED_RANGES
+}
+
+/// Symbol, timeframe and bounds of a range read from the candle file.
+type RangeKey = (String, TimeFrame, Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// The candle file of a store, with what is known of it in memory.
+struct CandleFile {
+    db: Mutex<CandleDb>,
+    /// Ranges reaching past the candles kept in memory, as last read.
+    ranges: Mutex<LruCache<RangeKey, CandleSeries>>,
+    recent_candles: usize,
+    /// Extent of each series in the file.
+    stored: HashMap<(String, TimeFrame), SeriesSummary>,
+}
+
+impl CandleFile {
+    fn save(&mut self, symbol: &str, timeframe: TimeFrame, candles: &[Candle]) -> Result<()> {
+        let db = self.db.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner());
+        db.save(symbol, timeframe, candles)?;
+        // Re-read rather than worked out, as the candles may replace stored ones.
+        self.stored.insert((symbol.to_string(), timeframe), db.summary(symbol, timeframe)?);
+        // Cached ranges of the series may be missing the new candles.
+        let ranges = self.ranges.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let stale: Vec<RangeKey> = ranges.iter().map(|(key, _)| key).filter(|key| key.0 == symbol && key.1 == timeframe).cloned().collect();
+        for key in stale {
+            ranges.pop(&key);
+        }
+        Ok(())
+    }
+
+    /// Whether candles from `from` on include some of the file's not kept in `recent`.
+    fn reaches_past(&self, symbol: &str, timeframe: TimeFrame, recent: &CandleSeries, from: Option<DateTime<Utc>>) -> bool {
+        let stored = self.stored.get(&(symbol.to_string(), timeframe)).map_or(0, |summary| summary.candles);
+        stored > recent.len() && recent.first().is_none_or(|first| from.is_none_or(|from| from < first.timestamp))
+    }
+
+    /// The file's candles in [from, to], from the cache if this range was read lately.
+    fn range(&self, symbol: &str, timeframe: TimeFrame, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Result<CandleSeries> {
+        let key = (symbol.to_string(), timeframe, from, to);
+        if let Some(series) = lock(&self.ranges).get(&key) {
+            return Ok(series.clone());
+        }
+        let series = CandleSeries::new(lock(&self.db).range(symbol, timeframe, from, to)?);
+        lock(&self.ranges).put(key, series.clone());
+        Ok(series)
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
 
 // Example structure, will be refined
 pub struct MarketDataStore {
-    // Stores market data per symbol and timeframe
-    // This is a simplified example; a more robust solution might use a database or specialized time-series storage.
-    data: HashMap<String, HashMap<TimeFrame, Vec<Candle>>>,
+    // Stores market data per symbol and timeframe: every candle, or with a candle file, the latest
+    // `recent_candles` of each series.
+    data: HashMap<String, HashMap<TimeFrame, CandleSeries>>,
+    file: Option<CandleFile>,
+    updates: broadcast::Sender<CandleUpdate>,
 }
 
 impl MarketDataStore {
     pub fn new() -> Self {
         MarketDataStore {
             data: HashMap::new(),
+            file: None,
+            updates: broadcast::channel(UPDATES_CHANNEL_CAPACITY).0,
         }
     }
 
+    /// A store writing every candle to the file `settings` name, starting with the series already in
+    /// it: their latest candles in memory, the older ones read when a range reaches them.
+    pub fn open(settings: &CandleStoreSettings) -> Result<Self> {
+        let db = CandleDb::open(Path::new(&settings.file))?;
+        let recent_candles = settings.recent_candles.max(1);
+        let cached_ranges = NonZeroUsize::new(settings.cached_ranges).context("engine.candle_store.cached_ranges must be at least 1")?;
+        let mut store = Self::new();
+        let mut stored = HashMap::new();
+        for summary in db.summaries()? {
+            let recent = db.latest(&summary.symbol, summary.timeframe, recent_candles)?;
+            store.data.entry(summary.symbol.clone()).or_default().insert(summary.timeframe, CandleSeries::new(recent));
+            stored.insert((summary.symbol.clone(), summary.timeframe), summary);
+        }
+        store.file = Some(CandleFile { db: Mutex::new(db), ranges: Mutex::new(LruCache::new(cached_ranges)), recent_candles, stored });
+        Ok(store)
+    }
+
+    /// An in-memory store, or the candle file's if `settings` name one.
+    pub fn from_settings(settings: Option<&CandleStoreSettings>) -> Result<Self> {
+        settings.map_or_else(|| Ok(Self::new()), Self::open)
+    }
+
     pub fn add_candles(&mut self, symbol: &str, timeframe: TimeFrame, new_candles: Vec<Candle>) -> Result<()> {
-        let symbol_data = self.data.entry(symbol.to_string()).or_insert_with(HashMap::new);
-        let timeframe_data = symbol_data.entry(timeframe).or_insert_with(Vec::new);
+        if let Some(bad) = new_candles.iter().find(|candle| !candle.is_valid()) {
+            anyhow::bail!("Refused inconsistent candles of {} {}: the one at {} has prices outside its range or a negative volume", symbol, timeframe, bad.timestamp);
+        }
+        // Candles the file refused are not added at all, so memory never holds what a restart would lose.
+        if let Some(file) = self.file.as_mut().filter(|_| !new_candles.is_empty()) {
+            file.save(symbol, timeframe, &new_candles)?;
+        }
+        let symbol_data = self.data.entry(symbol.to_string()).or_default();
+        let timeframe_data = symbol_data.entry(timeframe).or_default();
 
-        // TODO: Handle merging, sorting, and deduplication if necessary
-        timeframe_data.extend(new_candles);
-        timeframe_data.sort_by_key(|c| c.timestamp);
-        timeframe_data.dedup_by_key(|c| c.timestamp);
+        // A candle with the timestamp of a stored one replaces it (e.g. the still-forming last bar).
+        for candle in &new_candles {
+            // An error only means nobody is subscribed right now.
+            let _ = self.updates.send(CandleUpdate { symbol: symbol.to_string(), timeframe, candle: candle.clone() });
+        }
+        timeframe_data.merge(new_candles);
+        if let Some(file) = &self.file {
+            if timeframe_data.len() > file.recent_candles {
+                // Copied out, so the memory of the older candles is freed.
+                *timeframe_data = CandleSeries::new(timeframe_data[timeframe_data.len() - file.recent_candles..].to_vec());
+            }
+        }
 
         Ok(())
     }
 
-    pub fn get_candles(&self, symbol: &str, timeframe: TimeFrame, from_timestamp: Option<chrono::DateTime<chrono::Utc>>, to_timestamp: Option<chrono::DateTime<chrono::Utc>>) -> Option<Vec<Candle>> {
-        self.data.get(symbol)
-            .and_then(|symbol_data| symbol_data.get(&timeframe))
-            .map(|candles| {
-                candles.iter()
-                    .filter(|c| from_timestamp.map_or(true, |start| c.timestamp >= start))
-                    .filter(|c| to_timestamp.map_or(true, |end| c.timestamp <= end))
-                    .cloned()
-                    .collect()
-            })
+    /// Number of candles in [from, to], `None` if nothing is stored for the symbol and timeframe.
+    /// Counted by the candle file, without reading its candles, if the range reaches past memory.
+    pub fn count_candles(&self, symbol: &str, timeframe: TimeFrame, from_timestamp: Option<DateTime<Utc>>, to_timestamp: Option<DateTime<Utc>>) -> Result<Option<usize>> {
+        let Some(recent) = self.series(symbol, timeframe) else { return Ok(None) };
+        match &self.file {
+            Some(file) if file.reaches_past(symbol, timeframe, recent, from_timestamp) => lock(&file.db).count(symbol, timeframe, from_timestamp, to_timestamp).map(Some),
+            _ => Ok(Some(recent.range(from_timestamp, to_timestamp).len())),
+        }
+    }
+
+    /// The latest stored candle of the series.
+    pub fn last_candle(&self, symbol: &str, timeframe: TimeFrame) -> Option<&Candle> {
+        self.series(symbol, timeframe)?.last()
+    }
+
+    /// At most `max` candles in [from, to], after skipping the first `skip` of them.
+    /// Series are kept sorted, so a page costs a binary search plus the candles it copies,
+    /// however long the series is. A page reaching past memory is read alone from the candle file,
+    /// and not cached: a stream pages through a range once.
+    pub fn get_candles_page(
+        &self,
+        symbol: &str,
+        timeframe: TimeFrame,
+        from_timestamp: Option<DateTime<Utc>>,
+        to_timestamp: Option<DateTime<Utc>>,
+        skip: usize,
+        max: usize,
+    ) -> Result<Option<Vec<Candle>>> {
+        let Some(recent) = self.series(symbol, timeframe) else { return Ok(None) };
+        match &self.file {
+            Some(file) if file.reaches_past(symbol, timeframe, recent, from_timestamp) => {
+                lock(&file.db).page(symbol, timeframe, from_timestamp, to_timestamp, skip, max).map(Some)
+            }
+            _ => Ok(Some(recent.range(from_timestamp, to_timestamp).iter().skip(skip).take(max).cloned().collect())),
+        }
+    }
+
+    /// The last `max` candles at or before `to`, oldest first; none if nothing is stored for the
+    /// symbol and timeframe. The candle file is only read if memory holds fewer than `max` of them.
+    pub fn last_candles(&self, symbol: &str, timeframe: TimeFrame, to_timestamp: Option<DateTime<Utc>>, max: usize) -> Result<Vec<Candle>> {
+        let Some(recent) = self.series(symbol, timeframe) else { return Ok(Vec::new()) };
+        let candles = match &self.file {
+            Some(file) if recent.range(None, to_timestamp).len() < max && file.reaches_past(symbol, timeframe, recent, None) => {
+                file.range(symbol, timeframe, None, to_timestamp)?
+            }
+            _ => recent.slice(None, to_timestamp),
+        };
+        Ok(candles[candles.len().saturating_sub(max)..].to_vec())
+    }
+
+    /// The bar that was current at `timestamp`: the latest one at or before it.
+    pub fn candle_at(&self, symbol: &str, timeframe: TimeFrame, timestamp: DateTime<Utc>) -> Result<Option<Candle>> {
+        let Some(recent) = self.series(symbol, timeframe) else { return Ok(None) };
+        match (recent.at_or_before(timestamp), &self.file) {
+            (Some(candle), _) => Ok(Some(candle.clone())),
+            (None, Some(file)) if file.reaches_past(symbol, timeframe, recent, Some(timestamp)) => lock(&file.db).at_or_before(symbol, timeframe, timestamp),
+            (None, _) => Ok(None),
+        }
+    }
+
+    /// Missing bars in [from, to]: trading days without a daily bar, or intraday bars missing
+    /// between two bars of the same day. Nights, weekends and holidays are not gaps.
+    pub fn gaps(&self, symbol: &str, timeframe: TimeFrame, from_timestamp: Option<DateTime<Utc>>, to_timestamp: Option<DateTime<Utc>>) -> Result<Option<Vec<Gap>>> {
+        let bar_seconds = timeframe.duration().num_seconds();
+        let Some(candles) = self.get_candles(symbol, timeframe, from_timestamp, to_timestamp)? else { return Ok(None) };
+        Ok(Some(candles.windows(2).filter_map(|pair| {
+            let (after, before) = (pair[0].timestamp, pair[1].timestamp);
+            let missing = if timeframe == TimeFrame::Day1 {
+                trading_days_between(after.date_naive(), before.date_naive()).saturating_sub(1)
+            } else if after.date_naive() == before.date_naive() {
+                ((before - after).num_seconds() / bar_seconds - 1).max(0) as u32
+            } else {
+                0
+            };
+            (missing > 0).then_some(Gap { after, before, missing })
+        }).collect()))
+    }
+
+    /// Timeframes stored for `symbol`.
+    pub fn timeframes(&self, symbol: &str) -> Vec<TimeFrame> {
+        self.data.get(symbol).map(|symbol_data| symbol_data.keys().copied().collect()).unwrap_or_default()
+    }
+
+    /// Every stored series, in no particular order; with a candle file, as the file holds it.
+    pub fn summaries(&self) -> Vec<SeriesSummary> {
+        self.data.iter()
+            .flat_map(|(symbol, symbol_data)| symbol_data.iter().map(move |(timeframe, candles)| {
+                let stored = self.file.as_ref().and_then(|file| file.stored.get(&(symbol.clone(), *timeframe)));
+                SeriesSummary {
+                    symbol: symbol.clone(),
+                    timeframe: *timeframe,
+                    candles: stored.map_or(candles.len(), |stored| stored.candles),
+                    first: stored.map_or(candles.first().map(|c| c.timestamp), |stored| stored.first),
+                    last: candles.last().map(|c| c.timestamp),
+                }
+            }))
+            .collect()
+    }
+
+    fn series(&self, symbol: &str, timeframe: TimeFrame) -> Option<&CandleSeries> {
+        self.data.get(symbol).and_then(|symbol_data| symbol_data.get(&timeframe))
+    }
+
+    /// Candles added from now on, for every symbol and timeframe.
+    pub fn subscribe(&self) -> broadcast::Receiver<CandleUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// The candles in [from, to] as a snapshot sharing the stored candles: nothing is copied, and
+    /// candles added later do not change it. `None` if nothing is stored for the symbol and timeframe.
+    /// The bounds are found by binary search; a range reaching past the candles kept in memory is
+    /// read from the candle file, which is an error if the file cannot be read.
+    pub fn get_candles(&self, symbol: &str, timeframe: TimeFrame, from_timestamp: Option<chrono::DateTime<chrono::Utc>>, to_timestamp: Option<chrono::DateTime<chrono::Utc>>) -> Result<Option<CandleSeries>> {
+        let Some(recent) = self.series(symbol, timeframe) else { return Ok(None) };
+        match &self.file {
+            Some(file) if file.reaches_past(symbol, timeframe, recent, from_timestamp) => file.range(symbol, timeframe, from_timestamp, to_timestamp).map(Some),
+            _ => Ok(Some(recent.slice(from_timestamp, to_timestamp))),
+        }
     }
 
     // Other methods for managing and accessing market data...
@@ -49,3 +310,137 @@ impl Default for MarketDataStore {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::to_price;
+    use chrono::{TimeZone, Utc};
+
+    fn candle(day: u32, close: f64) -> Candle {
+        Candle { symbol: "TEST".to_string(), timestamp: Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap(), open: to_price(close), high: to_price(close), low: to_price(close), close: to_price(close), volume: 1.0, trades: 1 }
+    }
+
+    #[test]
+    fn test_added_candles_replace_and_are_broadcast() {
+        let mut store = MarketDataStore::new();
+        store.add_candles("TEST", TimeFrame::Day1, vec![candle(4, 10.0), candle(5, 11.0)]).unwrap();
+        let mut rx = store.subscribe();
+        store.add_candles("TEST", TimeFrame::Day1, vec![candle(5, 12.0)]).unwrap();
+
+        let closes: Vec<f64> = store.get_candles("TEST", TimeFrame::Day1, None, None).unwrap().unwrap().iter().map(|c| c.close_f64()).collect();
+        assert_eq!(closes, vec![10.0, 12.0]);
+        let update = rx.try_recv().unwrap();
+        assert_eq!((update.symbol.as_str(), update.timeframe, update.candle.close_f64()), ("TEST", TimeFrame::Day1, 12.0));
+        assert!(rx.try_recv().is_err());
+
+        // Inconsistent candles are refused, none of the batch added.
+        let inverted = Candle { high: to_price(9.0), ..candle(6, 10.0) };
+        assert!(store.add_candles("TEST", TimeFrame::Day1, vec![candle(6, 13.0), inverted]).is_err());
+        assert_eq!(store.count_candles("TEST", TimeFrame::Day1, None, None).unwrap(), Some(2));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_pages_of_a_range() {
+        let mut store = MarketDataStore::new();
+        store.add_candles("TEST", TimeFrame::Day1, (1..=9).map(|day| candle(day, day as f64)).collect()).unwrap();
+        let from = Some(Utc.with_ymd_and_hms(2024, 3, 3, 0, 0, 0).unwrap());
+        let to = Some(Utc.with_ymd_and_hms(2024, 3, 7, 12, 0, 0).unwrap());
+
+        assert_eq!(store.count_candles("TEST", TimeFrame::Day1, from, to).unwrap(), Some(5));
+        let page: Vec<f64> = store.get_candles_page("TEST", TimeFrame::Day1, from, to, 1, 3).unwrap().unwrap().iter().map(|c| c.close_f64()).collect();
+        assert_eq!(page, vec![4.0, 5.0, 6.0]);
+        assert!(store.get_candles_page("TEST", TimeFrame::Day1, from, to, 10, 3).unwrap().unwrap().is_empty());
+        assert_eq!(store.count_candles("TEST", TimeFrame::Day1, to, from).unwrap(), Some(0));
+        assert_eq!(store.count_candles("TEST", TimeFrame::Hour1, None, None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_gaps_skip_nights_and_holidays() {
+        let mut store = MarketDataStore::new();
+        // Friday 1 and Monday 4 are a weekend apart; Wednesday 6 is missing.
+        store.add_candles("TEST", TimeFrame::Day1, [1, 4, 5, 7].map(|day| candle(day, 10.0)).to_vec()).unwrap();
+        let at = |day, hour, minute| Utc.with_ymd_and_hms(2024, 3, day, hour, minute, 0).unwrap();
+        let minutes = [at(4, 10, 0), at(4, 10, 5), at(4, 10, 20), at(5, 10, 0)].map(|timestamp| Candle { timestamp, ..candle(1, 10.0) });
+        store.add_candles("TEST", TimeFrame::Minute5, minutes.to_vec()).unwrap();
+
+        assert_eq!(store.gaps("TEST", TimeFrame::Day1, None, None).unwrap(), Some(vec![Gap { after: at(5, 0, 0), before: at(7, 0, 0), missing: 1 }]));
+        assert_eq!(store.gaps("TEST", TimeFrame::Minute5, None, None).unwrap(), Some(vec![Gap { after: at(4, 10, 5), before: at(4, 10, 20), missing: 2 }]));
+        assert_eq!(store.gaps("TEST", TimeFrame::Day1, Some(at(6, 0, 0)), None).unwrap(), Some(vec![]));
+        assert_eq!(store.candle_at("TEST", TimeFrame::Minute5, at(4, 10, 19)).unwrap().map(|c| c.timestamp), Some(at(4, 10, 5)));
+        assert_eq!(store.gaps("TEST", TimeFrame::Hour1, None, None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_range_bounds_are_inclusive_and_exact() {
+        let mut store = MarketDataStore::new();
+        store.add_candles("TEST", TimeFrame::Day1, (1..=9).map(|day| candle(day, day as f64)).collect()).unwrap();
+        let day = |day| Some(Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap());
+        let closes = |from, to| -> Vec<f64> { store.get_candles("TEST", TimeFrame::Day1, from, to).unwrap().unwrap().iter().map(|c| c.close_f64()).collect() };
+
+        assert_eq!(closes(day(3), day(5)), vec![3.0, 4.0, 5.0]);
+        assert_eq!(closes(None, day(2)), vec![1.0, 2.0]);
+        assert_eq!(closes(day(8), None), vec![8.0, 9.0]);
+        assert!(closes(day(5), day(4)).is_empty());
+        assert_eq!(store.last_candle("TEST", TimeFrame::Day1).map(|c| c.close_f64()), Some(9.0));
+    }
+
+    fn candle_file(dir: &tempfile::TempDir) -> CandleStoreSettings {
+        CandleStoreSettings { file: dir.path().join("candles.db").display().to_string(), recent_candles: 3, cached_ranges: 2 }
+    }
+
+    #[test]
+    fn test_older_ranges_are_read_from_the_candle_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = candle_file(&dir);
+        let mut store = MarketDataStore::open(&settings).unwrap();
+        store.add_candles("TEST", TimeFrame::Day1, (1..=9).map(|day| candle(day, day as f64)).collect()).unwrap();
+        let day = |day| Some(Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap());
+        let closes = |store: &MarketDataStore, from, to| -> Vec<f64> { store.get_candles("TEST", TimeFrame::Day1, from, to).unwrap().unwrap().iter().map(|c| c.close_f64()).collect() };
+        let cached = |store: &MarketDataStore| lock(&store.file.as_ref().unwrap().ranges).len();
+
+        // Only the latest candles stay in memory; older ones come from the file, whole.
+        assert_eq!(store.series("TEST", TimeFrame::Day1).unwrap().len(), 3);
+        assert_eq!(closes(&store, day(8), None), vec![8.0, 9.0]);
+        assert_eq!(cached(&store), 0);
+        assert_eq!(closes(&store, None, None), (1..=9).map(f64::from).collect::<Vec<_>>());
+        assert_eq!(closes(&store, day(2), day(4)), vec![2.0, 3.0, 4.0]);
+        assert_eq!(closes(&store, day(1), day(1)), vec![1.0]);
+        assert_eq!(cached(&store), 2); // The least recently used range was dropped
+        assert_eq!(store.count_candles("TEST", TimeFrame::Day1, None, None).unwrap(), Some(9));
+        // Pages are read one at a time, resuming after the last candle sent, and are not cached.
+        let page = |from, skip| -> Vec<f64> { store.get_candles_page("TEST", TimeFrame::Day1, from, day(8), skip, 3).unwrap().unwrap().iter().map(|c| c.close_f64()).collect() };
+        assert_eq!(page(day(1), 1), vec![2.0, 3.0, 4.0]);
+        assert_eq!(page(Some(day(4).unwrap() + chrono::Duration::nanoseconds(1)), 0), vec![5.0, 6.0, 7.0]);
+        assert_eq!(page(day(7), 1), vec![8.0]);
+        assert_eq!(store.count_candles("TEST", TimeFrame::Day1, day(2), day(8)).unwrap(), Some(7));
+        assert_eq!(cached(&store), 2);
+        assert_eq!(store.candle_at("TEST", TimeFrame::Day1, day(5).unwrap() + chrono::Duration::hours(12)).unwrap().map(|c| c.close_f64()), Some(5.0));
+        let last = |to, max| -> Vec<f64> { store.last_candles("TEST", TimeFrame::Day1, to, max).unwrap().iter().map(|c| c.close_f64()).collect() };
+        assert_eq!((last(None, 2), last(None, 4), last(day(3), 2)), (vec![8.0, 9.0], vec![6.0, 7.0, 8.0, 9.0], vec![2.0, 3.0]));
+        let summary = &store.summaries()[0];
+        assert_eq!((summary.candles, summary.first, summary.last), (9, day(1), day(9)));
+
+        // A replaced old candle is not served from a stale cached range.
+        store.add_candles("TEST", TimeFrame::Day1, vec![candle(2, 20.0)]).unwrap();
+        assert_eq!(cached(&store), 0);
+        assert_eq!(closes(&store, day(2), day(4)), vec![20.0, 3.0, 4.0]);
+
+        // A restart finds every candle, prices exactly as added.
+        drop(store);
+        let store = MarketDataStore::open(&settings).unwrap();
+        assert_eq!(store.series("TEST", TimeFrame::Day1).unwrap().len(), 3);
+        assert_eq!(closes(&store, None, None).len(), 9);
+        assert_eq!(store.last_candle("TEST", TimeFrame::Day1).map(|c| c.close), Some(to_price(9.0)));
+        assert_eq!(store.summaries()[0].candles, 9);
+    }
+
+    #[test]
+    fn test_an_unusable_candle_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = CandleStoreSettings { file: dir.path().display().to_string(), ..candle_file(&dir) };
+        assert!(MarketDataStore::open(&settings).is_err());
+        assert!(MarketDataStore::open(&CandleStoreSettings { cached_ranges: 0, ..candle_file(&dir) }).is_err());
+    }
+}
@@ -1,5 +1,8 @@
 // Data handling module for the engine
+pub mod calendar;
+pub mod candle_db;
 pub mod csv_parser;
+pub mod export;
 pub mod market_data;
-// Potentially a module for brazilian_format utilities if not kept within csv_parser
-// pub mod brazilian_format;
+pub mod resample;
+// Brazilian number and date formats are in shared::utils::brazilian_format
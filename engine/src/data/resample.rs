@@ -0,0 +1,124 @@
+// Candle resampling
+// Aggregates candles into a longer timeframe along B3 sessions: intraday bars start at
+// the session open rather than on the clock hour, and daily bars are trading days.
+use chrono::{DateTime, Duration, Utc};
+use shared::models::{Candle, CandleSeries, TimeFrame};
+
+use super::calendar::{is_trading_day, session, SessionPhase};
+
+/// Length of a bar of `timeframe`; `None` for daily bars, which last one session.
+pub fn bar_duration(timeframe: TimeFrame) -> Option<Duration> {
+    (timeframe != TimeFrame::Day1).then(|| timeframe.duration())
+}
+
+/// Whether bars of `source` aggregate exactly into bars of `target`: each target bar must be a whole
+/// number of source bars. Any intraday series aggregates into daily bars.
+pub fn aggregates_into(source: TimeFrame, target: TimeFrame) -> bool {
+    match (bar_duration(source), bar_duration(target)) {
+        (_, None) => true,
+        (Some(source), Some(target)) => target.num_seconds() % source.num_seconds() == 0,
+        (None, Some(_)) => false,
+    }
+}
+
+/// Start of the `timeframe` bar that `candle` falls in, `None` if B3 was not trading then.
+/// Opening-auction prints belong to the first bar of the session.
+pub fn bar_start(candle: &Candle, timeframe: TimeFrame) -> Option<DateTime<Utc>> {
+    let date = candle.timestamp.date_naive();
+    let Some(duration) = bar_duration(timeframe) else {
+        // Daily bars keep whatever time the source used (exports often stamp them after the close).
+        return is_trading_day(date).then_some(candle.timestamp);
+    };
+    let session = session(&candle.symbol, date)?;
+    if session.phase_at(candle.timestamp.time()) == SessionPhase::Closed {
+        return None;
+    }
+    let elapsed = (candle.timestamp - session.open_at()).max(Duration::zero());
+    let bars = elapsed.num_seconds() / duration.num_seconds();
+    Some(session.open_at() + duration * bars as i32)
+}
+
+/// Aggregates chronological `candles` of one symbol into `timeframe` bars, each stamped
+/// with its start (daily bars with the first candle of the day). Candles outside a session,
+/// e.g. on holidays or after the close, are left out.
+pub fn resample(candles: &[Candle], timeframe: TimeFrame) -> CandleSeries {
+    let mut bars: Vec<Candle> = Vec::new();
+    let mut current: Option<DateTime<Utc>> = None; // Bucket of the last bar
+    let mut skipped = 0;
+    for candle in candles {
+        let Some(start) = bar_start(candle, timeframe) else {
+            skipped += 1;
+            continue;
+        };
+        let bucket = match bar_duration(timeframe) {
+            Some(_) => start,
+            None => TimeFrame::Day1.align(start),
+        };
+        match bars.last_mut() {
+            Some(bar) if current == Some(bucket) => bar.merge(candle),
+            _ => {
+                current = Some(bucket);
+                bars.push(Candle { timestamp: start, ..candle.clone() });
+            }
+        }
+    }
+    if skipped > 0 {
+        tracing::debug!(skipped, %timeframe, "Resampling left out candles outside B3 sessions");
+    }
+    CandleSeries::new(bars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::to_price;
+    use chrono::TimeZone;
+
+    fn candle(symbol: &str, ts: DateTime<Utc>, open: f64, close: f64) -> Candle {
+        Candle { symbol: symbol.to_string(), timestamp: ts, open: to_price(open), high: to_price(open.max(close)), low: to_price(open.min(close)), close: to_price(close), volume: 10.0, trades: 1 }
+    }
+
+    #[test]
+    fn test_hourly_bars_align_to_the_session_open() {
+        let at = |h, m| Utc.with_ymd_and_hms(2024, 11, 26, h, m, 0).unwrap();
+        let candles = vec![
+            candle("PETR4", at(9, 55), 37.0, 37.2),  // Opening auction: first bar
+            candle("PETR4", at(10, 30), 37.2, 37.5),
+            candle("PETR4", at(11, 0), 37.5, 37.1),  // Second bar
+            candle("PETR4", at(18, 30), 37.1, 36.0), // After the close: left out
+        ];
+        let bars = resample(&candles, TimeFrame::Hour1);
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].timestamp, at(10, 0));
+        assert_eq!((bars[0].open, bars[0].high, bars[0].close, bars[0].volume), (to_price(37.0), to_price(37.5), to_price(37.5), 20.0));
+        assert_eq!(bars[1].timestamp, at(11, 0));
+
+        // Mini index futures open at 09:00.
+        let win = resample(&[candle("WINZ24", at(9, 20), 1.0, 2.0)], TimeFrame::Minute15);
+        assert_eq!(win[0].timestamp, at(9, 15));
+    }
+
+    #[test]
+    fn test_aggregates_into() {
+        assert!(aggregates_into(TimeFrame::Minute5, TimeFrame::Hour1));
+        assert!(aggregates_into(TimeFrame::Minute15, TimeFrame::Minute15));
+        assert!(aggregates_into(TimeFrame::Hour1, TimeFrame::Day1));
+        assert!(!aggregates_into(TimeFrame::Hour1, TimeFrame::Minute30));
+        assert!(!aggregates_into(TimeFrame::Day1, TimeFrame::Hour1));
+    }
+
+    #[test]
+    fn test_daily_bars_skip_holidays() {
+        let day = |d, h| Utc.with_ymd_and_hms(2024, 12, d, h, 0, 0).unwrap();
+        let candles = vec![
+            candle("PETR4", day(23, 10), 36.0, 36.5),
+            candle("PETR4", day(23, 15), 36.5, 37.0),
+            candle("PETR4", day(24, 10), 37.0, 40.0), // Christmas Eve: B3 is closed
+            candle("PETR4", day(26, 10), 37.0, 37.3),
+        ];
+        let bars = resample(&candles, TimeFrame::Day1);
+        assert_eq!(bars.len(), 2);
+        assert_eq!((bars[0].timestamp, bars[0].close, bars[0].high), (day(23, 10), to_price(37.0), to_price(37.0)));
+        assert_eq!(bars[1].timestamp, day(26, 10));
+    }
+}
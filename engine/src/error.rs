@@ -1,4 +1,8 @@
+use shared::errors::ErrorCode;
 use thiserror::Error;
+use tonic::Code;
+
+use crate::services::error_details::ErrorDetails;
 
 #[derive(Error, Debug)]
 pub enum EngineError {
@@ -20,6 +24,10 @@ pub enum EngineError {
     #[error("CSV data format error: {0}")]
     CsvDataFormatError(String),
 
+    /// A bad value in one record of a CSV file; `line` counts the header as line 1.
+    #[error("CSV data format error: {message} at line {line}")]
+    CsvRecordError { line: usize, column: String, message: String },
+
     #[error("Market data store error: {0}")]
     MarketDataError(String),
 
@@ -29,6 +37,16 @@ pub enum EngineError {
     #[error("Trade simulation error: {0}")]
     SimulationError(String),
 
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// An invalid request `field`, e.g. "timeframe", reported to clients as a field violation.
+    #[error("Invalid request: {message}")]
+    InvalidField { field: String, message: String },
+
+    #[error("Deadline exceeded: {0}")]
+    DeadlineExceeded(String),
+
     // This can be used to wrap errors from anyhow if they don't fit other categories
     // or if a function using anyhow needs to return EngineError.
     #[error("Internal processing error: {0}")]
@@ -42,23 +60,52 @@ pub enum EngineError {
 impl From<EngineError> for tonic::Status {
     fn from(err: EngineError) -> Self {
         tracing::error!("Mapping EngineError to tonic::Status: {:?}", err); // Log the error source
-        match err {
-            EngineError::ConfigError(msg) => tonic::Status::failed_precondition(format!("Configuration error: {}", msg)),
-            EngineError::CsvSystemError { source } => tonic::Status::invalid_argument(format!("CSV parsing system error: {}", source)),
-            EngineError::IoError { source } => tonic::Status::internal(format!("I/O error: {}", source)),
-            EngineError::CsvDataFormatError(msg) => tonic::Status::invalid_argument(format!("CSV data format error: {}", msg)),
+        // Besides the message, clients get the reason and specifics as google.rpc error details.
+        let (code, message, details) = match err {
+            EngineError::ConfigError(msg) => (Code::FailedPrecondition, format!("Configuration error: {}", msg), ErrorDetails::new(ErrorCode::Config)),
+            EngineError::CsvSystemError { source } => {
+                let mut details = ErrorDetails::new(ErrorCode::CsvFile);
+                if let Some(position) = source.position() {
+                    details = details.with_metadata("line", position.line());
+                }
+                (Code::InvalidArgument, format!("CSV parsing system error: {}", source), details)
+            }
+            EngineError::IoError { source } if source.kind() == std::io::ErrorKind::NotFound => {
+                (Code::NotFound, format!("I/O error: {}", source), ErrorDetails::new(ErrorCode::FileNotFound))
+            }
+            EngineError::IoError { source } => (Code::Internal, format!("I/O error: {}", source), ErrorDetails::new(ErrorCode::Io)),
+            EngineError::CsvDataFormatError(msg) => (Code::InvalidArgument, format!("CSV data format error: {}", msg), ErrorDetails::new(ErrorCode::CsvDataFormat)),
+            EngineError::CsvRecordError { line, column, message } => (
+                Code::InvalidArgument,
+                format!("CSV data format error: {} at line {}", message, line),
+                ErrorDetails::new(ErrorCode::CsvDataFormat).with_metadata("line", line).with_metadata("column", column),
+            ),
 
             EngineError::MarketDataError(msg) => {
                 if msg.to_lowercase().contains("not found") {
-                    tonic::Status::not_found(msg)
+                    (Code::NotFound, msg, ErrorDetails::new(ErrorCode::MarketDataNotFound))
+                } else {
+                    (Code::Internal, format!("Market data error: {}", msg), ErrorDetails::new(ErrorCode::MarketData))
+                }
+            }
+            EngineError::IndicatorError(msg) => (Code::Internal, format!("Indicator calculation error: {}", msg), ErrorDetails::new(ErrorCode::Indicator)),
+            EngineError::SimulationError(msg) => {
+                if msg.to_lowercase().contains("not found") {
+                    (Code::NotFound, msg, ErrorDetails::new(ErrorCode::SimulationNotFound))
                 } else {
-                    tonic::Status::internal(format!("Market data error: {}", msg))
+                    (Code::Internal, format!("Trade simulation error: {}", msg), ErrorDetails::new(ErrorCode::Simulation))
                 }
             }
-            EngineError::IndicatorError(msg) => tonic::Status::internal(format!("Indicator calculation error: {}", msg)),
-            EngineError::SimulationError(msg) => tonic::Status::internal(format!("Trade simulation error: {}", msg)),
-            EngineError::ProcessingError(msg) => tonic::Status::internal(format!("Processing error: {}", msg)),
-            EngineError::AnyhowError(source) => tonic::Status::internal(format!("An internal error occurred: {}", source)),
-        }
+            EngineError::InvalidRequest(msg) => (Code::InvalidArgument, format!("Invalid request: {}", msg), ErrorDetails::new(ErrorCode::InvalidRequest)),
+            EngineError::InvalidField { field, message } => (
+                Code::InvalidArgument,
+                format!("Invalid request: {}", message),
+                ErrorDetails::new(ErrorCode::InvalidRequest).with_field_violation(field, message),
+            ),
+            EngineError::DeadlineExceeded(msg) => (Code::DeadlineExceeded, msg, ErrorDetails::new(ErrorCode::DeadlineExceeded)),
+            EngineError::ProcessingError(msg) => (Code::Internal, format!("Processing error: {}", msg), ErrorDetails::new(ErrorCode::Processing)),
+            EngineError::AnyhowError(source) => (Code::Internal, format!("An internal error occurred: {}", source), ErrorDetails::new(ErrorCode::Internal)),
+        };
+        details.into_status(code, message)
     }
 }
@@ -0,0 +1,90 @@
+// Average True Range (ATR) indicator implementation (Wilder's smoothing)
+use super::IndicatorCalculator;
+use crate::error::EngineError;
+use shared::models::ColumnSlices;
+use serde_json::Value;
+
+pub struct Atr {
+    name: String,
+    period: usize,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Result<Self, EngineError> {
+        if period == 0 {
+            return Err(EngineError::IndicatorError("ATR period must be greater than 0".to_string()));
+        }
+        Ok(Self {
+            name: format!("ATR({})", period),
+            period,
+        })
+    }
+}
+
+impl IndicatorCalculator for Atr {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({ "period": self.period })
+    }
+
+    fn calculate_columns(&self, data: ColumnSlices<'_>) -> Vec<f64> {
+        if data.len() < self.period {
+            return vec![f64::NAN; data.len()];
+        }
+
+        // True range: the bar's range extended to the previous close (gaps count as range).
+        let true_ranges: Vec<f64> = (0..data.len()).map(|i| {
+            let (high, low) = (data.high[i], data.low[i]);
+            if i == 0 {
+                high - low
+            } else {
+                let prev_close = data.close[i - 1];
+                (high - low).max((high - prev_close).abs()).max((low - prev_close).abs())
+            }
+        }).collect();
+
+        let mut results = vec![f64::NAN; self.period - 1];
+        let mut atr = true_ranges.iter().take(self.period).sum::<f64>() / self.period as f64;
+        results.push(atr);
+        for tr in &true_ranges[self.period..] {
+            atr = (atr * (self.period - 1) as f64 + tr) / self.period as f64;
+            results.push(atr);
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::{to_price, Candle};
+    use chrono::Utc;
+
+    fn create_candle(high: f64, low: f64, close: f64) -> Candle {
+        Candle {
+            symbol: "TEST".to_string(),
+            timestamp: Utc::now(),
+            open: to_price(close), high: to_price(high), low: to_price(low), close: to_price(close),
+            volume: 0.0, trades: 0,
+        }
+    }
+
+    #[test]
+    fn test_atr_calculation() {
+        let candles = vec![
+            create_candle(10.0, 8.0, 9.0),   // TR 2
+            create_candle(11.0, 9.0, 10.0),  // TR 2
+            create_candle(14.0, 12.0, 13.0), // TR 4 (gap from 10)
+            create_candle(13.0, 12.0, 12.5), // TR 1
+        ];
+        let atr = Atr::new(2).unwrap().calculate(&candles);
+        assert!(atr[0].is_nan());
+        assert!((atr[1] - 2.0).abs() < 1e-9);
+        assert!((atr[2] - 3.0).abs() < 1e-9);
+        assert!((atr[3] - 2.0).abs() < 1e-9);
+        assert!(Atr::new(5).unwrap().calculate(&candles).iter().all(|v| v.is_nan()));
+    }
+}
@@ -1,6 +1,7 @@
 // Exponential Moving Average (EMA) indicator implementation
 use super::IndicatorCalculator;
-use shared::models::Candle;
+use crate::error::EngineError;
+use shared::models::ColumnSlices;
 use serde_json::Value;
 
 pub struct Ema {
@@ -9,14 +10,14 @@ pub struct Ema {
 }
 
 impl Ema {
-    pub fn new(period: usize) -> Self {
+    pub fn new(period: usize) -> Result<Self, EngineError> {
         if period == 0 {
-            panic!("EMA period must be greater than 0");
+            return Err(EngineError::IndicatorError("EMA period must be greater than 0".to_string()));
         }
-        Self {
+        Ok(Self {
             name: format!("EMA({})", period),
             period,
-        }
+        })
     }
 }
 
@@ -29,7 +30,7 @@ impl IndicatorCalculator for Ema {
         serde_json::json!({ "period": self.period })
     }
 
-    fn calculate(&self, data: &[Candle]) -> Vec<f64> {
+    fn calculate_columns(&self, data: ColumnSlices<'_>) -> Vec<f64> {
         if self.period == 0 { // Should be caught by new()
              return vec![f64::NAN; data.len()];
         }
@@ -44,12 +45,12 @@ impl IndicatorCalculator for Ema {
         let multiplier = 2.0 / (self.period as f64 + 1.0);
 
         // Calculate initial SMA for the first EMA value
-        let initial_sum: f64 = data.iter().take(self.period).map(|c| c.close).sum();
+        let initial_sum: f64 = data.close[..self.period].iter().sum();
         let mut previous_ema = initial_sum / self.period as f64;
         results.push(previous_ema);
 
-        for candle in data.iter().skip(self.period) {
-            let ema = (candle.close - previous_ema) * multiplier + previous_ema;
+        for close in &data.close[self.period..] {
+            let ema = (close - previous_ema) * multiplier + previous_ema;
             results.push(ema);
             previous_ema = ema;
         }
@@ -60,13 +61,14 @@ impl IndicatorCalculator for Ema {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::models::{to_price, Candle};
     use chrono::Utc;
 
     fn create_candle(close: f64) -> Candle {
         Candle {
             symbol: "TEST".to_string(),
             timestamp: Utc::now(),
-            open: close, high: close, low: close, close,
+            open: to_price(close), high: to_price(close), low: to_price(close), close: to_price(close),
             volume: 0.0, trades: 0,
         }
     }
@@ -89,7 +91,7 @@ mod tests {
             create_candle(13.0), // EMA = (13-11)*0.5 + 11 = 1+11 = 12.0
             create_candle(14.0), // EMA = (14-12)*0.5 + 12 = 1+12 = 13.0
         ];
-        let ema = Ema::new(3); // Period 3
+        let ema = Ema::new(3).unwrap(); // Period 3
         let results = ema.calculate(&candles);
 
         let expected = vec![f64::NAN, f64::NAN, 11.0, 12.0, 13.0];
@@ -99,7 +101,7 @@ mod tests {
     #[test]
     fn test_ema_insufficient_data() {
         let candles = vec![create_candle(1.0), create_candle(2.0)];
-        let ema = Ema::new(3);
+        let ema = Ema::new(3).unwrap();
         let results = ema.calculate(&candles);
         assert_f64_vec_eq(&results, &[f64::NAN, f64::NAN]);
     }
@@ -109,7 +111,7 @@ mod tests {
         let candles = vec![
             create_candle(10.0), create_candle(11.0), create_candle(12.0),
         ];
-        let ema = Ema::new(1);
+        let ema = Ema::new(1).unwrap();
         let results = ema.calculate(&candles);
         // EMA(1) is just the close price. Initial SMA(1) is data[0].close.
         // results[0] = 10.0
@@ -121,15 +123,14 @@ mod tests {
     #[test]
     fn test_ema_empty_data() {
         let candles: Vec<Candle> = Vec::new();
-        let ema = Ema::new(3);
+        let ema = Ema::new(3).unwrap();
         let results = ema.calculate(&candles);
         assert_f64_vec_eq(&results, &[]);
     }
 
     #[test]
-    #[should_panic(expected = "EMA period must be greater than 0")]
-    fn test_ema_period_zero_panic() {
-        Ema::new(0);
+    fn test_ema_period_zero_is_an_error() {
+        assert!(matches!(Ema::new(0), Err(EngineError::IndicatorError(_))));
     }
 
     // Test from a known source: https://www.iexplain.org/ema-calculation/
@@ -147,7 +148,7 @@ mod tests {
             create_candle(22.32), // 11th candle
             // create_candle(22.50), // 12th candle
         ];
-        let ema_calculator = Ema::new(10);
+        let ema_calculator = Ema::new(10).unwrap();
         let results = ema_calculator.calculate(&candles);
 
         let mut expected_results = vec![f64::NAN; 9];
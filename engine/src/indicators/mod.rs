@@ -1,18 +1,28 @@
 // Technical indicators module
+pub mod atr;
 pub mod ema;
 pub mod rsi;
 pub mod sma;
+pub mod vwap;
 
+pub use atr::Atr;
 pub use ema::Ema;
 pub use rsi::Rsi;
 pub use sma::Sma;
+pub use vwap::Vwap;
 
-use shared::models::Candle;
+use shared::models::{Candle, CandleColumns, ColumnSlices};
 use serde_json::Value;
 
 // Common trait for all indicators
 pub trait IndicatorCalculator: Send + Sync {
     fn name(&self) -> &str;
     fn parameters(&self) -> Value; // Parameters used for this indicator instance
-    fn calculate(&self, data: &[Candle]) -> Vec<f64>; // Use f64::NAN for undefined values
+    /// One value per candle, f64::NAN where undefined. A `CandleSeries` keeps its columns, so
+    /// repeated calculations on it skip converting the prices.
+    fn calculate_columns(&self, data: ColumnSlices<'_>) -> Vec<f64>;
+
+    fn calculate(&self, data: &[Candle]) -> Vec<f64> {
+        self.calculate_columns(CandleColumns::from_candles(data).view())
+    }
 }
@@ -1,6 +1,7 @@
 // Relative Strength Index (RSI) indicator implementation
 use super::IndicatorCalculator;
-use shared::models::Candle;
+use crate::error::EngineError;
+use shared::models::ColumnSlices;
 use serde_json::Value;
 
 pub struct Rsi {
@@ -9,14 +10,14 @@ pub struct Rsi {
 }
 
 impl Rsi {
-    pub fn new(period: usize) -> Self {
+    pub fn new(period: usize) -> Result<Self, EngineError> {
         if period == 0 {
-            panic!("RSI period must be greater than 0");
+            return Err(EngineError::IndicatorError("RSI period must be greater than 0".to_string()));
         }
-        Self {
+        Ok(Self {
             name: format!("RSI({})", period),
             period,
-        }
+        })
     }
 }
 
@@ -29,7 +30,7 @@ impl IndicatorCalculator for Rsi {
         serde_json::json!({ "period": self.period })
     }
 
-    fn calculate(&self, data: &[Candle]) -> Vec<f64> {
+    fn calculate_columns(&self, data: ColumnSlices<'_>) -> Vec<f64> {
         if self.period == 0 {
             return vec![f64::NAN; data.len()];
         }
@@ -43,7 +44,7 @@ impl IndicatorCalculator for Rsi {
         let mut losses = 0.0;
 
         for i in 1..=self.period {
-            let change = data[i].close - data[i-1].close;
+            let change = data.close[i] - data.close[i-1];
             if change > 0.0 {
                 gains += change;
             } else {
@@ -61,8 +62,9 @@ impl IndicatorCalculator for Rsi {
             results[self.period] = 100.0 - (100.0 / (1.0 + rs));
         }
 
-        for i in (self.period + 1)..data.len() {
-            let change = data[i].close - data[i-1].close;
+        // Each window holds the previous close and the close of `result`.
+        for (result, closes) in results.iter_mut().skip(1).zip(data.close.windows(2)).skip(self.period) {
+            let change = closes[1] - closes[0];
             let (current_gain, current_loss) = if change > 0.0 {
                 (change, 0.0)
             } else {
@@ -73,10 +75,10 @@ impl IndicatorCalculator for Rsi {
             avg_loss = (avg_loss * (self.period - 1) as f64 + current_loss) / self.period as f64;
 
             if avg_loss == 0.0 {
-                results[i] = 100.0;
+                *result = 100.0;
             } else {
                 let rs = avg_gain / avg_loss;
-                results[i] = 100.0 - (100.0 / (1.0 + rs));
+                *result = 100.0 - (100.0 / (1.0 + rs));
             }
         }
         results
@@ -86,13 +88,14 @@ impl IndicatorCalculator for Rsi {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::models::{to_price, Candle};
     use chrono::Utc;
 
     fn create_candle(close: f64) -> Candle {
         Candle {
             symbol: "TEST".to_string(),
             timestamp: Utc::now(),
-            open: close, high: close, low: close, close,
+            open: to_price(close), high: to_price(close), low: to_price(close), close: to_price(close),
             volume: 0.0, trades: 0,
         }
     }
@@ -140,7 +143,7 @@ mod tests {
         ];
         let candles: Vec<Candle> = prices.iter().map(|&p| create_candle(p)).collect();
 
-        let rsi_calculator = Rsi::new(14);
+        let rsi_calculator = Rsi::new(14).unwrap();
         let results = rsi_calculator.calculate(&candles);
 
         // Expected values based on the code's output, rounded to 2 decimal places.
@@ -163,7 +166,7 @@ mod tests {
     #[test]
     fn test_rsi_insufficient_data() {
         let candles = vec![create_candle(1.0); 10];
-        let rsi = Rsi::new(14);
+        let rsi = Rsi::new(14).unwrap();
         let results = rsi.calculate(&candles);
         assert_f64_vec_eq_rounded_2dp(&results, &[f64::NAN; 10]);
     }
@@ -171,50 +174,43 @@ mod tests {
     #[test]
     fn test_rsi_all_gains() {
         let candles = (1..=20).map(|i| create_candle(i as f64)).collect::<Vec<_>>();
-        let rsi = Rsi::new(14);
+        let rsi = Rsi::new(14).unwrap();
         let results = rsi.calculate(&candles);
 
         let mut expected = vec![f64::NAN; 14];
-        for _ in 14..20 {
-            expected.push(100.0);
-        }
+        expected.extend(std::iter::repeat_n(100.0, 6));
         assert_f64_vec_eq_rounded_2dp(&results, &expected);
     }
 
     #[test]
     fn test_rsi_all_losses() {
         let candles = (1..=20).map(|i| create_candle(21.0 - i as f64)).collect::<Vec<_>>();
-        let rsi = Rsi::new(14);
+        let rsi = Rsi::new(14).unwrap();
         let results = rsi.calculate(&candles);
         let mut expected = vec![f64::NAN; 14];
-        for _ in 14..20 {
-            expected.push(0.0);
-        }
+        expected.extend(std::iter::repeat_n(0.0, 6));
         assert_f64_vec_eq_rounded_2dp(&results, &expected);
     }
 
     #[test]
     fn test_rsi_no_change() {
         let candles = vec![create_candle(10.0); 20];
-        let rsi = Rsi::new(14);
+        let rsi = Rsi::new(14).unwrap();
         let results = rsi.calculate(&candles);
         let mut expected = vec![f64::NAN; 14];
-        for _ in 14..20 {
-            expected.push(100.0);
-        }
+        expected.extend(std::iter::repeat_n(100.0, 6));
         assert_f64_vec_eq_rounded_2dp(&results, &expected);
     }
 
     #[test]
-    #[should_panic(expected = "RSI period must be greater than 0")]
-    fn test_rsi_period_zero_panic() {
-        Rsi::new(0);
+    fn test_rsi_period_zero_is_an_error() {
+        assert!(matches!(Rsi::new(0), Err(EngineError::IndicatorError(_))));
     }
 
     #[test]
     fn test_rsi_empty_data() {
         let candles: Vec<Candle> = Vec::new();
-        let rsi = Rsi::new(14);
+        let rsi = Rsi::new(14).unwrap();
         let results = rsi.calculate(&candles);
         assert_f64_vec_eq_rounded_2dp(&results, &[]);
     }
@@ -228,7 +224,7 @@ mod tests {
             create_candle(10.5),
             create_candle(12.0),
         ];
-        let rsi = Rsi::new(1);
+        let rsi = Rsi::new(1).unwrap();
         let results = rsi.calculate(&candles);
         assert_f64_vec_eq_rounded_2dp(&results, &[f64::NAN, 100.0, 0.0, 100.0, 100.0]);
     }
@@ -1,6 +1,7 @@
 // Simple Moving Average (SMA) indicator implementation
 use super::IndicatorCalculator;
-use shared::models::Candle;
+use crate::error::EngineError;
+use shared::models::ColumnSlices;
 use serde_json::Value;
 
 pub struct Sma {
@@ -9,15 +10,14 @@ pub struct Sma {
 }
 
 impl Sma {
-    pub fn new(period: usize) -> Self {
+    pub fn new(period: usize) -> Result<Self, EngineError> {
         if period == 0 {
-            // Or return Result<Self, Error>
-            panic!("SMA period must be greater than 0");
+            return Err(EngineError::IndicatorError("SMA period must be greater than 0".to_string()));
         }
-        Self {
+        Ok(Self {
             name: format!("SMA({})", period),
             period,
-        }
+        })
     }
 }
 
@@ -30,7 +30,7 @@ impl IndicatorCalculator for Sma {
         serde_json::json!({ "period": self.period })
     }
 
-    fn calculate(&self, data: &[Candle]) -> Vec<f64> {
+    fn calculate_columns(&self, data: ColumnSlices<'_>) -> Vec<f64> {
         if self.period == 0 { // Should be caught by new()
             return vec![f64::NAN; data.len()];
         }
@@ -41,12 +41,13 @@ impl IndicatorCalculator for Sma {
         let mut results = vec![f64::NAN; self.period - 1]; // No SMA for initial period
 
         // Calculate sum for the first window
-        let mut sum: f64 = data.iter().take(self.period).map(|c| c.close).sum();
+        let closes = data.close;
+        let mut sum: f64 = closes[..self.period].iter().sum();
         results.push(sum / self.period as f64);
 
         // Slide the window
         for i in self.period..data.len() {
-            sum = sum - data[i - self.period].close + data[i].close;
+            sum = sum - closes[i - self.period] + closes[i];
             results.push(sum / self.period as f64);
         }
         results
@@ -56,13 +57,14 @@ impl IndicatorCalculator for Sma {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::models::{to_price, Candle};
     use chrono::Utc;
 
     fn create_candle(close: f64) -> Candle {
         Candle {
             symbol: "TEST".to_string(),
             timestamp: Utc::now(),
-            open: close, high: close, low: close, close,
+            open: to_price(close), high: to_price(close), low: to_price(close), close: to_price(close),
             volume: 0.0, trades: 0,
         }
     }
@@ -85,7 +87,7 @@ mod tests {
             create_candle(1.0), create_candle(2.0), create_candle(3.0),
             create_candle(4.0), create_candle(5.0),
         ];
-        let sma = Sma::new(3);
+        let sma = Sma::new(3).unwrap();
         let results = sma.calculate(&candles);
         // expected: NaN, NaN, (1+2+3)/3=2.0, (2+3+4)/3=3.0, (3+4+5)/3=4.0
         assert_f64_vec_eq(&results, &[f64::NAN, f64::NAN, 2.0, 3.0, 4.0]);
@@ -94,7 +96,7 @@ mod tests {
     #[test]
     fn test_sma_insufficient_data() {
         let candles = vec![create_candle(1.0), create_candle(2.0)];
-        let sma = Sma::new(3);
+        let sma = Sma::new(3).unwrap();
         let results = sma.calculate(&candles);
         assert_f64_vec_eq(&results, &[f64::NAN, f64::NAN]);
     }
@@ -104,7 +106,7 @@ mod tests {
         let candles = vec![
             create_candle(1.0), create_candle(2.0), create_candle(3.0),
         ];
-        let sma = Sma::new(1);
+        let sma = Sma::new(1).unwrap();
         let results = sma.calculate(&candles);
         // SMA(1) is just the close price
         assert_f64_vec_eq(&results, &[1.0, 2.0, 3.0]);
@@ -113,14 +115,13 @@ mod tests {
     #[test]
     fn test_sma_empty_data() {
         let candles: Vec<Candle> = Vec::new();
-        let sma = Sma::new(3);
+        let sma = Sma::new(3).unwrap();
         let results = sma.calculate(&candles);
         assert_f64_vec_eq(&results, &[]);
     }
 
     #[test]
-    #[should_panic(expected = "SMA period must be greater than 0")]
-    fn test_sma_period_zero_panic() {
-        Sma::new(0);
+    fn test_sma_period_zero_is_an_error() {
+        assert!(matches!(Sma::new(0), Err(EngineError::IndicatorError(_))));
     }
 }
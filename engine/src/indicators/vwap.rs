@@ -0,0 +1,88 @@
+// Volume Weighted Average Price (VWAP) indicator implementation
+// Anchored to the session: the average restarts with the first bar of each B3 trading day.
+use super::IndicatorCalculator;
+use crate::data::calendar::is_trading_day;
+use shared::models::ColumnSlices;
+use serde_json::Value;
+
+pub struct Vwap {
+    name: String,
+}
+
+impl Vwap {
+    pub fn new() -> Self {
+        Self { name: "VWAP".to_string() }
+    }
+}
+
+impl Default for Vwap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IndicatorCalculator for Vwap {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn parameters(&self) -> Value {
+        serde_json::json!({})
+    }
+
+    fn calculate_columns(&self, data: ColumnSlices<'_>) -> Vec<f64> {
+        let mut results = Vec::with_capacity(data.len());
+        let mut session = None;
+        let (mut value, mut volume) = (0.0, 0.0);
+        for i in 0..data.len() {
+            let date = data.timestamps[i].date_naive();
+            // Prints on a day B3 does not trade belong to no session.
+            if !is_trading_day(date) {
+                results.push(f64::NAN);
+                continue;
+            }
+            if session != Some(date) {
+                session = Some(date);
+                value = 0.0;
+                volume = 0.0;
+            }
+            value += data.typical_price(i) * data.volume[i];
+            volume += data.volume[i];
+            results.push(if volume > 0.0 { value / volume } else { f64::NAN });
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::{to_price, Candle};
+    use chrono::{TimeZone, Utc};
+
+    fn create_candle(day: u32, hour: u32, price: f64, volume: f64) -> Candle {
+        Candle {
+            symbol: "TEST".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 12, day, hour, 0, 0).unwrap(),
+            open: to_price(price), high: to_price(price), low: to_price(price), close: to_price(price),
+            volume, trades: 0,
+        }
+    }
+
+    #[test]
+    fn test_vwap_resets_each_session() {
+        let candles = vec![
+            create_candle(23, 10, 10.0, 100.0),
+            create_candle(23, 11, 13.0, 200.0),
+            create_candle(24, 10, 50.0, 100.0), // Christmas Eve: no session
+            create_candle(26, 10, 20.0, 0.0),
+            create_candle(26, 11, 21.0, 50.0),
+        ];
+        let vwap = Vwap::new().calculate(&candles);
+        assert!((vwap[0] - 10.0).abs() < 1e-9);
+        assert!((vwap[1] - 12.0).abs() < 1e-9);
+        assert!(vwap[2].is_nan());
+        assert!(vwap[3].is_nan()); // No volume yet in the new session
+        assert!((vwap[4] - 21.0).abs() < 1e-9);
+    }
+}
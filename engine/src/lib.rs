@@ -7,6 +7,8 @@ pub mod indicators;
 pub mod services;
 pub mod models; // Even if models/candle.rs is minimal, the module itself exists.
 pub mod error;
+pub mod simulation;
+pub mod backtest;
 
 // The build script will place generated protobuf code in src/services/generated,
 // which is then included by src/services/mod.rs.
@@ -1,37 +1,184 @@
 // Engine main entry point
 // use engine::config::settings::EngineSettings; // No longer needed directly
+use engine::config::settings::config_path;
+use engine::config::watch::{next_change, watch_file};
 use engine::services::trading_service::MyTradingEngine;
 use engine::services::TradingEngineServer; // Import the generated server type
 use engine::data::market_data::MarketDataStore;
+use engine::services::limits::ClientRateLimiter;
+use engine::services::request_id::RequestIdLayer;
+use engine::services::catch_panic::CatchPanicLayer;
+use engine::services::auth::AuthTokenLayer;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock, Semaphore};
+#[cfg(unix)]
+use tokio_stream::wrappers::UnixListenerStream;
+use tonic::codec::CompressionEncoding;
 use tonic::transport::Server;
+use tower::limit::GlobalConcurrencyLimitLayer;
 use tracing::info;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing subscriber for logging
-    // Use a simple subscriber for now, can be configured further (e.g., with json output, filtering)
-    tracing_subscriber::fmt::init();
-
-    info!("Starting Home Trader Engine...");
-
     // Load configuration using the new utility function
-    let settings = engine::config::settings::get_engine_settings(); // Use the new function
+    // An invalid config file stops the engine here, with every problem found in it
+    let app_settings = engine::config::settings::get_app_settings()?;
+    let settings = app_settings.engine.clone();
+
+    // Logging as configured: console, and the log file if any, flushed when the guard is dropped.
+    // It starts after the settings are read, so the config file is named here.
+    let _log_guard = engine::config::logging::init(&settings)?;
+    info!(config = %config_path().display(), "Starting Home Trader Engine...");
+    if let Some(path) = &settings.log_file {
+        info!(path = %path, rotation = ?settings.log_rotation, "Also logging to a file as JSON lines");
+    }
     let addr = format!("{}:{}", settings.host, settings.port).parse()?;
     info!("Engine will listen on {} (Host: {}, Port: {})", addr, settings.host, settings.port);
 
     // Initialize shared data stores or services
-    let market_data_store = Arc::new(RwLock::new(MarketDataStore::new()));
+    // Candles are kept in the candle store file, if one is configured, so they outlive a restart.
+    if let Some(candle_store) = &settings.candle_store {
+        info!(file = %candle_store.file, recent_candles = candle_store.recent_candles, "Keeping candles in the candle store");
+    }
+    let market_data_store = Arc::new(RwLock::new(MarketDataStore::from_settings(settings.candle_store.as_ref())?));
+
+    // Create an instance of the trading service, restoring the paper-trading account if one is configured
+    let trading_engine_service = MyTradingEngine::from_settings(market_data_store, app_settings)?;
+
+    // SIGHUP reloads the config file, like the ReloadSettings RPC.
+    #[cfg(unix)]
+    {
+        let engine = trading_engine_service.clone();
+        let mut hangups = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+        tokio::spawn(async move {
+            while hangups.recv().await.is_some() {
+                info!("SIGHUP received, reloading settings");
+                if let Err(e) = engine.reload_config().await {
+                    tracing::warn!(error = %e, "Settings reload failed; keeping the current settings");
+                }
+            }
+        });
+    }
 
-    // Create an instance of the trading service
-    let trading_engine_service = MyTradingEngine::new(market_data_store.clone());
+    // Saving the config file reloads it as well. The watcher stops when main returns.
+    let (config_changed, mut config_changes) = watch::channel(());
+    let _config_watcher = match watch_file(&config_path(), config_changed) {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            tracing::warn!(error = %e, "Cannot watch the config file; save it and send SIGHUP or call ReloadSettings to reload it");
+            None
+        }
+    };
+    {
+        let engine = trading_engine_service.clone();
+        tokio::spawn(async move {
+            while next_change(&mut config_changes).await.is_some() {
+                info!("Config file changed, reloading settings");
+                match engine.reload_config().await {
+                    Ok(changes) if !changes.restart_required.is_empty() => {
+                        tracing::warn!(settings = ?changes.restart_required, "Restart the engine to apply these changed settings");
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!(error = %e, "Settings reload failed; keeping the current settings"),
+                }
+            }
+        });
+    }
 
-    // Build and start the gRPC server
-    Server::builder()
-        .add_service(TradingEngineServer::new(trading_engine_service))
-        .serve(addr)
-        .await?;
+    // Standard grpc.health.v1 service: clients check it before issuing requests.
+    // The trading service reports SERVING once the account is restored, NOT_SERVING while shutting down.
+    let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
+    health_reporter.set_serving::<TradingEngineServer<MyTradingEngine>>().await;
+
+    // Clients may compress requests with either codec; responses use the configured one.
+    let mut trading_server = TradingEngineServer::new(trading_engine_service.clone())
+        .accept_compressed(CompressionEncoding::Gzip)
+        .accept_compressed(CompressionEncoding::Zstd);
+    if let Some(encoding) = settings.compression.encoding() {
+        info!("Compressing responses with {:?}", settings.compression);
+        trading_server = trading_server.send_compressed(encoding);
+    }
+
+    // Build and start the gRPC server; Ctrl+C stops it and saves the account one last time.
+    // Each request is tagged with an ID (logged, and echoed in the response headers), is checked
+    // for the auth token if one is configured, goes through the per-client rate limiter, then
+    // waits for one of the global slots. A handler
+    // that panics fails its own call with INTERNAL; the connection keeps serving the others.
+    // Browsers can call the services over gRPC-web (HTTP/1.1, with CORS) without a proxy;
+    // native gRPC clients are unaffected.
+    let limits = &settings.request_limits;
+    info!(
+        requests_per_second = limits.requests_per_second,
+        burst = limits.burst,
+        max_concurrent_requests = limits.max_concurrent_requests,
+        "Request limits"
+    );
+    // TCP and the optional Unix socket serve the same services and share the global slots.
+    let slots = Arc::new(Semaphore::new(limits.max_concurrent_requests.max(1)));
+    if settings.auth_token.is_some() {
+        info!("Clients must send the configured auth token");
+    }
+    let auth = AuthTokenLayer::new(settings.auth_token.as_deref());
+    let router = |server: Server| {
+        server
+            .accept_http1(true)
+            .layer(RequestIdLayer)
+            .layer(CatchPanicLayer)
+            .layer(auth.clone())
+            .layer(ClientRateLimiter::new(limits))
+            .layer(GlobalConcurrencyLimitLayer::with_semaphore(slots.clone()))
+            .add_service(tonic_web::enable(health_service.clone()))
+            .add_service(tonic_web::enable(trading_server.clone()))
+    };
+    let (stop, stopped) = watch::channel(());
+    let until_stopped = |mut stopped: watch::Receiver<()>| async move {
+        let _ = stopped.changed().await;
+    };
+
+    // TLS covers TCP; the Unix socket is only reachable from this machine.
+    let mut tcp_server = Server::builder();
+    if let Some(tls) = &settings.tls {
+        info!(cert_file = %tls.cert_file, "Serving TCP connections over TLS");
+        tcp_server = tcp_server.tls_config(tls.server_config()?)?;
+    }
+    let tcp = async {
+        router(tcp_server).serve_with_shutdown(addr, until_stopped(stopped.clone())).await?;
+        Ok::<(), Box<dyn std::error::Error>>(())
+    };
+    // Local clients (the GUI on the same machine) can skip TCP; they are not rate limited.
+    let unix = async {
+        #[cfg(unix)]
+        if let Some(path) = &settings.unix_socket {
+            let listener = bind_unix_socket(path)?;
+            info!("Engine also listens on Unix socket {}", path);
+            router(Server::builder()).serve_with_incoming_shutdown(UnixListenerStream::new(listener), until_stopped(stopped.clone())).await?;
+            let _ = std::fs::remove_file(path);
+        }
+        #[cfg(not(unix))]
+        if settings.unix_socket.is_some() {
+            tracing::warn!("unix_socket is set but Unix domain sockets are not supported on this platform; listening on TCP only");
+        }
+        Ok::<(), Box<dyn std::error::Error>>(())
+    };
+    let shutdown = async move {
+        let _ = tokio::signal::ctrl_c().await;
+        info!("Shutting down Home Trader Engine...");
+        health_reporter.set_not_serving::<TradingEngineServer<MyTradingEngine>>().await;
+        let _ = stop.send(());
+        Ok::<(), Box<dyn std::error::Error>>(())
+    };
+    tokio::try_join!(tcp, unix, shutdown)?;
+    trading_engine_service.save_account().await?;
 
     Ok(())
 }
+
+/// Listens on `path`, replacing the socket file a previous run left behind.
+#[cfg(unix)]
+fn bind_unix_socket(path: &str) -> std::io::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+    if std::fs::metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+        std::fs::remove_file(path)?;
+    }
+    tokio::net::UnixListener::bind(path)
+}
@@ -0,0 +1,121 @@
+// Client authentication for the gRPC server.
+// With `engine.auth_token` set, every call must carry `authorization: Bearer <token>`; other
+// calls are refused with UNAUTHENTICATED before they reach a handler. Health checks and the
+// CORS preflights of gRPC-web stay open, so container probes and browsers need no token for them.
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use shared::errors::ErrorCode;
+use tonic::body::BoxBody;
+use tower::{Layer, Service};
+
+use super::error_details::ErrorDetails;
+
+pub const AUTHORIZATION_HEADER: &str = "authorization";
+
+/// Calls under this path are answered without a token.
+const HEALTH_SERVICE_PATH: &str = "/grpc.health.v1.Health/";
+
+/// Requires `token` from every client; without one, lets every call through.
+#[derive(Clone, Default)]
+pub struct AuthTokenLayer {
+    token: Option<Arc<str>>,
+}
+
+impl AuthTokenLayer {
+    pub fn new(token: Option<&str>) -> Self {
+        AuthTokenLayer { token: token.map(Arc::from) }
+    }
+
+    /// Whether `request` may go on: no token is required, it is exempt, or it sent the token.
+    pub fn allows<B>(&self, request: &http::Request<B>) -> bool {
+        let Some(token) = &self.token else { return true };
+        if request.method() == http::Method::OPTIONS || request.uri().path().starts_with(HEALTH_SERVICE_PATH) {
+            return true;
+        }
+        request.headers().get(AUTHORIZATION_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|sent| same_token(sent.trim().as_bytes(), token.as_bytes()))
+    }
+}
+
+/// Compares every byte whatever the first difference, so the time taken tells nothing about the token.
+fn same_token(sent: &[u8], token: &[u8]) -> bool {
+    sent.len() == token.len() && sent.iter().zip(token).fold(0, |differences, (a, b)| differences | (a ^ b)) == 0
+}
+
+impl<S> Layer<S> for AuthTokenLayer {
+    type Service = Authenticated<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Authenticated { inner, layer: self.clone() }
+    }
+}
+
+#[derive(Clone)]
+pub struct Authenticated<S> {
+    inner: S,
+    layer: AuthTokenLayer,
+}
+
+impl<S, B> Service<http::Request<B>> for Authenticated<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        if !self.layer.allows(&request) {
+            tracing::warn!(path = %request.uri().path(), "Request refused: missing or wrong auth token");
+            let status = ErrorDetails::new(ErrorCode::Unauthenticated)
+                .into_status(tonic::Code::Unauthenticated, "This engine requires an auth token: send it as 'authorization: Bearer <token>'");
+            return Box::pin(async move { Ok(status.to_http()) });
+        }
+        Box::pin(self.inner.call(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    fn request(path: &str, authorization: Option<&str>) -> http::Request<()> {
+        let mut request = http::Request::builder().method(http::Method::POST).uri(path);
+        if let Some(authorization) = authorization {
+            request = request.header(AUTHORIZATION_HEADER, authorization);
+        }
+        request.body(()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_calls_without_the_token_are_refused() {
+        let layer = AuthTokenLayer::new(Some("s3cret"));
+        let method = "/trading.TradingEngine/GetMarketData";
+        assert!(layer.allows(&request(method, Some("Bearer s3cret"))));
+        assert!(!layer.allows(&request(method, None)));
+        assert!(!layer.allows(&request(method, Some("Bearer s3cre"))));
+        assert!(!layer.allows(&request(method, Some("s3cret"))));
+        assert!(layer.allows(&request("/grpc.health.v1.Health/Check", None)));
+        assert!(layer.allows(&http::Request::builder().method(http::Method::OPTIONS).uri(method).body(()).unwrap()));
+        assert!(AuthTokenLayer::new(None).allows(&request(method, None)));
+
+        let service = layer.layer(tower::service_fn(|_: http::Request<()>| async {
+            Ok::<_, std::convert::Infallible>(http::Response::new(tonic::body::empty_body()))
+        }));
+        let response = service.oneshot(request(method, None)).await.unwrap();
+        let status = tonic::Status::from_header_map(response.headers()).expect("a gRPC status in the headers");
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        assert_eq!(ErrorDetails::from_status(&status).and_then(|d| d.code()), Some(ErrorCode::Unauthenticated));
+    }
+}
@@ -0,0 +1,111 @@
+// Panic isolation for the gRPC server.
+// A handler that panics gets its call answered with INTERNAL (and the panic logged in the call's
+// span) instead of unwinding through the task serving the connection, which would drop every
+// other call on it: a gRPC-web connection, or the embedded engine's pipe to the GUI.
+use std::any::Any;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use shared::errors::ErrorCode;
+use tonic::body::BoxBody;
+use tower::{Layer, Service};
+
+use super::error_details::ErrorDetails;
+
+#[derive(Debug, Clone, Default)]
+pub struct CatchPanicLayer;
+
+impl<S> Layer<S> for CatchPanicLayer {
+    type Service = CatchPanic<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CatchPanic { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CatchPanic<S> {
+    inner: S,
+}
+
+impl<S, B> Service<http::Request<B>> for CatchPanic<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let mut response = match catch_unwind(AssertUnwindSafe(|| Box::pin(self.inner.call(request)))) {
+            Ok(response) => response,
+            Err(panic) => {
+                let response = panic_response(panic);
+                return Box::pin(async move { Ok(response) });
+            }
+        };
+        // A future that panicked is not polled again; the call is answered with the error instead.
+        Box::pin(std::future::poll_fn(move |cx| {
+            catch_unwind(AssertUnwindSafe(|| response.as_mut().poll(cx)))
+                .unwrap_or_else(|panic| Poll::Ready(Ok(panic_response(panic))))
+        }))
+    }
+}
+
+/// The INTERNAL answer to a call whose handler panicked with `panic`.
+fn panic_response(panic: Box<dyn Any + Send>) -> http::Response<BoxBody> {
+    let message = panic.downcast_ref::<&str>().copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("(no message)");
+    tracing::error!(panic = message, "RPC handler panicked; answering INTERNAL");
+    ErrorDetails::new(ErrorCode::Internal)
+        .into_status(tonic::Code::Internal, "Internal engine error while handling this call; other calls are unaffected")
+        .to_http()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    fn status_of(response: &http::Response<BoxBody>) -> tonic::Status {
+        tonic::Status::from_header_map(response.headers()).expect("a gRPC status in the headers")
+    }
+
+    #[tokio::test]
+    async fn test_panics_become_internal_errors() {
+        let service = CatchPanicLayer.layer(tower::service_fn(|request: http::Request<()>| async move {
+            match request.uri().path() {
+                "/panic-later" => {
+                    tokio::task::yield_now().await;
+                    panic!("a bug in the handler");
+                }
+                _ => Ok::<_, std::convert::Infallible>(http::Response::new(tonic::body::empty_body())),
+            }
+        }));
+
+        let request = http::Request::builder().uri("/panic-later").body(()).unwrap();
+        let status = status_of(&service.clone().oneshot(request).await.unwrap());
+        assert_eq!(status.code(), tonic::Code::Internal);
+        assert_eq!(ErrorDetails::from_status(&status).and_then(|d| d.code()), Some(ErrorCode::Internal));
+
+        // The service keeps answering other calls.
+        let request = http::Request::builder().uri("/fine").body(()).unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        assert!(response.headers().get("grpc-status").is_none());
+
+        // Panicking before returning a future is caught as well.
+        let service = CatchPanicLayer.layer(tower::service_fn(|_: http::Request<()>| -> std::future::Ready<Result<http::Response<BoxBody>, std::convert::Infallible>> {
+            panic!("a bug before the handler's future")
+        }));
+        let request = http::Request::builder().uri("/panic-now").body(()).unwrap();
+        assert_eq!(status_of(&service.oneshot(request).await.unwrap()).code(), tonic::Code::Internal);
+    }
+}
@@ -0,0 +1,84 @@
+// In-process engine
+// Runs the trading and health services inside another program (the GUI's embedded mode) and connects
+// to them over in-memory pipes, so clients keep speaking gRPC without a second process, port or socket.
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, RwLock};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::codec::CompressionEncoding;
+use tonic::transport::{Channel, Endpoint, Server, Uri};
+
+use crate::config::settings::AppSettings;
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::services::request_id::RequestIdLayer;
+use crate::services::catch_panic::CatchPanicLayer;
+use crate::services::trading_service::MyTradingEngine;
+use crate::services::TradingEngineServer;
+
+/// Buffer of each in-memory connection, in each direction.
+const PIPE_CAPACITY: usize = 1 << 20;
+
+/// An engine serving in this process, until it is dropped.
+pub struct EmbeddedEngine {
+    engine: MyTradingEngine,
+    channel: Channel,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl EmbeddedEngine {
+    /// Starts the engine `settings` describe (account file, risk limits...) and connects to it.
+    /// Their host, port and Unix socket are not listened on. Must be called inside a Tokio runtime.
+    pub async fn start(settings: AppSettings) -> anyhow::Result<Self> {
+        let store = MarketDataStore::from_settings(settings.engine.candle_store.as_ref())?;
+        let engine = MyTradingEngine::from_settings(Arc::new(RwLock::new(store)), settings)?;
+        let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
+        health_reporter.set_serving::<TradingEngineServer<MyTradingEngine>>().await;
+        let trading_server = TradingEngineServer::new(engine.clone())
+            .accept_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Zstd);
+
+        // Every connection the channel opens is one end of a new pipe; the server gets the other end.
+        let (connections, incoming) = mpsc::unbounded_channel();
+        let router = Server::builder()
+            .layer(RequestIdLayer)
+            .layer(CatchPanicLayer)
+            .add_service(health_service)
+            .add_service(trading_server);
+        let server = tokio::spawn(async move {
+            let incoming = UnboundedReceiverStream::new(incoming).map(Ok::<_, std::io::Error>);
+            if let Err(e) = router.serve_with_incoming(incoming).await {
+                tracing::error!(error = %e, "Embedded engine stopped");
+            }
+        });
+        let channel = Endpoint::from_static("http://embedded.engine")
+            .connect_with_connector(tower::service_fn(move |_: Uri| {
+                let (client_end, server_end) = tokio::io::duplex(PIPE_CAPACITY);
+                let sent = connections.send(server_end);
+                async move {
+                    sent.map_err(|_| std::io::Error::new(std::io::ErrorKind::BrokenPipe, "the embedded engine stopped"))?;
+                    Ok::<_, std::io::Error>(client_end)
+                }
+            }))
+            .await?;
+        tracing::info!("Embedded engine started");
+        Ok(EmbeddedEngine { engine, channel, server })
+    }
+
+    /// A channel to the engine's services, for any number of clients.
+    pub fn channel(&self) -> Channel {
+        self.channel.clone()
+    }
+
+    /// Saves the paper-trading account, if the engine has an account file; call before exiting.
+    pub async fn save_account(&self) -> Result<(), EngineError> {
+        self.engine.save_account().await
+    }
+}
+
+impl Drop for EmbeddedEngine {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
@@ -0,0 +1,126 @@
+// Structured error details.
+// Failed calls carry a standard `google.rpc.Status` in their `grpc-status-details-bin` trailer,
+// besides the readable message: an `ErrorInfo` with a machine-readable reason (and e.g. the line
+// of a bad CSV record), a `BadRequest` listing invalid request fields, and a `RetryInfo` when
+// the call may be retried later. Clients decode them with `ErrorDetails::from_status`.
+use std::collections::HashMap;
+use std::time::Duration;
+
+use prost::Message;
+use shared::errors::ErrorCode;
+use tonic::{Code, Status};
+
+use super::rpc_status::{bad_request::FieldViolation, BadRequest, ErrorInfo, RetryInfo, Status as RpcStatus};
+
+/// `ErrorInfo.domain` of the engine's errors.
+pub const ERROR_DOMAIN: &str = "home-trader.engine";
+
+const ERROR_INFO_TYPE: &str = "type.googleapis.com/google.rpc.ErrorInfo";
+const RETRY_INFO_TYPE: &str = "type.googleapis.com/google.rpc.RetryInfo";
+const BAD_REQUEST_TYPE: &str = "type.googleapis.com/google.rpc.BadRequest";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ErrorDetails {
+    /// Why the call failed, e.g. "CSV_DATA_FORMAT"; empty when unknown.
+    pub reason: String,
+    /// Facts about the failure, e.g. "line" and "column" of a bad CSV record.
+    pub metadata: HashMap<String, String>,
+    /// Invalid request fields, with what is wrong with each.
+    pub field_violations: Vec<FieldViolation>,
+    /// How long to wait before trying again, when retrying can help.
+    pub retry_after: Option<Duration>,
+}
+
+impl ErrorDetails {
+    pub fn new(code: ErrorCode) -> Self {
+        ErrorDetails { reason: code.into(), ..Default::default() }
+    }
+
+    /// The reason as a code; `None` when it is empty or from a newer engine.
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.reason.parse().ok()
+    }
+
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.metadata.insert(key.into(), value.to_string());
+        self
+    }
+
+    pub fn with_field_violation(mut self, field: impl Into<String>, description: impl Into<String>) -> Self {
+        self.field_violations.push(FieldViolation { field: field.into(), description: description.into() });
+        self
+    }
+
+    pub fn with_retry_after(mut self, delay: Duration) -> Self {
+        self.retry_after = Some(delay);
+        self
+    }
+
+    /// A status with `code` and `message` carrying these details.
+    pub fn into_status(self, code: Code, message: impl Into<String>) -> Status {
+        let message = message.into();
+        let mut details = vec![any(ERROR_INFO_TYPE, &ErrorInfo { reason: self.reason, domain: ERROR_DOMAIN.to_string(), metadata: self.metadata })];
+        if !self.field_violations.is_empty() {
+            details.push(any(BAD_REQUEST_TYPE, &BadRequest { field_violations: self.field_violations }));
+        }
+        if let Some(delay) = self.retry_after {
+            let retry_delay = prost_types::Duration { seconds: delay.as_secs() as i64, nanos: delay.subsec_nanos() as i32 };
+            details.push(any(RETRY_INFO_TYPE, &RetryInfo { retry_delay: Some(retry_delay) }));
+        }
+        let encoded = RpcStatus { code: code as i32, message: message.clone(), details }.encode_to_vec();
+        Status::with_details(code, message, encoded.into())
+    }
+
+    /// The details `status` carries, `None` if it has none (e.g. it did not come from the engine).
+    /// Details of unknown types are skipped.
+    pub fn from_status(status: &Status) -> Option<Self> {
+        if status.details().is_empty() {
+            return None;
+        }
+        let rpc_status = RpcStatus::decode(status.details()).ok()?;
+        let mut details = ErrorDetails::default();
+        for detail in rpc_status.details {
+            match detail.type_url.as_str() {
+                ERROR_INFO_TYPE => if let Ok(info) = ErrorInfo::decode(detail.value.as_slice()) {
+                    details.reason = info.reason;
+                    details.metadata = info.metadata;
+                },
+                BAD_REQUEST_TYPE => if let Ok(bad_request) = BadRequest::decode(detail.value.as_slice()) {
+                    details.field_violations.extend(bad_request.field_violations);
+                },
+                RETRY_INFO_TYPE => if let Ok(retry) = RetryInfo::decode(detail.value.as_slice()) {
+                    details.retry_after = retry.retry_delay
+                        .map(|d| Duration::new(d.seconds.max(0) as u64, d.nanos.max(0) as u32));
+                },
+                _ => {}
+            }
+        }
+        Some(details)
+    }
+}
+
+fn any(type_url: &str, message: &impl Message) -> prost_types::Any {
+    prost_types::Any { type_url: type_url.to_string(), value: message.encode_to_vec() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_details_survive_a_round_trip() {
+        let details = ErrorDetails::new(ErrorCode::CsvDataFormat)
+            .with_metadata("line", 12)
+            .with_field_violation("timeframe", "Unknown timeframe 9")
+            .with_retry_after(Duration::from_millis(1500));
+        let status = details.clone().into_status(Code::InvalidArgument, "bad file");
+        assert_eq!((status.code(), status.message()), (Code::InvalidArgument, "bad file"));
+        assert_eq!(ErrorDetails::from_status(&status), Some(details));
+        assert_eq!(ErrorDetails::from_status(&status).unwrap().code(), Some(ErrorCode::CsvDataFormat));
+
+        // The status goes to clients in the response trailers.
+        let sent = Status::from_header_map(&status.to_http().headers().clone()).unwrap();
+        assert_eq!(ErrorDetails::from_status(&sent).unwrap().metadata["line"], "12");
+        assert_eq!(ErrorDetails::from_status(&Status::internal("plain")), None);
+    }
+}
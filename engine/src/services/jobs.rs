@@ -0,0 +1,304 @@
+// Long-running jobs.
+// CSV imports, backtests and analyses can take longer than a client wants to hold a call
+// open. Submitted as jobs, they run in the background: clients watch their progress, cancel
+// them and fetch the result later, over the same connection or another one. Cancelling is
+// cooperative: the work checks `JobControl::is_cancelled` as it goes. Finished jobs are kept,
+// the most recent `MAX_FINISHED_JOBS` of them, so their results can still be fetched.
+#![allow(clippy::result_large_err)] // Jobs fail, and lookups fail, with the Status their RPC returns
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::watch;
+use tonic::Status;
+use tracing::Instrument;
+
+/// Jobs allowed to run at once; further submissions are refused until one finishes.
+pub const MAX_RUNNING_JOBS: usize = 8;
+const MAX_FINISHED_JOBS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobState {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobState::Running => "RUNNING",
+            JobState::Succeeded => "SUCCEEDED",
+            JobState::Failed => "FAILED",
+            JobState::Cancelled => "CANCELLED",
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        *self != JobState::Running
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobStatus {
+    pub id: String,
+    /// What the job runs, e.g. "BACKTEST".
+    pub kind: &'static str,
+    pub state: JobState,
+    /// Share of the work done, from 0 to 1.
+    pub progress: f64,
+    pub message: String,
+    pub submitted_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Why the job failed or was cancelled.
+    pub error: Option<String>,
+}
+
+/// Handed to a job's work: reports its progress and tells it when to stop.
+#[derive(Debug, Clone)]
+pub struct JobControl {
+    status: Arc<watch::Sender<JobStatus>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl JobControl {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Records how far the job has come; `progress` is clamped to 0..1.
+    pub fn report(&self, progress: f64, message: impl Into<String>) {
+        let message = message.into();
+        self.status.send_if_modified(|status| {
+            let progress = progress.clamp(0.0, 1.0);
+            if status.state.is_finished() || (status.progress == progress && status.message == message) {
+                return false;
+            }
+            status.progress = progress;
+            status.message = message;
+            true
+        });
+    }
+}
+
+struct Job<T> {
+    control: JobControl,
+    /// Set once the job finishes.
+    result: Option<Result<T, Status>>,
+}
+
+/// Jobs submitted to the engine, running and finished; clones share them.
+pub struct JobRegistry<T> {
+    jobs: Arc<Mutex<HashMap<String, Job<T>>>>,
+}
+
+impl<T> Clone for JobRegistry<T> {
+    fn clone(&self) -> Self {
+        JobRegistry { jobs: self.jobs.clone() }
+    }
+}
+
+impl<T> Default for JobRegistry<T> {
+    fn default() -> Self {
+        JobRegistry { jobs: Arc::default() }
+    }
+}
+
+impl<T: Clone + Send + 'static> JobRegistry<T> {
+    /// Starts `work` in the background. Must be called inside a Tokio runtime.
+    pub fn submit<F>(&self, kind: &'static str, work: impl FnOnce(JobControl) -> F) -> Result<JobStatus, Status>
+    where
+        F: Future<Output = Result<T, Status>> + Send + 'static,
+    {
+        let mut jobs = self.lock();
+        let running = jobs.values().filter(|job| !job.control.status.borrow().state.is_finished()).count();
+        if running >= MAX_RUNNING_JOBS {
+            return Err(Status::resource_exhausted(format!(
+                "{} jobs are already running (at most {}); wait for one to finish or cancel one", running, MAX_RUNNING_JOBS
+            )));
+        }
+        prune_finished(&mut jobs);
+
+        let status = JobStatus {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            state: JobState::Running,
+            progress: 0.0,
+            message: "Started".to_string(),
+            submitted_at: Utc::now(),
+            finished_at: None,
+            error: None,
+        };
+        let control = JobControl { status: Arc::new(watch::channel(status.clone()).0), cancelled: Arc::default() };
+        jobs.insert(status.id.clone(), Job { control: control.clone(), result: None });
+        drop(jobs);
+
+        // The work runs in its own task, so a panic fails the job instead of leaving it running.
+        let id = status.id.clone();
+        let registry = self.clone();
+        let task = tokio::spawn(work(control).in_current_span());
+        tokio::spawn(async move {
+            let result = task.await.unwrap_or_else(|e| Err(Status::internal(format!("Job stopped unexpectedly: {}", e))));
+            registry.finish(&id, result);
+        }.in_current_span());
+        tracing::info!(job_id = %status.id, kind, "Job submitted");
+        Ok(status)
+    }
+
+    fn finish(&self, id: &str, result: Result<T, Status>) {
+        let mut jobs = self.lock();
+        let Some(job) = jobs.get_mut(id) else { return };
+        let result = match result {
+            Err(_) if job.control.is_cancelled() => Err(Status::cancelled(format!("Job {} was cancelled", id))),
+            result => result,
+        };
+        job.control.status.send_modify(|status| {
+            status.finished_at = Some(Utc::now());
+            match &result {
+                Ok(_) => {
+                    status.state = JobState::Succeeded;
+                    status.progress = 1.0;
+                    status.message = "Finished".to_string();
+                }
+                Err(e) => {
+                    status.state = if e.code() == tonic::Code::Cancelled { JobState::Cancelled } else { JobState::Failed };
+                    status.error = Some(e.message().to_string());
+                }
+            }
+            tracing::info!(job_id = %id, kind = status.kind, state = status.state.as_str(), error = ?status.error, "Job finished");
+        });
+        job.result = Some(result);
+    }
+
+    pub fn status(&self, id: &str) -> Result<JobStatus, Status> {
+        self.with_job(id, |job| job.control.status.borrow().clone())
+    }
+
+    /// Follows a job's status; the receiver sees its last change even after the job is pruned.
+    pub fn watch(&self, id: &str) -> Result<watch::Receiver<JobStatus>, Status> {
+        self.with_job(id, |job| job.control.status.subscribe())
+    }
+
+    /// Asks a running job to stop; finished jobs are left as they are.
+    pub fn cancel(&self, id: &str) -> Result<JobStatus, Status> {
+        self.with_job(id, |job| {
+            let (finished, progress) = {
+                let status = job.control.status.borrow();
+                (status.state.is_finished(), status.progress)
+            };
+            if !finished {
+                job.control.cancelled.store(true, Ordering::Relaxed);
+                job.control.report(progress, "Cancelling");
+            }
+            job.control.status.borrow().clone()
+        })
+    }
+
+    /// The job's result; its error if it failed, FAILED_PRECONDITION while it runs.
+    pub fn result(&self, id: &str) -> Result<T, Status> {
+        self.with_job(id, |job| job.result.clone())?
+            .unwrap_or_else(|| Err(Status::failed_precondition(format!("Job {} is still running", id))))
+    }
+
+    /// Every job kept, oldest first.
+    pub fn list(&self) -> Vec<JobStatus> {
+        let mut statuses: Vec<JobStatus> = self.lock().values().map(|job| job.control.status.borrow().clone()).collect();
+        statuses.sort_by_key(|status| status.submitted_at);
+        statuses
+    }
+
+    fn with_job<R>(&self, id: &str, f: impl FnOnce(&Job<T>) -> R) -> Result<R, Status> {
+        self.lock().get(id).map(f).ok_or_else(|| Status::not_found(format!("Job {} not found; finished jobs are kept for a while only", id)))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Job<T>>> {
+        self.jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Drops the oldest finished jobs beyond `MAX_FINISHED_JOBS`, making room for one more.
+fn prune_finished<T>(jobs: &mut HashMap<String, Job<T>>) {
+    let mut finished: Vec<(DateTime<Utc>, String)> = jobs.iter()
+        .filter_map(|(id, job)| job.control.status.borrow().finished_at.map(|at| (at, id.clone())))
+        .collect();
+    if finished.len() < MAX_FINISHED_JOBS {
+        return;
+    }
+    finished.sort();
+    for (_, id) in finished.iter().take(finished.len() + 1 - MAX_FINISHED_JOBS) {
+        jobs.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn wait_until_finished(registry: &JobRegistry<u32>, id: &str) -> JobStatus {
+        let mut status = registry.watch(id).unwrap();
+        let finished = tokio::time::timeout(Duration::from_secs(5), status.wait_for(|s| s.state.is_finished()));
+        finished.await.expect("job should finish").unwrap();
+        registry.status(id).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_jobs_report_progress_and_results() {
+        let registry = JobRegistry::<u32>::default();
+        let (go, wait) = tokio::sync::oneshot::channel::<()>();
+        let job = registry.submit("TEST", |control| async move {
+            control.report(0.5, "Half way");
+            wait.await.ok();
+            Ok(42)
+        }).unwrap();
+
+        let mut watch = registry.watch(&job.id).unwrap();
+        watch.wait_for(|s| s.progress == 0.5).await.unwrap();
+        assert_eq!(registry.status(&job.id).unwrap().message, "Half way");
+        assert_eq!(registry.result(&job.id).unwrap_err().code(), tonic::Code::FailedPrecondition);
+
+        go.send(()).unwrap();
+        let finished = wait_until_finished(&registry, &job.id).await;
+        assert_eq!((finished.state, finished.progress), (JobState::Succeeded, 1.0));
+        assert_eq!(registry.result(&job.id).unwrap(), 42);
+        assert_eq!(registry.list().len(), 1);
+        assert_eq!(registry.status("nope").unwrap_err().code(), tonic::Code::NotFound);
+
+        let failed = registry.submit("TEST", |_| async { Err(Status::invalid_argument("bad input")) }).unwrap();
+        let failed = wait_until_finished(&registry, &failed.id).await;
+        assert_eq!((failed.state, failed.error.as_deref()), (JobState::Failed, Some("bad input")));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_jobs_stop_and_say_so() {
+        let registry = JobRegistry::<u32>::default();
+        let job = registry.submit("TEST", |control| async move {
+            while !control.is_cancelled() {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+            Err(Status::internal("stopped"))
+        }).unwrap();
+
+        assert_eq!(registry.cancel(&job.id).unwrap().message, "Cancelling");
+        let cancelled = wait_until_finished(&registry, &job.id).await;
+        assert_eq!(cancelled.state, JobState::Cancelled);
+        assert_eq!(registry.result(&job.id).unwrap_err().code(), tonic::Code::Cancelled);
+        // Cancelling a finished job changes nothing.
+        assert_eq!(registry.cancel(&job.id).unwrap().state, JobState::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_running_jobs_are_capped() {
+        let registry = JobRegistry::<u32>::default();
+        let (_hold, wait) = tokio::sync::watch::channel(());
+        for _ in 0..MAX_RUNNING_JOBS {
+            let mut wait = wait.clone();
+            registry.submit("TEST", |_| async move { wait.changed().await.ok(); Ok(0) }).unwrap();
+        }
+        let refused = registry.submit("TEST", |_| async { Ok(0) }).unwrap_err();
+        assert_eq!(refused.code(), tonic::Code::ResourceExhausted);
+    }
+}
@@ -0,0 +1,168 @@
+// Request limits for the gRPC server.
+// Each client (by IP address) gets a token bucket: requests beyond its rate and burst are
+// refused with RESOURCE_EXHAUSTED instead of queueing. A global concurrency cap (tower's
+// GlobalConcurrencyLimitLayer, applied in main) bounds the requests handled at once, so a
+// runaway client cannot starve the engine.
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use shared::errors::ErrorCode;
+use tonic::body::BoxBody;
+use tonic::transport::server::TcpConnectInfo;
+use tower::{Layer, Service};
+
+use super::error_details::ErrorDetails;
+
+/// Buckets are pruned once this many clients have been seen.
+const MAX_TRACKED_CLIENTS: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RequestLimits {
+    /// Sustained requests per second allowed from one client; 0 disables rate limiting.
+    pub requests_per_second: f64,
+    /// Requests a client may make in a burst above its rate.
+    pub burst: u32,
+    /// Requests handled at once across all clients; further requests wait for a slot.
+    pub max_concurrent_requests: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        RequestLimits { requests_per_second: 50.0, burst: 100, max_concurrent_requests: 64 }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn full(burst: u32, now: Instant) -> Self {
+        TokenBucket { tokens: burst as f64, updated: now }
+    }
+
+    fn refill(&mut self, now: Instant, rate: f64, burst: u32) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(burst as f64);
+        self.updated = now;
+    }
+
+    fn try_take(&mut self, now: Instant, rate: f64, burst: u32) -> bool {
+        self.refill(now, rate, burst);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Per-client token buckets, shared by every connection.
+#[derive(Debug, Clone)]
+pub struct ClientRateLimiter {
+    rate: f64,
+    burst: u32,
+    buckets: Arc<Mutex<HashMap<IpAddr, TokenBucket>>>,
+}
+
+impl ClientRateLimiter {
+    pub fn new(limits: &RequestLimits) -> Self {
+        ClientRateLimiter { rate: limits.requests_per_second, burst: limits.burst.max(1), buckets: Arc::default() }
+    }
+
+    /// Takes a token for `client`; `false` if it is over its limit.
+    pub fn allow(&self, client: IpAddr, now: Instant) -> bool {
+        if self.rate <= 0.0 {
+            return true;
+        }
+        let mut buckets = self.buckets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(&client) {
+            // Clients whose bucket has refilled completely are the same as unseen ones.
+            let (rate, burst) = (self.rate, self.burst);
+            buckets.retain(|_, bucket| {
+                bucket.refill(now, rate, burst);
+                bucket.tokens < burst as f64
+            });
+        }
+        buckets.entry(client)
+            .or_insert_with(|| TokenBucket::full(self.burst, now))
+            .try_take(now, self.rate, self.burst)
+    }
+}
+
+impl<S> Layer<S> for ClientRateLimiter {
+    type Service = RateLimited<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimited { inner, limiter: self.clone() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimited<S> {
+    inner: S,
+    limiter: ClientRateLimiter,
+}
+
+impl<S, B> Service<http::Request<B>> for RateLimited<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        // Requests that did not come over TCP (e.g. in-process tests) are not limited.
+        let client = request.extensions().get::<TcpConnectInfo>().and_then(|info| info.remote_addr()).map(|addr| addr.ip());
+        if let Some(client) = client.filter(|client| !self.limiter.allow(*client, Instant::now())) {
+            tracing::warn!(%client, path = %request.uri().path(), "Request refused: client is over its rate limit");
+            // A token comes back every 1/rate seconds: the client may retry then.
+            let status = ErrorDetails::new(ErrorCode::RateLimited)
+                .with_retry_after(Duration::from_secs_f64(1.0 / self.limiter.rate))
+                .into_status(tonic::Code::ResourceExhausted, format!(
+                    "Rate limit exceeded: at most {} requests per second (burst {}) per client", self.limiter.rate, self.limiter.burst
+                ));
+            return Box::pin(async move { Ok(status.to_http()) });
+        }
+        Box::pin(self.inner.call(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_clients_have_separate_buckets() {
+        let limiter = ClientRateLimiter::new(&RequestLimits { requests_per_second: 2.0, burst: 3, ..Default::default() });
+        let (a, b): (IpAddr, IpAddr) = ("10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap());
+        let now = Instant::now();
+
+        assert!((0..3).all(|_| limiter.allow(a, now)));
+        assert!(!limiter.allow(a, now));
+        assert!(limiter.allow(b, now));
+        // Half a second at 2 requests per second buys one more.
+        assert!(limiter.allow(a, now + Duration::from_millis(500)));
+        assert!(!limiter.allow(a, now + Duration::from_millis(500)));
+
+        let unlimited = ClientRateLimiter::new(&RequestLimits { requests_per_second: 0.0, ..Default::default() });
+        assert!((0..1000).all(|_| unlimited.allow(a, now)));
+    }
+}
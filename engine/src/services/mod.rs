@@ -1,22 +1,58 @@
 // gRPC services and related logic
 pub mod trading_service; // This now refers to the directory engine/src/services/trading_service/
+pub mod limits; // Per-client rate limiting for the gRPC server
+pub mod request_id; // Request IDs and per-call tracing spans
+pub mod catch_panic; // Handler panics answered as INTERNAL errors
+pub mod auth; // Auth token required from clients
+pub mod jobs; // Background jobs with progress, cancellation and results
+pub mod error_details; // google.rpc error details attached to failed calls
+pub mod embedded; // The engine run inside another process (the GUI), over in-memory pipes
+
+/// Version of the TradingEngine service contract, bumped on changes that break existing
+/// clients; reported by GetEngineInfo so the GUI can tell whether it speaks the same API.
+pub const API_VERSION: u32 = 3;
 
 // This module will also contain the generated protobuf code.
 // The build script is configured to output to src/services/generated.
 // We need to make sure this generated code is properly included.
+#[allow(clippy::large_enum_variant)] // prost oneofs hold their messages inline
 pub mod generated {
     // This will include the Rust code generated by tonic_build from trading.proto
     // The exact name 'trading' comes from the package name in trading.proto
     tonic::include_proto!("trading");
 }
 
+/// The standard google.rpc error messages (see proto/google/rpc), for `error_details`.
+pub mod rpc_status {
+    tonic::include_proto!("google.rpc");
+}
+
 // Re-export generated types for easier access if needed elsewhere in the engine
 pub use generated::trading_engine_client::TradingEngineClient; // Added for GUI client access
 pub use generated::trading_engine_server::{TradingEngine, TradingEngineServer};
 pub use generated::{
     Candle as ProtoCandle, // Renaming to avoid conflict with shared::models::Candle
-    LoadCsvRequest, LoadCsvResponse,
-    MarketDataRequest, MarketDataResponse,
+    LoadCsvRequest, LoadCsvResponse, LoadCsvBatchRequest, LoadCsvBatchResponse, LoadCsvFileResult, CsvRowError,
+    MarketDataRequest, MarketDataResponse, SubscribeCandlesRequest, CandleUpdate as GrpcCandleUpdate,
+    AggregatedCandlesRequest, AggregatedCandlesResponse, SeriesStatsRequest, SeriesStats as GrpcSeriesStats,
+    ExportDataRequest, ExportDataResponse, ExportedIndicator,
     IndicatorRequest, IndicatorResponse,
-    TradeRequest, TradeResponse,
+    TradeRequest, TradeResponse, SessionRequest, SessionEvent,
+    CancelOrderRequest, ModifyOrderRequest, OrderState,
+    TradeHistoryRequest, TradeHistoryResponse, Execution as GrpcExecution,
+    PositionSizing, PositionSizeRequest, PositionSizeResponse,
+    StartReplayRequest, ReplayControlRequest, StepReplayRequest, ReplaySpeedRequest, ReplayStatus,
+    BacktestRequest, BacktestResponse, SessionReportRequest, StreamBacktestRequest, BacktestEvent as GrpcBacktestEvent,
+    StressTestRequest, StressTestResponse, StressScenario as GrpcStressScenario, StressResult as GrpcStressResult, CarryCosts as GrpcCarryCosts, CostCharge as GrpcCostCharge,
+    BenchmarkComparison as GrpcBenchmarkComparison,
+    PnlReport as GrpcPnlReport, MarginStatus as GrpcMarginStatus, PerformanceMetrics as GrpcPerformanceMetrics,
+    MonteCarloRequest, MonteCarloProgress, MonteCarloSummary as GrpcMonteCarloSummary, PercentileStats, TradeRecord as GrpcTradeRecord, EquityPoint as GrpcEquityPoint,
+    ResetAccountRequest, SnapshotAccountRequest, AccountState, PositionState,
+    CashTransferRequest, BaseCurrencyRequest,
+    TimeFrame as GrpcTimeFrame,
+    RiskLimitsRequest, RiskLimits as GrpcRiskLimits, RiskViolation as GrpcRiskViolation,
+    EngineInfoRequest, EngineInfo, ReloadSettingsRequest, ReloadSettingsResponse,
+    SubmitJobRequest, JobRequest, ListJobsRequest, ListJobsResponse, JobStatus as GrpcJobStatus, JobResult, SeriesSummary as GrpcSeriesSummary,
+    ListSymbolsRequest, ListSymbolsResponse, SymbolSummary as GrpcSymbolSummary,
+    CreateAlertRequest, AlertRequest, ListAlertsRequest, ListAlertsResponse, SubscribeAlertsRequest, Alert as GrpcAlert, AlertEvent as GrpcAlertEvent,
 };
@@ -0,0 +1,115 @@
+// Request IDs for the gRPC server.
+// Every call gets an ID: the one the client sent in `x-request-id`, or a fresh UUID. The call
+// runs inside an `rpc` tracing span carrying the ID and method (the trading service adds the
+// namespace, symbol and timeframe it works on), and the ID is echoed in the response headers,
+// so an error shown by the GUI can be matched with the engine's log lines for that call.
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use http::HeaderValue;
+use tonic::body::BoxBody;
+use tower::{Layer, Service};
+use tracing::Instrument;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied ID that is kept; longer ones are replaced with a generated ID.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// The ID for a call: the client's, if it sent a usable one, otherwise a new UUID.
+pub fn request_id(headers: &http::HeaderMap) -> HeaderValue {
+    headers.get(REQUEST_ID_HEADER)
+        .filter(|id| id.to_str().is_ok_and(|id| !id.trim().is_empty() && id.len() <= MAX_REQUEST_ID_LEN))
+        .cloned()
+        .unwrap_or_else(|| HeaderValue::from_str(&uuid::Uuid::new_v4().to_string()).expect("a UUID is a valid header value"))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RequestIdLayer;
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = WithRequestId<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        WithRequestId { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WithRequestId<S> {
+    inner: S,
+}
+
+impl<S, B> Service<http::Request<B>> for WithRequestId<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<B>) -> Self::Future {
+        let id = request_id(request.headers());
+        // Handlers see the ID in the request metadata, whether or not the client sent it.
+        request.headers_mut().insert(REQUEST_ID_HEADER, id.clone());
+        let span = tracing::info_span!(
+            "rpc",
+            request_id = id.to_str().unwrap_or_default(),
+            method = request.uri().path(),
+            namespace = tracing::field::Empty,
+            symbol = tracing::field::Empty,
+            timeframe = tracing::field::Empty,
+        );
+        let started = Instant::now();
+        let response = span.in_scope(|| self.inner.call(request));
+        Box::pin(async move {
+            let mut response = response.await?;
+            response.headers_mut().insert(REQUEST_ID_HEADER, id);
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            // Errors come back before any message, with the status in the headers; otherwise
+            // it follows the (possibly streamed) body in the trailers.
+            match response.headers().get("grpc-status").and_then(|code| code.to_str().ok()).filter(|code| *code != "0") {
+                Some(code) => {
+                    let message = response.headers().get("grpc-message").and_then(|m| m.to_str().ok()).unwrap_or_default();
+                    tracing::warn!(elapsed_ms, grpc_status = code, grpc_message = message, "RPC failed");
+                }
+                None => tracing::info!(elapsed_ms, "RPC answered"),
+            }
+            Ok(response)
+        }.instrument(span))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_request_id_is_kept_or_generated_and_echoed() {
+        let service = RequestIdLayer.layer(tower::service_fn(|request: http::Request<()>| async move {
+            let seen = request.headers().get(REQUEST_ID_HEADER).cloned();
+            let mut response = http::Response::new(tonic::body::empty_body());
+            response.headers_mut().insert("x-seen", seen.expect("handlers see the request ID"));
+            Ok::<_, std::convert::Infallible>(response)
+        }));
+
+        let request = http::Request::builder().uri("/trading.TradingEngine/GetEngineInfo").header(REQUEST_ID_HEADER, "gui-42").body(()).unwrap();
+        let response = service.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "gui-42");
+        assert_eq!(response.headers()["x-seen"], "gui-42");
+
+        let request = http::Request::builder().uri("/trading.TradingEngine/GetEngineInfo").header(REQUEST_ID_HEADER, "x".repeat(500)).body(()).unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        let generated = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(generated).is_ok());
+        assert_eq!(response.headers()["x-seen"], generated);
+    }
+}
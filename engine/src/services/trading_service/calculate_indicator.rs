@@ -6,9 +6,9 @@ use serde_json; // For Value
 
 use crate::data::market_data::MarketDataStore;
 use crate::services::{IndicatorRequest, IndicatorResponse};
-use shared::models::TimeFrame; // DomainCandle not directly used due to IndicatorCalculator taking &[Candle]
+use super::helpers::from_grpc_timeframe;
 use crate::error::EngineError;
-use crate::indicators::{IndicatorCalculator, Sma, Ema, Rsi};
+use crate::indicators::{IndicatorCalculator, Sma, Ema, Rsi, Atr, Vwap};
 
 pub async fn handle_calculate_indicator(
     req_payload: IndicatorRequest,
@@ -16,68 +16,59 @@ pub async fn handle_calculate_indicator(
 ) -> Result<Response<IndicatorResponse>, Status> {
     tracing::debug!(symbol = %req_payload.symbol, indicator_type = %req_payload.indicator_type, "Handling CalculateIndicatorRequest in dedicated handler");
 
-    let timeframe = TimeFrame::Day1;
+    let timeframe = from_grpc_timeframe(req_payload.timeframe)?;
     let store = market_data_store.read().await;
-    // .get_candles returns Option<Vec<DomainCandle>>, which is an owned type.
-    let candles = store.get_candles(&req_payload.symbol, timeframe, None, None);
+    // A snapshot of the series: its columns are extracted once and reused by later requests.
+    let candles = store.get_candles(&req_payload.symbol, timeframe, None, None).map_err(EngineError::from)?;
     drop(store); // Explicitly drop lock after data retrieval
 
-    if candles.is_none() || candles.as_ref().unwrap().is_empty() {
+    let Some(candle_data) = candles.filter(|c| !c.is_empty()) else {
         tracing::warn!(
             symbol = %req_payload.symbol,
             ?timeframe,
             "No candle data found to calculate indicator (handler)"
         );
-        return Err(EngineError::MarketDataError(format!("No candle data found for symbol '{}' and timeframe {:?} to calculate indicator", req_payload.symbol, timeframe)).into());
-    }
-    let candle_data = candles.unwrap(); // Now this is Vec<DomainCandle>
+        return Err(EngineError::MarketDataError(format!("No candle data found for symbol '{}' and timeframe {} to calculate indicator", req_payload.symbol, timeframe)).into());
+    };
+
+    let indicator_calculator = indicator_calculator(&req_payload.indicator_type, &req_payload.parameters)?;
+
+    let values = indicator_calculator.calculate_columns(candle_data.columns());
+
+    Ok(Response::new(IndicatorResponse {
+        indicator_name: indicator_calculator.name().to_string(),
+        values,
+    }))
+}
 
-    let params: serde_json::Value = match serde_json::from_str(&req_payload.parameters) {
+/// The calculator of `indicator_type` (`sma`, `ema`, `rsi`, `atr` or `vwap`, in any case) with its
+/// JSON `parameters`; also used by the ExportData RPC.
+pub fn indicator_calculator(indicator_type: &str, parameters: &str) -> Result<Box<dyn IndicatorCalculator>, EngineError> {
+    let params: serde_json::Value = match serde_json::from_str(parameters) {
         Ok(p) => p,
         Err(e) => {
             tracing::error!(
-                indicator_type = %req_payload.indicator_type,
-                parameters = %req_payload.parameters,
+                indicator_type = %indicator_type,
+                parameters = %parameters,
                 error_detail = ?e,
                 "Invalid JSON parameters for indicator (handler)"
             );
-            return Err(EngineError::ProcessingError(format!("Invalid JSON parameters for indicator '{}': {}", req_payload.indicator_type, e)).into());
+            return Err(EngineError::ProcessingError(format!("Invalid JSON parameters for indicator '{}': {}", indicator_type, e)));
         }
     };
 
-    let indicator_calculator: Box<dyn IndicatorCalculator> = match req_payload.indicator_type.to_lowercase().as_str() {
-        "sma" => {
-            let period = params.get("period").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
-            if period == 0 {
-                return Err(EngineError::IndicatorError("Indicator period cannot be 0".to_string()).into());
-            }
-            Box::new(Sma::new(period))
-        }
-        "ema" => {
-            let period = params.get("period").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
-             if period == 0 {
-                return Err(EngineError::IndicatorError("Indicator period cannot be 0".to_string()).into());
-            }
-            Box::new(Ema::new(period))
-        }
-        "rsi" => {
-            let period = params.get("period").and_then(|v| v.as_u64()).unwrap_or(14) as usize;
-             if period == 0 {
-                return Err(EngineError::IndicatorError("Indicator period cannot be 0".to_string()).into());
-            }
-            Box::new(Rsi::new(period))
-        }
+    // A period of 0 is refused by the indicator's constructor.
+    let period = |default: u64| params.get("period").and_then(|v| v.as_u64()).unwrap_or(default) as usize;
+    let indicator_calculator: Box<dyn IndicatorCalculator> = match indicator_type.to_lowercase().as_str() {
+        "sma" => Box::new(Sma::new(period(20))?),
+        "ema" => Box::new(Ema::new(period(20))?),
+        "rsi" => Box::new(Rsi::new(period(14))?),
+        "atr" => Box::new(Atr::new(period(14))?),
+        "vwap" => Box::new(Vwap::new()),
         _ => {
-            tracing::error!(indicator_type = %req_payload.indicator_type, "Unknown indicator type requested (handler)");
-            return Err(EngineError::IndicatorError(format!("Unknown indicator type: {}", req_payload.indicator_type)).into());
+            tracing::error!(indicator_type = %indicator_type, "Unknown indicator type requested (handler)");
+            return Err(EngineError::IndicatorError(format!("Unknown indicator type: {}", indicator_type)));
         }
     };
-
-    // IndicatorCalculator::calculate expects &[DomainCandle]
-    let values = indicator_calculator.calculate(&candle_data);
-
-    Ok(Response::new(IndicatorResponse {
-        indicator_name: indicator_calculator.name().to_string(),
-        values,
-    }))
+    Ok(indicator_calculator)
 }
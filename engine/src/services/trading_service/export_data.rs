@@ -0,0 +1,42 @@
+// Handler for the ExportData RPC
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::data::export::{export_candles, ExportFormat, ExportedSeries};
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::services::{ExportDataRequest, ExportDataResponse};
+use super::calculate_indicator::indicator_calculator;
+use super::helpers::{from_grpc_timeframe, from_grpc_timestamp};
+
+pub async fn handle_export_data(
+    req_payload: ExportDataRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+) -> Result<Response<ExportDataResponse>, Status> {
+    let format = ExportFormat::parse(&req_payload.format)?;
+    let timeframe = from_grpc_timeframe(req_payload.timeframe)?;
+    let from_ts = req_payload.from_timestamp.map(from_grpc_timestamp).transpose()?;
+    let to_ts = req_payload.to_timestamp.map(from_grpc_timestamp).transpose()?;
+    let calculators = req_payload.indicators.iter()
+        .map(|indicator| indicator_calculator(&indicator.indicator_type, &indicator.parameters))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let store = market_data_store.read().await;
+    let candles = store.get_candles(&req_payload.symbol, timeframe, None, None)
+        .map_err(EngineError::from)?
+        .ok_or_else(|| EngineError::MarketDataError(format!(
+            "Market data not found for symbol '{}' and timeframe {}", req_payload.symbol, timeframe
+        )))?;
+    drop(store);
+
+    // Indicators are calculated over the whole series, then cut to the range like the candles
+    let first = from_ts.map_or(0, |from| candles.position(from));
+    let end = to_ts.map_or(candles.len(), |to| candles.partition_point(|candle| candle.timestamp <= to)).max(first);
+    let series: Vec<ExportedSeries> = calculators.iter()
+        .map(|calculator| ExportedSeries { name: calculator.name().to_string(), values: calculator.calculate_columns(candles.columns())[first..end].to_vec() })
+        .collect();
+    let contents = export_candles(&req_payload.symbol, timeframe, &candles[first..end], &series, format)?;
+    tracing::debug!(symbol = %req_payload.symbol, ?timeframe, ?format, candles = end - first, "Exported candles (handler).");
+    Ok(Response::new(ExportDataResponse { contents, candles_exported: (end - first) as u32 }))
+}
@@ -0,0 +1,49 @@
+// Handler for the GetAggregatedCandles RPC
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::data::market_data::MarketDataStore;
+use crate::data::resample::{aggregates_into, bar_duration, resample};
+use crate::error::EngineError;
+use crate::services::{AggregatedCandlesRequest, AggregatedCandlesResponse, GrpcTimeFrame};
+use super::helpers::{from_grpc_timeframe, from_grpc_timestamp, to_grpc_candle};
+
+pub async fn handle_get_aggregated_candles(
+    req_payload: AggregatedCandlesRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+) -> Result<Response<AggregatedCandlesResponse>, Status> {
+    let timeframe = from_grpc_timeframe(req_payload.timeframe)?;
+    let from_ts = req_payload.from_timestamp.map(from_grpc_timestamp).transpose()?;
+    let to_ts = req_payload.to_timestamp.map(from_grpc_timestamp).transpose()?;
+
+    let store = market_data_store.read().await;
+    let source = if req_payload.source_timeframe == GrpcTimeFrame::Unspecified as i32 {
+        // The coarsest usable series has the fewest candles to aggregate (and is exact if it is `timeframe` itself).
+        store.timeframes(&req_payload.symbol).into_iter()
+            .filter(|source| aggregates_into(*source, timeframe))
+            .max_by_key(|source| bar_duration(*source).map_or(i64::MAX, |d| d.num_seconds()))
+            .ok_or_else(|| EngineError::MarketDataError(format!(
+                "Market data not found for symbol '{}' in any timeframe that aggregates into {}", req_payload.symbol, timeframe
+            )))?
+    } else {
+        let source = from_grpc_timeframe(req_payload.source_timeframe)?;
+        if !aggregates_into(source, timeframe) {
+            return Err(EngineError::InvalidRequest(format!("{} candles cannot be aggregated into {}", source, timeframe)).into());
+        }
+        source
+    };
+    let candles = store.get_candles(&req_payload.symbol, source, from_ts, to_ts)
+        .map_err(EngineError::from)?
+        .ok_or_else(|| EngineError::MarketDataError(format!(
+            "Market data not found for symbol '{}' and timeframe {}", req_payload.symbol, source
+        )))?;
+    drop(store);
+
+    let bars = if source == timeframe { candles } else { resample(&candles, timeframe) };
+    tracing::debug!(symbol = %req_payload.symbol, ?source, ?timeframe, candles = bars.len(), "Aggregated candles (handler).");
+    Ok(Response::new(AggregatedCandlesResponse {
+        candles: bars.iter().map(to_grpc_candle).collect(),
+        source_timeframe: GrpcTimeFrame::from(source) as i32,
+    }))
+}
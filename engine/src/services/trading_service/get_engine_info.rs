@@ -0,0 +1,40 @@
+// Handler for the GetEngineInfo RPC
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::data::market_data::MarketDataStore;
+use crate::services::{EngineInfo, GrpcSeriesSummary, GrpcTimeFrame, API_VERSION};
+
+pub async fn handle_get_engine_info(
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    started_at: Instant,
+    features: &[String],
+) -> Result<Response<EngineInfo>, Status> {
+    let series = series_summaries(&market_data_store).await;
+    Ok(Response::new(EngineInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        api_version: API_VERSION,
+        build_profile: if cfg!(debug_assertions) { "debug" } else { "release" }.to_string(),
+        build_target: env!("BUILD_TARGET").to_string(),
+        uptime_seconds: started_at.elapsed().as_secs(),
+        series,
+        features: features.to_vec(),
+    }))
+}
+
+/// Every loaded series, sorted by symbol then timeframe.
+pub(super) async fn series_summaries(market_data_store: &RwLock<MarketDataStore>) -> Vec<GrpcSeriesSummary> {
+    let mut series: Vec<GrpcSeriesSummary> = market_data_store.read().await.summaries().into_iter()
+        .map(|summary| GrpcSeriesSummary {
+            symbol: summary.symbol,
+            timeframe: GrpcTimeFrame::from(summary.timeframe) as i32,
+            candles: summary.candles as u64,
+            first_timestamp: summary.first.map(|t| t.timestamp_millis()).unwrap_or_default(),
+            last_timestamp: summary.last.map(|t| t.timestamp_millis()).unwrap_or_default(),
+        })
+        .collect();
+    series.sort_by(|a, b| a.symbol.cmp(&b.symbol).then(a.timeframe.cmp(&b.timeframe)));
+    series
+}
@@ -3,23 +3,30 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Response, Status}; // Removed Request
-use tokio::sync::mpsc;
+use tracing::Instrument;
+use tokio::sync::{broadcast, mpsc};
 
 use crate::data::market_data::MarketDataStore;
-// Assuming ProtoCandle is accessible from crate::services module where it's aliased
-use crate::services::{MarketDataRequest, MarketDataResponse, ProtoCandle as GrpcCandle};
-use shared::models::{/*Candle as DomainCandle,*/ TimeFrame}; // DomainCandle not directly used here due to helpers
-use super::helpers::{to_grpc_candle, from_grpc_timestamp};
-// use crate::error::EngineError; // from_grpc_timestamp is in helpers, which handles EngineError internally or returns it. This handler converts to Status.
+use crate::error::EngineError;
+use crate::simulation::ReplayController;
+use crate::services::{MarketDataRequest, MarketDataResponse};
+use shared::models::{CandleSeries, TimeFrame};
+use super::helpers::{to_grpc_candle, from_grpc_timeframe, from_grpc_timestamp};
+
+/// Candles per stream message when the request does not say.
+pub const DEFAULT_CANDLES_PER_MESSAGE: usize = 5_000;
+/// Upper bound on candles per message, keeping each well under gRPC's 4 MB default message limit.
+pub const MAX_CANDLES_PER_MESSAGE: usize = 20_000;
 
 pub async fn handle_get_market_data(
     req_payload: MarketDataRequest,
-    market_data_store: Arc<RwLock<MarketDataStore>>
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    replay: Arc<RwLock<ReplayController>>,
 ) -> Result<Response<ReceiverStream<Result<MarketDataResponse, Status>>>, Status> {
     // Main method logs initial reception.
     tracing::debug!(symbol = %req_payload.symbol, "Handling GetMarketDataRequest in dedicated handler");
 
-    let timeframe = TimeFrame::Day1;
+    let timeframe = from_grpc_timeframe(req_payload.timeframe)?;
 
     let from_ts = match from_grpc_timestamp(req_payload.from_timestamp) {
         Ok(ts) => ts,
@@ -36,12 +43,30 @@ pub async fn handle_get_market_data(
         }
     };
 
-    let store = market_data_store.read().await;
-    // .get_candles returns Option<Vec<DomainCandle>>, which is an owned type.
-    // So, the read lock on `store` is released after this line if `candles` is used later without store.
-    let candles = store.get_candles(&req_payload.symbol, timeframe, Some(from_ts), Some(to_ts));
-    drop(store); // Explicitly drop lock after data retrieval
+    // While a replay is active for the symbol, only the candles revealed so far are visible.
+    // Subscribing under the same lock guarantees no replayed candle falls between history and live updates.
+    // Replays run on the daily series; other timeframes are read from the store as usual.
+    let replay_guard = replay.read().await;
+    let replayed = timeframe == TimeFrame::Day1;
+    let replay_rx = if req_payload.follow_replay && replayed { replay_guard.subscribe(&req_payload.symbol) } else { None };
+    let source = match replay_guard.visible_candles(&req_payload.symbol).filter(|_| replayed) {
+        Some(visible) => Some(Source::Replay(visible.slice(Some(from_ts), Some(to_ts)))),
+        None => market_data_store.read().await
+            .count_candles(&req_payload.symbol, timeframe, Some(from_ts), Some(to_ts))
+            .map_err(EngineError::from)?
+            .map(Source::Store),
+    };
+    drop(replay_guard); // Explicitly drop lock after data retrieval
+
+    // Page through the range: `total` lets clients ask for the next page.
+    let offset = req_payload.offset as usize;
+    let limit = if req_payload.limit == 0 { usize::MAX } else { req_payload.limit as usize };
+    let per_message = match req_payload.max_candles_per_message as usize {
+        0 => DEFAULT_CANDLES_PER_MESSAGE,
+        n => n.min(MAX_CANDLES_PER_MESSAGE),
+    };
 
+    // A bounded channel: batches are read from the store only as fast as the client takes them.
     let (tx, rx) = mpsc::channel(4);
 
     // Clone what's needed for the spawned task. `req_payload.symbol` for logging.
@@ -49,25 +74,10 @@ pub async fn handle_get_market_data(
     let symbol_for_log = req_payload.symbol.clone();
 
     tokio::spawn(async move {
-        if let Some(domain_candles) = candles { // `candles` is moved into the async block
-            if domain_candles.is_empty() {
-                tracing::warn!(symbol = %symbol_for_log, ?timeframe, from_ts = ?from_ts, to_ts = ?to_ts, "No market data found in the given range (handler).");
-                let response = MarketDataResponse { candles: vec![] };
-                if let Err(e) = tx.send(Ok(response)).await {
-                    tracing::error!(error = ?e, symbol = %symbol_for_log, "Failed to send empty market data to stream (handler)");
-                }
-                return;
-            }
-            let grpc_candles: Vec<GrpcCandle> = domain_candles.iter().map(to_grpc_candle).collect();
-            tracing::debug!(symbol = %symbol_for_log, count = grpc_candles.len(), "Streaming market data (handler).");
-            let response = MarketDataResponse { candles: grpc_candles };
-            if let Err(e) = tx.send(Ok(response)).await {
-                tracing::error!(error = ?e, symbol = %symbol_for_log, "Failed to send market data to stream (handler)");
-            }
-        } else {
+        let Some(source) = source else {
             tracing::warn!(symbol = %symbol_for_log, ?timeframe, "No market data available (symbol/timeframe not found in store) (handler).");
             let status_msg = format!(
-                "Market data not found for symbol '{}' and timeframe {:?}",
+                "Market data not found for symbol '{}' and timeframe {}",
                 symbol_for_log, timeframe
             );
             // Send a NotFound status over the channel
@@ -77,8 +87,86 @@ pub async fn handle_get_market_data(
             if let Err(e) = tx.send(Err(status)).await {
                 tracing::error!(error = ?e, symbol = %symbol_for_log, "Failed to send NotFound status to stream (handler)");
             }
+            return;
+        };
+
+        let total = source.total();
+        let wanted = total.saturating_sub(offset).min(limit);
+        if wanted == 0 {
+            tracing::warn!(symbol = %symbol_for_log, ?timeframe, from_ts = ?from_ts, to_ts = ?to_ts, "No market data found in the given range (handler).");
+            let response = MarketDataResponse { candles: vec![], total: total as u32 };
+            if let Err(e) = tx.send(Ok(response)).await {
+                tracing::error!(error = ?e, symbol = %symbol_for_log, "Failed to send empty market data to stream (handler)");
+                return;
+            }
+        } else {
+            tracing::debug!(symbol = %symbol_for_log, count = wanted, per_message, "Streaming market data (handler).");
+            // Only one batch is held at a time. Store batches resume after the last timestamp sent,
+            // so candles added meanwhile do not shift the pages.
+            let (mut sent, mut resume_from, mut skip) = (0, from_ts, offset);
+            while sent < wanted {
+                let size = per_message.min(wanted - sent);
+                let batch = match &source {
+                    Source::Replay(visible) => visible[offset + sent..offset + sent + size].to_vec(),
+                    Source::Store(_) => match market_data_store.read().await
+                        .get_candles_page(&symbol_for_log, timeframe, Some(resume_from), Some(to_ts), skip, size)
+                    {
+                        Ok(batch) => batch.unwrap_or_default(),
+                        Err(e) => {
+                            tracing::error!(error = %e, symbol = %symbol_for_log, "Failed to read market data from the candle store (handler)");
+                            let _ = tx.send(Err(EngineError::from(e).into())).await;
+                            return;
+                        }
+                    },
+                };
+                let Some(last) = batch.last() else { break }; // The series shrank under us
+                resume_from = last.timestamp + chrono::Duration::nanoseconds(1);
+                skip = 0;
+                sent += batch.len();
+                let response = MarketDataResponse { candles: batch.iter().map(to_grpc_candle).collect(), total: total as u32 };
+                if let Err(e) = tx.send(Ok(response)).await {
+                    tracing::error!(error = ?e, symbol = %symbol_for_log, "Failed to send market data to stream (handler)");
+                    return;
+                }
+            }
+        }
+
+        // Follow an active replay: push each revealed candle until the replay ends or the client goes away.
+        if let Some(mut live_rx) = replay_rx {
+            loop {
+                match live_rx.recv().await {
+                    Ok(candle) => {
+                        let response = MarketDataResponse { candles: vec![to_grpc_candle(&candle)], total: 0 };
+                        if tx.send(Ok(response)).await.is_err() {
+                            tracing::debug!(symbol = %symbol_for_log, "Client disconnected from replay stream (handler).");
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(symbol = %symbol_for_log, skipped, "Replay stream subscriber lagged; candles were skipped (handler).");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
         }
-    });
+    }.in_current_span());
 
     Ok(Response::new(ReceiverStream::new(rx)))
 }
+
+/// Where a GetMarketData stream reads its candles from.
+enum Source {
+    /// The candles of the range revealed by an active replay.
+    Replay(CandleSeries),
+    /// The store's series, read a batch at a time; holds the number of candles in the range.
+    Store(usize),
+}
+
+impl Source {
+    fn total(&self) -> usize {
+        match self {
+            Source::Replay(candles) => candles.len(),
+            Source::Store(total) => *total,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+// Handler for the GetSeriesStats RPC
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::services::{GrpcSeriesStats, SeriesStatsRequest};
+use shared::models::MarketData;
+use super::helpers::{from_grpc_timeframe, from_grpc_timestamp, to_grpc_price};
+
+pub async fn handle_get_series_stats(
+    req_payload: SeriesStatsRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+) -> Result<Response<GrpcSeriesStats>, Status> {
+    let timeframe = from_grpc_timeframe(req_payload.timeframe)?;
+    let from_ts = req_payload.from_timestamp.map(from_grpc_timestamp).transpose()?;
+    let to_ts = req_payload.to_timestamp.map(from_grpc_timestamp).transpose()?;
+
+    let candles = market_data_store.read().await.get_candles(&req_payload.symbol, timeframe, from_ts, to_ts)
+        .map_err(EngineError::from)?
+        .ok_or_else(|| EngineError::MarketDataError(format!(
+            "Market data not found for symbol '{}' and timeframe {}", req_payload.symbol, timeframe
+        )))?;
+    let data = MarketData { symbol: req_payload.symbol, candles: candles.into_vec(), timeframe };
+    let stats = data.stats().ok_or_else(|| EngineError::MarketDataError(format!(
+        "Market data not found for symbol '{}' and timeframe {} in the requested range", data.symbol, timeframe
+    )))?;
+
+    Ok(Response::new(GrpcSeriesStats {
+        candles: stats.candles as u64,
+        first_timestamp: stats.first.timestamp_millis(),
+        last_timestamp: stats.last.timestamp_millis(),
+        open: to_grpc_price(stats.open),
+        close: to_grpc_price(stats.close),
+        high: to_grpc_price(stats.high),
+        low: to_grpc_price(stats.low),
+        change_pct: stats.change_pct,
+        average_volume: stats.average_volume,
+        volatility_pct: stats.volatility_pct,
+    }))
+}
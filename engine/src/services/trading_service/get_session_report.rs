@@ -0,0 +1,62 @@
+// Handler for the GetSessionReport RPC
+// Reports on the paper-trading session: every fill booked by SimulateTrade, resting
+// orders and replays, with positions marked at the candles known to the engine.
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::backtest::build_report;
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::services::{GrpcPnlReport, SessionReportRequest};
+use crate::simulation::{Contracts, Fill, Portfolio, ReplayController};
+use shared::models::TimeFrame;
+use super::helpers::{latest_closes, to_grpc_margin_status, to_grpc_pnl_report};
+
+pub async fn handle_get_session_report(
+    req_payload: SessionReportRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    replay: Arc<RwLock<ReplayController>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    contracts: Contracts,
+) -> Result<Response<GrpcPnlReport>, Status> {
+    let timeframe = TimeFrame::Day1;
+
+    let replay_guard = replay.read().await;
+    let store = market_data_store.read().await;
+    let portfolio_guard = portfolio.read().await;
+    let initial_capital = portfolio_guard.initial_capital;
+    let margin = portfolio_guard.margin_status(&latest_closes(portfolio_guard.positions().map(|p| p.symbol.as_str()), &replay_guard, &store));
+    let fills: Vec<Fill> = portfolio_guard.fills()
+        .filter(|f| req_payload.symbol.is_empty() || f.symbol == req_payload.symbol)
+        .cloned()
+        .collect();
+    drop(portfolio_guard);
+
+    // The equity curve starts at the first fill; during a replay only revealed candles are used.
+    let mut marks = HashMap::new();
+    if let Some(session_start) = fills.iter().map(|f| f.timestamp).min() {
+        for fill in &fills {
+            if marks.contains_key(&fill.symbol) {
+                continue;
+            }
+            let candles = match replay_guard.visible_candles(&fill.symbol) {
+                Some(visible) => visible,
+                None => store.get_candles(&fill.symbol, timeframe, Some(session_start), None).map_err(EngineError::from)?.unwrap_or_default(),
+            };
+            let candles = candles
+                .range(Some(session_start), None)
+                .to_vec();
+            marks.insert(fill.symbol.clone(), candles);
+        }
+    }
+
+    drop(store);
+    drop(replay_guard);
+
+    let report = build_report(initial_capital, &fills, &marks, &contracts);
+    let mut response = to_grpc_pnl_report(&report);
+    response.margin = Some(to_grpc_margin_status(&margin));
+    Ok(Response::new(response))
+}
@@ -0,0 +1,21 @@
+// Handler for the GetTradeHistory RPC (trade blotter)
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::services::{TradeHistoryRequest, TradeHistoryResponse};
+use crate::simulation::Portfolio;
+use super::helpers::{from_grpc_timestamp, to_grpc_execution};
+
+pub async fn handle_get_trade_history(
+    req_payload: TradeHistoryRequest,
+    portfolio: Arc<RwLock<Portfolio>>,
+) -> Result<Response<TradeHistoryResponse>, Status> {
+    let from_ts = req_payload.from_timestamp.map(from_grpc_timestamp).transpose()?;
+    let to_ts = req_payload.to_timestamp.map(from_grpc_timestamp).transpose()?;
+    let symbol = Some(req_payload.symbol.as_str()).filter(|s| !s.is_empty());
+
+    let portfolio = portfolio.read().await;
+    let executions = portfolio.history(symbol, from_ts, to_ts).into_iter().map(to_grpc_execution).collect();
+    Ok(Response::new(TradeHistoryResponse { executions }))
+}
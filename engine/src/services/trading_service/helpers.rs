@@ -8,16 +8,72 @@ use shared::models::Candle as DomainCandle;
 // or ensure `ProtoCandle` is re-exported at a higher level accessible here.
 // For now, assuming `crate::services::ProtoCandle` is the way.
 use crate::services::ProtoCandle as GrpcCandle;
+use crate::services::ReplayStatus as GrpcReplayStatus;
+use crate::services::{GrpcEquityPoint, GrpcMarginStatus, GrpcMonteCarloSummary, GrpcPerformanceMetrics, GrpcPnlReport, GrpcTradeRecord, PercentileStats};
+use crate::data::market_data::MarketDataStore;
+use crate::services::{GrpcExecution, OrderState as GrpcOrderState};
+use crate::services::PositionSizing;
+use crate::services::PositionState;
+use crate::simulation::{Execution, MarginStatus, Order, Position, ReplayController, ReplaySnapshot, RiskLimits, RiskViolation, SizingMethod};
+use crate::services::{GrpcRiskLimits, GrpcRiskViolation, GrpcTimeFrame};
+use crate::services::jobs::JobStatus;
+use crate::services::GrpcJobStatus;
+use crate::services::{GrpcAlert, GrpcAlertEvent};
+use crate::simulation::{AlertCondition, AlertTrigger, PriceAlert};
+use shared::models::{Price, TimeFrame};
+use std::collections::HashMap;
+use crate::backtest::{BacktestEvent, BenchmarkComparison, CarryCosts, CostCharge, CostKind, MonteCarloSummary, PerformanceMetrics, Percentiles, PnlReport, StressResult, StressScenario};
+use crate::services::generated::{backtest_event::Event as GrpcEvent, BacktestFillEvent, BacktestOrderEvent, BacktestPositionEvent};
+use crate::services::{GrpcBacktestEvent, GrpcBenchmarkComparison, GrpcCarryCosts, GrpcCostCharge, GrpcStressResult, GrpcStressScenario};
+use std::time::{Duration, Instant};
+use tonic::Request;
 
+/// Time kept in hand before a client's deadline, so the engine's own DEADLINE_EXCEEDED
+/// reaches the client before tonic cancels the call.
+const DEADLINE_MARGIN: Duration = Duration::from_millis(5);
+
+/// When work on `request` must stop: the client's `grpc-timeout` from now, less `DEADLINE_MARGIN`.
+/// `None` if the client set no deadline (or an unreadable one, which tonic ignores as well).
+pub fn request_deadline<T>(request: &Request<T>) -> Option<Instant> {
+    let timeout = request.metadata().get("grpc-timeout")?.to_str().ok()?;
+    if timeout.is_empty() || timeout.len() > 9 {
+        return None;
+    }
+    let (value, unit) = timeout.split_at(timeout.len() - 1);
+    let value: u64 = value.parse().ok()?;
+    let timeout = match unit {
+        "H" => Duration::from_secs(value * 3600),
+        "M" => Duration::from_secs(value * 60),
+        "S" => Duration::from_secs(value),
+        "m" => Duration::from_millis(value),
+        "u" => Duration::from_micros(value),
+        "n" => Duration::from_nanos(value),
+        _ => return None,
+    };
+    Some(Instant::now() + timeout.saturating_sub(DEADLINE_MARGIN))
+}
+
+/// `price` as the proto sends it: an exact decimal string without trailing zeros, e.g. "30.15".
+pub fn to_grpc_price(price: Price) -> String {
+    price.normalize().to_string()
+}
+
+/// The price a client sent in `field` as a decimal string; an empty string is refused like any other non-number.
+pub fn from_grpc_price(field: &str, value: &str) -> Result<Price, EngineError> {
+    value.trim().parse().map_err(|_| EngineError::InvalidField {
+        field: field.to_string(),
+        message: format!("'{}' is not a decimal price", value),
+    })
+}
 
 pub fn to_grpc_candle(domain_candle: &DomainCandle) -> GrpcCandle {
     GrpcCandle {
         symbol: domain_candle.symbol.clone(),
         timestamp: domain_candle.timestamp.timestamp_millis(),
-        open: domain_candle.open,
-        high: domain_candle.high,
-        low: domain_candle.low,
-        close: domain_candle.close,
+        open: to_grpc_price(domain_candle.open),
+        high: to_grpc_price(domain_candle.high),
+        low: to_grpc_price(domain_candle.low),
+        close: to_grpc_price(domain_candle.close),
         volume: domain_candle.volume,
         trades: domain_candle.trades as i32,
     }
@@ -27,3 +83,368 @@ pub fn from_grpc_timestamp(ts_millis: i64) -> Result<chrono::DateTime<chrono::Ut
     chrono::DateTime::from_timestamp_millis(ts_millis)
         .ok_or_else(|| EngineError::ProcessingError(format!("Invalid gRPC timestamp: {}", ts_millis)))
 }
+
+pub fn to_grpc_replay_status(snapshot: &ReplaySnapshot) -> GrpcReplayStatus {
+    GrpcReplayStatus {
+        symbol: snapshot.symbol.clone(),
+        state: snapshot.state.as_str().to_string(),
+        position: snapshot.position as u32,
+        total: snapshot.total as u32,
+        speed: snapshot.speed,
+        current_timestamp: snapshot.current_timestamp.map(|ts| ts.timestamp_millis()),
+        market_phase: snapshot.market_phase.as_str().to_string(),
+    }
+}
+
+pub fn to_grpc_pnl_report(report: &PnlReport) -> GrpcPnlReport {
+    GrpcPnlReport {
+        initial_capital: report.initial_capital,
+        final_equity: report.final_equity,
+        total_pnl: report.total_pnl,
+        realized_pnl: report.realized_pnl,
+        total_costs: report.total_costs,
+        trades: report.trades.iter().map(|t| GrpcTradeRecord {
+            symbol: t.symbol.clone(),
+            direction: t.direction.as_str().to_string(),
+            entry_timestamp: t.entry_time.timestamp_millis(),
+            exit_timestamp: t.exit_time.timestamp_millis(),
+            entry_price: to_grpc_price(t.entry_price),
+            exit_price: to_grpc_price(t.exit_price),
+            quantity: t.quantity,
+            pnl: to_grpc_price(t.pnl),
+            return_pct: t.return_pct,
+            mae: to_grpc_price(t.mae),
+            mfe: to_grpc_price(t.mfe),
+        }).collect(),
+        equity_curve: report.equity_curve.iter().map(|p| GrpcEquityPoint {
+            timestamp: p.timestamp.timestamp_millis(),
+            equity: p.equity,
+        }).collect(),
+        margin: None,
+    }
+}
+
+pub fn to_grpc_metrics(metrics: &PerformanceMetrics) -> GrpcPerformanceMetrics {
+    GrpcPerformanceMetrics {
+        total_trades: metrics.total_trades as u32,
+        sharpe_ratio: metrics.sharpe_ratio,
+        sortino_ratio: metrics.sortino_ratio,
+        max_drawdown: metrics.max_drawdown,
+        max_drawdown_pct: metrics.max_drawdown_pct,
+        win_rate: metrics.win_rate,
+        profit_factor: metrics.profit_factor,
+        expectancy: metrics.expectancy,
+        avg_mae: metrics.avg_mae,
+        avg_mfe: metrics.avg_mfe,
+    }
+}
+
+fn to_grpc_percentiles(p: &Percentiles) -> PercentileStats {
+    PercentileStats { p5: p.p5, p25: p.p25, p50: p.p50, p75: p.p75, p95: p.p95 }
+}
+
+pub fn to_grpc_monte_carlo_summary(summary: &MonteCarloSummary) -> GrpcMonteCarloSummary {
+    GrpcMonteCarloSummary {
+        iterations: summary.iterations as u32,
+        return_pct: Some(to_grpc_percentiles(&summary.return_pct)),
+        max_drawdown_pct: Some(to_grpc_percentiles(&summary.max_drawdown_pct)),
+        probability_of_loss: summary.probability_of_loss,
+    }
+}
+
+/// Latest known close per symbol, used to mark portfolio positions.
+/// During a replay only the revealed candles count.
+pub fn latest_closes<'a>(
+    symbols: impl Iterator<Item = &'a str>,
+    replay: &ReplayController,
+    store: &MarketDataStore,
+) -> HashMap<String, f64> {
+    symbols
+        .filter_map(|symbol| {
+            let close = match replay.current_candle(symbol) {
+                Some(candle) => candle.close_f64(),
+                None => store.last_candle(symbol, TimeFrame::Day1)?.close_f64(),
+            };
+            Some((symbol.to_string(), close))
+        })
+        .collect()
+}
+
+pub fn to_grpc_margin_status(status: &MarginStatus) -> GrpcMarginStatus {
+    GrpcMarginStatus {
+        equity: status.equity,
+        initial_margin: status.initial_margin,
+        maintenance_margin: status.maintenance_margin,
+        buying_power: status.buying_power,
+        margin_call: status.margin_call,
+    }
+}
+
+pub fn to_grpc_order_state(order: &Order) -> GrpcOrderState {
+    GrpcOrderState {
+        order_id: order.id.clone(),
+        symbol: order.symbol.clone(),
+        side: order.side.as_str().to_string(),
+        quantity: order.quantity,
+        limit_price: to_grpc_price(order.limit_price),
+        time_in_force: order.time_in_force.as_str().to_string(),
+        status: order.status.as_str().to_string(),
+        placed_at: order.placed_at.timestamp_millis(),
+        filled_price: order.filled_price.map(to_grpc_price),
+        filled_at: order.filled_at.map(|ts| ts.timestamp_millis()),
+    }
+}
+
+pub fn to_grpc_alert(alert: &PriceAlert) -> GrpcAlert {
+    let (level, indicator, period, direction) = match &alert.condition {
+        AlertCondition::PriceCross { level } => (*level, "", 0, ""),
+        AlertCondition::Indicator { indicator, threshold, direction } => (*threshold, indicator.name(), indicator.period() as u32, direction.as_str()),
+        AlertCondition::PercentMove { percent } => (*percent, "", 0, ""),
+    };
+    GrpcAlert {
+        alert_id: alert.id.clone(),
+        symbol: alert.symbol.clone(),
+        timeframe: GrpcTimeFrame::from(alert.timeframe) as i32,
+        kind: alert.condition.kind().to_string(),
+        level,
+        indicator: indicator.to_string(),
+        period,
+        direction: direction.to_string(),
+        note: alert.note.clone(),
+        created_at: alert.created_at.timestamp_millis(),
+        reference_price: alert.reference_price,
+    }
+}
+
+pub fn to_grpc_alert_event(trigger: &AlertTrigger) -> GrpcAlertEvent {
+    GrpcAlertEvent {
+        alert: Some(to_grpc_alert(&trigger.alert)),
+        timestamp: trigger.timestamp.timestamp_millis(),
+        price: trigger.price,
+        value: trigger.value,
+        source: trigger.source.to_string(),
+    }
+}
+
+pub fn to_grpc_execution(execution: &Execution) -> GrpcExecution {
+    GrpcExecution {
+        order_id: execution.fill.order_id.clone(),
+        symbol: execution.fill.symbol.clone(),
+        side: execution.fill.side.as_str().to_string(),
+        price: to_grpc_price(execution.fill.price),
+        quantity: execution.fill.quantity,
+        timestamp: execution.fill.timestamp.timestamp_millis(),
+        position_after: execution.position_after,
+        average_price_after: to_grpc_price(execution.average_price_after),
+        realized_pnl: to_grpc_price(execution.realized_pnl),
+    }
+}
+
+pub fn to_grpc_position_state(position: &Position) -> PositionState {
+    PositionState {
+        symbol: position.symbol.clone(),
+        quantity: position.quantity,
+        average_price: to_grpc_price(position.average_price),
+        realized_pnl: to_grpc_price(position.realized_pnl),
+    }
+}
+
+pub fn to_grpc_job_status(status: &JobStatus) -> GrpcJobStatus {
+    GrpcJobStatus {
+        job_id: status.id.clone(),
+        kind: status.kind.to_string(),
+        state: status.state.as_str().to_string(),
+        progress: status.progress,
+        message: status.message.clone(),
+        submitted_at: status.submitted_at.timestamp_millis(),
+        finished_at: status.finished_at.map(|at| at.timestamp_millis()).unwrap_or_default(),
+        error: status.error.clone().unwrap_or_default(),
+    }
+}
+
+/// Default stop distance, in ATRs, for ATR sizing.
+pub const DEFAULT_ATR_MULTIPLE: f64 = 2.0;
+/// Default fraction of the full Kelly bet.
+pub const DEFAULT_KELLY_FRACTION: f64 = 0.5;
+
+pub fn from_grpc_sizing(sizing: &PositionSizing) -> Result<SizingMethod, EngineError> {
+    let or_default = |value: f64, default: f64| if value == 0.0 { default } else { value };
+    match sizing.method.trim().to_uppercase().as_str() {
+        "FIXED_CONTRACTS" => Ok(SizingMethod::FixedContracts { contracts: sizing.contracts }),
+        "FIXED_FRACTIONAL" => Ok(SizingMethod::FixedFractional { risk_fraction: sizing.risk_fraction }),
+        "ATR" => Ok(SizingMethod::AtrRisk {
+            risk_fraction: sizing.risk_fraction,
+            atr_multiple: or_default(sizing.atr_multiple, DEFAULT_ATR_MULTIPLE),
+        }),
+        "KELLY" => Ok(SizingMethod::Kelly {
+            win_rate: sizing.win_rate,
+            payoff_ratio: sizing.payoff_ratio,
+            fraction: or_default(sizing.kelly_fraction, DEFAULT_KELLY_FRACTION),
+        }),
+        other => Err(EngineError::InvalidRequest(format!(
+            "Unsupported sizing method: '{}'. Use 'FIXED_CONTRACTS', 'FIXED_FRACTIONAL', 'ATR' or 'KELLY'.",
+            other
+        ))),
+    }
+}
+
+pub fn from_grpc_carry_costs(costs: &GrpcCarryCosts) -> Result<CarryCosts, EngineError> {
+    let values = [costs.overnight_rate, costs.overnight_fee, costs.rollover_fee, costs.rollover_spread];
+    if values.iter().any(|v| !v.is_finite() || *v < 0.0) {
+        return Err(EngineError::InvalidRequest("Carrying costs must be non-negative numbers".to_string()));
+    }
+    Ok(CarryCosts {
+        overnight_rate: costs.overnight_rate,
+        overnight_fee: costs.overnight_fee,
+        rollover_fee: costs.rollover_fee,
+        rollover_spread: costs.rollover_spread,
+    })
+}
+
+pub fn to_grpc_cost_charge(charge: &CostCharge) -> GrpcCostCharge {
+    let (kind, description) = match &charge.kind {
+        CostKind::Overnight => ("OVERNIGHT", format!("{} held overnight", charge.symbol)),
+        CostKind::Rollover { from, to } => ("ROLLOVER", format!("{} -> {}", from, to)),
+    };
+    GrpcCostCharge {
+        timestamp: charge.timestamp.timestamp_millis(),
+        kind: kind.to_string(),
+        description,
+        amount: charge.amount,
+        symbol: charge.symbol.clone(),
+    }
+}
+
+pub fn to_grpc_backtest_event(event: &BacktestEvent) -> GrpcBacktestEvent {
+    let grpc_event = match event {
+        BacktestEvent::Candle { candle, .. } => GrpcEvent::Candle(to_grpc_candle(candle)),
+        BacktestEvent::Order { symbol, side, quantity, target, .. } => GrpcEvent::Order(BacktestOrderEvent {
+            side: side.as_str().to_string(),
+            quantity: *quantity,
+            target_position: *target,
+            symbol: symbol.clone(),
+        }),
+        BacktestEvent::Fill(fill) => GrpcEvent::Fill(BacktestFillEvent {
+            order_id: fill.order_id.clone(),
+            side: fill.side.as_str().to_string(),
+            price: to_grpc_price(fill.price),
+            quantity: fill.quantity,
+            symbol: fill.symbol.clone(),
+        }),
+        BacktestEvent::Position { symbol, quantity, average_price, realized_pnl, .. } => GrpcEvent::Position(BacktestPositionEvent {
+            quantity: *quantity,
+            average_price: to_grpc_price(*average_price),
+            realized_pnl: to_grpc_price(*realized_pnl),
+            symbol: symbol.clone(),
+        }),
+        BacktestEvent::Cost(charge) => GrpcEvent::Cost(to_grpc_cost_charge(charge)),
+    };
+    GrpcBacktestEvent { timestamp: event.timestamp().timestamp_millis(), event: Some(grpc_event) }
+}
+
+pub fn from_grpc_stress_scenario(scenario: &GrpcStressScenario) -> StressScenario {
+    let volatility_multiplier = if scenario.volatility_multiplier == 0.0 { 1.0 } else { scenario.volatility_multiplier };
+    let name = if scenario.name.trim().is_empty() {
+        format!("Gap {:+}%, volatility x{}, spread {}%", scenario.gap_pct, volatility_multiplier, scenario.spread_pct)
+    } else {
+        scenario.name.clone()
+    };
+    StressScenario {
+        name,
+        gap_pct: scenario.gap_pct,
+        gap_at: scenario.gap_at.map(|i| i as usize),
+        volatility_multiplier,
+        spread_pct: scenario.spread_pct,
+    }
+}
+
+pub fn to_grpc_stress_result(result: &StressResult) -> GrpcStressResult {
+    GrpcStressResult {
+        scenario: result.scenario.name.clone(),
+        total_pnl: result.total_pnl,
+        pnl_change: result.pnl_change,
+        pnl_change_pct: result.pnl_change_pct,
+        metrics: Some(to_grpc_metrics(&result.metrics)),
+    }
+}
+
+pub fn to_grpc_benchmark(comparison: &BenchmarkComparison) -> GrpcBenchmarkComparison {
+    GrpcBenchmarkComparison {
+        symbol: comparison.symbol.clone(),
+        equity_curve: comparison.equity_curve.iter().map(|p| GrpcEquityPoint {
+            timestamp: p.timestamp.timestamp_millis(),
+            equity: p.equity,
+        }).collect(),
+        benchmark_return_pct: comparison.benchmark_return_pct,
+        strategy_return_pct: comparison.strategy_return_pct,
+        excess_return_pct: comparison.excess_return_pct,
+        alpha: comparison.alpha,
+        beta: comparison.beta,
+        correlation: comparison.correlation,
+    }
+}
+
+pub fn to_grpc_risk_violation(violation: &RiskViolation) -> GrpcRiskViolation {
+    GrpcRiskViolation {
+        rule: violation.rule().to_string(),
+        symbol: match violation {
+            RiskViolation::MaxPosition { symbol, .. } => symbol.clone(),
+            _ => String::new(),
+        },
+        limit: violation.limit(),
+        value: violation.value(),
+    }
+}
+
+pub fn to_grpc_risk_limits(limits: &RiskLimits) -> GrpcRiskLimits {
+    GrpcRiskLimits {
+        max_position: limits.max_position,
+        max_position_by_symbol: limits.max_position_by_symbol.clone(),
+        max_daily_loss: limits.max_daily_loss,
+        max_open_orders: limits.max_open_orders.map(|n| n as u32),
+    }
+}
+
+pub fn from_grpc_risk_limits(limits: GrpcRiskLimits) -> Result<RiskLimits, EngineError> {
+    let limits = RiskLimits {
+        max_position: limits.max_position,
+        max_position_by_symbol: limits.max_position_by_symbol,
+        max_daily_loss: limits.max_daily_loss,
+        max_open_orders: limits.max_open_orders.map(|n| n as usize),
+    };
+    limits.validate().map_err(EngineError::InvalidRequest)?;
+    Ok(limits)
+}
+
+/// Maps a request's `timeframe`; unset (0) keeps the daily series older clients use.
+pub fn from_grpc_timeframe(value: i32) -> Result<TimeFrame, EngineError> {
+    GrpcTimeFrame::try_from(value)
+        .map(TimeFrame::from)
+        .map_err(|_| EngineError::InvalidField { field: "timeframe".to_string(), message: format!("Unknown timeframe {}", value) })
+}
+
+impl From<GrpcTimeFrame> for TimeFrame {
+    fn from(timeframe: GrpcTimeFrame) -> Self {
+        match timeframe {
+            GrpcTimeFrame::Unspecified | GrpcTimeFrame::Day1 => TimeFrame::Day1,
+            GrpcTimeFrame::Minute1 => TimeFrame::Minute1,
+            GrpcTimeFrame::Minute5 => TimeFrame::Minute5,
+            GrpcTimeFrame::Minute15 => TimeFrame::Minute15,
+            GrpcTimeFrame::Minute30 => TimeFrame::Minute30,
+            GrpcTimeFrame::Hour1 => TimeFrame::Hour1,
+        }
+    }
+}
+
+impl From<TimeFrame> for GrpcTimeFrame {
+    fn from(timeframe: TimeFrame) -> Self {
+        match timeframe {
+            TimeFrame::Minute1 => GrpcTimeFrame::Minute1,
+            TimeFrame::Minute5 => GrpcTimeFrame::Minute5,
+            TimeFrame::Minute15 => GrpcTimeFrame::Minute15,
+            TimeFrame::Minute30 => GrpcTimeFrame::Minute30,
+            TimeFrame::Hour1 => GrpcTimeFrame::Hour1,
+            TimeFrame::Day1 => GrpcTimeFrame::Day1,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+// Handler for the ListSymbols RPC
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::data::market_data::MarketDataStore;
+use crate::services::{GrpcSymbolSummary, ListSymbolsResponse};
+use super::get_engine_info::series_summaries;
+
+pub async fn handle_list_symbols(
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+) -> Result<Response<ListSymbolsResponse>, Status> {
+    let mut symbols: Vec<GrpcSymbolSummary> = Vec::new();
+    // Summaries come sorted by symbol, so each symbol's series are next to each other.
+    for series in series_summaries(&market_data_store).await {
+        match symbols.last_mut() {
+            Some(last) if last.symbol == series.symbol => last.series.push(series),
+            _ => symbols.push(GrpcSymbolSummary { symbol: series.symbol.clone(), series: vec![series] }),
+        }
+    }
+    Ok(Response::new(ListSymbolsResponse { symbols }))
+}
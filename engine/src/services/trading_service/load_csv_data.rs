@@ -1,25 +1,35 @@
-// Handler for the LoadCsvData RPC
+// Handlers for the LoadCsvData and LoadCsvBatch RPCs
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Instant;
+use tokio::sync::{RwLock, Semaphore};
+use tokio::task::JoinSet;
 use tonic::{Response, Status}; // Removed Request as it's not used directly here
+use tracing::Instrument;
 
-use crate::data::csv_parser::BrazilianCsvParser;
+use crate::data::csv_parser::{BrazilianCsvParser, CsvFormat, CsvLoad, SkippedRow};
 use crate::data::market_data::MarketDataStore;
 use crate::error::EngineError;
-use crate::services::{LoadCsvRequest, LoadCsvResponse}; // These come from services/mod.rs
+use crate::services::{CsvRowError, LoadCsvBatchRequest, LoadCsvBatchResponse, LoadCsvFileResult, LoadCsvRequest, LoadCsvResponse}; // These come from services/mod.rs
+use crate::simulation::{OrderBook, Portfolio};
 use shared::models::TimeFrame;
+use super::helpers::from_grpc_timeframe;
 
 pub async fn handle_load_csv_data(
     req_payload: LoadCsvRequest, // Changed from req to req_payload for clarity
-    market_data_store: Arc<RwLock<MarketDataStore>>
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    csv_format: CsvFormat,
+    deadline: Option<Instant>,
 ) -> Result<Response<LoadCsvResponse>, Status> {
     // Original tracing::info for request reception is in the main trading_service.rs method
     // This handler can log its specific actions if needed, or we rely on the caller's log.
     // For now, let's assume the main method logs the initial reception.
 
-    let timeframe = TimeFrame::Day1;
+    let timeframe = from_grpc_timeframe(req_payload.timeframe)?;
 
-    let candles = match BrazilianCsvParser::load_candles_from_csv(&req_payload.file_path, &req_payload.symbol) {
+    let load = match BrazilianCsvParser::load_candles_from_csv_until(&req_payload.file_path, &req_payload.symbol, &csv_format, req_payload.skip_bad_rows, deadline) {
         Ok(c) => c,
         Err(e) => {
             // Error already logged sufficiently by CsvParser or by the error mapping
@@ -28,23 +38,155 @@ pub async fn handle_load_csv_data(
         }
     };
 
+    let response = store_loaded_candles(&req_payload, timeframe, load, &market_data_store, &order_book, &portfolio).await?;
+    Ok(Response::new(response))
+}
+
+/// Files of a batch read at once when the request does not say, and at most.
+const DEFAULT_BATCH_WORKERS: usize = 4;
+const MAX_BATCH_WORKERS: usize = 16;
+const MAX_BATCH_FILES: usize = 1000;
+
+/// Skipped rows listed in a load's response; all of them are counted.
+const MAX_REPORTED_ROW_ERRORS: usize = 100;
+
+/// Imports every file of the batch, `max_parallel` at a time; a file that fails does not stop the others.
+/// `csv_formats` holds the format of each file, in order.
+pub async fn handle_load_csv_batch(
+    req_payload: LoadCsvBatchRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    csv_formats: Vec<CsvFormat>,
+    deadline: Option<Instant>,
+) -> Result<Response<LoadCsvBatchResponse>, Status> {
+    if req_payload.files.is_empty() || req_payload.files.len() > MAX_BATCH_FILES {
+        return Err(EngineError::InvalidField {
+            field: "files".to_string(),
+            message: format!("A CSV batch takes 1 to {} files, got {}", MAX_BATCH_FILES, req_payload.files.len()),
+        }.into());
+    }
+    let workers = match req_payload.max_parallel as usize {
+        0 => DEFAULT_BATCH_WORKERS,
+        n => n.min(MAX_BATCH_WORKERS),
+    };
+    let slots = Arc::new(Semaphore::new(workers));
+
+    let mut imports = JoinSet::new();
+    for (index, (file, csv_format)) in req_payload.files.iter().cloned().zip(csv_formats).enumerate() {
+        let slots = slots.clone();
+        let (market_data_store, order_book, portfolio) = (market_data_store.clone(), order_book.clone(), portfolio.clone());
+        imports.spawn(async move {
+            let _slot = slots.acquire_owned().await.expect("the batch semaphore is never closed");
+            let result = load_batch_file(&file, &csv_format, &market_data_store, &order_book, &portfolio, deadline).await;
+            (index, result)
+        }.in_current_span());
+    }
+
+    let mut outcomes: Vec<Option<Result<LoadCsvResponse, EngineError>>> = req_payload.files.iter().map(|_| None).collect();
+    while let Some(joined) = imports.join_next().await {
+        match joined {
+            Ok((index, result)) => outcomes[index] = Some(result),
+            // The file it was importing is reported as failed below; the others still load.
+            Err(e) => tracing::error!(error = %e, "CSV import task failed (handler)."),
+        }
+    }
+
+    let mut response = LoadCsvBatchResponse::default();
+    for (file, outcome) in req_payload.files.into_iter().zip(outcomes) {
+        let mut result = LoadCsvFileResult { file_path: file.file_path, symbol: file.symbol, timeframe: file.timeframe, ..Default::default() };
+        match outcome.unwrap_or_else(|| Err(EngineError::ProcessingError("The import of this file stopped unexpectedly".to_string()))) {
+            Ok(loaded) => {
+                response.files_loaded += 1;
+                response.candles_loaded += loaded.candles_loaded as i64;
+                result.success = true;
+                result.message = loaded.message;
+                result.candles_loaded = loaded.candles_loaded;
+                result.rows_skipped = loaded.rows_skipped;
+                result.row_errors = loaded.row_errors;
+            }
+            Err(e) => {
+                tracing::warn!(path = %result.file_path, symbol = %result.symbol, error = %e, "CSV batch file failed (handler).");
+                response.files_failed += 1;
+                result.message = e.to_string();
+            }
+        }
+        response.results.push(result);
+    }
+    tracing::info!(files_loaded = response.files_loaded, files_failed = response.files_failed, workers, "CSV batch imported (handler).");
+    Ok(Response::new(response))
+}
+
+async fn load_batch_file(
+    file: &LoadCsvRequest,
+    csv_format: &CsvFormat,
+    market_data_store: &Arc<RwLock<MarketDataStore>>,
+    order_book: &Arc<RwLock<OrderBook>>,
+    portfolio: &Arc<RwLock<Portfolio>>,
+    deadline: Option<Instant>,
+) -> Result<LoadCsvResponse, EngineError> {
+    let timeframe = from_grpc_timeframe(file.timeframe)?;
+    // Parsing is blocking work: off the runtime, so the files of a batch are read in parallel.
+    let (path, symbol, csv_format, skip_bad_rows) = (file.file_path.clone(), file.symbol.clone(), csv_format.clone(), file.skip_bad_rows);
+    let load = tokio::task::spawn_blocking(move || {
+        BrazilianCsvParser::load_candles_from_csv_until(&path, &symbol, &csv_format, skip_bad_rows, deadline)
+    })
+    .await
+    .map_err(|e| EngineError::ProcessingError(format!("CSV import task failed: {}", e)))??;
+    store_loaded_candles(file, timeframe, load, market_data_store, order_book, portfolio).await
+}
+
+/// Stores candles parsed from `req_payload`'s file, after letting working orders react to them,
+/// and reports the rows that were skipped. Shared with CSV import jobs.
+pub(super) async fn store_loaded_candles(
+    req_payload: &LoadCsvRequest,
+    timeframe: TimeFrame,
+    CsvLoad { candles, skipped }: CsvLoad,
+    market_data_store: &Arc<RwLock<MarketDataStore>>,
+    order_book: &Arc<RwLock<OrderBook>>,
+    portfolio: &Arc<RwLock<Portfolio>>,
+) -> Result<LoadCsvResponse, EngineError> {
     let candles_loaded = candles.len() as i32;
+
+    // Newly arrived candles advance simulated time: give resting limit orders a chance to fill or expire.
+    let fills = order_book.write().await.process_candles(&req_payload.symbol, &candles);
+    let mut portfolio_guard = portfolio.write().await;
+    if !fills.is_empty() {
+        tracing::info!(symbol = %req_payload.symbol, fills = fills.len(), "Working orders filled by newly loaded candles");
+        portfolio_guard.apply_fills(fills);
+    }
+    if let Some(last) = candles.iter().max_by_key(|c| c.timestamp) {
+        portfolio_guard.check_margin(&HashMap::from([(req_payload.symbol.clone(), last.close_f64())]));
+    }
+    drop(portfolio_guard);
+
     let mut store = market_data_store.write().await;
 
     match store.add_candles(&req_payload.symbol, timeframe, candles) {
         Ok(_) => {
             // Success log can also be in the main method after this handler returns Ok.
             // tracing::info!(symbol = %req_payload.symbol, count = candles_loaded, "Successfully loaded and stored CSV data in handler");
-            Ok(Response::new(LoadCsvResponse {
+            let mut message = format!("Loaded {} candles for symbol {}", candles_loaded, req_payload.symbol);
+            if !skipped.is_empty() {
+                tracing::warn!(symbol = %req_payload.symbol, path = %req_payload.file_path, rows_skipped = skipped.len(), "Bad CSV rows skipped");
+                message.push_str(&format!(", skipping {} bad rows", skipped.len()));
+            }
+            Ok(LoadCsvResponse {
                 success: true,
-                message: format!("Loaded {} candles for symbol {}", candles_loaded, req_payload.symbol),
+                message,
                 candles_loaded,
-            }))
+                rows_skipped: skipped.len() as u32,
+                row_errors: skipped.into_iter().take(MAX_REPORTED_ROW_ERRORS).map(to_grpc_row_error).collect(),
+            })
         }
         Err(e) => {
             // Error already logged sufficiently by MarketDataStore or by the error mapping
             // tracing::error!(symbol = %req_payload.symbol, error_detail = ?e, "Error storing candles in handler");
-            Err(EngineError::from(e).into())
+            Err(EngineError::from(e))
         }
     }
 }
+
+fn to_grpc_row_error(row: SkippedRow) -> CsvRowError {
+    CsvRowError { line: row.line as u32, column: row.column, reason: row.reason }
+}
@@ -0,0 +1,163 @@
+// Handlers for the account RPCs (ResetAccount, SnapshotAccount, DepositCash, WithdrawCash, SetBaseCurrency)
+use std::path::Path;
+use std::sync::Arc;
+use chrono::Utc;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::services::{AccountState, BaseCurrencyRequest, CashTransferRequest, PositionState, ResetAccountRequest, SnapshotAccountRequest};
+use crate::simulation::{AccountSnapshot, AccountStore, OrderBook, Portfolio, ReplayController};
+use super::helpers::{latest_closes, to_grpc_position_state, to_grpc_price};
+
+/// Account summary for `snapshot`; `saved_to` is the file it was just written to, if any.
+async fn account_state(
+    snapshot: &AccountSnapshot,
+    saved_to: Option<&Path>,
+    market_data_store: &Arc<RwLock<MarketDataStore>>,
+    replay: &Arc<RwLock<ReplayController>>,
+) -> AccountState {
+    let replay_guard = replay.read().await;
+    let store = market_data_store.read().await;
+    let portfolio = &snapshot.portfolio;
+    let marks = latest_closes(portfolio.positions().map(|p| p.symbol.as_str()), &replay_guard, &store);
+    let mut positions: Vec<PositionState> = portfolio.positions()
+        .filter(|p| !p.is_flat())
+        .map(to_grpc_position_state)
+        .collect();
+    positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+    AccountState {
+        initial_capital: portfolio.initial_capital,
+        cash: to_grpc_price(portfolio.cash),
+        equity: portfolio.equity(&marks),
+        positions,
+        working_orders: snapshot.order_book.working_orders(None).len() as u32,
+        executions: portfolio.executions().len() as u32,
+        path: saved_to.map(|p| p.display().to_string()).unwrap_or_default(),
+        saved_at: if saved_to.is_some() { snapshot.saved_at.timestamp_millis() } else { 0 },
+        base_currency: portfolio.base_currency.clone(),
+        net_deposits: to_grpc_price(portfolio.net_deposits()),
+    }
+}
+
+/// Writes the account to its file, if one is configured, right after a change made over RPC.
+async fn save_changed_account(
+    market_data_store: &Arc<RwLock<MarketDataStore>>,
+    replay: &Arc<RwLock<ReplayController>>,
+    order_book: &Arc<RwLock<OrderBook>>,
+    portfolio: &Arc<RwLock<Portfolio>>,
+    account: Option<&AccountStore>,
+) -> Result<AccountState, EngineError> {
+    let snapshot = AccountSnapshot::capture(portfolio, order_book).await;
+    if let Some(store) = account {
+        store.save(&snapshot)?;
+    }
+    Ok(account_state(&snapshot, account.map(|store| store.path()), market_data_store, replay).await)
+}
+
+pub async fn handle_reset_account(
+    req_payload: ResetAccountRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    replay: Arc<RwLock<ReplayController>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    account: Option<Arc<AccountStore>>,
+) -> Result<Response<AccountState>, Status> {
+    if req_payload.initial_capital < 0.0 || !req_payload.initial_capital.is_finite() {
+        return Err(EngineError::InvalidField {
+            field: "initial_capital".to_string(),
+            message: format!("Invalid initial capital: {}", req_payload.initial_capital),
+        }.into());
+    }
+
+    // Same order as AccountSnapshot::capture: order book, then portfolio.
+    let mut order_book_guard = order_book.write().await;
+    let mut portfolio_guard = portfolio.write().await;
+    let initial_capital = if req_payload.initial_capital > 0.0 { req_payload.initial_capital } else { portfolio_guard.initial_capital };
+    let mut fresh = Portfolio::new(initial_capital);
+    fresh.base_currency = portfolio_guard.base_currency.clone();
+    fresh.set_contracts(portfolio_guard.contracts().clone());
+    if !req_payload.base_currency.trim().is_empty() {
+        fresh.set_base_currency(&req_payload.base_currency).map_err(EngineError::InvalidRequest)?;
+    }
+    *order_book_guard = OrderBook::new();
+    *portfolio_guard = fresh;
+    drop(portfolio_guard);
+    drop(order_book_guard);
+    tracing::info!(initial_capital, "Paper-trading account reset");
+
+    let state = save_changed_account(&market_data_store, &replay, &order_book, &portfolio, account.as_deref()).await?;
+    Ok(Response::new(state))
+}
+
+pub async fn handle_deposit_cash(
+    req_payload: CashTransferRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    replay: Arc<RwLock<ReplayController>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    account: Option<Arc<AccountStore>>,
+) -> Result<Response<AccountState>, Status> {
+    portfolio.write().await.deposit(req_payload.amount, Utc::now()).map_err(EngineError::InvalidRequest)?;
+    let state = save_changed_account(&market_data_store, &replay, &order_book, &portfolio, account.as_deref()).await?;
+    Ok(Response::new(state))
+}
+
+pub async fn handle_withdraw_cash(
+    req_payload: CashTransferRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    replay: Arc<RwLock<ReplayController>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    account: Option<Arc<AccountStore>>,
+) -> Result<Response<AccountState>, Status> {
+    let replay_guard = replay.read().await;
+    let store = market_data_store.read().await;
+    let mut portfolio_guard = portfolio.write().await;
+    let marks = latest_closes(portfolio_guard.positions().map(|p| p.symbol.as_str()), &replay_guard, &store);
+    portfolio_guard.withdraw(req_payload.amount, &marks, Utc::now()).map_err(EngineError::InvalidRequest)?;
+    drop(portfolio_guard);
+    drop(store);
+    drop(replay_guard);
+
+    let state = save_changed_account(&market_data_store, &replay, &order_book, &portfolio, account.as_deref()).await?;
+    Ok(Response::new(state))
+}
+
+pub async fn handle_set_base_currency(
+    req_payload: BaseCurrencyRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    replay: Arc<RwLock<ReplayController>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    account: Option<Arc<AccountStore>>,
+) -> Result<Response<AccountState>, Status> {
+    portfolio.write().await.set_base_currency(&req_payload.currency).map_err(EngineError::InvalidRequest)?;
+    tracing::info!(currency = %req_payload.currency, "Paper-trading account currency changed");
+    let state = save_changed_account(&market_data_store, &replay, &order_book, &portfolio, account.as_deref()).await?;
+    Ok(Response::new(state))
+}
+
+pub async fn handle_snapshot_account(
+    req_payload: SnapshotAccountRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    replay: Arc<RwLock<ReplayController>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    account: Option<Arc<AccountStore>>,
+) -> Result<Response<AccountState>, Status> {
+    let store = if req_payload.path.trim().is_empty() {
+        account.as_deref().cloned().ok_or_else(|| {
+            EngineError::ConfigError("No account file is configured; pass a path to write the snapshot to".to_string())
+        })?
+    } else {
+        AccountStore::new(req_payload.path.trim())
+    };
+
+    let snapshot = AccountSnapshot::capture(&portfolio, &order_book).await;
+    store.save(&snapshot)?;
+    tracing::info!(path = ?store.path(), "Paper-trading account snapshot written");
+    Ok(Response::new(account_state(&snapshot, Some(store.path()), &market_data_store, &replay).await))
+}
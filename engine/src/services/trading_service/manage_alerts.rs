@@ -0,0 +1,113 @@
+// Handlers for the price alert RPCs (CreateAlert, DeleteAlert, ListAlerts, SubscribeAlerts)
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Response, Status};
+use tracing::Instrument;
+
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::services::{AlertRequest, CreateAlertRequest, GrpcAlert, GrpcAlertEvent, ListAlertsRequest, ListAlertsResponse, SubscribeAlertsRequest};
+use crate::simulation::alerts::{watch_alerts, ALERT_HISTORY_BARS};
+use crate::simulation::{AlertBook, AlertCondition, AlertIndicator, CrossDirection, ReplayController};
+use shared::models::TimeFrame;
+use super::helpers::{from_grpc_timeframe, to_grpc_alert, to_grpc_alert_event};
+
+fn alert_condition(req_payload: &CreateAlertRequest) -> Result<AlertCondition, EngineError> {
+    match req_payload.kind.trim().to_uppercase().as_str() {
+        "PRICE_CROSS" => Ok(AlertCondition::PriceCross { level: req_payload.level }),
+        "INDICATOR" => Ok(AlertCondition::Indicator {
+            indicator: AlertIndicator::parse(&req_payload.indicator, req_payload.period as usize)?,
+            threshold: req_payload.level,
+            direction: CrossDirection::parse(&req_payload.direction).ok_or_else(|| EngineError::InvalidField {
+                field: "direction".to_string(),
+                message: format!("Indicator alerts cross ABOVE or BELOW their threshold, not '{}'", req_payload.direction),
+            })?,
+        }),
+        "PERCENT_MOVE" => Ok(AlertCondition::PercentMove { percent: req_payload.level }),
+        other => Err(EngineError::InvalidField {
+            field: "kind".to_string(),
+            message: format!("Unknown alert kind '{}': use PRICE_CROSS, INDICATOR or PERCENT_MOVE", other),
+        }),
+    }
+}
+
+/// Sets the alert from the series as it stands: the candles revealed so far while the symbol's
+/// daily series is being replayed, the stored ones otherwise.
+pub async fn handle_create_alert(
+    req_payload: CreateAlertRequest,
+    alerts: Arc<RwLock<AlertBook>>,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    replay: Arc<RwLock<ReplayController>>,
+) -> Result<Response<GrpcAlert>, Status> {
+    let symbol = req_payload.symbol.trim().to_string();
+    if symbol.is_empty() {
+        return Err(EngineError::InvalidField { field: "symbol".to_string(), message: "Alert names no symbol".to_string() }.into());
+    }
+    let timeframe = from_grpc_timeframe(req_payload.timeframe)?;
+    let condition = alert_condition(&req_payload)?;
+
+    let replayed = match timeframe {
+        TimeFrame::Day1 => replay.read().await.visible_candles(&symbol),
+        _ => None,
+    };
+    let history = match replayed {
+        Some(candles) => candles[candles.len().saturating_sub(ALERT_HISTORY_BARS)..].to_vec(),
+        None => {
+            market_data_store.read().await.last_candles(&symbol, timeframe, None, ALERT_HISTORY_BARS).map_err(EngineError::from)?
+        }
+    };
+
+    watch_alerts(alerts.clone(), market_data_store, replay).await;
+    let alert = alerts.write().await.add(&symbol, timeframe, condition, &req_payload.note, &history)?;
+    Ok(Response::new(to_grpc_alert(&alert)))
+}
+
+pub async fn handle_delete_alert(
+    req_payload: AlertRequest,
+    alerts: Arc<RwLock<AlertBook>>,
+) -> Result<Response<GrpcAlert>, Status> {
+    let alert = alerts.write().await.remove(&req_payload.alert_id)?;
+    Ok(Response::new(to_grpc_alert(&alert)))
+}
+
+pub async fn handle_list_alerts(
+    req_payload: ListAlertsRequest,
+    alerts: Arc<RwLock<AlertBook>>,
+) -> Result<Response<ListAlertsResponse>, Status> {
+    let symbol = Some(req_payload.symbol.trim()).filter(|s| !s.is_empty());
+    let alerts = alerts.read().await.alerts(symbol).into_iter().map(to_grpc_alert).collect();
+    Ok(Response::new(ListAlertsResponse { alerts }))
+}
+
+pub async fn handle_subscribe_alerts(
+    req_payload: SubscribeAlertsRequest,
+    alerts: Arc<RwLock<AlertBook>>,
+) -> Result<Response<ReceiverStream<Result<GrpcAlertEvent, Status>>>, Status> {
+    let symbol = req_payload.symbol.trim().to_string();
+    let mut triggers = alerts.read().await.subscribe();
+
+    let (tx, rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        loop {
+            let trigger = tokio::select! {
+                _ = tx.closed() => break,
+                received = triggers.recv() => match received {
+                    Ok(trigger) if symbol.is_empty() || trigger.alert.symbol == symbol => trigger,
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "Alert subscriber lagged behind; fired alerts were skipped (handler).");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            };
+            if tx.send(Ok(to_grpc_alert_event(&trigger))).await.is_err() {
+                break;
+            }
+        }
+        tracing::debug!(symbol = %symbol, "Alert subscription ended (handler).");
+    }.in_current_span());
+
+    Ok(Response::new(ReceiverStream::new(rx)))
+}
@@ -0,0 +1,203 @@
+// Handlers for the SubmitJob, WatchJob, CancelJob, GetJobResult and ListJobs RPCs
+// A job runs the same code as the matching unary RPC, with no deadline, reporting its
+// progress and checking for cancellation as it goes.
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Response, Status};
+use tracing::Instrument;
+
+use crate::backtest::{run_monte_carlo, run_portfolio_backtest_with_events, BacktestEvent};
+use crate::data::csv_parser::{BrazilianCsvParser, CsvFormat};
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::services::generated::{job_result::Result as JobOutput, submit_job_request::Job};
+use crate::services::jobs::{JobControl, JobRegistry};
+use crate::services::{
+    BacktestRequest, BacktestResponse, GrpcJobStatus, GrpcMonteCarloSummary, JobRequest, JobResult, ListJobsResponse,
+    LoadCsvRequest, LoadCsvResponse, MonteCarloRequest, SubmitJobRequest,
+};
+use crate::simulation::{Contracts, OrderBook, Portfolio};
+use shared::models::TimeFrame;
+use super::helpers::{from_grpc_timeframe, to_grpc_job_status, to_grpc_monte_carlo_summary};
+use super::load_csv_data::store_loaded_candles;
+use super::run_backtest::{backtest_legs, compare_to_requested_benchmark, prepare_backtest, to_backtest_response, PreparedBacktest};
+use super::run_monte_carlo::{prepare_monte_carlo, PreparedMonteCarlo};
+use super::run_stress_test::execute_stress_test;
+
+/// The engine's jobs, keeping each one's result until it is pruned.
+pub type Jobs = JobRegistry<JobOutput>;
+
+/// The `kind` a job is listed under, e.g. "BACKTEST".
+pub fn job_kind(job: &Job) -> &'static str {
+    match job {
+        Job::LoadCsv(_) => "LOAD_CSV",
+        Job::Backtest(_) => "BACKTEST",
+        Job::StressTest(_) => "STRESS_TEST",
+        Job::MonteCarlo(_) => "MONTE_CARLO",
+    }
+}
+
+/// The symbol whose CSV file a job loads, empty for jobs loading none.
+pub fn job_symbol(job: Option<&Job>) -> &str {
+    match job {
+        Some(Job::LoadCsv(request)) => &request.symbol,
+        _ => "",
+    }
+}
+
+pub async fn handle_submit_job(
+    req_payload: SubmitJobRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    contracts: Contracts,
+    csv_format: CsvFormat,
+    jobs: Jobs,
+) -> Result<Response<GrpcJobStatus>, Status> {
+    let job = req_payload.job
+        .ok_or_else(|| EngineError::InvalidRequest("Submit job request names no job to run".to_string()))?;
+    let kind = job_kind(&job);
+    let status = match job {
+        Job::LoadCsv(request) => {
+            // Checked now, so a bad request fails the call rather than the job.
+            let timeframe = from_grpc_timeframe(request.timeframe)?;
+            jobs.submit(kind, move |control| async move {
+                let response = load_csv_job(request, timeframe, csv_format, market_data_store, order_book, portfolio, control).await?;
+                Ok(JobOutput::LoadCsv(response))
+            })?
+        }
+        Job::Backtest(request) => jobs.submit(kind, move |control| async move {
+            Ok(JobOutput::Backtest(backtest_job(request, market_data_store, contracts, control).await?))
+        })?,
+        Job::StressTest(request) => jobs.submit(kind, move |control| async move {
+            let response = execute_stress_test(request, &market_data_store, &contracts, None, move |completed, total| {
+                control.report(completed as f64 / total as f64, format!("Ran {} of {} scenarios, baseline included", completed, total));
+                !control.is_cancelled()
+            }).await?;
+            Ok(JobOutput::StressTest(response))
+        })?,
+        Job::MonteCarlo(request) => jobs.submit(kind, move |control| async move {
+            Ok(JobOutput::MonteCarlo(monte_carlo_job(request, market_data_store, contracts, control).await?))
+        })?,
+    };
+    Ok(Response::new(to_grpc_job_status(&status)))
+}
+
+async fn load_csv_job(
+    request: LoadCsvRequest,
+    timeframe: TimeFrame,
+    csv_format: CsvFormat,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    control: JobControl,
+) -> Result<LoadCsvResponse, EngineError> {
+    // Reading the file is most of the work; storing the candles is reported as the last 10%.
+    let (path, symbol, skip_bad_rows, parsing) = (request.file_path.clone(), request.symbol.clone(), request.skip_bad_rows, control.clone());
+    let load = tokio::task::spawn_blocking(move || {
+        BrazilianCsvParser::load_candles_from_csv_checked(&path, &symbol, &csv_format, skip_bad_rows, |line_num, read| {
+            if parsing.is_cancelled() {
+                return Err(EngineError::ProcessingError(format!("Loading {} was cancelled at line {}", path, line_num)));
+            }
+            parsing.report(read * 0.9, format!("Read {:.0}% of the file", read * 100.0));
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| EngineError::ProcessingError(format!("CSV import task failed: {}", e)))??;
+
+    // A cancelled import stores nothing.
+    if control.is_cancelled() {
+        return Err(EngineError::ProcessingError(format!("Loading {} was cancelled", request.file_path)));
+    }
+    control.report(0.9, format!("Storing {} candles", load.candles.len()));
+    store_loaded_candles(&request, timeframe, load, &market_data_store, &order_book, &portfolio).await
+}
+
+async fn backtest_job(
+    request: BacktestRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    contracts: Contracts,
+    control: JobControl,
+) -> Result<BacktestResponse, EngineError> {
+    let PreparedBacktest { mut legs, config, benchmark, .. } = prepare_backtest(&request, &market_data_store, &contracts, None).await?;
+    let bars: usize = legs.iter().map(|leg| leg.candles.len()).sum();
+    // Every report wakes the job's watchers: one per percent is plenty.
+    let report_every = (bars / 100).max(1);
+
+    tokio::task::spawn_blocking(move || {
+        let mut done = 0;
+        let mut outcome = run_portfolio_backtest_with_events(backtest_legs(&mut legs), config, |event| {
+            if let BacktestEvent::Candle { .. } = event {
+                done += 1;
+                if done % report_every == 0 {
+                    control.report(done as f64 / bars as f64, format!("Processed {} of {} bars", done, bars));
+                }
+            }
+            !control.is_cancelled()
+        })?;
+        compare_to_requested_benchmark(&mut outcome, &request.benchmark_symbol, benchmark.as_deref());
+        Ok(to_backtest_response(&outcome))
+    })
+    .await
+    .map_err(|e| EngineError::ProcessingError(format!("Backtest task failed: {}", e)))?
+}
+
+async fn monte_carlo_job(
+    request: MonteCarloRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    contracts: Contracts,
+    control: JobControl,
+) -> Result<GrpcMonteCarloSummary, EngineError> {
+    control.report(0.0, "Running the backtest");
+    let PreparedMonteCarlo { config, pnls, initial_capital, .. } = prepare_monte_carlo(request, &market_data_store, &contracts, None).await?;
+    let summary = tokio::task::spawn_blocking(move || {
+        run_monte_carlo(&pnls, initial_capital, config, |completed, total| {
+            control.report(completed as f64 / total as f64, format!("Ran {} of {} simulations", completed, total));
+            !control.is_cancelled()
+        })
+    })
+    .await
+    .map_err(|e| EngineError::ProcessingError(format!("Monte Carlo task failed: {}", e)))??;
+    Ok(to_grpc_monte_carlo_summary(&summary))
+}
+
+pub async fn handle_watch_job(
+    req_payload: JobRequest,
+    jobs: Jobs,
+) -> Result<Response<ReceiverStream<Result<GrpcJobStatus, Status>>>, Status> {
+    let mut status = jobs.watch(&req_payload.job_id)?;
+    let (tx, rx) = mpsc::channel(16);
+    tokio::spawn(async move {
+        loop {
+            let (current, finished) = {
+                let status = status.borrow_and_update();
+                (to_grpc_job_status(&status), status.state.is_finished())
+            };
+            if tx.send(Ok(current)).await.is_err() || finished {
+                break;
+            }
+            tokio::select! {
+                // An error means the job was pruned: there is nothing more to see.
+                changed = status.changed() => if changed.is_err() { break },
+                _ = tx.closed() => break,
+            }
+        }
+    }.in_current_span());
+    Ok(Response::new(ReceiverStream::new(rx)))
+}
+
+pub async fn handle_cancel_job(req_payload: JobRequest, jobs: Jobs) -> Result<Response<GrpcJobStatus>, Status> {
+    let status = jobs.cancel(&req_payload.job_id)?;
+    Ok(Response::new(to_grpc_job_status(&status)))
+}
+
+pub async fn handle_get_job_result(req_payload: JobRequest, jobs: Jobs) -> Result<Response<JobResult>, Status> {
+    let result = jobs.result(&req_payload.job_id)?;
+    Ok(Response::new(JobResult { job_id: req_payload.job_id, result: Some(result) }))
+}
+
+pub async fn handle_list_jobs(jobs: Jobs) -> Result<Response<ListJobsResponse>, Status> {
+    Ok(Response::new(ListJobsResponse { jobs: jobs.list().iter().map(to_grpc_job_status).collect() }))
+}
@@ -0,0 +1,72 @@
+// Handlers for the CancelOrder and ModifyOrder RPCs (working limit orders in the simulated book)
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::services::{CancelOrderRequest, ModifyOrderRequest, OrderState};
+use crate::simulation::{Contracts, OrderBook, OrderSide, Portfolio, ReplayController, RiskLimits};
+use shared::models::{to_f64, TimeFrame};
+use super::helpers::{from_grpc_price, latest_closes, to_grpc_order_state};
+
+pub async fn handle_cancel_order(
+    req_payload: CancelOrderRequest,
+    order_book: Arc<RwLock<OrderBook>>,
+) -> Result<Response<OrderState>, Status> {
+    let order = order_book.write().await.cancel(&req_payload.order_id)?;
+    Ok(Response::new(to_grpc_order_state(&order)))
+}
+
+pub async fn handle_modify_order(
+    req_payload: ModifyOrderRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    replay: Arc<RwLock<ReplayController>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    risk_limits: Arc<RwLock<RiskLimits>>,
+    contracts: Contracts,
+) -> Result<Response<OrderState>, Status> {
+    let current = order_book.read().await.get(&req_payload.order_id).cloned()
+        .ok_or_else(|| EngineError::SimulationError(format!("Order '{}' not found", req_payload.order_id)))?;
+    let limit_price = req_payload.limit_price.as_deref()
+        .map(|p| from_grpc_price("limit_price", p))
+        .transpose()?
+        .map(|p| contracts.round_limit_price(&current.symbol, current.side == OrderSide::Buy, p));
+
+    // Latest visible candle: a modification can make the order marketable right away, like a new order.
+    let replay_guard = replay.read().await;
+    let store = market_data_store.read().await;
+    let latest_candle = replay_guard.current_candle(&current.symbol)
+        .or_else(|| store.last_candle(&current.symbol, TimeFrame::Day1).cloned());
+
+    // Increasing the size needs buying power for the extra quantity and must stay within the risk limits.
+    if let Some(extra) = req_payload.quantity.map(|q| q - current.quantity).filter(|extra| *extra > 0.0) {
+        let portfolio_guard = portfolio.read().await;
+        let mut marks = latest_closes(portfolio_guard.positions().map(|p| p.symbol.as_str()), &replay_guard, &store);
+        let price = to_f64(limit_price.unwrap_or(current.limit_price));
+        if let Err(reason) = portfolio_guard.check_buying_power(&current.symbol, current.side, extra, price, &marks) {
+            return Err(EngineError::InvalidRequest(reason).into());
+        }
+        if let Some(candle) = &latest_candle {
+            marks.insert(current.symbol.clone(), candle.close_f64());
+            let quantity = current.quantity + extra; // The whole order, as it would fill
+            if let Err(violation) = risk_limits.read().await.check_order(&current.symbol, current.side, quantity, &portfolio_guard, &marks, candle.timestamp) {
+                return Err(EngineError::InvalidRequest(violation.to_string()).into());
+            }
+        }
+    }
+    drop(store);
+    drop(replay_guard);
+
+    let mut book = order_book.write().await;
+    let mut order = book.modify(&req_payload.order_id, limit_price, req_payload.quantity)?;
+    if let Some(candle) = latest_candle.filter(|c| order.is_marketable(c)) {
+        // Like a new market order, at the latest close; a limit moved past it fills there.
+        let fill = book.fill(&order.id, contracts.round_to_tick(&order.symbol, candle.close), candle.timestamp)?;
+        order = book.get(&order.id).cloned().unwrap_or(order);
+        drop(book);
+        portfolio.write().await.apply_fill(fill);
+    }
+    Ok(Response::new(to_grpc_order_state(&order)))
+}
@@ -0,0 +1,26 @@
+// Handlers for the GetRiskLimits and SetRiskLimits RPCs
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::services::GrpcRiskLimits;
+use crate::simulation::RiskLimits;
+use super::helpers::{from_grpc_risk_limits, to_grpc_risk_limits};
+
+pub async fn handle_get_risk_limits(
+    risk_limits: Arc<RwLock<RiskLimits>>,
+) -> Result<Response<GrpcRiskLimits>, Status> {
+    Ok(Response::new(to_grpc_risk_limits(&*risk_limits.read().await)))
+}
+
+/// Replaces all limits at once; working orders that already break a new limit are left alone.
+pub async fn handle_set_risk_limits(
+    req_payload: GrpcRiskLimits,
+    risk_limits: Arc<RwLock<RiskLimits>>,
+) -> Result<Response<GrpcRiskLimits>, Status> {
+    let limits = from_grpc_risk_limits(req_payload)?;
+    let mut guard = risk_limits.write().await;
+    *guard = limits;
+    tracing::info!(limits = ?*guard, "Risk limits updated (handler).");
+    Ok(Response::new(to_grpc_risk_limits(&guard)))
+}
@@ -5,35 +5,267 @@
 
 // Use statements adjusted for the new module structure.
 use super::{ // Imports from engine/src/services/mod.rs
-    TradingEngine, LoadCsvRequest, LoadCsvResponse,
-    MarketDataRequest, MarketDataResponse,
+    TradingEngine, LoadCsvRequest, LoadCsvResponse, LoadCsvBatchRequest, LoadCsvBatchResponse,
+    MarketDataRequest, MarketDataResponse, SubscribeCandlesRequest, GrpcCandleUpdate,
+    ListSymbolsRequest, ListSymbolsResponse,
+    AggregatedCandlesRequest, AggregatedCandlesResponse, SeriesStatsRequest, GrpcSeriesStats,
+    ExportDataRequest, ExportDataResponse,
     IndicatorRequest, IndicatorResponse,
     TradeRequest, TradeResponse,
+    CancelOrderRequest, ModifyOrderRequest, OrderState,
+    TradeHistoryRequest, TradeHistoryResponse,
+    PositionSizeRequest, PositionSizeResponse,
+    StartReplayRequest, ReplayControlRequest, StepReplayRequest, ReplaySpeedRequest, ReplayStatus,
+    BacktestRequest, BacktestResponse, SessionReportRequest, GrpcPnlReport, StreamBacktestRequest, GrpcBacktestEvent,
+    StressTestRequest, StressTestResponse,
+    MonteCarloRequest, MonteCarloProgress,
+    ResetAccountRequest, SnapshotAccountRequest, AccountState, CashTransferRequest, BaseCurrencyRequest,
+    RiskLimitsRequest, GrpcRiskLimits,
+    CreateAlertRequest, AlertRequest, ListAlertsRequest, ListAlertsResponse, SubscribeAlertsRequest, GrpcAlert, GrpcAlertEvent,
+    EngineInfoRequest, EngineInfo, ReloadSettingsRequest, ReloadSettingsResponse,
+    SessionRequest, SessionEvent, GrpcTimeFrame,
+    SubmitJobRequest, JobRequest, ListJobsRequest, ListJobsResponse, GrpcJobStatus, JobResult,
     // ProtoCandle as GrpcCandle, // Removed as unused at this top level
 };
+use crate::config::settings::{AppSettings, SettingsChanges};
+use crate::data::csv_parser::CsvFormat;
 use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::services::error_details::ErrorDetails;
+use crate::simulation::account::spawn_autosave;
+use crate::simulation::{AccountSnapshot, AccountStore, AlertBook, Contracts, OrderBook, Portfolio, ReplayController, RiskLimits};
+use helpers::request_deadline;
+use shared::errors::ErrorCode;
 // shared::models are moved to mod tests
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use tokio::sync::RwLock;
 
 // Declare submodules for handlers and helpers. These are now sibling modules.
 pub mod helpers;
 pub mod load_csv_data;
 pub mod get_market_data;
+pub mod list_symbols;
+pub mod subscribe_candles;
+pub mod get_aggregated_candles;
+pub mod get_series_stats;
+pub mod export_data;
 pub mod calculate_indicator;
 pub mod simulate_trade;
+pub mod manage_orders;
+pub mod get_trade_history;
+pub mod suggest_position_size;
+pub mod replay_control;
+pub mod run_backtest;
+pub mod get_session_report;
+pub mod run_monte_carlo;
+pub mod run_stress_test;
+pub mod manage_account;
+pub mod manage_risk;
+pub mod manage_alerts;
+pub mod get_engine_info;
+pub mod trading_session;
+pub mod manage_jobs;
+pub mod reload_settings;
+
+/// Request metadata naming the namespace a call works in. Each namespace has its own candles,
+/// paper-trading account, replays and jobs, so clients (e.g. GUI workspaces) sharing one
+/// engine do not see each other's data. Calls without it work in the default namespace.
+pub const NAMESPACE_HEADER: &str = "x-namespace";
+pub const DEFAULT_NAMESPACE: &str = "default";
+/// Namespaces besides the default one; they last until the engine stops.
+pub const MAX_NAMESPACES: usize = 32;
+const MAX_NAMESPACE_LEN: usize = 64;
 
 // MyTradingEngine struct definition
+#[derive(Clone)]
 pub struct MyTradingEngine {
     market_data_store: Arc<RwLock<MarketDataStore>>,
+    order_book: Arc<RwLock<OrderBook>>, // Resting limit orders, re-evaluated as candles arrive
+    replay: Arc<RwLock<ReplayController>>, // Active bar-by-bar replay sessions, per symbol
+    portfolio: Arc<RwLock<Portfolio>>, // Paper-trading session: cash, positions and the fill journal
+    account: Option<Arc<AccountStore>>, // Where the paper-trading account is persisted, if anywhere
+    risk_limits: Arc<RwLock<RiskLimits>>, // Checked before a simulated order is accepted
+    alerts: Arc<RwLock<AlertBook>>, // Price alerts waiting to fire on incoming and replayed candles
+    started_at: Instant, // For the uptime in GetEngineInfo
+    features: Arc<Vec<String>>, // Optional capabilities reported by GetEngineInfo
+    jobs: manage_jobs::Jobs, // Background CSV imports, backtests and analyses
+    settings: Arc<RwLock<AppSettings>>, // Config file settings in effect, shared by every namespace; see ReloadSettings
+    contracts: Contracts, // Tick sizes and point values per the `symbols` settings, shared by every namespace
+    namespaces: Arc<Mutex<HashMap<String, MyTradingEngine>>>, // Isolated engines named by NAMESPACE_HEADER, created on first use
 }
 
 // impl MyTradingEngine { new ... }
 impl MyTradingEngine {
     pub fn new(market_data_store: Arc<RwLock<MarketDataStore>>) -> Self {
-        MyTradingEngine { market_data_store }
+        Self::with_contracts(market_data_store, Contracts::default())
+    }
+
+    /// Engine looking tick sizes and point values up in `contracts`.
+    fn with_contracts(market_data_store: Arc<RwLock<MarketDataStore>>, contracts: Contracts) -> Self {
+        let mut portfolio = Portfolio::default();
+        portfolio.set_contracts(contracts.clone());
+        MyTradingEngine {
+            market_data_store,
+            order_book: Arc::new(RwLock::new(OrderBook::new())),
+            replay: Arc::new(RwLock::new(ReplayController::new())),
+            portfolio: Arc::new(RwLock::new(portfolio)),
+            account: None,
+            risk_limits: Arc::new(RwLock::new(RiskLimits::default())),
+            alerts: Arc::new(RwLock::new(AlertBook::new())),
+            started_at: Instant::now(),
+            features: Arc::new(Vec::new()),
+            jobs: manage_jobs::Jobs::default(),
+            settings: Arc::default(),
+            contracts,
+            namespaces: Arc::default(),
+        }
+    }
+
+    /// Remembers the `settings` the engine started with, for the CSV data format and settings reloads.
+    /// Does not apply their risk limits or features: see `with_risk_limits` and `with_features`.
+    pub fn with_settings(self, settings: AppSettings) -> Self {
+        MyTradingEngine { settings: Arc::new(RwLock::new(settings)), ..self }
+    }
+
+    /// Starts the engine with `limits` (from the settings) instead of no risk limits.
+    pub fn with_risk_limits(self, limits: RiskLimits) -> Self {
+        MyTradingEngine { risk_limits: Arc::new(RwLock::new(limits)), ..self }
+    }
+
+    /// Lists `features` (see `EngineSettings::features`) in GetEngineInfo.
+    pub fn with_features(self, features: Vec<String>) -> Self {
+        MyTradingEngine { features: Arc::new(features), ..self }
+    }
+
+    /// Engine whose paper-trading account (portfolio and working orders) is loaded from
+    /// `account` and autosaved back to it. Must be called inside a Tokio runtime.
+    pub fn with_account(market_data_store: Arc<RwLock<MarketDataStore>>, account: AccountStore) -> Result<Self, EngineError> {
+        Self::new(market_data_store).load_account(account)
+    }
+
+    fn load_account(self, account: AccountStore) -> Result<Self, EngineError> {
+        let mut engine = self;
+        if let Some(mut snapshot) = account.load()? {
+            tracing::info!(
+                path = ?account.path(),
+                saved_at = %snapshot.saved_at,
+                cash = %snapshot.portfolio.cash,
+                "Restored paper-trading account"
+            );
+            snapshot.portfolio.set_contracts(engine.contracts.clone());
+            engine.portfolio = Arc::new(RwLock::new(snapshot.portfolio));
+            engine.order_book = Arc::new(RwLock::new(snapshot.order_book));
+        }
+        let account = Arc::new(account);
+        spawn_autosave(account.clone(), engine.portfolio.clone(), engine.order_book.clone());
+        engine.account = Some(account);
+        Ok(engine)
+    }
+
+    /// The engine `settings` describe: with their paper-trading account file (if any), risk limits
+    /// and features. Must be called inside a Tokio runtime.
+    pub fn from_settings(market_data_store: Arc<RwLock<MarketDataStore>>, settings: AppSettings) -> Result<Self, EngineError> {
+        let engine = Self::with_contracts(market_data_store, Contracts::new(settings.symbols.clone()));
+        let engine = match &settings.engine.account_file {
+            Some(path) => {
+                tracing::info!("Paper-trading account file: {}", path);
+                engine.load_account(AccountStore::new(path))?
+            }
+            None => engine,
+        };
+        Ok(engine
+            .with_risk_limits(settings.engine.risk_limits.clone())
+            .with_features(settings.engine.features())
+            .with_settings(settings))
+    }
+
+    /// The engine a request with `metadata` works on: this one for the default namespace,
+    /// otherwise the one of the namespace it names, created on first use with empty data, no
+    /// account file and the default namespace's current risk limits.
+    async fn in_namespace(&self, metadata: &tonic::metadata::MetadataMap) -> Result<MyTradingEngine, Status> {
+        let Some(value) = metadata.get(NAMESPACE_HEADER) else {
+            return Ok(self.clone());
+        };
+        let name = value.to_str().ok()
+            .map(str::trim)
+            .filter(|name| !name.is_empty() && name.len() <= MAX_NAMESPACE_LEN)
+            .filter(|name| name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+            .ok_or_else(|| {
+                let message = format!("Invalid {}: use up to {} letters, digits, '-', '_' or '.'", NAMESPACE_HEADER, MAX_NAMESPACE_LEN);
+                ErrorDetails::new(ErrorCode::InvalidRequest).with_field_violation(NAMESPACE_HEADER, &message).into_status(tonic::Code::InvalidArgument, message)
+            })?;
+        tracing::Span::current().record("namespace", name);
+        if name == DEFAULT_NAMESPACE {
+            return Ok(self.clone());
+        }
+        if let Some(engine) = self.lock_namespaces().get(name) {
+            return Ok(engine.clone());
+        }
+
+        let risk_limits = self.risk_limits.read().await.clone();
+        let mut namespaces = self.lock_namespaces();
+        if namespaces.len() >= MAX_NAMESPACES && !namespaces.contains_key(name) {
+            return Err(Status::resource_exhausted(format!(
+                "The engine already holds {} namespaces (at most {}); use one of them", namespaces.len(), MAX_NAMESPACES
+            )));
+        }
+        let engine = namespaces.entry(name.to_string()).or_insert_with(|| {
+            tracing::info!(namespace = name, "Created namespace");
+            MyTradingEngine {
+                started_at: self.started_at,
+                features: self.features.clone(),
+                settings: self.settings.clone(),
+                ..MyTradingEngine::with_contracts(Arc::new(RwLock::new(MarketDataStore::new())), self.contracts.clone()).with_risk_limits(risk_limits)
+            }
+        });
+        Ok(engine.clone())
+    }
+
+    fn lock_namespaces(&self) -> std::sync::MutexGuard<'_, HashMap<String, MyTradingEngine>> {
+        self.namespaces.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn session_context(&self) -> trading_session::SessionContext {
+        trading_session::SessionContext {
+            market_data_store: self.market_data_store.clone(),
+            order_book: self.order_book.clone(),
+            replay: self.replay.clone(),
+            portfolio: self.portfolio.clone(),
+            risk_limits: self.risk_limits.clone(),
+            contracts: self.contracts.clone(),
+        }
+    }
+
+    /// Re-reads the config file (e.g. on SIGHUP); see ReloadSettings.
+    pub async fn reload_config(&self) -> Result<SettingsChanges, EngineError> {
+        reload_settings::reload_settings(&self.settings, &self.risk_limits, &self.contracts).await
+    }
+
+    /// How the CSV files of `symbol` are written, per the settings in effect.
+    async fn csv_format(&self, symbol: &str) -> CsvFormat {
+        self.settings.read().await.csv_format(symbol)
+    }
+
+    /// Writes the paper-trading account to its file now (e.g. on shutdown). No-op without an account file.
+    pub async fn save_account(&self) -> Result<(), EngineError> {
+        match &self.account {
+            Some(account) => account.save(&AccountSnapshot::capture(&self.portfolio, &self.order_book).await),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Adds the series a call works on to the `rpc` span opened by `RequestIdLayer`, so that
+/// everything logged while handling it can be found by symbol.
+fn record_series(symbol: &str, timeframe: Option<i32>) {
+    let span = tracing::Span::current();
+    span.record("symbol", symbol);
+    if let Some(timeframe) = timeframe.and_then(|t| GrpcTimeFrame::try_from(t).ok()) {
+        span.record("timeframe", timeframe.as_str_name());
     }
 }
 
@@ -41,61 +273,496 @@ impl MyTradingEngine {
 #[tonic::async_trait]
 impl TradingEngine for MyTradingEngine {
     async fn load_csv_data(&self, request: Request<LoadCsvRequest>) -> Result<Response<LoadCsvResponse>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let deadline = request_deadline(&request);
         let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, Some(req_payload.timeframe));
         tracing::info!(
             symbol = %req_payload.symbol,
             path = %req_payload.file_path,
             "Received LoadCsvRequest in main service, dispatching to handler."
         );
         // Calls handler from sibling module
-        load_csv_data::handle_load_csv_data(req_payload, self.market_data_store.clone()).await
+        let csv_format = engine.csv_format(&req_payload.symbol).await;
+        load_csv_data::handle_load_csv_data(req_payload, engine.market_data_store.clone(), engine.order_book.clone(), engine.portfolio.clone(), csv_format, deadline).await
+    }
+
+    async fn load_csv_batch(&self, request: Request<LoadCsvBatchRequest>) -> Result<Response<LoadCsvBatchResponse>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let deadline = request_deadline(&request);
+        let req_payload = request.into_inner();
+        tracing::info!(files = req_payload.files.len(), max_parallel = req_payload.max_parallel, "Received LoadCsvBatchRequest in main service, dispatching to handler.");
+        let mut csv_formats = Vec::with_capacity(req_payload.files.len());
+        for file in &req_payload.files {
+            csv_formats.push(engine.csv_format(&file.symbol).await);
+        }
+        load_csv_data::handle_load_csv_batch(
+            req_payload, engine.market_data_store.clone(), engine.order_book.clone(), engine.portfolio.clone(), csv_formats, deadline,
+        ).await
     }
 
     type GetMarketDataStream = ReceiverStream<Result<MarketDataResponse, Status>>;
     async fn get_market_data(&self, request: Request<MarketDataRequest>) -> Result<Response<Self::GetMarketDataStream>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
         let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, Some(req_payload.timeframe));
         tracing::info!(
             symbol = %req_payload.symbol,
             from_timestamp_ms = req_payload.from_timestamp,
             to_timestamp_ms = req_payload.to_timestamp,
+            follow_replay = req_payload.follow_replay,
             "Received GetMarketDataRequest in main service, dispatching to handler."
         );
-        get_market_data::handle_get_market_data(req_payload, self.market_data_store.clone()).await
+        get_market_data::handle_get_market_data(req_payload, engine.market_data_store.clone(), engine.replay.clone()).await
+    }
+
+    async fn list_symbols(&self, request: Request<ListSymbolsRequest>) -> Result<Response<ListSymbolsResponse>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        tracing::info!("Received ListSymbols request in main service, dispatching to handler.");
+        list_symbols::handle_list_symbols(engine.market_data_store.clone()).await
+    }
+
+    async fn get_aggregated_candles(&self, request: Request<AggregatedCandlesRequest>) -> Result<Response<AggregatedCandlesResponse>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, Some(req_payload.timeframe));
+        tracing::info!(
+            symbol = %req_payload.symbol,
+            timeframe = req_payload.timeframe,
+            source_timeframe = req_payload.source_timeframe,
+            "Received AggregatedCandlesRequest in main service, dispatching to handler."
+        );
+        get_aggregated_candles::handle_get_aggregated_candles(req_payload, engine.market_data_store.clone()).await
+    }
+
+    async fn get_series_stats(&self, request: Request<SeriesStatsRequest>) -> Result<Response<GrpcSeriesStats>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, Some(req_payload.timeframe));
+        tracing::info!(
+            symbol = %req_payload.symbol,
+            timeframe = req_payload.timeframe,
+            "Received SeriesStatsRequest in main service, dispatching to handler."
+        );
+        get_series_stats::handle_get_series_stats(req_payload, engine.market_data_store.clone()).await
+    }
+
+    async fn export_data(&self, request: Request<ExportDataRequest>) -> Result<Response<ExportDataResponse>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, Some(req_payload.timeframe));
+        tracing::info!(
+            symbol = %req_payload.symbol,
+            timeframe = req_payload.timeframe,
+            format = %req_payload.format,
+            indicators = req_payload.indicators.len(),
+            "Received ExportDataRequest in main service, dispatching to handler."
+        );
+        export_data::handle_export_data(req_payload, engine.market_data_store.clone()).await
+    }
+
+    type SubscribeCandlesStream = ReceiverStream<Result<GrpcCandleUpdate, Status>>;
+    async fn subscribe_candles(&self, request: Request<SubscribeCandlesRequest>) -> Result<Response<Self::SubscribeCandlesStream>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, Some(req_payload.timeframe));
+        tracing::info!(
+            symbol = %req_payload.symbol,
+            timeframe = req_payload.timeframe,
+            "Received SubscribeCandlesRequest in main service, dispatching to handler."
+        );
+        subscribe_candles::handle_subscribe_candles(req_payload, engine.market_data_store.clone(), engine.replay.clone()).await
     }
 
     async fn calculate_indicator(&self, request: Request<IndicatorRequest>) -> Result<Response<IndicatorResponse>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
         let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, Some(req_payload.timeframe));
         tracing::info!(
             symbol = %req_payload.symbol,
             indicator_type = %req_payload.indicator_type,
             parameters = %req_payload.parameters,
             "Received CalculateIndicatorRequest in main service, dispatching to handler."
         );
-        calculate_indicator::handle_calculate_indicator(req_payload, self.market_data_store.clone()).await
+        calculate_indicator::handle_calculate_indicator(req_payload, engine.market_data_store.clone()).await
     }
 
     async fn simulate_trade(&self, request: Request<TradeRequest>) -> Result<Response<TradeResponse>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
         let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, Some(req_payload.timeframe));
         tracing::info!(
             symbol = %req_payload.symbol,
             action = %req_payload.action,
             quantity = req_payload.quantity,
             order_type = %req_payload.order_type,
             price = ?req_payload.price,
+            time_in_force = %req_payload.time_in_force,
             "Received SimulateTradeRequest in main service, dispatching to handler."
         );
-        simulate_trade::handle_simulate_trade(req_payload, self.market_data_store.clone()).await
+        simulate_trade::handle_simulate_trade(req_payload, engine.market_data_store.clone(), engine.order_book.clone(), engine.replay.clone(), engine.portfolio.clone(), engine.risk_limits.clone(), engine.contracts.clone()).await
+    }
+
+    async fn cancel_order(&self, request: Request<CancelOrderRequest>) -> Result<Response<OrderState>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(order_id = %req_payload.order_id, "Received CancelOrderRequest in main service, dispatching to handler.");
+        manage_orders::handle_cancel_order(req_payload, engine.order_book.clone()).await
+    }
+
+    async fn modify_order(&self, request: Request<ModifyOrderRequest>) -> Result<Response<OrderState>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(
+            order_id = %req_payload.order_id,
+            limit_price = ?req_payload.limit_price,
+            quantity = ?req_payload.quantity,
+            "Received ModifyOrderRequest in main service, dispatching to handler."
+        );
+        manage_orders::handle_modify_order(req_payload, engine.market_data_store.clone(), engine.order_book.clone(), engine.replay.clone(), engine.portfolio.clone(), engine.risk_limits.clone(), engine.contracts.clone()).await
+    }
+
+    async fn get_trade_history(&self, request: Request<TradeHistoryRequest>) -> Result<Response<TradeHistoryResponse>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, None);
+        tracing::info!(
+            symbol = %req_payload.symbol,
+            from_timestamp_ms = ?req_payload.from_timestamp,
+            to_timestamp_ms = ?req_payload.to_timestamp,
+            "Received TradeHistoryRequest in main service, dispatching to handler."
+        );
+        get_trade_history::handle_get_trade_history(req_payload, engine.portfolio.clone()).await
+    }
+
+    type TradingSessionStream = ReceiverStream<Result<SessionEvent, Status>>;
+
+    async fn trading_session(&self, request: Request<tonic::Streaming<SessionRequest>>) -> Result<Response<Self::TradingSessionStream>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        tracing::info!("Received TradingSession request in main service, dispatching to handler.");
+        trading_session::handle_trading_session(request.into_inner(), engine.session_context()).await
+    }
+
+    async fn suggest_position_size(&self, request: Request<PositionSizeRequest>) -> Result<Response<PositionSizeResponse>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, None);
+        tracing::info!(
+            symbol = %req_payload.symbol,
+            method = ?req_payload.sizing.as_ref().map(|s| s.method.as_str()),
+            "Received PositionSizeRequest in main service, dispatching to handler."
+        );
+        suggest_position_size::handle_suggest_position_size(
+            req_payload,
+            engine.market_data_store.clone(),
+            engine.replay.clone(),
+            engine.portfolio.clone(),
+            engine.contracts.clone(),
+        ).await
+    }
+
+    async fn start_replay(&self, request: Request<StartReplayRequest>) -> Result<Response<ReplayStatus>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, None);
+        tracing::info!(
+            symbol = %req_payload.symbol,
+            from_timestamp_ms = ?req_payload.from_timestamp,
+            speed = req_payload.speed,
+            start_paused = req_payload.start_paused,
+            "Received StartReplayRequest in main service, dispatching to handler."
+        );
+        replay_control::handle_start_replay(req_payload, engine.market_data_store.clone(), engine.replay.clone(), engine.order_book.clone(), engine.portfolio.clone()).await
+    }
+
+    async fn pause_replay(&self, request: Request<ReplayControlRequest>) -> Result<Response<ReplayStatus>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, None);
+        tracing::info!(symbol = %req_payload.symbol, "Received PauseReplayRequest in main service, dispatching to handler.");
+        replay_control::handle_pause_replay(req_payload, engine.replay.clone()).await
+    }
+
+    async fn resume_replay(&self, request: Request<ReplayControlRequest>) -> Result<Response<ReplayStatus>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, None);
+        tracing::info!(symbol = %req_payload.symbol, "Received ResumeReplayRequest in main service, dispatching to handler.");
+        replay_control::handle_resume_replay(req_payload, engine.replay.clone(), engine.order_book.clone(), engine.portfolio.clone()).await
+    }
+
+    async fn step_replay(&self, request: Request<StepReplayRequest>) -> Result<Response<ReplayStatus>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, None);
+        tracing::info!(symbol = %req_payload.symbol, steps = req_payload.steps, "Received StepReplayRequest in main service, dispatching to handler.");
+        replay_control::handle_step_replay(req_payload, engine.replay.clone(), engine.order_book.clone(), engine.portfolio.clone()).await
+    }
+
+    async fn set_replay_speed(&self, request: Request<ReplaySpeedRequest>) -> Result<Response<ReplayStatus>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, None);
+        tracing::info!(symbol = %req_payload.symbol, speed = req_payload.speed, "Received SetReplaySpeedRequest in main service, dispatching to handler.");
+        replay_control::handle_set_replay_speed(req_payload, engine.replay.clone()).await
+    }
+
+    async fn stop_replay(&self, request: Request<ReplayControlRequest>) -> Result<Response<ReplayStatus>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, None);
+        tracing::info!(symbol = %req_payload.symbol, "Received StopReplayRequest in main service, dispatching to handler.");
+        replay_control::handle_stop_replay(req_payload, engine.replay.clone()).await
+    }
+
+    async fn run_backtest(&self, request: Request<BacktestRequest>) -> Result<Response<BacktestResponse>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let deadline = request_deadline(&request);
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, None);
+        tracing::info!(
+            symbol = %req_payload.symbol,
+            strategy = %req_payload.strategy,
+            parameters = %req_payload.parameters,
+            "Received BacktestRequest in main service, dispatching to handler."
+        );
+        run_backtest::handle_run_backtest(req_payload, engine.market_data_store.clone(), engine.contracts.clone(), deadline).await
+    }
+
+    type StreamBacktestStream = ReceiverStream<Result<GrpcBacktestEvent, Status>>;
+
+    async fn stream_backtest(&self, request: Request<StreamBacktestRequest>) -> Result<Response<Self::StreamBacktestStream>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let deadline = request_deadline(&request);
+        let req_payload = request.into_inner();
+        record_series(req_payload.backtest.as_ref().map(|b| b.symbol.as_str()).unwrap_or_default(), None);
+        tracing::info!(
+            symbol = ?req_payload.backtest.as_ref().map(|b| b.symbol.as_str()),
+            bar_delay_ms = req_payload.bar_delay_ms,
+            "Received StreamBacktestRequest in main service, dispatching to handler."
+        );
+        run_backtest::handle_stream_backtest(req_payload, engine.market_data_store.clone(), engine.contracts.clone(), deadline).await
+    }
+
+    async fn run_stress_test(&self, request: Request<StressTestRequest>) -> Result<Response<StressTestResponse>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let deadline = request_deadline(&request);
+        let req_payload = request.into_inner();
+        record_series(req_payload.backtest.as_ref().map(|b| b.symbol.as_str()).unwrap_or_default(), None);
+        tracing::info!(
+            symbol = ?req_payload.backtest.as_ref().map(|b| b.symbol.as_str()),
+            scenarios = req_payload.scenarios.len(),
+            "Received StressTestRequest in main service, dispatching to handler."
+        );
+        run_stress_test::handle_run_stress_test(req_payload, engine.market_data_store.clone(), engine.contracts.clone(), deadline).await
+    }
+
+    async fn get_session_report(&self, request: Request<SessionReportRequest>) -> Result<Response<GrpcPnlReport>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, None);
+        tracing::info!(symbol = %req_payload.symbol, "Received SessionReportRequest in main service, dispatching to handler.");
+        get_session_report::handle_get_session_report(req_payload, engine.market_data_store.clone(), engine.replay.clone(), engine.portfolio.clone(), engine.contracts.clone()).await
+    }
+
+    type RunMonteCarloStream = ReceiverStream<Result<MonteCarloProgress, Status>>;
+    async fn run_monte_carlo(&self, request: Request<MonteCarloRequest>) -> Result<Response<Self::RunMonteCarloStream>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let deadline = request_deadline(&request);
+        let req_payload = request.into_inner();
+        record_series(req_payload.backtest.as_ref().map(|b| b.symbol.as_str()).unwrap_or_default(), None);
+        tracing::info!(
+            symbol = %req_payload.backtest.as_ref().map(|b| b.symbol.as_str()).unwrap_or_default(),
+            iterations = req_payload.iterations,
+            method = %req_payload.method,
+            "Received MonteCarloRequest in main service, dispatching to handler."
+        );
+        run_monte_carlo::handle_run_monte_carlo(req_payload, engine.market_data_store.clone(), engine.contracts.clone(), deadline).await
+    }
+
+    async fn reset_account(&self, request: Request<ResetAccountRequest>) -> Result<Response<AccountState>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(initial_capital = req_payload.initial_capital, "Received ResetAccountRequest in main service, dispatching to handler.");
+        manage_account::handle_reset_account(
+            req_payload,
+            engine.market_data_store.clone(),
+            engine.replay.clone(),
+            engine.order_book.clone(),
+            engine.portfolio.clone(),
+            engine.account.clone(),
+        ).await
+    }
+
+    async fn snapshot_account(&self, request: Request<SnapshotAccountRequest>) -> Result<Response<AccountState>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(path = %req_payload.path, "Received SnapshotAccountRequest in main service, dispatching to handler.");
+        manage_account::handle_snapshot_account(
+            req_payload,
+            engine.market_data_store.clone(),
+            engine.replay.clone(),
+            engine.order_book.clone(),
+            engine.portfolio.clone(),
+            engine.account.clone(),
+        ).await
+    }
+
+    async fn deposit_cash(&self, request: Request<CashTransferRequest>) -> Result<Response<AccountState>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(amount = req_payload.amount, "Received deposit CashTransferRequest in main service, dispatching to handler.");
+        manage_account::handle_deposit_cash(
+            req_payload,
+            engine.market_data_store.clone(),
+            engine.replay.clone(),
+            engine.order_book.clone(),
+            engine.portfolio.clone(),
+            engine.account.clone(),
+        ).await
+    }
+
+    async fn withdraw_cash(&self, request: Request<CashTransferRequest>) -> Result<Response<AccountState>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(amount = req_payload.amount, "Received withdrawal CashTransferRequest in main service, dispatching to handler.");
+        manage_account::handle_withdraw_cash(
+            req_payload,
+            engine.market_data_store.clone(),
+            engine.replay.clone(),
+            engine.order_book.clone(),
+            engine.portfolio.clone(),
+            engine.account.clone(),
+        ).await
+    }
+
+    async fn set_base_currency(&self, request: Request<BaseCurrencyRequest>) -> Result<Response<AccountState>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(currency = %req_payload.currency, "Received BaseCurrencyRequest in main service, dispatching to handler.");
+        manage_account::handle_set_base_currency(
+            req_payload,
+            engine.market_data_store.clone(),
+            engine.replay.clone(),
+            engine.order_book.clone(),
+            engine.portfolio.clone(),
+            engine.account.clone(),
+        ).await
+    }
+
+    async fn get_risk_limits(&self, request: Request<RiskLimitsRequest>) -> Result<Response<GrpcRiskLimits>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        tracing::info!("Received RiskLimitsRequest in main service, dispatching to handler.");
+        manage_risk::handle_get_risk_limits(engine.risk_limits.clone()).await
+    }
+
+    async fn set_risk_limits(&self, request: Request<GrpcRiskLimits>) -> Result<Response<GrpcRiskLimits>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(
+            max_position = ?req_payload.max_position,
+            max_daily_loss = ?req_payload.max_daily_loss,
+            max_open_orders = ?req_payload.max_open_orders,
+            "Received SetRiskLimits request in main service, dispatching to handler."
+        );
+        manage_risk::handle_set_risk_limits(req_payload, engine.risk_limits.clone()).await
+    }
+
+    async fn create_alert(&self, request: Request<CreateAlertRequest>) -> Result<Response<GrpcAlert>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        record_series(&req_payload.symbol, Some(req_payload.timeframe));
+        tracing::info!(
+            symbol = %req_payload.symbol,
+            kind = %req_payload.kind,
+            level = req_payload.level,
+            "Received CreateAlertRequest in main service, dispatching to handler."
+        );
+        manage_alerts::handle_create_alert(req_payload, engine.alerts.clone(), engine.market_data_store.clone(), engine.replay.clone()).await
+    }
+
+    async fn delete_alert(&self, request: Request<AlertRequest>) -> Result<Response<GrpcAlert>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(alert_id = %req_payload.alert_id, "Received DeleteAlert request in main service, dispatching to handler.");
+        manage_alerts::handle_delete_alert(req_payload, engine.alerts.clone()).await
+    }
+
+    async fn list_alerts(&self, request: Request<ListAlertsRequest>) -> Result<Response<ListAlertsResponse>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(symbol = %req_payload.symbol, "Received ListAlertsRequest in main service, dispatching to handler.");
+        manage_alerts::handle_list_alerts(req_payload, engine.alerts.clone()).await
+    }
+
+    type SubscribeAlertsStream = ReceiverStream<Result<GrpcAlertEvent, Status>>;
+    async fn subscribe_alerts(&self, request: Request<SubscribeAlertsRequest>) -> Result<Response<Self::SubscribeAlertsStream>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(symbol = %req_payload.symbol, "Received SubscribeAlertsRequest in main service, dispatching to handler.");
+        manage_alerts::handle_subscribe_alerts(req_payload, engine.alerts.clone()).await
+    }
+
+    async fn get_engine_info(&self, request: Request<EngineInfoRequest>) -> Result<Response<EngineInfo>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        tracing::info!("Received GetEngineInfo request in main service, dispatching to handler.");
+        get_engine_info::handle_get_engine_info(engine.market_data_store.clone(), engine.started_at, &engine.features).await
+    }
+
+    async fn reload_settings(&self, request: Request<ReloadSettingsRequest>) -> Result<Response<ReloadSettingsResponse>, Status> {
+        // Settings are engine-wide; the risk limits reloaded are the default namespace's.
+        self.in_namespace(request.metadata()).await?;
+        tracing::info!("Received ReloadSettings request in main service, dispatching to handler.");
+        reload_settings::handle_reload_settings(&self.settings, &self.risk_limits, &self.contracts).await
+    }
+
+    async fn submit_job(&self, request: Request<SubmitJobRequest>) -> Result<Response<GrpcJobStatus>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(kind = ?req_payload.job.as_ref().map(manage_jobs::job_kind), "Received SubmitJobRequest in main service, dispatching to handler.");
+        let csv_format = engine.csv_format(manage_jobs::job_symbol(req_payload.job.as_ref())).await;
+        manage_jobs::handle_submit_job(req_payload, engine.market_data_store.clone(), engine.order_book.clone(), engine.portfolio.clone(), engine.contracts.clone(), csv_format, engine.jobs.clone()).await
+    }
+
+    type WatchJobStream = ReceiverStream<Result<GrpcJobStatus, Status>>;
+    async fn watch_job(&self, request: Request<JobRequest>) -> Result<Response<Self::WatchJobStream>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(job_id = %req_payload.job_id, "Received WatchJob request in main service, dispatching to handler.");
+        manage_jobs::handle_watch_job(req_payload, engine.jobs.clone()).await
+    }
+
+    async fn cancel_job(&self, request: Request<JobRequest>) -> Result<Response<GrpcJobStatus>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(job_id = %req_payload.job_id, "Received CancelJob request in main service, dispatching to handler.");
+        manage_jobs::handle_cancel_job(req_payload, engine.jobs.clone()).await
+    }
+
+    async fn get_job_result(&self, request: Request<JobRequest>) -> Result<Response<JobResult>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        let req_payload = request.into_inner();
+        tracing::info!(job_id = %req_payload.job_id, "Received GetJobResult request in main service, dispatching to handler.");
+        manage_jobs::handle_get_job_result(req_payload, engine.jobs.clone()).await
+    }
+
+    async fn list_jobs(&self, request: Request<ListJobsRequest>) -> Result<Response<ListJobsResponse>, Status> {
+        let engine = self.in_namespace(request.metadata()).await?;
+        tracing::info!("Received ListJobs request in main service, dispatching to handler.");
+        manage_jobs::handle_list_jobs(engine.jobs.clone()).await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::symbols::SymbolSettings;
     use crate::data::market_data::MarketDataStore;
-    use shared::models::{Candle as DomainCandle, TimeFrame}; // Moved here
+    use shared::models::{to_price, Candle as DomainCandle, Price, TimeFrame}; // Moved here
     use tempfile::NamedTempFile;
     use std::io::Write;
     use chrono::Utc;
+    use crate::services::{GrpcStressScenario as StressScenario, GrpcTimeFrame, PositionSizing};
     // Removed: use crate::services::ProtoCandle as GrpcCandle; // This was causing unused import warning
 
     fn create_test_engine() -> MyTradingEngine {
@@ -124,13 +791,13 @@ mod tests {
         let csv_content = "Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade\nWINFUT;30/12/2024;18:20:00;124.080;124.090;123.938;123.983;600.822.115,84;24.228";
         let tmp_file = create_dummy_csv(csv_content);
         let file_path = tmp_file.path().to_str().unwrap().to_string();
-        let request = Request::new(LoadCsvRequest { file_path: file_path.clone(), symbol: "WINFUT".to_string() });
+        let request = Request::new(LoadCsvRequest { file_path: file_path.clone(), symbol: "WINFUT".to_string(), ..Default::default() });
         let response = engine.load_csv_data(request).await.unwrap().into_inner();
         assert!(response.success);
         assert_eq!(response.candles_loaded, 1);
         assert!(response.message.contains("Loaded 1 candles"));
         let store = engine.market_data_store.read().await;
-        let candles_in_store = store.get_candles("WINFUT", TimeFrame::Day1, None, None);
+        let candles_in_store = store.get_candles("WINFUT", TimeFrame::Day1, None, None).unwrap();
         assert!(candles_in_store.is_some());
         assert_eq!(candles_in_store.unwrap().len(), 1);
     }
@@ -138,12 +805,13 @@ mod tests {
     #[tokio::test]
     async fn test_load_csv_data_parsing_error_file_not_found() {
         let engine = create_test_engine();
-        let request = Request::new(LoadCsvRequest { file_path: "non_existent_file.csv".to_string(), symbol: "TEST".to_string() });
+        let request = Request::new(LoadCsvRequest { file_path: "non_existent_file.csv".to_string(), symbol: "TEST".to_string(), ..Default::default() });
         let result = engine.load_csv_data(request).await;
         assert!(result.is_err());
         let status = result.err().unwrap();
-        assert_eq!(status.code(), tonic::Code::Internal);
+        assert_eq!(status.code(), tonic::Code::NotFound);
         assert!(status.message().contains("I/O error"));
+        assert_eq!(ErrorDetails::from_status(&status).unwrap().code(), Some(ErrorCode::FileNotFound));
     }
 
     #[tokio::test]
@@ -152,7 +820,7 @@ mod tests {
         let csv_content = "Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade\nWINFUT;30/12/2024;18:20:00;NOT_A_NUMBER";
         let tmp_file = create_dummy_csv(csv_content);
         let file_path = tmp_file.path().to_str().unwrap().to_string();
-        let request = Request::new(LoadCsvRequest { file_path: file_path.clone(), symbol: "WINFUT".to_string() });
+        let request = Request::new(LoadCsvRequest { file_path: file_path.clone(), symbol: "WINFUT".to_string(), ..Default::default() });
         let result = engine.load_csv_data(request).await;
         assert!(result.is_err());
         let status = result.err().unwrap();
@@ -168,7 +836,7 @@ mod tests {
         let csv_content = "Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade\nWINFUT;30/12/2024;18:20:00;NOT_A_NUMBER;124.090;123.938;123.983;600.822.115,84;24.228";
         let tmp_file = create_dummy_csv(csv_content);
         let file_path = tmp_file.path().to_str().unwrap().to_string();
-        let request = Request::new(LoadCsvRequest { file_path: file_path.clone(), symbol: "WINFUT".to_string() });
+        let request = Request::new(LoadCsvRequest { file_path: file_path.clone(), symbol: "WINFUT".to_string(), ..Default::default() });
         let result = engine.load_csv_data(request).await;
         assert!(result.is_err());
         let status = result.err().unwrap();
@@ -178,27 +846,45 @@ mod tests {
         assert!(status.message().contains("Failed to parse decimal 'NOT_A_NUMBER'"));
     }
 
+    #[tokio::test]
+    async fn test_load_csv_data_can_skip_bad_rows() {
+        use crate::services::CsvRowError;
+        let engine = create_test_engine();
+        let csv_content = "Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade\n\
+            WINFUT;30/12/2024;18:20:00;NOT_A_NUMBER;124.090;123.938;123.983;600.822.115,84;24.228\n\
+            WINFUT;30/12/2024;18:25:00;124.080;124.090;123.938;123.983;600.822.115,84;24.228";
+        let tmp_file = create_dummy_csv(csv_content);
+        let request = LoadCsvRequest { file_path: tmp_file.path().to_str().unwrap().to_string(), symbol: "WINFUT".to_string(), skip_bad_rows: true, ..Default::default() };
+        let response = engine.load_csv_data(Request::new(request)).await.unwrap().into_inner();
+        assert_eq!((response.candles_loaded, response.rows_skipped), (1, 1));
+        assert_eq!(response.row_errors, vec![CsvRowError {
+            line: 2, column: "Abertura".to_string(), reason: "Error parsing 'Abertura': Failed to parse decimal 'NOT_A_NUMBER': Invalid decimal: unknown character".to_string(),
+        }]);
+        assert!(response.message.contains("skipping 1 bad rows"));
+        assert_eq!(engine.market_data_store.read().await.get_candles("WINFUT", TimeFrame::Day1, None, None).unwrap().unwrap().len(), 1);
+    }
+
     #[tokio::test]
     async fn test_simulate_trade_no_market_data() {
         let engine = create_test_engine();
-        let request = Request::new(TradeRequest { symbol: "NODATA".to_string(), action: "BUY".to_string(), quantity: 10.0, price: None, order_type: "MARKET".to_string() });
+        let request = Request::new(TradeRequest { symbol: "NODATA".to_string(), action: "BUY".to_string(), quantity: 10.0, price: None, order_type: "MARKET".to_string(), ..Default::default() });
         let response = engine.simulate_trade(request).await.unwrap().into_inner();
         assert!(!response.success);
         assert!(response.message.contains("No market data available"));
     }
 
     fn sample_candle(symbol: &str, open: f64, high: f64, low: f64, close: f64) -> DomainCandle {
-        DomainCandle { symbol: symbol.to_string(), timestamp: Utc::now(), open, high, low, close, volume: 1000.0, trades: 100 }
+        DomainCandle { symbol: symbol.to_string(), timestamp: Utc::now(), open: to_price(open), high: to_price(high), low: to_price(low), close: to_price(close), volume: 1000.0, trades: 100 }
     }
 
     #[tokio::test]
     async fn test_simulate_trade_market_buy() {
         let candle = sample_candle("TEST", 100.0, 102.0, 98.0, 101.0);
         let engine = create_test_engine_with_candle("TEST", candle.clone()).await;
-        let request = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "BUY".to_string(), quantity: 10.0, price: None, order_type: "MARKET".to_string() });
+        let request = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "BUY".to_string(), quantity: 10.0, price: None, order_type: "MARKET".to_string(), ..Default::default() });
         let response = engine.simulate_trade(request).await.unwrap().into_inner();
         assert!(response.success);
-        assert_eq!(response.filled_price, candle.close);
+        assert_eq!(response.filled_price, "101");
         assert_eq!(response.filled_quantity, 10.0);
         assert!(response.message.contains("Market BUY order"));
     }
@@ -207,8 +893,8 @@ mod tests {
     async fn test_simulate_trade_limit_buy_fill() {
         let candle = sample_candle("TEST", 100.0, 102.0, 98.0, 101.0);
         let engine = create_test_engine_with_candle("TEST", candle.clone()).await;
-        let limit_price = 99.0;
-        let request = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "BUY".to_string(), quantity: 5.0, price: Some(limit_price), order_type: "LIMIT".to_string() });
+        let limit_price = "99";
+        let request = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "BUY".to_string(), quantity: 5.0, price: Some(limit_price.to_string()), order_type: "LIMIT".to_string(), ..Default::default() });
         let response = engine.simulate_trade(request).await.unwrap().into_inner();
         assert!(response.success);
         assert_eq!(response.filled_price, limit_price);
@@ -219,8 +905,8 @@ mod tests {
     async fn test_simulate_trade_limit_buy_no_fill() {
         let candle = sample_candle("TEST", 100.0, 102.0, 99.0, 101.0);
         let engine = create_test_engine_with_candle("TEST", candle.clone()).await;
-        let limit_price = 98.0;
-        let request = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "BUY".to_string(), quantity: 5.0, price: Some(limit_price), order_type: "LIMIT".to_string() });
+        let limit_price = "98";
+        let request = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "BUY".to_string(), quantity: 5.0, price: Some(limit_price.to_string()), order_type: "LIMIT".to_string(), ..Default::default() });
         let response = engine.simulate_trade(request).await.unwrap().into_inner();
         assert!(!response.success);
         assert!(response.message.contains("not filled"));
@@ -230,8 +916,8 @@ mod tests {
     async fn test_simulate_trade_limit_sell_fill() {
         let candle = sample_candle("TEST", 100.0, 102.0, 98.0, 101.0);
         let engine = create_test_engine_with_candle("TEST", candle.clone()).await;
-        let limit_price = 101.5;
-        let request = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "SELL".to_string(), quantity: 7.0, price: Some(limit_price), order_type: "LIMIT".to_string() });
+        let limit_price = "101.5";
+        let request = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "SELL".to_string(), quantity: 7.0, price: Some(limit_price.to_string()), order_type: "LIMIT".to_string(), ..Default::default() });
         let response = engine.simulate_trade(request).await.unwrap().into_inner();
         assert!(response.success);
         assert_eq!(response.filled_price, limit_price);
@@ -242,8 +928,8 @@ mod tests {
     async fn test_simulate_trade_limit_sell_no_fill() {
         let candle = sample_candle("TEST", 100.0, 101.0, 98.0, 100.5);
         let engine = create_test_engine_with_candle("TEST", candle.clone()).await;
-        let limit_price = 101.5;
-        let request = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "SELL".to_string(), quantity: 7.0, price: Some(limit_price), order_type: "LIMIT".to_string() });
+        let limit_price = "101.5";
+        let request = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "SELL".to_string(), quantity: 7.0, price: Some(limit_price.to_string()), order_type: "LIMIT".to_string(), ..Default::default() });
         let response = engine.simulate_trade(request).await.unwrap().into_inner();
         assert!(!response.success);
         assert!(response.message.contains("not filled"));
@@ -259,6 +945,7 @@ mod tests {
             quantity: 1.0,
             price: None,
             order_type: "LIMIT".to_string(),
+            ..Default::default()
         });
         let response = engine.simulate_trade(request).await.unwrap().into_inner();
         assert!(!response.success);
@@ -276,6 +963,7 @@ mod tests {
             quantity: 1.0,
             price: None,
             order_type: order_type.clone(),
+            ..Default::default()
         });
         let response = engine.simulate_trade(request).await.unwrap().into_inner();
         assert!(!response.success);
@@ -291,11 +979,1132 @@ mod tests {
             symbol: "TEST".to_string(),
             action: action.clone(),
             quantity: 1.0,
-            price: Some(100.0),
+            price: Some("100".to_string()),
             order_type: "LIMIT".to_string(),
+            ..Default::default()
         });
         let response = engine.simulate_trade(request).await.unwrap().into_inner();
         assert!(!response.success);
         assert_eq!(response.message, format!("Unknown action '{}' for LIMIT order. Use 'BUY' or 'SELL'.", action));
     }
+
+    #[tokio::test]
+    async fn test_simulate_trade_rejects_quantities_that_are_not_positive() {
+        let candle = sample_candle("TEST", 100.0, 101.0, 99.0, 100.0);
+        let engine = create_test_engine_with_candle("TEST", candle).await;
+        for quantity in [0.0, f64::NAN] {
+            let request = Request::new(TradeRequest {
+                symbol: "TEST".to_string(),
+                action: "BUY".to_string(),
+                quantity,
+                order_type: "MARKET".to_string(),
+                ..Default::default()
+            });
+            let status = engine.simulate_trade(request).await.unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+            assert!(status.message().contains("quantity"));
+        }
+        assert_eq!(engine.portfolio.read().await.fills().count(), 0);
+        // The engine keeps serving trades afterwards.
+        let request = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "BUY".to_string(), quantity: 1.0, order_type: "MARKET".to_string(), ..Default::default() });
+        assert!(engine.simulate_trade(request).await.unwrap().into_inner().success);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_trade_limit_rests_and_fills_on_new_candles() {
+        use chrono::TimeZone;
+        use crate::simulation::OrderStatus;
+        let mut candle = sample_candle("WINFUT", 124000.0, 124100.0, 123990.0, 124050.0);
+        candle.timestamp = Utc.with_ymd_and_hms(2024, 12, 27, 18, 0, 0).unwrap();
+        let engine = create_test_engine_with_candle("WINFUT", candle).await;
+        let request = Request::new(TradeRequest {
+            symbol: "WINFUT".to_string(),
+            action: "BUY".to_string(),
+            quantity: 1.0,
+            price: Some("123950".to_string()),
+            order_type: "LIMIT".to_string(),
+            time_in_force: "GTC".to_string(),
+            ..Default::default()
+        });
+        let response = engine.simulate_trade(request).await.unwrap().into_inner();
+        assert!(!response.success);
+        assert_eq!(response.status, "WORKING");
+        assert_eq!(engine.order_book.read().await.working_orders(Some("WINFUT")).len(), 1);
+
+        // A later candle (low 123.938) trades through the resting limit.
+        let csv_content = "Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade\nWINFUT;30/12/2024;18:20:00;124.080;124.090;123.938;123.983;600.822.115,84;24.228";
+        let tmp_file = create_dummy_csv(csv_content);
+        let load_request = Request::new(LoadCsvRequest { file_path: tmp_file.path().to_str().unwrap().to_string(), symbol: "WINFUT".to_string(), ..Default::default() });
+        engine.load_csv_data(load_request).await.unwrap();
+
+        let book = engine.order_book.read().await;
+        let order = book.get(&response.order_id).unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.filled_price, Some(Price::from(123_950)));
+    }
+
+    #[tokio::test]
+    async fn test_simulate_trade_gtd_requires_expiry() {
+        let candle = sample_candle("TEST", 100.0, 101.0, 99.0, 100.0);
+        let engine = create_test_engine_with_candle("TEST", candle).await;
+        let request = Request::new(TradeRequest {
+            symbol: "TEST".to_string(),
+            action: "BUY".to_string(),
+            quantity: 1.0,
+            price: Some("90".to_string()),
+            order_type: "LIMIT".to_string(),
+            time_in_force: "GTD".to_string(),
+            ..Default::default()
+        });
+        let response = engine.simulate_trade(request).await.unwrap().into_inner();
+        assert!(!response.success);
+        assert_eq!(response.status, "REJECTED");
+        assert!(response.message.contains("expire_at"));
+        assert!(engine.order_book.read().await.working_orders(None).is_empty());
+    }
+    #[tokio::test]
+    async fn test_replay_reveals_candles_to_market_data_stream() {
+        use chrono::{Duration, TimeZone};
+        use tokio_stream::StreamExt;
+        let engine = create_test_engine();
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let candles: Vec<DomainCandle> = (0..3).map(|i| {
+            let mut c = sample_candle("TEST", 100.0, 102.0, 98.0, 100.0 + i as f64);
+            c.timestamp = t0 + Duration::days(i);
+            c
+        }).collect();
+        engine.market_data_store.write().await.add_candles("TEST", TimeFrame::Day1, candles.clone()).unwrap();
+
+        let start = Request::new(StartReplayRequest { symbol: "TEST".to_string(), from_timestamp: Some(candles[1].timestamp.timestamp_millis()), speed: 0.0, start_paused: true });
+        let status = engine.start_replay(start).await.unwrap().into_inner();
+        assert_eq!(status.state, "PAUSED");
+        assert_eq!((status.position, status.total), (1, 3));
+        assert_eq!(status.speed, 1.0);
+
+        let request = Request::new(MarketDataRequest { symbol: "TEST".to_string(), from_timestamp: 0, to_timestamp: Utc::now().timestamp_millis(), follow_replay: true, ..Default::default() });
+        let mut stream = engine.get_market_data(request).await.unwrap().into_inner();
+        let history = stream.next().await.unwrap().unwrap();
+        assert_eq!(history.candles.len(), 1); // Only the candle before from_timestamp is visible
+
+        let status = engine.step_replay(Request::new(StepReplayRequest { symbol: "TEST".to_string(), steps: 0 })).await.unwrap().into_inner();
+        assert_eq!(status.position, 2);
+        let live = stream.next().await.unwrap().unwrap();
+        assert_eq!(live.candles.len(), 1);
+        assert_eq!(live.candles[0].close, "101");
+
+        // The simulator prices market orders off the replay's current candle, not the last stored one.
+        let trade = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "BUY".to_string(), quantity: 1.0, price: None, order_type: "MARKET".to_string(), ..Default::default() });
+        assert_eq!(engine.simulate_trade(trade).await.unwrap().into_inner().filled_price, "101");
+
+        // Finishing the replay closes the follow stream.
+        engine.step_replay(Request::new(StepReplayRequest { symbol: "TEST".to_string(), steps: 5 })).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap().candles[0].close, "102");
+        assert!(stream.next().await.is_none());
+
+        let stopped = engine.stop_replay(Request::new(ReplayControlRequest { symbol: "TEST".to_string() })).await.unwrap().into_inner();
+        assert_eq!(stopped.state, "FINISHED");
+        let err = engine.pause_replay(Request::new(ReplayControlRequest { symbol: "TEST".to_string() })).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_session_report_tracks_simulated_fills() {
+        use chrono::{Duration, TimeZone};
+        let engine = create_test_engine();
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let candles: Vec<DomainCandle> = (0..3).map(|i| {
+            let mut c = sample_candle("TEST", 100.0, 102.0, 98.0, 100.0 + i as f64);
+            c.timestamp = t0 + Duration::days(i);
+            c
+        }).collect();
+        engine.market_data_store.write().await.add_candles("TEST", TimeFrame::Day1, candles.clone()).unwrap();
+        let start = Request::new(StartReplayRequest { symbol: "TEST".to_string(), from_timestamp: Some(candles[1].timestamp.timestamp_millis()), speed: 0.0, start_paused: true });
+        engine.start_replay(start).await.unwrap();
+
+        // Buy 10 at the first close (100), sell 10 on the next bar (close 101).
+        let buy = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "BUY".to_string(), quantity: 10.0, price: None, order_type: "MARKET".to_string(), ..Default::default() });
+        engine.simulate_trade(buy).await.unwrap();
+        engine.step_replay(Request::new(StepReplayRequest { symbol: "TEST".to_string(), steps: 1 })).await.unwrap();
+        let sell = Request::new(TradeRequest { symbol: "TEST".to_string(), action: "SELL".to_string(), quantity: 10.0, price: None, order_type: "MARKET".to_string(), ..Default::default() });
+        engine.simulate_trade(sell).await.unwrap();
+
+        let report = engine.get_session_report(Request::new(SessionReportRequest { symbol: String::new() })).await.unwrap().into_inner();
+        assert_eq!(report.trades.len(), 1);
+        assert_eq!(report.trades[0].direction, "LONG");
+        assert_eq!(report.trades[0].pnl, "10");
+        assert!((report.realized_pnl - 10.0).abs() < 1e-9);
+        assert_eq!(report.equity_curve.len(), 2); // From the first fill to the last revealed candle
+        assert!((report.total_pnl - 10.0).abs() < 1e-9);
+
+        let other = engine.get_session_report(Request::new(SessionReportRequest { symbol: "OTHER".to_string() })).await.unwrap().into_inner();
+        assert!(other.trades.is_empty());
+        assert_eq!(other.final_equity, other.initial_capital);
+    }
+
+    #[tokio::test]
+    async fn test_run_backtest() {
+        use chrono::{Duration, TimeZone};
+        let engine = create_test_engine();
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        // Down, up, down: one crossover in each direction.
+        let closes = [10.0, 9.0, 8.0, 7.0, 9.0, 11.0, 12.0, 8.0, 5.0, 4.0];
+        let candles: Vec<DomainCandle> = closes.iter().enumerate().map(|(i, &close)| {
+            let mut c = sample_candle("TEST", close, close, close, close);
+            c.timestamp = t0 + Duration::days(i as i64);
+            c
+        }).collect();
+        engine.market_data_store.write().await.add_candles("TEST", TimeFrame::Day1, candles).unwrap();
+
+        let request = Request::new(BacktestRequest {
+            symbol: "TEST".to_string(),
+            strategy: "sma_crossover".to_string(),
+            parameters: r#"{"fast_period": 2, "slow_period": 3}"#.to_string(),
+            initial_capital: 1_000.0,
+            quantity: 1.0,
+            ..Default::default()
+        });
+        let response = engine.run_backtest(request).await.unwrap().into_inner();
+        assert_eq!(response.strategy_name, "SMA Crossover(2, 3)");
+        let report = response.report.unwrap();
+        assert_eq!(report.initial_capital, 1_000.0);
+        assert_eq!(report.trades.len(), 1);
+        assert_eq!(report.equity_curve.len(), closes.len());
+        assert_eq!(response.metrics.unwrap().total_trades, 1);
+
+        let bad = Request::new(BacktestRequest { symbol: "TEST".to_string(), strategy: "unknown".to_string(), ..Default::default() });
+        assert_eq!(engine.run_backtest(bad).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+        let missing = Request::new(BacktestRequest { symbol: "NODATA".to_string(), ..Default::default() });
+        assert_eq!(engine.run_backtest(missing).await.unwrap_err().code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_run_monte_carlo_streams_progress_and_summary() {
+        use chrono::{Duration, TimeZone};
+        use tokio_stream::StreamExt;
+        let engine = create_test_engine();
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let closes = [10.0, 9.0, 8.0, 7.0, 9.0, 11.0, 12.0, 8.0, 5.0, 4.0];
+        let candles: Vec<DomainCandle> = closes.iter().enumerate().map(|(i, &close)| {
+            let mut c = sample_candle("TEST", close, close, close, close);
+            c.timestamp = t0 + Duration::days(i as i64);
+            c
+        }).collect();
+        engine.market_data_store.write().await.add_candles("TEST", TimeFrame::Day1, candles).unwrap();
+
+        let backtest = BacktestRequest { symbol: "TEST".to_string(), parameters: r#"{"fast_period": 2, "slow_period": 3}"#.to_string(), ..Default::default() };
+        let request = Request::new(MonteCarloRequest { backtest: Some(backtest.clone()), iterations: 100, method: "bootstrap".to_string(), seed: Some(1) });
+        let messages: Vec<MonteCarloProgress> = engine.run_monte_carlo(request).await.unwrap().into_inner()
+            .map(|m| m.unwrap()).collect().await;
+        assert_eq!(messages.len(), 21); // 20 progress updates + the summary
+        assert!(messages[..20].iter().all(|m| m.summary.is_none() && m.total == 100));
+        let summary = messages.last().unwrap().summary.clone().unwrap();
+        assert_eq!(summary.iterations, 100);
+        assert!(summary.return_pct.is_some());
+
+        let bad = Request::new(MonteCarloRequest { backtest: Some(backtest), method: "nope".to_string(), ..Default::default() });
+        assert_eq!(engine.run_monte_carlo(bad).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_trade_futures_margin() {
+        let candle = sample_candle("WINFUT", 120_000.0, 120_500.0, 119_500.0, 120_000.0);
+        let engine = create_test_engine_with_candle("WINFUT", candle).await;
+
+        // 100k of capital covers 33 mini-index contracts at 3000 initial margin each.
+        let buy = |quantity| Request::new(TradeRequest { symbol: "WINFUT".to_string(), action: "BUY".to_string(), quantity, price: None, order_type: "MARKET".to_string(), ..Default::default() });
+        let response = engine.simulate_trade(buy(30.0)).await.unwrap().into_inner();
+        assert!(response.success);
+        let rejected = engine.simulate_trade(buy(5.0)).await.unwrap().into_inner();
+        assert!(!rejected.success);
+        assert_eq!(rejected.status, "REJECTED");
+        assert!(rejected.message.contains("Insufficient buying power"));
+
+        let report = engine.get_session_report(Request::new(SessionReportRequest { symbol: String::new() })).await.unwrap().into_inner();
+        let margin = report.margin.unwrap();
+        assert_eq!(margin.initial_margin, 90_000.0);
+        assert_eq!(margin.equity, 100_000.0); // Futures do not spend their notional
+        assert_eq!(margin.buying_power, 10_000.0);
+        assert!(!margin.margin_call);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_trade_rounds_to_ticks_and_reports_brl_pnl() {
+        use chrono::TimeZone;
+        let engine = create_test_engine();
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let mut first = sample_candle("WINFUT", 120_000.0, 120_050.0, 119_950.0, 120_003.0);
+        first.timestamp = t0;
+        engine.market_data_store.write().await.add_candles("WINFUT", TimeFrame::Day1, vec![first]).unwrap();
+
+        let buy = Request::new(TradeRequest { symbol: "WINFUT".to_string(), action: "BUY".to_string(), quantity: 2.0, price: None, order_type: "MARKET".to_string(), ..Default::default() });
+        let response = engine.simulate_trade(buy).await.unwrap().into_inner();
+        assert_eq!(response.filled_price, "120005"); // Close 120003 rounded to the 5-point tick
+
+        // Off-tick sell limit is moved up to the next tick, never below the requested price.
+        let sell = Request::new(TradeRequest { symbol: "WINFUT".to_string(), action: "SELL".to_string(), quantity: 2.0, price: Some("120041".to_string()), order_type: "LIMIT".to_string(), ..Default::default() });
+        let response = engine.simulate_trade(sell).await.unwrap().into_inner();
+        assert_eq!(response.filled_price, "120045");
+        // 40 points x R$0.20 x 2 contracts.
+        assert_eq!(response.realized_pnl, "16");
+    }
+
+    #[tokio::test]
+    async fn test_cancel_and_modify_working_orders() {
+        let candle = sample_candle("TEST", 100.0, 102.0, 99.0, 101.0);
+        let engine = create_test_engine_with_candle("TEST", candle).await;
+        let place = || Request::new(TradeRequest { symbol: "TEST".to_string(), action: "BUY".to_string(), quantity: 5.0, price: Some("95".to_string()), order_type: "LIMIT".to_string(), time_in_force: "GTC".to_string(), ..Default::default() });
+
+        let first = engine.simulate_trade(place()).await.unwrap().into_inner();
+        assert_eq!(first.status, "WORKING");
+        let cancelled = engine.cancel_order(Request::new(CancelOrderRequest { order_id: first.order_id.clone() })).await.unwrap().into_inner();
+        assert_eq!(cancelled.status, "CANCELLED");
+        let again = engine.cancel_order(Request::new(CancelOrderRequest { order_id: first.order_id })).await.unwrap_err();
+        assert_eq!(again.code(), tonic::Code::InvalidArgument);
+        let missing = engine.cancel_order(Request::new(CancelOrderRequest { order_id: "nope".to_string() })).await.unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::NotFound);
+
+        let second = engine.simulate_trade(place()).await.unwrap().into_inner();
+        let garbled = ModifyOrderRequest { order_id: second.order_id.clone(), limit_price: Some("96,5".to_string()), quantity: None };
+        assert_eq!(engine.modify_order(Request::new(garbled)).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+        let resized = engine.modify_order(Request::new(ModifyOrderRequest { order_id: second.order_id.clone(), limit_price: Some("96".to_string()), quantity: Some(8.0) })).await.unwrap().into_inner();
+        assert_eq!((resized.status.as_str(), resized.limit_price.as_str(), resized.quantity), ("WORKING", "96", 8.0));
+
+        // Raising the bid into the current candle's range fills immediately.
+        let filled = engine.modify_order(Request::new(ModifyOrderRequest { order_id: second.order_id, limit_price: Some("100".to_string()), quantity: None })).await.unwrap().into_inner();
+        assert_eq!(filled.status, "FILLED");
+        assert_eq!(filled.filled_price, Some("100".to_string()));
+        assert_eq!(engine.portfolio.read().await.position("TEST").unwrap().quantity, 8.0);
+    }
+
+    #[tokio::test]
+    async fn test_orders_modified_past_the_market_fill_at_the_market() {
+        let candle = sample_candle("TEST", 100.0, 102.0, 99.0, 101.0);
+        let engine = create_test_engine_with_candle("TEST", candle).await;
+        let place = |action: &str, price: &str| Request::new(TradeRequest { symbol: "TEST".to_string(), action: action.to_string(), quantity: 5.0, price: Some(price.to_string()), order_type: "LIMIT".to_string(), time_in_force: "GTC".to_string(), ..Default::default() });
+
+        let buy = engine.simulate_trade(place("BUY", "95")).await.unwrap().into_inner();
+        let modify = |order_id: String, price: &str| Request::new(ModifyOrderRequest { order_id, limit_price: Some(price.to_string()), quantity: None });
+        let filled = engine.modify_order(modify(buy.order_id, "110")).await.unwrap().into_inner();
+        assert_eq!((filled.status.as_str(), filled.filled_price.as_deref()), ("FILLED", Some("101")));
+
+        let sell = engine.simulate_trade(place("SELL", "110")).await.unwrap().into_inner();
+        let filled = engine.modify_order(modify(sell.order_id, "90")).await.unwrap().into_inner();
+        assert_eq!((filled.status.as_str(), filled.filled_price.as_deref()), ("FILLED", Some("101")));
+    }
+
+    #[tokio::test]
+    async fn test_get_trade_history() {
+        use chrono::{Duration, TimeZone};
+        let engine = create_test_engine();
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        for (day, close) in [(0, 100.0), (1, 104.0)] {
+            let mut candle = sample_candle("TEST", close, close, close, close);
+            candle.timestamp = t0 + Duration::days(day);
+            engine.market_data_store.write().await.add_candles("TEST", TimeFrame::Day1, vec![candle]).unwrap();
+            let action = if day == 0 { "BUY" } else { "SELL" };
+            let trade = Request::new(TradeRequest { symbol: "TEST".to_string(), action: action.to_string(), quantity: 10.0, price: None, order_type: "MARKET".to_string(), ..Default::default() });
+            assert!(engine.simulate_trade(trade).await.unwrap().into_inner().success);
+        }
+
+        let all = engine.get_trade_history(Request::new(TradeHistoryRequest { symbol: "TEST".to_string(), ..Default::default() })).await.unwrap().into_inner();
+        assert_eq!(all.executions.len(), 2);
+        assert_eq!((all.executions[0].side.as_str(), all.executions[0].position_after), ("BUY", 10.0));
+        assert_eq!(all.executions[1].position_after, 0.0);
+        assert_eq!(all.executions[1].realized_pnl, "40");
+
+        let from_second_day = TradeHistoryRequest { symbol: String::new(), from_timestamp: Some((t0 + Duration::hours(12)).timestamp_millis()), to_timestamp: None };
+        let later = engine.get_trade_history(Request::new(from_second_day)).await.unwrap().into_inner();
+        assert_eq!(later.executions.len(), 1);
+        let other = engine.get_trade_history(Request::new(TradeHistoryRequest { symbol: "OTHER".to_string(), ..Default::default() })).await.unwrap().into_inner();
+        assert!(other.executions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_suggest_position_size() {
+        let engine = create_test_engine_with_candle("WINFUT", sample_candle("WINFUT", 120_000.0, 121_000.0, 119_000.0, 120_500.0)).await;
+
+        // 1% of the 100k paper account over a 300-point stop at R$0.20/point: 16 contracts.
+        let request = Request::new(PositionSizeRequest {
+            symbol: "WINFUT".to_string(),
+            sizing: Some(PositionSizing { method: "fixed_fractional".to_string(), risk_fraction: 0.01, ..Default::default() }),
+            stop_distance: 300.0,
+            ..Default::default()
+        });
+        let response = engine.suggest_position_size(request).await.unwrap().into_inner();
+        assert_eq!(response.quantity, 16.0);
+        assert_eq!(response.price, 120_500.0);
+        assert_eq!(response.account_equity, 100_000.0);
+        assert_eq!(response.capital_required, 48_000.0);
+
+        // A single candle is not enough for an ATR.
+        let atr = Request::new(PositionSizeRequest {
+            symbol: "WINFUT".to_string(),
+            sizing: Some(PositionSizing { method: "ATR".to_string(), risk_fraction: 0.01, ..Default::default() }),
+            ..Default::default()
+        });
+        assert_eq!(engine.suggest_position_size(atr).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        let unknown = Request::new(PositionSizeRequest { symbol: "NODATA".to_string(), sizing: Some(PositionSizing { method: "KELLY".to_string(), win_rate: 0.6, payoff_ratio: 2.0, ..Default::default() }), ..Default::default() });
+        assert_eq!(engine.suggest_position_size(unknown).await.unwrap_err().code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_account_persists_across_engines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("account.json");
+        let candle = sample_candle("PETR4", 30.0, 31.0, 29.0, 30.0);
+
+        let engine = MyTradingEngine::with_account(Arc::new(RwLock::new(MarketDataStore::new())), AccountStore::new(&path)).unwrap();
+        engine.market_data_store.write().await.add_candles("PETR4", TimeFrame::Day1, vec![candle.clone()]).unwrap();
+        let trade = Request::new(TradeRequest { symbol: "PETR4".to_string(), action: "BUY".to_string(), quantity: 100.0, order_type: "MARKET".to_string(), ..Default::default() });
+        engine.simulate_trade(trade).await.unwrap();
+        engine.save_account().await.unwrap();
+
+        let restarted = MyTradingEngine::with_account(Arc::new(RwLock::new(MarketDataStore::new())), AccountStore::new(&path)).unwrap();
+        restarted.market_data_store.write().await.add_candles("PETR4", TimeFrame::Day1, vec![candle]).unwrap();
+        let snapshot = restarted.snapshot_account(Request::new(SnapshotAccountRequest::default())).await.unwrap().into_inner();
+        assert_eq!(snapshot.cash, "97000");
+        assert_eq!(snapshot.equity, 100_000.0);
+        assert_eq!(snapshot.positions.len(), 1);
+        assert_eq!(snapshot.positions[0].quantity, 100.0);
+        assert_eq!(snapshot.executions, 1);
+        assert_eq!(snapshot.path, path.display().to_string());
+
+        let reset = restarted.reset_account(Request::new(ResetAccountRequest { initial_capital: 20_000.0, ..Default::default() })).await.unwrap().into_inner();
+        assert_eq!(reset.cash, "20000");
+        assert!(reset.positions.is_empty());
+        assert_eq!(AccountStore::new(&path).load().unwrap().unwrap().portfolio.cash, Price::from(20_000));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_without_account_file() {
+        let engine = create_test_engine();
+        let err = engine.snapshot_account(Request::new(SnapshotAccountRequest::default())).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("copy.json");
+        let state = engine.snapshot_account(Request::new(SnapshotAccountRequest { path: path.display().to_string() })).await.unwrap().into_inner();
+        assert_eq!(state.cash, "100000");
+        assert!(state.saved_at > 0);
+        assert!(path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_sell_from_flat_opens_short() {
+        let engine = create_test_engine_with_candle("TEST", sample_candle("TEST", 10.0, 11.0, 9.0, 10.0)).await;
+        let trade = |action: &str, quantity: f64| Request::new(TradeRequest {
+            symbol: "TEST".to_string(), action: action.to_string(), quantity, order_type: "MARKET".to_string(), ..Default::default()
+        });
+
+        let response = engine.simulate_trade(trade("SELL", 100.0)).await.unwrap().into_inner();
+        assert!(response.success);
+        assert_eq!(engine.portfolio.read().await.position("TEST").unwrap().quantity, -100.0);
+
+        // Buying 150 covers the short and leaves 50 long.
+        engine.market_data_store.write().await.add_candles("TEST", TimeFrame::Day1, vec![{
+            let mut c = sample_candle("TEST", 8.0, 8.5, 7.5, 8.0);
+            c.timestamp += chrono::Duration::days(1);
+            c
+        }]).unwrap();
+        let response = engine.simulate_trade(trade("BUY", 150.0)).await.unwrap().into_inner();
+        assert_eq!(response.realized_pnl, "200");
+        let portfolio = engine.portfolio.read().await;
+        let position = portfolio.position("TEST").unwrap();
+        assert_eq!(position.quantity, 50.0);
+        assert_eq!(position.average_price, Price::from(8));
+    }
+
+    #[tokio::test]
+    async fn test_stream_backtest_events() {
+        use crate::services::generated::backtest_event::Event;
+        use tokio_stream::StreamExt;
+
+        let engine = create_test_engine();
+        let t0 = Utc::now();
+        let closes = [10.0, 10.0, 10.0, 11.0, 12.0, 13.0, 12.0, 10.0, 9.0, 8.0];
+        let candles: Vec<DomainCandle> = closes.iter().enumerate().map(|(i, &c)| {
+            let mut candle = sample_candle("TEST", c, c + 0.5, c - 0.5, c);
+            candle.timestamp = t0 + chrono::Duration::days(i as i64);
+            candle
+        }).collect();
+        engine.market_data_store.write().await.add_candles("TEST", TimeFrame::Day1, candles).unwrap();
+
+        let backtest = BacktestRequest { symbol: "TEST".to_string(), parameters: r#"{"fast_period": 2, "slow_period": 3}"#.to_string(), quantity: 1.0, ..Default::default() };
+        let request = Request::new(StreamBacktestRequest { backtest: Some(backtest), ..Default::default() });
+        let events: Vec<GrpcBacktestEvent> = engine.stream_backtest(request).await.unwrap().into_inner()
+            .map(|e| e.unwrap())
+            .collect()
+            .await;
+
+        let fills = events.iter().filter(|e| matches!(e.event, Some(Event::Fill(_)))).count();
+        assert_eq!(fills, 2);
+        assert!(!events.iter().any(|e| matches!(e.event, Some(Event::Candle(_)))));
+        let Some(Event::Result(result)) = &events.last().unwrap().event else { panic!("stream must end with the result") };
+        assert_eq!(result.report.as_ref().unwrap().trades.len(), 1);
+
+        let missing = Request::new(StreamBacktestRequest::default());
+        assert_eq!(engine.stream_backtest(missing).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_run_stress_test() {
+        let engine = create_test_engine();
+        let t0 = Utc::now();
+        let closes = [10.0, 10.0, 10.0, 11.0, 12.0, 13.0, 12.0, 10.0, 9.0, 8.0];
+        let candles: Vec<DomainCandle> = closes.iter().enumerate().map(|(i, &c)| {
+            let mut candle = sample_candle("TEST", c, c + 0.5, c - 0.5, c);
+            candle.timestamp = t0 + chrono::Duration::days(i as i64);
+            candle
+        }).collect();
+        engine.market_data_store.write().await.add_candles("TEST", TimeFrame::Day1, candles).unwrap();
+
+        let backtest = BacktestRequest { symbol: "TEST".to_string(), parameters: r#"{"fast_period": 2, "slow_period": 3}"#.to_string(), quantity: 1.0, ..Default::default() };
+        let request = Request::new(StressTestRequest { backtest: Some(backtest.clone()), scenarios: vec![] });
+        let response = engine.run_stress_test(request).await.unwrap().into_inner();
+        assert_eq!(response.strategy_name, "SMA Crossover(2, 3)");
+        assert_eq!(response.baseline.unwrap().scenario, "Baseline");
+        assert_eq!(response.results.len(), 4);
+        let spread = response.results.iter().find(|r| r.scenario == "Spread 0.2%").unwrap();
+        assert!(spread.pnl_change < 0.0);
+
+        let bad = StressScenario { volatility_multiplier: -1.0, ..Default::default() };
+        let request = Request::new(StressTestRequest { backtest: Some(backtest), scenarios: vec![bad] });
+        assert_eq!(engine.run_stress_test(request).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_run_backtest_against_benchmark_symbol() {
+        use chrono::{Duration, TimeZone};
+        let engine = create_test_engine();
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let series = |symbol: &str, closes: &[f64]| -> Vec<DomainCandle> {
+            closes.iter().enumerate().map(|(i, &close)| {
+                let mut c = sample_candle(symbol, close, close, close, close);
+                c.timestamp = t0 + Duration::days(i as i64);
+                c
+            }).collect()
+        };
+        let mut store = engine.market_data_store.write().await;
+        store.add_candles("TEST", TimeFrame::Day1, series("TEST", &[10.0, 9.0, 8.0, 7.0, 9.0, 11.0, 12.0, 8.0])).unwrap();
+        store.add_candles("IBOV", TimeFrame::Day1, series("IBOV", &[100.0, 101.0, 102.0, 103.0, 104.0, 105.0, 106.0, 110.0])).unwrap();
+        drop(store);
+
+        let base = BacktestRequest {
+            symbol: "TEST".to_string(),
+            strategy: "sma_crossover".to_string(),
+            parameters: r#"{"fast_period": 2, "slow_period": 3}"#.to_string(),
+            initial_capital: 1_000.0,
+            quantity: 1.0,
+            ..Default::default()
+        };
+        let own = engine.run_backtest(Request::new(base.clone())).await.unwrap().into_inner().benchmark.unwrap();
+        assert_eq!(own.symbol, "TEST");
+        assert!((own.benchmark_return_pct - (8.0 / 10.0 - 1.0) * 100.0).abs() < 1e-9);
+
+        let request = BacktestRequest { benchmark_symbol: "IBOV".to_string(), ..base.clone() };
+        let response = engine.run_backtest(Request::new(request)).await.unwrap().into_inner();
+        let benchmark = response.benchmark.unwrap();
+        assert_eq!(benchmark.symbol, "IBOV");
+        assert_eq!(benchmark.equity_curve.len(), 8);
+        assert!((benchmark.benchmark_return_pct - 10.0).abs() < 1e-9);
+        assert!((benchmark.equity_curve.last().unwrap().equity - 1_100.0).abs() < 1e-9);
+        let final_equity = response.report.unwrap().final_equity;
+        assert!((benchmark.strategy_return_pct - (final_equity / 1_000.0 - 1.0) * 100.0).abs() < 1e-9);
+
+        let missing = BacktestRequest { benchmark_symbol: "NODATA".to_string(), ..base };
+        assert_eq!(engine.run_backtest(Request::new(missing)).await.unwrap_err().code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_run_backtest_on_a_basket() {
+        use chrono::{Duration, TimeZone};
+        let engine = create_test_engine();
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let closes = [10.0, 9.0, 8.0, 7.0, 9.0, 11.0, 12.0, 8.0, 5.0, 4.0];
+        let mut store = engine.market_data_store.write().await;
+        for symbol in ["PETR4", "VALE3"] {
+            let candles: Vec<DomainCandle> = closes.iter().enumerate().map(|(i, &close)| {
+                let mut c = sample_candle(symbol, close, close, close, close);
+                c.timestamp = t0 + Duration::days(i as i64);
+                c
+            }).collect();
+            store.add_candles(symbol, TimeFrame::Day1, candles).unwrap();
+        }
+        drop(store);
+
+        let basket = BacktestRequest {
+            symbol: "PETR4".to_string(),
+            symbols: vec!["VALE3".to_string(), "PETR4".to_string()], // The duplicate is traded once
+            strategy: "sma_crossover".to_string(),
+            parameters: r#"{"fast_period": 2, "slow_period": 3}"#.to_string(),
+            initial_capital: 10_000.0,
+            quantity: 1.0,
+            ..Default::default()
+        };
+        let response = engine.run_backtest(Request::new(basket.clone())).await.unwrap().into_inner();
+        let report = response.report.unwrap();
+        let mut traded: Vec<&str> = report.trades.iter().map(|t| t.symbol.as_str()).collect();
+        traded.sort();
+        assert_eq!(traded, ["PETR4", "VALE3"]);
+        assert_eq!(report.equity_curve.len(), closes.len());
+        assert!(response.benchmark.is_none());
+
+        let with_index = BacktestRequest { benchmark_symbol: "PETR4".to_string(), ..basket.clone() };
+        assert!(engine.run_backtest(Request::new(with_index)).await.unwrap().into_inner().benchmark.is_some());
+
+        let missing = BacktestRequest { symbols: vec!["NODATA".to_string()], ..basket.clone() };
+        assert_eq!(engine.run_backtest(Request::new(missing)).await.unwrap_err().code(), tonic::Code::NotFound);
+        let stress = StressTestRequest { backtest: Some(basket), scenarios: Vec::new() };
+        assert_eq!(engine.run_stress_test(Request::new(stress)).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_cash_transfers_and_base_currency() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("account.json");
+        let engine = MyTradingEngine::with_account(Arc::new(RwLock::new(MarketDataStore::new())), AccountStore::new(&path)).unwrap();
+        engine.market_data_store.write().await.add_candles("PETR4", TimeFrame::Day1, vec![sample_candle("PETR4", 30.0, 31.0, 29.0, 30.0)]).unwrap();
+
+        let state = engine.deposit_cash(Request::new(CashTransferRequest { amount: 50_000.0 })).await.unwrap().into_inner();
+        assert_eq!((state.cash.as_str(), state.equity, state.net_deposits.as_str()), ("150000", 150_000.0, "50000"));
+        assert_eq!(state.path, path.display().to_string()); // Saved right away
+        let negative = Request::new(CashTransferRequest { amount: -1.0 });
+        assert_eq!(engine.deposit_cash(negative).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        // 3,000 in shares stays in the account.
+        let trade = Request::new(TradeRequest { symbol: "PETR4".to_string(), action: "BUY".to_string(), quantity: 100.0, order_type: "MARKET".to_string(), ..Default::default() });
+        assert!(engine.simulate_trade(trade).await.unwrap().into_inner().success);
+        let too_much = Request::new(CashTransferRequest { amount: 148_000.0 });
+        assert_eq!(engine.withdraw_cash(too_much).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+        let state = engine.withdraw_cash(Request::new(CashTransferRequest { amount: 47_000.0 })).await.unwrap().into_inner();
+        assert_eq!((state.cash.as_str(), state.net_deposits.as_str()), ("100000", "3000"));
+
+        let currency = Request::new(BaseCurrencyRequest { currency: "USD".to_string() });
+        assert_eq!(engine.set_base_currency(currency).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        let reset = Request::new(ResetAccountRequest { initial_capital: 25_000.0, base_currency: "usd".to_string() });
+        let state = engine.reset_account(reset).await.unwrap().into_inner();
+        assert_eq!((state.cash.as_str(), state.base_currency.as_str(), state.net_deposits.as_str()), ("25000", "USD", "0"));
+        let restarted = MyTradingEngine::with_account(Arc::new(RwLock::new(MarketDataStore::new())), AccountStore::new(&path)).unwrap();
+        assert_eq!(restarted.portfolio.read().await.base_currency, "USD");
+    }
+
+    #[tokio::test]
+    async fn test_risk_limits_reject_orders() {
+        let engine = create_test_engine_with_candle("PETR4", sample_candle("PETR4", 30.0, 31.0, 29.0, 30.0)).await;
+        let invalid = Request::new(GrpcRiskLimits { max_position: Some(-5.0), ..Default::default() });
+        assert_eq!(engine.set_risk_limits(invalid).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+        let limits = GrpcRiskLimits { max_position: Some(100.0), max_open_orders: Some(1), ..Default::default() };
+        engine.set_risk_limits(Request::new(limits.clone())).await.unwrap();
+        assert_eq!(engine.get_risk_limits(Request::new(RiskLimitsRequest {})).await.unwrap().into_inner(), limits);
+
+        let order = |action: &str, quantity: f64, price: Option<&str>| Request::new(TradeRequest {
+            symbol: "PETR4".to_string(), action: action.to_string(), quantity,
+            order_type: if price.is_some() { "LIMIT" } else { "MARKET" }.to_string(), price: price.map(str::to_string), ..Default::default()
+        });
+        assert!(engine.simulate_trade(order("BUY", 80.0, None)).await.unwrap().into_inner().success);
+        let rejected = engine.simulate_trade(order("BUY", 30.0, None)).await.unwrap().into_inner();
+        assert_eq!(rejected.status, "REJECTED");
+        let violation = rejected.risk_violation.unwrap();
+        assert_eq!((violation.rule.as_str(), violation.symbol.as_str(), violation.limit, violation.value), ("MAX_POSITION", "PETR4", 100.0, 110.0));
+        // Reducing the position is always allowed.
+        assert!(engine.simulate_trade(order("SELL", 80.0, None)).await.unwrap().into_inner().success);
+
+        assert_eq!(engine.simulate_trade(order("BUY", 10.0, Some("25"))).await.unwrap().into_inner().status, "WORKING");
+        let second = engine.simulate_trade(order("BUY", 10.0, Some("24"))).await.unwrap().into_inner();
+        assert_eq!(second.risk_violation.map(|v| v.rule), Some("MAX_OPEN_ORDERS".to_string()));
+        assert_eq!(engine.order_book.read().await.working_orders(None).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_requests_select_the_timeframe() {
+        use tokio_stream::StreamExt;
+        let engine = create_test_engine();
+        let csv_content = "Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade\nWINFUT;30/12/2024;10:05:00;124.080;124.090;123.938;123.983;600.822.115,84;24.228";
+        let tmp_file = create_dummy_csv(csv_content);
+        let minute5 = GrpcTimeFrame::Minute5 as i32;
+        let load = Request::new(LoadCsvRequest { file_path: tmp_file.path().to_str().unwrap().to_string(), symbol: "WINFUT".to_string(), timeframe: minute5, ..Default::default() });
+        engine.load_csv_data(load).await.unwrap();
+        assert!(engine.market_data_store.read().await.get_candles("WINFUT", TimeFrame::Minute5, None, None).unwrap().is_some());
+        assert!(engine.market_data_store.read().await.get_candles("WINFUT", TimeFrame::Day1, None, None).unwrap().is_none());
+
+        let request = Request::new(MarketDataRequest { symbol: "WINFUT".to_string(), from_timestamp: 0, to_timestamp: Utc::now().timestamp_millis(), timeframe: minute5, ..Default::default() });
+        let mut stream = engine.get_market_data(request).await.unwrap().into_inner();
+        assert_eq!(stream.next().await.unwrap().unwrap().candles.len(), 1);
+        let daily = Request::new(MarketDataRequest { symbol: "WINFUT".to_string(), to_timestamp: Utc::now().timestamp_millis(), ..Default::default() });
+        let mut stream = engine.get_market_data(daily).await.unwrap().into_inner();
+        assert_eq!(stream.next().await.unwrap().unwrap_err().code(), tonic::Code::NotFound);
+
+        let trade = Request::new(TradeRequest { symbol: "WINFUT".to_string(), action: "BUY".to_string(), quantity: 1.0, order_type: "MARKET".to_string(), timeframe: minute5, ..Default::default() });
+        assert!(engine.simulate_trade(trade).await.unwrap().into_inner().success);
+        let unknown = Request::new(IndicatorRequest { symbol: "WINFUT".to_string(), indicator_type: "sma".to_string(), parameters: "{}".to_string(), timeframe: 42 });
+        assert_eq!(engine.calculate_indicator(unknown).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_candles_pushes_store_and_replay_updates() {
+        use tokio_stream::StreamExt;
+        let engine = create_test_engine();
+        let request = Request::new(SubscribeCandlesRequest { symbol: "TEST".to_string(), timeframe: 0 });
+        let mut stream = engine.subscribe_candles(request).await.unwrap().into_inner();
+
+        let mut first = sample_candle("TEST", 10.0, 11.0, 9.0, 10.5);
+        first.timestamp -= chrono::Duration::days(1);
+        let other = sample_candle("OTHER", 1.0, 1.0, 1.0, 1.0);
+        engine.market_data_store.write().await.add_candles("OTHER", TimeFrame::Day1, vec![other]).unwrap();
+        engine.market_data_store.write().await.add_candles("TEST", TimeFrame::Minute5, vec![first.clone()]).unwrap();
+        engine.market_data_store.write().await.add_candles("TEST", TimeFrame::Day1, vec![first.clone(), sample_candle("TEST", 10.5, 12.0, 10.0, 11.5)]).unwrap();
+
+        let update = stream.next().await.unwrap().unwrap();
+        assert_eq!((update.symbol.as_str(), update.source.as_str(), update.candle.unwrap().close.as_str()), ("TEST", "STORE", "10.5"));
+        assert_eq!(stream.next().await.unwrap().unwrap().candle.unwrap().close, "11.5");
+
+        let start = StartReplayRequest { symbol: "TEST".to_string(), start_paused: true, ..Default::default() };
+        engine.start_replay(Request::new(start)).await.unwrap();
+        engine.step_replay(Request::new(StepReplayRequest { symbol: "TEST".to_string(), steps: 1 })).await.unwrap();
+        let replayed = stream.next().await.unwrap().unwrap();
+        assert_eq!((replayed.source.as_str(), replayed.candle.unwrap().close.as_str()), ("REPLAY", "10.5"));
+
+        let empty = Request::new(SubscribeCandlesRequest { symbol: " ".to_string(), timeframe: 0 });
+        assert_eq!(engine.subscribe_candles(empty).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_get_market_data_pages_and_chunks() {
+        use tokio_stream::StreamExt;
+        let engine = create_test_engine();
+        let candles: Vec<DomainCandle> = (0..25).map(|i| {
+            let mut candle = sample_candle("TEST", i as f64, i as f64 + 1.0, i as f64, i as f64);
+            candle.timestamp -= chrono::Duration::days(30 - i);
+            candle
+        }).collect();
+        engine.market_data_store.write().await.add_candles("TEST", TimeFrame::Day1, candles).unwrap();
+
+        let request = |offset, limit, max_candles_per_message| Request::new(MarketDataRequest {
+            symbol: "TEST".to_string(), to_timestamp: Utc::now().timestamp_millis(), offset, limit, max_candles_per_message, ..Default::default()
+        });
+        let messages: Vec<MarketDataResponse> = engine.get_market_data(request(5, 12, 5)).await.unwrap().into_inner()
+            .map(|m| m.unwrap()).collect().await;
+        assert_eq!(messages.iter().map(|m| m.candles.len()).collect::<Vec<_>>(), vec![5, 5, 2]);
+        assert!(messages.iter().all(|m| m.total == 25));
+        assert_eq!((messages[0].candles[0].close.as_str(), messages[2].candles[1].close.as_str()), ("5", "16"));
+
+        let all: Vec<MarketDataResponse> = engine.get_market_data(request(0, 0, 0)).await.unwrap().into_inner()
+            .map(|m| m.unwrap()).collect().await;
+        assert_eq!((all.len(), all[0].candles.len()), (1, 25));
+    }
+
+    #[tokio::test]
+    async fn test_get_aggregated_candles() {
+        use chrono::TimeZone;
+        let engine = create_test_engine();
+        let minutes: Vec<DomainCandle> = (0..12).map(|i| DomainCandle {
+            timestamp: Utc.with_ymd_and_hms(2024, 11, 26, 10, 0, 0).unwrap() + chrono::Duration::minutes(5 * i),
+            ..sample_candle("PETR4", 30.0 + i as f64, 31.0 + i as f64, 29.0, 30.5 + i as f64)
+        }).collect();
+        engine.market_data_store.write().await.add_candles("PETR4", TimeFrame::Minute5, minutes).unwrap();
+
+        let request = |timeframe: GrpcTimeFrame, source_timeframe: GrpcTimeFrame| Request::new(AggregatedCandlesRequest {
+            symbol: "PETR4".to_string(), timeframe: timeframe as i32, source_timeframe: source_timeframe as i32, ..Default::default()
+        });
+        let hourly = engine.get_aggregated_candles(request(GrpcTimeFrame::Hour1, GrpcTimeFrame::Unspecified)).await.unwrap().into_inner();
+        assert_eq!(hourly.source_timeframe, GrpcTimeFrame::Minute5 as i32);
+        assert_eq!(hourly.candles.len(), 1);
+        let bar = &hourly.candles[0];
+        assert_eq!((bar.open.as_str(), bar.high.as_str(), bar.low.as_str(), bar.close.as_str(), bar.volume), ("30", "42", "29", "41.5", 12_000.0));
+
+        let finer = engine.get_aggregated_candles(request(GrpcTimeFrame::Minute1, GrpcTimeFrame::Unspecified)).await;
+        assert_eq!(finer.unwrap_err().code(), tonic::Code::NotFound);
+        let impossible = engine.get_aggregated_candles(request(GrpcTimeFrame::Minute15, GrpcTimeFrame::Hour1)).await;
+        assert_eq!(impossible.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_expired_deadline_aborts_long_running_calls() {
+        let engine = create_test_engine_with_candle("TEST", sample_candle("TEST", 10.0, 11.0, 9.0, 10.5)).await;
+        // A deadline shorter than the safety margin has already passed when the work starts.
+        fn expiring<T>(mut request: Request<T>) -> Request<T> {
+            request.metadata_mut().insert("grpc-timeout", "1m".parse().unwrap());
+            request
+        }
+
+        let csv = create_dummy_csv("Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade\nWINFUT;30/12/2024;18:20:00;124.080;124.090;123.938;123.983;600.822.115,84;24.228");
+        let load = LoadCsvRequest { file_path: csv.path().to_str().unwrap().to_string(), symbol: "WINFUT".to_string(), ..Default::default() };
+        let status = engine.load_csv_data(expiring(Request::new(load.clone()))).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+        assert!(engine.market_data_store.read().await.get_candles("WINFUT", TimeFrame::Day1, None, None).unwrap().is_none());
+
+        let backtest = BacktestRequest { symbol: "TEST".to_string(), ..Default::default() };
+        let status = engine.run_backtest(expiring(Request::new(backtest.clone()))).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+        assert!(status.message().contains("gave up after 0 of 1 bars"));
+
+        // A generous deadline changes nothing.
+        let mut request = Request::new(load);
+        request.metadata_mut().insert("grpc-timeout", "30S".parse().unwrap());
+        assert!(engine.load_csv_data(request).await.unwrap().into_inner().success);
+        assert!(engine.run_backtest(Request::new(backtest)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_engine_info() {
+        let engine = create_test_engine_with_candle("VALE3", sample_candle("VALE3", 60.0, 61.0, 59.0, 60.5)).await
+            .with_features(vec!["grpc-web".to_string()]);
+        let mut hourly = sample_candle("PETR4", 37.0, 37.5, 36.8, 37.2);
+        let first = hourly.timestamp;
+        engine.market_data_store.write().await.add_candles("PETR4", TimeFrame::Hour1, vec![hourly.clone()]).unwrap();
+        hourly.timestamp += chrono::Duration::hours(1);
+        engine.market_data_store.write().await.add_candles("PETR4", TimeFrame::Hour1, vec![hourly.clone()]).unwrap();
+
+        let info = engine.get_engine_info(Request::new(EngineInfoRequest {})).await.unwrap().into_inner();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.api_version, crate::services::API_VERSION);
+        assert!(!info.build_target.is_empty());
+        assert_eq!(info.features, ["grpc-web"]);
+        let series: Vec<(&str, i32, u64)> = info.series.iter().map(|s| (s.symbol.as_str(), s.timeframe, s.candles)).collect();
+        assert_eq!(series, [("PETR4", crate::services::GrpcTimeFrame::Hour1 as i32, 2), ("VALE3", crate::services::GrpcTimeFrame::Day1 as i32, 1)]);
+        assert_eq!((info.series[0].first_timestamp, info.series[0].last_timestamp), (first.timestamp_millis(), hourly.timestamp.timestamp_millis()));
+    }
+
+    #[tokio::test]
+    async fn test_trading_session_streams_results_and_fills() {
+        use crate::services::generated::{session_event::Event, session_request::Action};
+        use tokio_stream::StreamExt;
+        let engine = create_test_engine_with_candle("TEST", sample_candle("TEST", 100.0, 102.0, 98.0, 101.0)).await;
+        let (requests, inbound) = tokio::sync::mpsc::channel(8);
+        let mut events = trading_session::handle_trading_session(tokio_stream::wrappers::ReceiverStream::new(inbound).map(Ok), engine.session_context())
+            .await.unwrap().into_inner();
+        let send = |request_id: &str, action| SessionRequest { request_id: request_id.to_string(), action: Some(action) };
+
+        // A market order: its result, then its fill and the position it opened.
+        requests.send(send("buy", Action::Order(TradeRequest { symbol: "TEST".to_string(), action: "BUY".to_string(), quantity: 10.0, order_type: "MARKET".to_string(), ..Default::default() }))).await.unwrap();
+        let result = events.next().await.unwrap().unwrap();
+        assert!(matches!((result.request_id.as_str(), result.event), ("buy", Some(Event::Order(r))) if r.status == "FILLED"));
+        assert!(matches!(events.next().await.unwrap().unwrap().event, Some(Event::Fill(fill)) if fill.quantity == 10.0));
+        assert!(matches!(events.next().await.unwrap().unwrap().event, Some(Event::Position(p)) if p.quantity == 10.0));
+
+        // A resting order fills once a later candle reaches it; trades from outside the session are not streamed.
+        requests.send(send("rest", Action::Order(TradeRequest { symbol: "TEST".to_string(), action: "SELL".to_string(), quantity: 4.0, price: Some("105".to_string()), order_type: "LIMIT".to_string(), time_in_force: "GTC".to_string(), ..Default::default() }))).await.unwrap();
+        assert!(matches!(events.next().await.unwrap().unwrap().event, Some(Event::Order(r)) if r.status == "WORKING"));
+        let outside = TradeRequest { symbol: "TEST".to_string(), action: "BUY".to_string(), quantity: 1.0, order_type: "MARKET".to_string(), ..Default::default() };
+        engine.simulate_trade(Request::new(outside)).await.unwrap();
+        let mut rally = sample_candle("TEST", 104.0, 106.0, 103.0, 105.5);
+        rally.timestamp += chrono::Duration::days(1);
+        let fills = engine.order_book.write().await.process_candles("TEST", &[rally]);
+        engine.portfolio.write().await.apply_fills(fills);
+        let fill = events.next().await.unwrap().unwrap();
+        assert!(fill.request_id.is_empty());
+        assert!(matches!(fill.event, Some(Event::Fill(f)) if f.side == "SELL" && f.price == "105"));
+        assert!(matches!(events.next().await.unwrap().unwrap().event, Some(Event::Position(p)) if p.quantity == 7.0));
+
+        // Failures are answered and the session goes on; closing the request stream ends it.
+        requests.send(send("bad", Action::Cancel(CancelOrderRequest { order_id: "missing".to_string() }))).await.unwrap();
+        assert!(matches!(events.next().await.unwrap().unwrap().event, Some(Event::Error(e)) if e.contains("not found")));
+        drop(requests);
+        assert!(events.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_jobs_import_csv_and_run_backtests_in_the_background() {
+        use crate::services::generated::{job_result::Result as JobOutput, submit_job_request::Job};
+        use tokio_stream::StreamExt;
+
+        let engine = create_test_engine();
+        let rows: Vec<String> = (1..=20).map(|day| format!("PETR4;{:02}/01/2024;10:00:00;{0},00;{0},00;{0},00;{0},00;1.000;10", day)).collect();
+        let tmp_file = create_dummy_csv(&format!("Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade\n{}", rows.join("\n")));
+        let load = LoadCsvRequest { file_path: tmp_file.path().to_str().unwrap().to_string(), symbol: "PETR4".to_string(), ..Default::default() };
+
+        let submitted = engine.submit_job(Request::new(SubmitJobRequest { job: Some(Job::LoadCsv(load)) })).await.unwrap().into_inner();
+        assert_eq!((submitted.kind.as_str(), submitted.state.as_str()), ("LOAD_CSV", "RUNNING"));
+        let job = JobRequest { job_id: submitted.job_id.clone() };
+        let updates: Vec<GrpcJobStatus> = engine.watch_job(Request::new(job.clone())).await.unwrap().into_inner()
+            .map(|status| status.unwrap()).collect().await;
+        let last = updates.last().unwrap();
+        assert_eq!((last.state.as_str(), last.progress), ("SUCCEEDED", 1.0));
+        assert!(last.finished_at >= last.submitted_at);
+        let result = engine.get_job_result(Request::new(job)).await.unwrap().into_inner();
+        assert!(matches!(result.result, Some(JobOutput::LoadCsv(ref r)) if r.candles_loaded == 20));
+
+        let backtest = BacktestRequest {
+            symbol: "PETR4".to_string(),
+            strategy: "sma_crossover".to_string(),
+            parameters: r#"{"fast_period": 2, "slow_period": 3}"#.to_string(),
+            ..Default::default()
+        };
+        let submitted = engine.submit_job(Request::new(SubmitJobRequest { job: Some(Job::Backtest(backtest)) })).await.unwrap().into_inner();
+        let job = JobRequest { job_id: submitted.job_id };
+        let _: Vec<_> = engine.watch_job(Request::new(job.clone())).await.unwrap().into_inner().collect().await;
+        let result = engine.get_job_result(Request::new(job)).await.unwrap().into_inner();
+        let Some(JobOutput::Backtest(response)) = result.result else { panic!("expected a backtest result") };
+        assert_eq!(response.report.unwrap().equity_curve.len(), 20);
+
+        // A job that fails keeps its error for GetJobResult; unknown jobs are NOT_FOUND.
+        let missing = BacktestRequest { symbol: "NOPE".to_string(), strategy: "sma_crossover".to_string(), ..Default::default() };
+        let submitted = engine.submit_job(Request::new(SubmitJobRequest { job: Some(Job::Backtest(missing)) })).await.unwrap().into_inner();
+        let job = JobRequest { job_id: submitted.job_id };
+        let _: Vec<_> = engine.watch_job(Request::new(job.clone())).await.unwrap().into_inner().collect().await;
+        assert_eq!(engine.get_job_result(Request::new(job)).await.unwrap_err().code(), tonic::Code::NotFound);
+        let jobs = engine.list_jobs(Request::new(ListJobsRequest {})).await.unwrap().into_inner().jobs;
+        assert_eq!(jobs.iter().map(|j| j.state.as_str()).collect::<Vec<_>>(), ["SUCCEEDED", "SUCCEEDED", "FAILED"]);
+        let unknown = engine.cancel_job(Request::new(JobRequest { job_id: "nope".to_string() })).await.unwrap_err();
+        assert_eq!(unknown.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_namespaces_keep_data_and_accounts_apart() {
+        fn in_namespace<T>(name: &str, message: T) -> Request<T> {
+            let mut request = Request::new(message);
+            request.metadata_mut().insert(NAMESPACE_HEADER, name.parse().unwrap());
+            request
+        }
+        let engine = create_test_engine();
+        let csv_content = "Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade\nWINFUT;30/12/2024;18:20:00;124.080;124.090;123.938;123.983;600.822.115,84;24.228";
+        let tmp_file = create_dummy_csv(csv_content);
+        let load = LoadCsvRequest { file_path: tmp_file.path().to_str().unwrap().to_string(), symbol: "WINFUT".to_string(), ..Default::default() };
+        engine.load_csv_data(in_namespace("a", load)).await.unwrap();
+
+        let a = engine.in_namespace(in_namespace("a", ()).metadata()).await.unwrap();
+        assert!(a.market_data_store.read().await.get_candles("WINFUT", TimeFrame::Day1, None, None).unwrap().is_some());
+        assert!(engine.market_data_store.read().await.get_candles("WINFUT", TimeFrame::Day1, None, None).unwrap().is_none());
+        // The default namespace can also be named.
+        let default = engine.in_namespace(in_namespace(DEFAULT_NAMESPACE, ()).metadata()).await.unwrap();
+        assert!(Arc::ptr_eq(&default.market_data_store, &engine.market_data_store));
+
+        let trade = TradeRequest { symbol: "WINFUT".to_string(), action: "BUY".to_string(), quantity: 1.0, order_type: "MARKET".to_string(), ..Default::default() };
+        assert_eq!(engine.simulate_trade(in_namespace("a", trade.clone())).await.unwrap().into_inner().status, "FILLED");
+        assert_eq!(engine.simulate_trade(in_namespace("b", trade)).await.unwrap().into_inner().status, "REJECTED");
+        assert_eq!(a.portfolio.read().await.position("WINFUT").unwrap().quantity, 1.0);
+        assert!(engine.portfolio.read().await.position("WINFUT").is_none());
+
+        let invalid = engine.load_csv_data(in_namespace("no spaces", LoadCsvRequest::default())).await.unwrap_err();
+        assert_eq!(invalid.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_reloaded_settings_apply_risk_limits_and_csv_format() {
+        let engine = create_test_engine().with_settings(AppSettings::default());
+        let mut new = AppSettings::default();
+        new.engine.port = 50052;
+        new.engine.log_level = "debug".to_string();
+        new.engine.risk_limits.max_open_orders = Some(3);
+        new.data.csv_delimiter = '|';
+        new.data.date_format = "%Y-%m-%d".to_string();
+        // VALE3's files keep the default format
+        new.symbols.insert("VALE3".to_string(), SymbolSettings { csv_format: Some(CsvFormat::default()), ..Default::default() });
+
+        let changes = reload_settings::apply_settings(new, &engine.settings, &engine.risk_limits, &engine.contracts).await;
+        assert_eq!(changes.applied, ["engine.log_level", "engine.risk_limits", "data.csv_delimiter", "data.date_format", "symbols"]);
+        assert_eq!(changes.restart_required, ["engine.port"]);
+        assert_eq!(engine.risk_limits.read().await.max_open_orders, Some(3));
+        assert_eq!(engine.settings.read().await.engine.log_level, "debug");
+        // The port in effect is still the one the engine started with.
+        assert_eq!(engine.settings.read().await.engine.port, AppSettings::default().engine.port);
+
+        let tmp_file = create_dummy_csv("Ativo|Data|Hora|Abertura|Máximo|Mínimo|Fechamento|Volume|Quantidade\nPETR4|2024-01-02|10:00:00|36,50|37,00|36,00|36,80|1.000,00|10");
+        let load = LoadCsvRequest { file_path: tmp_file.path().to_str().unwrap().to_string(), symbol: "PETR4".to_string(), ..Default::default() };
+        assert_eq!(engine.load_csv_data(Request::new(load)).await.unwrap().into_inner().candles_loaded, 1);
+        let tmp_file = create_dummy_csv("Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade\nVALE3;02/01/2024;10:00:00;60,50;61,00;60,00;60,80;1.000,00;10");
+        let load = LoadCsvRequest { file_path: tmp_file.path().to_str().unwrap().to_string(), symbol: "VALE3".to_string(), ..Default::default() };
+        assert_eq!(engine.load_csv_data(Request::new(load)).await.unwrap().into_inner().candles_loaded, 1);
+    }
+
+    #[tokio::test]
+    async fn test_load_csv_batch_reports_each_file() {
+        let engine = create_test_engine();
+        let header = "Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade";
+        let files: Vec<NamedTempFile> = ["PETR4", "VALE3", "ITUB4"].iter()
+            .map(|symbol| create_dummy_csv(&format!("{}\n{};02/01/2024;10:00:00;10,00;11,00;9,00;10,50;1.000;10\n{};03/01/2024;10:00:00;10,50;11,00;10,00;10,80;1.000;10", header, symbol, symbol)))
+            .collect();
+        let entry = |file: &NamedTempFile, symbol: &str| LoadCsvRequest { file_path: file.path().to_str().unwrap().to_string(), symbol: symbol.to_string(), ..Default::default() };
+        let mut batch = vec![entry(&files[0], "PETR4"), entry(&files[1], "VALE3"), entry(&files[2], "ITUB4")];
+        batch.insert(1, LoadCsvRequest { file_path: "/no/such/file.csv".to_string(), symbol: "NOPE".to_string(), ..Default::default() });
+
+        let response = engine.load_csv_batch(Request::new(LoadCsvBatchRequest { files: batch, max_parallel: 2 })).await.unwrap().into_inner();
+        assert_eq!((response.files_loaded, response.files_failed, response.candles_loaded), (3, 1, 6));
+        assert_eq!(response.results.iter().map(|r| r.symbol.as_str()).collect::<Vec<_>>(), ["PETR4", "NOPE", "VALE3", "ITUB4"]);
+        assert!(!response.results[1].success && response.results[1].message.contains("I/O error"));
+        assert_eq!(response.results[3].candles_loaded, 2);
+        assert!(engine.market_data_store.read().await.get_candles("ITUB4", TimeFrame::Day1, None, None).unwrap().is_some());
+
+        let empty = engine.load_csv_batch(Request::new(LoadCsvBatchRequest::default())).await.unwrap_err();
+        assert_eq!(empty.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_errors_carry_structured_details() {
+        let engine = create_test_engine();
+        let tmp_file = create_dummy_csv("Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade\nPETR4;02/01/2024;10:00:00;10,00;11,00;9,00;10,50;1.000;10\nPETR4;03/01/2024;10:00:00;10,50;abc;10,00;10,80;1.000;10");
+        let load = LoadCsvRequest { file_path: tmp_file.path().to_str().unwrap().to_string(), symbol: "PETR4".to_string(), ..Default::default() };
+        let error = engine.load_csv_data(Request::new(load.clone())).await.unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+        let details = ErrorDetails::from_status(&error).unwrap();
+        assert_eq!(details.reason, "CSV_DATA_FORMAT");
+        assert_eq!(details.code(), Some(ErrorCode::CsvDataFormat));
+        assert_eq!((details.metadata["line"].as_str(), details.metadata["column"].as_str()), ("3", "Máximo"));
+
+        let error = engine.load_csv_data(Request::new(LoadCsvRequest { timeframe: 99, ..load })).await.unwrap_err();
+        let details = ErrorDetails::from_status(&error).unwrap();
+        assert_eq!(details.field_violations[0].field, "timeframe");
+
+        let missing = engine.cancel_job(Request::new(JobRequest { job_id: "nope".to_string() })).await.unwrap_err();
+        // Statuses not built from an EngineError keep just their message.
+        assert_eq!(ErrorDetails::from_status(&missing), None);
+    }
+
+    #[tokio::test]
+    async fn test_list_symbols_groups_series_by_symbol() {
+        let engine = create_test_engine_with_candle("VALE3", sample_candle("VALE3", 60.0, 61.0, 59.0, 60.5)).await;
+        let hourly = sample_candle("VALE3", 60.0, 60.5, 59.5, 60.2);
+        engine.market_data_store.write().await.add_candles("VALE3", TimeFrame::Hour1, vec![hourly]).unwrap();
+        engine.market_data_store.write().await.add_candles("PETR4", TimeFrame::Day1, vec![sample_candle("PETR4", 37.0, 37.5, 36.8, 37.2)]).unwrap();
+
+        let symbols = engine.list_symbols(Request::new(ListSymbolsRequest {})).await.unwrap().into_inner().symbols;
+        let listed: Vec<(&str, Vec<i32>)> = symbols.iter()
+            .map(|s| (s.symbol.as_str(), s.series.iter().map(|series| series.timeframe).collect()))
+            .collect();
+        assert_eq!(listed, [
+            ("PETR4", vec![crate::services::GrpcTimeFrame::Day1 as i32]),
+            ("VALE3", vec![crate::services::GrpcTimeFrame::Hour1 as i32, crate::services::GrpcTimeFrame::Day1 as i32]),
+        ]);
+
+        // Another namespace has loaded nothing yet.
+        let mut request = Request::new(ListSymbolsRequest {});
+        request.metadata_mut().insert(NAMESPACE_HEADER, "empty".parse().unwrap());
+        assert!(engine.list_symbols(request).await.unwrap().into_inner().symbols.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_alerts_fire_once_on_incoming_candles() {
+        use tokio_stream::StreamExt;
+        let engine = create_test_engine_with_candle("PETR4", sample_candle("PETR4", 30.0, 31.0, 29.0, 30.0)).await;
+        let mut stream = engine.subscribe_alerts(Request::new(SubscribeAlertsRequest { symbol: "PETR4".to_string() })).await.unwrap().into_inner();
+
+        let create = |kind: &str, level: f64| Request::new(CreateAlertRequest { symbol: "PETR4".to_string(), kind: kind.to_string(), level, note: "breakout".to_string(), ..Default::default() });
+        let cross = engine.create_alert(create("PRICE_CROSS", 32.0)).await.unwrap().into_inner();
+        let fall = engine.create_alert(create("PERCENT_MOVE", -10.0)).await.unwrap().into_inner();
+        assert_eq!((fall.kind.as_str(), fall.reference_price), ("PERCENT_MOVE", Some(30.0)));
+        assert_eq!(engine.create_alert(create("VOLUME_SPIKE", 1.0)).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+        let indicator = Request::new(CreateAlertRequest { symbol: "PETR4".to_string(), kind: "INDICATOR".to_string(), indicator: "RSI".to_string(), direction: "SIDEWAYS".to_string(), ..Default::default() });
+        assert_eq!(engine.create_alert(indicator).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+        let listed = engine.list_alerts(Request::new(ListAlertsRequest::default())).await.unwrap().into_inner().alerts;
+        assert_eq!(listed.iter().map(|a| a.alert_id.as_str()).collect::<Vec<_>>(), [cross.alert_id.as_str(), fall.alert_id.as_str()]);
+
+        let mut next = sample_candle("PETR4", 31.0, 33.0, 30.5, 32.5);
+        next.timestamp += chrono::Duration::days(1);
+        engine.market_data_store.write().await.add_candles("PETR4", TimeFrame::Day1, vec![next]).unwrap();
+        let event = stream.next().await.unwrap().unwrap();
+        assert_eq!((event.alert.unwrap().alert_id, event.price, event.source.as_str()), (cross.alert_id, 32.0, "STORE"));
+
+        engine.delete_alert(Request::new(AlertRequest { alert_id: fall.alert_id.clone() })).await.unwrap();
+        assert!(engine.list_alerts(Request::new(ListAlertsRequest::default())).await.unwrap().into_inner().alerts.is_empty());
+        let missing = engine.delete_alert(Request::new(AlertRequest { alert_id: fall.alert_id })).await.unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_embedded_engine_serves_over_in_memory_pipes() {
+        use crate::services::embedded::EmbeddedEngine;
+        use crate::services::{TradingEngineClient, TradingEngineServer};
+        use tonic::server::NamedService;
+        use tonic_health::pb::{health_check_response::ServingStatus, health_client::HealthClient, HealthCheckRequest};
+
+        let embedded = EmbeddedEngine::start(AppSettings::default()).await.unwrap();
+        let service = <TradingEngineServer<MyTradingEngine> as NamedService>::NAME.to_string();
+        let health = HealthClient::new(embedded.channel()).check(HealthCheckRequest { service }).await.unwrap().into_inner();
+        assert_eq!(health.status, ServingStatus::Serving as i32);
+
+        // Clients of the same embedded engine share its data.
+        let tmp_file = create_dummy_csv("Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade\nPETR4;02/01/2024;10:00:00;10,00;11,00;9,00;10,50;1.000;10");
+        let load = LoadCsvRequest { file_path: tmp_file.path().to_str().unwrap().to_string(), symbol: "PETR4".to_string(), ..Default::default() };
+        TradingEngineClient::new(embedded.channel()).load_csv_data(load).await.unwrap();
+        let symbols = TradingEngineClient::new(embedded.channel()).list_symbols(ListSymbolsRequest {}).await.unwrap().into_inner().symbols;
+        assert_eq!(symbols.iter().map(|s| s.symbol.as_str()).collect::<Vec<_>>(), ["PETR4"]);
+    }
+
+    #[tokio::test]
+    async fn test_export_data_cuts_warmed_up_indicators_to_the_range() {
+        use chrono::TimeZone;
+        use crate::services::ExportedIndicator;
+        let engine = create_test_engine();
+        let days: Vec<DomainCandle> = (0..5).map(|i| DomainCandle {
+            timestamp: Utc.with_ymd_and_hms(2024, 11, 25 + i, 13, 0, 0).unwrap(),
+            ..sample_candle("PETR4", 30.0, 35.0, 29.0, 30.0 + i as f64)
+        }).collect();
+        engine.market_data_store.write().await.add_candles("PETR4", TimeFrame::Day1, days.clone()).unwrap();
+
+        let request = |format: &str| Request::new(ExportDataRequest {
+            symbol: "PETR4".to_string(),
+            from_timestamp: Some(days[2].timestamp.timestamp_millis()),
+            to_timestamp: Some(days[3].timestamp.timestamp_millis()),
+            indicators: vec![ExportedIndicator { indicator_type: "sma".to_string(), parameters: r#"{"period": 3}"#.to_string() }],
+            format: format.to_string(),
+            ..Default::default()
+        });
+        let csv = engine.export_data(request("csv")).await.unwrap().into_inner();
+        assert_eq!(csv.candles_exported, 2);
+        let lines: Vec<&str> = csv.contents.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with(";SMA(3)"));
+        // The SMA of the range's first candle uses the two before it
+        assert!(lines[1].starts_with("PETR4;27/11/2024;13:00:00;") && lines[1].ends_with(";31,0000"));
+
+        let json: serde_json::Value = serde_json::from_str(&engine.export_data(request("JSON")).await.unwrap().into_inner().contents).unwrap();
+        assert_eq!(json["candles"][1]["indicators"]["SMA(3)"], 32.0);
+        let unknown = engine.export_data(request("xlsx")).await.unwrap_err();
+        assert_eq!(unknown.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_get_series_stats() {
+        use chrono::TimeZone;
+        let engine = create_test_engine();
+        let days: Vec<DomainCandle> = [100.0, 110.0, 99.0].iter().enumerate().map(|(i, close)| DomainCandle {
+            timestamp: Utc.with_ymd_and_hms(2024, 11, 26, 13, 0, 0).unwrap() + chrono::Duration::days(i as i64),
+            ..sample_candle("PETR4", 100.0, 112.0, 90.0 + i as f64, *close)
+        }).collect();
+        engine.market_data_store.write().await.add_candles("PETR4", TimeFrame::Day1, days.clone()).unwrap();
+
+        let request = |from_timestamp: Option<i64>| Request::new(SeriesStatsRequest {
+            symbol: "PETR4".to_string(), timeframe: GrpcTimeFrame::Day1 as i32, from_timestamp, ..Default::default()
+        });
+        let stats = engine.get_series_stats(request(None)).await.unwrap().into_inner();
+        assert_eq!((stats.candles, stats.open.as_str(), stats.close.as_str(), stats.high.as_str(), stats.low.as_str()), (3, "100", "99", "112", "90"));
+        assert!((stats.change_pct + 1.0).abs() < 1e-9);
+        assert_eq!(stats.last_timestamp, days[2].timestamp.timestamp_millis());
+
+        let tail = engine.get_series_stats(request(Some(days[1].timestamp.timestamp_millis()))).await.unwrap().into_inner();
+        assert_eq!((tail.candles, tail.low.as_str(), tail.volatility_pct), (2, "91", 0.0));
+        let empty = engine.get_series_stats(request(Some(days[2].timestamp.timestamp_millis() + 1))).await;
+        assert_eq!(empty.unwrap_err().code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_proto_messages_round_trip_through_json() {
+        use crate::services::generated::submit_job_request::Job;
+        let request = SubmitJobRequest { job: Some(Job::Backtest(BacktestRequest { symbol: "PETR4".to_string(), ..Default::default() })) };
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["job"]["Backtest"]["symbol"], "PETR4");
+        assert_eq!(serde_json::from_value::<SubmitJobRequest>(json).unwrap(), request);
+
+        let engine = create_test_engine_with_candle("TEST", sample_candle("TEST", 10.0, 11.0, 9.0, 10.5)).await;
+        let stats = engine.get_series_stats(Request::new(SeriesStatsRequest {
+            symbol: "TEST".to_string(), timeframe: GrpcTimeFrame::Day1 as i32, ..Default::default()
+        })).await.unwrap().into_inner();
+        let cached = serde_json::to_string(&stats).unwrap();
+        assert!(cached.contains(r#""high":"11""#));
+        assert_eq!(serde_json::from_str::<GrpcSeriesStats>(&cached).unwrap(), stats);
+    }
+
+    #[tokio::test]
+    async fn test_zero_indicator_periods_are_errors_not_panics() {
+        let engine = create_test_engine_with_candle("TEST", sample_candle("TEST", 10.0, 11.0, 9.0, 10.5)).await;
+        for indicator_type in ["sma", "ema", "rsi", "atr"] {
+            let request = Request::new(IndicatorRequest {
+                symbol: "TEST".to_string(), indicator_type: indicator_type.to_string(), parameters: r#"{"period": 0}"#.to_string(), timeframe: 0,
+            });
+            let status = engine.calculate_indicator(request).await.unwrap_err();
+            assert_eq!(ErrorDetails::from_status(&status).unwrap().code(), Some(ErrorCode::Indicator), "{}", indicator_type);
+        }
+    }
 }
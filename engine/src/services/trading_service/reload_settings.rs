@@ -0,0 +1,61 @@
+// Handler for the ReloadSettings RPC, also run when the engine gets SIGHUP
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::config::logging;
+use crate::config::settings::{AppSettings, SettingsChanges};
+use crate::error::EngineError;
+use crate::services::ReloadSettingsResponse;
+use crate::simulation::{Contracts, RiskLimits};
+
+/// Puts the settings a running engine can change into effect: the log level, the risk limits, if
+/// the file's changed (replacing any set with SetRiskLimits), the CSV data format and the symbol
+/// settings. Other changes
+/// are reported and left for the next restart.
+pub async fn apply_settings(
+    new: AppSettings,
+    settings: &Arc<RwLock<AppSettings>>,
+    risk_limits: &Arc<RwLock<RiskLimits>>,
+    contracts: &Contracts,
+) -> SettingsChanges {
+    let mut current = settings.write().await;
+    let changes = new.changes_from(&current);
+    if new.engine.log_level != current.engine.log_level {
+        if let Ok(filter) = new.engine.log_filter() {
+            logging::set_filter(filter);
+        }
+        current.engine.log_level = new.engine.log_level.clone();
+    }
+    if new.engine.risk_limits != current.engine.risk_limits {
+        *risk_limits.write().await = new.engine.risk_limits.clone();
+        current.engine.risk_limits = new.engine.risk_limits;
+    }
+    current.data = new.data;
+    if new.symbols != current.symbols {
+        contracts.set_symbols(new.symbols.clone());
+        current.symbols = new.symbols;
+    }
+    tracing::info!(applied = ?changes.applied, restart_required = ?changes.restart_required, "Settings reloaded");
+    changes
+}
+
+/// Re-reads the config file the engine started with; an unreadable or invalid file changes nothing.
+pub async fn reload_settings(
+    settings: &Arc<RwLock<AppSettings>>,
+    risk_limits: &Arc<RwLock<RiskLimits>>,
+    contracts: &Contracts,
+) -> Result<SettingsChanges, EngineError> {
+    let new = AppSettings::load_default_dev()
+        .map_err(|e| EngineError::ConfigError(format!("Settings not reloaded: {}", e.root_cause())))?;
+    Ok(apply_settings(new, settings, risk_limits, contracts).await)
+}
+
+pub async fn handle_reload_settings(
+    settings: &Arc<RwLock<AppSettings>>,
+    risk_limits: &Arc<RwLock<RiskLimits>>,
+    contracts: &Contracts,
+) -> Result<Response<ReloadSettingsResponse>, Status> {
+    let changes = reload_settings(settings, risk_limits, contracts).await?;
+    Ok(Response::new(ReloadSettingsResponse { applied: changes.applied, restart_required: changes.restart_required }))
+}
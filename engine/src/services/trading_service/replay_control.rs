@@ -0,0 +1,92 @@
+// Handlers for the replay RPCs (StartReplay, PauseReplay, ResumeReplay, StepReplay, SetReplaySpeed, StopReplay)
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::services::{ReplayControlRequest, ReplaySpeedRequest, ReplayStatus, StartReplayRequest, StepReplayRequest};
+use crate::simulation::replay::{advance_replay, spawn_playback, DEFAULT_REPLAY_SPEED};
+use crate::simulation::{OrderBook, Portfolio, ReplayController, ReplayState};
+use shared::models::TimeFrame;
+use super::helpers::{from_grpc_timestamp, to_grpc_replay_status};
+
+pub async fn handle_start_replay(
+    req_payload: StartReplayRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    replay: Arc<RwLock<ReplayController>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+) -> Result<Response<ReplayStatus>, Status> {
+    let timeframe = TimeFrame::Day1;
+    let from_ts = req_payload.from_timestamp.map(from_grpc_timestamp).transpose()?;
+    let speed = if req_payload.speed == 0.0 { DEFAULT_REPLAY_SPEED } else { req_payload.speed };
+
+    let candles = market_data_store.read().await
+        .get_candles(&req_payload.symbol, timeframe, None, None)
+        .map_err(EngineError::from)?
+        .unwrap_or_default();
+
+    let mut controller = replay.write().await;
+    let generation = controller.start(&req_payload.symbol, candles, from_ts, speed, req_payload.start_paused)?;
+    let snapshot = controller.status(&req_payload.symbol)?;
+    drop(controller);
+
+    if snapshot.state == ReplayState::Playing {
+        spawn_playback(replay, order_book, portfolio, req_payload.symbol.clone(), generation);
+    }
+    Ok(Response::new(to_grpc_replay_status(&snapshot)))
+}
+
+pub async fn handle_pause_replay(
+    req_payload: ReplayControlRequest,
+    replay: Arc<RwLock<ReplayController>>,
+) -> Result<Response<ReplayStatus>, Status> {
+    let snapshot = replay.write().await.pause(&req_payload.symbol)?;
+    Ok(Response::new(to_grpc_replay_status(&snapshot)))
+}
+
+pub async fn handle_resume_replay(
+    req_payload: ReplayControlRequest,
+    replay: Arc<RwLock<ReplayController>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+) -> Result<Response<ReplayStatus>, Status> {
+    let mut controller = replay.write().await;
+    let was_playing = controller.status(&req_payload.symbol)?.state == ReplayState::Playing;
+    let (snapshot, generation) = controller.resume(&req_payload.symbol)?;
+    drop(controller);
+
+    // Only a paused session needs a new playback task; a playing one already has one.
+    if !was_playing && snapshot.state == ReplayState::Playing {
+        spawn_playback(replay, order_book, portfolio, req_payload.symbol.clone(), generation);
+    }
+    Ok(Response::new(to_grpc_replay_status(&snapshot)))
+}
+
+pub async fn handle_step_replay(
+    req_payload: StepReplayRequest,
+    replay: Arc<RwLock<ReplayController>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+) -> Result<Response<ReplayStatus>, Status> {
+    let steps = req_payload.steps.max(1) as usize;
+    let snapshot = advance_replay(&replay, &order_book, &portfolio, &req_payload.symbol, steps).await?;
+    Ok(Response::new(to_grpc_replay_status(&snapshot)))
+}
+
+pub async fn handle_set_replay_speed(
+    req_payload: ReplaySpeedRequest,
+    replay: Arc<RwLock<ReplayController>>,
+) -> Result<Response<ReplayStatus>, Status> {
+    let snapshot = replay.write().await.set_speed(&req_payload.symbol, req_payload.speed)?;
+    Ok(Response::new(to_grpc_replay_status(&snapshot)))
+}
+
+pub async fn handle_stop_replay(
+    req_payload: ReplayControlRequest,
+    replay: Arc<RwLock<ReplayController>>,
+) -> Result<Response<ReplayStatus>, Status> {
+    let snapshot = replay.write().await.stop(&req_payload.symbol)?;
+    Ok(Response::new(to_grpc_replay_status(&snapshot)))
+}
@@ -0,0 +1,200 @@
+// Handlers for the RunBacktest and StreamBacktest RPCs
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, RwLock};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Response, Status};
+
+use crate::backtest::{compare_to_benchmark, create_strategy, run_portfolio_backtest_with_events, BacktestConfig, BacktestEvent, BacktestLeg, BacktestOutcome, Strategy};
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::simulation::Contracts;
+use crate::services::{BacktestRequest, BacktestResponse, GrpcBacktestEvent, StreamBacktestRequest};
+use crate::services::generated::backtest_event::Event as GrpcEvent;
+use shared::models::{Candle, CandleSeries, TimeFrame};
+use super::helpers::{from_grpc_carry_costs, from_grpc_sizing, from_grpc_timestamp, to_grpc_backtest_event, to_grpc_benchmark, to_grpc_cost_charge, to_grpc_metrics, to_grpc_pnl_report};
+
+/// One traded symbol of a `PreparedBacktest`.
+pub(super) struct PreparedLeg {
+    pub symbol: String,
+    pub strategy: Box<dyn Strategy>,
+    pub candles: CandleSeries,
+}
+
+/// Everything a run needs, resolved from a `BacktestRequest`.
+pub(super) struct PreparedBacktest {
+    /// `symbol` first, then the other `symbols` of the request.
+    pub legs: Vec<PreparedLeg>,
+    pub config: BacktestConfig,
+    /// Candles of `BacktestRequest.benchmark_symbol`, when one was requested.
+    pub benchmark: Option<CandleSeries>,
+    /// Strategy parameters, to build further instances of the same strategy.
+    pub params: serde_json::Value,
+}
+
+/// `deadline` (see `request_deadline`) goes into the config, so the runs give up once it passes.
+pub(super) async fn prepare_backtest(
+    req_payload: &BacktestRequest,
+    market_data_store: &Arc<RwLock<MarketDataStore>>,
+    contracts: &Contracts,
+    deadline: Option<Instant>,
+) -> Result<PreparedBacktest, EngineError> {
+    let timeframe = TimeFrame::Day1;
+    let from_ts = req_payload.from_timestamp.map(from_grpc_timestamp).transpose()?;
+    let to_ts = req_payload.to_timestamp.map(from_grpc_timestamp).transpose()?;
+
+    let params: serde_json::Value = if req_payload.parameters.trim().is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::from_str(&req_payload.parameters).map_err(|e| {
+            EngineError::ProcessingError(format!("Invalid JSON parameters for strategy '{}': {}", req_payload.strategy, e))
+        })?
+    };
+
+    let mut config = BacktestConfig { deadline, contracts: contracts.clone(), ..Default::default() };
+    if req_payload.initial_capital > 0.0 {
+        config.initial_capital = req_payload.initial_capital;
+    }
+    if req_payload.quantity > 0.0 {
+        config.quantity = req_payload.quantity;
+    }
+    config.sizing = req_payload.sizing.as_ref().map(from_grpc_sizing).transpose()?;
+    if let Some(costs) = &req_payload.costs {
+        config.costs = from_grpc_carry_costs(costs)?;
+    }
+
+    let mut symbols: Vec<&str> = Vec::new();
+    for symbol in std::iter::once(&req_payload.symbol).chain(&req_payload.symbols).map(|s| s.trim()) {
+        if !symbol.is_empty() && !symbols.contains(&symbol) {
+            symbols.push(symbol);
+        }
+    }
+    if symbols.is_empty() {
+        return Err(EngineError::InvalidField { field: "symbol".to_string(), message: "Backtest request names no symbol to trade".to_string() });
+    }
+
+    let mut legs = Vec::with_capacity(symbols.len());
+    for symbol in symbols {
+        let candles = market_data_store.read().await
+            .get_candles(symbol, timeframe, from_ts, to_ts)?
+            .unwrap_or_default();
+        if candles.is_empty() {
+            return Err(EngineError::MarketDataError(format!(
+                "Market data not found for symbol '{}' and timeframe {}; load candles before running a backtest", symbol, timeframe
+            )));
+        }
+        // Each symbol gets its own instance: strategies keep per-series state.
+        let strategy = create_strategy(&req_payload.strategy, &params)?;
+        legs.push(PreparedLeg { symbol: symbol.to_string(), strategy, candles });
+    }
+
+    let benchmark = match req_payload.benchmark_symbol.trim() {
+        "" => None,
+        benchmark_symbol => Some(market_data_store.read().await
+            .get_candles(benchmark_symbol, timeframe, None, to_ts)?
+            .filter(|c| !c.is_empty())
+            .ok_or_else(|| EngineError::MarketDataError(format!(
+                "Market data not found for benchmark symbol '{}'; load it before using it as a benchmark", benchmark_symbol
+            )))?),
+    };
+
+    Ok(PreparedBacktest { legs, config, benchmark, params })
+}
+
+/// Resolves the strategy, configuration and candles of a `BacktestRequest` and runs it.
+/// Shared with the Monte Carlo handler, which starts from a backtest's trades.
+pub async fn execute_backtest(
+    req_payload: &BacktestRequest,
+    market_data_store: &Arc<RwLock<MarketDataStore>>,
+    contracts: &Contracts,
+    deadline: Option<Instant>,
+) -> Result<BacktestOutcome, EngineError> {
+    let mut prepared = prepare_backtest(req_payload, market_data_store, contracts, deadline).await?;
+    let mut outcome = run_portfolio_backtest_with_events(backtest_legs(&mut prepared.legs), prepared.config, |_| true)?;
+    compare_to_requested_benchmark(&mut outcome, &req_payload.benchmark_symbol, prepared.benchmark.as_deref());
+    Ok(outcome)
+}
+
+pub(super) fn backtest_legs(legs: &mut [PreparedLeg]) -> Vec<BacktestLeg<'_>> {
+    legs.iter_mut()
+        .map(|leg| BacktestLeg { strategy: leg.strategy.as_mut(), symbol: &leg.symbol, candles: &leg.candles })
+        .collect()
+}
+
+/// Replaces the runner's buy-and-hold of the traded symbol (none for a basket) with the requested benchmark, if any.
+pub(super) fn compare_to_requested_benchmark(outcome: &mut BacktestOutcome, benchmark_symbol: &str, benchmark: Option<&[Candle]>) {
+    if let Some(candles) = benchmark {
+        outcome.benchmark = compare_to_benchmark(benchmark_symbol.trim(), &outcome.report.equity_curve, candles);
+    }
+}
+
+pub(super) fn to_backtest_response(outcome: &BacktestOutcome) -> BacktestResponse {
+    BacktestResponse {
+        strategy_name: outcome.strategy_name.clone(),
+        report: Some(to_grpc_pnl_report(&outcome.report)),
+        metrics: Some(to_grpc_metrics(&outcome.metrics)),
+        charges: outcome.charges.iter().map(to_grpc_cost_charge).collect(),
+        benchmark: outcome.benchmark.as_ref().map(to_grpc_benchmark),
+    }
+}
+
+pub async fn handle_run_backtest(
+    req_payload: BacktestRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    contracts: Contracts,
+    deadline: Option<Instant>,
+) -> Result<Response<BacktestResponse>, Status> {
+    let outcome = execute_backtest(&req_payload, &market_data_store, &contracts, deadline).await?;
+    Ok(Response::new(to_backtest_response(&outcome)))
+}
+
+pub async fn handle_stream_backtest(
+    req_payload: StreamBacktestRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    contracts: Contracts,
+    deadline: Option<Instant>,
+) -> Result<Response<ReceiverStream<Result<GrpcBacktestEvent, Status>>>, Status> {
+    let backtest = req_payload.backtest
+        .ok_or_else(|| EngineError::InvalidRequest("Stream backtest request is missing the backtest to run".to_string()))?;
+    let PreparedBacktest { mut legs, config, benchmark, .. } = prepare_backtest(&backtest, &market_data_store, &contracts, deadline).await?;
+    let bar_delay = Duration::from_millis(req_payload.bar_delay_ms as u64);
+    let include_candles = req_payload.include_candles;
+
+    let (tx, rx) = mpsc::channel(64);
+    let symbol = backtest.symbol;
+    let benchmark_symbol = backtest.benchmark_symbol;
+
+    // The event loop is synchronous; pacing it with a sleep would block a runtime worker.
+    tokio::task::spawn_blocking(move || {
+        let result = run_portfolio_backtest_with_events(backtest_legs(&mut legs), config, |event| {
+            if let BacktestEvent::Candle { .. } = event {
+                if !bar_delay.is_zero() {
+                    std::thread::sleep(bar_delay);
+                }
+                if !include_candles {
+                    return true;
+                }
+            }
+            // A failed send means the client went away: stop the run.
+            tx.blocking_send(Ok(to_grpc_backtest_event(event))).is_ok()
+        });
+        let message = match result {
+            Ok(mut outcome) => {
+                compare_to_requested_benchmark(&mut outcome, &benchmark_symbol, benchmark.as_deref());
+                Ok(GrpcBacktestEvent {
+                    timestamp: legs.iter().filter_map(|leg| leg.candles.last()).map(|c| c.timestamp.timestamp_millis()).max().unwrap_or_default(),
+                    event: Some(GrpcEvent::Result(to_backtest_response(&outcome))),
+                })
+            }
+            Err(e) => {
+                tracing::debug!(symbol = %symbol, error = ?e, "Streamed backtest stopped (handler).");
+                Err(e.into())
+            }
+        };
+        if tx.blocking_send(message).is_err() {
+            tracing::debug!(symbol = %symbol, "Client disconnected from backtest stream (handler).");
+        }
+    });
+
+    Ok(Response::new(ReceiverStream::new(rx)))
+}
@@ -0,0 +1,89 @@
+// Handler for the RunMonteCarlo RPC
+// Runs the requested backtest, then resamples its closed trades on a blocking
+// thread, streaming progress messages and finally the percentile summary.
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{mpsc, RwLock};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Response, Status};
+
+use crate::backtest::monte_carlo::DEFAULT_MONTE_CARLO_ITERATIONS;
+use crate::backtest::{run_monte_carlo, MonteCarloConfig, MonteCarloMethod};
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::services::{MonteCarloProgress, MonteCarloRequest};
+use crate::simulation::Contracts;
+use super::helpers::to_grpc_monte_carlo_summary;
+use super::run_backtest::execute_backtest;
+use shared::models::to_f64;
+
+/// A Monte Carlo analysis ready to run: the backtest behind it has been run.
+pub(super) struct PreparedMonteCarlo {
+    pub config: MonteCarloConfig,
+    /// P&L of the backtest's closed trades, never empty.
+    pub pnls: Vec<f64>,
+    pub initial_capital: f64,
+    pub symbol: String,
+}
+
+/// Validates a `MonteCarloRequest` and runs its backtest. Shared with Monte Carlo jobs.
+pub(super) async fn prepare_monte_carlo(
+    req_payload: MonteCarloRequest,
+    market_data_store: &Arc<RwLock<MarketDataStore>>,
+    contracts: &Contracts,
+    deadline: Option<Instant>,
+) -> Result<PreparedMonteCarlo, EngineError> {
+    let backtest = req_payload.backtest
+        .ok_or_else(|| EngineError::InvalidRequest("Monte Carlo request is missing the backtest to analyse".to_string()))?;
+    let config = MonteCarloConfig {
+        iterations: if req_payload.iterations == 0 { DEFAULT_MONTE_CARLO_ITERATIONS } else { req_payload.iterations as usize },
+        method: MonteCarloMethod::parse(&req_payload.method)?,
+        seed: req_payload.seed,
+    };
+
+    let outcome = execute_backtest(&backtest, market_data_store, contracts, deadline).await?;
+    let pnls: Vec<f64> = outcome.report.trades.iter().map(|t| to_f64(t.pnl)).collect();
+    if pnls.is_empty() {
+        return Err(EngineError::InvalidRequest(format!(
+            "Backtest of '{}' on {} produced no closed trades to resample", outcome.strategy_name, backtest.symbol
+        )));
+    }
+    Ok(PreparedMonteCarlo { config, pnls, initial_capital: outcome.report.initial_capital, symbol: backtest.symbol })
+}
+
+pub async fn handle_run_monte_carlo(
+    req_payload: MonteCarloRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    contracts: Contracts,
+    deadline: Option<Instant>,
+) -> Result<Response<ReceiverStream<Result<MonteCarloProgress, Status>>>, Status> {
+    let PreparedMonteCarlo { config, pnls, initial_capital, symbol: symbol_for_log } = prepare_monte_carlo(req_payload, &market_data_store, &contracts, deadline).await?;
+    let (tx, rx) = mpsc::channel(4);
+
+    tokio::task::spawn_blocking(move || {
+        let result = run_monte_carlo(&pnls, initial_capital, config, |completed, total| {
+            let progress = MonteCarloProgress { completed: completed as u32, total: total as u32, summary: None };
+            // A failed send means the client went away: stop simulating.
+            tx.blocking_send(Ok(progress)).is_ok()
+        });
+        let message = match result {
+            Ok(summary) => {
+                tracing::info!(symbol = %symbol_for_log, iterations = summary.iterations, "Monte Carlo analysis finished (handler).");
+                Ok(MonteCarloProgress {
+                    completed: summary.iterations as u32,
+                    total: summary.iterations as u32,
+                    summary: Some(to_grpc_monte_carlo_summary(&summary)),
+                })
+            }
+            Err(e) => {
+                tracing::debug!(symbol = %symbol_for_log, error = ?e, "Monte Carlo analysis stopped (handler).");
+                Err(e.into())
+            }
+        };
+        if tx.blocking_send(message).is_err() {
+            tracing::debug!(symbol = %symbol_for_log, "Client disconnected from Monte Carlo stream (handler).");
+        }
+    });
+
+    Ok(Response::new(ReceiverStream::new(rx)))
+}
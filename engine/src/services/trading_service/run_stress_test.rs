@@ -0,0 +1,72 @@
+// Handler for the RunStressTest RPC
+// Re-runs the requested backtest under each shock scenario on a blocking thread
+// (one backtest per scenario plus the baseline).
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::backtest::{create_strategy, run_stress_test_with_progress, StressScenario};
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::services::{StressTestRequest, StressTestResponse};
+use crate::simulation::Contracts;
+use super::helpers::{from_grpc_stress_scenario, to_grpc_stress_result};
+use super::run_backtest::prepare_backtest;
+
+pub async fn handle_run_stress_test(
+    req_payload: StressTestRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    contracts: Contracts,
+    deadline: Option<Instant>,
+) -> Result<Response<StressTestResponse>, Status> {
+    let response = execute_stress_test(req_payload, &market_data_store, &contracts, deadline, |_, _| true).await?;
+    Ok(Response::new(response))
+}
+
+/// Runs a `StressTestRequest`, passing `on_progress` to `run_stress_test_with_progress`.
+/// Shared with stress test jobs.
+pub(super) async fn execute_stress_test(
+    req_payload: StressTestRequest,
+    market_data_store: &Arc<RwLock<MarketDataStore>>,
+    contracts: &Contracts,
+    deadline: Option<Instant>,
+    on_progress: impl FnMut(usize, usize) -> bool + Send + 'static,
+) -> Result<StressTestResponse, EngineError> {
+    let backtest = req_payload.backtest
+        .ok_or_else(|| EngineError::InvalidRequest("Stress test request is missing the backtest to run".to_string()))?;
+    let scenarios: Vec<StressScenario> = if req_payload.scenarios.is_empty() {
+        StressScenario::defaults()
+    } else {
+        req_payload.scenarios.iter().map(from_grpc_stress_scenario).collect()
+    };
+
+    let mut prepared = prepare_backtest(&backtest, market_data_store, contracts, deadline).await?;
+    if prepared.legs.len() > 1 {
+        return Err(EngineError::InvalidRequest("Stress tests run on a single symbol; remove the extra backtest symbols".to_string()));
+    }
+    let leg = prepared.legs.remove(0);
+    let strategy_name = leg.strategy.name();
+    let strategy_kind = backtest.strategy.clone();
+    let symbol = leg.symbol;
+
+    let (baseline, results) = tokio::task::spawn_blocking(move || {
+        run_stress_test_with_progress(
+            || create_strategy(&strategy_kind, &prepared.params),
+            &symbol,
+            &leg.candles,
+            prepared.config,
+            &scenarios,
+            on_progress,
+        )
+    })
+    .await
+    .map_err(|e| EngineError::ProcessingError(format!("Stress test task failed: {}", e)))??;
+
+    tracing::info!(symbol = %backtest.symbol, scenarios = results.len(), baseline_pnl = baseline.total_pnl, "Stress test finished (handler).");
+    Ok(StressTestResponse {
+        strategy_name,
+        baseline: Some(to_grpc_stress_result(&baseline)),
+        results: results.iter().map(to_grpc_stress_result).collect(),
+    })
+}
@@ -6,97 +6,187 @@ use uuid::Uuid;
 
 use crate::data::market_data::MarketDataStore;
 use crate::services::{TradeRequest, TradeResponse};
-use shared::models::TimeFrame;
+use shared::models::{to_f64, Price, TimeFrame};
 use crate::error::EngineError;
+use super::helpers::{from_grpc_price, from_grpc_timeframe, latest_closes, to_grpc_price, to_grpc_risk_violation};
+use crate::simulation::{Contracts, Fill, Order, OrderBook, OrderSide, OrderStatus, Portfolio, ReplayController, RiskLimits, TimeInForce};
 
 pub async fn handle_simulate_trade(
     req_payload: TradeRequest,
-    market_data_store: Arc<RwLock<MarketDataStore>>
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    replay: Arc<RwLock<ReplayController>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    risk_limits: Arc<RwLock<RiskLimits>>,
+    contracts: Contracts,
 ) -> Result<Response<TradeResponse>, Status> {
     tracing::debug!(symbol = %req_payload.symbol, action = %req_payload.action, "Handling SimulateTradeRequest in dedicated handler");
 
+    if !req_payload.quantity.is_finite() || req_payload.quantity <= 0.0 {
+        return Err(EngineError::InvalidField {
+            field: "quantity".to_string(),
+            message: format!("Invalid quantity: {}", req_payload.quantity),
+        }.into());
+    }
+
     let order_id = Uuid::new_v4().to_string();
-    let timeframe = TimeFrame::Day1;
+    let timeframe = from_grpc_timeframe(req_payload.timeframe)?;
+    let requested_price = req_payload.price.as_deref().map(|p| from_grpc_price("price", p)).transpose()?;
 
+    let replay_guard = replay.read().await;
     let store = market_data_store.read().await;
-    let candles_opt = store.get_candles(&req_payload.symbol, timeframe, None, None);
+    // During a replay (of the daily series) the simulator only knows the candles revealed so far.
+    let latest_candle = match replay_guard.visible_candles(&req_payload.symbol).filter(|_| timeframe == TimeFrame::Day1) {
+        Some(visible) => visible.last().cloned(),
+        None => store.last_candle(&req_payload.symbol, timeframe).cloned(),
+    };
 
-    if candles_opt.is_none() || candles_opt.as_ref().unwrap().is_empty() {
+    let Some(latest_candle) = latest_candle else {
         tracing::warn!(symbol = %req_payload.symbol, ?timeframe, "No market data available to simulate trade (handler).");
         return Ok(Response::new(TradeResponse {
             success: false,
-            message: format!("No market data available for symbol '{}' and timeframe {:?} to simulate trade.", req_payload.symbol, timeframe),
+            message: format!("No market data available for symbol '{}' and timeframe {} to simulate trade.", req_payload.symbol, timeframe),
             order_id,
-            filled_price: 0.0,
+            filled_price: to_grpc_price(Price::ZERO),
             filled_quantity: 0.0,
+            status: OrderStatus::Rejected.as_str().to_string(),
+            realized_pnl: to_grpc_price(Price::ZERO),
+            risk_violation: None,
         }));
-    }
-
-    let candles = candles_opt.unwrap();
-    let latest_candle = match candles.last() {
-        Some(c) => c.clone(),
-        None => {
-            let err_msg = format!("Logic error: candles list was non-empty for symbol '{}' but last() is None (handler).", req_payload.symbol);
-            tracing::error!("{}", err_msg);
-            return Err(EngineError::MarketDataError(err_msg).into());
-        }
     };
+    let portfolio_guard = portfolio.read().await;
+    let mut marks = latest_closes(portfolio_guard.positions().map(|p| p.symbol.as_str()), &replay_guard, &store);
+    marks.insert(req_payload.symbol.clone(), latest_candle.close_f64());
     drop(store);
+    drop(replay_guard);
+
+    // Orders that would add exposure beyond the account's buying power are rejected up front.
+    if let Some(side) = OrderSide::parse(&req_payload.action) {
+        let reference_price = requested_price.map(to_f64).unwrap_or(latest_candle.close_f64());
+        if let Err(reason) = portfolio_guard.check_buying_power(&req_payload.symbol, side, req_payload.quantity, reference_price, &marks) {
+            tracing::warn!(order_id = %order_id, symbol = %req_payload.symbol, failure_reason = %reason, "Trade rejected (handler)");
+            return Ok(Response::new(TradeResponse {
+                success: false,
+                message: reason,
+                order_id,
+                filled_price: to_grpc_price(Price::ZERO),
+                filled_quantity: 0.0,
+                status: OrderStatus::Rejected.as_str().to_string(),
+                realized_pnl: to_grpc_price(Price::ZERO),
+                risk_violation: None,
+            }));
+        }
+        // Risk limits are judged at the simulated time, so a replay's "today" is the replayed day.
+        if let Err(violation) = risk_limits.read().await.check_order(&req_payload.symbol, side, req_payload.quantity, &portfolio_guard, &marks, latest_candle.timestamp) {
+            tracing::warn!(order_id = %order_id, symbol = %req_payload.symbol, rule = violation.rule(), failure_reason = %violation, "Trade rejected by risk limit (handler)");
+            return Ok(Response::new(TradeResponse {
+                success: false,
+                message: violation.to_string(),
+                order_id,
+                filled_price: to_grpc_price(Price::ZERO),
+                filled_quantity: 0.0,
+                status: OrderStatus::Rejected.as_str().to_string(),
+                realized_pnl: to_grpc_price(Price::ZERO),
+                risk_violation: Some(to_grpc_risk_violation(&violation)),
+            }));
+        }
+    }
+    drop(portfolio_guard);
+
+    let mut risk_violation = None;
 
-    let (success, filled_price, message_detail) = match req_payload.order_type.to_uppercase().as_str() {
+    let (status, filled_price, message_detail) = match req_payload.order_type.to_uppercase().as_str() {
         "MARKET" => {
-            let price = latest_candle.close;
+            let price = contracts.round_to_tick(&req_payload.symbol, latest_candle.close);
             let msg = format!(
                 "Market {} order for {} of {} simulated at {:.2}",
                 req_payload.action.to_uppercase(), req_payload.quantity, req_payload.symbol, price
             );
-            (true, price, msg)
+            (OrderStatus::Filled, price, msg)
         }
         "LIMIT" => {
-            match req_payload.price {
-                Some(limit_price) => {
-                    match req_payload.action.to_uppercase().as_str() {
-                        "BUY" => {
-                            if latest_candle.low <= limit_price {
-                                let msg = format!("Limit BUY order for {} of {} simulated at {:.2}", req_payload.quantity, req_payload.symbol, limit_price);
-                                (true, limit_price, msg)
-                            } else {
-                                let msg = format!("Limit BUY order for {} not filled: market low {:.2} did not reach limit price {:.2}", req_payload.symbol, latest_candle.low, limit_price);
-                                (false, 0.0, msg)
-                            }
-                        }
-                        "SELL" => {
-                            if latest_candle.high >= limit_price {
-                                let msg = format!("Limit SELL order for {} of {} simulated at {:.2}", req_payload.quantity, req_payload.symbol, limit_price);
-                                (true, limit_price, msg)
+            match (requested_price, OrderSide::parse(&req_payload.action)) {
+                (None, _) => {
+                    let msg = "Limit price is required for LIMIT orders.".to_string();
+                    (OrderStatus::Rejected, Price::ZERO, msg)
+                }
+                (Some(_), None) => {
+                    let msg = format!("Unknown action '{}' for LIMIT order. Use 'BUY' or 'SELL'.", req_payload.action);
+                    (OrderStatus::Rejected, Price::ZERO, msg)
+                }
+                (Some(requested_price), Some(side)) => {
+                    // Off-tick limit prices are moved to the nearest valid tick on the safe side.
+                    let limit_price = contracts.round_limit_price(&req_payload.symbol, side == OrderSide::Buy, requested_price);
+                    match TimeInForce::parse(&req_payload.time_in_force, req_payload.expire_at) {
+                        Err(e) => (OrderStatus::Rejected, Price::ZERO, EngineError::SimulationError(e).to_string()),
+                        Ok(time_in_force) => {
+                            let order = Order {
+                                id: order_id.clone(),
+                                symbol: req_payload.symbol.clone(),
+                                side,
+                                quantity: req_payload.quantity,
+                                limit_price,
+                                time_in_force,
+                                placed_at: latest_candle.timestamp,
+                                status: OrderStatus::Working,
+                                filled_price: None,
+                                filled_at: None,
+                            };
+                            if order.is_marketable(&latest_candle) {
+                                let msg = format!("Limit {} order for {} of {} simulated at {:.2}", side.as_str(), req_payload.quantity, req_payload.symbol, limit_price);
+                                (OrderStatus::Filled, limit_price, msg)
+                            } else if let Err(violation) = {
+                                let open = order_book.read().await.working_orders(None).len();
+                                risk_limits.read().await.check_open_orders(open)
+                            } {
+                                let msg = violation.to_string();
+                                risk_violation = Some(to_grpc_risk_violation(&violation));
+                                (OrderStatus::Rejected, Price::ZERO, msg)
                             } else {
-                                let msg = format!("Limit SELL order for {} not filled: market high {:.2} did not reach limit price {:.2}", req_payload.symbol, latest_candle.high, limit_price);
-                                (false, 0.0, msg)
+                                let (reference, reference_price) = match side {
+                                    OrderSide::Buy => ("low", latest_candle.low),
+                                    OrderSide::Sell => ("high", latest_candle.high),
+                                };
+                                let msg = format!(
+                                    "Limit {} order for {} not filled: market {} {:.2} did not reach limit price {:.2}. Order is working ({}).",
+                                    side.as_str(), req_payload.symbol, reference, reference_price, limit_price, time_in_force.as_str()
+                                );
+                                order_book.write().await.place(order);
+                                (OrderStatus::Working, Price::ZERO, msg)
                             }
                         }
-                        _ => {
-                            let msg = format!("Unknown action '{}' for LIMIT order. Use 'BUY' or 'SELL'.", req_payload.action);
-                            (false, 0.0, msg)
-                        }
                     }
                 }
-                None => {
-                    let msg = "Limit price is required for LIMIT orders.".to_string();
-                    (false, 0.0, msg)
-                }
             }
         }
         _ => {
             let msg = format!("Unsupported order type: '{}'. Use 'MARKET' or 'LIMIT'.", req_payload.order_type);
-            (false, 0.0, msg)
+            (OrderStatus::Rejected, Price::ZERO, msg)
         }
     };
 
-    if success {
-        tracing::info!(order_id = %order_id, symbol = %req_payload.symbol, action = %req_payload.action, order_type = %req_payload.order_type, quantity = req_payload.quantity, filled_price, message = %message_detail, "Trade simulated successfully (handler)");
-        Ok(Response::new(TradeResponse { success: true, message: message_detail, order_id, filled_price, filled_quantity: req_payload.quantity }))
+    if status == OrderStatus::Filled {
+        // Fills are booked into the session portfolio (needs a BUY/SELL side to know the direction).
+        let realized_pnl = match OrderSide::parse(&req_payload.action) {
+            Some(side) => {
+                portfolio.write().await.apply_fill(Fill {
+                    order_id: order_id.clone(),
+                    symbol: req_payload.symbol.clone(),
+                    side,
+                    price: filled_price,
+                    quantity: req_payload.quantity,
+                    timestamp: latest_candle.timestamp,
+                })
+            }
+            None => {
+                tracing::warn!(order_id = %order_id, action = %req_payload.action, "Fill not booked into portfolio: unknown action");
+                Price::ZERO
+            }
+        };
+        tracing::info!(order_id = %order_id, symbol = %req_payload.symbol, action = %req_payload.action, order_type = %req_payload.order_type, quantity = req_payload.quantity, %filled_price, message = %message_detail, "Trade simulated successfully (handler)");
+        Ok(Response::new(TradeResponse { success: true, message: message_detail, order_id, filled_price: to_grpc_price(filled_price), filled_quantity: req_payload.quantity, status: status.as_str().to_string(), realized_pnl: to_grpc_price(realized_pnl), risk_violation: None }))
     } else {
-        tracing::warn!(order_id = %order_id, symbol = %req_payload.symbol, action = %req_payload.action, order_type = %req_payload.order_type, price = ?req_payload.price, failure_reason = %message_detail, "Trade simulation failed (handler)");
-        Ok(Response::new(TradeResponse { success: false, message: message_detail, order_id, filled_price: 0.0, filled_quantity: 0.0 }))
+        tracing::warn!(order_id = %order_id, symbol = %req_payload.symbol, action = %req_payload.action, order_type = %req_payload.order_type, price = ?req_payload.price, status = status.as_str(), failure_reason = %message_detail, "Trade not filled (handler)");
+        Ok(Response::new(TradeResponse { success: false, message: message_detail, order_id, filled_price: to_grpc_price(Price::ZERO), filled_quantity: 0.0, status: status.as_str().to_string(), realized_pnl: to_grpc_price(Price::ZERO), risk_violation }))
     }
 }
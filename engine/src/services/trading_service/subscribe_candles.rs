@@ -0,0 +1,69 @@
+// Handler for the SubscribeCandles RPC
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Response, Status};
+use tracing::Instrument;
+
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::services::{GrpcCandleUpdate, GrpcTimeFrame, SubscribeCandlesRequest};
+use crate::simulation::ReplayController;
+use shared::models::{Candle, TimeFrame};
+use super::helpers::{from_grpc_timeframe, to_grpc_candle};
+
+pub async fn handle_subscribe_candles(
+    req_payload: SubscribeCandlesRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    replay: Arc<RwLock<ReplayController>>,
+) -> Result<Response<ReceiverStream<Result<GrpcCandleUpdate, Status>>>, Status> {
+    let symbol = req_payload.symbol.trim().to_string();
+    if symbol.is_empty() {
+        return Err(EngineError::InvalidField { field: "symbol".to_string(), message: "Candle subscription names no symbol".to_string() }.into());
+    }
+    let timeframe = from_grpc_timeframe(req_payload.timeframe)?;
+
+    let mut store_rx = market_data_store.read().await.subscribe();
+    // Replays run on the daily series: only daily subscribers follow them.
+    let replayed = timeframe == TimeFrame::Day1;
+    let mut replay_rx = replay.read().await.subscribe_all();
+
+    let (tx, rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        let update = |candle: &Candle, source: &str| GrpcCandleUpdate {
+            symbol: symbol.clone(),
+            timeframe: GrpcTimeFrame::from(timeframe) as i32,
+            candle: Some(to_grpc_candle(candle)),
+            source: source.to_string(),
+        };
+        loop {
+            let message = tokio::select! {
+                _ = tx.closed() => break,
+                received = store_rx.recv() => match received {
+                    Ok(u) if u.symbol == symbol && u.timeframe == timeframe => update(&u.candle, "STORE"),
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(symbol = %symbol, skipped, "Candle subscriber lagged behind the store; updates were skipped (handler).");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                received = replay_rx.recv(), if replayed => match received {
+                    Ok((replayed_symbol, candle)) if replayed_symbol == symbol => update(&candle, "REPLAY"),
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(symbol = %symbol, skipped, "Candle subscriber lagged behind the replay; candles were skipped (handler).");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            };
+            if tx.send(Ok(message)).await.is_err() {
+                break;
+            }
+        }
+        tracing::debug!(symbol = %symbol, "Candle subscription ended (handler).");
+    }.in_current_span());
+
+    Ok(Response::new(ReceiverStream::new(rx)))
+}
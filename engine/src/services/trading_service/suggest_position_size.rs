@@ -0,0 +1,71 @@
+// Handler for the SuggestPositionSize RPC
+// Lets the GUI order panel propose a quantity from the account size, the entry
+// price and the stop distance (or the symbol's ATR).
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tonic::{Response, Status};
+
+use crate::backtest::runner::SIZING_ATR_PERIOD;
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::indicators::{Atr, IndicatorCalculator};
+use crate::services::{PositionSizeRequest, PositionSizeResponse};
+use crate::simulation::{position_size, Contracts, Portfolio, ReplayController, SizingInput, SizingMethod};
+use shared::models::TimeFrame;
+use super::helpers::{from_grpc_sizing, latest_closes};
+
+pub async fn handle_suggest_position_size(
+    req_payload: PositionSizeRequest,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    replay: Arc<RwLock<ReplayController>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    contracts: Contracts,
+) -> Result<Response<PositionSizeResponse>, Status> {
+    let timeframe = TimeFrame::Day1;
+    let sizing = req_payload.sizing.as_ref()
+        .ok_or_else(|| EngineError::InvalidRequest("A sizing method is required".to_string()))?;
+    let method = from_grpc_sizing(sizing)?;
+
+    let replay_guard = replay.read().await;
+    let store = market_data_store.read().await;
+    // During a replay only the revealed candles count, as for order fills.
+    let candles = match replay_guard.visible_candles(&req_payload.symbol) {
+        Some(visible) => visible,
+        None => store.get_candles(&req_payload.symbol, timeframe, None, None).map_err(EngineError::from)?.unwrap_or_default(),
+    };
+
+    let account_equity = if req_payload.account_equity > 0.0 {
+        req_payload.account_equity
+    } else {
+        let portfolio_guard = portfolio.read().await;
+        portfolio_guard.equity(&latest_closes(portfolio_guard.positions().map(|p| p.symbol.as_str()), &replay_guard, &store))
+    };
+    drop(store);
+    drop(replay_guard);
+
+    let price = if req_payload.price > 0.0 {
+        req_payload.price
+    } else {
+        candles.last().map(|c| c.close_f64()).ok_or_else(|| EngineError::MarketDataError(format!(
+            "Market data not found for symbol '{}'; pass a price or load candles first", req_payload.symbol
+        )))?
+    };
+
+    let mut input = SizingInput::for_symbol(&req_payload.symbol, account_equity, price, &contracts);
+    input.stop_distance = Some(req_payload.stop_distance).filter(|d| *d > 0.0);
+    input.lot_size = req_payload.lot_size;
+    if let SizingMethod::AtrRisk { .. } = method {
+        let period = if req_payload.atr_period == 0 { SIZING_ATR_PERIOD } else { req_payload.atr_period as usize };
+        input.atr = Atr::new(period)?.calculate_columns(candles.columns()).last().copied().filter(|a| !a.is_nan());
+    }
+
+    let result = position_size(method, &input)?;
+    Ok(Response::new(PositionSizeResponse {
+        quantity: result.quantity,
+        risk_amount: result.risk_amount,
+        capital_required: result.capital_required,
+        account_equity,
+        price,
+        atr: input.atr.unwrap_or(0.0),
+    }))
+}
@@ -0,0 +1,129 @@
+// Handler for the TradingSession RPC
+// An interactive paper-trading session over one bidirectional stream: each request is
+// handled like the matching unary RPC (SimulateTrade, CancelOrder, ModifyOrder) and
+// answered on the response stream, followed by the fills of the session's orders and the
+// positions they leave as they happen, whether candles arrive by loading or replay.
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Response, Status};
+use tracing::Instrument;
+
+use crate::data::market_data::MarketDataStore;
+use crate::services::generated::session_event::Event;
+use crate::services::generated::session_request::Action;
+use crate::services::{SessionEvent, SessionRequest};
+use crate::simulation::{Contracts, Execution, OrderBook, OrderStatus, Portfolio, ReplayController, RiskLimits};
+use super::helpers::{to_grpc_execution, to_grpc_position_state, to_grpc_price};
+use super::{manage_orders, simulate_trade};
+
+/// Shared state the session trades against, as in the unary handlers.
+#[derive(Clone)]
+pub struct SessionContext {
+    pub market_data_store: Arc<RwLock<MarketDataStore>>,
+    pub order_book: Arc<RwLock<OrderBook>>,
+    pub replay: Arc<RwLock<ReplayController>>,
+    pub portfolio: Arc<RwLock<Portfolio>>,
+    pub risk_limits: Arc<RwLock<RiskLimits>>,
+    pub contracts: Contracts,
+}
+
+/// Runs the session until the client closes its side of the stream or goes away.
+pub async fn handle_trading_session(
+    requests: impl Stream<Item = Result<SessionRequest, Status>> + Send + Unpin + 'static,
+    context: SessionContext,
+) -> Result<Response<ReceiverStream<Result<SessionEvent, Status>>>, Status> {
+    let (tx, rx) = mpsc::channel(64);
+    let mut fills = context.portfolio.read().await.subscribe_executions();
+
+    tokio::spawn(async move {
+        let mut requests = requests;
+        let mut orders: HashSet<String> = HashSet::new(); // Placed in this session
+        loop {
+            let events = tokio::select! {
+                _ = tx.closed() => break,
+                request = requests.next() => match request {
+                    Some(Ok(request)) => handle_request(request, &context, &mut orders).await,
+                    Some(Err(status)) => {
+                        tracing::debug!(error = %status, "Trading session request stream failed (handler).");
+                        break;
+                    }
+                    None => break,
+                },
+                received = fills.recv() => match received {
+                    Ok(execution) if orders.contains(&execution.fill.order_id) => fill_events(&execution, &context).await,
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "Trading session lagged behind the executions; fills were skipped (handler).");
+                        continue;
+                    }
+                    // The account was reset: follow the new portfolio.
+                    Err(broadcast::error::RecvError::Closed) => {
+                        fills = context.portfolio.read().await.subscribe_executions();
+                        continue;
+                    }
+                },
+            };
+            for event in events {
+                if tx.send(Ok(event)).await.is_err() {
+                    return;
+                }
+            }
+        }
+        tracing::debug!(orders = orders.len(), "Trading session ended (handler).");
+    }.in_current_span());
+
+    Ok(Response::new(ReceiverStream::new(rx)))
+}
+
+async fn handle_request(request: SessionRequest, context: &SessionContext, orders: &mut HashSet<String>) -> Vec<SessionEvent> {
+    let answer = |event| SessionEvent { request_id: request.request_id.clone(), event: Some(event) };
+    let result = match request.action.clone() {
+        Some(Action::Order(order)) => simulate_trade::handle_simulate_trade(
+            order,
+            context.market_data_store.clone(),
+            context.order_book.clone(),
+            context.replay.clone(),
+            context.portfolio.clone(),
+            context.risk_limits.clone(),
+            context.contracts.clone(),
+        ).await.map(|response| {
+            let response = response.into_inner();
+            // Resting orders answer with success = false but may still fill later.
+            if [OrderStatus::Filled, OrderStatus::Working].iter().any(|status| response.status == status.as_str()) {
+                orders.insert(response.order_id.clone());
+            }
+            Event::Order(response)
+        }),
+        Some(Action::Cancel(cancel)) => manage_orders::handle_cancel_order(cancel, context.order_book.clone()).await
+            .map(|response| Event::OrderState(response.into_inner())),
+        Some(Action::Modify(modify)) => manage_orders::handle_modify_order(
+            modify,
+            context.market_data_store.clone(),
+            context.order_book.clone(),
+            context.replay.clone(),
+            context.portfolio.clone(),
+            context.risk_limits.clone(),
+            context.contracts.clone(),
+        ).await.map(|response| Event::OrderState(response.into_inner())),
+        None => Err(Status::invalid_argument("Session request carries no order, cancel or modify")),
+    };
+    match result {
+        Ok(event) => vec![answer(event)],
+        Err(status) => vec![answer(Event::Error(status.message().to_string()))],
+    }
+}
+
+async fn fill_events(execution: &Execution, context: &SessionContext) -> Vec<SessionEvent> {
+    let mut events = vec![SessionEvent { request_id: String::new(), event: Some(Event::Fill(to_grpc_execution(execution))) }];
+    if let Some(position) = context.portfolio.read().await.position(&execution.fill.symbol) {
+        // The position as this fill left it; realized P&L includes any later fills already booked.
+        let mut state = to_grpc_position_state(position);
+        state.quantity = execution.position_after;
+        state.average_price = to_grpc_price(execution.average_price_after);
+        events.push(SessionEvent { request_id: String::new(), event: Some(Event::Position(state)) });
+    }
+    events
+}
@@ -0,0 +1,176 @@
+// Persistent paper-trading account.
+// The portfolio (cash, positions, fill journal) and the order book are saved as JSON
+// so a paper-trading account survives engine restarts. Saving goes through a temporary
+// file and a rename, so a crash mid-write leaves the previous snapshot intact.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use super::{OrderBook, Portfolio};
+use crate::error::EngineError;
+
+/// How often the autosave task checks the account for changes.
+pub const ACCOUNT_AUTOSAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSnapshot {
+    pub saved_at: DateTime<Utc>,
+    pub portfolio: Portfolio,
+    pub order_book: OrderBook,
+}
+
+impl AccountSnapshot {
+    /// Copies the current account state. Locks the order book before the portfolio and holds
+    /// both, so a snapshot never sees one side of a fill without the other.
+    pub async fn capture(portfolio: &Arc<RwLock<Portfolio>>, order_book: &Arc<RwLock<OrderBook>>) -> Self {
+        let order_book = order_book.read().await;
+        let portfolio = portfolio.read().await;
+        Self { saved_at: Utc::now(), portfolio: portfolio.clone(), order_book: order_book.clone() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountStore {
+    path: PathBuf,
+}
+
+impl AccountStore {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self { path: path.as_ref().to_path_buf() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the saved account; `None` when nothing has been saved yet.
+    pub fn load(&self) -> Result<Option<AccountSnapshot>, EngineError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&self.path)?;
+        let mut snapshot: AccountSnapshot = serde_json::from_str(&content).map_err(|e| {
+            EngineError::ConfigError(format!("Invalid paper-trading account file {:?}: {}", self.path, e))
+        })?;
+        snapshot.portfolio.relink_contracts();
+        snapshot.order_book.retire_closed_orders();
+        Ok(Some(snapshot))
+    }
+
+    pub fn save(&self, snapshot: &AccountSnapshot) -> Result<(), EngineError> {
+        let json = serde_json::to_string_pretty(snapshot)
+            .map_err(|e| EngineError::ProcessingError(format!("Failed to serialize the paper-trading account: {}", e)))?;
+        write_atomically(&self.path, &json)
+    }
+}
+
+fn write_atomically(path: &Path, content: &str) -> Result<(), EngineError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Saves the account every `ACCOUNT_AUTOSAVE_INTERVAL` when it has changed since the last save.
+/// Covers every writer (trades, order fills during replays, resets) without each having to save.
+pub fn spawn_autosave(
+    store: Arc<AccountStore>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    order_book: Arc<RwLock<OrderBook>>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        // Compared as JSON values (ordered maps), so HashMap iteration order does not matter.
+        let mut last_saved: Option<serde_json::Value> = None;
+        let mut interval = tokio::time::interval(ACCOUNT_AUTOSAVE_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let snapshot = AccountSnapshot::capture(&portfolio, &order_book).await;
+            let state = serde_json::to_value((&snapshot.portfolio, &snapshot.order_book)).ok();
+            if state.is_some() && state == last_saved {
+                continue;
+            }
+            match store.save(&snapshot) {
+                Ok(()) => {
+                    tracing::debug!(path = ?store.path(), "Paper-trading account saved");
+                    last_saved = state;
+                }
+                Err(e) => tracing::warn!(path = ?store.path(), error = %e, "Failed to save the paper-trading account"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::Price;
+    use crate::simulation::{Fill, Order, OrderSide, OrderStatus, TimeInForce};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_account_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AccountStore::new(dir.path().join("nested").join("account.json"));
+        assert!(store.load().unwrap().is_none());
+
+        let ts = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+        let mut portfolio = Portfolio::new(50_000.0);
+        portfolio.apply_fill(Fill { order_id: "o1".to_string(), symbol: "WINFUT".to_string(), side: OrderSide::Sell, price: Price::from(120_000), quantity: 2.0, timestamp: ts });
+        let mut order_book = OrderBook::new();
+        order_book.place(Order {
+            id: "o2".to_string(), symbol: "PETR4".to_string(), side: OrderSide::Buy, quantity: 100.0, limit_price: Price::new(3_015, 2),
+            time_in_force: TimeInForce::Gtd(ts), placed_at: ts, status: OrderStatus::Working, filled_price: None, filled_at: None,
+        });
+        store.save(&AccountSnapshot { saved_at: ts, portfolio, order_book }).unwrap();
+
+        let loaded = store.load().unwrap().unwrap();
+        let position = loaded.portfolio.position("WINFUT").unwrap();
+        assert_eq!(position.quantity, -2.0);
+        assert_eq!(position.point_value, 0.2); // Re-attached on load
+        assert_eq!(loaded.portfolio.cash, Price::from(50_000));
+        assert_eq!(loaded.portfolio.executions().len(), 1);
+        assert_eq!(loaded.order_book.get("o2").unwrap().time_in_force, TimeInForce::Gtd(ts));
+        assert_eq!(loaded.order_book.get("o2").unwrap().limit_price, Price::new(3_015, 2));
+    }
+
+    #[test]
+    fn test_prices_are_saved_as_strings_and_old_numeric_files_still_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("account.json");
+        let ts = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+        let mut order_book = OrderBook::new();
+        order_book.place(Order {
+            id: "o1".to_string(), symbol: "PETR4".to_string(), side: OrderSide::Buy, quantity: 100.0, limit_price: Price::new(3_015, 2),
+            time_in_force: TimeInForce::Day, placed_at: ts, status: OrderStatus::Working, filled_price: None, filled_at: None,
+        });
+        AccountStore::new(&path).save(&AccountSnapshot { saved_at: ts, portfolio: Portfolio::new(50_000.0), order_book }).unwrap();
+
+        let mut saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["portfolio"]["cash"], "50000");
+        assert_eq!(saved["order_book"]["orders"]["o1"]["limit_price"], "30.15");
+
+        // Files written before prices were saved as strings hold JSON numbers.
+        saved["portfolio"]["cash"] = serde_json::json!(49_999.5);
+        saved["order_book"]["orders"]["o1"]["limit_price"] = serde_json::json!(30.15);
+        fs::write(&path, saved.to_string()).unwrap();
+        let loaded = AccountStore::new(&path).load().unwrap().unwrap();
+        assert_eq!(loaded.portfolio.cash, Price::new(499_995, 1));
+        assert_eq!(loaded.order_book.get("o1").unwrap().limit_price, Price::new(3_015, 2));
+    }
+
+    #[test]
+    fn test_corrupt_account_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("account.json");
+        fs::write(&path, "not json").unwrap();
+        assert!(AccountStore::new(&path).load().is_err());
+    }
+}
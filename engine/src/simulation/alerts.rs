@@ -0,0 +1,469 @@
+// Price alerts: conditions on a symbol's series (a price crossing a level, an indicator crossing a
+// threshold, a percent move) checked against every candle that arrives for it, whether loaded into
+// the store or revealed by a replay. An alert fires once and is then removed; whoever subscribed
+// to the book hears about it. Alerts live in memory and do not survive an engine restart.
+use chrono::{DateTime, Utc};
+use shared::models::{Candle, TimeFrame};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+
+use super::ReplayController;
+use crate::data::market_data::MarketDataStore;
+use crate::error::EngineError;
+use crate::indicators::{Atr, Ema, IndicatorCalculator, Rsi, Sma};
+
+const ALERT_CHANNEL_CAPACITY: usize = 256;
+/// Candles an indicator alert computes its indicator over, ending at the candle being checked:
+/// enough for the indicators' smoothing to settle without recomputing the whole series.
+pub const ALERT_HISTORY_BARS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrossDirection {
+    Above,
+    Below,
+}
+
+impl CrossDirection {
+    /// Parses "ABOVE"/"BELOW", case-insensitive.
+    pub fn parse(direction: &str) -> Option<Self> {
+        match direction.trim().to_uppercase().as_str() {
+            "ABOVE" => Some(CrossDirection::Above),
+            "BELOW" => Some(CrossDirection::Below),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CrossDirection::Above => "ABOVE",
+            CrossDirection::Below => "BELOW",
+        }
+    }
+}
+
+/// An indicator an alert can watch, with its period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertIndicator {
+    Sma(usize),
+    Ema(usize),
+    Rsi(usize),
+    Atr(usize),
+}
+
+impl AlertIndicator {
+    /// Parses "SMA", "EMA", "RSI" or "ATR" (case-insensitive); a period of 0 takes the indicator's
+    /// default, as CalculateIndicator does.
+    pub fn parse(name: &str, period: usize) -> Result<Self, EngineError> {
+        match name.trim().to_uppercase().as_str() {
+            "SMA" => Ok(AlertIndicator::Sma(if period == 0 { 20 } else { period })),
+            "EMA" => Ok(AlertIndicator::Ema(if period == 0 { 20 } else { period })),
+            "RSI" => Ok(AlertIndicator::Rsi(if period == 0 { 14 } else { period })),
+            "ATR" => Ok(AlertIndicator::Atr(if period == 0 { 14 } else { period })),
+            _ => Err(EngineError::InvalidField {
+                field: "indicator".to_string(),
+                message: format!("Alerts watch SMA, EMA, RSI or ATR, not '{}'", name),
+            }),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AlertIndicator::Sma(_) => "SMA",
+            AlertIndicator::Ema(_) => "EMA",
+            AlertIndicator::Rsi(_) => "RSI",
+            AlertIndicator::Atr(_) => "ATR",
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        match *self {
+            AlertIndicator::Sma(period) | AlertIndicator::Ema(period) | AlertIndicator::Rsi(period) | AlertIndicator::Atr(period) => period,
+        }
+    }
+
+    /// The indicator's value at the last of `candles`, if it has one yet (never, with a period of 0).
+    pub fn latest_value(&self, candles: &[Candle]) -> Option<f64> {
+        let calculator: Box<dyn IndicatorCalculator> = match *self {
+            AlertIndicator::Sma(period) => Box::new(Sma::new(period).ok()?),
+            AlertIndicator::Ema(period) => Box::new(Ema::new(period).ok()?),
+            AlertIndicator::Rsi(period) => Box::new(Rsi::new(period).ok()?),
+            AlertIndicator::Atr(period) => Box::new(Atr::new(period).ok()?),
+        };
+        calculator.calculate(candles).last().copied().filter(|value| value.is_finite())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertCondition {
+    /// The price reaches `level`, coming from the side the previous close was on.
+    PriceCross { level: f64 },
+    /// The indicator goes from one side of `threshold` to the other, in `direction`, between closes.
+    Indicator { indicator: AlertIndicator, threshold: f64, direction: CrossDirection },
+    /// The price moves `percent` away from the alert's reference price; negative for a fall.
+    PercentMove { percent: f64 },
+}
+
+impl AlertCondition {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AlertCondition::PriceCross { .. } => "PRICE_CROSS",
+            AlertCondition::Indicator { .. } => "INDICATOR",
+            AlertCondition::PercentMove { .. } => "PERCENT_MOVE",
+        }
+    }
+
+    fn validate(&self) -> Result<(), EngineError> {
+        let invalid = |field: &str, message: &str| Err(EngineError::InvalidField { field: field.to_string(), message: message.to_string() });
+        match *self {
+            AlertCondition::PriceCross { level } if !(level.is_finite() && level > 0.0) => invalid("level", "The alert price must be positive"),
+            AlertCondition::Indicator { threshold, .. } if !threshold.is_finite() => invalid("level", "The indicator threshold must be a number"),
+            AlertCondition::PercentMove { percent } if !(percent.is_finite() && percent != 0.0 && percent > -100.0) => {
+                invalid("level", "The percent move must be non-zero and above -100%")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceAlert {
+    pub id: String,
+    pub symbol: String,
+    pub timeframe: TimeFrame,
+    pub condition: AlertCondition,
+    /// The user's own words for the alert, e.g. "breakout"; may be empty.
+    pub note: String,
+    pub created_at: DateTime<Utc>,
+    /// Close of the latest candle when the alert was set (or the open of the first candle checked,
+    /// if there was none): what percent moves are measured from.
+    pub reference_price: Option<f64>,
+    // What the previous candle checked left behind, for crossings
+    last_close: Option<f64>,
+    last_value: Option<f64>,
+    last_seen: Option<DateTime<Utc>>,
+}
+
+impl PriceAlert {
+    /// Checks the last of `candles` (its series up to it), returning the price the alert fired at.
+    /// Candles older than the last one checked are ignored, so loading history never fires alerts.
+    fn check(&mut self, candles: &[Candle]) -> Option<(f64, Option<f64>)> {
+        let candle = candles.last()?;
+        if self.last_seen.is_some_and(|seen| candle.timestamp < seen) {
+            return None;
+        }
+        let value = match &self.condition {
+            AlertCondition::Indicator { indicator, .. } => indicator.latest_value(candles),
+            _ => None,
+        };
+        let fired_at = match self.condition {
+            AlertCondition::PriceCross { level } => {
+                let reached = match self.last_close {
+                    Some(previous) if previous < level => candle.high_f64() >= level,
+                    Some(previous) if previous > level => candle.low_f64() <= level,
+                    _ => candle.low_f64() <= level && level <= candle.high_f64(),
+                };
+                reached.then_some(level)
+            }
+            AlertCondition::Indicator { threshold, direction, .. } => {
+                let crossed = match (self.last_value, value, direction) {
+                    (Some(previous), Some(current), CrossDirection::Above) => previous <= threshold && current > threshold,
+                    (Some(previous), Some(current), CrossDirection::Below) => previous >= threshold && current < threshold,
+                    _ => false,
+                };
+                crossed.then_some(candle.close_f64())
+            }
+            AlertCondition::PercentMove { percent } => {
+                let target = *self.reference_price.get_or_insert(candle.open_f64()) * (1.0 + percent / 100.0);
+                let reached = if percent > 0.0 { candle.high_f64() >= target } else { candle.low_f64() <= target };
+                reached.then_some(target)
+            }
+        };
+        self.last_close = Some(candle.close_f64());
+        self.last_value = value.or(self.last_value);
+        self.last_seen = Some(candle.timestamp);
+        fired_at.map(|price| (price, value))
+    }
+}
+
+/// An alert that fired, and the candle that fired it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertTrigger {
+    pub alert: PriceAlert,
+    pub timestamp: DateTime<Utc>,
+    /// The level reached: the alert price, the percent move's target, or the close for indicators.
+    pub price: f64,
+    /// The indicator's value, for indicator alerts.
+    pub value: Option<f64>,
+    /// "STORE" (a candle loaded into the engine) or "REPLAY" (revealed by a replay).
+    pub source: &'static str,
+}
+
+pub struct AlertBook {
+    alerts: HashMap<String, PriceAlert>,
+    triggers: broadcast::Sender<AlertTrigger>,
+    watching: bool, // Whether the task checking incoming candles runs; see `watch_alerts`
+}
+
+impl Default for AlertBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AlertBook {
+    pub fn new() -> Self {
+        AlertBook {
+            alerts: HashMap::new(),
+            triggers: broadcast::channel(ALERT_CHANNEL_CAPACITY).0,
+            watching: false,
+        }
+    }
+
+    /// Sets an alert on `symbol`'s `timeframe` series, whose candles so far end with `history`:
+    /// its latest candle is where crossings and percent moves start from.
+    pub fn add(&mut self, symbol: &str, timeframe: TimeFrame, condition: AlertCondition, note: &str, history: &[Candle]) -> Result<PriceAlert, EngineError> {
+        condition.validate()?;
+        let latest = history.last();
+        let last_value = match &condition {
+            AlertCondition::Indicator { indicator, .. } => indicator.latest_value(history),
+            _ => None,
+        };
+        let alert = PriceAlert {
+            id: uuid::Uuid::new_v4().to_string(),
+            symbol: symbol.to_string(),
+            timeframe,
+            condition,
+            note: note.trim().to_string(),
+            created_at: Utc::now(),
+            reference_price: latest.map(|c| c.close_f64()),
+            last_close: latest.map(|c| c.close_f64()),
+            last_value,
+            last_seen: latest.map(|c| c.timestamp),
+        };
+        tracing::info!(alert_id = %alert.id, symbol, kind = alert.condition.kind(), "Price alert set");
+        self.alerts.insert(alert.id.clone(), alert.clone());
+        Ok(alert)
+    }
+
+    /// Removes an alert that has not fired.
+    pub fn remove(&mut self, alert_id: &str) -> Result<PriceAlert, EngineError> {
+        let alert = self.alerts.remove(alert_id)
+            .ok_or_else(|| EngineError::SimulationError(format!("Alert '{}' not found; it may have fired already", alert_id)))?;
+        tracing::info!(alert_id, symbol = %alert.symbol, "Price alert removed");
+        Ok(alert)
+    }
+
+    /// Alerts waiting to fire, optionally restricted to a symbol, oldest first.
+    pub fn alerts(&self, symbol: Option<&str>) -> Vec<&PriceAlert> {
+        let mut alerts: Vec<&PriceAlert> = self.alerts.values()
+            .filter(|a| symbol.is_none_or(|s| a.symbol == s))
+            .collect();
+        alerts.sort_by_key(|a| a.created_at);
+        alerts
+    }
+
+    /// Whether any alert watches `symbol`'s `timeframe` series.
+    pub fn watches(&self, symbol: &str, timeframe: TimeFrame) -> bool {
+        self.alerts.values().any(|a| a.symbol == symbol && a.timeframe == timeframe)
+    }
+
+    /// Checks the alerts on `symbol`'s `timeframe` series against the last of `candles` (the
+    /// series up to it). Alerts that fire are removed, broadcast to subscribers and returned.
+    pub fn evaluate(&mut self, symbol: &str, timeframe: TimeFrame, candles: &[Candle], source: &'static str) -> Vec<AlertTrigger> {
+        let Some(candle) = candles.last() else { return Vec::new() };
+        let mut fired: Vec<String> = Vec::new();
+        let mut triggers = Vec::new();
+        for alert in self.alerts.values_mut().filter(|a| a.symbol == symbol && a.timeframe == timeframe) {
+            if let Some((price, value)) = alert.check(candles) {
+                fired.push(alert.id.clone());
+                triggers.push(AlertTrigger { alert: alert.clone(), timestamp: candle.timestamp, price, value, source });
+            }
+        }
+        for id in fired {
+            self.alerts.remove(&id);
+        }
+        for trigger in &triggers {
+            tracing::info!(alert_id = %trigger.alert.id, symbol, price = trigger.price, source, "Price alert fired");
+            // Nobody listening is fine: the alert still fired.
+            let _ = self.triggers.send(trigger.clone());
+        }
+        triggers
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<AlertTrigger> {
+        self.triggers.subscribe()
+    }
+}
+
+/// Starts checking `alerts` against the candles the store and replays broadcast, unless that is
+/// already running. The task runs for as long as the engine does. Must be called inside a Tokio runtime.
+pub async fn watch_alerts(
+    alerts: Arc<RwLock<AlertBook>>,
+    market_data_store: Arc<RwLock<MarketDataStore>>,
+    replay: Arc<RwLock<ReplayController>>,
+) {
+    {
+        let mut book = alerts.write().await;
+        if book.watching {
+            return;
+        }
+        book.watching = true;
+    }
+    let mut store_rx = market_data_store.read().await.subscribe();
+    let mut replay_rx = replay.read().await.subscribe_all();
+
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                received = store_rx.recv() => match received {
+                    Ok(update) => {
+                        if !alerts.read().await.watches(&update.symbol, update.timeframe) {
+                            continue;
+                        }
+                        let history = market_data_store.read().await
+                            .last_candles(&update.symbol, update.timeframe, Some(update.candle.timestamp), ALERT_HISTORY_BARS);
+                        let history = match history {
+                            Ok(history) => history,
+                            Err(e) => {
+                                tracing::warn!(error = %e, symbol = %update.symbol, "Alerts not checked: candles could not be read from the candle store");
+                                continue;
+                            }
+                        };
+                        alerts.write().await.evaluate(&update.symbol, update.timeframe, &history, "STORE");
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "Alert checks lagged behind the store; candles were not checked");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                received = replay_rx.recv() => match received {
+                    // Replays reveal the daily series
+                    Ok((symbol, candle)) => {
+                        if !alerts.read().await.watches(&symbol, TimeFrame::Day1) {
+                            continue;
+                        }
+                        let visible = replay.read().await.visible_candles(&symbol).unwrap_or_default();
+                        let history = visible.range(None, Some(candle.timestamp));
+                        let start = history.len().saturating_sub(ALERT_HISTORY_BARS);
+                        alerts.write().await.evaluate(&symbol, TimeFrame::Day1, &history[start..], "REPLAY");
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "Alert checks lagged behind the replay; candles were not checked");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            }
+        }
+        tracing::debug!("Alert watcher exited");
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use shared::models::to_price;
+
+    fn series(closes: &[f64]) -> Vec<Candle> {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        closes.iter().enumerate().map(|(i, &close)| Candle {
+            symbol: "TEST".to_string(),
+            timestamp: t0 + Duration::days(i as i64),
+            open: to_price(close),
+            high: to_price(close + 0.5),
+            low: to_price(close - 0.5),
+            close: to_price(close),
+            volume: 10.0,
+            trades: 1,
+        }).collect()
+    }
+
+    #[test]
+    fn test_price_cross_fires_once_when_reached() {
+        let candles = series(&[100.0, 101.0, 102.0, 104.0, 106.0]);
+        let mut book = AlertBook::new();
+        let mut rx = book.subscribe();
+        book.add("TEST", TimeFrame::Day1, AlertCondition::PriceCross { level: 105.0 }, "", &candles[..2]).unwrap();
+
+        assert!(book.evaluate("TEST", TimeFrame::Day1, &candles[..3], "STORE").is_empty());
+        assert!(book.evaluate("TEST", TimeFrame::Day1, &candles[..4], "STORE").is_empty());
+        let triggers = book.evaluate("TEST", TimeFrame::Day1, &candles[..5], "STORE");
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].price, 105.0);
+        assert_eq!(triggers[0].timestamp, candles[4].timestamp);
+        assert_eq!(rx.try_recv().unwrap(), triggers[0]);
+        // Fired alerts are gone
+        assert!(book.alerts(None).is_empty());
+        assert!(!book.watches("TEST", TimeFrame::Day1));
+    }
+
+    #[test]
+    fn test_price_cross_from_above() {
+        let candles = series(&[110.0, 104.0]);
+        let mut book = AlertBook::new();
+        book.add("TEST", TimeFrame::Day1, AlertCondition::PriceCross { level: 105.0 }, "", &candles[..1]).unwrap();
+        assert_eq!(book.evaluate("TEST", TimeFrame::Day1, &candles, "STORE").len(), 1);
+    }
+
+    #[test]
+    fn test_older_candles_and_other_series_are_ignored() {
+        let candles = series(&[100.0, 101.0, 110.0]);
+        let mut book = AlertBook::new();
+        book.add("TEST", TimeFrame::Day1, AlertCondition::PriceCross { level: 100.0 }, "", &candles).unwrap();
+
+        // History loaded before the latest candle reaches the level, but is older
+        assert!(book.evaluate("TEST", TimeFrame::Day1, &candles[..1], "STORE").is_empty());
+        assert!(book.evaluate("TEST", TimeFrame::Hour1, &series(&[100.0]), "STORE").is_empty());
+        assert!(book.evaluate("OTHER", TimeFrame::Day1, &series(&[100.0]), "STORE").is_empty());
+        assert_eq!(book.alerts(Some("TEST")).len(), 1);
+    }
+
+    #[test]
+    fn test_percent_move_from_reference() {
+        let candles = series(&[100.0, 102.0, 104.0, 106.0]);
+        let mut book = AlertBook::new();
+        let rise = book.add("TEST", TimeFrame::Day1, AlertCondition::PercentMove { percent: 5.0 }, "", &candles[..1]).unwrap();
+        book.add("TEST", TimeFrame::Day1, AlertCondition::PercentMove { percent: -5.0 }, "", &candles[..1]).unwrap();
+        assert_eq!(rise.reference_price, Some(100.0));
+
+        assert!(book.evaluate("TEST", TimeFrame::Day1, &candles[..2], "STORE").is_empty());
+        assert!(book.evaluate("TEST", TimeFrame::Day1, &candles[..3], "STORE").is_empty());
+        let triggers = book.evaluate("TEST", TimeFrame::Day1, &candles, "REPLAY");
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].alert.id, rise.id);
+        assert!((triggers[0].price - 105.0).abs() < 1e-9);
+        assert_eq!(triggers[0].source, "REPLAY");
+        // The fall is still waiting
+        assert_eq!(book.alerts(None).len(), 1);
+    }
+
+    #[test]
+    fn test_indicator_threshold_crossing() {
+        // Falling closes push the RSI down, then a rally lifts it back over 50
+        let mut closes: Vec<f64> = (0..20).map(|i| 120.0 - i as f64).collect();
+        closes.extend((0..20).map(|i| 101.0 + 2.0 * i as f64));
+        let candles = series(&closes);
+        let condition = AlertCondition::Indicator { indicator: AlertIndicator::parse("rsi", 0).unwrap(), threshold: 50.0, direction: CrossDirection::Above };
+        let mut book = AlertBook::new();
+        book.add("TEST", TimeFrame::Day1, condition, "oversold bounce", &candles[..20]).unwrap();
+
+        let fired_at = (21..=candles.len())
+            .find(|&end| !book.evaluate("TEST", TimeFrame::Day1, &candles[..end], "STORE").is_empty())
+            .expect("the RSI crosses 50 during the rally");
+        let rsi = AlertIndicator::Rsi(14);
+        assert!(rsi.latest_value(&candles[..fired_at]).unwrap() > 50.0);
+        assert!(rsi.latest_value(&candles[..fired_at - 1]).unwrap() <= 50.0);
+    }
+
+    #[test]
+    fn test_invalid_alerts_are_rejected() {
+        let mut book = AlertBook::new();
+        assert!(book.add("TEST", TimeFrame::Day1, AlertCondition::PriceCross { level: 0.0 }, "", &[]).is_err());
+        assert!(book.add("TEST", TimeFrame::Day1, AlertCondition::PercentMove { percent: 0.0 }, "", &[]).is_err());
+        assert!(book.add("TEST", TimeFrame::Day1, AlertCondition::PercentMove { percent: -100.0 }, "", &[]).is_err());
+        assert!(AlertIndicator::parse("MACD", 12).is_err());
+        assert_eq!(AlertIndicator::parse("sma", 0).unwrap(), AlertIndicator::Sma(20));
+        assert!(book.remove("missing").is_err());
+    }
+}
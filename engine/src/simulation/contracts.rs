@@ -0,0 +1,280 @@
+// Contract specifications for B3 futures, plus tick-size rounding for every symbol.
+// Futures are margin-settled: opening a position does not spend its notional, it
+// blocks margin, and P&L is realized in BRL as points x point value. Symbols
+// without a spec are treated as cash equities (price = currency, full notional paid).
+// The config file's `symbols` section overrides tick sizes and point values: an engine holds
+// its section in a `Contracts`, which the simulator, sizing and backtests look symbols up in.
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use rust_decimal::RoundingStrategy;
+use shared::models::{to_price, Price};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::config::symbols::{self, SymbolSettings};
+use crate::data::calendar::trading_day_on_or_after;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContractSpec {
+    /// Ticker root; matches the generic series (WINFUT) and specific months (WINM25).
+    pub root: &'static str,
+    /// BRL per point per contract.
+    pub point_value: f64,
+    /// Minimum price increment, in points.
+    pub tick_size: f64,
+    /// Margin blocked per contract to open a position (BRL).
+    pub initial_margin: f64,
+    /// Equity per contract below which the account is in margin call (BRL).
+    pub maintenance_margin: f64,
+}
+
+// Approximate exchange margins; brokers usually ask for less on day trades.
+const CONTRACT_SPECS: [ContractSpec; 4] = [
+    ContractSpec { root: "WIN", point_value: 0.20, tick_size: 5.0, initial_margin: 3_000.0, maintenance_margin: 2_400.0 }, // Mini Ibovespa
+    ContractSpec { root: "IND", point_value: 1.00, tick_size: 5.0, initial_margin: 15_000.0, maintenance_margin: 12_000.0 }, // Full Ibovespa
+    ContractSpec { root: "WDO", point_value: 10.00, tick_size: 0.5, initial_margin: 6_000.0, maintenance_margin: 4_800.0 }, // Mini dollar
+    ContractSpec { root: "DOL", point_value: 50.00, tick_size: 0.5, initial_margin: 30_000.0, maintenance_margin: 24_000.0 }, // Full dollar
+];
+
+/// Price increment for cash equities (R$0.01).
+pub const EQUITY_TICK_SIZE: f64 = 0.01;
+
+/// Tick sizes and point values by symbol: the built-in specs, with an engine's `symbols`
+/// section over them. Clones share the section, so a settings reload reaches every holder.
+/// The default has no `symbols` section.
+#[derive(Debug, Clone, Default)]
+pub struct Contracts {
+    symbols: Arc<RwLock<HashMap<String, SymbolSettings>>>,
+}
+
+impl Contracts {
+    pub fn new(symbols: HashMap<String, SymbolSettings>) -> Self {
+        Contracts { symbols: Arc::new(RwLock::new(symbols)) }
+    }
+
+    /// Puts `symbols` into effect for every clone, replacing the previous section.
+    pub fn set_symbols(&self, symbols: HashMap<String, SymbolSettings>) {
+        *self.symbols.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = symbols;
+    }
+
+    fn settings(&self, symbol: &str) -> Option<SymbolSettings> {
+        let symbols = self.symbols.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        symbols::lookup(&symbols, symbol).cloned()
+    }
+
+    /// Spec for a futures symbol, with its tick size and point value as the `symbols` section sets them.
+    pub fn spec(&self, symbol: &str) -> Option<ContractSpec> {
+        spec_with(symbol, self.settings(symbol).as_ref())
+    }
+
+    pub fn tick_size(&self, symbol: &str) -> f64 {
+        tick_size_with(symbol, self.settings(symbol).as_ref())
+    }
+
+    /// Currency value of a one-point move for one share/contract: the contract's, 1 for equities,
+    /// unless the `symbols` section says otherwise.
+    pub fn point_value(&self, symbol: &str) -> f64 {
+        point_value_with(symbol, self.settings(symbol).as_ref())
+    }
+
+    /// Nearest valid price for `symbol`; used for market fills.
+    pub fn round_to_tick(&self, symbol: &str, price: Price) -> Price {
+        let tick = to_price(self.tick_size(symbol));
+        ((price / tick).round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero) * tick).normalize()
+    }
+
+    /// Valid limit price that is never worse than requested: buys round down, sells round up.
+    pub fn round_limit_price(&self, symbol: &str, is_buy: bool, price: Price) -> Price {
+        let tick = to_price(self.tick_size(symbol));
+        let ticks = price / tick;
+        let ticks = if is_buy { ticks.floor() } else { ticks.ceil() };
+        (ticks * tick).normalize()
+    }
+}
+
+fn spec_with(symbol: &str, settings: Option<&SymbolSettings>) -> Option<ContractSpec> {
+    let mut spec = contract_spec(symbol)?;
+    if let Some(settings) = settings {
+        spec.tick_size = settings.tick_size.unwrap_or(spec.tick_size);
+        spec.point_value = settings.point_value.unwrap_or(spec.point_value);
+    }
+    Some(spec)
+}
+
+/// Ticker root of a futures symbol (WIN for WINFUT and WINM25), `None` for equities.
+pub fn futures_root(symbol: &str) -> Option<&'static str> {
+    contract_spec(symbol).map(|spec| spec.root)
+}
+
+/// Built-in spec for a futures symbol, or `None` for symbols traded as cash equities. Its margins
+/// hold whatever the settings say; see `Contracts::spec` for the tick size and point value in effect.
+pub fn contract_spec(symbol: &str) -> Option<ContractSpec> {
+    let symbol = symbol.trim().to_uppercase();
+    CONTRACT_SPECS.iter().copied().find(|spec| {
+        // WINFUT, WINM25, WDOZ24, ... but not an equity that merely starts with the root.
+        symbol.strip_prefix(spec.root)
+            .is_some_and(|suffix| suffix == "FUT" || (suffix.len() == 3 && suffix[1..].chars().all(|c| c.is_ascii_digit())))
+    })
+}
+
+/// B3 month codes, January to December (WINZ24 = December 2024).
+const MONTH_CODES: [char; 12] = ['F', 'G', 'H', 'J', 'K', 'M', 'N', 'Q', 'U', 'V', 'X', 'Z'];
+
+/// Contract month of a specific futures symbol: WINZ24 -> (WIN spec, 2024, 12).
+/// `None` for the generic FUT series and for equities.
+pub fn contract_month(symbol: &str) -> Option<(ContractSpec, i32, u32)> {
+    let spec = contract_spec(symbol)?;
+    let symbol = symbol.trim().to_uppercase();
+    let suffix = &symbol[spec.root.len()..];
+    let code = suffix.chars().next()?;
+    let month = MONTH_CODES.iter().position(|c| *c == code)? as u32 + 1;
+    let year = 2000 + suffix[1..].parse::<i32>().ok()?;
+    Some((spec, year, month))
+}
+
+/// Months with listed contracts: Ibovespa futures expire in even months, dollar futures every month.
+fn is_listed_month(root: &str, month: u32) -> bool {
+    match root {
+        "WIN" | "IND" => month.is_multiple_of(2),
+        _ => true,
+    }
+}
+
+pub fn contract_symbol(root: &str, year: i32, month: u32) -> String {
+    format!("{}{}{:02}", root, MONTH_CODES[(month - 1) as usize], year % 100)
+}
+
+/// Expiration date: the Wednesday closest to the 15th for Ibovespa futures, the first
+/// trading day of the month for dollar futures. An expiry on a holiday moves to the next trading day.
+pub fn expiry_date(root: &str, year: i32, month: u32) -> Option<NaiveDate> {
+    match root {
+        "WIN" | "IND" => {
+            let fifteenth = NaiveDate::from_ymd_opt(year, month, 15)?;
+            let mut offset = Weekday::Wed.num_days_from_monday() as i64 - fifteenth.weekday().num_days_from_monday() as i64;
+            if offset > 3 {
+                offset -= 7;
+            } else if offset < -3 {
+                offset += 7;
+            }
+            Some(trading_day_on_or_after(fifteenth + Duration::days(offset)))
+        }
+        _ => Some(trading_day_on_or_after(NaiveDate::from_ymd_opt(year, month, 1)?)),
+    }
+}
+
+fn next_listed_month(root: &str, year: i32, month: u32) -> (i32, u32) {
+    let (mut year, mut month) = (year, month);
+    loop {
+        if month == 12 {
+            year += 1;
+            month = 1;
+        } else {
+            month += 1;
+        }
+        if is_listed_month(root, month) {
+            return (year, month);
+        }
+    }
+}
+
+/// Contract traded after `symbol` expires: WINZ24 -> WING25, WDOZ24 -> WDOF25.
+pub fn next_contract(symbol: &str) -> Option<String> {
+    let (spec, year, month) = contract_month(symbol)?;
+    let (year, month) = next_listed_month(spec.root, year, month);
+    Some(contract_symbol(spec.root, year, month))
+}
+
+/// Front contract for `root` on `date`: the first listed contract that has not expired yet
+/// (on its expiry date a contract has already been rolled).
+pub fn front_contract(root: &str, date: NaiveDate) -> Option<String> {
+    let (mut year, mut month) = (date.year(), date.month());
+    if !is_listed_month(root, month) {
+        (year, month) = next_listed_month(root, year, month);
+    }
+    while expiry_date(root, year, month)? <= date {
+        (year, month) = next_listed_month(root, year, month);
+    }
+    Some(contract_symbol(root, year, month))
+}
+
+/// Expiration date of a specific contract symbol (WINZ24).
+pub fn contract_expiry(symbol: &str) -> Option<NaiveDate> {
+    let (spec, year, month) = contract_month(symbol)?;
+    expiry_date(spec.root, year, month)
+}
+
+fn tick_size_with(symbol: &str, settings: Option<&SymbolSettings>) -> f64 {
+    let configured = settings.and_then(|settings| settings.tick_size);
+    configured.or_else(|| contract_spec(symbol).map(|c| c.tick_size)).unwrap_or(EQUITY_TICK_SIZE)
+}
+
+fn point_value_with(symbol: &str, settings: Option<&SymbolSettings>) -> f64 {
+    let configured = settings.and_then(|settings| settings.point_value);
+    configured.or_else(|| contract_spec(symbol).map(|c| c.point_value)).unwrap_or(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contract_spec_lookup() {
+        assert_eq!(contract_spec("WINFUT").unwrap().point_value, 0.20);
+        assert_eq!(contract_spec("winm25").unwrap().root, "WIN");
+        assert_eq!(contract_spec("WDOFUT").unwrap().point_value, 10.0);
+        assert!(contract_spec("PETR4").is_none());
+        assert!(contract_spec("WINE3").is_none());
+    }
+
+    #[test]
+    fn test_symbol_settings_override_specs() {
+        let contracts = Contracts::new(HashMap::from([
+            ("DOL".to_string(), SymbolSettings { point_value: Some(25.0), ..Default::default() }),
+            ("PETR4".to_string(), SymbolSettings { tick_size: Some(0.05), point_value: Some(100.0), ..Default::default() }),
+        ]));
+        assert_eq!(contracts.spec("DOLFUT").unwrap().point_value, 25.0);
+        assert_eq!(contracts.spec("DOLFUT").unwrap().tick_size, 0.5);
+        assert_eq!(contracts.point_value("DOLF25"), 25.0);
+        assert_eq!(contracts.tick_size("PETR4"), 0.05);
+        assert_eq!(contracts.point_value("PETR4"), 100.0);
+        assert!(contracts.spec("PETR4").is_none());
+        assert_eq!(contracts.point_value("VALE3"), 1.0);
+        assert_eq!(contracts.round_to_tick("PETR4", Price::new(3_847, 2)), Price::new(3_845, 2));
+
+        // Another engine's settings, and the defaults, are not affected.
+        assert_eq!(Contracts::default().point_value("DOLF25"), 50.0);
+        let shared = contracts.clone();
+        contracts.set_symbols(HashMap::new());
+        assert_eq!(shared.point_value("DOLF25"), 50.0);
+    }
+
+    #[test]
+    fn test_tick_rounding() {
+        let contracts = Contracts::default();
+        assert_eq!(contracts.round_to_tick("WINFUT", Price::from(120_003)), Price::from(120_005));
+        assert_eq!(contracts.round_to_tick("WINFUT", Price::from(120_002)), Price::from(120_000));
+        assert_eq!(contracts.round_to_tick("WDOFUT", Price::new(51_233, 1)), Price::new(51_235, 1));
+        assert_eq!(contracts.round_to_tick("PETR4", Price::new(38_456, 3)), Price::new(3_846, 2));
+        assert_eq!(contracts.round_limit_price("WINFUT", true, Price::from(120_004)), Price::from(120_000));
+        assert_eq!(contracts.round_limit_price("WINFUT", false, Price::from(120_001)), Price::from(120_005));
+        assert_eq!(contracts.round_limit_price("PETR4", true, Price::new(3_845, 2)), Price::new(3_845, 2));
+    }
+
+    #[test]
+    fn test_contract_months_and_rollover() {
+        let (spec, year, month) = contract_month("WINZ24").unwrap();
+        assert_eq!((spec.root, year, month), ("WIN", 2024, 12));
+        assert!(contract_month("WINFUT").is_none());
+        assert_eq!(next_contract("WINZ24").unwrap(), "WING25");
+        assert_eq!(next_contract("WDOZ24").unwrap(), "WDOF25");
+        assert_eq!(next_contract("INDQ25").unwrap(), "INDV25");
+
+        // 15 Dec 2024 is a Sunday: the closest Wednesday is the 18th.
+        assert_eq!(contract_expiry("WINZ24").unwrap(), NaiveDate::from_ymd_opt(2024, 12, 18).unwrap());
+        // 1 Feb 2025 is a Saturday: WDOG25 expires on Monday the 3rd.
+        assert_eq!(contract_expiry("WDOG25").unwrap(), NaiveDate::from_ymd_opt(2025, 2, 3).unwrap());
+
+        assert_eq!(front_contract("WIN", NaiveDate::from_ymd_opt(2024, 11, 20).unwrap()).unwrap(), "WINZ24");
+        assert_eq!(front_contract("WIN", NaiveDate::from_ymd_opt(2024, 12, 18).unwrap()).unwrap(), "WING25");
+        assert_eq!(front_contract("WDO", NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()).unwrap(), "WDOG25");
+    }
+}
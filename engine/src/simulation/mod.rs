@@ -0,0 +1,24 @@
+// Trading simulation module
+// Holds the engine-side state that outlives a single SimulateTrade call,
+// such as resting limit orders that are re-evaluated as new candles arrive,
+// replay sessions that reveal stored candles in simulated time, price alerts
+// checked against the candles that arrive, and the paper-trading account that
+// is persisted between engine restarts.
+pub mod account;
+pub mod alerts;
+pub mod contracts;
+pub mod orders;
+pub mod portfolio;
+pub mod replay;
+pub mod risk;
+pub mod sizing;
+
+pub use account::{AccountSnapshot, AccountStore};
+pub use alerts::{AlertBook, AlertCondition, AlertIndicator, AlertTrigger, CrossDirection, PriceAlert};
+pub use orders::OrderBook;
+pub use contracts::{contract_expiry, contract_spec, front_contract, next_contract, ContractSpec, Contracts};
+pub use portfolio::{CashTransfer, Execution, MarginStatus, Portfolio};
+pub use replay::{ReplayController, ReplaySnapshot, ReplayState};
+pub use risk::{RiskLimits, RiskViolation};
+pub use sizing::{position_size, SizingInput, SizingMethod, SizingResult};
+pub use shared::models::{Fill, Order, OrderSide, OrderStatus, Position, TimeInForce};
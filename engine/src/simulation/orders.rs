@@ -0,0 +1,326 @@
+// Simulated order book for resting (working) limit orders.
+// A limit order that does not fill against the latest candle is kept here and
+// re-evaluated against every later candle for its symbol until it fills or its
+// time-in-force expires. Orders that filled, expired or were cancelled leave the book for a
+// history of the most recent `MAX_CLOSED_ORDERS`, so a long-lived account does not grow without bound.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shared::models::{Candle, Fill, Order, OrderStatus, Price};
+use std::collections::{HashMap, VecDeque};
+
+use crate::error::EngineError;
+
+const MAX_CLOSED_ORDERS: usize = 100;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrderBook {
+    orders: HashMap<String, Order>, // Working orders only
+    #[serde(default)]
+    closed: VecDeque<Order>, // Oldest first
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a working order to the book.
+    pub fn place(&mut self, order: Order) {
+        tracing::debug!(order_id = %order.id, symbol = %order.symbol, tif = order.time_in_force.as_str(), "Resting limit order in book");
+        self.orders.insert(order.id.clone(), order);
+    }
+
+    /// A working order, or one of the recently closed ones.
+    pub fn get(&self, order_id: &str) -> Option<&Order> {
+        self.orders.get(order_id).or_else(|| self.closed.iter().rev().find(|o| o.id == order_id))
+    }
+
+    fn working_order_mut(&mut self, order_id: &str) -> Result<&mut Order, EngineError> {
+        if let Some(closed) = self.closed.iter().rev().find(|o| o.id == order_id) {
+            return Err(EngineError::InvalidRequest(format!(
+                "Order '{}' is {} and can no longer be changed", order_id, closed.status.as_str()
+            )));
+        }
+        self.orders.get_mut(order_id)
+            .ok_or_else(|| EngineError::SimulationError(format!("Order '{}' not found", order_id)))
+    }
+
+    /// Moves a closed order from the book to the history, dropping the oldest beyond `MAX_CLOSED_ORDERS`.
+    fn close(&mut self, order_id: &str) -> Option<Order> {
+        let order = self.orders.remove(order_id)?;
+        self.closed.push_back(order.clone());
+        while self.closed.len() > MAX_CLOSED_ORDERS {
+            self.closed.pop_front();
+        }
+        Some(order)
+    }
+
+    /// Moves orders that are no longer working into the history, e.g. after loading an account
+    /// saved when closed orders were kept in the book.
+    pub fn retire_closed_orders(&mut self) {
+        let mut closed: Vec<(DateTime<Utc>, String)> = self.orders.values()
+            .filter(|o| o.status != OrderStatus::Working)
+            .map(|o| (o.placed_at, o.id.clone()))
+            .collect();
+        closed.sort();
+        for (_, id) in closed {
+            self.close(&id);
+        }
+    }
+
+    /// Cancels a working order and returns its final state.
+    pub fn cancel(&mut self, order_id: &str) -> Result<Order, EngineError> {
+        let order = self.working_order_mut(order_id)?;
+        order.status = OrderStatus::Cancelled;
+        tracing::info!(order_id = %order.id, symbol = %order.symbol, "Working order cancelled");
+        Ok(self.close(order_id).expect("the order was just found in the book"))
+    }
+
+    /// Changes the limit price and/or quantity of a working order.
+    /// The order keeps its placement time, so it is evaluated from the next candle on.
+    pub fn modify(&mut self, order_id: &str, limit_price: Option<Price>, quantity: Option<f64>) -> Result<Order, EngineError> {
+        if limit_price.is_some_and(|p| p <= Price::ZERO) || quantity.is_some_and(|q| !q.is_finite() || q <= 0.0) {
+            return Err(EngineError::InvalidRequest("Modified limit price and quantity must be positive".to_string()));
+        }
+        let order = self.working_order_mut(order_id)?;
+        if let Some(price) = limit_price {
+            order.limit_price = price;
+        }
+        if let Some(quantity) = quantity {
+            order.quantity = quantity;
+        }
+        tracing::info!(order_id = %order.id, symbol = %order.symbol, limit_price = %order.limit_price, quantity = order.quantity, "Working order modified");
+        Ok(order.clone())
+    }
+
+    /// Fills a working order with the market at `market_price`, e.g. when a modification made it marketable.
+    pub fn fill(&mut self, order_id: &str, market_price: Price, timestamp: DateTime<Utc>) -> Result<Fill, EngineError> {
+        let order = self.working_order_mut(order_id)?;
+        let fill = Self::fill_order(order, market_price, timestamp);
+        self.close(order_id);
+        Ok(fill)
+    }
+
+    fn fill_order(order: &mut Order, market_price: Price, timestamp: DateTime<Utc>) -> Fill {
+        let price = order.fill_price(market_price);
+        order.status = OrderStatus::Filled;
+        order.filled_price = Some(price);
+        order.filled_at = Some(timestamp);
+        tracing::info!(order_id = %order.id, symbol = %order.symbol, %price, "Working order filled");
+        Fill {
+            order_id: order.id.clone(),
+            symbol: order.symbol.clone(),
+            side: order.side,
+            price,
+            quantity: order.quantity,
+            timestamp,
+        }
+    }
+
+    /// Working orders, optionally restricted to a symbol, oldest first.
+    pub fn working_orders(&self, symbol: Option<&str>) -> Vec<&Order> {
+        let mut working: Vec<&Order> = self.orders.values()
+            .filter(|o| symbol.is_none_or(|s| o.symbol == s))
+            .collect();
+        working.sort_by(|a, b| (a.placed_at, &a.id).cmp(&(b.placed_at, &b.id)));
+        working
+    }
+
+    /// Evaluates every working order for `symbol` against one candle.
+    /// Only candles strictly after an order's placement time are considered,
+    /// so an order is never filled by the bar it was already checked against. A candle that
+    /// opens through the limit fills the order at the open.
+    /// Orders triggered by the same candle fill oldest first (ties by id), so a replay always
+    /// takes the same cash and position path.
+    pub fn process_candle(&mut self, symbol: &str, candle: &Candle) -> Vec<Fill> {
+        let mut pending: Vec<&mut Order> = self.orders.values_mut()
+            .filter(|o| o.symbol == symbol && candle.timestamp > o.placed_at)
+            .collect();
+        pending.sort_by(|a, b| (a.placed_at, &a.id).cmp(&(b.placed_at, &b.id)));
+
+        let mut fills = Vec::new();
+        let mut closed = Vec::new();
+        for order in pending {
+            if order.time_in_force.is_expired(order.placed_at, candle.timestamp) {
+                order.status = OrderStatus::Expired;
+                tracing::info!(order_id = %order.id, symbol = %order.symbol, tif = order.time_in_force.as_str(), "Working order expired");
+                closed.push(order.id.clone());
+                continue;
+            }
+            if order.is_marketable(candle) {
+                fills.push(Self::fill_order(order, candle.open, candle.timestamp));
+                closed.push(order.id.clone());
+            }
+        }
+        for order_id in closed {
+            self.close(&order_id);
+        }
+        fills
+    }
+
+    /// Evaluates working orders against a batch of candles in chronological order.
+    pub fn process_candles(&mut self, symbol: &str, candles: &[Candle]) -> Vec<Fill> {
+        let mut sorted: Vec<&Candle> = candles.iter().collect();
+        sorted.sort_by_key(|c| c.timestamp);
+        sorted.into_iter().flat_map(|c| self.process_candle(symbol, c)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::{to_price, OrderSide, TimeInForce};
+    use chrono::{Duration, TimeZone};
+
+    fn candle_at(ts: DateTime<Utc>, low: f64, high: f64) -> Candle {
+        Candle { symbol: "TEST".to_string(), timestamp: ts, open: to_price((low + high) / 2.0), high: to_price(high), low: to_price(low), close: to_price(high), volume: 0.0, trades: 0 }
+    }
+
+    fn limit_order(id: &str, side: OrderSide, price: f64, tif: TimeInForce, placed_at: DateTime<Utc>) -> Order {
+        Order {
+            id: id.to_string(),
+            symbol: "TEST".to_string(),
+            side,
+            quantity: 1.0,
+            limit_price: to_price(price),
+            time_in_force: tif,
+            placed_at,
+            status: OrderStatus::Working,
+            filled_price: None,
+            filled_at: None,
+        }
+    }
+
+    #[test]
+    fn test_gtc_order_fills_on_later_candle() {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let mut book = OrderBook::new();
+        book.place(limit_order("o1", OrderSide::Buy, 95.0, TimeInForce::Gtc, t0));
+
+        // Same timestamp as placement: ignored.
+        assert!(book.process_candle("TEST", &candle_at(t0, 90.0, 100.0)).is_empty());
+        // Later candle that does not reach the limit.
+        assert!(book.process_candle("TEST", &candle_at(t0 + Duration::days(1), 96.0, 100.0)).is_empty());
+        // Later candle that trades through the limit.
+        let fills = book.process_candle("TEST", &candle_at(t0 + Duration::days(2), 94.0, 99.0));
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].price, Price::from(95));
+        assert_eq!(book.get("o1").unwrap().status, OrderStatus::Filled);
+        assert!(book.working_orders(None).is_empty());
+    }
+
+    #[test]
+    fn test_orders_gapped_through_fill_at_the_open() {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let mut book = OrderBook::new();
+        book.place(limit_order("buy", OrderSide::Buy, 95.0, TimeInForce::Gtc, t0));
+        book.place(limit_order("sell", OrderSide::Sell, 105.0, TimeInForce::Gtc, t0));
+
+        // Opens at 92, below the buy limit.
+        let gap_down = Candle { open: Price::from(92), ..candle_at(t0 + Duration::days(1), 90.0, 96.0) };
+        let fills = book.process_candle("TEST", &gap_down);
+        assert_eq!((fills[0].order_id.as_str(), fills[0].price), ("buy", Price::from(92)));
+        assert_eq!(book.get("buy").unwrap().filled_price, Some(Price::from(92)));
+
+        // Opens at 108, above the sell limit.
+        let gap_up = Candle { open: Price::from(108), ..candle_at(t0 + Duration::days(2), 104.0, 110.0) };
+        let fills = book.process_candle("TEST", &gap_up);
+        assert_eq!((fills[0].order_id.as_str(), fills[0].price), ("sell", Price::from(108)));
+    }
+
+    #[test]
+    fn test_day_order_expires_next_day() {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let mut book = OrderBook::new();
+        book.place(limit_order("o1", OrderSide::Sell, 110.0, TimeInForce::Day, t0));
+
+        assert!(book.process_candle("TEST", &candle_at(t0 + Duration::hours(1), 100.0, 105.0)).is_empty());
+        assert_eq!(book.get("o1").unwrap().status, OrderStatus::Working);
+        // Next day: expires before being evaluated, even though the high reaches the limit.
+        assert!(book.process_candle("TEST", &candle_at(t0 + Duration::days(1), 100.0, 120.0)).is_empty());
+        assert_eq!(book.get("o1").unwrap().status, OrderStatus::Expired);
+    }
+
+    #[test]
+    fn test_gtd_order_expires_after_date() {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let mut book = OrderBook::new();
+        book.place(limit_order("o1", OrderSide::Buy, 90.0, TimeInForce::Gtd(t0 + Duration::days(2)), t0));
+
+        let candles = vec![
+            candle_at(t0 + Duration::days(3), 80.0, 100.0),
+            candle_at(t0 + Duration::days(1), 95.0, 100.0),
+        ];
+        // Processed chronologically: day 1 does not fill, day 3 is past the expiry.
+        assert!(book.process_candles("TEST", &candles).is_empty());
+        assert_eq!(book.get("o1").unwrap().status, OrderStatus::Expired);
+    }
+
+    #[test]
+    fn test_cancel_and_modify() {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let mut book = OrderBook::new();
+        book.place(limit_order("o1", OrderSide::Buy, 90.0, TimeInForce::Gtc, t0));
+        book.place(limit_order("o2", OrderSide::Buy, 90.0, TimeInForce::Gtc, t0));
+
+        let modified = book.modify("o1", Some(Price::from(95)), Some(3.0)).unwrap();
+        assert_eq!((modified.limit_price, modified.quantity), (Price::from(95), 3.0));
+        assert!(book.modify("o1", Some(Price::from(-1)), None).is_err());
+        assert_eq!(book.cancel("o2").unwrap().status, OrderStatus::Cancelled);
+        assert!(book.cancel("o2").is_err()); // Already cancelled
+        assert!(book.cancel("missing").is_err());
+
+        // Only the modified order is left to fill.
+        let fills = book.process_candle("TEST", &candle_at(t0 + Duration::days(1), 94.0, 99.0));
+        assert_eq!(fills.len(), 1);
+        assert_eq!((fills[0].order_id.as_str(), fills[0].quantity), ("o1", 3.0));
+    }
+
+    #[test]
+    fn test_orders_triggered_together_fill_oldest_first() {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let mut book = OrderBook::new();
+        for (id, placed_at) in [("o9", t0), ("o3", t0 + Duration::minutes(5)), ("o1", t0 + Duration::minutes(5)), ("o2", t0 + Duration::minutes(1))] {
+            book.place(limit_order(id, OrderSide::Buy, 95.0, TimeInForce::Gtc, placed_at));
+        }
+        let fills = book.process_candle("TEST", &candle_at(t0 + Duration::days(1), 90.0, 99.0));
+        let order_ids: Vec<&str> = fills.iter().map(|f| f.order_id.as_str()).collect();
+        assert_eq!(order_ids, ["o9", "o2", "o1", "o3"]);
+    }
+
+    #[test]
+    fn test_closed_orders_leave_the_book_for_a_capped_history() {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let mut book = OrderBook::new();
+        for i in 0..MAX_CLOSED_ORDERS + 5 {
+            book.place(limit_order(&format!("o{}", i), OrderSide::Buy, 90.0, TimeInForce::Gtc, t0 + Duration::minutes(i as i64)));
+            book.cancel(&format!("o{}", i)).unwrap();
+        }
+        book.place(limit_order("working", OrderSide::Buy, 90.0, TimeInForce::Gtc, t0));
+        assert_eq!(book.orders.len(), 1);
+        assert_eq!(book.closed.len(), MAX_CLOSED_ORDERS);
+        assert!(book.get("o0").is_none()); // Dropped as the oldest
+        let last = format!("o{}", MAX_CLOSED_ORDERS + 4);
+        assert_eq!(book.get(&last).unwrap().status, OrderStatus::Cancelled);
+        assert!(book.modify(&last, None, Some(2.0)).unwrap_err().to_string().contains("CANCELLED"));
+
+        // Books saved before closed orders had a history keep them with the working ones.
+        let mut old = OrderBook::new();
+        old.place(limit_order("filled", OrderSide::Buy, 90.0, TimeInForce::Gtc, t0));
+        old.orders.get_mut("filled").unwrap().status = OrderStatus::Filled;
+        old.place(limit_order("working", OrderSide::Buy, 90.0, TimeInForce::Gtc, t0));
+        old.retire_closed_orders();
+        assert_eq!(old.working_orders(None).len(), 1);
+        assert_eq!(old.get("filled").unwrap().status, OrderStatus::Filled);
+    }
+
+    #[test]
+    fn test_orders_for_other_symbols_are_ignored() {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let mut book = OrderBook::new();
+        book.place(limit_order("o1", OrderSide::Buy, 95.0, TimeInForce::Gtc, t0));
+        assert!(book.process_candle("OTHER", &candle_at(t0 + Duration::days(1), 80.0, 100.0)).is_empty());
+        assert_eq!(book.working_orders(Some("TEST")).len(), 1);
+        assert!(book.working_orders(Some("OTHER")).is_empty());
+    }
+}
@@ -0,0 +1,510 @@
+// Simulated portfolio: cash, per-symbol positions and the journal of fills that produced them.
+// Positions use average-cost accounting; quantities are signed (negative = short).
+// Futures (symbols with a contract spec) are margin-settled: only realized P&L moves
+// cash, open positions block margin and are valued at their unrealized P&L.
+// Cash, average prices and realized P&L are exact decimals; marks and margins are floats.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shared::models::{to_f64, to_price, Price};
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+
+use super::contracts::{contract_spec, Contracts};
+use super::{Fill, OrderSide, Position};
+
+pub const DEFAULT_INITIAL_CAPITAL: f64 = 100_000.0;
+pub const DEFAULT_BASE_CURRENCY: &str = "BRL";
+
+/// Executions a live subscriber may fall behind by before it misses some.
+const EXECUTION_FEED_CAPACITY: usize = 256;
+
+/// What `position` adds to account equity at `mark_price`: the unrealized P&L for futures,
+/// the market value for equities.
+fn market_value(position: &Position, mark_price: f64) -> f64 {
+    match contract_spec(&position.symbol) {
+        Some(_) => position.unrealized_pnl(mark_price),
+        None => position.quantity * mark_price,
+    }
+}
+
+fn initial_margin(position: &Position) -> f64 {
+    contract_spec(&position.symbol).map_or(0.0, |c| c.initial_margin * position.quantity.abs())
+}
+
+fn maintenance_margin(position: &Position) -> f64 {
+    contract_spec(&position.symbol).map_or(0.0, |c| c.maintenance_margin * position.quantity.abs())
+}
+
+/// A booked fill together with the position it left behind (the trade blotter).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Execution {
+    pub fill: Fill,
+    pub position_after: f64, // Signed quantity after the fill
+    pub average_price_after: Price,
+    pub realized_pnl: Price, // Realized by this fill, in currency
+}
+
+/// Account margin snapshot at given mark prices.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MarginStatus {
+    pub equity: f64,
+    pub initial_margin: f64,
+    pub maintenance_margin: f64,
+    /// Equity not committed as initial margin (for equities, also not spent on shares).
+    pub buying_power: f64,
+    /// Equity has fallen below the maintenance requirement of the open futures positions.
+    pub margin_call: bool,
+}
+
+/// Cash moved into (positive) or out of (negative) the account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CashTransfer {
+    pub timestamp: DateTime<Utc>,
+    pub amount: Price,
+    pub cash_after: Price,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub initial_capital: f64,
+    pub cash: Price,
+    /// ISO 4217 code of the account; prices are assumed to be quoted in it, nothing is converted.
+    #[serde(default = "default_base_currency")]
+    pub base_currency: String,
+    positions: HashMap<String, Position>,
+    executions: Vec<Execution>,
+    #[serde(default)] // Accounts saved before deposits existed have none
+    transfers: Vec<CashTransfer>,
+    /// Executions as they are booked, for live subscribers; not persisted.
+    #[serde(skip, default = "execution_feed")]
+    feed: broadcast::Sender<Execution>,
+    /// Point values of the engine the account belongs to; not persisted.
+    #[serde(skip)]
+    contracts: Contracts,
+}
+
+fn default_base_currency() -> String {
+    DEFAULT_BASE_CURRENCY.to_string()
+}
+
+fn execution_feed() -> broadcast::Sender<Execution> {
+    broadcast::channel(EXECUTION_FEED_CAPACITY).0
+}
+
+impl Default for Portfolio {
+    fn default() -> Self {
+        Self::new(DEFAULT_INITIAL_CAPITAL)
+    }
+}
+
+impl Portfolio {
+    pub fn new(initial_capital: f64) -> Self {
+        Self {
+            initial_capital,
+            cash: to_price(initial_capital),
+            base_currency: default_base_currency(),
+            positions: HashMap::new(),
+            executions: Vec::new(),
+            transfers: Vec::new(),
+            feed: execution_feed(),
+            contracts: Contracts::default(),
+        }
+    }
+
+    /// Adds `amount` to the cash balance.
+    pub fn deposit(&mut self, amount: f64, timestamp: DateTime<Utc>) -> Result<(), String> {
+        validate_transfer(amount)?;
+        self.record_transfer(to_price(amount), timestamp);
+        Ok(())
+    }
+
+    /// Takes `amount` out of the cash balance. Only free buying power can leave the
+    /// account: cash backing open equity positions or futures margin stays.
+    pub fn withdraw(&mut self, amount: f64, marks: &HashMap<String, f64>, timestamp: DateTime<Utc>) -> Result<(), String> {
+        validate_transfer(amount)?;
+        let available = self.margin_status(marks).buying_power.min(to_f64(self.cash)).max(0.0);
+        if amount > available {
+            return Err(format!("Cannot withdraw {:.2} {}: only {:.2} is free", amount, self.base_currency, available));
+        }
+        self.record_transfer(-to_price(amount), timestamp);
+        Ok(())
+    }
+
+    fn record_transfer(&mut self, amount: Price, timestamp: DateTime<Utc>) {
+        self.cash += amount;
+        tracing::info!(%amount, cash = %self.cash, currency = %self.base_currency, "Cash transferred");
+        self.transfers.push(CashTransfer { timestamp, amount, cash_after: self.cash });
+    }
+
+    pub fn transfers(&self) -> &[CashTransfer] {
+        &self.transfers
+    }
+
+    /// Deposits minus withdrawals since the account was opened.
+    pub fn net_deposits(&self) -> Price {
+        self.transfers.iter().map(|t| t.amount).sum()
+    }
+
+    /// Relabels the account's currency. Refused while positions are open, since their
+    /// prices would silently change meaning.
+    pub fn set_base_currency(&mut self, code: &str) -> Result<(), String> {
+        let code = code.trim().to_uppercase();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(format!("Invalid currency code '{}': expected three letters such as BRL or USD", code));
+        }
+        if self.positions.values().any(|p| !p.is_flat()) {
+            return Err("Close all positions before changing the account currency".to_string());
+        }
+        self.base_currency = code;
+        Ok(())
+    }
+
+    /// Re-attaches the point values, which are not persisted, after loading a saved account.
+    pub fn relink_contracts(&mut self) {
+        for position in self.positions.values_mut() {
+            position.point_value = self.contracts.point_value(&position.symbol);
+        }
+    }
+
+    /// Looks point values up in `contracts` from now on, including those of the open positions.
+    pub fn set_contracts(&mut self, contracts: Contracts) {
+        self.contracts = contracts;
+        self.relink_contracts();
+    }
+
+    pub fn contracts(&self) -> &Contracts {
+        &self.contracts
+    }
+
+    /// Books a fill: moves cash, updates the position and records the execution in the journal.
+    pub fn apply_fill(&mut self, fill: Fill) -> Price {
+        let position = self.positions.entry(fill.symbol.clone()).or_insert_with(|| Position::new(&fill.symbol, self.contracts.point_value(&fill.symbol)));
+        let realized = position.apply(fill.side, fill.quantity, fill.price);
+        if contract_spec(&fill.symbol).is_some() {
+            self.cash += realized; // Futures settle P&L only
+        } else {
+            let notional = to_price(fill.quantity) * fill.price;
+            self.cash -= match fill.side {
+                OrderSide::Buy => notional,
+                OrderSide::Sell => -notional,
+            };
+        }
+        tracing::debug!(order_id = %fill.order_id, symbol = %fill.symbol, side = fill.side.as_str(), price = %fill.price, quantity = fill.quantity, %realized, "Fill applied to portfolio");
+        let execution = Execution {
+            position_after: position.quantity,
+            average_price_after: position.average_price,
+            realized_pnl: realized,
+            fill,
+        };
+        // An error only means nobody is subscribed right now.
+        let _ = self.feed.send(execution.clone());
+        self.executions.push(execution);
+        realized
+    }
+
+    pub fn apply_fills(&mut self, fills: Vec<Fill>) {
+        for fill in fills {
+            self.apply_fill(fill);
+        }
+    }
+
+    pub fn position(&self, symbol: &str) -> Option<&Position> {
+        self.positions.get(symbol)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = &Position> {
+        self.positions.values()
+    }
+
+    pub fn fills(&self) -> impl Iterator<Item = &Fill> {
+        self.executions.iter().map(|e| &e.fill)
+    }
+
+    pub fn executions(&self) -> &[Execution] {
+        &self.executions
+    }
+
+    /// Executions booked from now on. The feed belongs to this portfolio: when the account
+    /// is reset or replaced, the receiver is closed and must subscribe to the new one.
+    pub fn subscribe_executions(&self) -> broadcast::Receiver<Execution> {
+        self.feed.subscribe()
+    }
+
+    /// Executions for a symbol (or all) within an inclusive time range, oldest first.
+    pub fn history(&self, symbol: Option<&str>, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Vec<&Execution> {
+        let mut history: Vec<&Execution> = self.executions.iter()
+            .filter(|e| symbol.is_none_or(|s| e.fill.symbol == s))
+            .filter(|e| from.is_none_or(|f| e.fill.timestamp >= f))
+            .filter(|e| to.is_none_or(|t| e.fill.timestamp <= t))
+            .collect();
+        history.sort_by_key(|e| e.fill.timestamp); // Stable: same-instant fills keep booking order
+        history
+    }
+
+    pub fn realized_pnl(&self) -> Price {
+        self.positions.values().map(|p| p.realized_pnl).sum()
+    }
+
+    /// Cash plus the value of open positions, marked at the given prices.
+    /// Positions without a mark are valued at their average price.
+    pub fn equity(&self, marks: &HashMap<String, f64>) -> f64 {
+        to_f64(self.cash) + self.positions.values()
+            .map(|p| market_value(p, marks.get(&p.symbol).copied().unwrap_or(to_f64(p.average_price))))
+            .sum::<f64>()
+    }
+
+    pub fn margin_status(&self, marks: &HashMap<String, f64>) -> MarginStatus {
+        let equity = self.equity(marks);
+        let initial_margin: f64 = self.positions.values().map(initial_margin).sum();
+        let maintenance_margin: f64 = self.positions.values().map(maintenance_margin).sum();
+        // Shares already bought are not available to buy more, and shares sold short must stay
+        // covered by collateral: only the rest of the equity is free.
+        let equity_positions: f64 = self.positions.values()
+            .filter(|p| contract_spec(&p.symbol).is_none())
+            .map(|p| market_value(p, marks.get(&p.symbol).copied().unwrap_or(to_f64(p.average_price))).abs())
+            .sum();
+        MarginStatus {
+            equity,
+            initial_margin,
+            maintenance_margin,
+            buying_power: equity - equity_positions - initial_margin,
+            margin_call: maintenance_margin > 0.0 && equity < maintenance_margin,
+        }
+    }
+
+    /// Margin status after a price update; logs when the account is in margin call.
+    pub fn check_margin(&self, marks: &HashMap<String, f64>) -> MarginStatus {
+        let status = self.margin_status(marks);
+        if status.margin_call {
+            tracing::warn!(equity = status.equity, maintenance_margin = status.maintenance_margin, "Simulated account is in margin call");
+        }
+        status
+    }
+
+    /// Checks whether the account can take a new order. Only orders that increase
+    /// exposure are checked: futures need free initial margin, equities need free
+    /// equity for the shares bought or sold short.
+    pub fn check_buying_power(&self, symbol: &str, side: OrderSide, quantity: f64, price: f64, marks: &HashMap<String, f64>) -> Result<(), String> {
+        let current = self.positions.get(symbol).map(|p| p.quantity).unwrap_or(0.0);
+        let signed_qty = match side {
+            OrderSide::Buy => quantity,
+            OrderSide::Sell => -quantity,
+        };
+        let added_exposure = (current + signed_qty).abs() - current.abs();
+        if added_exposure <= 0.0 {
+            return Ok(());
+        }
+        let required = match contract_spec(symbol) {
+            Some(spec) => added_exposure * spec.initial_margin,
+            None => added_exposure * price,
+        };
+        let available = self.margin_status(marks).buying_power;
+        if required > available {
+            Err(format!(
+                "Insufficient buying power: order requires {:.2} but only {:.2} is available.",
+                required, available
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn validate_transfer(amount: f64) -> Result<(), String> {
+    if amount.is_finite() && amount > 0.0 {
+        Ok(())
+    } else {
+        Err(format!("Transfer amount must be a positive number, got {}", amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn fill(side: OrderSide, quantity: f64, price: f64) -> Fill {
+        Fill { order_id: "o".to_string(), symbol: "TEST".to_string(), side, price: to_price(price), quantity, timestamp: Utc::now() }
+    }
+
+    #[test]
+    fn test_long_round_trip() {
+        let mut portfolio = Portfolio::new(1_000.0);
+        portfolio.apply_fill(fill(OrderSide::Buy, 10.0, 10.0));
+        portfolio.apply_fill(fill(OrderSide::Buy, 10.0, 12.0));
+        let position = portfolio.position("TEST").unwrap();
+        assert_eq!(position.quantity, 20.0);
+        assert_eq!(position.average_price, Price::from(11));
+
+        let realized = portfolio.apply_fill(fill(OrderSide::Sell, 20.0, 13.0));
+        assert_eq!(realized, Price::from(40));
+        assert!(portfolio.position("TEST").unwrap().is_flat());
+        assert_eq!(portfolio.cash, Price::from(1_040));
+        assert_eq!(portfolio.executions().len(), 3);
+        let last = portfolio.executions().last().unwrap();
+        assert_eq!(last.position_after, 0.0);
+        assert_eq!(last.realized_pnl, Price::from(40));
+        assert_eq!(portfolio.history(Some("TEST"), None, None).len(), 3);
+        assert!(portfolio.history(Some("OTHER"), None, None).is_empty());
+    }
+
+    #[test]
+    fn test_futures_are_margin_settled() {
+        let mut portfolio = Portfolio::new(10_000.0);
+        let win = |side, quantity, price| Fill { symbol: "WINFUT".to_string(), ..fill(side, quantity, price) };
+        portfolio.apply_fill(win(OrderSide::Buy, 2.0, 120_000.0));
+        assert_eq!(portfolio.cash, Price::from(10_000)); // No notional paid
+
+        // 500 points x R$0.20 x 2 contracts against us.
+        let marks = HashMap::from([("WINFUT".to_string(), 119_500.0)]);
+        let status = portfolio.margin_status(&marks);
+        assert!((status.equity - 9_800.0).abs() < 1e-9);
+        assert_eq!(status.initial_margin, 6_000.0);
+        assert!((status.buying_power - 3_800.0).abs() < 1e-9);
+        assert!(!status.margin_call);
+        // A third and fourth contract would need 6000 of free margin.
+        assert!(portfolio.check_buying_power("WINFUT", OrderSide::Buy, 2.0, 119_500.0, &marks).is_err());
+        assert!(portfolio.check_buying_power("WINFUT", OrderSide::Sell, 2.0, 119_500.0, &marks).is_ok());
+
+        // A 14,000-point drop costs 5600: equity 4400 is below the 4800 maintenance requirement.
+        let crash = HashMap::from([("WINFUT".to_string(), 106_000.0)]);
+        assert!(portfolio.margin_status(&crash).margin_call);
+
+        let realized = portfolio.apply_fill(win(OrderSide::Sell, 2.0, 121_000.0));
+        assert_eq!(realized, Price::from(400));
+        assert_eq!(portfolio.cash, Price::from(10_400));
+    }
+
+    #[test]
+    fn test_equity_marks_open_positions() {
+        let mut portfolio = Portfolio::new(1_000.0);
+        portfolio.apply_fill(fill(OrderSide::Buy, 10.0, 10.0));
+        let marks = HashMap::from([("TEST".to_string(), 15.0)]);
+        assert!((portfolio.equity(&marks) - 1_050.0).abs() < 1e-9);
+        assert!((portfolio.position("TEST").unwrap().unrealized_pnl(15.0) - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_short_round_trip() {
+        let mut portfolio = Portfolio::new(1_000.0);
+        portfolio.apply_fill(fill(OrderSide::Sell, 10.0, 20.0));
+        let position = portfolio.position("TEST").unwrap();
+        assert_eq!(position.quantity, -10.0);
+        assert_eq!(position.average_price, Price::from(20));
+        assert_eq!(portfolio.cash, Price::from(1_200)); // Short sale proceeds
+        // The short gains when the price falls.
+        assert!((position.unrealized_pnl(18.0) - 20.0).abs() < 1e-9);
+        let marks = HashMap::from([("TEST".to_string(), 18.0)]);
+        assert!((portfolio.equity(&marks) - 1_020.0).abs() < 1e-9);
+
+        // Adding to the short blends the average price.
+        portfolio.apply_fill(fill(OrderSide::Sell, 10.0, 22.0));
+        assert_eq!(portfolio.position("TEST").unwrap().average_price, Price::from(21));
+
+        // Partial cover at a loss, then full cover at a gain.
+        let realized = portfolio.apply_fill(fill(OrderSide::Buy, 5.0, 23.0));
+        assert_eq!(realized, Price::from(-10));
+        assert_eq!(portfolio.position("TEST").unwrap().quantity, -15.0);
+        let realized = portfolio.apply_fill(fill(OrderSide::Buy, 15.0, 19.0));
+        assert_eq!(realized, Price::from(30));
+        assert!(portfolio.position("TEST").unwrap().is_flat());
+        assert_eq!(portfolio.cash, Price::from(1_020));
+        assert_eq!(portfolio.realized_pnl(), Price::from(20));
+    }
+
+    #[test]
+    fn test_fill_larger_than_position_flips_it() {
+        let mut portfolio = Portfolio::new(1_000.0);
+        portfolio.apply_fill(fill(OrderSide::Buy, 10.0, 10.0));
+        // Sells 10 to close at +20 and opens a 5-share short at 12.
+        let realized = portfolio.apply_fill(fill(OrderSide::Sell, 15.0, 12.0));
+        assert_eq!(realized, Price::from(20));
+        let position = portfolio.position("TEST").unwrap();
+        assert_eq!(position.quantity, -5.0);
+        assert_eq!(position.average_price, Price::from(12));
+        let last = portfolio.executions().last().unwrap();
+        assert_eq!(last.position_after, -5.0);
+
+        // And back: buys 5 to cover at +10 and opens a 3-share long at 10.
+        let realized = portfolio.apply_fill(fill(OrderSide::Buy, 8.0, 10.0));
+        assert_eq!(realized, Price::from(10));
+        let position = portfolio.position("TEST").unwrap();
+        assert_eq!(position.quantity, 3.0);
+        assert_eq!(position.average_price, Price::from(10));
+        assert_eq!(portfolio.cash, Price::from(1_000)); // 1000 + 30 realized - 30 held in the 3 shares
+    }
+
+    #[test]
+    fn test_prices_add_up_exactly() {
+        // In f64, 10 x (10.3 - 10.1) is 2.0000000000000107
+        let mut portfolio = Portfolio::new(1_000.0);
+        portfolio.apply_fill(fill(OrderSide::Buy, 10.0, 10.1));
+        let realized = portfolio.apply_fill(fill(OrderSide::Sell, 10.0, 10.3));
+        assert_eq!(realized, Price::from(2));
+        assert_eq!(portfolio.cash, Price::from(1_002));
+    }
+
+    #[test]
+    fn test_fractional_fills_close_to_flat() {
+        let mut portfolio = Portfolio::new(1_000.0);
+        portfolio.apply_fill(fill(OrderSide::Sell, 0.3, 10.0));
+        portfolio.apply_fill(fill(OrderSide::Buy, 0.1, 10.0));
+        portfolio.apply_fill(fill(OrderSide::Buy, 0.2, 10.0));
+        let position = portfolio.position("TEST").unwrap();
+        assert!(position.is_flat());
+        assert!(position.average_price.is_zero());
+    }
+
+    #[test]
+    fn test_short_futures_and_short_equity_collateral() {
+        let mut portfolio = Portfolio::new(10_000.0);
+        let win = |side, quantity, price| Fill { symbol: "WINFUT".to_string(), ..fill(side, quantity, price) };
+        portfolio.apply_fill(win(OrderSide::Sell, 1.0, 120_000.0));
+        assert_eq!(portfolio.cash, Price::from(10_000));
+        // 1,000 points in favour of the short x R$0.20.
+        let marks = HashMap::from([("WINFUT".to_string(), 119_000.0)]);
+        assert!((portfolio.equity(&marks) - 10_200.0).abs() < 1e-9);
+        assert_eq!(portfolio.margin_status(&marks).initial_margin, 3_000.0);
+        let realized = portfolio.apply_fill(win(OrderSide::Buy, 1.0, 119_000.0));
+        assert_eq!(realized, Price::from(200));
+
+        // Shorting shares ties up their value as collateral.
+        let mut portfolio = Portfolio::new(1_000.0);
+        assert!(portfolio.check_buying_power("TEST", OrderSide::Sell, 100.0, 20.0, &HashMap::new()).is_err());
+        portfolio.apply_fill(fill(OrderSide::Sell, 40.0, 20.0));
+        let marks = HashMap::from([("TEST".to_string(), 20.0)]);
+        assert!((portfolio.margin_status(&marks).buying_power - 200.0).abs() < 1e-9);
+        // Covering reduces exposure and is always allowed.
+        assert!(portfolio.check_buying_power("TEST", OrderSide::Buy, 40.0, 20.0, &marks).is_ok());
+        assert!(portfolio.check_buying_power("TEST", OrderSide::Sell, 20.0, 20.0, &marks).is_err());
+    }
+
+    #[test]
+    fn test_deposits_and_withdrawals() {
+        let mut portfolio = Portfolio::new(1_000.0);
+        portfolio.deposit(500.0, Utc::now()).unwrap();
+        assert!(portfolio.deposit(-1.0, Utc::now()).is_err());
+        assert!(portfolio.deposit(f64::NAN, Utc::now()).is_err());
+
+        // 1,200 spent on shares: only the remaining 300 can leave.
+        portfolio.apply_fill(fill(OrderSide::Buy, 100.0, 12.0));
+        let marks = HashMap::from([("TEST".to_string(), 12.0)]);
+        assert!(portfolio.withdraw(301.0, &marks, Utc::now()).is_err());
+        portfolio.withdraw(300.0, &marks, Utc::now()).unwrap();
+        assert!(portfolio.cash.is_zero());
+        assert_eq!(portfolio.transfers().len(), 2);
+        assert_eq!(portfolio.net_deposits(), Price::from(200));
+        assert!((portfolio.equity(&marks) - 1_200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_base_currency_changes_only_when_flat() {
+        let mut portfolio = Portfolio::new(1_000.0);
+        assert_eq!(portfolio.base_currency, DEFAULT_BASE_CURRENCY);
+        portfolio.set_base_currency("usd").unwrap();
+        assert_eq!(portfolio.base_currency, "USD");
+        assert!(portfolio.set_base_currency("DOLLAR").is_err());
+        portfolio.apply_fill(fill(OrderSide::Buy, 1.0, 10.0));
+        assert!(portfolio.set_base_currency("BRL").is_err());
+    }
+}
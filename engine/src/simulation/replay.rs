@@ -0,0 +1,369 @@
+// Bar-by-bar market replay.
+// A replay session takes the stored candles of a symbol and reveals them one at a
+// time in simulated time. While a session is active, GetMarketData and
+// SimulateTrade only see the candles revealed so far, each revealed candle is
+// pushed to streaming subscribers and resting orders are evaluated against it,
+// so the GUI and the order simulator behave as if the data were live.
+use chrono::{DateTime, Utc};
+use shared::models::{Candle, CandleSeries};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+
+use super::{OrderBook, Portfolio};
+use crate::data::calendar::{session_phase, SessionPhase};
+use crate::error::EngineError;
+
+const REPLAY_CHANNEL_CAPACITY: usize = 256;
+pub const DEFAULT_REPLAY_SPEED: f64 = 1.0; // Bars per second
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayState {
+    Playing,
+    Paused,
+    Finished,
+}
+
+impl ReplayState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReplayState::Playing => "PLAYING",
+            ReplayState::Paused => "PAUSED",
+            ReplayState::Finished => "FINISHED",
+        }
+    }
+}
+
+/// Snapshot of a replay session, as reported back to clients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplaySnapshot {
+    pub symbol: String,
+    pub state: ReplayState,
+    pub position: usize, // Number of candles revealed so far
+    pub total: usize,
+    pub speed: f64,
+    pub current_timestamp: Option<DateTime<Utc>>,
+    /// B3 session phase at `current_timestamp`; telling for intraday data only, as daily bars are often stamped after the close.
+    pub market_phase: SessionPhase,
+}
+
+pub struct ReplaySession {
+    candles: CandleSeries,
+    cursor: usize, // candles[..cursor] are revealed
+    state: ReplayState,
+    speed: f64,
+    generation: u64, // Bumped on every (re)start so stale playback tasks stop
+    tx: Option<broadcast::Sender<Candle>>, // Dropped when the replay finishes, closing subscriber streams
+}
+
+pub struct ReplayController {
+    sessions: HashMap<String, ReplaySession>,
+    next_generation: u64,
+    feed: broadcast::Sender<(String, Candle)>, // Every revealed candle of every session, with its symbol
+}
+
+impl Default for ReplayController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReplayController {
+    pub fn new() -> Self {
+        ReplayController {
+            sessions: HashMap::new(),
+            next_generation: 0,
+            feed: broadcast::channel(REPLAY_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    /// Starts (or restarts) a replay of `candles` for `symbol`.
+    /// Candles before `from` are revealed immediately as history; the rest are replayed.
+    /// Returns the session generation, used to tie a playback task to this session.
+    pub fn start(&mut self, symbol: &str, candles: CandleSeries, from: Option<DateTime<Utc>>, speed: f64, paused: bool) -> Result<u64, EngineError> {
+        if candles.is_empty() {
+            return Err(EngineError::MarketDataError(format!("Market data not found for symbol '{}': nothing to replay", symbol)));
+        }
+        validate_speed(speed)?;
+        let cursor = from.map_or(0, |start| candles.position(start));
+        self.next_generation += 1;
+        let (tx, _) = broadcast::channel(REPLAY_CHANNEL_CAPACITY);
+        let state = if cursor >= candles.len() {
+            ReplayState::Finished
+        } else if paused {
+            ReplayState::Paused
+        } else {
+            ReplayState::Playing
+        };
+        self.sessions.insert(symbol.to_string(), ReplaySession {
+            candles,
+            cursor,
+            state,
+            speed,
+            generation: self.next_generation,
+            tx: if state == ReplayState::Finished { None } else { Some(tx) },
+        });
+        tracing::info!(symbol, cursor, speed, state = state.as_str(), "Replay session started");
+        Ok(self.next_generation)
+    }
+
+    pub fn stop(&mut self, symbol: &str) -> Result<ReplaySnapshot, EngineError> {
+        let status = self.status(symbol)?;
+        self.sessions.remove(symbol);
+        tracing::info!(symbol, "Replay session stopped");
+        Ok(status)
+    }
+
+    pub fn pause(&mut self, symbol: &str) -> Result<ReplaySnapshot, EngineError> {
+        let session = self.session_mut(symbol)?;
+        if session.state == ReplayState::Playing {
+            session.state = ReplayState::Paused;
+        }
+        self.status(symbol)
+    }
+
+    pub fn resume(&mut self, symbol: &str) -> Result<(ReplaySnapshot, u64), EngineError> {
+        let session = self.session_mut(symbol)?;
+        if session.state == ReplayState::Paused {
+            session.state = ReplayState::Playing;
+        }
+        let generation = session.generation;
+        Ok((self.status(symbol)?, generation))
+    }
+
+    pub fn set_speed(&mut self, symbol: &str, speed: f64) -> Result<ReplaySnapshot, EngineError> {
+        validate_speed(speed)?;
+        self.session_mut(symbol)?.speed = speed;
+        self.status(symbol)
+    }
+
+    /// Reveals up to `count` candles, broadcasting each to subscribers.
+    /// Returns the newly revealed candles (empty once the replay is finished).
+    pub fn step(&mut self, symbol: &str, count: usize) -> Result<Vec<Candle>, EngineError> {
+        let feed = self.feed.clone();
+        let session = self.session_mut(symbol)?;
+        let end = (session.cursor + count).min(session.candles.len());
+        let revealed = session.candles[session.cursor..end].to_vec();
+        session.cursor = end;
+        for candle in &revealed {
+            // An error only means nobody is subscribed right now.
+            if let Some(tx) = &session.tx {
+                let _ = tx.send(candle.clone());
+            }
+            let _ = feed.send((symbol.to_string(), candle.clone()));
+        }
+        if session.cursor >= session.candles.len() {
+            session.state = ReplayState::Finished;
+            session.tx = None;
+            tracing::info!(symbol, "Replay session finished");
+        }
+        Ok(revealed)
+    }
+
+    pub fn status(&self, symbol: &str) -> Result<ReplaySnapshot, EngineError> {
+        let session = self.session(symbol)?;
+        let current_timestamp = session.cursor.checked_sub(1).map(|i| session.candles[i].timestamp);
+        Ok(ReplaySnapshot {
+            symbol: symbol.to_string(),
+            state: session.state,
+            position: session.cursor,
+            total: session.candles.len(),
+            speed: session.speed,
+            current_timestamp,
+            market_phase: current_timestamp.map_or(SessionPhase::Closed, |ts| session_phase(symbol, ts)),
+        })
+    }
+
+    pub fn is_active(&self, symbol: &str) -> bool {
+        self.sessions.contains_key(symbol)
+    }
+
+    /// Candles revealed so far, if a replay session is active for `symbol`.
+    pub fn visible_candles(&self, symbol: &str) -> Option<CandleSeries> {
+        self.sessions.get(symbol).map(|s| s.candles.prefix(s.cursor))
+    }
+
+    /// The most recently revealed candle: the replay's notion of "now".
+    pub fn current_candle(&self, symbol: &str) -> Option<Candle> {
+        self.sessions.get(symbol)
+            .and_then(|s| s.cursor.checked_sub(1).map(|i| s.candles[i].clone()))
+    }
+
+    /// Subscribes to the candles every session reveals from now on, including sessions started later.
+    pub fn subscribe_all(&self) -> broadcast::Receiver<(String, Candle)> {
+        self.feed.subscribe()
+    }
+
+    /// Subscribes to candles revealed from now on. `None` if no session is running.
+    pub fn subscribe(&self, symbol: &str) -> Option<broadcast::Receiver<Candle>> {
+        self.sessions.get(symbol).and_then(|s| s.tx.as_ref()).map(|tx| tx.subscribe())
+    }
+
+    fn session(&self, symbol: &str) -> Result<&ReplaySession, EngineError> {
+        self.sessions.get(symbol)
+            .ok_or_else(|| EngineError::MarketDataError(format!("Replay session not found for symbol '{}'", symbol)))
+    }
+
+    fn session_mut(&mut self, symbol: &str) -> Result<&mut ReplaySession, EngineError> {
+        self.sessions.get_mut(symbol)
+            .ok_or_else(|| EngineError::MarketDataError(format!("Replay session not found for symbol '{}'", symbol)))
+    }
+}
+
+fn validate_speed(speed: f64) -> Result<(), EngineError> {
+    if speed.is_finite() && speed > 0.0 {
+        Ok(())
+    } else {
+        Err(EngineError::InvalidRequest(format!("Replay speed must be a positive number of bars per second, got {}", speed)))
+    }
+}
+
+/// Reveals up to `count` candles and lets resting orders react to them.
+/// Resulting fills are booked into the session portfolio.
+pub async fn advance_replay(
+    replay: &Arc<RwLock<ReplayController>>,
+    order_book: &Arc<RwLock<OrderBook>>,
+    portfolio: &Arc<RwLock<Portfolio>>,
+    symbol: &str,
+    count: usize,
+) -> Result<ReplaySnapshot, EngineError> {
+    let mut controller = replay.write().await;
+    let revealed = controller.step(symbol, count)?;
+    let status = controller.status(symbol)?;
+    drop(controller);
+
+    if let Some(last) = revealed.last() {
+        let fills = order_book.write().await.process_candles(symbol, &revealed);
+        let mut portfolio = portfolio.write().await;
+        if !fills.is_empty() {
+            tracing::info!(symbol, fills = fills.len(), "Working orders filled during replay");
+            portfolio.apply_fills(fills);
+        }
+        portfolio.check_margin(&HashMap::from([(symbol.to_string(), last.close_f64())]));
+    }
+    Ok(status)
+}
+
+/// Drives a playing session in the background, one candle every `1 / speed` seconds.
+/// The task exits when the session is paused, finished, stopped or restarted.
+pub fn spawn_playback(
+    replay: Arc<RwLock<ReplayController>>,
+    order_book: Arc<RwLock<OrderBook>>,
+    portfolio: Arc<RwLock<Portfolio>>,
+    symbol: String,
+    generation: u64,
+) {
+    tokio::spawn(async move {
+        loop {
+            let speed = {
+                let controller = replay.read().await;
+                match controller.sessions.get(&symbol) {
+                    Some(s) if s.generation == generation && s.state == ReplayState::Playing => s.speed,
+                    _ => break,
+                }
+            };
+            tokio::time::sleep(Duration::from_secs_f64(1.0 / speed)).await;
+
+            // Re-check after sleeping: the session may have been paused or restarted meanwhile.
+            let still_playing = replay.read().await.sessions.get(&symbol)
+                .is_some_and(|s| s.generation == generation && s.state == ReplayState::Playing);
+            if !still_playing {
+                break;
+            }
+            if let Err(e) = advance_replay(&replay, &order_book, &portfolio, &symbol, 1).await {
+                tracing::warn!(symbol = %symbol, error = ?e, "Replay playback stopped");
+                break;
+            }
+        }
+        tracing::debug!(symbol = %symbol, generation, "Replay playback task exited");
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::models::{to_price, Price};
+    use crate::simulation::{Order, OrderSide, OrderStatus, TimeInForce};
+    use chrono::{Duration as ChronoDuration, TimeZone};
+
+    fn candles(n: usize) -> CandleSeries {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        CandleSeries::new((0..n).map(|i| {
+            let price = 100.0 + i as f64;
+            Candle { symbol: "TEST".to_string(), timestamp: t0 + ChronoDuration::days(i as i64), open: to_price(price), high: to_price(price + 1.0), low: to_price(price - 1.0), close: to_price(price), volume: 10.0, trades: 1 }
+        }).collect())
+    }
+
+    #[test]
+    fn test_start_reveals_history_before_from() {
+        let data = candles(5);
+        let mut controller = ReplayController::new();
+        controller.start("TEST", data.clone(), Some(data[2].timestamp), 1.0, true).unwrap();
+        let status = controller.status("TEST").unwrap();
+        assert_eq!(status.position, 2);
+        assert_eq!(status.state, ReplayState::Paused);
+        assert_eq!(controller.visible_candles("TEST").unwrap().len(), 2);
+        assert_eq!(controller.current_candle("TEST").unwrap().timestamp, data[1].timestamp);
+        assert_eq!(status.market_phase, SessionPhase::Continuous); // 10:00 on a Wednesday
+    }
+
+    #[test]
+    fn test_step_broadcasts_and_finishes() {
+        let mut controller = ReplayController::new();
+        controller.start("TEST", candles(3), None, 1.0, true).unwrap();
+        let mut rx = controller.subscribe("TEST").unwrap();
+
+        assert_eq!(controller.step("TEST", 2).unwrap().len(), 2);
+        assert_eq!(rx.try_recv().unwrap().close, Price::from(100));
+        assert_eq!(rx.try_recv().unwrap().close, Price::from(101));
+
+        assert_eq!(controller.step("TEST", 5).unwrap().len(), 1);
+        assert_eq!(controller.status("TEST").unwrap().state, ReplayState::Finished);
+        assert!(controller.step("TEST", 1).unwrap().is_empty());
+        // The sender is dropped on finish, so the subscriber sees the last candle then a closed channel.
+        assert_eq!(rx.try_recv().unwrap().close, Price::from(102));
+        assert!(matches!(rx.try_recv(), Err(broadcast::error::TryRecvError::Closed)));
+    }
+
+    #[test]
+    fn test_invalid_speed_and_missing_session() {
+        let mut controller = ReplayController::new();
+        assert!(controller.start("TEST", candles(3), None, 0.0, false).is_err());
+        assert!(controller.start("TEST", CandleSeries::default(), None, 1.0, false).is_err());
+        assert!(controller.pause("NOPE").is_err());
+        controller.start("TEST", candles(3), None, 2.0, false).unwrap();
+        assert!(controller.set_speed("TEST", -1.0).is_err());
+        assert_eq!(controller.set_speed("TEST", 4.0).unwrap().speed, 4.0);
+        assert_eq!(controller.pause("TEST").unwrap().state, ReplayState::Paused);
+        assert_eq!(controller.resume("TEST").unwrap().0.state, ReplayState::Playing);
+        controller.stop("TEST").unwrap();
+        assert!(!controller.is_active("TEST"));
+    }
+
+    #[tokio::test]
+    async fn test_advance_replay_fills_resting_orders() {
+        let data = candles(4);
+        let replay = Arc::new(RwLock::new(ReplayController::new()));
+        let order_book = Arc::new(RwLock::new(OrderBook::new()));
+        let portfolio = Arc::new(RwLock::new(Portfolio::new(1_000.0)));
+        replay.write().await.start("TEST", data.clone(), Some(data[1].timestamp), 1.0, true).unwrap();
+        order_book.write().await.place(Order {
+            id: "o1".to_string(),
+            symbol: "TEST".to_string(),
+            side: OrderSide::Buy,
+            quantity: 1.0,
+            limit_price: Price::new(1_005, 1), // Reached by the low of the second candle (101.0 - 1.0)
+            time_in_force: TimeInForce::Gtc,
+            placed_at: data[0].timestamp,
+            status: OrderStatus::Working,
+            filled_price: None,
+            filled_at: None,
+        });
+
+        let status = advance_replay(&replay, &order_book, &portfolio, "TEST", 1).await.unwrap();
+        assert_eq!(status.position, 2);
+        assert_eq!(order_book.read().await.get("o1").unwrap().status, OrderStatus::Filled);
+        assert_eq!(portfolio.read().await.position("TEST").unwrap().quantity, 1.0);
+    }
+}
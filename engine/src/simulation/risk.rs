@@ -0,0 +1,205 @@
+// Risk limits for the paper-trading account.
+// Checked before a simulated order is accepted: orders that only reduce exposure always
+// pass, so a limit can stop an account from growing a position but never from closing it.
+// Limits come from the engine settings and can be replaced at runtime over RPC.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shared::models::to_f64;
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{OrderSide, Portfolio};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskLimits {
+    /// Largest absolute position in any symbol, in shares/contracts.
+    pub max_position: Option<f64>,
+    /// Per-symbol overrides of `max_position`.
+    pub max_position_by_symbol: HashMap<String, f64>,
+    /// Loss for the trading day (realized that day plus open P&L) beyond which no new exposure is taken.
+    pub max_daily_loss: Option<f64>,
+    /// Working (resting) orders allowed at once.
+    pub max_open_orders: Option<usize>,
+}
+
+/// Why an order was refused, with the numbers that broke the limit.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskViolation {
+    MaxPosition { symbol: String, limit: f64, resulting: f64 },
+    MaxDailyLoss { limit: f64, loss: f64 },
+    MaxOpenOrders { limit: usize, open: usize },
+}
+
+impl RiskViolation {
+    pub fn rule(&self) -> &'static str {
+        match self {
+            RiskViolation::MaxPosition { .. } => "MAX_POSITION",
+            RiskViolation::MaxDailyLoss { .. } => "MAX_DAILY_LOSS",
+            RiskViolation::MaxOpenOrders { .. } => "MAX_OPEN_ORDERS",
+        }
+    }
+
+    pub fn limit(&self) -> f64 {
+        match self {
+            RiskViolation::MaxPosition { limit, .. } | RiskViolation::MaxDailyLoss { limit, .. } => *limit,
+            RiskViolation::MaxOpenOrders { limit, .. } => *limit as f64,
+        }
+    }
+
+    /// The value that would exceed the limit.
+    pub fn value(&self) -> f64 {
+        match self {
+            RiskViolation::MaxPosition { resulting, .. } => resulting.abs(),
+            RiskViolation::MaxDailyLoss { loss, .. } => *loss,
+            RiskViolation::MaxOpenOrders { open, .. } => (*open + 1) as f64,
+        }
+    }
+}
+
+impl fmt::Display for RiskViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskViolation::MaxPosition { symbol, limit, resulting } => write!(
+                f, "Risk limit MAX_POSITION: the order would take {} to {} (limit {})", symbol, resulting, limit
+            ),
+            RiskViolation::MaxDailyLoss { limit, loss } => write!(
+                f, "Risk limit MAX_DAILY_LOSS: today's loss of {:.2} has reached the limit of {:.2}; only orders that reduce positions are accepted", loss, limit
+            ),
+            RiskViolation::MaxOpenOrders { limit, open } => write!(
+                f, "Risk limit MAX_OPEN_ORDERS: {} orders are already working (limit {})", open, limit
+            ),
+        }
+    }
+}
+
+impl RiskLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = |name: &str, value: Option<f64>| match value {
+            Some(v) if !v.is_finite() || v <= 0.0 => Err(format!("Risk limit {} must be a positive number, got {}", name, v)),
+            _ => Ok(()),
+        };
+        positive("max_position", self.max_position)?;
+        positive("max_daily_loss", self.max_daily_loss)?;
+        for (symbol, limit) in &self.max_position_by_symbol {
+            positive(&format!("max_position for {}", symbol), Some(*limit))?;
+        }
+        Ok(())
+    }
+
+    pub fn position_limit(&self, symbol: &str) -> Option<f64> {
+        self.max_position_by_symbol.get(symbol).copied().or(self.max_position)
+    }
+
+    /// Checks an order for `quantity` of `symbol` against the position and daily loss limits.
+    /// `now` is the simulated time (the latest candle), which decides what "today" is.
+    pub fn check_order(
+        &self,
+        symbol: &str,
+        side: OrderSide,
+        quantity: f64,
+        portfolio: &Portfolio,
+        marks: &HashMap<String, f64>,
+        now: DateTime<Utc>,
+    ) -> Result<(), RiskViolation> {
+        let current = portfolio.position(symbol).map(|p| p.quantity).unwrap_or(0.0);
+        let resulting = match side {
+            OrderSide::Buy => current + quantity,
+            OrderSide::Sell => current - quantity,
+        };
+        if resulting.abs() <= current.abs() {
+            return Ok(());
+        }
+
+        if let Some(limit) = self.position_limit(symbol).filter(|limit| resulting.abs() > *limit) {
+            return Err(RiskViolation::MaxPosition { symbol: symbol.to_string(), limit, resulting });
+        }
+        if let Some(limit) = self.max_daily_loss {
+            let loss = -daily_pnl(portfolio, marks, now);
+            if loss >= limit {
+                return Err(RiskViolation::MaxDailyLoss { limit, loss });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks whether one more order can rest in a book that has `open` working orders.
+    pub fn check_open_orders(&self, open: usize) -> Result<(), RiskViolation> {
+        match self.max_open_orders {
+            Some(limit) if open >= limit => Err(RiskViolation::MaxOpenOrders { limit, open }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// P&L realized on `now`'s date plus the open P&L of every position at `marks`.
+pub fn daily_pnl(portfolio: &Portfolio, marks: &HashMap<String, f64>, now: DateTime<Utc>) -> f64 {
+    let today = now.date_naive();
+    let realized: f64 = portfolio.executions().iter()
+        .filter(|e| e.fill.timestamp.date_naive() == today)
+        .map(|e| to_f64(e.realized_pnl))
+        .sum();
+    let open: f64 = portfolio.positions()
+        .filter(|p| !p.is_flat())
+        .map(|p| p.unrealized_pnl(marks.get(&p.symbol).copied().unwrap_or(to_f64(p.average_price))))
+        .sum();
+    realized + open
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::Fill;
+    use chrono::{Duration, TimeZone};
+    use shared::models::to_price;
+
+    fn fill(side: OrderSide, quantity: f64, price: f64, timestamp: DateTime<Utc>) -> Fill {
+        Fill { order_id: "o".to_string(), symbol: "TEST".to_string(), side, price: to_price(price), quantity, timestamp }
+    }
+
+    #[test]
+    fn test_position_limit_blocks_growth_only() {
+        let now = Utc::now();
+        let limits = RiskLimits { max_position: Some(100.0), max_position_by_symbol: HashMap::from([("OTHER".to_string(), 5.0)]), ..Default::default() };
+        let mut portfolio = Portfolio::new(100_000.0);
+        portfolio.apply_fill(fill(OrderSide::Buy, 80.0, 10.0, now));
+        let marks = HashMap::new();
+
+        let violation = limits.check_order("TEST", OrderSide::Buy, 30.0, &portfolio, &marks, now).unwrap_err();
+        assert_eq!((violation.rule(), violation.limit(), violation.value()), ("MAX_POSITION", 100.0, 110.0));
+        assert!(limits.check_order("TEST", OrderSide::Buy, 20.0, &portfolio, &marks, now).is_ok());
+        assert!(limits.check_order("TEST", OrderSide::Sell, 150.0, &portfolio, &marks, now).is_ok()); // Ends 70 short
+        assert!(limits.check_order("TEST", OrderSide::Sell, 190.0, &portfolio, &marks, now).is_err());
+        assert!(limits.check_order("OTHER", OrderSide::Buy, 6.0, &portfolio, &marks, now).is_err());
+    }
+
+    #[test]
+    fn test_daily_loss_counts_today_and_open_pnl() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 5, 15, 0, 0).unwrap();
+        let limits = RiskLimits { max_daily_loss: Some(500.0), ..Default::default() };
+        let mut portfolio = Portfolio::new(100_000.0);
+        // A 1,000 loss yesterday does not count today.
+        portfolio.apply_fill(fill(OrderSide::Buy, 100.0, 20.0, now - Duration::days(1)));
+        portfolio.apply_fill(fill(OrderSide::Sell, 100.0, 10.0, now - Duration::days(1)));
+        portfolio.apply_fill(fill(OrderSide::Buy, 100.0, 10.0, now));
+
+        let marks = HashMap::from([("TEST".to_string(), 7.0)]);
+        assert!(limits.check_order("TEST", OrderSide::Buy, 1.0, &portfolio, &marks, now).is_ok());
+        let marks = HashMap::from([("TEST".to_string(), 5.0)]);
+        let violation = limits.check_order("TEST", OrderSide::Buy, 1.0, &portfolio, &marks, now).unwrap_err();
+        assert_eq!(violation, RiskViolation::MaxDailyLoss { limit: 500.0, loss: 500.0 });
+        assert!(limits.check_order("TEST", OrderSide::Sell, 100.0, &portfolio, &marks, now).is_ok());
+    }
+
+    #[test]
+    fn test_open_orders_limit_and_validate() {
+        let limits = RiskLimits { max_open_orders: Some(2), ..Default::default() };
+        assert!(limits.check_open_orders(1).is_ok());
+        assert_eq!(limits.check_open_orders(2).unwrap_err().value(), 3.0);
+        assert!(RiskLimits::default().check_open_orders(100).is_ok());
+
+        assert!(RiskLimits::default().validate().is_ok());
+        assert!(RiskLimits { max_position: Some(0.0), ..Default::default() }.validate().is_err());
+        assert!(RiskLimits { max_daily_loss: Some(f64::NAN), ..Default::default() }.validate().is_err());
+    }
+}
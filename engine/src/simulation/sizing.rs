@@ -0,0 +1,164 @@
+// Position sizing helpers.
+// Turn an account size and a risk budget into a quantity, for strategies in the
+// backtester and for the GUI order panel (via SuggestPositionSize).
+use super::contracts::{contract_spec, Contracts};
+use crate::error::EngineError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizingMethod {
+    /// Always the same number of shares/contracts.
+    FixedContracts { contracts: f64 },
+    /// Risk a fraction of equity between entry and stop: qty = equity * risk / (stop distance * point value).
+    FixedFractional { risk_fraction: f64 },
+    /// Like fixed fractional, with the stop placed `atr_multiple` ATRs away.
+    AtrRisk { risk_fraction: f64, atr_multiple: f64 },
+    /// Kelly criterion f* = W - (1 - W) / R, scaled by `fraction` (0.5 = half Kelly), as share of equity committed.
+    Kelly { win_rate: f64, payoff_ratio: f64, fraction: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SizingInput {
+    pub equity: f64,
+    /// Currency per point per unit (1 for equities).
+    pub point_value: f64,
+    /// Capital tied up per unit: the price for equities, the initial margin for futures.
+    pub capital_per_unit: f64,
+    /// Distance from entry to stop, in points.
+    pub stop_distance: Option<f64>,
+    pub atr: Option<f64>,
+    /// Quantities are rounded down to a multiple of this (1 contract, 100-share round lot, ...).
+    pub lot_size: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SizingResult {
+    pub quantity: f64,
+    /// Loss at the stop for `quantity`, when a stop distance is known.
+    pub risk_amount: f64,
+    pub capital_required: f64,
+}
+
+impl SizingInput {
+    /// Input for `symbol` at `price`: futures use the contract's point value and initial margin
+    /// per unit, equities 1 currency per point and the price itself, unless `contracts` set another point value.
+    pub fn for_symbol(symbol: &str, equity: f64, price: f64, contracts: &Contracts) -> Self {
+        let capital_per_unit = contract_spec(symbol).map_or(price, |spec| spec.initial_margin);
+        Self { equity, point_value: contracts.point_value(symbol), capital_per_unit, stop_distance: None, atr: None, lot_size: 1.0 }
+    }
+}
+
+fn check_fraction(name: &str, value: f64) -> Result<(), EngineError> {
+    if value > 0.0 && value <= 1.0 {
+        Ok(())
+    } else {
+        Err(EngineError::InvalidRequest(format!("{} must be in (0, 1], got {}", name, value)))
+    }
+}
+
+fn risk_based(equity: f64, risk_fraction: f64, stop_distance: f64, point_value: f64) -> Result<f64, EngineError> {
+    check_fraction("Risk fraction", risk_fraction)?;
+    if stop_distance <= 0.0 {
+        return Err(EngineError::InvalidRequest("Stop distance must be positive for risk-based sizing".to_string()));
+    }
+    Ok(equity * risk_fraction / (stop_distance * point_value))
+}
+
+pub fn position_size(method: SizingMethod, input: &SizingInput) -> Result<SizingResult, EngineError> {
+    if input.equity <= 0.0 {
+        return Err(EngineError::InvalidRequest("Account equity must be positive".to_string()));
+    }
+    let point_value = if input.point_value > 0.0 { input.point_value } else { 1.0 };
+    let lot_size = if input.lot_size > 0.0 { input.lot_size } else { 1.0 };
+
+    let (raw_quantity, stop_distance) = match method {
+        SizingMethod::FixedContracts { contracts } => {
+            if contracts <= 0.0 {
+                return Err(EngineError::InvalidRequest("Fixed quantity must be positive".to_string()));
+            }
+            (contracts, input.stop_distance)
+        }
+        SizingMethod::FixedFractional { risk_fraction } => {
+            let stop = input.stop_distance
+                .ok_or_else(|| EngineError::InvalidRequest("Fixed fractional sizing needs a stop distance".to_string()))?;
+            (risk_based(input.equity, risk_fraction, stop, point_value)?, Some(stop))
+        }
+        SizingMethod::AtrRisk { risk_fraction, atr_multiple } => {
+            let atr = input.atr.filter(|a| a.is_finite() && *a > 0.0)
+                .ok_or_else(|| EngineError::InvalidRequest("ATR sizing needs a positive ATR (not enough candles?)".to_string()))?;
+            if atr_multiple <= 0.0 {
+                return Err(EngineError::InvalidRequest("ATR multiple must be positive".to_string()));
+            }
+            let stop = atr * atr_multiple;
+            (risk_based(input.equity, risk_fraction, stop, point_value)?, Some(stop))
+        }
+        SizingMethod::Kelly { win_rate, payoff_ratio, fraction } => {
+            check_fraction("Win rate", win_rate)?;
+            check_fraction("Kelly fraction", fraction)?;
+            if payoff_ratio <= 0.0 {
+                return Err(EngineError::InvalidRequest("Payoff ratio must be positive".to_string()));
+            }
+            if input.capital_per_unit <= 0.0 {
+                return Err(EngineError::InvalidRequest("Kelly sizing needs the capital per unit (price or margin)".to_string()));
+            }
+            // A negative edge means "do not trade".
+            let kelly = (win_rate - (1.0 - win_rate) / payoff_ratio).max(0.0);
+            (input.equity * kelly * fraction / input.capital_per_unit, input.stop_distance)
+        }
+    };
+
+    let quantity = (raw_quantity / lot_size + 1e-9).floor() * lot_size;
+    Ok(SizingResult {
+        quantity,
+        risk_amount: stop_distance.map(|s| quantity * s * point_value).unwrap_or(0.0),
+        capital_required: quantity * input.capital_per_unit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input() -> SizingInput {
+        SizingInput { equity: 100_000.0, point_value: 0.20, capital_per_unit: 3_000.0, stop_distance: Some(300.0), atr: Some(250.0), lot_size: 1.0 }
+    }
+
+    #[test]
+    fn test_risk_based_sizing() {
+        // 1% of 100k = 1000 at risk; 300 points x R$0.20 = R$60 per contract -> 16 contracts.
+        let result = position_size(SizingMethod::FixedFractional { risk_fraction: 0.01 }, &input()).unwrap();
+        assert_eq!(result.quantity, 16.0);
+        assert!((result.risk_amount - 960.0).abs() < 1e-9);
+        assert_eq!(result.capital_required, 48_000.0);
+
+        // Stop at 2 ATR = 500 points = R$100 per contract -> 10 contracts.
+        let atr = position_size(SizingMethod::AtrRisk { risk_fraction: 0.01, atr_multiple: 2.0 }, &input()).unwrap();
+        assert_eq!(atr.quantity, 10.0);
+
+        let no_stop = SizingInput { stop_distance: None, ..input() };
+        assert!(position_size(SizingMethod::FixedFractional { risk_fraction: 0.01 }, &no_stop).is_err());
+        assert!(position_size(SizingMethod::FixedFractional { risk_fraction: 1.5 }, &input()).is_err());
+    }
+
+    #[test]
+    fn test_input_for_symbol() {
+        let futures = SizingInput::for_symbol("WINFUT", 50_000.0, 120_000.0, &Contracts::default());
+        assert_eq!(futures.point_value, 0.20);
+        assert_eq!(futures.capital_per_unit, 3_000.0);
+        let equity = SizingInput::for_symbol("PETR4", 50_000.0, 38.5, &Contracts::default());
+        assert_eq!(equity.point_value, 1.0);
+        assert_eq!(equity.capital_per_unit, 38.5);
+    }
+
+    #[test]
+    fn test_kelly_and_fixed_sizing() {
+        // W = 0.6, R = 2: f* = 0.6 - 0.4 / 2 = 0.4; half Kelly = 20% of 100k = 20k / 3k margin -> 6 contracts.
+        let kelly = position_size(SizingMethod::Kelly { win_rate: 0.6, payoff_ratio: 2.0, fraction: 0.5 }, &input()).unwrap();
+        assert_eq!(kelly.quantity, 6.0);
+        let no_edge = position_size(SizingMethod::Kelly { win_rate: 0.3, payoff_ratio: 1.0, fraction: 1.0 }, &input()).unwrap();
+        assert_eq!(no_edge.quantity, 0.0);
+
+        // Round lots: 250 shares rounds down to 200.
+        let stocks = SizingInput { point_value: 1.0, capital_per_unit: 38.0, lot_size: 100.0, ..input() };
+        assert_eq!(position_size(SizingMethod::FixedContracts { contracts: 250.0 }, &stocks).unwrap().quantity, 200.0);
+    }
+}
@@ -0,0 +1,427 @@
+# Home Trader GUI text, English (United States).
+# Every message here needs a counterpart in pt-BR.ftl.
+
+## Main window
+
+app-title = Home Trader
+app-palette-hint = Press '{ $shortcut }' to open/close the command palette.
+app-drop-hint = Drop CSV files on the window to load them.
+app-toggle-palette = Toggle Command Palette
+app-light-theme = Light Theme
+app-dark-theme = Dark Theme
+app-error = Error: { $message }
+app-displaying = Displaying: { $symbol }
+app-dismiss = Dismiss
+app-chart-style = Chart style
+
+## Dialogs
+
+dialog-close = Close
+dialog-cancel = Cancel
+csv-drop-title = Load { $file }
+csv-drop-symbol = Symbol
+csv-drop-timeframe = Timeframe
+csv-drop-load = Load
+csv-drop-skip = Skip
+csv-drop-remaining = { $count } more dropped files to go
+csv-drop-no-symbol = Enter the symbol to load the file as.
+csv-drop-not-csv = Only CSV files can be loaded by dropping them: { $files }
+dialog-save = Save
+
+## Status bar and engine panel
+
+status-connecting = Connecting to the engine...
+status-connected = Engine connected
+status-reconnecting = Engine not responding, reconnecting (attempt { $attempt }, next in { $seconds }s)...
+status-offline = Engine offline, retrying in { $seconds }s
+status-chart = { $symbol } · { $timeframe } · { $candles } candles
+status-range = { $low } – { $high } ({ $change })
+status-updated = Updated { $time }
+engine-not-connected = Engine: not connected
+engine-info = Engine { $version } (API v{ $api_version }, { $profile }, { $target }), up { $uptime } when connected
+engine-loaded = Loaded: { $series } series, { $candles } candles
+engine-features = Features: { $features }
+engine-features-none = none
+
+## Command palette
+
+palette-placeholder = Type a command...
+palette-no-match = No commands match your search.
+palette-recent = Recently used
+palette-all = All commands
+
+command-load-csv = Load CSV Data (Sample WINFUT)
+command-load-csv-desc = Import WINFUT market data from a sample CSV file
+command-add-sma = Add Indicator: SMA
+command-add-sma-desc = Add Simple Moving Average indicator
+command-add-ema = Add Indicator: EMA
+command-add-ema-desc = Add Exponential Moving Average indicator
+command-add-rsi = Add Indicator: RSI
+command-add-rsi-desc = Add Relative Strength Index indicator
+command-toggle-theme = Toggle Theme
+command-toggle-theme-desc = Switch between the dark and light themes
+command-backtest-sma-crossover = Backtest: SMA Crossover
+command-backtest-sma-crossover-desc = Backtest the SMA crossover strategy and show its trades on the chart
+command-save-project = Save Project
+command-save-project-desc = Save the loaded symbols, indicators, drawings and layout to the project file
+command-open-project = Open Project
+command-open-project-desc = Restore the workspace saved in the project file
+command-edit-shortcuts = Edit Keyboard Shortcuts
+command-edit-shortcuts-desc = Rebind the keyboard shortcuts
+command-settings = Settings
+command-settings-desc = Edit the chart, indicator, data format and engine settings
+command-switch-language = Language: { $language }
+command-switch-language-desc = Show the interface in { $language }
+command-export-chart-png = Export Chart as PNG
+command-export-chart-png-desc = Save the chart with its indicators and drawings as a PNG image
+command-export-chart-svg = Export Chart as SVG
+command-export-chart-svg-desc = Save the chart with its indicators and drawings as an SVG image
+command-export-data-csv = Export Visible Data as CSV
+command-export-data-csv-desc = Save the visible candles and the values of the chart's indicators as a CSV file
+command-export-data-json = Export Visible Data as JSON
+command-export-data-json-desc = Save the visible candles and the values of the chart's indicators as a JSON file
+command-engine-connection = Engine Connection
+command-engine-connection-desc = Change the engine address and reconnect, or test a connection
+command-link-charts = Toggle Linked Charts
+command-link-charts-desc = Keep the chart on the same times (visible range and crosshair) when switching symbols
+command-backtest-results = Backtest Results
+command-backtest-results-desc = Show the metrics, equity curve and trades of the last backtest
+command-panel-watchlist = Show/Hide Watchlist
+command-panel-watchlist-desc = Show or hide the watchlist beside the chart
+command-panel-indicators = Show/Hide Indicator Panel
+command-panel-indicators-desc = Show or hide the list of the chart's indicators and their styles
+command-panel-price-levels = Show/Hide Price Levels
+command-panel-price-levels-desc = Show or hide the list of the chart's price levels
+command-panel-alerts = Show/Hide Alerts
+command-panel-alerts-desc = Show or hide the engine alerts of the chart's symbol
+command-panel-replay = Show/Hide Replay Controls
+command-panel-replay-desc = Show or hide the replay controls below the chart
+command-open-recent = Open Recent: { $file }
+command-open-recent-csv-desc = Load this CSV file again
+command-open-recent-project-desc = Open this project again
+command-limit-buy = Buy Limit @ { $price }
+command-limit-buy-desc = Place a simulated limit order to buy at { $price }
+command-limit-sell = Sell Limit @ { $price }
+command-limit-sell-desc = Place a simulated limit order to sell at { $price }
+command-add-alert = Add Alert @ { $price }
+command-add-alert-desc = Notify when the price crosses { $price }
+command-copy-price = Copy Price ({ $price })
+command-copy-price-desc = Copy { $price } to the clipboard
+command-copy-time = Copy Time ({ $time })
+command-copy-time-desc = Copy { $time } to the clipboard
+command-exit = Exit Application
+command-exit-desc = Close Home Trader
+command-chart-style = Chart Style: { $style }
+command-chart-style-desc = Draw the chart as { $style }
+command-remove-indicator = Remove Indicator: { $name }
+command-remove-indicator-desc = Remove this indicator from the current symbol
+
+## Progress, notices and errors
+
+loading-csv = Loading { $file }
+loading-calculating = Calculating { $indicator }
+loading-recalculating = Recalculating { $indicator }
+loading-fetching = Fetching { $symbol }
+loading-backtest = Backtesting { $strategy } on { $symbol }
+loading-project = Opening project
+
+notice-project-saved = Project saved to { $path }
+notice-settings-saved = Settings saved to { $path }
+notice-settings-saved-restart = Settings saved to { $path }. Restart to apply: { $settings }
+notice-settings-reloaded = Settings reloaded from { $path }
+notice-settings-reloaded-restart = Settings reloaded from { $path }. Restart to apply: { $settings }
+notice-charts-linked = Charts linked: switching symbols keeps the same times
+notice-charts-unlinked = Charts unlinked
+notice-order-placed = Order placed: { $message }
+notice-copied = Copied { $text }
+notice-chart-exported = Chart saved to { $path }
+notice-data-exported = { $count } candles saved to { $path }
+notice-backtest = Backtest { $strategy } on { $symbol }: { $trades } trades
+notice-nothing-to-undo = Nothing to undo.
+notice-nothing-to-redo = Nothing to redo.
+notice-level-crossed = { $symbol } crossed { $level } (now { $price })
+notice-alert-fired = { $symbol } alert: { $alert } (at { $price })
+
+error-no-engine = Engine client not available.
+error-alert-level = '{ $level }' is not a number.
+error-alert-create = Failed to set the alert: { $error }
+error-connect = Failed to connect to trading engine: { $error }
+error-spawn-engine = Failed to start the engine { $path }: { $error }
+engine-process-exited = The engine process exited ({ $status })
+engine-process-timed-out = The engine did not start serving within { $seconds } seconds
+error-api-version = Trading engine { $version } speaks API version { $api_version }, this GUI expects { $expected }; please update both together
+error-engine-uri = Failed to create URI for gRPC channel: { $error }
+error-engine-namespace = Invalid engine namespace: { $error }
+error-engine-auth-token = Invalid engine auth token: { $error }
+error-import-no-result = The engine's CSV import job returned no import result
+error-backtest-no-result = The engine's backtest job returned no backtest result
+error-market-data = Failed to get market data for { $symbol }: { $error }
+error-load-csv = Failed to load CSV { $file }: { $error }
+error-csv-not-found = { $file } does not exist or cannot be opened.
+error-csv-format = { $file } is not a CSV file the engine can read: { $error }
+error-no-market-data = The engine has no candles for { $symbol }; load its CSV file first.
+error-load-symbol = Failed to load { $symbol } from { $file }: { $error }
+error-indicator-no-data = Indicator { $indicator } for { $symbol } returned no data.
+error-calculate-indicator = Failed to calculate indicator { $indicator } for { $symbol }: { $error }
+error-no-symbol-add-indicator = No active symbol to add indicator to.
+error-no-symbol-remove-indicator = No active symbol to remove an indicator from.
+error-no-symbol-backtest = No active symbol to backtest.
+error-no-backtest-results = No backtest run yet: run one from the command palette first.
+error-backtest-symbol-not-loaded = { $symbol } is not loaded; load its data to show the trade.
+error-backtest = Failed to backtest { $strategy } on { $symbol }: { $error }
+error-save-project = Failed to save the project to { $path }: { $error }
+error-open-project = Failed to open the project { $path }: { $error }
+error-project-newer = { $path } was saved by a newer version (project format { $version }, this GUI reads up to { $supported })
+error-no-chart = No chart to export.
+error-no-chart-open = Open a symbol's chart first.
+error-place-order = Failed to place the order on { $symbol }: { $error }
+error-order-rejected = Order rejected: { $message }
+error-export-chart = Failed to export the chart: { $error }
+error-export-data = Failed to export the data of { $symbol }: { $error }
+error-write-file = Failed to write { $path }
+error-replay-start = Failed to start the replay of { $symbol }: { $error }
+error-replay-control = Replay { $action } failed for { $symbol }: { $error }
+
+## Watchlist and symbol tabs
+
+watchlist-title = Watchlist
+watchlist-symbol-placeholder = Symbol
+watchlist-add = Add
+watchlist-empty = Add symbols to follow their prices.
+watchlist-loading = Loading...
+watchlist-remove = Remove from the watchlist
+symbols-empty = No symbols loaded yet.
+symbols-refresh = Refresh the engine's symbols
+
+## Indicator and price level panels
+
+indicators-title = Indicators ({ $symbol })
+indicators-empty = None yet. Add one from the command palette.
+indicators-no-data = { $indicator } returned no data with these parameters.
+indicators-recalculate-failed = Failed to recalculate { $indicator } for { $symbol }: { $error }
+indicators-visible-hint = Show on the chart
+indicators-color-hint = Line color
+indicators-width-hint = Line width
+indicators-dash-hint = Line style
+indicators-remove = Remove
+indicators-apply = Apply
+price-levels-title = Price levels ({ $symbol })
+price-levels-empty = Click the chart's price axis to add one.
+price-levels-alert-hint = Alert when the price crosses this level
+price-levels-alert = Alert
+price-levels-delete = Delete
+alerts-title = Alerts ({ $symbol })
+alerts-empty = None waiting. The engine checks them on every new or replayed candle.
+alerts-kind-price-cross = Price crosses
+alerts-kind-indicator = Indicator crosses
+alerts-kind-percent-move = Percent move
+alerts-price = Price
+alerts-threshold = Threshold
+alerts-percent = % (negative for a fall)
+alerts-period = Period
+alerts-above = above
+alerts-below = below
+alerts-note = Note
+alerts-add = Add
+alerts-delete = Delete
+alerts-price-cross = Crosses { $level }
+alerts-indicator-above = { $indicator }({ $period }) above { $level }
+alerts-indicator-below = { $indicator }({ $period }) below { $level }
+alerts-percent-move = Moves { $percent }% from where it was set
+
+## Chart
+
+chart-no-data = No candle data available.
+chart-range = Candles { $first }–{ $last } of { $total }
+chart-minimap-hint = All the candles: click to show that period, drag the highlighted range to move it
+chart-legend-title = { $symbol } · { $timeframe }
+chart-legend-no-value = –
+chart-measure = { $change } ({ $percent }) · { $bars } bars · { $elapsed }
+trade-buy = BUY
+trade-sell = SELL
+trade-fill = { $action } { $quantity } @ { $price }
+trade-pnl = P&L { $pnl }
+trade-realized-pnl = Realized P&L { $pnl }
+trade-long = Long
+trade-short = Short
+trade-position = { $direction } { $entry } → { $exit }
+trade-position-open = { $direction } from { $entry } (open)
+
+## Replay
+
+replay-idle = Not replaying
+replay-progress = { $state } · { $position }/{ $total } candles · { $phase } · { $speed }x
+replay-start = ▶ Replay
+replay-start-hint = Replay from the first candle
+replay-play = ▶ Play
+replay-pause = ⏸ Pause
+replay-step = ⏭ Step
+replay-step-hint = Reveal the next candle
+replay-stop = ⏹ Stop
+replay-speed-hint = Bars per second
+
+## Keyboard shortcuts
+
+shortcuts-title = Keyboard Shortcuts
+shortcuts-needs-modifier = { $combo } needs Ctrl or Alt (or use a function key).
+shortcuts-in-use = { $combo } is already used by "{ $action }"; pick another combination.
+shortcuts-defaults-failed = Failed to read the default shortcuts: { $error }
+shortcuts-conflict-hint = Also bound to another action
+shortcuts-capturing = Press keys... (Esc cancels)
+shortcuts-rebind = Rebind
+shortcuts-reset = Reset to defaults
+shortcut-command-palette = Open/close the command palette
+shortcut-load-csv = Load CSV data
+shortcut-save-project = Save the project
+shortcut-exit = Exit
+shortcut-zoom-in = Zoom the chart in
+shortcut-zoom-out = Zoom the chart out
+shortcut-reset-zoom = Show every candle
+shortcut-rerun-last-command = Re-run the last command
+shortcut-undo = Undo the last price level or indicator change
+shortcut-redo = Redo the last change undone
+
+## Engine connection
+
+connection-title = Engine Connection
+connection-edit-hint = Change the engine connection
+connection-test = Test connection
+connection-testing = Testing...
+connection-test-ok = The engine answered and is serving.
+connection-test-failed = Connection failed: { $error }
+connection-not-serving = the engine is reachable but not serving
+connection-timed-out = no answer within { $seconds }s
+connection-connect = Connect
+
+## Settings
+
+settings-title = Settings
+settings-save-failed = Failed to save the settings: { $error }
+error-settings-reload = Settings in { $path } not reloaded: { $error }
+error-config-problems = Fix these settings: { $problems }
+settings-field-error = { $field }: { $error }
+settings-invalid-number = "{ $value }" is not a valid number
+settings-invalid-toggle = "{ $value }" is not true or false
+settings-invalid-list-item = "{ $value }" is not valid in this list
+
+settings-section-general = General
+settings-section-engine = Engine
+settings-section-chart = Chart
+settings-section-indicators = Indicators
+settings-section-data = Data format
+
+settings-theme = Theme
+settings-language = Language
+settings-auto-save = Auto-save
+settings-auto-save-interval = Auto-save interval (s)
+settings-preferences-file = Preferences file
+settings-project-file = Project file
+settings-ui-scale = UI scale
+settings-font-size = Font size (px)
+settings-engine-embedded = Run the engine inside the GUI
+settings-engine-spawn = Start the engine with the GUI
+settings-engine-executable = Engine executable
+settings-engine-host = Host
+settings-engine-port = Port
+settings-engine-unix-socket = Unix socket
+settings-engine-namespace = Namespace
+settings-engine-compression = Compression
+settings-engine-health-check = Health check interval (s)
+settings-chart-type = Chart style
+settings-bullish-color = Bullish candles
+settings-bearish-color = Bearish candles
+settings-candle-border = Candle border width
+settings-wick-width = Wick width
+settings-background = Background
+settings-grid = Grid
+settings-grid-color = Grid color
+settings-grid-style = Grid style
+settings-sessions = Trading sessions
+settings-crosshair = Crosshair
+settings-crosshair-color = Crosshair color
+settings-scale-color = Scale text
+settings-scale-border = Scale border
+settings-sma-periods = SMA periods
+settings-sma-colors = SMA colors
+settings-sma-line-width = SMA line width
+settings-ema-periods = EMA periods
+settings-ema-colors = EMA colors
+settings-ema-line-width = EMA line width
+settings-rsi-period = RSI period
+settings-rsi-overbought = RSI overbought
+settings-rsi-oversold = RSI oversold
+settings-rsi-color = RSI color
+settings-csv-delimiter = CSV delimiter
+settings-decimal-separator = Decimal separator
+settings-thousand-separator = Thousand separator
+settings-date-format = Date format
+settings-time-format = Time format
+
+settings-option-dark = Dark
+settings-option-light = Light
+settings-option-dashed = Dashed
+settings-option-solid = Solid
+settings-option-dotted = Dotted
+settings-option-candlestick = Candlesticks
+settings-option-bar = OHLC bars
+settings-option-line = Line
+settings-option-area = Area
+settings-option-heikin-ashi = Heikin-Ashi
+settings-option-none = None
+settings-option-gzip = gzip
+settings-option-zstd = zstd
+settings-option-en-US = English (US)
+settings-option-pt-BR = Português (Brasil)
+
+## Settings validation
+
+invalid-engine-host = The engine host is empty.
+invalid-engine-port = The engine port must be between 1 and 65535.
+invalid-health-check = The health check interval must be at least 1 second.
+invalid-engine-spawn-embedded = The engine cannot both run inside the GUI and be started with it.
+invalid-engine-executable = The engine executable is empty.
+invalid-auto-save = The auto-save interval must be at least 1 second.
+invalid-preferences-file = The preferences file is empty.
+invalid-project-file = The project file is empty.
+invalid-ui-scale = The UI scale must be between 0.5 and 3.
+invalid-font-size = The font size must be between 8 and 32 pixels.
+invalid-color = { $name }: "{ $color }" is not a hex color such as #26a69a.
+invalid-periods = The { $indicator } periods must be whole numbers above 0.
+invalid-period-colors = Every { $indicator } period needs a color.
+invalid-indicator-colors = The { $indicator } colors must be hex colors such as #2196F3.
+invalid-line-width = The { $indicator } line width must be at least 1.
+invalid-rsi-period = The RSI period must be at least 1.
+invalid-rsi-levels = The RSI levels need oversold < overbought <= 100.
+invalid-separator = { $name } must be a single character.
+invalid-separators-equal = The decimal and thousand separators must differ.
+invalid-date-format = The date format "{ $format }" is not a valid strftime format.
+invalid-time-format = The time format "{ $format }" is not a valid strftime format.
+invalid-shortcut = The { $action } shortcut "{ $binding }" is not a key combination such as Ctrl+Shift+P.
+invalid-user-config = { $path } could not be read, so the default settings are used: { $error }
+backtest-results-title = Backtest { $strategy } on { $symbol }
+backtest-results-close = Close
+backtest-results-total-return = Total return
+backtest-results-total-pnl = Total P&L
+backtest-results-final-equity = Final equity
+backtest-results-trade-count = Trades
+backtest-results-win-rate = Win rate
+backtest-results-profit-factor = Profit factor
+backtest-results-sharpe = Sharpe ratio
+backtest-results-sortino = Sortino ratio
+backtest-results-max-drawdown = Max drawdown
+backtest-results-expectancy = Expectancy per trade
+backtest-results-avg-excursion = Avg MAE / MFE
+backtest-results-total-costs = Total costs
+backtest-results-benchmark = Buy and hold { $symbol }
+backtest-results-equity = Equity
+backtest-results-drawdown = Drawdown
+backtest-results-trades = Trades
+backtest-results-no-trades = The strategy closed no trades.
+backtest-results-direction = Side
+backtest-results-entry = Entry
+backtest-results-exit = Exit
+backtest-results-prices = Prices
+backtest-results-quantity = Quantity
+backtest-results-pnl = P&L
+backtest-results-return = Return
+backtest-results-show-trade = Show this trade on the chart
@@ -0,0 +1,427 @@
+# Textos da GUI do Home Trader, português (Brasil).
+# Cada mensagem precisa de uma correspondente em en-US.ftl.
+
+## Janela principal
+
+app-title = Home Trader
+app-palette-hint = Pressione '{ $shortcut }' para abrir/fechar a paleta de comandos.
+app-drop-hint = Solte arquivos CSV na janela para carregá-los.
+app-toggle-palette = Paleta de Comandos
+app-light-theme = Tema Claro
+app-dark-theme = Tema Escuro
+app-error = Erro: { $message }
+app-displaying = Exibindo: { $symbol }
+app-dismiss = Dispensar
+app-chart-style = Estilo do gráfico
+
+## Diálogos
+
+dialog-close = Fechar
+dialog-cancel = Cancelar
+csv-drop-title = Carregar { $file }
+csv-drop-symbol = Símbolo
+csv-drop-timeframe = Periodicidade
+csv-drop-load = Carregar
+csv-drop-skip = Pular
+csv-drop-remaining = Mais { $count } arquivos soltos a seguir
+csv-drop-no-symbol = Informe o símbolo com que o arquivo será carregado.
+csv-drop-not-csv = Só arquivos CSV podem ser carregados soltando-os: { $files }
+dialog-save = Salvar
+
+## Barra de status e painel do motor
+
+status-connecting = Conectando ao motor...
+status-connected = Motor conectado
+status-reconnecting = Motor sem resposta, reconectando (tentativa { $attempt }, próxima em { $seconds }s)...
+status-offline = Motor offline, nova tentativa em { $seconds }s
+status-chart = { $symbol } · { $timeframe } · { $candles } candles
+status-range = { $low } – { $high } ({ $change })
+status-updated = Atualizado às { $time }
+engine-not-connected = Motor: não conectado
+engine-info = Motor { $version } (API v{ $api_version }, { $profile }, { $target }), ativo há { $uptime } ao conectar
+engine-loaded = Carregado: { $series } séries, { $candles } candles
+engine-features = Recursos: { $features }
+engine-features-none = nenhum
+
+## Paleta de comandos
+
+palette-placeholder = Digite um comando...
+palette-no-match = Nenhum comando corresponde à busca.
+palette-recent = Usados recentemente
+palette-all = Todos os comandos
+
+command-load-csv = Carregar Dados CSV (Exemplo WINFUT)
+command-load-csv-desc = Importar dados de mercado do WINFUT de um arquivo CSV de exemplo
+command-add-sma = Adicionar Indicador: SMA
+command-add-sma-desc = Adicionar o indicador Média Móvel Simples
+command-add-ema = Adicionar Indicador: EMA
+command-add-ema-desc = Adicionar o indicador Média Móvel Exponencial
+command-add-rsi = Adicionar Indicador: RSI
+command-add-rsi-desc = Adicionar o indicador Índice de Força Relativa
+command-toggle-theme = Alternar Tema
+command-toggle-theme-desc = Alternar entre os temas escuro e claro
+command-backtest-sma-crossover = Backtest: Cruzamento de SMA
+command-backtest-sma-crossover-desc = Fazer o backtest da estratégia de cruzamento de SMA e mostrar suas operações no gráfico
+command-save-project = Salvar Projeto
+command-save-project-desc = Salvar os símbolos carregados, indicadores, desenhos e layout no arquivo de projeto
+command-open-project = Abrir Projeto
+command-open-project-desc = Restaurar o espaço de trabalho salvo no arquivo de projeto
+command-edit-shortcuts = Editar Atalhos de Teclado
+command-edit-shortcuts-desc = Redefinir os atalhos de teclado
+command-settings = Configurações
+command-settings-desc = Editar as configurações de gráfico, indicadores, formato de dados e motor
+command-switch-language = Idioma: { $language }
+command-switch-language-desc = Mostrar a interface em { $language }
+command-export-chart-png = Exportar Gráfico como PNG
+command-export-chart-png-desc = Salvar o gráfico com seus indicadores e desenhos como uma imagem PNG
+command-export-chart-svg = Exportar Gráfico como SVG
+command-export-chart-svg-desc = Salvar o gráfico com seus indicadores e desenhos como uma imagem SVG
+command-export-data-csv = Exportar Dados Visíveis como CSV
+command-export-data-csv-desc = Salvar os candles visíveis e os valores dos indicadores do gráfico como um arquivo CSV
+command-export-data-json = Exportar Dados Visíveis como JSON
+command-export-data-json-desc = Salvar os candles visíveis e os valores dos indicadores do gráfico como um arquivo JSON
+command-engine-connection = Conexão com o Motor
+command-engine-connection-desc = Mudar o endereço do motor e reconectar, ou testar uma conexão
+command-link-charts = Alternar Gráficos Vinculados
+command-link-charts-desc = Manter o gráfico nos mesmos horários (período visível e cursor) ao trocar de ativo
+command-backtest-results = Resultados do Backtest
+command-backtest-results-desc = Mostrar as métricas, a curva de capital e as operações do último backtest
+command-panel-watchlist = Mostrar/Ocultar Lista de Observação
+command-panel-watchlist-desc = Mostrar ou ocultar a lista de observação ao lado do gráfico
+command-panel-indicators = Mostrar/Ocultar Painel de Indicadores
+command-panel-indicators-desc = Mostrar ou ocultar a lista dos indicadores do gráfico e seus estilos
+command-panel-price-levels = Mostrar/Ocultar Níveis de Preço
+command-panel-price-levels-desc = Mostrar ou ocultar a lista dos níveis de preço do gráfico
+command-panel-alerts = Mostrar/Ocultar Alertas
+command-panel-alerts-desc = Mostrar ou ocultar os alertas do motor para o ativo do gráfico
+command-panel-replay = Mostrar/Ocultar Controles de Replay
+command-panel-replay-desc = Mostrar ou ocultar os controles de replay abaixo do gráfico
+command-open-recent = Abrir Recente: { $file }
+command-open-recent-csv-desc = Carregar este arquivo CSV novamente
+command-open-recent-project-desc = Abrir este projeto novamente
+command-limit-buy = Compra Limitada @ { $price }
+command-limit-buy-desc = Enviar uma ordem limitada simulada de compra a { $price }
+command-limit-sell = Venda Limitada @ { $price }
+command-limit-sell-desc = Enviar uma ordem limitada simulada de venda a { $price }
+command-add-alert = Adicionar Alerta @ { $price }
+command-add-alert-desc = Avisar quando o preço cruzar { $price }
+command-copy-price = Copiar Preço ({ $price })
+command-copy-price-desc = Copiar { $price } para a área de transferência
+command-copy-time = Copiar Horário ({ $time })
+command-copy-time-desc = Copiar { $time } para a área de transferência
+command-exit = Sair do Aplicativo
+command-exit-desc = Fechar o Home Trader
+command-chart-style = Estilo do Gráfico: { $style }
+command-chart-style-desc = Desenhar o gráfico como { $style }
+command-remove-indicator = Remover Indicador: { $name }
+command-remove-indicator-desc = Remover este indicador do símbolo atual
+
+## Progresso, avisos e erros
+
+loading-csv = Carregando { $file }
+loading-calculating = Calculando { $indicator }
+loading-recalculating = Recalculando { $indicator }
+loading-fetching = Buscando { $symbol }
+loading-backtest = Fazendo backtest de { $strategy } em { $symbol }
+loading-project = Abrindo projeto
+
+notice-project-saved = Projeto salvo em { $path }
+notice-settings-saved = Configurações salvas em { $path }
+notice-settings-saved-restart = Configurações salvas em { $path }. Reinicie para aplicar: { $settings }
+notice-settings-reloaded = Configurações recarregadas de { $path }
+notice-settings-reloaded-restart = Configurações recarregadas de { $path }. Reinicie para aplicar: { $settings }
+notice-charts-linked = Gráficos vinculados: trocar de ativo mantém os mesmos horários
+notice-charts-unlinked = Gráficos desvinculados
+notice-order-placed = Ordem enviada: { $message }
+notice-copied = Copiado: { $text }
+notice-chart-exported = Gráfico salvo em { $path }
+notice-data-exported = { $count } candles salvos em { $path }
+notice-backtest = Backtest de { $strategy } em { $symbol }: { $trades } operações
+notice-nothing-to-undo = Nada para desfazer.
+notice-nothing-to-redo = Nada para refazer.
+notice-level-crossed = { $symbol } cruzou { $level } (agora { $price })
+notice-alert-fired = Alerta de { $symbol }: { $alert } (em { $price })
+
+error-no-engine = Cliente do motor indisponível.
+error-alert-level = '{ $level }' não é um número.
+error-alert-create = Falha ao criar o alerta: { $error }
+error-connect = Falha ao conectar ao motor de negociação: { $error }
+error-spawn-engine = Falha ao iniciar o motor { $path }: { $error }
+engine-process-exited = O processo do motor terminou ({ $status })
+engine-process-timed-out = O motor não começou a atender em { $seconds } segundos
+error-api-version = O motor de negociação { $version } usa a versão { $api_version } da API, esta GUI espera a { $expected }; atualize os dois juntos
+error-engine-uri = Falha ao criar a URI do canal gRPC: { $error }
+error-engine-namespace = Namespace do motor inválido: { $error }
+error-engine-auth-token = Token de autenticação do motor inválido: { $error }
+error-import-no-result = A importação de CSV do motor não retornou resultado
+error-backtest-no-result = O backtest do motor não retornou resultado
+error-market-data = Falha ao obter os dados de mercado de { $symbol }: { $error }
+error-load-csv = Falha ao carregar o CSV { $file }: { $error }
+error-csv-not-found = { $file } não existe ou não pode ser aberto.
+error-csv-format = { $file } não é um CSV que o motor consiga ler: { $error }
+error-no-market-data = O motor não tem candles de { $symbol }; carregue o arquivo CSV primeiro.
+error-load-symbol = Falha ao carregar { $symbol } de { $file }: { $error }
+error-indicator-no-data = O indicador { $indicator } de { $symbol } não retornou dados.
+error-calculate-indicator = Falha ao calcular o indicador { $indicator } de { $symbol }: { $error }
+error-no-symbol-add-indicator = Nenhum símbolo ativo para adicionar o indicador.
+error-no-symbol-remove-indicator = Nenhum símbolo ativo para remover um indicador.
+error-no-symbol-backtest = Nenhum símbolo ativo para o backtest.
+error-no-backtest-results = Nenhum backtest executado: execute um pela paleta de comandos primeiro.
+error-backtest-symbol-not-loaded = { $symbol } não está carregado; carregue seus dados para mostrar a operação.
+error-backtest = Falha no backtest de { $strategy } em { $symbol }: { $error }
+error-save-project = Falha ao salvar o projeto em { $path }: { $error }
+error-open-project = Falha ao abrir o projeto { $path }: { $error }
+error-project-newer = { $path } foi salvo por uma versão mais nova (formato de projeto { $version }, esta GUI lê até o { $supported })
+error-no-chart = Nenhum gráfico para exportar.
+error-no-chart-open = Abra o gráfico de um ativo primeiro.
+error-place-order = Falha ao enviar a ordem em { $symbol }: { $error }
+error-order-rejected = Ordem rejeitada: { $message }
+error-export-chart = Falha ao exportar o gráfico: { $error }
+error-export-data = Falha ao exportar os dados de { $symbol }: { $error }
+error-write-file = Falha ao gravar { $path }
+error-replay-start = Falha ao iniciar o replay de { $symbol }: { $error }
+error-replay-control = Falha no comando de replay { $action } de { $symbol }: { $error }
+
+## Lista de observação e abas de símbolos
+
+watchlist-title = Lista de Observação
+watchlist-symbol-placeholder = Símbolo
+watchlist-add = Adicionar
+watchlist-empty = Adicione símbolos para acompanhar seus preços.
+watchlist-loading = Carregando...
+watchlist-remove = Remover da lista de observação
+symbols-empty = Nenhum símbolo carregado ainda.
+symbols-refresh = Atualizar os símbolos do motor
+
+## Painéis de indicadores e níveis de preço
+
+indicators-title = Indicadores ({ $symbol })
+indicators-empty = Nenhum ainda. Adicione um pela paleta de comandos.
+indicators-no-data = { $indicator } não retornou dados com estes parâmetros.
+indicators-recalculate-failed = Falha ao recalcular { $indicator } de { $symbol }: { $error }
+indicators-visible-hint = Mostrar no gráfico
+indicators-color-hint = Cor da linha
+indicators-width-hint = Espessura da linha
+indicators-dash-hint = Estilo da linha
+indicators-remove = Remover
+indicators-apply = Aplicar
+price-levels-title = Níveis de preço ({ $symbol })
+price-levels-empty = Clique no eixo de preços do gráfico para adicionar um.
+price-levels-alert-hint = Alertar quando o preço cruzar este nível
+price-levels-alert = Alerta
+price-levels-delete = Excluir
+alerts-title = Alertas ({ $symbol })
+alerts-empty = Nenhum aguardando. O motor os verifica a cada candle novo ou reproduzido.
+alerts-kind-price-cross = Preço cruza
+alerts-kind-indicator = Indicador cruza
+alerts-kind-percent-move = Variação percentual
+alerts-price = Preço
+alerts-threshold = Limite
+alerts-percent = % (negativo para queda)
+alerts-period = Período
+alerts-above = acima de
+alerts-below = abaixo de
+alerts-note = Nota
+alerts-add = Adicionar
+alerts-delete = Excluir
+alerts-price-cross = Cruza { $level }
+alerts-indicator-above = { $indicator }({ $period }) acima de { $level }
+alerts-indicator-below = { $indicator }({ $period }) abaixo de { $level }
+alerts-percent-move = Varia { $percent }% desde que foi criado
+
+## Gráfico
+
+chart-no-data = Nenhum dado de candle disponível.
+chart-range = Candles { $first }–{ $last } de { $total }
+chart-minimap-hint = Todos os candles: clique para mostrar esse período, arraste a faixa destacada para movê-la
+chart-legend-title = { $symbol } · { $timeframe }
+chart-legend-no-value = –
+chart-measure = { $change } ({ $percent }) · { $bars } barras · { $elapsed }
+trade-buy = COMPRA
+trade-sell = VENDA
+trade-fill = { $action } { $quantity } @ { $price }
+trade-pnl = Resultado { $pnl }
+trade-realized-pnl = Resultado realizado { $pnl }
+trade-long = Comprado
+trade-short = Vendido
+trade-position = { $direction } { $entry } → { $exit }
+trade-position-open = { $direction } desde { $entry } (aberta)
+
+## Replay
+
+replay-idle = Sem replay
+replay-progress = { $state } · { $position }/{ $total } candles · { $phase } · { $speed }x
+replay-start = ▶ Replay
+replay-start-hint = Reproduzir desde o primeiro candle
+replay-play = ▶ Reproduzir
+replay-pause = ⏸ Pausar
+replay-step = ⏭ Avançar
+replay-step-hint = Revelar o próximo candle
+replay-stop = ⏹ Parar
+replay-speed-hint = Barras por segundo
+
+## Atalhos de teclado
+
+shortcuts-title = Atalhos de Teclado
+shortcuts-needs-modifier = { $combo } precisa de Ctrl ou Alt (ou use uma tecla de função).
+shortcuts-in-use = { $combo } já é usado por "{ $action }"; escolha outra combinação.
+shortcuts-defaults-failed = Falha ao ler os atalhos padrão: { $error }
+shortcuts-conflict-hint = Também associado a outra ação
+shortcuts-capturing = Pressione as teclas... (Esc cancela)
+shortcuts-rebind = Redefinir
+shortcuts-reset = Restaurar padrões
+shortcut-command-palette = Abrir/fechar a paleta de comandos
+shortcut-load-csv = Carregar dados CSV
+shortcut-save-project = Salvar o projeto
+shortcut-exit = Sair
+shortcut-zoom-in = Aproximar o gráfico
+shortcut-zoom-out = Afastar o gráfico
+shortcut-reset-zoom = Mostrar todos os candles
+shortcut-rerun-last-command = Repetir o último comando
+shortcut-undo = Desfazer a última alteração de nível de preço ou indicador
+shortcut-redo = Refazer a última alteração desfeita
+
+## Conexão com o motor
+
+connection-title = Conexão com o Motor
+connection-edit-hint = Mudar a conexão com o motor
+connection-test = Testar conexão
+connection-testing = Testando...
+connection-test-ok = O motor respondeu e está atendendo.
+connection-test-failed = Falha na conexão: { $error }
+connection-not-serving = o motor está acessível mas não está atendendo
+connection-timed-out = sem resposta em { $seconds }s
+connection-connect = Conectar
+
+## Configurações
+
+settings-title = Configurações
+settings-save-failed = Falha ao salvar as configurações: { $error }
+error-settings-reload = Configurações de { $path } não recarregadas: { $error }
+error-config-problems = Corrija estas configurações: { $problems }
+settings-field-error = { $field }: { $error }
+settings-invalid-number = "{ $value }" não é um número válido
+settings-invalid-toggle = "{ $value }" não é verdadeiro nem falso
+settings-invalid-list-item = "{ $value }" não é válido nesta lista
+
+settings-section-general = Geral
+settings-section-engine = Motor
+settings-section-chart = Gráfico
+settings-section-indicators = Indicadores
+settings-section-data = Formato de dados
+
+settings-theme = Tema
+settings-language = Idioma
+settings-auto-save = Salvamento automático
+settings-auto-save-interval = Intervalo de salvamento (s)
+settings-preferences-file = Arquivo de preferências
+settings-project-file = Arquivo de projeto
+settings-ui-scale = Escala da interface
+settings-font-size = Tamanho da fonte (px)
+settings-engine-embedded = Rodar o motor dentro da GUI
+settings-engine-spawn = Iniciar o motor com a GUI
+settings-engine-executable = Executável do motor
+settings-engine-host = Host
+settings-engine-port = Porta
+settings-engine-unix-socket = Socket Unix
+settings-engine-namespace = Namespace
+settings-engine-compression = Compressão
+settings-engine-health-check = Intervalo de verificação (s)
+settings-chart-type = Estilo do gráfico
+settings-bullish-color = Candles de alta
+settings-bearish-color = Candles de baixa
+settings-candle-border = Espessura da borda do candle
+settings-wick-width = Espessura do pavio
+settings-background = Fundo
+settings-grid = Grade
+settings-grid-color = Cor da grade
+settings-grid-style = Estilo da grade
+settings-sessions = Pregões
+settings-crosshair = Mira
+settings-crosshair-color = Cor da mira
+settings-scale-color = Texto das escalas
+settings-scale-border = Borda das escalas
+settings-sma-periods = Períodos da SMA
+settings-sma-colors = Cores da SMA
+settings-sma-line-width = Espessura da SMA
+settings-ema-periods = Períodos da EMA
+settings-ema-colors = Cores da EMA
+settings-ema-line-width = Espessura da EMA
+settings-rsi-period = Período do RSI
+settings-rsi-overbought = RSI sobrecomprado
+settings-rsi-oversold = RSI sobrevendido
+settings-rsi-color = Cor do RSI
+settings-csv-delimiter = Delimitador do CSV
+settings-decimal-separator = Separador decimal
+settings-thousand-separator = Separador de milhar
+settings-date-format = Formato de data
+settings-time-format = Formato de hora
+
+settings-option-dark = Escuro
+settings-option-light = Claro
+settings-option-dashed = Tracejada
+settings-option-solid = Contínua
+settings-option-dotted = Pontilhada
+settings-option-candlestick = Candles
+settings-option-bar = Barras OHLC
+settings-option-line = Linha
+settings-option-area = Área
+settings-option-heikin-ashi = Heikin-Ashi
+settings-option-none = Nenhuma
+settings-option-gzip = gzip
+settings-option-zstd = zstd
+settings-option-en-US = English (US)
+settings-option-pt-BR = Português (Brasil)
+
+## Validação das configurações
+
+invalid-engine-host = O host do motor está vazio.
+invalid-engine-port = A porta do motor deve estar entre 1 e 65535.
+invalid-health-check = O intervalo de verificação deve ser de pelo menos 1 segundo.
+invalid-engine-spawn-embedded = O motor não pode rodar dentro da GUI e também ser iniciado com ela.
+invalid-engine-executable = O executável do motor está vazio.
+invalid-auto-save = O intervalo de salvamento deve ser de pelo menos 1 segundo.
+invalid-preferences-file = O arquivo de preferências está vazio.
+invalid-project-file = O arquivo de projeto está vazio.
+invalid-ui-scale = A escala da interface deve estar entre 0,5 e 3.
+invalid-font-size = O tamanho da fonte deve estar entre 8 e 32 pixels.
+invalid-color = { $name }: "{ $color }" não é uma cor hexadecimal como #26a69a.
+invalid-periods = Os períodos da { $indicator } devem ser números inteiros acima de 0.
+invalid-period-colors = Cada período da { $indicator } precisa de uma cor.
+invalid-indicator-colors = As cores da { $indicator } devem ser cores hexadecimais como #2196F3.
+invalid-line-width = A espessura da { $indicator } deve ser de pelo menos 1.
+invalid-rsi-period = O período do RSI deve ser de pelo menos 1.
+invalid-rsi-levels = Os níveis do RSI precisam de sobrevendido < sobrecomprado <= 100.
+invalid-separator = { $name } deve ter um único caractere.
+invalid-separators-equal = Os separadores decimal e de milhar devem ser diferentes.
+invalid-date-format = O formato de data "{ $format }" não é um formato strftime válido.
+invalid-time-format = O formato de hora "{ $format }" não é um formato strftime válido.
+invalid-shortcut = O atalho de { $action } "{ $binding }" não é uma combinação de teclas como Ctrl+Shift+P.
+invalid-user-config = Não foi possível ler { $path }, então as configurações padrão são usadas: { $error }
+backtest-results-title = Backtest { $strategy } em { $symbol }
+backtest-results-close = Fechar
+backtest-results-total-return = Retorno total
+backtest-results-total-pnl = L&P total
+backtest-results-final-equity = Capital final
+backtest-results-trade-count = Operações
+backtest-results-win-rate = Taxa de acerto
+backtest-results-profit-factor = Fator de lucro
+backtest-results-sharpe = Índice de Sharpe
+backtest-results-sortino = Índice de Sortino
+backtest-results-max-drawdown = Drawdown máximo
+backtest-results-expectancy = Expectativa por operação
+backtest-results-avg-excursion = MAE / MFE médios
+backtest-results-total-costs = Custos totais
+backtest-results-benchmark = Comprar e manter { $symbol }
+backtest-results-equity = Capital
+backtest-results-drawdown = Drawdown
+backtest-results-trades = Operações
+backtest-results-no-trades = A estratégia não fechou nenhuma operação.
+backtest-results-direction = Lado
+backtest-results-entry = Entrada
+backtest-results-exit = Saída
+backtest-results-prices = Preços
+backtest-results-quantity = Quantidade
+backtest-results-pnl = L&P
+backtest-results-return = Retorno
+backtest-results-show-trade = Mostrar esta operação no gráfico
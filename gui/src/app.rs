@@ -1,20 +1,50 @@
 #![allow(non_snake_case)]
 use dioxus::prelude::*;
+use dioxus::html::HasFileData; // Paths of the files dropped on the window
 use dioxus_desktop::use_window; // For DesktopContext and use_window
+use dioxus_desktop::{tao::event::Event as WindowLoopEvent, use_wry_event_handler};
 
 // Import necessary types
 use crate::components::command_palette::CommandPalette;
+use crate::components::commands::{run_shortcut, CommandContext};
+use crate::components::connection_dialog::ConnectionDialog;
+use crate::components::csv_drop_dialog::CsvDropDialog;
+use crate::components::settings_dialog::SettingsDialog;
+use crate::components::shortcut_editor::ShortcutEditor;
+use crate::components::engine_status::EngineStatus;
+use crate::components::alert_panel::AlertPanel;
+use crate::components::backtest_results::BacktestResultsView;
+use crate::components::indicator_panel::IndicatorPanel;
+use crate::components::loading_overlay::LoadingOverlay;
+use crate::components::price_levels::PriceLevelPanel;
+use crate::components::replay_controls::ReplayControls;
+use crate::components::status_bar::StatusBar;
+use crate::components::symbol_tabs::SymbolTabs;
+use crate::components::watchlist::Watchlist;
 use crate::components::chart::candlestick::CandlestickChart;
-use crate::config::AppConfig;
-use crate::state::app_state::AppState;
+use crate::components::chart::context_menu::{ChartContextMenu, ChartPoint};
+use crate::components::chart::style::ChartType;
+use crate::components::chart::viewport::LinkedView;
+use crate::config::{AppConfig, DEFAULT_PRICE_DECIMALS};
+use crate::config::shortcuts::KeyCombo;
+use crate::i18n::Language;
+use crate::state::app_state::{AppState, Panel, Theme};
+use crate::state::preferences::{save_theme, UserPreferences};
+use crate::state::csv_drop::DroppedCsv;
+use crate::state::session::{session_path, SessionState};
+use crate::services::config_reload::watch_user_config;
+use crate::services::connection::maintain_connection;
 use crate::services::engine_client::EngineClient;
+use crate::services::engine_process;
+use crate::services::session::keep_session;
 use shared::models::{Candle, Indicator}; // Candle & Indicator used for CandlestickChart props
 
 #[component]
 pub fn App() -> Element {
-    // Load AppConfig
-    let app_config = match AppConfig::load_default() {
-        Ok(config) => config,
+    // Load AppConfig: the defaults with the user's saved settings over them. What is wrong with the
+    // user's settings is shown until fixed (saving the fixed file reloads it)
+    let (app_config, config_problems) = match AppConfig::load_checked() {
+        Ok(loaded) => loaded,
         Err(e) => {
             // Consider a more graceful error display than panic in a real app
             panic!("Failed to load default configuration: {:?}", e);
@@ -22,8 +52,31 @@ pub fn App() -> Element {
     };
 
     // Provide AppState, AppConfig, and EngineClient (Option) to the component tree
-    use_shared_state_provider(AppState::default);
-    use_shared_state_provider(|| app_config.clone());
+    // The theme and language picked last time win over the configured ones, and the last session's
+    // panels, recent files and chart come back
+    use_shared_state_provider(|| {
+        let preferences = UserPreferences::load(&app_config.app.preferences_file);
+        let theme = preferences.theme.unwrap_or_else(|| Theme::from_name(&app_config.app.theme));
+        let chart_type = ChartType::from_name(&app_config.chart.chart_type);
+        let mut app_state = AppState { current_theme: theme, watchlist: preferences.watchlist, chart_type, ..AppState::default() };
+        app_state.set_language(preferences.language.unwrap_or_else(|| Language::from_code(&app_config.app.language)));
+        SessionState::load(&session_path()).apply(&mut app_state);
+        if !config_problems.is_empty() {
+            for problem in &config_problems {
+                tracing::warn!("Config problem: {}", problem);
+            }
+            app_state.error_message = Some(t!("error-config-problems", problems = config_problems.join(" ")));
+        }
+        app_state
+    });
+    // Shortcuts rebound in the shortcut editor replace the configured ones
+    use_shared_state_provider(|| {
+        let mut config = app_config.clone();
+        if let Some(shortcuts) = UserPreferences::load(&app_config.app.preferences_file).shortcuts {
+            config.shortcuts = shortcuts;
+        }
+        config
+    });
     use_shared_state_provider::<Option<EngineClient>>(|| None);
 
 
@@ -32,30 +85,38 @@ pub fn App() -> Element {
     let app_config_ref = use_shared_state::<AppConfig>().unwrap(); // Removed cx
     let engine_client_ref = use_shared_state::<Option<EngineClient>>().unwrap(); // Removed cx
 
-    // Clone necessary items for the async future
-    let app_config_clone_for_future = app_config_ref.read().clone();
-    let mut engine_client_writer = engine_client_ref.clone(); // Clone the UseSharedState handle
-    let app_state_handle_for_future = app_state_ref.clone(); // Clone for the future
-
-    // Initialize EngineClient asynchronously
-    use_future((), move |_| { // Removed cx, added move for captures
-        // app_config_clone_for_future, engine_client_writer are captured by move
-        // app_state_handle_for_future is also captured
-        let engine_config_captured = app_config_clone_for_future.engine.clone(); // Clone data for async block
+    // Connect to the engine and keep health-checking it for the status bar
+    let engine_client_handle = engine_client_ref.clone();
+    let app_state_handle_for_future = app_state_ref.clone();
+    let app_config_handle_for_future = app_config_ref.clone();
+    use_future((), move |_| maintain_connection(app_state_handle_for_future.clone(), engine_client_handle.clone(), app_config_handle_for_future.clone()));
+    // Saving the user config file in an editor applies it, as saving the settings dialog does
+    let app_state_handle_for_config = app_state_ref.clone();
+    let app_config_handle_for_config = app_config_ref.clone();
+    use_future((), move |_| watch_user_config(app_state_handle_for_config.clone(), app_config_handle_for_config.clone()));
+    // An engine the GUI started (`engine.spawn`) stops with it
+    use_wry_event_handler(|event, _| {
+        if let WindowLoopEvent::LoopDestroyed = event {
+            engine_process::stop();
+        }
+    });
+    // Save the session as it changes
+    let app_state_handle_for_session = app_state_ref.clone();
+    let session_window = window.clone();
+    use_future((), move |_| {
+        let path = session_path();
+        let saved = SessionState::load(&path);
+        keep_session(app_state_handle_for_session.clone(), session_window.clone(), path, saved)
+    });
 
-        async move {
-            let endpoint = format!("http://{}:{}", engine_config_captured.host, engine_config_captured.port);
-            match EngineClient::new(endpoint).await {
-                Ok(client) => {
-                    *engine_client_writer.write() = Some(client);
-                    tracing::info!("Successfully connected to trading engine.");
-                }
-                Err(e) => {
-                    let error_msg = format!("Failed to connect to trading engine: {}", e);
-                    tracing::error!("{}", error_msg);
-                    app_state_handle_for_future.write().error_message = Some(error_msg);
-                }
-            }
+    // Scale the window and size its text as configured: sizes are in rem, relative to the root font
+    let ui_scale = app_config_ref.read().app.ui_scale;
+    let root_font_size = app_config_ref.read().app.root_font_size();
+    let display_window = window.clone();
+    use_effect((ui_scale, root_font_size), move |(ui_scale, root_font_size)| {
+        eval(&format!("document.documentElement.style.fontSize = '{}px';", root_font_size));
+        if let Err(e) = display_window.webview.zoom(ui_scale) {
+            tracing::warn!("Failed to scale the window to {}: {}", ui_scale, e);
         }
     });
 
@@ -63,24 +124,42 @@ pub fn App() -> Element {
     let app_state_reader = app_state_ref.read();
     let display_candles = app_state_reader.current_candles_display.clone();
     let display_indicators = app_state_reader.current_indicators_display.clone();
-    let is_loading = app_state_reader.is_loading;
+    let display_indicator_lines = app_state_reader.current_indicator_lines(&app_config_ref.read().indicators);
+    let display_price_levels = app_state_reader.current_price_levels();
+    let display_trades = app_state_reader.current_trades();
+    let chart_zoom_request = app_state_reader.chart_zoom_request;
+    let chart_type = app_state_reader.chart_type;
+    let linked_view = app_state_reader.linked_view.clone().filter(|_| app_state_reader.charts_linked);
     let error_message = app_state_reader.error_message.clone();
+    let notice = app_state_reader.notice.clone();
     let current_symbol = app_state_reader.current_symbol_display.clone();
+    let current_timeframe = current_symbol.as_ref()
+        .and_then(|symbol| app_state_reader.all_market_data.get(symbol))
+        .map(|market_data| market_data.timeframe);
+    let open_panels = app_state_reader.open_panels.clone();
+    let palette = app_state_reader.palette();
+    let theme_button_label = if app_state_reader.current_theme == Theme::Dark { t!("app-light-theme") } else { t!("app-dark-theme") };
     // Drop the read lock
     drop(app_state_reader);
 
-    // State for tracking Ctrl key press for global shortcut
-    let ctrl_pressed_for_shortcut = use_ref(|| false);
-    // Clone necessary handles for onkeydown/onkeyup closures
-    let app_state_for_shortcut_handler = app_state_ref.clone();
-    let app_config_for_shortcut_handler = app_config_ref.read().clone();
+    let preferences_file = app_config_ref.read().app.preferences_file.clone();
+    let chart_palette = palette.clone().with_candle_colors(&app_config_ref.read().chart.candle);
+    let crosshair = Some(app_config_ref.read().chart.crosshair.clone()).filter(|crosshair| crosshair.enabled);
+    let sessions = app_config_ref.read().chart.sessions;
+    let price_decimals = current_symbol.as_deref().map_or(DEFAULT_PRICE_DECIMALS, |symbol| app_config_ref.read().price_decimals(symbol));
+    let command_context = CommandContext {
+        app_state: app_state_ref.clone(),
+        app_config: app_config_ref.clone(),
+        engine_client: engine_client_ref.clone(),
+        window: window.clone(),
+    };
 
 
     rsx! {
         div {
             // Make the root div focusable and handle key events for global-like shortcuts
             tabindex: "0", // Important for receiving focus and key events
-            style: "outline: none; width: 100%; height: 100%;", // Remove default focus outline
+            style: "outline: none; width: 100%; height: 100%; font-size: 1.6rem;", // Remove default focus outline
             onmounted: move |event| {
                 // Attempt to focus the div when it's mounted to catch keyboard events.
                 // This might require specific handling based on Dioxus version for focusing elements.
@@ -88,64 +167,158 @@ pub fn App() -> Element {
                 // Or, use JS interop to focus if absolutely necessary.
                 tracing::info!("Root div mounted. Set tabindex=0 to allow focus for keyboard shortcuts.");
             },
+            // Every configured shortcut goes through the dispatcher; none fire while they are being rebound
             onkeydown: move |event: Event<KeyboardData>| {
-                let shortcut_str = app_config_for_shortcut_handler.shortcuts.command_palette.to_lowercase();
-                if event.key().to_string().to_lowercase() == "control" {
-                    *ctrl_pressed_for_shortcut.write() = true;
-                } else if *ctrl_pressed_for_shortcut.read() && event.key().to_string().to_lowercase() == shortcut_str.trim_start_matches("ctrl+") {
-                    let mut app_state_writer = app_state_for_shortcut_handler.write();
-                    app_state_writer.command_palette_visible = !app_state_writer.command_palette_visible;
-                    if app_state_writer.command_palette_visible {
-                        tracing::info!("Command Palette Toggled ON via shortcut. Input field should autofocus.");
-                    }
+                let modifiers = event.modifiers();
+                let combo = KeyCombo::new(modifiers.ctrl() || modifiers.meta(), modifiers.shift(), modifiers.alt(), &event.key().to_string());
+                if combo.is_modifier_only() || app_state_ref.read().shortcut_editor_visible {
+                    return;
+                }
+                let action = app_config_ref.read().shortcuts.action_for(&combo);
+                if let Some(action) = action {
+                    tracing::debug!(%combo, ?action, "Keyboard shortcut");
+                    run_shortcut(action, &command_context);
                 }
             },
-            onkeyup: move |event: Event<KeyboardData>| {
-                if event.key().to_string().to_lowercase() == "control" {
-                    *ctrl_pressed_for_shortcut.write() = false;
+            // CSV files dropped anywhere on the window are loaded after asking for their symbol and timeframe
+            prevent_default: "ondragover ondrop",
+            ondragover: move |_| {},
+            ondrop: move |event: Event<DragData>| {
+                let Some(files) = event.files() else { return };
+                let (csvs, others): (Vec<String>, Vec<String>) = files.files().into_iter().partition(|path| DroppedCsv::is_csv(path));
+                let dropped: Vec<DroppedCsv> = csvs.iter().map(|path| DroppedCsv::inspect(path, &app_config_ref.read())).collect();
+                let mut app_state_writer = app_state_ref.write();
+                if !others.is_empty() {
+                    app_state_writer.error_message = Some(t!("csv-drop-not-csv", files = others.join(", ")));
                 }
+                app_state_writer.dropped_csvs.extend(dropped);
             },
 
             // Render the CommandPalette component
             CommandPalette {},
+            ShortcutEditor {},
+            SettingsDialog {},
+            BacktestResultsView {},
+            ConnectionDialog {},
+            CsvDropDialog {},
+            ChartContextMenu {},
             // Main content area
             div {
                 id: "main-content",
-                style: "padding: 20px; color: #ddd; background-color: #1e1e1e; height: calc(100vh - 40px); display: flex; flex-direction: column; align-items: center;", // Adjusted style
+                style: "padding: 20px; color: {palette.foreground}; background-color: {palette.background}; height: calc(100vh - 40px); display: flex; flex-direction: column; align-items: center;", // Adjusted style
 
                 // Title and Command Palette toggle info
                 div {
                     style: "text-align: center; margin-bottom: 20px;",
-                    h1 { "Home Trader" }
+                    h1 { {t!("app-title")} }
                     // Use app_config_ref for shortcut display
-                    p { "Press '{app_config_ref.read().shortcuts.command_palette}' to open/close the command palette." }
+                    p { {t!("app-palette-hint", shortcut = app_config_ref.read().shortcuts.command_palette)} }
+                    p { style: "color: {palette.muted};", {t!("app-drop-hint")} }
                     button {
                         // Use app_state_ref for onclick
                         onclick: move |_| app_state_ref.write().command_palette_visible = !app_state_ref.read().command_palette_visible,
-                        style: "padding: 8px 12px; background-color: #007bff; color: white; border: none; border-radius: 4px; cursor: pointer;",
-                        "Toggle Command Palette"
+                        style: "padding: 8px 12px; background-color: {palette.primary}; color: white; border: none; border-radius: 4px; cursor: pointer;",
+                        {t!("app-toggle-palette")}
+                    }
+                    button {
+                        onclick: move |_| save_theme(&preferences_file, app_state_ref.write().toggle_theme()),
+                        style: "margin-left: 8px; padding: 8px 12px; background-color: {palette.secondary}; color: {palette.foreground}; border: none; border-radius: 4px; cursor: pointer;",
+                        "{theme_button_label}"
+                    }
+                    select {
+                        title: t!("app-chart-style"),
+                        style: "margin-left: 8px; padding: 7px; background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border}; border-radius: 4px;",
+                        value: "{chart_type.name()}",
+                        onchange: move |evt: FormEvent| app_state_ref.write().chart_type = ChartType::from_name(&evt.value),
+                        {ChartType::ALL.iter().map(|style| rsx! {
+                            option { key: "{style.name()}", value: "{style.name()}", selected: *style == chart_type, {t!(format!("settings-option-{}", style.name()))} }
+                        })}
                     }
                 }
 
-                // Display loading status and error messages
-                {is_loading.then(|| rsx! { p { style: "color: yellow;", "Loading data..." } })}
-                {error_message.as_ref().map(|err_msg| rsx! { p { style: "color: red;", "Error: {err_msg}" } })}
-                {current_symbol.as_ref().map(|symbol| rsx! { h3 { "Displaying: {symbol}" } })}
-
+                // Display loading progress and error messages
+                LoadingOverlay {}
+                {error_message.as_ref().map(|err_msg| rsx! { p { style: "color: {palette.chart_bearish}; white-space: pre-line;", {t!("app-error", message = err_msg)} } })}
+                {notice.as_ref().map(|notice| rsx! {
+                    p {
+                        style: "color: {palette.accent}; cursor: pointer;",
+                        title: t!("app-dismiss"),
+                        onclick: move |_| app_state_ref.write().notice = None,
+                        "🔔 {notice}"
+                    }
+                })}
+                {current_symbol.as_ref().map(|symbol| rsx! { h3 { {t!("app-displaying", symbol = symbol)} } })}
+                EngineStatus {}
+                SymbolTabs {}
 
-                // Candlestick Chart
+                // Candlestick Chart between the watchlist and the indicator panel
                 div {
-                    style: "margin-top: 20px; border: 1px solid #555; box-shadow: 0 0 10px rgba(0,0,0,0.5);",
-                    // Pass dynamic data to CandlestickChart
-                    // Ensure display_candles and display_indicators are correctly typed for the chart
-                    // The chart component will need to handle Option<Vec<Candle>>
-                    CandlestickChart {
-                        candles: display_candles.unwrap_or_default(), // Pass empty vec if None, or chart handles Option
-                        width: 800.0,
-                        height: 450.0,
-                        indicator_data: Some(display_indicators) // Pass current indicators
+                    style: "display: flex; margin-top: 20px;",
+                    if open_panels.contains(&Panel::Watchlist) {
+                        div {
+                            style: "margin-right: 12px;",
+                            Watchlist {}
+                        }
+                    }
+                    div {
+                        style: "border: 1px solid {palette.border}; box-shadow: 0 0 10px rgba(0,0,0,0.5);",
+                        // Pass dynamic data to CandlestickChart
+                        // Ensure display_candles and display_indicators are correctly typed for the chart
+                        // The chart component will need to handle Option<Vec<Candle>>
+                        CandlestickChart {
+                            candles: display_candles.unwrap_or_default(), // Pass empty vec if None, or chart handles Option
+                            width: 800.0,
+                            height: 450.0,
+                            indicator_data: Some(display_indicators), // Pass current (visible) indicators
+                            indicator_lines: Some(display_indicator_lines),
+                            palette: chart_palette,
+                            price_levels: Some(display_price_levels),
+                            on_price_level: move |price: f64| {
+                                let mut app_state_writer = app_state_ref.write();
+                                if let Some(symbol) = app_state_writer.current_symbol_display.clone() {
+                                    app_state_writer.record_edit(&symbol);
+                                    app_state_writer.add_price_level(&symbol, price);
+                                }
+                            },
+                            trades: Some(display_trades),
+                            zoom_request: chart_zoom_request,
+                            symbol: current_symbol.clone(),
+                            timeframe: current_timeframe,
+                            crosshair: crosshair,
+                            chart_type: chart_type,
+                            sessions: sessions,
+                            price_decimals: price_decimals,
+                            linked_view: linked_view,
+                            on_view_change: move |view: LinkedView| {
+                                let visible = LinkedView { crosshair: None, ..view.clone() };
+                                if app_state_ref.read().visible_view.as_ref() != Some(&visible) {
+                                    app_state_ref.write().visible_view = Some(visible);
+                                }
+                                let linked = app_state_ref.read().charts_linked && app_state_ref.read().linked_view.as_ref() != Some(&view);
+                                if linked {
+                                    app_state_ref.write().linked_view = Some(view);
+                                }
+                            },
+                            on_context_menu: move |point: ChartPoint| app_state_ref.write().chart_context_menu = Some(point)
+                        }
                     }
+                    div {
+                        style: "display: flex; flex-direction: column; margin-left: 12px;",
+                        if open_panels.contains(&Panel::Indicators) {
+                            IndicatorPanel {}
+                        }
+                        if open_panels.contains(&Panel::PriceLevels) {
+                            PriceLevelPanel {}
+                        }
+                        if open_panels.contains(&Panel::Alerts) {
+                            AlertPanel {}
+                        }
+                    }
+                }
+                if open_panels.contains(&Panel::Replay) {
+                    ReplayControls {}
                 }
+                StatusBar {}
                 // Placeholder for other UI elements like Toolbar, Indicator controls etc.
             }
         }
@@ -0,0 +1,181 @@
+// Alert panel
+// Lists the current symbol's engine alerts waiting to fire, with a button to delete each, and a form
+// to set new ones: the price crossing a level, an indicator crossing a threshold, or a percent move.
+// The engine checks them against loaded and replayed candles; see `services::alerts`.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+use engine::services::CreateAlertRequest;
+
+use crate::services::alerts::alert_description;
+use crate::services::engine_client::EngineClient;
+use crate::state::app_state::AppState;
+
+/// Kinds of alert offered, as CreateAlert names them; the alerts-kind-{name} message labels them.
+const ALERT_KINDS: [&str; 3] = ["PRICE_CROSS", "INDICATOR", "PERCENT_MOVE"];
+/// Indicators an alert can watch.
+const ALERT_INDICATORS: [&str; 4] = ["RSI", "SMA", "EMA", "ATR"];
+
+#[component]
+pub fn AlertPanel() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let engine_client = use_shared_state::<Option<EngineClient>>().unwrap();
+    let kind = use_state(|| "PRICE_CROSS");
+    let level = use_state(String::new);
+    let indicator = use_state(|| "RSI");
+    let period = use_state(|| "14".to_string());
+    let direction = use_state(|| "ABOVE");
+    let note = use_state(String::new);
+
+    let app_state_reader = app_state.read();
+    let Some(symbol) = app_state_reader.current_symbol_display.clone() else {
+        return None;
+    };
+    let alerts = app_state_reader.current_engine_alerts();
+    let palette = app_state_reader.palette();
+    drop(app_state_reader);
+
+    let add = {
+        let symbol = symbol.clone();
+        move |_| {
+            let Some(mut client) = engine_client.read().as_ref().cloned() else {
+                app_state.write().error_message = Some(t!("error-no-engine"));
+                return;
+            };
+            let Ok(level_value) = level.get().trim().replace(',', ".").parse::<f64>() else {
+                app_state.write().error_message = Some(t!("error-alert-level", level = level.get().clone()));
+                return;
+            };
+            let request = CreateAlertRequest {
+                symbol: symbol.clone(),
+                kind: kind.get().to_string(),
+                level: level_value,
+                indicator: indicator.get().to_string(),
+                period: period.get().trim().parse().unwrap_or(0),
+                direction: direction.get().to_string(),
+                note: note.get().clone(),
+                ..Default::default()
+            };
+            let (level, note) = (level.clone(), note.clone());
+            spawn(async move {
+                match client.create_alert(request).await {
+                    Ok(alert) => {
+                        app_state.write().engine_alerts.push(alert);
+                        level.set(String::new());
+                        note.set(String::new());
+                    }
+                    Err(e) => app_state.write().error_message = Some(t!("error-alert-create", error = e)),
+                }
+            });
+        }
+    };
+
+    let input_style = format!("background-color: {}; color: {}; border: 1px solid {};", palette.surface, palette.foreground, palette.border);
+    let level_hint = match *kind.get() {
+        "INDICATOR" => t!("alerts-threshold"),
+        "PERCENT_MOVE" => t!("alerts-percent"),
+        _ => t!("alerts-price"),
+    };
+
+    rsx! {
+        div {
+            class: "alert-panel",
+            style: "width: 240px; margin-top: 12px; border: 1px solid {palette.border}; border-radius: 4px; padding: 8px; font-size: 1.2rem; color: {palette.foreground};",
+            h4 { style: "margin: 0 0 8px 0;", {t!("alerts-title", symbol = symbol)} }
+            if alerts.is_empty() {
+                p { style: "color: {palette.muted};", {t!("alerts-empty")} }
+            }
+            {alerts.into_iter().map(|alert| {
+                let description = alert_description(&alert);
+                let alert_id = alert.alert_id.clone();
+                rsx! {
+                    div {
+                        key: "{alert.alert_id}",
+                        style: "display: flex; align-items: center; gap: 6px; border-top: 1px solid {palette.border}; padding: 4px 0;",
+                        span { style: "flex: 1;", title: "{alert.note}", "{description}" }
+                        button {
+                            title: t!("alerts-delete"),
+                            style: "background: none; border: none; color: {palette.chart_bearish}; cursor: pointer;",
+                            onclick: move |_| {
+                                let Some(mut client) = engine_client.read().as_ref().cloned() else { return };
+                                let alert_id = alert_id.clone();
+                                spawn(async move {
+                                    let result = client.delete_alert(alert_id.clone()).await;
+                                    let mut app_state_writer = app_state.write();
+                                    // Gone either way: deleted, or it fired meanwhile
+                                    app_state_writer.engine_alerts.retain(|waiting| waiting.alert_id != alert_id);
+                                    if let Err(e) = result {
+                                        tracing::warn!(%alert_id, "Failed to delete alert: {}", e);
+                                    }
+                                });
+                            },
+                            "✕"
+                        }
+                    }
+                }
+            })}
+            div {
+                style: "display: flex; flex-wrap: wrap; gap: 4px; border-top: 1px solid {palette.border}; padding-top: 6px;",
+                select {
+                    value: "{kind.get()}",
+                    style: "{input_style}",
+                    onchange: move |evt: FormEvent| {
+                        let chosen = ALERT_KINDS.into_iter().find(|name| *name == evt.value).unwrap_or("PRICE_CROSS");
+                        kind.set(chosen);
+                    },
+                    {ALERT_KINDS.iter().map(|name| rsx! {
+                        option { key: "{name}", value: "{name}", selected: *name == *kind.get(), {t!(format!("alerts-kind-{}", name.to_lowercase().replace('_', "-")))} }
+                    })}
+                }
+                input {
+                    r#type: "number",
+                    step: "any",
+                    placeholder: "{level_hint}",
+                    title: "{level_hint}",
+                    value: "{level.get()}",
+                    style: "width: 80px; {input_style}",
+                    oninput: move |evt: FormEvent| level.set(evt.value.clone()),
+                }
+                if *kind.get() == "INDICATOR" {
+                    select {
+                        value: "{indicator.get()}",
+                        style: "{input_style}",
+                        onchange: move |evt: FormEvent| {
+                            let chosen = ALERT_INDICATORS.into_iter().find(|name| *name == evt.value).unwrap_or("RSI");
+                            indicator.set(chosen);
+                        },
+                        {ALERT_INDICATORS.iter().map(|name| rsx! {
+                            option { key: "{name}", value: "{name}", selected: *name == *indicator.get(), "{name}" }
+                        })}
+                    }
+                    input {
+                        r#type: "number",
+                        min: "1",
+                        title: t!("alerts-period"),
+                        value: "{period.get()}",
+                        style: "width: 40px; {input_style}",
+                        oninput: move |evt: FormEvent| period.set(evt.value.clone()),
+                    }
+                    select {
+                        value: "{direction.get()}",
+                        style: "{input_style}",
+                        onchange: move |evt: FormEvent| direction.set(if evt.value == "BELOW" { "BELOW" } else { "ABOVE" }),
+                        option { value: "ABOVE", selected: *direction.get() == "ABOVE", {t!("alerts-above")} }
+                        option { value: "BELOW", selected: *direction.get() == "BELOW", {t!("alerts-below")} }
+                    }
+                }
+                input {
+                    r#type: "text",
+                    placeholder: t!("alerts-note"),
+                    value: "{note.get()}",
+                    style: "flex: 1; min-width: 0; {input_style}",
+                    oninput: move |evt: FormEvent| note.set(evt.value.clone()),
+                }
+                button {
+                    style: "padding: 2px 8px; border-radius: 3px; cursor: pointer; {input_style}",
+                    onclick: add,
+                    {t!("alerts-add")}
+                }
+            }
+        }
+    }
+}
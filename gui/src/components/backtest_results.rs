@@ -0,0 +1,171 @@
+// Backtest results view
+// Shows the last backtest run from the command palette: its metrics, the equity curve and its
+// drawdown, and the closed trades. Clicking a trade shows its symbol's chart zoomed to the trade.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+use engine::services::GrpcEquityPoint;
+use shared::models::Price;
+
+use crate::state::app_state::AppState;
+use crate::state::backtest::drawdown_curve;
+
+const CURVE_WIDTH: f64 = 640.0;
+const EQUITY_HEIGHT: f64 = 140.0;
+const DRAWDOWN_HEIGHT: f64 = 70.0;
+
+/// SVG points of `values` scaled into a `CURVE_WIDTH` x `height` box, between `low` and `high`.
+fn curve_points(values: &[f64], low: f64, high: f64, height: f64) -> String {
+    let range = (high - low).max(f64::EPSILON);
+    let step = CURVE_WIDTH / (values.len().max(2) - 1) as f64;
+    values.iter().enumerate()
+        .map(|(i, value)| format!("{:.1},{:.1}", i as f64 * step, height - (value - low) / range * height))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn equity_points(equity_curve: &[GrpcEquityPoint]) -> String {
+    let equity: Vec<f64> = equity_curve.iter().map(|point| point.equity).collect();
+    let (low, high) = equity.iter().fold((f64::MAX, f64::MIN), |(low, high), &value| (low.min(value), high.max(value)));
+    curve_points(&equity, low, high, EQUITY_HEIGHT)
+}
+
+/// The drawdown as a closed area hanging from the top edge (no drawdown) down to the deepest one.
+fn drawdown_area(equity_curve: &[GrpcEquityPoint]) -> String {
+    let drawdown = drawdown_curve(equity_curve);
+    let deepest = drawdown.iter().copied().fold(0.0, f64::min);
+    format!("0,0 {} {:.1},0", curve_points(&drawdown, deepest, 0.0, DRAWDOWN_HEIGHT), CURVE_WIDTH)
+}
+
+/// `value` with two decimals, or ∞ (a profit factor without losing trades).
+fn format_ratio(value: f64) -> String {
+    if value.is_infinite() { "∞".to_string() } else { format!("{:.2}", value) }
+}
+
+#[component]
+pub fn BacktestResultsView() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+
+    let app_state_reader = app_state.read();
+    if !app_state_reader.backtest_results_visible {
+        return None;
+    }
+    let Some(results) = app_state_reader.backtest_results.clone() else {
+        return None;
+    };
+    let palette = app_state_reader.palette();
+    drop(app_state_reader);
+
+    let metrics = results.metrics();
+    let report = results.response.report.clone().unwrap_or_default();
+    let mut rows = vec![
+        (t!("backtest-results-total-return"), results.total_return_pct().map_or("-".to_string(), |pct| format!("{:+.2}%", pct))),
+        (t!("backtest-results-total-pnl"), format!("{:+.2}", report.total_pnl)),
+        (t!("backtest-results-final-equity"), format!("{:.2}", report.final_equity)),
+        (t!("backtest-results-trade-count"), metrics.total_trades.to_string()),
+        (t!("backtest-results-win-rate"), format!("{:.1}%", metrics.win_rate)),
+        (t!("backtest-results-profit-factor"), format_ratio(metrics.profit_factor)),
+        (t!("backtest-results-sharpe"), format_ratio(metrics.sharpe_ratio)),
+        (t!("backtest-results-sortino"), format_ratio(metrics.sortino_ratio)),
+        (t!("backtest-results-max-drawdown"), format!("{:.2} ({:.2}%)", metrics.max_drawdown, metrics.max_drawdown_pct)),
+        (t!("backtest-results-expectancy"), format!("{:+.2}", metrics.expectancy)),
+        (t!("backtest-results-avg-excursion"), format!("{:.2} / {:+.2}", metrics.avg_mae, metrics.avg_mfe)),
+        (t!("backtest-results-total-costs"), format!("{:.2}", report.total_costs)),
+    ];
+    if let Some(benchmark) = &results.response.benchmark {
+        rows.push((t!("backtest-results-benchmark", symbol = benchmark.symbol), format!("{:+.2}%", benchmark.benchmark_return_pct)));
+    }
+    let equity_curve = results.equity_curve();
+    let has_curve = equity_curve.len() > 1;
+    let equity = equity_points(equity_curve);
+    let drawdown = drawdown_area(equity_curve);
+    let trades = results.trades.clone();
+    let header_style = format!("text-align: right; padding: 2px 6px; color: {}; font-weight: normal;", palette.muted);
+
+    rsx! {
+        div {
+            class: "backtest-results",
+            style: "position: fixed; top: 5%; left: 50%; transform: translateX(-50%); width: 680px; max-height: 85vh; overflow-y: auto; padding: 15px; z-index: 1000; background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border}; border-radius: 8px; box-shadow: 0 5px 15px rgba(0,0,0,0.5); font-size: 1.3rem;",
+            div {
+                style: "display: flex; align-items: center; margin-bottom: 10px;",
+                h3 { style: "margin: 0; flex: 1;", {t!("backtest-results-title", strategy = results.strategy, symbol = results.symbol)} }
+                button {
+                    title: t!("backtest-results-close"),
+                    style: "background: none; border: none; color: {palette.muted}; cursor: pointer; font-size: 1.6rem;",
+                    onclick: move |_| app_state.write().backtest_results_visible = false,
+                    "✕"
+                }
+            }
+            table {
+                style: "width: 100%; border-collapse: collapse; margin-bottom: 10px;",
+                {rows.into_iter().map(|(label, value)| rsx! {
+                    tr {
+                        key: "{label}",
+                        td { style: "padding: 2px 6px; color: {palette.muted};", "{label}" }
+                        td { style: "padding: 2px 6px; text-align: right;", "{value}" }
+                    }
+                })}
+            }
+            if has_curve {
+                h4 { style: "margin: 6px 0;", {t!("backtest-results-equity")} }
+                svg {
+                    width: "{CURVE_WIDTH}",
+                    height: "{EQUITY_HEIGHT}",
+                    style: "border: 1px solid {palette.border};",
+                    polyline { points: "{equity}", fill: "none", stroke: "{palette.primary}", stroke_width: "1.5" }
+                }
+                h4 { style: "margin: 6px 0;", {t!("backtest-results-drawdown")} }
+                svg {
+                    width: "{CURVE_WIDTH}",
+                    height: "{DRAWDOWN_HEIGHT}",
+                    style: "border: 1px solid {palette.border};",
+                    polygon { points: "{drawdown}", fill: "{palette.chart_bearish}", fill_opacity: "0.4", stroke: "{palette.chart_bearish}", stroke_width: "1" }
+                }
+            }
+            h4 { style: "margin: 10px 0 6px 0;", {t!("backtest-results-trades")} }
+            if trades.is_empty() {
+                p { style: "color: {palette.muted};", {t!("backtest-results-no-trades")} }
+            } else {
+                table {
+                    style: "width: 100%; border-collapse: collapse; font-size: 1.2rem;",
+                    tr {
+                        th { style: "{header_style} text-align: left;", {t!("backtest-results-direction")} }
+                        th { style: "{header_style} text-align: left;", {t!("backtest-results-entry")} }
+                        th { style: "{header_style} text-align: left;", {t!("backtest-results-exit")} }
+                        th { style: "{header_style}", {t!("backtest-results-prices")} }
+                        th { style: "{header_style}", {t!("backtest-results-quantity")} }
+                        th { style: "{header_style}", {t!("backtest-results-pnl")} }
+                        th { style: "{header_style}", {t!("backtest-results-return")} }
+                    }
+                    {trades.into_iter().enumerate().map(|(i, trade)| {
+                        let color = if trade.pnl >= Price::ZERO { palette.chart_bullish.clone() } else { palette.chart_bearish.clone() };
+                        let entry = trade.entry_time.format("%Y-%m-%d %H:%M").to_string();
+                        let exit = trade.exit_time.format("%Y-%m-%d %H:%M").to_string();
+                        let prices = format!("{:.2} → {:.2}", trade.entry_price, trade.exit_price);
+                        let (direction, quantity, pnl, return_pct) = (trade.direction.as_str(), trade.quantity, trade.pnl, trade.return_pct);
+                        rsx! {
+                            tr {
+                                key: "{i}",
+                                title: t!("backtest-results-show-trade"),
+                                style: "cursor: pointer; border-top: 1px solid {palette.border};",
+                                onclick: move |_| {
+                                    let mut app_state_writer = app_state.write();
+                                    match app_state_writer.show_trade(&trade) {
+                                        true => app_state_writer.backtest_results_visible = false,
+                                        false => app_state_writer.error_message = Some(t!("error-backtest-symbol-not-loaded", symbol = trade.symbol)),
+                                    }
+                                },
+                                td { style: "padding: 2px 6px;", "{direction}" }
+                                td { style: "padding: 2px 6px;", "{entry}" }
+                                td { style: "padding: 2px 6px;", "{exit}" }
+                                td { style: "padding: 2px 6px; text-align: right;", "{prices}" }
+                                td { style: "padding: 2px 6px; text-align: right;", "{quantity}" }
+                                td { style: "padding: 2px 6px; text-align: right; color: {color};", "{pnl:+.2}" }
+                                td { style: "padding: 2px 6px; text-align: right; color: {color};", "{return_pct:+.2}%" }
+                            }
+                        }
+                    })}
+                }
+            }
+        }
+    }
+}
@@ -1,14 +1,37 @@
 // Candlestick chart rendering component
 #![allow(non_snake_case)]
+use chrono::{DateTime, Utc};
+use dioxus::html::input_data::MouseButton;
 use dioxus::prelude::*;
-use shared::models::{Candle, Indicator}; // Import Candle and Indicator structs
-use crate::components::chart::indicators::IndicatorOverlay; // Import IndicatorOverlay
+use shared::models::{Candle, Indicator, TimeFrame}; // Import Candle and Indicator structs
+use shared::utils::brazilian_format::{format_decimal, format_signed_decimal};
+use crate::components::chart::indicators::{IndicatorOverlay, LineStyle}; // Import IndicatorOverlay
+use crate::config::theme::ThemePalette;
+use crate::config::CrosshairStyle;
+use crate::state::app_state::PriceLevel;
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::components::chart::viewport::{LinkedView, VisibleRange, ZoomStep, MIN_VISIBLE_CANDLES};
+use crate::components::chart::oscillator::{oscillator_scale, OscillatorPane, OSCILLATOR_PANE_SHARE};
+use crate::components::chart::volume::{volume_bars, VolumePane, VOLUME_PANE_SHARE};
+use crate::components::chart::canvas::{CandleCanvas, CanvasScene, CANVAS_MIN_CANDLES};
+use crate::components::chart::trades::TradeOverlay;
+use crate::components::chart::legend::{ChartLegend, LegendEntry};
+use crate::components::chart::style::{heikin_ashi, ChartType};
+use crate::components::chart::sessions::SessionOverlay;
+use crate::components::chart::measure::{format_elapsed, MeasureOverlay, Measurement};
+use crate::components::chart::context_menu::ChartPoint;
+use crate::components::chart::minimap::ChartMinimap;
+use crate::state::trades::ChartTrades;
+
+/// Visible candles are scaled by this much per mouse-wheel step.
+const WHEEL_ZOOM_FACTOR: f64 = 1.15;
 
 // This will be a complex component. For now, a simple placeholder.
 // It will need to:
 // - Take market data (Vec<Candle>) as input.
-// - Render SVG or Canvas elements for candles, wicks, volume bars.
-// - Handle zooming, panning, and scaling.
+// - Render SVG or Canvas elements for candles, wicks, volume bars (canvas above `CANVAS_MIN_CANDLES`).
+// - Handle zooming, panning, and scaling (see `viewport::VisibleRange`).
 // - Potentially interact with other components like indicator overlays.
 
 // Props are now defined as function arguments for the component
@@ -30,24 +53,104 @@ pub fn CandlestickChart(
     width: f64,
     height: f64,
     indicator_data: Option<Vec<Indicator>>,
+    // Lines of the indicators, by name (see `AppState::current_indicator_lines`)
+    indicator_lines: Option<HashMap<String, LineStyle>>,
+    // Colors of the current theme
+    palette: ThemePalette,
+    // Horizontal support/resistance lines of the symbol
+    price_levels: Option<Vec<PriceLevel>>,
+    // Called with the price clicked on the price axis (left margin), to place a level there
+    on_price_level: EventHandler<f64>,
+    // Simulated or backtested trades of the symbol, marked on the candles they happened in
+    trades: Option<ChartTrades>,
+    // Latest zoom asked for by a shortcut, numbered so that repeating the same step applies it again
+    zoom_request: Option<(u64, ZoomStep)>,
+    // Symbol and timeframe named in the legend
+    symbol: Option<String>,
+    timeframe: Option<TimeFrame>,
+    // Crosshair lines, or `None` to hide them (the legend still follows the pointer)
+    crosshair: Option<CrosshairStyle>,
+    // How the price pane draws the candles
+    chart_type: ChartType,
+    // Whether intraday charts shade time outside the trading session and separate the days
+    sessions: bool,
+    // Decimals prices are shown with, per the config's `symbols` section
+    price_decimals: usize,
+    // View shared by linked charts, or `None` when charts are not linked
+    linked_view: Option<LinkedView>,
+    // Called with this chart's view as it is zoomed, panned or hovered, for linked charts
+    on_view_change: EventHandler<LinkedView>,
+    // Called where the chart is right-clicked, to open its context menu there
+    on_context_menu: EventHandler<ChartPoint>,
 ) -> Element {
+    // Zoom and pan state: the visible candles, and where a drag started (x, range at that time).
+    let visible_range = use_state(|| VisibleRange::all(candles.len()));
+    let drag_start = use_ref(|| None::<(f64, VisibleRange)>);
+    let known_total = use_ref(|| candles.len());
+    // Candle under the pointer (index into all candles) and the pointer's y, for the crosshair and legend
+    let hover = use_state(|| None::<(usize, f64)>);
+    // Span measured with the ruler (shift-drag), kept after the drag until the next click, and whether it is being dragged
+    let measurement = use_state(|| None::<Measurement>);
+    let measuring = use_ref(|| false);
+    // Linked charts share times rather than indices, since each symbol has its own candles
+    let timestamps: Rc<Vec<DateTime<Utc>>> = Rc::new(candles.iter().map(|candle| candle.timestamp).collect());
+    let report_view = {
+        let timestamps = timestamps.clone();
+        let symbol = symbol.clone();
+        move |range: VisibleRange, crosshair: Option<usize>| {
+            let (Some(symbol), Some(first), Some(last)) = (symbol.clone(), timestamps.get(range.first), timestamps.get(range.last)) else { return };
+            let crosshair = crosshair.and_then(|index| timestamps.get(index).copied());
+            on_view_change.call(LinkedView { symbol, first: *first, last: *last, crosshair });
+        }
+    };
+    // When candles are added (e.g. live updates) a range showing the latest ones keeps following them.
+    use_effect((candles.len(),), move |(len,)| {
+        let old_total = *known_total.read();
+        visible_range.set(visible_range.get().follow(old_total, len));
+        *known_total.write() = len;
+    });
+    // Shortcut zooms keep the right edge (the latest candles) in place
+    let report_zoom = report_view.clone();
+    let span_timestamps = timestamps.clone();
+    use_effect((zoom_request,), move |(zoom_request,)| {
+        let Some((_, step)) = zoom_request else { return };
+        let total = *known_total.read();
+        let range = visible_range.get().clamp(total);
+        let zoomed = match step {
+            ZoomStep::In => range.zoom(1.0 / WHEEL_ZOOM_FACTOR, 1.0, total),
+            ZoomStep::Out => range.zoom(WHEEL_ZOOM_FACTOR, 1.0, total),
+            ZoomStep::Reset => VisibleRange::all(total),
+            // As many candles again around the span, half on each side
+            ZoomStep::Span { first, last } => match VisibleRange::covering(&span_timestamps, first, last) {
+                Some(span) => span.widen((span.len() / 2).max(MIN_VISIBLE_CANDLES), total),
+                None => return,
+            },
+        };
+        visible_range.set(zoomed);
+        report_zoom(zoomed, hover.get().map(|(index, _)| index));
+    });
+    // Switching to another symbol while linked shows the same time window as the chart it was linked to
+    let linked_timestamps = timestamps.clone();
+    let linked = linked_view.clone();
+    use_effect((symbol.clone(), timestamps.first().copied()), move |(symbol, _)| {
+        let Some(view) = linked.filter(|view| Some(&view.symbol) != symbol.as_ref()) else { return };
+        if let Some(linked_range) = VisibleRange::covering(&linked_timestamps, view.first, view.last) {
+            visible_range.set(linked_range);
+        }
+    });
+
     if candles.is_empty() {
         // Need cx to render, but it's not an argument for #[component] functions in Dioxus 0.5 style.
         // The function body itself is the render context.
         // So, we return Element directly.
         return rsx! { // No cx.render() needed here.
             div {
-                style: "width: {width}px; height: {height}px; display: flex; align-items: center; justify-content: center; border: 1px solid #ccc; background-color: #f0f0f0;",
-                "No candle data available."
+                style: "width: {width}px; height: {height}px; display: flex; align-items: center; justify-content: center; border: 1px solid {palette.border}; background-color: {palette.surface}; color: {palette.muted};",
+                {t!("chart-no-data")}
             }
         };
     }
 
-    // Access props directly by their names
-    // let candles = &candles; // candles is already a direct argument
-    // let chart_width = width; // width is a direct argument
-    // let chart_height = height; // height is a direct argument
-
     // Define margins
     let margin_top = 20.0;
     let margin_bottom = 30.0;
@@ -58,15 +161,43 @@ pub fn CandlestickChart(
     let plot_width = width - margin_left - margin_right;
     let plot_height = height - margin_top - margin_bottom;
 
-    // Determine price range
-    let mut min_price = candles.first().map_or(0.0, |c| c.low);
-    let mut max_price = candles.first().map_or(0.0, |c| c.high);
+    // Only the candles in the visible range are drawn; the range may still be stale for one render after new data arrives.
+    let total_candles = candles.len();
+    let range = visible_range.get().clamp(total_candles);
+    // Heikin-Ashi candles depend on all the candles before them, so they are computed before cutting the range
+    let all_candles = if chart_type == ChartType::HeikinAshi { heikin_ashi(&candles) } else { candles };
+    let candles = &all_candles[range.first..=range.last];
+    let next_timestamp = all_candles.get(range.last + 1).map(|candle| candle.timestamp);
+
+    // Indicator values line up with the candles, so they are cut to the same range.
+    // Oscillators get their own panes; the rest are drawn over the candles.
+    let (oscillators, price_indicators): (Vec<Indicator>, Vec<Indicator>) = indicator_data.iter().flatten()
+        .map(|indicator| Indicator {
+            values: indicator.values.iter().copied().skip(range.first).take(range.len()).collect(),
+            ..indicator.clone()
+        })
+        .partition(|indicator| oscillator_scale(&indicator.name).is_some());
+
+    // The plot is split into the price pane on top, then the volume pane and one pane per oscillator.
+    // Sub-panes shrink as more are added, so the price pane keeps at least 40% of the plot.
+    let lines = indicator_lines.unwrap_or_default();
+    let pane_gap = 6.0;
+    let sub_pane_shares = VOLUME_PANE_SHARE + oscillators.len() as f64 * OSCILLATOR_PANE_SHARE;
+    let sub_pane_fit = (0.6 / sub_pane_shares).min(1.0);
+    let volume_pane_height = plot_height * VOLUME_PANE_SHARE * sub_pane_fit;
+    let oscillator_pane_height = plot_height * OSCILLATOR_PANE_SHARE * sub_pane_fit;
+    let price_pane_height = plot_height * (1.0 - sub_pane_shares * sub_pane_fit) - pane_gap * (1 + oscillators.len()) as f64;
+    let volume_pane_top = margin_top + price_pane_height + pane_gap;
+
+    // Determine price range (of the visible candles, so zooming in also stretches them vertically)
+    let mut min_price = candles.first().map_or(0.0, |c| c.low_f64());
+    let mut max_price = candles.first().map_or(0.0, |c| c.high_f64());
     for candle in candles.iter() {
-        if candle.low < min_price {
-            min_price = candle.low;
+        if candle.low_f64() < min_price {
+            min_price = candle.low_f64();
         }
-        if candle.high > max_price {
-            max_price = candle.high;
+        if candle.high_f64() > max_price {
+            max_price = candle.high_f64();
         }
     }
     // Add some padding to min/max price for better visualization
@@ -78,23 +209,67 @@ pub fn CandlestickChart(
     let price_range = if (max_price - min_price) > 0.0 { max_price - min_price } else { 1.0 }; // Avoid division by zero
 
     // Scaling factors
-    let y_scale_factor = plot_height / price_range;
+    let y_scale_factor = price_pane_height / price_range;
     // Function to convert price to Y coordinate
     // Y is inverted in SVG (0 is top), so (max_price - price_value)
     let price_to_y = |price: f64| margin_top + (max_price - price) * y_scale_factor;
+    let y_to_price = move |y: f64| max_price - (y - margin_top) / y_scale_factor;
 
     let num_candles = candles.len() as f64;
     let candle_plot_width = plot_width / num_candles; // Includes spacing
     let candle_width = (candle_plot_width * 0.7).max(1.0); // Candle body is 70% of its allocated space, min 1px
     let candle_spacing = candle_plot_width - candle_width;
+    let cursor = if *measuring.read() { "crosshair" } else if drag_start.read().is_some() { "grabbing" } else { "grab" };
+    let show_sessions = sessions && timeframe.is_some_and(|timeframe| timeframe != TimeFrame::Day1);
+
+    // The legend shows the values at the hovered candle, or at the latest visible one
+    let hovered = hover.get().filter(|(index, _)| range.contains(*index));
+    // Until the pointer is over this chart, a linked chart's crosshair time is marked on it
+    let linked_crosshair = linked_view.as_ref()
+        .filter(|view| hovered.is_none() && Some(&view.symbol) != symbol.as_ref())
+        .and_then(|view| view.crosshair)
+        .and_then(|time| timestamps.binary_search(&time).ok())
+        .filter(|index| range.contains(*index));
+    let legend_index = hovered.map(|(index, _)| index).or(linked_crosshair).unwrap_or(range.last);
+    let legend_entries: Vec<LegendEntry> = indicator_data.iter().flatten()
+        .map(|indicator| LegendEntry {
+            name: indicator.name.clone(),
+            color: lines.get(&indicator.name).cloned().unwrap_or_default().color,
+            value: indicator.values.get(legend_index).copied().filter(|value| value.is_finite()),
+        })
+        .collect();
+    // Candle under x, clamped to the visible ones
+    let x_to_index = move |x: f64| range.first + ((x - margin_left) / candle_plot_width).floor().clamp(0.0, (range.len() - 1) as f64) as usize;
+    let index_to_x = |index: usize| margin_left + (index as f64 - range.first as f64 + 0.5) * candle_plot_width;
+    // Dropped if the candles were replaced by fewer (e.g. another symbol)
+    let ruler = measurement.get().filter(|measured| measured.from.0.max(measured.to.0) < total_candles).map(|measured| {
+        let elapsed = all_candles[measured.to.0].timestamp - all_candles[measured.from.0].timestamp;
+        let percent = measured.percent_change().map_or_else(|| t!("chart-legend-no-value"), |percent| format!("{}%", format_signed_decimal(percent, 2)));
+        let label = t!("chart-measure", change = format_signed_decimal(measured.price_change(), price_decimals), percent = percent, bars = measured.bars(), elapsed = format_elapsed(elapsed));
+        (index_to_x(measured.from.0), price_to_y(measured.from.1), index_to_x(measured.to.0), price_to_y(measured.to.1), label, measured.price_change() >= 0.0)
+    });
+    // The vertical line at the hovered (or linked) candle; the horizontal one and price at the pointer
+    let crosshair = crosshair.and_then(|style| {
+        let (index, y) = match (hovered, linked_crosshair) {
+            (Some((index, y)), _) => (index, Some(y.clamp(margin_top, margin_top + plot_height))),
+            (None, Some(index)) => (index, None),
+            (None, None) => return None,
+        };
+        let x = margin_left + (index - range.first) as f64 * candle_plot_width + candle_plot_width / 2.0;
+        // The price is only labelled over the price pane
+        let price = y.filter(|y| *y <= margin_top + price_pane_height).map(y_to_price);
+        let dasharray = if style.style == "solid" { "none" } else { "3 3" };
+        Some((style.color, dasharray, x, y, price))
+    });
 
 
-    let candle_elements: Vec<Element> = candles.iter().enumerate().map(|(i, candle)| {
+    // Wick and body of each candle: wick x, wick top, wick bottom, body x, body y, body height, bullish
+    let candle_shapes: Vec<(f64, f64, f64, f64, f64, f64, bool)> = candles.iter().enumerate().map(|(i, candle)| {
         let x_base = margin_left + (i as f64 * candle_plot_width);
         let candle_x = x_base + candle_spacing / 2.0;
 
-        let body_top_price = candle.open.max(candle.close);
-        let body_bottom_price = candle.open.min(candle.close);
+        let body_top_price = candle.open_f64().max(candle.close_f64());
+        let body_bottom_price = candle.open_f64().min(candle.close_f64());
 
         let body_y = price_to_y(body_top_price);
         let body_height = (body_top_price - body_bottom_price) * y_scale_factor;
@@ -102,15 +277,45 @@ pub fn CandlestickChart(
         let body_height = if body_height < 1.0 && candle.open != candle.close { 1.0 } else { body_height.max(0.0) };
 
 
-        let wick_top_y = price_to_y(candle.high);
-        let wick_bottom_y = price_to_y(candle.low);
+        let wick_top_y = price_to_y(candle.high_f64());
+        let wick_bottom_y = price_to_y(candle.low_f64());
         let wick_x_center = candle_x + candle_width / 2.0;
 
-        // Standard trading colors
-        let bullish_color = "#26a69a"; // Greenish
-        let bearish_color = "#ef5350"; // Reddish
-        let color = if candle.close >= candle.open { bullish_color } else { bearish_color };
+        (wick_x_center, wick_top_y, wick_bottom_y, candle_x, body_y, body_height, candle.is_bullish())
+    }).collect();
+
+    // Large ranges go to the canvas; SVG keeps the overlays drawn above it
+    let use_canvas = candles.len() > CANVAS_MIN_CANDLES;
+    let canvas_scene = use_canvas.then(|| CanvasScene {
+        width,
+        height,
+        panes: vec![[margin_left, margin_top, plot_width, price_pane_height], [margin_left, volume_pane_top, plot_width, volume_pane_height]],
+        pane_fill: palette.surface.clone(),
+        bullish: palette.chart_bullish.clone(),
+        bearish: palette.chart_bearish.clone(),
+        bars: chart_type == ChartType::Bar,
+        // Lines of closes are drawn in SVG whatever the number of candles
+        candles: candle_shapes.iter().filter(|_| !chart_type.is_line())
+            .map(|&(wick_x, wick_top, wick_bottom, body_x, body_y, body_height, bullish)| [wick_x, wick_top, wick_bottom, body_x, body_y, candle_width, body_height, bullish as u8 as f64])
+            .collect(),
+        volume_bars: volume_bars(candles, margin_left, volume_pane_top, volume_pane_height, candle_plot_width).into_iter()
+            .map(|(x, y, bar_width, bar_height, bullish)| [x, y, bar_width, bar_height, bullish as u8 as f64])
+            .collect(),
+    });
 
+    let candle_elements: Vec<Element> = if use_canvas || chart_type.is_line() { Vec::new() } else { candle_shapes.into_iter().map(|(wick_x_center, wick_top_y, wick_bottom_y, candle_x, body_y, body_height, bullish)| {
+        // Bullish/bearish colors of the theme
+        let color = if bullish { &palette.chart_bullish } else { &palette.chart_bearish };
+
+        if chart_type == ChartType::Bar {
+            // The high-low range, with the open ticked on its left and the close on its right
+            let (open_y, close_y) = if bullish { (body_y + body_height, body_y) } else { (body_y, body_y + body_height) };
+            return rsx! {
+                line { x1: "{wick_x_center}", y1: "{wick_top_y}", x2: "{wick_x_center}", y2: "{wick_bottom_y}", stroke: "{color}", stroke_width: "1" }
+                line { x1: "{candle_x}", y1: "{open_y}", x2: "{wick_x_center}", y2: "{open_y}", stroke: "{color}", stroke_width: "1" }
+                line { x1: "{wick_x_center}", y1: "{close_y}", x2: "{candle_x + candle_width}", y2: "{close_y}", stroke: "{color}", stroke_width: "1" }
+            };
+        }
         rsx! {
             // Wick
             line {
@@ -118,7 +323,7 @@ pub fn CandlestickChart(
                 y1: "{wick_top_y}",
                 x2: "{wick_x_center}",
                 y2: "{wick_bottom_y}",
-                stroke: color,
+                stroke: "{color}",
                 stroke_width: "1"
             }
             // Candle Body
@@ -127,56 +332,274 @@ pub fn CandlestickChart(
                 y: "{body_y}",
                 width: "{candle_width}",
                 height: "{body_height}",
-                fill: color,
+                fill: "{color}",
                 // Optional: add a stroke to the body
                 // stroke: "black",
                 // stroke_width: "0.5"
             }
         }
-    }).collect();
+    }).collect() };
+
+    // Line and area styles: the closes joined at the middle of each candle; areas are closed along the pane's bottom
+    let close_line = chart_type.is_line().then(|| {
+        let points: Vec<String> = candles.iter().enumerate()
+            .map(|(i, candle)| format!("{:.2},{:.2}", margin_left + (i as f64 + 0.5) * candle_plot_width, price_to_y(candle.close_f64())))
+            .collect();
+        let pane_bottom = margin_top + price_pane_height;
+        let area = (chart_type == ChartType::Area).then(|| format!(
+            "{:.2},{:.2} {} {:.2},{:.2}",
+            margin_left + 0.5 * candle_plot_width, pane_bottom, points.join(" "), margin_left + (num_candles - 0.5) * candle_plot_width, pane_bottom
+        ));
+        (points.join(" "), area)
+    });
+
+    let report_wheel = report_view.clone();
+    let report_move = report_view.clone();
+    let report_jump = report_view.clone();
+    let overview_closes: Vec<f64> = all_candles.iter().map(|candle| candle.close_f64()).collect();
+    let context_timestamps = timestamps.clone();
 
     // The function body implicitly returns this rsx block if it's the last expression
     rsx! {
         div {
+            id: "candlestick-chart",
             class: "candlestick-chart-container",
             // Use direct prop values for width and height in style
-            style: "width: {width}px; height: {height}px; border: 1px solid #444; background-color: #222; color: #eee;",
+            style: "width: {width}px; height: {height}px; border: 1px solid {palette.border}; background-color: {palette.background}; color: {palette.foreground}; cursor: {cursor}; position: relative;",
+            // Wheel zooms around the pointer, dragging pans, double-click shows every candle again.
+            // Positions are relative to this div, since the canvas and svg below ignore pointer events.
+            onwheel: move |event: Event<WheelData>| {
+                let factor = if event.delta().strip_units().y > 0.0 { WHEEL_ZOOM_FACTOR } else { 1.0 / WHEEL_ZOOM_FACTOR };
+                let anchor = (event.element_coordinates().x - margin_left) / plot_width;
+                let zoomed = range.zoom(factor, anchor, total_candles);
+                visible_range.set(zoomed);
+                report_wheel(zoomed, hover.get().map(|(index, _)| index));
+            },
+            onmousedown: move |event: Event<MouseData>| {
+                // Right clicks open the context menu instead
+                if event.trigger_button() == Some(MouseButton::Secondary) {
+                    return;
+                }
+                let point = event.element_coordinates();
+                // A click on the price axis places a level, a shift-click on the price pane starts measuring,
+                // anywhere else starts panning
+                let over_price_pane = point.y >= margin_top && point.y <= margin_top + price_pane_height;
+                if point.x < margin_left && over_price_pane {
+                    on_price_level.call(y_to_price(point.y));
+                } else if event.modifiers().shift() && over_price_pane {
+                    measurement.set(Some(Measurement::at(x_to_index(point.x), y_to_price(point.y))));
+                    *measuring.write() = true;
+                } else {
+                    measurement.set(None);
+                    *drag_start.write() = Some((point.x, range));
+                }
+            },
+            onmousemove: move |event: Event<MouseData>| {
+                let point = event.element_coordinates();
+                let start = *drag_start.read();
+                let mut shown = range;
+                if let Some((start_x, start_range)) = start {
+                    // Dragging right brings older candles into view
+                    let moved_candles = -((point.x - start_x) / candle_plot_width).round() as isize;
+                    shown = start_range.pan(moved_candles, total_candles);
+                    visible_range.set(shown);
+                }
+                if *measuring.read() {
+                    if let Some(measured) = *measurement.get() {
+                        let y = point.y.clamp(margin_top, margin_top + price_pane_height);
+                        measurement.set(Some(Measurement { to: (x_to_index(point.x), y_to_price(y)), ..measured }));
+                    }
+                }
+                let offset = ((point.x - margin_left) / candle_plot_width).floor();
+                let over_plot = offset >= 0.0 && offset < range.len() as f64 && point.y >= margin_top && point.y <= margin_top + plot_height;
+                let hovered = over_plot.then(|| (range.first + offset as usize, point.y));
+                hover.set(hovered);
+                // Leaving the plot keeps the last crosshair time for linked charts
+                if hovered.is_some() || start.is_some() {
+                    report_move(shown, hovered.map(|(index, _)| index));
+                }
+            },
+            onmouseup: move |_| {
+                *drag_start.write() = None;
+                *measuring.write() = false;
+            },
+            onmouseleave: move |_| {
+                *drag_start.write() = None;
+                *measuring.write() = false;
+                hover.set(None);
+            },
+            prevent_default: "oncontextmenu",
+            oncontextmenu: move |event: Event<MouseData>| {
+                let point = event.element_coordinates();
+                let window_point = event.client_coordinates();
+                let over_plot = point.x >= margin_left && point.x <= margin_left + plot_width && point.y >= margin_top && point.y <= margin_top + plot_height;
+                let over_price_pane = over_plot && point.y <= margin_top + price_pane_height;
+                on_context_menu.call(ChartPoint {
+                    left: window_point.x,
+                    top: window_point.y,
+                    price: over_price_pane.then(|| y_to_price(point.y)),
+                    timestamp: over_plot.then(|| context_timestamps[x_to_index(point.x)]),
+                });
+            },
+            ondoubleclick: move |_| {
+                visible_range.set(VisibleRange::all(total_candles));
+                report_view(VisibleRange::all(total_candles), hover.get().map(|(index, _)| index));
+            },
+            if let Some(scene) = canvas_scene {
+                CandleCanvas { scene: scene }
+            }
             svg {
+                // Found by its id when the chart is exported
+                id: "candlestick-svg",
                 // Use direct prop values
                 width: "{width}",
                 height: "{height}",
-                // Background for the plot area
-                rect {
-                    x: "{margin_left}",
-                    y: "{margin_top}",
-                    width: "{plot_width}",
-                    height: "{plot_height}",
-                    fill: "#2a2a2a"
+                style: "position: absolute; left: 0; top: 0; pointer-events: none;",
+                // Background for the plot area (on the canvas when it draws the candles)
+                if !use_canvas {
+                    rect {
+                        x: "{margin_left}",
+                        y: "{margin_top}",
+                        width: "{plot_width}",
+                        height: "{price_pane_height}",
+                        fill: "{palette.surface}"
+                    }
+                }
+                if show_sessions {
+                    SessionOverlay {
+                        candles: candles.to_vec(),
+                        margin_left: margin_left,
+                        margin_top: margin_top,
+                        pane_height: price_pane_height,
+                        plot_height: plot_height,
+                        candle_plot_width: candle_plot_width,
+                        price_decimals: price_decimals,
+                        palette: palette.clone()
+                    }
+                }
+                VolumePane {
+                    candles: candles.to_vec(),
+                    margin_left: margin_left,
+                    pane_top: volume_pane_top,
+                    pane_width: plot_width,
+                    pane_height: volume_pane_height,
+                    candle_plot_width: candle_plot_width,
+                    palette: palette.clone(),
+                    on_canvas: use_canvas
                 }
                 // Group for actual candle elements
                 g {
                     // candle_elements is an iterator, rsx! can render iterators of Elements
                     {candle_elements}
                 }
-                // Render IndicatorOverlay if data is provided
-                {
-                    // Access indicator_data directly
-                    if let Some(indicators) = &indicator_data {
-                        if !indicators.is_empty() {
+                if let Some((points, area)) = close_line {
+                    g {
+                        class: "close-line",
+                        if let Some(area) = area {
+                            polygon { points: "{area}", fill: "{palette.primary}", fill_opacity: "0.2", stroke: "none" }
+                        }
+                        polyline { points: "{points}", fill: "none", stroke: "{palette.primary}", stroke_width: "2" }
+                    }
+                }
+                // Indicators on the price scale (moving averages, VWAP...)
+                if !price_indicators.is_empty() {
+                    IndicatorOverlay {
+                        indicators: price_indicators,
+                        min_price: min_price,
+                        max_price: max_price,
+                        plot_height: price_pane_height,
+                        margin_left: margin_left,
+                        margin_top: margin_top,
+                        candle_plot_width: candle_plot_width,
+                        num_candles_on_chart: candles.len(),
+                        styles: lines.clone()
+                    }
+                }
+                if let Some(trades) = trades {
+                    TradeOverlay {
+                        trades: trades,
+                        candles: candles.to_vec(),
+                        next_timestamp: next_timestamp,
+                        min_price: min_price,
+                        max_price: max_price,
+                        margin_left: margin_left,
+                        margin_top: margin_top,
+                        pane_height: price_pane_height,
+                        candle_plot_width: candle_plot_width,
+                        palette: palette.clone()
+                    }
+                }
+                // Oscillators, each in its own pane beneath the volume
+                {oscillators.into_iter().enumerate().map(|(i, indicator)| {
+                    let pane_top = volume_pane_top + volume_pane_height + pane_gap + i as f64 * (oscillator_pane_height + pane_gap);
+                    let scale = oscillator_scale(&indicator.name).expect("oscillators were picked by their scale");
+                    let line = lines.get(&indicator.name).cloned().unwrap_or_default();
+                    rsx! {
+                        OscillatorPane {
+                            indicator: indicator,
+                            scale: scale,
+                            line: line,
+                            margin_left: margin_left,
+                            pane_top: pane_top,
+                            pane_width: plot_width,
+                            pane_height: oscillator_pane_height,
+                            candle_plot_width: candle_plot_width,
+                            palette: palette.clone()
+                        }
+                    }
+                })}
+                // Price levels in view, dashed across the price pane; alerts in the accent color
+                {price_levels.iter().flatten().filter(|level| level.price >= min_price && level.price <= max_price).map(|level| {
+                    let level_y = price_to_y(level.price);
+                    let color = if level.alert { &palette.accent } else { &palette.muted };
+                    let label = if level.alert { format!("🔔 {}", format_decimal(level.price, price_decimals)) } else { format_decimal(level.price, price_decimals) };
+                    rsx! {
+                        g {
+                            key: "{level.id}",
+                            line {
+                                x1: "{margin_left}",
+                                y1: "{level_y}",
+                                x2: "{margin_left + plot_width}",
+                                y2: "{level_y}",
+                                stroke: "{color}",
+                                stroke_width: "1",
+                                stroke_dasharray: "6 4"
+                            }
+                            text {
+                                x: "{margin_left + plot_width - 4.0}",
+                                y: "{level_y - 3.0}",
+                                fill: "{color}",
+                                font_size: "1rem",
+                                text_anchor: "end",
+                                "{label}"
+                            }
+                        }
+                    }
+                })}
+                if let Some((x1, y1, x2, y2, label, rising)) = ruler {
+                    MeasureOverlay { x1: x1, y1: y1, x2: x2, y2: y2, label: label, rising: rising, palette: palette.clone() }
+                }
+                if let Some((color, dasharray, x, y, price)) = crosshair {
+                    g {
+                        class: "crosshair",
+                        line { x1: "{x}", y1: "{margin_top}", x2: "{x}", y2: "{margin_top + plot_height}", stroke: "{color}", stroke_width: "1", stroke_dasharray: "{dasharray}" }
+                        {y.map(|y| rsx! {
+                            line { x1: "{margin_left}", y1: "{y}", x2: "{margin_left + plot_width}", y2: "{y}", stroke: "{color}", stroke_width: "1", stroke_dasharray: "{dasharray}" }
+                        })}
+                        {y.zip(price).map(|(y, price)| {
+                            let price = format_decimal(price, price_decimals);
                             rsx! {
-                                IndicatorOverlay {
-                                    indicators: indicators.clone(),
-                                    min_price: min_price,
-                                    max_price: max_price,
-                                    plot_height: plot_height,
-                                    margin_left: margin_left,
-                                    margin_top: margin_top,
-                                    candle_plot_width: candle_plot_width,
-                                    num_candles_on_chart: candles.len()
-                                }
+                                text { x: "{margin_left - 4.0}", y: "{y + 3.0}", fill: "{color}", font_size: "1rem", text_anchor: "end", "{price}" }
                             }
-                        } else { None } // Render nothing if indicators is Some but empty
-                    } else { None } // Render nothing if indicator_data is None
+                        })}
+                    }
+                }
+                text {
+                    x: "{margin_left}",
+                    y: "{margin_top - 6.0}",
+                    fill: "{palette.muted}",
+                    font_size: "1.1rem",
+                    {t!("chart-range", first = range.first + 1, last = range.last + 1, total = total_candles)}
                 }
                 // Remove placeholder text or comment out
                 /*
@@ -184,19 +607,38 @@ pub fn CandlestickChart(
                     x: "{margin_left + 10.0}",
                     y: "{margin_top + 20.0}",
                     fill: "#aaa",
-                    font_size: "12px",
+                    font_size: "1.2rem",
                     "Min Price: {min_price:.2}, Max Price: {max_price:.2}, Y-Scale: {y_scale_factor:.2}"
                 }
                 text {
                     x: "{margin_left + 10.0}",
                     y: "{margin_top + 40.0}",
                     fill: "#aaa",
-                    font_size: "12px",
+                    font_size: "1.2rem",
                     "Candle Width: {candle_width:.2}, Spacing: {candle_spacing:.2}"
                 }
                 */
                 // TODO: Add Axes (numbers for price and time)
             }
+            ChartLegend {
+                symbol: symbol,
+                timeframe: timeframe,
+                entries: legend_entries,
+                left: margin_left + 4.0,
+                top: margin_top + 4.0,
+                palette: palette.clone()
+            }
+        }
+        ChartMinimap {
+            closes: overview_closes,
+            range: range,
+            margin_left: margin_left,
+            plot_width: plot_width,
+            palette: palette.clone(),
+            on_jump: move |jumped: VisibleRange| {
+                visible_range.set(jumped);
+                report_jump(jumped, None);
+            }
         }
     })
 }
@@ -0,0 +1,92 @@
+// Canvas rendering of candles and volume bars
+// One SVG element per candle gets slow with tens of thousands of candles, so large visible ranges
+// are drawn imperatively on a canvas instead; SVG then only carries the overlays (indicators,
+// oscillator panes, labels) drawn on top of it.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+use serde::Serialize;
+
+/// Visible ranges with more candles than this are drawn on the canvas.
+pub const CANVAS_MIN_CANDLES: usize = 1_500;
+
+const CANVAS_ID: &str = "candlestick-canvas";
+
+/// What the canvas draws, in chart coordinates; sent to the webview as JSON.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CanvasScene {
+    pub width: f64,
+    pub height: f64,
+    /// Pane backgrounds: x, y, width, height.
+    pub panes: Vec<[f64; 4]>,
+    pub pane_fill: String,
+    pub bullish: String,
+    pub bearish: String,
+    /// Draws OHLC bars (ticks for the open and close) instead of candle bodies.
+    pub bars: bool,
+    /// Wick x, wick top, wick bottom, body x, body y, body width, body height, 1 if bullish.
+    pub candles: Vec<[f64; 8]>,
+    /// x, y, width, height, 1 if bullish.
+    pub volume_bars: Vec<[f64; 5]>,
+}
+
+// Draws a scene on the canvas; shapes are batched by color so each color is one path.
+const DRAW_SCRIPT: &str = r#"(function () {
+    const canvas = document.getElementById("__CANVAS_ID__");
+    const scene = __SCENE__;
+    if (!canvas || !scene) return;
+    const ratio = window.devicePixelRatio || 1;
+    canvas.width = scene.width * ratio;
+    canvas.height = scene.height * ratio;
+    const ctx = canvas.getContext("2d");
+    ctx.setTransform(ratio, 0, 0, ratio, 0, 0);
+    ctx.clearRect(0, 0, scene.width, scene.height);
+    ctx.fillStyle = scene.pane_fill;
+    for (const [x, y, w, h] of scene.panes) ctx.fillRect(x, y, w, h);
+    for (const bullish of [1, 0]) {
+        const color = bullish ? scene.bullish : scene.bearish;
+        ctx.strokeStyle = color;
+        ctx.fillStyle = color;
+        ctx.lineWidth = 1;
+        ctx.beginPath();
+        for (const c of scene.candles) if (c[7] === bullish) {
+            ctx.moveTo(c[0], c[1]);
+            ctx.lineTo(c[0], c[2]);
+            if (scene.bars) {
+                // Bullish bars open at the bottom of the body and close at its top
+                const [open, close] = bullish ? [c[4] + c[6], c[4]] : [c[4], c[4] + c[6]];
+                ctx.moveTo(c[3], open);
+                ctx.lineTo(c[0], open);
+                ctx.moveTo(c[0], close);
+                ctx.lineTo(c[3] + c[5], close);
+            }
+        }
+        ctx.stroke();
+        if (!scene.bars) for (const c of scene.candles) if (c[7] === bullish) ctx.fillRect(c[3], c[4], c[5], c[6]);
+        ctx.globalAlpha = 0.6;
+        for (const v of scene.volume_bars) if (v[4] === bullish) ctx.fillRect(v[0], v[1], v[2], v[3]);
+        ctx.globalAlpha = 1;
+    }
+})();"#;
+
+fn draw_script(scene: &CanvasScene) -> String {
+    let scene_json = serde_json::to_string(scene).unwrap_or_else(|_| "null".to_string());
+    DRAW_SCRIPT.replace("__CANVAS_ID__", CANVAS_ID).replace("__SCENE__", &scene_json)
+}
+
+#[component]
+pub fn CandleCanvas(scene: CanvasScene) -> Element {
+    // Redrawn after every render that changes the scene, once the canvas is in the page
+    let script = draw_script(&scene);
+    use_effect((script,), move |(script,)| {
+        eval(&script);
+    });
+
+    rsx! {
+        canvas {
+            id: CANVAS_ID,
+            width: "{scene.width}",
+            height: "{scene.height}",
+            style: "position: absolute; left: 0; top: 0; width: {scene.width}px; height: {scene.height}px; pointer-events: none;",
+        }
+    }
+}
@@ -0,0 +1,64 @@
+// Context menu of the chart: right-clicking it lists commands acting at that point (see
+// `commands::chart_point_commands`), run like the palette's. Any other click closes it.
+#![allow(non_snake_case)]
+use chrono::{DateTime, Utc};
+use dioxus::prelude::*;
+
+use crate::components::commands::{chart_point_commands, execute_command, CommandContext};
+use crate::config::AppConfig;
+use crate::services::engine_client::EngineClient;
+use crate::state::app_state::AppState;
+
+/// Where the chart was right-clicked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartPoint {
+    /// Position in the window, where the menu opens.
+    pub left: f64,
+    pub top: f64,
+    /// Price under the pointer, when it was over the price pane.
+    pub price: Option<f64>,
+    /// Time of the candle under the pointer, when it was over the plot.
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+#[component]
+pub fn ChartContextMenu() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let app_config = use_shared_state::<AppConfig>().unwrap();
+    let engine_client = use_shared_state::<Option<EngineClient>>().unwrap();
+    let window = dioxus_desktop::use_window();
+
+    let Some(point) = app_state.read().chart_context_menu.clone() else { return None };
+    let palette = app_state.read().palette();
+    let context = CommandContext { app_state: app_state.clone(), app_config: app_config.clone(), engine_client: engine_client.clone(), window: window.clone() };
+    let close = move || app_state.write().chart_context_menu = None;
+
+    rsx! {
+        // Catches the clicks outside the menu
+        div {
+            style: "position: fixed; inset: 0; z-index: 999;",
+            onclick: move |_| close(),
+            prevent_default: "oncontextmenu",
+            oncontextmenu: move |_| close(),
+        }
+        ul {
+            class: "chart-context-menu",
+            style: "position: fixed; left: {point.left}px; top: {point.top}px; z-index: 1000; list-style: none; margin: 0; padding: 4px 0; min-width: 220px; background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border}; border-radius: 4px; box-shadow: 0 3px 10px rgba(0,0,0,0.4); font-size: 1.3rem;",
+            {chart_point_commands(&point).into_iter().map(|command| {
+                let context = context.clone();
+                rsx! {
+                    li {
+                        key: "{command.id}",
+                        title: "{command.description}",
+                        style: "padding: 6px 14px; cursor: pointer;",
+                        onclick: move |_| {
+                            close();
+                            execute_command(&command, &context);
+                        },
+                        "{command.name}"
+                    }
+                }
+            })}
+        }
+    }
+}
@@ -1,7 +1,91 @@
 // Component for rendering technical indicators on the chart
 #![allow(non_snake_case)]
 use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
 use shared::models::Indicator; // Import the Indicator struct
+use std::collections::HashMap;
+
+use crate::components::chart::oscillator::oscillator_scale;
+use crate::config::IndicatorDefaults;
+
+/// The engine's indicator type of an indicator it named, e.g. "SMA" for "SMA(20)".
+pub fn indicator_type(name: &str) -> String {
+    name.split('(').next().unwrap_or_default().trim().to_string()
+}
+
+/// Dash pattern of an indicator's line, picked in the indicator panel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineDash {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl LineDash {
+    pub const ALL: [LineDash; 3] = [LineDash::Solid, LineDash::Dashed, LineDash::Dotted];
+
+    /// The name shown by the settings-option-{name} message, e.g. "dashed".
+    pub fn name(self) -> &'static str {
+        match self {
+            LineDash::Solid => "solid",
+            LineDash::Dashed => "dashed",
+            LineDash::Dotted => "dotted",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Self {
+        LineDash::ALL.into_iter().find(|dash| dash.name() == name).unwrap_or_default()
+    }
+
+    /// SVG stroke-dasharray of a line `width` wide, so dashes keep their proportions.
+    pub fn dasharray(self, width: f64) -> String {
+        match self {
+            LineDash::Solid => "none".to_string(),
+            LineDash::Dashed => format!("{} {}", width * 4.0, width * 3.0),
+            LineDash::Dotted => format!("{} {}", width, width * 2.0),
+        }
+    }
+}
+
+/// How an indicator's line is drawn: its style from the indicator panel, with the defaults of its
+/// type filled in (see `default_line_style`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineStyle {
+    pub color: String,
+    pub width: f64,
+    pub dash: LineDash,
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        Self { color: "#9C27B0".to_string(), width: 2.0, dash: LineDash::Solid }
+    }
+}
+
+/// Line of an indicator nobody styled, from the `indicators` config: SMA and EMA lines take the color
+/// listed with their period (or the first one) and the configured width, RSI its color. Names come from
+/// the engine, e.g. "SMA(20)". Oscillators are drawn thinner, in their smaller panes.
+pub fn default_line_style(name: &str, defaults: &IndicatorDefaults) -> LineStyle {
+    let fallback = LineStyle {
+        width: if oscillator_scale(name).is_some() { 1.5 } else { 2.0 },
+        ..LineStyle::default()
+    };
+    let period: Option<u32> = name.split_once('(').and_then(|(_, rest)| rest.trim_end_matches(')').trim().parse().ok());
+    let setting = match indicator_type(name).to_lowercase().as_str() {
+        "sma" => &defaults.sma,
+        "ema" => &defaults.ema,
+        "rsi" => return LineStyle { color: defaults.rsi.color.clone(), ..fallback },
+        _ => return fallback,
+    };
+    let color_index = period.and_then(|period| setting.periods.iter().position(|p| *p == period)).unwrap_or(0);
+    LineStyle {
+        color: setting.colors.get(color_index).or(setting.colors.first()).cloned().unwrap_or(fallback.color),
+        width: setting.line_width as f64,
+        dash: LineDash::Solid,
+    }
+}
 
 // Removed manual Props struct definition
 // #[derive(Props, PartialEq, Clone)]
@@ -27,6 +111,8 @@ pub fn IndicatorOverlay(
     margin_top: f64,
     candle_plot_width: f64,
     num_candles_on_chart: usize,
+    // Line of each indicator, by name
+    styles: HashMap<String, LineStyle>,
 ) -> Element {
     if indicators.is_empty() {
         return None;
@@ -48,12 +134,8 @@ pub fn IndicatorOverlay(
         }
         points = points.trim_end().to_string();
 
-        let line_color = match indicator.name.to_lowercase().as_str() {
-            "sma" => "#FFC107",
-            "ema" => "#03A9F4",
-            _ => "#9C27B0"
-        };
-        let stroke_width_val = 2.0;
+        let line = styles.get(&indicator.name).cloned().unwrap_or_default();
+        let dasharray = line.dash.dasharray(line.width);
 
         if points.is_empty() {
             None
@@ -62,8 +144,9 @@ pub fn IndicatorOverlay(
                 polyline {
                     points: "{points}",
                     fill: "none",
-                    stroke: "{line_color}",
-                    stroke_width: "{stroke_width_val}"
+                    stroke: "{line.color}",
+                    stroke_width: "{line.width}",
+                    stroke_dasharray: "{dasharray}"
                 }
             })
         }
@@ -82,7 +165,7 @@ pub fn IndicatorOverlay(
             x: "{props.margin_left + 10.0}",
             y: "{props.margin_top + 60.0}",
             fill: "#88f",
-            font_size: "12px",
+            font_size: "1.2rem",
             "Indicator(0) Name: {props.indicators.first().map_or("N/A", |i| i.name.as_str())}, Values: {props.indicators.first().map_or(0, |i| i.values.len())}"
         }
         */
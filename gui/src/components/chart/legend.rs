@@ -0,0 +1,53 @@
+// Legend in the chart's top-left corner: the symbol and timeframe, then each indicator on the chart
+// with its value at the crosshair (or at the latest visible candle), in the color of its line.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+use shared::models::TimeFrame;
+use shared::utils::brazilian_format::format_decimal;
+
+use crate::config::theme::ThemePalette;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendEntry {
+    pub name: String,
+    pub color: String,
+    /// `None` where the indicator has no value yet (e.g. before its period is filled).
+    pub value: Option<f64>,
+}
+
+#[component]
+pub fn ChartLegend(
+    symbol: Option<String>,
+    timeframe: Option<TimeFrame>,
+    entries: Vec<LegendEntry>,
+    left: f64,
+    top: f64,
+    palette: ThemePalette,
+) -> Element {
+    let title = symbol.map(|symbol| match timeframe {
+        Some(timeframe) => t!("chart-legend-title", symbol = symbol, timeframe = timeframe.to_string()),
+        None => symbol,
+    });
+    if title.is_none() && entries.is_empty() {
+        return None;
+    }
+
+    rsx! {
+        div {
+            class: "chart-legend",
+            style: "position: absolute; left: {left}px; top: {top}px; pointer-events: none; font-size: 1.1rem; line-height: 1.4; padding: 2px 4px; border-radius: 3px; background-color: {palette.surface}cc; color: {palette.foreground};",
+            {title.map(|title| rsx! { div { style: "font-weight: bold;", "{title}" } })}
+            {entries.into_iter().map(|entry| {
+                let value = entry.value.map_or_else(|| t!("chart-legend-no-value"), |value| format_decimal(value, 2));
+                rsx! {
+                    div {
+                        key: "{entry.name}",
+                        style: "color: {entry.color};",
+                        span { style: "display: inline-block; width: 10px; height: 2px; margin-right: 4px; vertical-align: middle; background-color: {entry.color};" }
+                        "{entry.name} {value}"
+                    }
+                }
+            })}
+        }
+    }
+}
@@ -0,0 +1,78 @@
+// Ruler: shift-dragging over the price pane measures the move between two points, labelled with
+// the price change, percent change, number of bars and time elapsed between their candles.
+#![allow(non_snake_case)]
+use chrono::Duration;
+use dioxus::prelude::*;
+
+use crate::config::theme::ThemePalette;
+
+/// A measured span, from where the drag started to where the pointer is: candle index (into all
+/// candles) and price at each end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    pub from: (usize, f64),
+    pub to: (usize, f64),
+}
+
+impl Measurement {
+    pub fn at(index: usize, price: f64) -> Self {
+        Measurement { from: (index, price), to: (index, price) }
+    }
+
+    pub fn price_change(&self) -> f64 {
+        self.to.1 - self.from.1
+    }
+
+    /// Change relative to the starting price; `None` from a price of zero.
+    pub fn percent_change(&self) -> Option<f64> {
+        (self.from.1 != 0.0).then(|| self.price_change() / self.from.1 * 100.0)
+    }
+
+    /// Bars from one end to the other (negative when measuring backwards).
+    pub fn bars(&self) -> isize {
+        self.to.0 as isize - self.from.0 as isize
+    }
+}
+
+/// `elapsed` in its two largest units, e.g. "2d 4h" or "35min".
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let sign = if elapsed < Duration::zero() { "-" } else { "" };
+    let elapsed = elapsed.abs();
+    let units = [(elapsed.num_days(), "d"), (elapsed.num_hours() % 24, "h"), (elapsed.num_minutes() % 60, "min")];
+    let shown: Vec<String> = units.iter()
+        .skip_while(|(amount, _)| *amount == 0)
+        .take(2)
+        .filter(|(amount, _)| *amount != 0)
+        .map(|(amount, unit)| format!("{}{}", amount, unit))
+        .collect();
+    if shown.is_empty() { "0min".to_string() } else { format!("{}{}", sign, shown.join(" ")) }
+}
+
+#[component]
+pub fn MeasureOverlay(
+    // Corners of the measured box on the chart
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+    // What was measured, e.g. "+1.25 (+3.10%) · 12 bars · 1h"
+    label: String,
+    // Up (or flat) moves in the bullish color, down moves in the bearish one
+    rising: bool,
+    palette: ThemePalette,
+) -> Element {
+    let color = if rising { &palette.chart_bullish } else { &palette.chart_bearish };
+    let (left, width) = (x1.min(x2), (x2 - x1).abs());
+    let (top, height) = (y1.min(y2), (y2 - y1).abs());
+    // The label sits past the end of the move: above a rise, below a fall
+    let label_y = if rising { top - 6.0 } else { top + height + 14.0 };
+
+    rsx! {
+        g {
+            class: "measure",
+            rect { x: "{left}", y: "{top}", width: "{width}", height: "{height}", fill: "{color}", fill_opacity: "0.15", stroke: "{color}", stroke_width: "1" }
+            line { x1: "{x1}", y1: "{y1}", x2: "{x2}", y2: "{y2}", stroke: "{color}", stroke_width: "1", stroke_dasharray: "4 3" }
+            text { x: "{left + width / 2.0}", y: "{label_y}", fill: "{color}", font_size: "1.1rem", font_weight: "bold", text_anchor: "middle", "{label}" }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// Overview strip under the chart: every loaded close, squeezed into the plot's width, with the
+// visible range highlighted. Clicking it centers the view there; dragging the highlight moves it.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+
+use crate::components::chart::viewport::VisibleRange;
+use crate::config::theme::ThemePalette;
+
+/// Height of the strip, in pixels.
+pub const MINIMAP_HEIGHT: f64 = 36.0;
+
+/// Area points of `closes`, one per pixel column of `width` at most, closed along the bottom.
+fn overview_area(closes: &[f64], width: f64, height: f64) -> String {
+    let columns = (width.max(1.0) as usize).min(closes.len()).max(1);
+    let sampled: Vec<f64> = (0..columns).map(|column| closes[column * closes.len() / columns]).collect();
+    let (low, high) = sampled.iter().fold((f64::MAX, f64::MIN), |(low, high), &close| (low.min(close), high.max(close)));
+    let range = (high - low).max(f64::EPSILON);
+    let step = width / (columns.max(2) - 1) as f64;
+    let points: Vec<String> = sampled.iter().enumerate()
+        .map(|(i, close)| format!("{:.1},{:.1}", i as f64 * step, height - 2.0 - (close - low) / range * (height - 4.0)))
+        .collect();
+    format!("0,{height:.1} {} {:.1},{height:.1}", points.join(" "), (columns - 1) as f64 * step)
+}
+
+#[component]
+pub fn ChartMinimap(
+    // Closes of all the candles, oldest first
+    closes: Vec<f64>,
+    range: VisibleRange,
+    // Lined up with the chart's plot
+    margin_left: f64,
+    plot_width: f64,
+    palette: ThemePalette,
+    // Called with the range to show after a click or drag
+    on_jump: EventHandler<VisibleRange>,
+) -> Element {
+    // While dragging, how many candles the pointer is after the start of the range
+    let grab = use_ref(|| None::<f64>);
+
+    let total = closes.len();
+    if total < 2 {
+        return None;
+    }
+    let candle_width = plot_width / total as f64;
+    let window_x = range.first as f64 * candle_width;
+    let window_width = (range.len() as f64 * candle_width).max(2.0);
+    let area = overview_area(&closes, plot_width, MINIMAP_HEIGHT);
+    // The range starting `grabbed` candles before the candle under x (from the strip's left edge)
+    let move_to = move |x: f64, grabbed: f64| {
+        let first = (x / candle_width - grabbed).round() as isize;
+        range.pan(first - range.first as isize, total)
+    };
+
+    rsx! {
+        div {
+            class: "chart-minimap",
+            title: t!("chart-minimap-hint"),
+            style: "position: relative; height: {MINIMAP_HEIGHT}px; margin-left: {margin_left}px; width: {plot_width}px; border: 1px solid {palette.border}; border-top: none; background-color: {palette.surface}; cursor: pointer;",
+            onmousedown: move |event: Event<MouseData>| {
+                let x = event.element_coordinates().x;
+                let offset = x / candle_width - range.first as f64;
+                // Grabbing the highlight keeps the pointer where it is on it; elsewhere centers the range
+                let grabbed = if (0.0..range.len() as f64).contains(&offset) { offset } else { range.len() as f64 / 2.0 };
+                *grab.write() = Some(grabbed);
+                on_jump.call(move_to(x, grabbed));
+            },
+            onmousemove: move |event: Event<MouseData>| {
+                let Some(grabbed) = *grab.read() else { return };
+                on_jump.call(move_to(event.element_coordinates().x, grabbed));
+            },
+            onmouseup: move |_| *grab.write() = None,
+            onmouseleave: move |_| *grab.write() = None,
+            svg {
+                width: "{plot_width}",
+                height: "{MINIMAP_HEIGHT}",
+                style: "position: absolute; left: 0; top: 0; pointer-events: none;",
+                polygon { points: "{area}", fill: "{palette.primary}", fill_opacity: "0.25", stroke: "{palette.primary}", stroke_width: "1" }
+                rect {
+                    x: "{window_x}",
+                    y: "0",
+                    width: "{window_width}",
+                    height: "{MINIMAP_HEIGHT}",
+                    fill: "{palette.accent}",
+                    fill_opacity: "0.2",
+                    stroke: "{palette.accent}",
+                    stroke_width: "1"
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,17 @@
 // Chart components module
 pub mod candlestick;
+pub mod canvas; // Canvas backend for candles and volume bars of large ranges
 pub mod indicators; // Visualization of indicators on the chart
+pub mod oscillator; // Sub-panes for oscillators with their own scales
+pub mod volume; // Volume histogram sub-pane
+pub mod viewport; // Zoomed/panned range of visible candles
+pub mod trades; // Buy/sell markers and position lines
+pub mod legend; // Symbol, timeframe and indicator values at the crosshair
+pub mod style; // Candlestick, bar, line, area and Heikin-Ashi styles
+pub mod sessions; // Session shading and day separators of intraday charts
+pub mod measure; // Ruler measuring moves between two points
+pub mod context_menu; // Right-click menu of actions at a point of the chart
+pub mod minimap; // Overview strip of all the candles, to jump to any period
 
 // pub use candlestick::CandlestickChart;
 // pub use indicators::IndicatorOverlay;
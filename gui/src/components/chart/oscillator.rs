@@ -0,0 +1,152 @@
+// Sub-panes for oscillators (RSI, Stochastic, MACD histogram...), which have their own y-scale
+// instead of sharing the price scale with the candles.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+use shared::models::Indicator;
+
+use crate::components::chart::indicators::LineStyle;
+use crate::config::theme::ThemePalette;
+
+/// Share of the chart's plot height taken by each oscillator pane.
+pub const OSCILLATOR_PANE_SHARE: f64 = 0.18;
+
+/// How an oscillator's values map to its pane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OscillatorScale {
+    /// A bounded line, e.g. RSI in 0–100, with dashed reference levels (overbought/oversold).
+    Bounded { min: f64, max: f64, levels: &'static [f64] },
+    /// Bars around a zero line, scaled to the largest visible magnitude (e.g. MACD histogram).
+    Histogram,
+    /// A line scaled to its visible values (e.g. ATR, which is far below the price).
+    Fitted,
+}
+
+/// The pane an indicator is drawn in: `None` for the price pane, or its oscillator scale.
+/// Indicator names come from the engine, e.g. "RSI(14)"; the parameters are ignored.
+pub fn oscillator_scale(indicator_name: &str) -> Option<OscillatorScale> {
+    let base_name = indicator_name.split('(').next().unwrap_or_default().trim().to_lowercase();
+    match base_name.as_str() {
+        "rsi" => Some(OscillatorScale::Bounded { min: 0.0, max: 100.0, levels: &[30.0, 70.0] }),
+        "stoch" | "stochastic" => Some(OscillatorScale::Bounded { min: 0.0, max: 100.0, levels: &[20.0, 80.0] }),
+        "macd_histogram" | "macd histogram" | "macd_hist" => Some(OscillatorScale::Histogram),
+        "atr" => Some(OscillatorScale::Fitted),
+        _ => None,
+    }
+}
+
+#[component]
+pub fn OscillatorPane(
+    // Values of the candles on screen, aligned with them
+    indicator: Indicator,
+    scale: OscillatorScale,
+    line: LineStyle,
+    margin_left: f64,
+    pane_top: f64,
+    pane_width: f64,
+    pane_height: f64,
+    candle_plot_width: f64,
+    palette: ThemePalette,
+) -> Element {
+    let finite_values = || indicator.values.iter().copied().filter(|v| v.is_finite());
+    let (min_value, max_value) = match scale {
+        OscillatorScale::Bounded { min, max, .. } => (min, max),
+        OscillatorScale::Histogram => {
+            let magnitude = finite_values().fold(0.0, |m: f64, v| m.max(v.abs()));
+            (-magnitude, magnitude)
+        }
+        OscillatorScale::Fitted => (
+            finite_values().fold(f64::INFINITY, f64::min),
+            finite_values().fold(f64::NEG_INFINITY, f64::max),
+        ),
+    };
+    let value_range = if max_value - min_value > 0.0 && (max_value - min_value).is_finite() { max_value - min_value } else { 1.0 };
+    let value_to_y = |value: f64| pane_top + (max_value - value.clamp(min_value, max_value)) * pane_height / value_range;
+
+    let series = match scale {
+        OscillatorScale::Histogram => {
+            let zero_y = value_to_y(0.0);
+            let bar_width = (candle_plot_width * 0.7).max(1.0);
+            let bars = indicator.values.iter().enumerate().filter(|(_, v)| v.is_finite()).map(|(i, &value)| {
+                let bar_x = margin_left + (i as f64 * candle_plot_width) + (candle_plot_width - bar_width) / 2.0;
+                let value_y = value_to_y(value);
+                let color = if value >= 0.0 { &palette.chart_bullish } else { &palette.chart_bearish };
+                rsx! {
+                    rect {
+                        x: "{bar_x}",
+                        y: "{value_y.min(zero_y)}",
+                        width: "{bar_width}",
+                        height: "{(value_y - zero_y).abs()}",
+                        fill: "{color}"
+                    }
+                }
+            });
+            rsx! { {bars} }
+        }
+        _ => {
+            let points = indicator.values.iter().enumerate().filter(|(_, v)| v.is_finite()).map(|(i, &value)| {
+                let x = margin_left + (i as f64 * candle_plot_width) + (candle_plot_width / 2.0);
+                format!("{:.2},{:.2}", x, value_to_y(value))
+            }).collect::<Vec<_>>().join(" ");
+            rsx! {
+                polyline {
+                    points: "{points}",
+                    fill: "none",
+                    stroke: "{line.color}",
+                    stroke_width: "{line.width}",
+                    stroke_dasharray: "{line.dash.dasharray(line.width)}"
+                }
+            }
+        }
+    };
+
+    // Reference lines: overbought/oversold levels, or zero for histograms
+    let levels: Vec<f64> = match scale {
+        OscillatorScale::Bounded { levels, .. } => levels.to_vec(),
+        OscillatorScale::Histogram => vec![0.0],
+        OscillatorScale::Fitted => Vec::new(),
+    };
+    let level_lines = levels.into_iter().map(|level| {
+        let level_y = value_to_y(level);
+        rsx! {
+            line {
+                x1: "{margin_left}",
+                y1: "{level_y}",
+                x2: "{margin_left + pane_width}",
+                y2: "{level_y}",
+                stroke: "{palette.secondary}",
+                stroke_width: "1",
+                stroke_dasharray: "4 3"
+            }
+            text {
+                x: "{margin_left - 4.0}",
+                y: "{level_y + 3.0}",
+                fill: "{palette.muted}",
+                font_size: "1rem",
+                text_anchor: "end",
+                "{level}"
+            }
+        }
+    });
+
+    rsx! {
+        g {
+            class: "oscillator-pane",
+            rect {
+                x: "{margin_left}",
+                y: "{pane_top}",
+                width: "{pane_width}",
+                height: "{pane_height}",
+                fill: "{palette.surface}"
+            }
+            {level_lines}
+            {series}
+            text {
+                x: "{margin_left + 4.0}",
+                y: "{pane_top + 11.0}",
+                fill: "{palette.muted}",
+                font_size: "1rem",
+                "{indicator.name}"
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+// Trading sessions on intraday charts: candles outside the regular session (the opening and closing
+// calls, after the close, days B3 does not trade) are shaded, and a line marks where each day starts.
+// Sessions come from the engine's B3 calendar, which reads candle timestamps as São Paulo time.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+use engine::data::calendar::{session, SessionPhase};
+use shared::models::Candle;
+
+use crate::config::theme::ThemePalette;
+
+/// Where a candle falls relative to its day's regular session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionPart {
+    Regular,
+    /// Before or after the continuous session of a trading day, auctions included.
+    Extended,
+    /// A weekend or exchange holiday.
+    NonTrading,
+}
+
+impl SessionPart {
+    pub fn of(candle: &Candle) -> Self {
+        match session(&candle.symbol, candle.timestamp.date_naive()) {
+            None => SessionPart::NonTrading,
+            Some(day) if day.phase_at(candle.timestamp.time()) == SessionPhase::Continuous => SessionPart::Regular,
+            Some(_) => SessionPart::Extended,
+        }
+    }
+}
+
+/// Shaded spans of `candles` (x, width, part), one per run of candles outside the regular session.
+pub fn session_shades(candles: &[Candle], margin_left: f64, candle_plot_width: f64) -> Vec<(f64, f64, SessionPart)> {
+    let mut shades: Vec<(f64, f64, SessionPart)> = Vec::new();
+    let mut previous = SessionPart::Regular;
+    for (i, candle) in candles.iter().enumerate() {
+        let part = SessionPart::of(candle);
+        match shades.last_mut() {
+            _ if part == SessionPart::Regular => {}
+            Some((_, width, _)) if part == previous => *width += candle_plot_width,
+            _ => shades.push((margin_left + i as f64 * candle_plot_width, candle_plot_width, part)),
+        }
+        previous = part;
+    }
+    shades
+}
+
+/// X of each candle that starts a new day, after the first one on screen.
+pub fn session_separators(candles: &[Candle], margin_left: f64, candle_plot_width: f64) -> Vec<f64> {
+    candles.windows(2).enumerate()
+        .filter(|(_, pair)| pair[0].timestamp.date_naive() != pair[1].timestamp.date_naive())
+        .map(|(i, _)| margin_left + (i + 1) as f64 * candle_plot_width)
+        .collect()
+}
+
+#[component]
+pub fn SessionOverlay(
+    // The candles on screen, in the same order and spacing as the price pane
+    candles: Vec<Candle>,
+    margin_left: f64,
+    margin_top: f64,
+    // Shades cover the price pane; separators run down through the sub-panes as well
+    pane_height: f64,
+    plot_height: f64,
+    candle_plot_width: f64,
+    palette: ThemePalette,
+) -> Element {
+    let shades = session_shades(&candles, margin_left, candle_plot_width);
+    let separators = session_separators(&candles, margin_left, candle_plot_width);
+
+    rsx! {
+        g {
+            class: "sessions",
+            {shades.into_iter().map(|(x, width, part)| {
+                let opacity = if part == SessionPart::NonTrading { "0.16" } else { "0.08" };
+                rsx! { rect { x: "{x}", y: "{margin_top}", width: "{width}", height: "{pane_height}", fill: "{palette.muted}", fill_opacity: "{opacity}" } }
+            })}
+            {separators.into_iter().map(|x| rsx! {
+                line { x1: "{x}", y1: "{margin_top}", x2: "{x}", y2: "{margin_top + plot_height}", stroke: "{palette.border}", stroke_width: "1", stroke_dasharray: "2 4" }
+            })}
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// Chart styles: how the price pane draws candles. Set by `chart.type` in the config, and switched
+// from the header or the command palette.
+use serde::{Deserialize, Serialize};
+use shared::models::{Candle, Price};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChartType {
+    #[default]
+    Candlestick,
+    /// OHLC bars: the high-low range, with the open ticked on the left and the close on the right.
+    Bar,
+    /// The closes joined by a line.
+    Line,
+    /// The line of closes, filled down to the bottom of the pane.
+    Area,
+    /// Candles of averaged prices (see `heikin_ashi`), which smooth out the noise in trends.
+    HeikinAshi,
+}
+
+impl ChartType {
+    pub const ALL: [ChartType; 5] = [ChartType::Candlestick, ChartType::Bar, ChartType::Line, ChartType::Area, ChartType::HeikinAshi];
+
+    /// The name used by `chart.type`, e.g. "heikin-ashi"; the settings-option-{name} message names it in the GUI.
+    pub fn name(self) -> &'static str {
+        match self {
+            ChartType::Candlestick => "candlestick",
+            ChartType::Bar => "bar",
+            ChartType::Line => "line",
+            ChartType::Area => "area",
+            ChartType::HeikinAshi => "heikin-ashi",
+        }
+    }
+
+    /// The style named `name`; candlesticks for unknown names.
+    pub fn from_name(name: &str) -> Self {
+        ChartType::ALL.into_iter().find(|chart_type| chart_type.name() == name).unwrap_or_default()
+    }
+
+    /// Whether the price pane draws a line of closes instead of one shape per candle.
+    pub fn is_line(self) -> bool {
+        matches!(self, ChartType::Line | ChartType::Area)
+    }
+}
+
+/// Heikin-Ashi candles of `candles`: each closes at the average of its candle's prices and opens
+/// halfway through the previous Heikin-Ashi candle, so it depends on all the candles before it.
+pub fn heikin_ashi(candles: &[Candle]) -> Vec<Candle> {
+    let mut averaged: Vec<Candle> = Vec::with_capacity(candles.len());
+    for candle in candles {
+        let close = (candle.open + candle.high + candle.low + candle.close) / Price::from(4);
+        let open = match averaged.last() {
+            Some(previous) => (previous.open + previous.close) / Price::from(2),
+            None => (candle.open + candle.close) / Price::from(2),
+        };
+        averaged.push(Candle {
+            open,
+            high: candle.high.max(open).max(close),
+            low: candle.low.min(open).min(close),
+            close,
+            ..candle.clone()
+        });
+    }
+    averaged
+}
@@ -0,0 +1,128 @@
+// Trade markers on the price pane: an arrow per fill (buys below the candle, sells above it) and a
+// dashed line from each position's entry to its exit, colored by its P&L. Hovering shows the details.
+#![allow(non_snake_case)]
+use chrono::{DateTime, Utc};
+use dioxus::prelude::*;
+use shared::models::{Candle, OrderSide};
+use shared::utils::brazilian_format::{format_datetime, format_decimal, format_signed_decimal};
+
+use crate::config::theme::ThemePalette;
+use crate::state::trades::ChartTrades;
+
+/// Height of a marker arrow, and its gap from the candle's high or low.
+const MARKER_SIZE: f64 = 7.0;
+
+#[component]
+pub fn TradeOverlay(
+    trades: ChartTrades,
+    // Visible candles; a trade belongs to the last one opened at or before it
+    candles: Vec<Candle>,
+    // Open time of the candle after the visible ones, if any: trades from then on are off to the right
+    next_timestamp: Option<DateTime<Utc>>,
+    min_price: f64,
+    max_price: f64,
+    margin_left: f64,
+    margin_top: f64,
+    pane_height: f64,
+    candle_plot_width: f64,
+    price_decimals: usize,
+    palette: ThemePalette,
+) -> Element {
+    if candles.is_empty() {
+        return None;
+    }
+    let y_scale = pane_height / (max_price - min_price).max(f64::EPSILON);
+    let price_to_y = |price: f64| (margin_top + (max_price - price) * y_scale).clamp(margin_top, margin_top + pane_height);
+    // Index of the visible candle holding `timestamp`: `Err` is -1 before the first, `len` after the last
+    let candle_index = |timestamp: DateTime<Utc>| -> Result<usize, isize> {
+        if next_timestamp.is_some_and(|next| timestamp >= next) {
+            return Err(candles.len() as isize);
+        }
+        match candles.partition_point(|candle| candle.timestamp <= timestamp) {
+            0 => Err(-1),
+            after => Ok(after - 1),
+        }
+    };
+    let candle_x = |index: f64| margin_left + (index + 0.5) * candle_plot_width;
+    let pnl_color = |pnl: f64| if pnl >= 0.0 { &palette.chart_bullish } else { &palette.chart_bearish };
+
+    let markers = trades.markers.iter().filter_map(|marker| {
+        let index = candle_index(marker.timestamp).ok()?;
+        let candle = &candles[index];
+        let x = candle_x(index as f64);
+        let half_width = (candle_plot_width * 0.4).clamp(3.0, MARKER_SIZE);
+        // Arrow tip at the candle, pointing up for buys and down for sells
+        let (points, color, action) = match marker.side {
+            OrderSide::Buy => {
+                let tip = price_to_y(candle.low_f64()) + MARKER_SIZE / 2.0;
+                (format!("{x},{tip} {},{} {},{}", x - half_width, tip + MARKER_SIZE, x + half_width, tip + MARKER_SIZE), &palette.chart_bullish, t!("trade-buy"))
+            }
+            OrderSide::Sell => {
+                let tip = price_to_y(candle.high_f64()) - MARKER_SIZE / 2.0;
+                (format!("{x},{tip} {},{} {},{}", x - half_width, tip - MARKER_SIZE, x + half_width, tip - MARKER_SIZE), &palette.chart_bearish, t!("trade-sell"))
+            }
+        };
+        let fill = t!("trade-fill", action = action, quantity = marker.quantity, price = format_decimal(marker.price, price_decimals));
+        let mut tooltip = format!("{}\n{}", fill, format_datetime(marker.timestamp));
+        if let Some(pnl) = marker.realized_pnl {
+            tooltip.push_str(&format!("\n{}", t!("trade-pnl", pnl = format_signed_decimal(pnl, 2))));
+        }
+        Some(rsx! {
+            polygon {
+                points: "{points}",
+                fill: "{color}",
+                stroke: "{palette.background}",
+                stroke_width: "0.5",
+                style: "pointer-events: auto;",
+                title { "{tooltip}" }
+            }
+        })
+    });
+
+    // Positions run from the entry to the exit fill (to the right edge while open), clipped to the view
+    let spans = trades.spans.iter().filter_map(|span| {
+        let position = |timestamp: DateTime<Utc>| match candle_index(timestamp) {
+            Ok(index) => index as f64,
+            Err(outside) => outside as f64,
+        };
+        let (entry_x, entry_price) = (position(span.entry.0), span.entry.1);
+        let (exit_x, exit_price) = match span.exit {
+            Some((exited_at, price)) => (position(exited_at), price),
+            None => (candles.len() as f64 - 1.0, entry_price),
+        };
+        let last = candles.len() as f64 - 1.0;
+        if exit_x < 0.0 || entry_x > last {
+            return None;
+        }
+        let price_at = |x: f64| if exit_x > entry_x { entry_price + (exit_price - entry_price) * (x - entry_x) / (exit_x - entry_x) } else { entry_price };
+        let (from_x, to_x) = (entry_x.max(0.0), exit_x.min(last));
+        let color = pnl_color(span.pnl);
+        let direction = if span.long { t!("trade-long") } else { t!("trade-short") };
+        let (entry, pnl) = (format_decimal(entry_price, 2), format_signed_decimal(span.pnl, 2));
+        let tooltip = match span.exit {
+            Some((_, exit_price)) => format!("{}\n{}", t!("trade-position", direction = direction, entry = entry, exit = format_decimal(exit_price, 2)), t!("trade-pnl", pnl = pnl)),
+            None => format!("{}\n{}", t!("trade-position-open", direction = direction, entry = entry), t!("trade-realized-pnl", pnl = pnl)),
+        };
+        Some(rsx! {
+            line {
+                x1: "{candle_x(from_x)}",
+                y1: "{price_to_y(price_at(from_x))}",
+                x2: "{candle_x(to_x)}",
+                y2: "{price_to_y(price_at(to_x))}",
+                stroke: "{color}",
+                stroke_width: "1.5",
+                stroke_dasharray: "4 3",
+                style: "pointer-events: auto;",
+                title { "{tooltip}" }
+            }
+        })
+    });
+
+    rsx! {
+        g {
+            class: "trade-markers",
+            {spans}
+            {markers}
+        }
+    }
+}
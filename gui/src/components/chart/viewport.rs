@@ -0,0 +1,107 @@
+// Visible range of the chart: which candles are on screen after zooming and panning.
+use chrono::{DateTime, Utc};
+
+/// Zooming in stops once this many candles are visible (or all of them, if there are fewer).
+pub const MIN_VISIBLE_CANDLES: usize = 10;
+
+/// A zoom asked for from outside the chart, e.g. by a keyboard shortcut.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoomStep {
+    In,
+    Out,
+    /// Shows every candle again.
+    Reset,
+    /// Shows the candles from `first` to `last` with some context around them, e.g. a backtest
+    /// trade picked in the results view.
+    Span { first: DateTime<Utc>, last: DateTime<Utc> },
+}
+
+/// What linked charts share (see `AppState::charts_linked`): the times of the first and last visible
+/// candles and under the crosshair, as last seen on the chart of `symbol`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkedView {
+    pub symbol: String,
+    pub first: DateTime<Utc>,
+    pub last: DateTime<Utc>,
+    pub crosshair: Option<DateTime<Utc>>,
+}
+
+/// Indices of the first and last visible candles, both inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VisibleRange {
+    pub first: usize,
+    pub last: usize,
+}
+
+impl VisibleRange {
+    /// The range showing all `total` candles.
+    pub fn all(total: usize) -> Self {
+        VisibleRange { first: 0, last: total.saturating_sub(1) }
+    }
+
+    /// Number of visible candles.
+    pub fn len(&self) -> usize {
+        self.last - self.first + 1
+    }
+
+    /// Whether the candle at `index` is on screen.
+    pub fn contains(&self, index: usize) -> bool {
+        (self.first..=self.last).contains(&index)
+    }
+
+    /// Scales the number of visible candles by `factor` (< 1 zooms in, > 1 zooms out), keeping
+    /// the candle at `anchor` (0.0 = left edge, 1.0 = right edge of the plot) where it is.
+    pub fn zoom(self, factor: f64, anchor: f64, total: usize) -> Self {
+        if total == 0 {
+            return self;
+        }
+        let anchor = anchor.clamp(0.0, 1.0);
+        let len = self.len() as f64;
+        let new_len = ((len * factor).round() as usize).clamp(MIN_VISIBLE_CANDLES.min(total), total);
+        let anchor_index = self.first as f64 + anchor * len;
+        let new_first = (anchor_index - anchor * new_len as f64).round().max(0.0) as usize;
+        VisibleRange { first: new_first, last: new_first + new_len - 1 }.clamp(total)
+    }
+
+    /// Moves the range by `candles` (negative = towards older candles), keeping its length.
+    pub fn pan(self, candles: isize, total: usize) -> Self {
+        let len = self.len();
+        let max_first = total.saturating_sub(len) as isize;
+        let first = (self.first as isize + candles).clamp(0, max_first) as usize;
+        VisibleRange { first, last: first + len - 1 }.clamp(total)
+    }
+
+    /// The range after the number of candles went from `old_total` to `new_total`: still everything
+    /// if it showed everything, still the latest candles if it ended at the last one, else unchanged.
+    pub fn follow(self, old_total: usize, new_total: usize) -> Self {
+        if old_total == 0 || self == VisibleRange::all(old_total) {
+            return VisibleRange::all(new_total);
+        }
+        if self.last + 1 == old_total && new_total > old_total {
+            return self.pan((new_total - old_total) as isize, new_total);
+        }
+        self.clamp(new_total)
+    }
+
+    /// The candles of `timestamps` (in time order) from `first` to `last`; `None` if there are none.
+    pub fn covering(timestamps: &[DateTime<Utc>], first: DateTime<Utc>, last: DateTime<Utc>) -> Option<Self> {
+        let start = timestamps.partition_point(|timestamp| *timestamp < first);
+        let end = timestamps.partition_point(|timestamp| *timestamp <= last);
+        (start < end).then(|| VisibleRange { first: start, last: end - 1 })
+    }
+
+    /// The range with up to `candles` more on each side, within `total` candles.
+    pub fn widen(self, candles: usize, total: usize) -> Self {
+        VisibleRange { first: self.first.saturating_sub(candles), last: (self.last + candles).min(total.saturating_sub(1)) }
+    }
+
+    /// The range moved and shrunk as needed to fit in `total` candles.
+    pub fn clamp(self, total: usize) -> Self {
+        if total == 0 {
+            return VisibleRange::all(0);
+        }
+        let len = self.len().min(total);
+        let first = self.first.min(total - len);
+        VisibleRange { first, last: first + len - 1 }
+    }
+}
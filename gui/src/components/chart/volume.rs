@@ -0,0 +1,91 @@
+// Volume histogram drawn in its own pane beneath the candles
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+use shared::models::Candle;
+use shared::utils::brazilian_format::format_decimal;
+
+use crate::config::theme::ThemePalette;
+
+/// Share of the chart's plot height taken by the volume pane.
+pub const VOLUME_PANE_SHARE: f64 = 0.2;
+
+/// Bars of `candles`: x, y, width, height and whether the candle is bullish.
+/// Bars are scaled to the largest volume, so they fill the pane after zooming too.
+pub fn volume_bars(candles: &[Candle], margin_left: f64, pane_top: f64, pane_height: f64, candle_plot_width: f64) -> Vec<(f64, f64, f64, f64, bool)> {
+    let max_volume = candles.iter().map(|c| c.volume).fold(0.0, f64::max);
+    let volume_scale = if max_volume > 0.0 { pane_height / max_volume } else { 0.0 };
+    let bar_width = (candle_plot_width * 0.7).max(1.0);
+    let bar_offset = (candle_plot_width - bar_width) / 2.0;
+    candles.iter().enumerate().map(|(i, candle)| {
+        let bar_height = candle.volume.max(0.0) * volume_scale;
+        let bar_x = margin_left + (i as f64 * candle_plot_width) + bar_offset;
+        (bar_x, pane_top + pane_height - bar_height, bar_width, bar_height, candle.is_bullish())
+    }).collect()
+}
+
+#[component]
+pub fn VolumePane(
+    // The candles on screen, in the same order and spacing as the price pane above
+    candles: Vec<Candle>,
+    margin_left: f64,
+    pane_top: f64,
+    pane_width: f64,
+    pane_height: f64,
+    candle_plot_width: f64,
+    palette: ThemePalette,
+    // The background and bars are already drawn on the chart's canvas; only the label is left
+    on_canvas: bool,
+) -> Element {
+    let max_volume = candles.iter().map(|c| c.volume).fold(0.0, f64::max);
+    let bars = if on_canvas { Vec::new() } else { volume_bars(&candles, margin_left, pane_top, pane_height, candle_plot_width) };
+
+    let bar_elements = bars.into_iter().map(|(bar_x, bar_y, bar_width, bar_height, bullish)| {
+        // Same colors as the candle bodies: bullish or bearish
+        let color = if bullish { &palette.chart_bullish } else { &palette.chart_bearish };
+        rsx! {
+            rect {
+                x: "{bar_x}",
+                y: "{bar_y}",
+                width: "{bar_width}",
+                height: "{bar_height}",
+                fill: "{color}",
+                fill_opacity: "0.6"
+            }
+        }
+    });
+
+    rsx! {
+        g {
+            class: "volume-pane",
+            // Pane background, separate from the price pane
+            if !on_canvas {
+                rect {
+                    x: "{margin_left}",
+                    y: "{pane_top}",
+                    width: "{pane_width}",
+                    height: "{pane_height}",
+                    fill: "{palette.surface}"
+                }
+            }
+            {bar_elements}
+            text {
+                x: "{margin_left - 4.0}",
+                y: "{pane_top + 10.0}",
+                fill: "{palette.muted}",
+                font_size: "1rem",
+                text_anchor: "end",
+                "{format_volume(max_volume)}"
+            }
+        }
+    }
+}
+
+/// Compact volume label, e.g. 1,5M.
+fn format_volume(volume: f64) -> String {
+    match volume {
+        v if v >= 1e9 => format!("{}B", format_decimal(v / 1e9, 1)),
+        v if v >= 1e6 => format!("{}M", format_decimal(v / 1e6, 1)),
+        v if v >= 1e3 => format!("{}K", format_decimal(v / 1e3, 1)),
+        v => format_decimal(v, 0),
+    }
+}
@@ -0,0 +1,470 @@
+// Commands of the command palette
+// What each command does, kept apart from the palette's UI so a command can also run from a
+// keyboard shortcut (e.g. re-running the last one).
+use dioxus::prelude::*;
+use dioxus_desktop::DesktopContext;
+use shared::models::OrderSide;
+
+use crate::components::chart::context_menu::ChartPoint;
+use crate::components::chart::style::ChartType;
+use crate::components::chart::viewport::ZoomStep;
+use crate::config::shortcuts::ShortcutAction;
+use crate::i18n::Language;
+use crate::state::app_state::{AppState, Panel, PriceLevel};
+use crate::state::preferences::{save_language, save_theme};
+use crate::config::AppConfig; // Import AppConfig
+use crate::services::chart_export::{export_chart, ChartImageFormat};
+use crate::services::data_export::{export_visible_data, DataFormat};
+use crate::services::engine_client::EngineClient; // Import EngineClient
+use crate::services::project::open_project;
+use crate::services::symbols::load_csv_file;
+use crate::services::trades::{show_backtest_trades, show_simulated_trades};
+use crate::state::project::Project;
+use crate::state::session::RecentFileKind;
+use serde_json::json; // For indicator parameters
+
+/// Quantity of the limit orders placed from the chart's context menu.
+const CHART_ORDER_QUANTITY: f64 = 1.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    LoadCsv { path: Option<String> },
+    Configure,
+    OpenSettings,
+    EditConnection,
+    Exit,
+    AddIndicator { indicator_type: String },
+    RemoveIndicator { name: String },
+    ToggleTheme,
+    SetLanguage { language: Language },
+    SetChartType { chart_type: ChartType },
+    ToggleChartLink,
+    TogglePanel { panel: Panel },
+    BacktestTrades { strategy: String },
+    ShowBacktestResults,
+    SaveProject { path: Option<String> },
+    LoadProject { path: Option<String> },
+    ExportChart { format: ChartImageFormat },
+    ExportData { format: DataFormat },
+    // Actions at a point of the chart (its context menu)
+    PlaceLimitOrder { side: OrderSide, price: f64 },
+    AddPriceAlert { price: f64 },
+    CopyText { text: String },
+}
+
+#[derive(Clone, Debug)] // Added Debug for easier inspection
+pub struct CommandDefinition {
+    pub id: usize, // Unique ID for keying and selection
+    pub key: String, // Stable name, whatever the language, e.g. "toggle-theme"; kept in the command history
+    pub name: String,
+    pub description: String,
+    pub shortcut: Option<String>,
+    pub action: Command,
+}
+
+impl CommandDefinition {
+    /// A command named and described by the `command-{key}` and `command-{key}-desc` messages.
+    fn new(id: usize, key: &str, action: Command) -> Self {
+        Self::with_text(id, key, t!(format!("command-{}", key)), t!(format!("command-{}-desc", key)), action)
+    }
+
+    fn with_text(id: usize, key: &str, name: String, description: String, action: Command) -> Self {
+        Self {
+            id,
+            key: key.to_string(),
+            name,
+            description,
+            shortcut: None,
+            action,
+        }
+    }
+}
+
+/// The commands on offer, in the current language: the built-in ones, one to switch to each other
+/// language and chart style, one to show or hide each panel and one to open each recent file, then
+/// one per indicator of the current symbol to remove it.
+pub fn available_commands(app_state: &AppState) -> Vec<CommandDefinition> {
+    let mut commands = vec![
+        CommandDefinition::new(0, "load-csv", Command::LoadCsv { path: Some("tests/data/sample.csv".to_string()) }),
+        CommandDefinition::new(1, "add-sma", Command::AddIndicator { indicator_type: "SMA".to_string() }),
+        CommandDefinition::new(2, "add-ema", Command::AddIndicator { indicator_type: "EMA".to_string() }),
+        CommandDefinition::new(3, "add-rsi", Command::AddIndicator { indicator_type: "RSI".to_string() }),
+        CommandDefinition::new(4, "toggle-theme", Command::ToggleTheme),
+        CommandDefinition::new(5, "backtest-sma-crossover", Command::BacktestTrades { strategy: "sma_crossover".to_string() }),
+        CommandDefinition::new(6, "save-project", Command::SaveProject { path: None }),
+        CommandDefinition::new(7, "open-project", Command::LoadProject { path: None }),
+        CommandDefinition::new(8, "edit-shortcuts", Command::Configure),
+        CommandDefinition::new(9, "settings", Command::OpenSettings),
+        CommandDefinition::new(10, "engine-connection", Command::EditConnection),
+        CommandDefinition::new(11, "export-chart-png", Command::ExportChart { format: ChartImageFormat::Png }),
+        CommandDefinition::new(12, "export-chart-svg", Command::ExportChart { format: ChartImageFormat::Svg }),
+        CommandDefinition::new(13, "link-charts", Command::ToggleChartLink),
+        CommandDefinition::new(14, "backtest-results", Command::ShowBacktestResults),
+        CommandDefinition::new(15, "export-data-csv", Command::ExportData { format: DataFormat::Csv }),
+        CommandDefinition::new(16, "export-data-json", Command::ExportData { format: DataFormat::Json }),
+        CommandDefinition::new(17, "exit", Command::Exit),
+        // More commands...
+    ];
+    // Languages are listed by their own names, so they can be found whatever the current one
+    let first_language_id = commands.len();
+    let other_languages = Language::ALL.into_iter().filter(|language| *language != app_state.language);
+    commands.extend(other_languages.enumerate().map(|(i, language)| {
+        CommandDefinition::with_text(
+            first_language_id + i,
+            &format!("language-{}", language.code()),
+            t!("command-switch-language", language = language.native_name()),
+            t!("command-switch-language-desc", language = language.native_name()),
+            Command::SetLanguage { language },
+        )
+    }));
+    let first_style_id = commands.len();
+    let other_styles = ChartType::ALL.into_iter().filter(|chart_type| *chart_type != app_state.chart_type);
+    commands.extend(other_styles.enumerate().map(|(i, chart_type)| {
+        let style = t!(format!("settings-option-{}", chart_type.name()));
+        CommandDefinition::with_text(
+            first_style_id + i,
+            &format!("chart-style-{}", chart_type.name()),
+            t!("command-chart-style", style = style),
+            t!("command-chart-style-desc", style = style),
+            Command::SetChartType { chart_type },
+        )
+    }));
+    let first_panel_id = commands.len();
+    commands.extend(Panel::ALL.into_iter().enumerate().map(|(i, panel)| {
+        CommandDefinition::new(first_panel_id + i, &format!("panel-{}", panel.name()), Command::TogglePanel { panel })
+    }));
+    let first_recent_id = commands.len();
+    commands.extend(app_state.recent_files.iter().enumerate().map(|(i, recent)| {
+        let action = match recent.kind {
+            RecentFileKind::Csv => Command::LoadCsv { path: Some(recent.path.clone()) },
+            RecentFileKind::Project => Command::LoadProject { path: Some(recent.path.clone()) },
+        };
+        CommandDefinition::with_text(
+            first_recent_id + i,
+            &format!("recent:{}", recent.path),
+            t!("command-open-recent", file = recent.path),
+            t!(if recent.kind == RecentFileKind::Csv { "command-open-recent-csv-desc" } else { "command-open-recent-project-desc" }),
+            action,
+        )
+    }));
+    let removable_indicators = app_state.current_symbol_display.as_ref()
+        .and_then(|symbol| app_state.all_indicators.get(symbol))
+        .into_iter()
+        .flatten();
+    let first_dynamic_id = commands.len();
+    commands.extend(removable_indicators.enumerate().map(|(i, indicator)| CommandDefinition::with_text(
+        first_dynamic_id + i,
+        &format!("remove-indicator:{}", indicator.name),
+        t!("command-remove-indicator", name = indicator.name),
+        t!("command-remove-indicator-desc"),
+        Command::RemoveIndicator { name: indicator.name.clone() },
+    )));
+    commands
+}
+
+/// The commands of the chart's context menu at `point`: adding an indicator, then, over the price
+/// pane, a buy or sell limit order and an alert at its price, then copying its price and time.
+pub fn chart_point_commands(point: &ChartPoint) -> Vec<CommandDefinition> {
+    let mut commands = vec![
+        CommandDefinition::new(0, "add-sma", Command::AddIndicator { indicator_type: "SMA".to_string() }),
+        CommandDefinition::new(1, "add-ema", Command::AddIndicator { indicator_type: "EMA".to_string() }),
+        CommandDefinition::new(2, "add-rsi", Command::AddIndicator { indicator_type: "RSI".to_string() }),
+    ];
+    if let Some(price) = point.price {
+        let shown = format!("{:.2}", price);
+        let at_price = [
+            ("limit-buy", Command::PlaceLimitOrder { side: OrderSide::Buy, price }),
+            ("limit-sell", Command::PlaceLimitOrder { side: OrderSide::Sell, price }),
+            ("add-alert", Command::AddPriceAlert { price }),
+            ("copy-price", Command::CopyText { text: shown.clone() }),
+        ];
+        commands.extend(at_price.into_iter().map(|(key, action)| CommandDefinition::with_text(
+            0,
+            key,
+            t!(format!("command-{}", key), price = shown),
+            t!(format!("command-{}-desc", key), price = shown),
+            action,
+        )));
+    }
+    if let Some(timestamp) = point.timestamp {
+        let shown = timestamp.format("%Y-%m-%d %H:%M").to_string();
+        commands.push(CommandDefinition::with_text(0, "copy-time", t!("command-copy-time", time = shown), t!("command-copy-time-desc", time = shown), Command::CopyText { text: shown }));
+    }
+    commands.into_iter().enumerate().map(|(id, command)| CommandDefinition { id, ..command }).collect()
+}
+
+/// The available command with the key `key`, e.g. one from the history.
+pub fn find_command(app_state: &AppState, key: &str) -> Option<CommandDefinition> {
+    available_commands(app_state).into_iter().find(|command| command.key == key)
+}
+
+/// Runs the action of a keyboard shortcut: a command of the palette, or a palette or chart action.
+pub fn run_shortcut(action: ShortcutAction, context: &CommandContext) {
+    let command = match action {
+        ShortcutAction::CommandPalette => {
+            let mut app_state_writer = context.app_state.write();
+            app_state_writer.command_palette_visible = !app_state_writer.command_palette_visible;
+            return;
+        }
+        ShortcutAction::ZoomIn => return context.app_state.write().request_chart_zoom(ZoomStep::In),
+        ShortcutAction::ZoomOut => return context.app_state.write().request_chart_zoom(ZoomStep::Out),
+        ShortcutAction::ResetZoom => return context.app_state.write().request_chart_zoom(ZoomStep::Reset),
+        ShortcutAction::Undo => {
+            let mut app_state_writer = context.app_state.write();
+            if !app_state_writer.undo() {
+                app_state_writer.notice = Some(t!("notice-nothing-to-undo"));
+            }
+            return;
+        }
+        ShortcutAction::Redo => {
+            let mut app_state_writer = context.app_state.write();
+            if !app_state_writer.redo() {
+                app_state_writer.notice = Some(t!("notice-nothing-to-redo"));
+            }
+            return;
+        }
+        // The most recent command, if it is still available (e.g. its indicator was not removed)
+        ShortcutAction::RerunLastCommand => {
+            let app_state_reader = context.app_state.read();
+            app_state_reader.command_history.first().and_then(|key| find_command(&app_state_reader, key))
+        }
+        ShortcutAction::LoadCsv => find_action(context, |command| matches!(command, Command::LoadCsv { .. })),
+        ShortcutAction::SaveProject => find_action(context, |command| matches!(command, Command::SaveProject { .. })),
+        ShortcutAction::Exit => find_action(context, |command| *command == Command::Exit),
+    };
+    match command {
+        Some(command) => execute_command(&command, context),
+        None => tracing::info!("No command for the {:?} shortcut.", action),
+    }
+}
+
+fn find_action(context: &CommandContext, matches: impl Fn(&Command) -> bool) -> Option<CommandDefinition> {
+    available_commands(&context.app_state.read()).into_iter().find(|command| matches(&command.action))
+}
+
+/// What commands act on; cloned into the palette and the shortcut handler.
+#[derive(Clone)]
+pub struct CommandContext {
+    pub app_state: UseSharedState<AppState>,
+    pub app_config: UseSharedState<AppConfig>,
+    pub engine_client: UseSharedState<Option<EngineClient>>,
+    pub window: DesktopContext,
+}
+
+/// Runs `definition`'s command, closing the palette and recording it in the command history.
+pub fn execute_command(definition: &CommandDefinition, context: &CommandContext) {
+    let CommandContext { app_state, app_config, engine_client, window } = context;
+    let mut app_state_writer = app_state.write();
+    app_state_writer.command_palette_visible = false;
+    app_state_writer.record_command(&definition.key);
+    let maybe_client = engine_client.read().as_ref().cloned();
+    let command = definition.action.clone();
+
+    match command {
+        Command::LoadCsv { path } => {
+            let file_to_load = path.unwrap_or_else(|| "tests/data/sample.csv".to_string());
+            if let Some(client) = maybe_client {
+                drop(app_state_writer);
+                load_csv_file(app_state.clone(), client, file_to_load, "WINFUT".to_string(), shared::models::TimeFrame::Minute1);
+            } else {
+                app_state_writer.error_message = Some(t!("error-no-engine"));
+                tracing::warn!("[COMMAND ACTION] Engine client not available for Load CSV");
+            }
+        }
+        Command::AddIndicator { indicator_type } => {
+            let current_symbol = app_state_writer.current_symbol_display.clone();
+            if let Some(mut client) = maybe_client {
+                if let Some(symbol) = current_symbol {
+                    app_state_writer.start_loading(t!("loading-calculating", indicator = indicator_type));
+                    app_state_writer.error_message = None;
+
+                    let app_config_reader = app_config.read();
+                    let params_json = match indicator_type.as_str() {
+                        "SMA" => json!({"period": app_config_reader.indicators.sma.periods.get(0).unwrap_or(&20)}),
+                        "EMA" => json!({"period": app_config_reader.indicators.ema.periods.get(0).unwrap_or(&9)}),
+                        "RSI" => json!({"period": app_config_reader.indicators.rsi.period}),
+                        _ => json!({}),
+                    };
+                    drop(app_config_reader);
+                    drop(app_state_writer); // Release lock
+
+                    let app_state_async = app_state.clone();
+                    spawn(async move { // Use dioxus::prelude::spawn
+                        let mut app_state_writer_async = app_state_async.write();
+                        match client.calculate_indicator(symbol.clone(), indicator_type.clone(), params_json.to_string()).await {
+                            Ok(Some(indicator_data)) => {
+                                app_state_writer_async.record_edit(&symbol);
+                                app_state_writer_async.add_indicator_to_symbol(&symbol, indicator_data);
+                                app_state_writer_async.error_message = None;
+                                tracing::info!("[COMMAND ACTION] Added indicator {} for {}", indicator_type, symbol);
+                            }
+                            Ok(None) => {
+                                let info_msg = t!("error-indicator-no-data", indicator = indicator_type, symbol = symbol);
+                                tracing::info!("{}", info_msg);
+                                app_state_writer_async.error_message = Some(info_msg);
+                            }
+                            Err(e) => {
+                                let err_msg = t!("error-calculate-indicator", indicator = indicator_type, symbol = symbol, error = e);
+                                tracing::error!("{}", err_msg);
+                                app_state_writer_async.error_message = Some(err_msg);
+                            }
+                        }
+                        app_state_writer_async.finish_loading();
+                    });
+                } else {
+                    app_state_writer.error_message = Some(t!("error-no-symbol-add-indicator"));
+                    tracing::warn!("[COMMAND ACTION] No active symbol for Add Indicator");
+                }
+            } else {
+                app_state_writer.error_message = Some(t!("error-no-engine"));
+                tracing::warn!("[COMMAND ACTION] Engine client not available for Add Indicator");
+            }
+        }
+        Command::RemoveIndicator { name } => {
+            if let Some(symbol) = app_state_writer.current_symbol_display.clone() {
+                app_state_writer.record_edit(&symbol);
+                app_state_writer.remove_indicator(&symbol, &name);
+                tracing::info!("[COMMAND ACTION] Removed indicator {} from {}", name, symbol);
+            } else {
+                app_state_writer.error_message = Some(t!("error-no-symbol-remove-indicator"));
+            }
+        }
+        Command::ToggleTheme => {
+            let theme = app_state_writer.toggle_theme();
+            tracing::info!("[COMMAND ACTION] Switched to the {:?} theme", theme);
+            save_theme(&app_config.read().app.preferences_file, theme);
+        }
+        Command::SetLanguage { language } => {
+            app_state_writer.set_language(language);
+            tracing::info!("[COMMAND ACTION] Switched to {}", language.code());
+            save_language(&app_config.read().app.preferences_file, language);
+        }
+        Command::SetChartType { chart_type } => {
+            app_state_writer.chart_type = chart_type;
+            tracing::info!("[COMMAND ACTION] Drawing the chart as {}", chart_type.name());
+        }
+        Command::TogglePanel { panel } => {
+            app_state_writer.toggle_panel(panel);
+        }
+        Command::ToggleChartLink => {
+            app_state_writer.charts_linked = !app_state_writer.charts_linked;
+            app_state_writer.linked_view = None;
+            let linked = app_state_writer.charts_linked;
+            app_state_writer.notice = Some(if linked { t!("notice-charts-linked") } else { t!("notice-charts-unlinked") });
+            tracing::info!("[COMMAND ACTION] Linked charts: {}", linked);
+        }
+        Command::BacktestTrades { strategy } => {
+            match (maybe_client, app_state_writer.current_symbol_display.clone()) {
+                (Some(client), Some(symbol)) => {
+                    drop(app_state_writer);
+                    show_backtest_trades(app_state.clone(), client, symbol, strategy);
+                }
+                (None, _) => app_state_writer.error_message = Some(t!("error-no-engine")),
+                (_, None) => app_state_writer.error_message = Some(t!("error-no-symbol-backtest")),
+            }
+        }
+        Command::ShowBacktestResults => {
+            match app_state_writer.backtest_results.is_some() {
+                true => app_state_writer.backtest_results_visible = true,
+                false => app_state_writer.error_message = Some(t!("error-no-backtest-results")),
+            }
+        }
+        Command::SaveProject { path } => {
+            let path = path.unwrap_or_else(|| app_config.read().app.project_file.clone());
+            match Project::from_state(&app_state_writer).save(&path) {
+                Ok(()) => {
+                    tracing::info!("[COMMAND ACTION] Saved the project to {}", path);
+                    app_state_writer.notice = Some(t!("notice-project-saved", path = path));
+                }
+                Err(e) => app_state_writer.error_message = Some(t!("error-save-project", path = path, error = e)),
+            }
+        }
+        Command::LoadProject { path } => {
+            let path = path.unwrap_or_else(|| app_config.read().app.project_file.clone());
+            match (Project::load(&path), maybe_client) {
+                (Ok(project), Some(client)) => {
+                    tracing::info!("[COMMAND ACTION] Opening the project {}", path);
+                    app_state_writer.add_recent_file(&path, RecentFileKind::Project);
+                    drop(app_state_writer);
+                    save_theme(&app_config.read().app.preferences_file, project.theme.clone());
+                    open_project(app_state.clone(), client, project);
+                }
+                (Ok(_), None) => app_state_writer.error_message = Some(t!("error-no-engine")),
+                (Err(e), _) => app_state_writer.error_message = Some(t!("error-open-project", path = path, error = e)),
+            }
+        }
+        Command::ExportChart { format } => {
+            drop(app_state_writer);
+            export_chart(app_state.clone(), format);
+        }
+        Command::ExportData { format } => match maybe_client {
+            Some(client) => {
+                drop(app_state_writer);
+                export_visible_data(app_state.clone(), client, format);
+            }
+            None => app_state_writer.error_message = Some(t!("error-no-engine")),
+        },
+        Command::PlaceLimitOrder { side, price } => {
+            match (maybe_client, app_state_writer.current_symbol_display.clone()) {
+                (Some(mut client), Some(symbol)) => {
+                    drop(app_state_writer);
+                    let app_state_async = app_state.clone();
+                    spawn(async move {
+                        let result = client.place_limit_order(symbol.clone(), side, CHART_ORDER_QUANTITY, price).await;
+                        let mut app_state_writer_async = app_state_async.write();
+                        match result {
+                            Ok(response) if response.success => {
+                                tracing::info!("[COMMAND ACTION] {}", response.message);
+                                app_state_writer_async.notice = Some(t!("notice-order-placed", message = response.message));
+                                drop(app_state_writer_async);
+                                // A limit order at a price the market already reached fills at once
+                                show_simulated_trades(app_state_async.clone(), client, symbol);
+                            }
+                            Ok(response) => {
+                                let err_msg = t!("error-order-rejected", message = response.message);
+                                tracing::warn!("{}", err_msg);
+                                app_state_writer_async.error_message = Some(err_msg);
+                            }
+                            Err(e) => {
+                                let err_msg = t!("error-place-order", symbol = symbol, error = e);
+                                tracing::error!("{}", err_msg);
+                                app_state_writer_async.error_message = Some(err_msg);
+                            }
+                        }
+                    });
+                }
+                (None, _) => app_state_writer.error_message = Some(t!("error-no-engine")),
+                (_, None) => app_state_writer.error_message = Some(t!("error-no-chart-open")),
+            }
+        }
+        Command::AddPriceAlert { price } => {
+            if let Some(symbol) = app_state_writer.current_symbol_display.clone() {
+                app_state_writer.record_edit(&symbol);
+                let id = app_state_writer.add_price_level(&symbol, price);
+                app_state_writer.update_price_level(&symbol, PriceLevel { id, price, alert: true });
+                tracing::info!("[COMMAND ACTION] Added an alert at {:.2} on {}", price, symbol);
+            } else {
+                app_state_writer.error_message = Some(t!("error-no-chart-open"));
+            }
+        }
+        Command::CopyText { text } => {
+            // The webview has the clipboard; the text is passed as a JSON string literal
+            let script = format!("navigator.clipboard.writeText({})", serde_json::Value::String(text.clone()));
+            eval(&script);
+            app_state_writer.notice = Some(t!("notice-copied", text = text));
+        }
+        Command::Configure => {
+            app_state_writer.shortcut_editor_visible = true;
+        }
+        Command::OpenSettings => {
+            app_state_writer.settings_visible = true;
+        }
+        Command::EditConnection => {
+            app_state_writer.connection_dialog_visible = true;
+        }
+        Command::Exit => {
+            tracing::info!("[COMMAND ACTION] Exit Application");
+            window.close();
+        }
+    }
+}
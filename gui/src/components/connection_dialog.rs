@@ -0,0 +1,131 @@
+// Engine connection dialog
+// The engine fields of the settings, prefilled from the current `EngineConnSettings`. "Test connection"
+// health-checks the engine at the edited address without leaving the current one; "Connect" saves the
+// settings to the user config file and reconnects right away.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+use std::collections::HashMap;
+
+use crate::components::settings_dialog::SettingsFieldRow;
+use crate::config::editor::{apply_edits, settings_fields};
+use crate::config::AppConfig;
+use crate::services::connection::{reconnect_now, test_connection};
+use crate::state::app_state::AppState;
+
+const ENGINE_SECTION: &str = "settings-section-engine";
+
+/// Outcome of the last connection test.
+#[derive(Debug, Clone, PartialEq)]
+enum TestStatus {
+    Testing,
+    Passed,
+    Failed(String),
+}
+
+#[component]
+pub fn ConnectionDialog() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let app_config = use_shared_state::<AppConfig>().unwrap();
+    // Field id -> edited text, as in the settings dialog
+    let edits = use_ref(HashMap::<&'static str, String>::new);
+    let problems = use_state(Vec::<String>::new);
+    let test_status = use_state(|| None::<TestStatus>);
+
+    if !app_state.read().connection_dialog_visible {
+        return None;
+    }
+    let palette = app_state.read().palette();
+    let config = app_config.read().clone();
+    let fields: Vec<_> = settings_fields().into_iter().filter(|field| field.section == ENGINE_SECTION).collect();
+
+    // The current config with the edits, or the problems found in them (shown in the dialog)
+    let edited_config = move || {
+        let current = app_config.read().clone();
+        let edited = edits.read();
+        let result = apply_edits(&current, edited.iter().map(|(id, value)| (*id, value.as_str())));
+        if let Err(found) = &result {
+            problems.set(found.clone());
+        }
+        result.ok()
+    };
+    let close = move || {
+        edits.write().clear();
+        problems.set(Vec::new());
+        test_status.set(None);
+        app_state.write().connection_dialog_visible = false;
+    };
+    let test = move |_| {
+        let Some(new_config) = edited_config() else { return };
+        problems.set(Vec::new());
+        test_status.set(Some(TestStatus::Testing));
+        spawn(async move {
+            let status = match test_connection(&new_config.engine).await {
+                Ok(()) => TestStatus::Passed,
+                Err(e) => TestStatus::Failed(format!("{:#}", e)),
+            };
+            test_status.set(Some(status));
+        });
+    };
+    let connect = move |_| {
+        let Some(new_config) = edited_config() else { return };
+        match new_config.save_user() {
+            Ok(path) => tracing::info!("Saved the engine connection to {}", path.display()),
+            Err(e) => return problems.set(vec![t!("settings-save-failed", error = e)]),
+        }
+        *app_config.write() = new_config;
+        reconnect_now();
+        close();
+    };
+    let testing = *test_status.get() == Some(TestStatus::Testing);
+    let test_result = match test_status.get() {
+        Some(TestStatus::Passed) => Some((palette.chart_bullish.clone(), t!("connection-test-ok"))),
+        Some(TestStatus::Failed(error)) => Some((palette.chart_bearish.clone(), t!("connection-test-failed", error = error))),
+        Some(TestStatus::Testing) | None => None,
+    };
+
+    rsx! {
+        div {
+            class: "connection-dialog",
+            style: "position: fixed; top: 15%; left: 50%; transform: translateX(-50%); width: 480px; padding: 15px; z-index: 1000; background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border}; border-radius: 8px; box-shadow: 0 5px 15px rgba(0,0,0,0.5); font-size: 1.3rem;",
+            h3 { style: "margin: 0 0 10px 0;", {t!("connection-title")} }
+            {problems.get().iter().map(|problem| rsx! { p { style: "margin: 2px 0; color: {palette.chart_bearish}; font-size: 1.2rem;", "{problem}" } })}
+            {fields.iter().map(|field| {
+                let id = field.id;
+                let value = edits.read().get(id).cloned().unwrap_or_else(|| (field.get)(&config));
+                rsx! {
+                    SettingsFieldRow {
+                        key: "{id}",
+                        id: id,
+                        kind: field.kind,
+                        value: value,
+                        on_change: move |value: String| {
+                            edits.write().insert(id, value);
+                            test_status.set(None);
+                        },
+                        palette: palette.clone()
+                    }
+                }
+            })}
+            {test_result.map(|(color, message)| rsx! { p { style: "margin: 8px 0 0 0; color: {color};", "{message}" } })}
+            div {
+                style: "display: flex; justify-content: flex-end; gap: 8px; margin-top: 10px;",
+                button {
+                    style: "margin-right: auto; background: none; color: {palette.foreground}; border: 1px solid {palette.border}; border-radius: 3px; padding: 4px 10px; cursor: pointer;",
+                    disabled: testing,
+                    onclick: test,
+                    if testing { {t!("connection-testing")} } else { {t!("connection-test")} }
+                }
+                button {
+                    style: "background: none; color: {palette.muted}; border: 1px solid {palette.border}; border-radius: 3px; padding: 4px 10px; cursor: pointer;",
+                    onclick: move |_| close(),
+                    {t!("dialog-cancel")}
+                }
+                button {
+                    style: "background-color: {palette.primary}; color: white; border: none; border-radius: 3px; padding: 4px 10px; cursor: pointer;",
+                    onclick: connect,
+                    {t!("connection-connect")}
+                }
+            }
+        }
+    }
+}
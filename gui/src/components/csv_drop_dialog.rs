@@ -0,0 +1,94 @@
+// CSV drop dialog
+// Asks how to load a CSV file dropped on the window: its symbol and timeframe, prefilled with the
+// guesses of `state::csv_drop`. Files dropped together are asked about one after the other.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+use shared::models::TimeFrame;
+
+use crate::services::engine_client::EngineClient;
+use crate::services::symbols::load_csv_file;
+use crate::state::app_state::AppState;
+
+#[component]
+pub fn CsvDropDialog() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let engine_client = use_shared_state::<Option<EngineClient>>().unwrap();
+
+    let app_state_reader = app_state.read();
+    let Some(dropped) = app_state_reader.dropped_csvs.first().cloned() else {
+        return None;
+    };
+    let remaining = app_state_reader.dropped_csvs.len() - 1;
+    let palette = app_state_reader.palette();
+    drop(app_state_reader);
+
+    let file_name = std::path::Path::new(&dropped.path).file_name().map_or_else(|| dropped.path.clone(), |name| name.to_string_lossy().to_string());
+    let skip = move |_| {
+        app_state.write().dropped_csvs.remove(0);
+    };
+    let load = move |_| {
+        let mut app_state_writer = app_state.write();
+        let symbol = app_state_writer.dropped_csvs[0].symbol.trim().to_uppercase();
+        if symbol.is_empty() {
+            app_state_writer.error_message = Some(t!("csv-drop-no-symbol"));
+            return;
+        }
+        let Some(client) = engine_client.read().as_ref().cloned() else {
+            app_state_writer.error_message = Some(t!("error-no-engine"));
+            return;
+        };
+        let dropped = app_state_writer.dropped_csvs.remove(0);
+        drop(app_state_writer);
+        tracing::info!(path = %dropped.path, %symbol, timeframe = %dropped.timeframe, "Loading a dropped CSV file");
+        load_csv_file(app_state.clone(), client, dropped.path, symbol, dropped.timeframe);
+    };
+    let label_style = format!("display: block; margin: 8px 0 2px 0; color: {};", palette.muted);
+    let input_style = format!(
+        "width: 100%; box-sizing: border-box; padding: 4px; background-color: {}; color: {}; border: 1px solid {}; border-radius: 3px;",
+        palette.background, palette.foreground, palette.border
+    );
+
+    rsx! {
+        div {
+            class: "csv-drop-dialog",
+            style: "position: fixed; top: 20%; left: 50%; transform: translateX(-50%); width: 420px; padding: 15px; z-index: 1000; background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border}; border-radius: 8px; box-shadow: 0 5px 15px rgba(0,0,0,0.5); font-size: 1.3rem;",
+            h3 { style: "margin: 0 0 4px 0;", {t!("csv-drop-title", file = file_name)} }
+            p { style: "margin: 0; color: {palette.muted}; font-size: 1.1rem; word-break: break-all;", "{dropped.path}" }
+            label { style: "{label_style}", {t!("csv-drop-symbol")} }
+            input {
+                style: "{input_style}",
+                value: "{dropped.symbol}",
+                oninput: move |evt: FormEvent| app_state.write().dropped_csvs[0].symbol = evt.value.clone(),
+            }
+            label { style: "{label_style}", {t!("csv-drop-timeframe")} }
+            select {
+                style: "{input_style}",
+                value: "{dropped.timeframe}",
+                onchange: move |evt: FormEvent| {
+                    if let Ok(timeframe) = evt.value.parse::<TimeFrame>() {
+                        app_state.write().dropped_csvs[0].timeframe = timeframe;
+                    }
+                },
+                {TimeFrame::ALL.into_iter().map(|timeframe| rsx! {
+                    option { key: "{timeframe}", value: "{timeframe}", selected: timeframe == dropped.timeframe, "{timeframe}" }
+                })}
+            }
+            if remaining > 0 {
+                p { style: "margin: 8px 0 0 0; color: {palette.muted};", {t!("csv-drop-remaining", count = remaining)} }
+            }
+            div {
+                style: "display: flex; justify-content: flex-end; gap: 8px; margin-top: 12px;",
+                button {
+                    style: "background: none; color: {palette.muted}; border: 1px solid {palette.border}; border-radius: 3px; padding: 4px 10px; cursor: pointer;",
+                    onclick: skip,
+                    {t!("csv-drop-skip")}
+                }
+                button {
+                    style: "background-color: {palette.primary}; color: white; border: none; border-radius: 3px; padding: 4px 10px; cursor: pointer;",
+                    onclick: load,
+                    {t!("csv-drop-load")}
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+// Engine status panel
+// Shows what the connected engine reported through GetEngineInfo: version, build,
+// uptime at connection time, loaded series and enabled features.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+
+use crate::state::app_state::AppState;
+
+fn format_uptime(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86_400, seconds % 86_400 / 3_600, seconds % 3_600 / 60);
+    if days > 0 { format!("{}d {}h", days, hours) } else { format!("{}h {}m", hours, minutes) }
+}
+
+#[component]
+pub fn EngineStatus() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let palette = app_state.read().palette();
+    let Some(info) = app_state.read().engine_info.clone() else {
+        return rsx! { p { style: "color: {palette.muted}; font-size: 1.2rem;", {t!("engine-not-connected")} } };
+    };
+    let uptime = format_uptime(info.uptime_seconds);
+    let series = info.series.len();
+    let candles: u64 = info.series.iter().map(|s| s.candles).sum();
+    let features = if info.features.is_empty() { t!("engine-features-none") } else { info.features.join(", ") };
+    let summary = t!("engine-info", version = info.version, api_version = info.api_version, profile = info.build_profile, target = info.build_target, uptime = uptime);
+
+    rsx! {
+        div {
+            style: "font-size: 1.2rem; color: {palette.muted}; border: 1px solid {palette.border}; border-radius: 4px; padding: 6px 10px; margin-bottom: 10px;",
+            div { "{summary}" }
+            div { {t!("engine-loaded", series = series, candles = candles)} }
+            div { {t!("engine-features", features = features)} }
+        }
+    }
+}
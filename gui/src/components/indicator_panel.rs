@@ -0,0 +1,217 @@
+// Indicator management panel
+// Lists the current symbol's indicators with controls to show/hide them, pick their color, line
+// width and dash, edit their parameters (recalculated by the engine) and remove them. Choices live
+// in AppState, and are saved with the project.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+use shared::models::Indicator;
+
+use crate::components::chart::indicators::{default_line_style, indicator_type, LineDash, LineStyle};
+use crate::config::theme::ThemePalette;
+use crate::config::AppConfig;
+use crate::services::engine_client::EngineClient;
+use crate::state::app_state::{AppState, IndicatorStyle};
+
+#[component]
+pub fn IndicatorPanel() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let app_config = use_shared_state::<AppConfig>().unwrap();
+    let app_state_reader = app_state.read();
+    let Some(symbol) = app_state_reader.current_symbol_display.clone() else {
+        return None;
+    };
+    let defaults = app_config.read().indicators.clone();
+    let rows: Vec<(Indicator, IndicatorStyle, LineStyle)> = app_state_reader.all_indicators.get(&symbol).into_iter().flatten()
+        .map(|indicator| {
+            let style = app_state_reader.indicator_style(&symbol, &indicator.name);
+            let line = style.line_style(default_line_style(&indicator.name, &defaults));
+            (indicator.clone(), style, line)
+        })
+        .collect();
+    let palette = app_state_reader.palette();
+    drop(app_state_reader);
+
+    rsx! {
+        div {
+            class: "indicator-panel",
+            style: "width: 240px; border: 1px solid {palette.border}; border-radius: 4px; padding: 8px; font-size: 1.2rem; color: {palette.foreground}; align-self: flex-start;",
+            h4 { style: "margin: 0 0 8px 0;", {t!("indicators-title", symbol = symbol)} }
+            if rows.is_empty() {
+                p { style: "color: {palette.muted};", {t!("indicators-empty")} }
+            }
+            {rows.into_iter().map(|(indicator, style, line)| rsx! {
+                IndicatorRow {
+                    key: "{indicator.name}",
+                    symbol: symbol.clone(),
+                    indicator: indicator,
+                    style: style,
+                    line: line,
+                    palette: palette.clone()
+                }
+            })}
+        }
+    }
+}
+
+#[component]
+fn IndicatorRow(
+    symbol: String,
+    indicator: Indicator,
+    style: IndicatorStyle,
+    // The line drawn: `style` over the defaults of the indicator's type
+    line: LineStyle,
+    palette: ThemePalette,
+) -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let engine_client = use_shared_state::<Option<EngineClient>>().unwrap();
+    // Parameters being edited, applied (recalculated) on demand
+    let draft_parameters = use_ref(|| indicator.parameters.as_object().cloned().unwrap_or_default());
+
+    let name = indicator.name.clone();
+    let color = line.color.to_lowercase();
+
+    let toggle_visible = {
+        let (symbol, name, style) = (symbol.clone(), name.clone(), style.clone());
+        move |_| app_state.write().set_indicator_style(&symbol, &name, IndicatorStyle { visible: !style.visible, ..style.clone() })
+    };
+    let pick_color = {
+        let (symbol, name, style) = (symbol.clone(), name.clone(), style.clone());
+        move |evt: FormEvent| app_state.write().set_indicator_style(&symbol, &name, IndicatorStyle { color: Some(evt.value.clone()), ..style.clone() })
+    };
+    let pick_width = {
+        let (symbol, name, style) = (symbol.clone(), name.clone(), style.clone());
+        move |evt: FormEvent| {
+            let Ok(width) = evt.value.parse::<f64>() else { return };
+            if width > 0.0 {
+                app_state.write().set_indicator_style(&symbol, &name, IndicatorStyle { line_width: Some(width), ..style.clone() });
+            }
+        }
+    };
+    let pick_dash = {
+        let (symbol, name, style) = (symbol.clone(), name.clone(), style.clone());
+        move |evt: FormEvent| app_state.write().set_indicator_style(&symbol, &name, IndicatorStyle { dash: LineDash::from_name(&evt.value), ..style.clone() })
+    };
+    let remove = {
+        let (symbol, name) = (symbol.clone(), name.clone());
+        move |_| {
+            let mut app_state_writer = app_state.write();
+            app_state_writer.record_edit(&symbol);
+            app_state_writer.remove_indicator(&symbol, &name);
+        }
+    };
+    let apply_parameters = {
+        let (symbol, name) = (symbol.clone(), name.clone());
+        move |_| {
+            let Some(mut client) = engine_client.read().as_ref().cloned() else {
+                app_state.write().error_message = Some(t!("error-no-engine"));
+                return;
+            };
+            let parameters = serde_json::Value::Object(draft_parameters.read().clone()).to_string();
+            let (symbol, old_name) = (symbol.clone(), name.clone());
+            app_state.write().start_loading(t!("loading-recalculating", indicator = name));
+            spawn(async move {
+                let result = client.calculate_indicator(symbol.clone(), indicator_type(&old_name), parameters).await;
+                let mut app_state_writer = app_state.write();
+                match result {
+                    Ok(Some(indicator)) => app_state_writer.replace_indicator(&symbol, &old_name, indicator),
+                    Ok(None) => app_state_writer.error_message = Some(t!("indicators-no-data", indicator = old_name)),
+                    Err(e) => {
+                        let err_msg = t!("indicators-recalculate-failed", indicator = old_name, symbol = symbol, error = e);
+                        tracing::error!("{}", err_msg);
+                        app_state_writer.error_message = Some(err_msg);
+                    }
+                }
+                app_state_writer.finish_loading();
+            });
+        }
+    };
+
+    // One number input per numeric parameter, e.g. "period"
+    let parameter_keys: Vec<String> = draft_parameters.read().iter()
+        .filter(|(_, value)| value.is_number())
+        .map(|(key, _)| key.clone())
+        .collect();
+    let has_parameters = !parameter_keys.is_empty();
+    let opacity = if style.visible { "1" } else { "0.5" };
+
+    rsx! {
+        div {
+            style: "border-top: 1px solid {palette.border}; padding: 6px 0; opacity: {opacity};",
+            div {
+                style: "display: flex; align-items: center; gap: 6px;",
+                input {
+                    r#type: "checkbox",
+                    title: t!("indicators-visible-hint"),
+                    checked: style.visible,
+                    onclick: toggle_visible,
+                }
+                input {
+                    r#type: "color",
+                    title: t!("indicators-color-hint"),
+                    value: "{color}",
+                    style: "width: 24px; height: 18px; padding: 0; border: none; background: none;",
+                    oninput: pick_color,
+                }
+                span { style: "flex: 1;", "{name}" }
+                input {
+                    r#type: "number",
+                    title: t!("indicators-width-hint"),
+                    value: "{line.width}",
+                    min: "0.5",
+                    max: "6",
+                    step: "0.5",
+                    style: "width: 40px; background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border};",
+                    oninput: pick_width,
+                }
+                select {
+                    title: t!("indicators-dash-hint"),
+                    value: "{line.dash.name()}",
+                    style: "background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border};",
+                    onchange: pick_dash,
+                    {LineDash::ALL.iter().map(|dash| rsx! {
+                        option { key: "{dash.name()}", value: "{dash.name()}", selected: *dash == line.dash, {t!(format!("settings-option-{}", dash.name()))} }
+                    })}
+                }
+                button {
+                    title: t!("indicators-remove"),
+                    style: "background: none; border: none; color: {palette.chart_bearish}; cursor: pointer;",
+                    onclick: remove,
+                    "✕"
+                }
+            }
+            {parameter_keys.into_iter().map(|key| {
+                let value = draft_parameters.read().get(&key).cloned().unwrap_or_default();
+                let key_for_input = key.clone();
+                rsx! {
+                    label {
+                        key: "{key}",
+                        style: "display: flex; justify-content: space-between; margin: 4px 0 0 24px;",
+                        "{key}"
+                        input {
+                            r#type: "number",
+                            value: "{value}",
+                            style: "width: 70px; background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border};",
+                            oninput: move |evt: FormEvent| {
+                                // Whole numbers stay integers, so e.g. "period" is sent as 20, not 20.0
+                                let number = match evt.value.parse::<i64>() {
+                                    Ok(int_value) => Some(serde_json::Value::from(int_value)),
+                                    Err(_) => evt.value.parse::<f64>().ok().map(serde_json::Value::from),
+                                };
+                                if let Some(number) = number {
+                                    draft_parameters.write().insert(key_for_input.clone(), number);
+                                }
+                            },
+                        }
+                    }
+                }
+            })}
+            if has_parameters {
+                button {
+                    style: "margin: 4px 0 0 24px; padding: 2px 8px; background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border}; border-radius: 3px; cursor: pointer;",
+                    onclick: apply_parameters,
+                    {t!("indicators-apply")}
+                }
+            }
+        }
+    }
+}
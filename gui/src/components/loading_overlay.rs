@@ -0,0 +1,46 @@
+// Loading overlay
+// Covers the window during long operations (CSV imports, backtests...) with a progress bar fed by
+// the engine job's progress updates; the bar is indeterminate until the first update arrives.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+
+use crate::state::app_state::AppState;
+
+#[component]
+pub fn LoadingOverlay() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let app_state_reader = app_state.read();
+    let Some(loading) = app_state_reader.loading.clone() else {
+        return None;
+    };
+    let palette = app_state_reader.palette();
+    drop(app_state_reader);
+
+    // An indeterminate bar is shown full width, dimmed
+    let (bar_width, bar_opacity, percent) = match loading.progress {
+        Some(progress) => (progress * 100.0, 1.0, format!("{:.0}%", progress * 100.0)),
+        None => (100.0, 0.35, String::new()),
+    };
+
+    rsx! {
+        div {
+            class: "loading-overlay",
+            style: "position: fixed; inset: 0; display: flex; align-items: center; justify-content: center; background-color: rgba(0, 0, 0, 0.45); z-index: 900;",
+            div {
+                style: "width: 360px; padding: 16px; background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border}; border-radius: 6px;",
+                div {
+                    style: "display: flex; justify-content: space-between; margin-bottom: 8px;",
+                    span { "{loading.label}" }
+                    span { style: "color: {palette.muted};", "{percent}" }
+                }
+                div {
+                    style: "height: 8px; background-color: {palette.background}; border-radius: 4px; overflow: hidden;",
+                    div { style: "width: {bar_width}%; height: 100%; background-color: {palette.primary}; opacity: {bar_opacity};" }
+                }
+                if !loading.message.is_empty() {
+                    div { style: "margin-top: 6px; font-size: 1.2rem; color: {palette.muted};", "{loading.message}" }
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,22 @@
 // GUI components module
+pub mod alert_panel;
+pub mod backtest_results;
 pub mod chart;
 pub mod command_palette;
+pub mod commands; // What the command palette's commands do
+pub mod connection_dialog;
+pub mod csv_drop_dialog; // Loading CSV files dropped on the window
+pub mod engine_status;
+pub mod indicator_panel;
+pub mod loading_overlay;
+pub mod price_levels;
+pub mod replay_controls;
+pub mod settings_dialog;
+pub mod shortcut_editor;
+pub mod status_bar;
+pub mod symbol_tabs;
 pub mod toolbar;
+pub mod watchlist;
 
 // Re-export components for easier access from app.rs or other modules
 // pub use chart::ChartComponent;
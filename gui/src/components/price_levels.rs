@@ -0,0 +1,82 @@
+// Price level panel
+// Lists the current symbol's horizontal price levels (placed by clicking the chart's price axis)
+// with controls to edit their price, turn them into price alerts and delete them.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+
+use crate::config::AppConfig;
+use crate::state::app_state::{AppState, PriceLevel};
+
+#[component]
+pub fn PriceLevelPanel() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let app_config = use_shared_state::<AppConfig>().unwrap();
+    let app_state_reader = app_state.read();
+    let Some(symbol) = app_state_reader.current_symbol_display.clone() else {
+        return None;
+    };
+    let levels = app_state_reader.current_price_levels();
+    let palette = app_state_reader.palette();
+    drop(app_state_reader);
+    let decimals = app_config.read().price_decimals(&symbol);
+
+    rsx! {
+        div {
+            class: "price-level-panel",
+            style: "width: 240px; margin-top: 12px; border: 1px solid {palette.border}; border-radius: 4px; padding: 8px; font-size: 1.2rem; color: {palette.foreground};",
+            h4 { style: "margin: 0 0 8px 0;", {t!("price-levels-title", symbol = symbol)} }
+            if levels.is_empty() {
+                p { style: "color: {palette.muted};", {t!("price-levels-empty")} }
+            }
+            {levels.into_iter().map(|level| {
+                let (symbol_for_price, symbol_for_alert, symbol_for_remove) = (symbol.clone(), symbol.clone(), symbol.clone());
+                let (level_for_price, level_for_alert) = (level.clone(), level.clone());
+                let price = format!("{:.*}", decimals, level.price);
+                rsx! {
+                    div {
+                        key: "{level.id}",
+                        style: "display: flex; align-items: center; gap: 6px; border-top: 1px solid {palette.border}; padding: 4px 0;",
+                        input {
+                            r#type: "number",
+                            step: "any",
+                            value: "{price}",
+                            style: "width: 90px; background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border};",
+                            onchange: move |evt: FormEvent| {
+                                if let Ok(price) = evt.value.parse::<f64>() {
+                                    let mut app_state_writer = app_state.write();
+                                    app_state_writer.record_edit(&symbol_for_price);
+                                    app_state_writer.update_price_level(&symbol_for_price, PriceLevel { price, ..level_for_price.clone() });
+                                }
+                            },
+                        }
+                        label {
+                            style: "flex: 1;",
+                            title: t!("price-levels-alert-hint"),
+                            input {
+                                r#type: "checkbox",
+                                checked: level.alert,
+                                onclick: move |_| {
+                                    let alert = !level_for_alert.alert;
+                                    let mut app_state_writer = app_state.write();
+                                    app_state_writer.record_edit(&symbol_for_alert);
+                                    app_state_writer.update_price_level(&symbol_for_alert, PriceLevel { alert, ..level_for_alert.clone() });
+                                },
+                            }
+                            {t!("price-levels-alert")}
+                        }
+                        button {
+                            title: t!("price-levels-delete"),
+                            style: "background: none; border: none; color: {palette.chart_bearish}; cursor: pointer;",
+                            onclick: move |_| {
+                                let mut app_state_writer = app_state.write();
+                                app_state_writer.record_edit(&symbol_for_remove);
+                                app_state_writer.remove_price_level(&symbol_for_remove, level.id);
+                            },
+                            "✕"
+                        }
+                    }
+                }
+            })}
+        }
+    }
+}
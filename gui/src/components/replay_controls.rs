@@ -0,0 +1,151 @@
+// Replay controls
+// Play/pause/step/speed/stop buttons driving the engine's replay RPCs for the current symbol.
+// While a replay runs the chart shows only the candles revealed so far, one more per step.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+use shared::models::TimeFrame;
+
+use crate::services::engine_client::{to_shared_candle, EngineClient};
+use crate::state::app_state::AppState;
+
+/// Replay speeds offered, in bars per second.
+const REPLAY_SPEEDS: [f64; 5] = [0.5, 1.0, 2.0, 5.0, 10.0];
+
+#[component]
+pub fn ReplayControls() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let engine_client = use_shared_state::<Option<EngineClient>>().unwrap();
+    let speed = use_state(|| 1.0f64);
+
+    let app_state_reader = app_state.read();
+    let Some(symbol) = app_state_reader.current_symbol_display.clone() else {
+        return None;
+    };
+    let status = app_state_reader.replay_status.clone().filter(|status| status.symbol == symbol);
+    let palette = app_state_reader.palette();
+    drop(app_state_reader);
+
+    let state = status.as_ref().map(|status| status.state.clone()).unwrap_or_default();
+    let (running, paused) = (status.is_some() && state != "FINISHED", state == "PAUSED");
+
+    // Starts the replay, then follows it: the chart is emptied and every revealed candle appended.
+    let start = {
+        let symbol = symbol.clone();
+        move |_| {
+            let Some(mut client) = engine_client.read().as_ref().cloned() else {
+                app_state.write().error_message = Some(t!("error-no-engine"));
+                return;
+            };
+            let (symbol, speed) = (symbol.clone(), *speed.get());
+            spawn(async move {
+                let started = client.start_replay(symbol.clone(), speed, false).await;
+                let stream = match started {
+                    Ok(status) => {
+                        let mut app_state_writer = app_state.write();
+                        app_state_writer.replay_status = Some(status);
+                        app_state_writer.set_candles(&symbol, Vec::new());
+                        drop(app_state_writer);
+                        client.follow_replay(symbol.clone()).await
+                    }
+                    Err(e) => Err(e),
+                };
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        app_state.write().error_message = Some(t!("error-replay-start", symbol = symbol, error = e));
+                        return;
+                    }
+                };
+                while let Ok(Some(response)) = stream.message().await {
+                    let mut app_state_writer = app_state.write();
+                    for candle in response.candles {
+                        let timestamp = candle.timestamp;
+                        app_state_writer.upsert_candle(&symbol, to_shared_candle(candle));
+                        let revealed = app_state_writer.all_market_data.get(&symbol).map_or(0, |data| data.candles.len());
+                        if let Some(status) = app_state_writer.replay_status.as_mut().filter(|status| status.symbol == symbol) {
+                            status.position = revealed as u32;
+                            status.current_timestamp = Some(timestamp);
+                        }
+                    }
+                }
+                // The stream ends with the replay: it finished, or was stopped (status already cleared)
+                if let Some(status) = app_state.write().replay_status.as_mut().filter(|status| status.symbol == symbol) {
+                    status.state = "FINISHED".to_string();
+                }
+            });
+        }
+    };
+
+    // Runs a replay control call ("pause", "resume", "step", "speed" with `speed`, or "stop") and shows the status it returns.
+    let control = {
+        let symbol = symbol.clone();
+        std::rc::Rc::new(move |action: &'static str, speed: f64| {
+            let Some(mut client) = engine_client.read().as_ref().cloned() else { return };
+            let symbol = symbol.clone();
+            spawn(async move {
+                let result = match action {
+                    "pause" => client.pause_replay(symbol.clone()).await,
+                    "resume" => client.resume_replay(symbol.clone()).await,
+                    "step" => client.step_replay(symbol.clone(), 1).await,
+                    "speed" => client.set_replay_speed(symbol.clone(), speed).await,
+                    _ => client.stop_replay(symbol.clone()).await,
+                };
+                let mut app_state_writer = app_state.write();
+                match result {
+                    Ok(status) => app_state_writer.replay_status = Some(status),
+                    Err(e) => app_state_writer.error_message = Some(t!("error-replay-control", action = action, symbol = symbol, error = e)),
+                }
+                if action != "stop" {
+                    return;
+                }
+                // Stopped: show every candle of the replayed (daily) series again
+                app_state_writer.replay_status = None;
+                drop(app_state_writer);
+                match client.get_market_data(symbol.clone(), TimeFrame::Day1).await {
+                    Ok(candles) => app_state.write().set_candles(&symbol, candles),
+                    Err(e) => app_state.write().error_message = Some(t!("error-market-data", symbol = symbol, error = e)),
+                }
+            });
+        })
+    };
+    let (pause_or_resume, step, set_speed, stop) = (control.clone(), control.clone(), control.clone(), control);
+
+    let progress = status.as_ref()
+        .map(|status| t!("replay-progress", state = status.state, position = status.position, total = status.total, phase = status.market_phase, speed = status.speed))
+        .unwrap_or_else(|| t!("replay-idle"));
+    let button_style = format!("padding: 4px 10px; background-color: {}; color: {}; border: 1px solid {}; border-radius: 4px; cursor: pointer;", palette.surface, palette.foreground, palette.border);
+
+    rsx! {
+        div {
+            class: "replay-controls",
+            style: "display: flex; gap: 6px; align-items: center; width: 800px; margin-top: 8px; font-size: 1.2rem; color: {palette.muted};",
+            if !running {
+                button { style: "{button_style}", title: t!("replay-start-hint"), onclick: start, {t!("replay-start")} }
+            } else {
+                button {
+                    style: "{button_style}",
+                    onclick: move |_| pause_or_resume(if paused { "resume" } else { "pause" }, 0.0),
+                    if paused { {t!("replay-play")} } else { {t!("replay-pause")} }
+                }
+                button { style: "{button_style}", disabled: !paused, title: t!("replay-step-hint"), onclick: move |_| step("step", 0.0), {t!("replay-step")} }
+                button { style: "{button_style}", onclick: move |_| stop("stop", 0.0), {t!("replay-stop")} }
+            }
+            select {
+                style: "background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border};",
+                title: t!("replay-speed-hint"),
+                onchange: move |evt: FormEvent| {
+                    if let Ok(new_speed) = evt.value.parse::<f64>() {
+                        speed.set(new_speed);
+                        if running {
+                            set_speed("speed", new_speed);
+                        }
+                    }
+                },
+                {REPLAY_SPEEDS.iter().map(|&option| rsx! {
+                    option { value: "{option}", selected: option == *speed.get(), "{option}x" }
+                })}
+            }
+            span { "{progress}" }
+        }
+    }
+}
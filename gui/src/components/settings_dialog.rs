@@ -0,0 +1,156 @@
+// Settings dialog
+// Edits the whole `AppConfig` (besides shortcuts, which have their own editor). Edits are kept as
+// text until "Save", which parses and validates all of them, writes the user config file and applies
+// the new config (see `services::config_reload`).
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+use std::collections::HashMap;
+
+use crate::config::editor::{apply_edits, settings_fields, FieldKind};
+use crate::config::theme::ThemePalette;
+use crate::config::AppConfig;
+use crate::services::config_reload::apply_config;
+use crate::state::app_state::AppState;
+
+#[component]
+pub fn SettingsDialog() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let app_config = use_shared_state::<AppConfig>().unwrap();
+    // Field id -> edited text, for the fields changed since the dialog was opened
+    let edits = use_ref(HashMap::<&'static str, String>::new);
+    let problems = use_state(Vec::<String>::new);
+
+    if !app_state.read().settings_visible {
+        return None;
+    }
+    let palette = app_state.read().palette();
+    let config = app_config.read().clone();
+    let fields = settings_fields();
+    let mut sections: Vec<&str> = Vec::new();
+    for field in &fields {
+        if !sections.contains(&field.section) {
+            sections.push(field.section);
+        }
+    }
+
+    let close = move || {
+        edits.write().clear();
+        problems.set(Vec::new());
+        app_state.write().settings_visible = false;
+    };
+    let save = move |_| {
+        let current = app_config.read().clone();
+        let edited = edits.read();
+        let new_config = match apply_edits(&current, edited.iter().map(|(id, value)| (*id, value.as_str()))) {
+            Ok(new_config) => new_config,
+            Err(found) => return problems.set(found),
+        };
+        drop(edited);
+        let saved_to = match new_config.save_user() {
+            Ok(path) => path,
+            Err(e) => return problems.set(vec![t!("settings-save-failed", error = e)]),
+        };
+        tracing::info!("Saved the settings to {}", saved_to.display());
+        let restart_required = apply_config(app_state.clone(), app_config.clone(), new_config);
+        app_state.write().notice = Some(if restart_required.is_empty() {
+            t!("notice-settings-saved", path = saved_to.display())
+        } else {
+            t!("notice-settings-saved-restart", path = saved_to.display(), settings = restart_required.join(", "))
+        });
+        close();
+    };
+
+    rsx! {
+        div {
+            class: "settings-dialog",
+            style: "position: fixed; top: 5%; left: 50%; transform: translateX(-50%); width: 560px; max-height: 85vh; overflow-y: auto; padding: 15px; z-index: 1000; background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border}; border-radius: 8px; box-shadow: 0 5px 15px rgba(0,0,0,0.5); font-size: 1.3rem;",
+            h3 { style: "margin: 0 0 10px 0;", {t!("settings-title")} }
+            {problems.get().iter().map(|problem| rsx! { p { style: "margin: 2px 0; color: {palette.chart_bearish}; font-size: 1.2rem;", "{problem}" } })}
+            {sections.into_iter().map(|section| {
+                let section_fields = fields.iter().filter(|field| field.section == section).map(|field| {
+                    let id = field.id;
+                    let value = edits.read().get(id).cloned().unwrap_or_else(|| (field.get)(&config));
+                    rsx! {
+                        SettingsFieldRow {
+                            key: "{id}",
+                            id: id,
+                            kind: field.kind,
+                            value: value,
+                            on_change: move |value: String| {
+                                edits.write().insert(id, value);
+                            },
+                            palette: palette.clone()
+                        }
+                    }
+                });
+                rsx! {
+                    fieldset {
+                        key: "{section}",
+                        style: "border: 1px solid {palette.border}; border-radius: 4px; margin: 0 0 10px 0;",
+                        legend { {t!(section)} }
+                        {section_fields}
+                    }
+                }
+            })}
+            div {
+                style: "display: flex; justify-content: flex-end; gap: 8px;",
+                button {
+                    style: "background: none; color: {palette.muted}; border: 1px solid {palette.border}; border-radius: 3px; padding: 4px 10px; cursor: pointer;",
+                    onclick: move |_| close(),
+                    {t!("dialog-cancel")}
+                }
+                button {
+                    style: "background-color: {palette.primary}; color: white; border: none; border-radius: 3px; padding: 4px 10px; cursor: pointer;",
+                    onclick: save,
+                    {t!("dialog-save")}
+                }
+            }
+        }
+    }
+}
+
+/// One setting: its name, then the input for its kind. Also used by the connection dialog.
+#[component]
+pub fn SettingsFieldRow(id: &'static str, kind: FieldKind, value: String, on_change: EventHandler<String>, palette: ThemePalette) -> Element {
+    let input_style = format!("flex: 1; background-color: {}; color: {}; border: 1px solid {};", palette.background, palette.foreground, palette.border);
+    let input = match kind {
+        FieldKind::Toggle => rsx! {
+            input {
+                r#type: "checkbox",
+                checked: value == "true",
+                oninput: move |evt: FormEvent| on_change.call(evt.value.clone()),
+            }
+        },
+        FieldKind::Choice(options) => rsx! {
+            select {
+                style: "{input_style}",
+                value: "{value}",
+                onchange: move |evt: FormEvent| on_change.call(evt.value.clone()),
+                {options.iter().map(|option| rsx! { option { key: "{option}", value: "{option}", selected: *option == value, {t!(format!("settings-option-{}", option))} } })}
+            }
+        },
+        FieldKind::Color => rsx! {
+            input {
+                r#type: "color",
+                value: "{value}",
+                oninput: move |evt: FormEvent| on_change.call(evt.value.clone()),
+            }
+            code { style: "color: {palette.muted};", "{value}" }
+        },
+        FieldKind::Number | FieldKind::Text => rsx! {
+            input {
+                r#type: if kind == FieldKind::Number { "number" } else { "text" },
+                style: "{input_style}",
+                value: "{value}",
+                oninput: move |evt: FormEvent| on_change.call(evt.value.clone()),
+            }
+        },
+    };
+    rsx! {
+        label {
+            style: "display: flex; align-items: center; gap: 8px; padding: 3px 0;",
+            span { style: "width: 190px; color: {palette.muted};", {t!(id)} }
+            {input}
+        }
+    }
+}
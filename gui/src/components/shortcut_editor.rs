@@ -0,0 +1,129 @@
+// Keyboard shortcut editor
+// Lists the actions of the `shortcuts` config section with their bindings. "Rebind" records the
+// next key combination pressed; it applies at once unless another action already uses it, and is
+// saved with the user preferences. Opened by the "Edit Keyboard Shortcuts" command.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+
+use crate::config::shortcuts::{KeyCombo, ShortcutAction};
+use crate::config::{AppConfig, Shortcuts};
+use crate::state::app_state::AppState;
+use crate::state::preferences::save_shortcuts;
+
+/// Without Ctrl or Alt, only function keys make shortcuts, so typing in text fields never triggers one.
+fn usable_without_modifier(combo: &KeyCombo) -> bool {
+    combo.key.len() > 1 && combo.key.starts_with('F') && combo.key[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+/// Changes the running app's shortcuts and saves them.
+fn apply_shortcuts(app_config: &UseSharedState<AppConfig>, update: impl FnOnce(&mut Shortcuts)) {
+    let mut config_writer = app_config.write();
+    update(&mut config_writer.shortcuts);
+    save_shortcuts(&config_writer.app.preferences_file, &config_writer.shortcuts);
+}
+
+#[component]
+pub fn ShortcutEditor() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let app_config = use_shared_state::<AppConfig>().unwrap();
+    let capturing = use_state(|| None::<ShortcutAction>);
+    let message = use_state(|| None::<String>);
+
+    if !app_state.read().shortcut_editor_visible {
+        return None;
+    }
+    let palette = app_state.read().palette();
+    let shortcuts = app_config.read().shortcuts.clone();
+    let conflicting = shortcuts.conflicting_actions();
+
+    let handle_keydown = move |event: Event<KeyboardData>| {
+        let Some(action) = *capturing.get() else { return };
+        let modifiers = event.modifiers();
+        let combo = KeyCombo::new(modifiers.ctrl() || modifiers.meta(), modifiers.shift(), modifiers.alt(), &event.key().to_string());
+        if combo.is_modifier_only() {
+            return;
+        }
+        if combo.key == "Escape" && !combo.ctrl && !combo.alt {
+            capturing.set(None);
+            message.set(None);
+            return;
+        }
+        if !combo.ctrl && !combo.alt && !usable_without_modifier(&combo) {
+            message.set(Some(t!("shortcuts-needs-modifier", combo = combo)));
+            return;
+        }
+        let conflicts = app_config.read().shortcuts.conflicts_with(action, &combo);
+        if let Some(other) = conflicts.first() {
+            message.set(Some(t!("shortcuts-in-use", combo = combo, action = other.label())));
+            return;
+        }
+        tracing::info!(?action, %combo, "Rebound keyboard shortcut");
+        apply_shortcuts(&app_config, |shortcuts| action.set_binding(shortcuts, combo.to_string()));
+        capturing.set(None);
+        message.set(None);
+    };
+
+    let reset_to_defaults = move |_| match AppConfig::load_default() {
+        Ok(defaults) => {
+            apply_shortcuts(&app_config, |shortcuts| *shortcuts = defaults.shortcuts);
+            capturing.set(None);
+            message.set(None);
+        }
+        Err(e) => message.set(Some(t!("shortcuts-defaults-failed", error = e))),
+    };
+
+    rsx! {
+        div {
+            class: "shortcut-editor",
+            tabindex: "0",
+            style: "position: fixed; top: 10%; left: 50%; transform: translateX(-50%); width: 520px; padding: 15px; z-index: 1000; background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border}; border-radius: 8px; box-shadow: 0 5px 15px rgba(0,0,0,0.5); outline: none;",
+            onkeydown: handle_keydown,
+            h3 { style: "margin: 0 0 10px 0;", {t!("shortcuts-title")} }
+            {message.get().as_ref().map(|text| rsx! { p { style: "color: {palette.chart_bearish}; font-size: 1.2rem;", "{text}" } })}
+            {ShortcutAction::ALL.into_iter().map(|action| {
+                let binding = action.binding(&shortcuts).to_string();
+                let binding_color = if conflicting.contains(&action) { palette.chart_bearish.as_str() } else { palette.foreground.as_str() };
+                let waiting = *capturing.get() == Some(action);
+                let button_label = if waiting { t!("shortcuts-capturing") } else { t!("shortcuts-rebind") };
+                let binding_title = if conflicting.contains(&action) { t!("shortcuts-conflict-hint") } else { String::new() };
+                rsx! {
+                    div {
+                        key: "{action:?}",
+                        style: "display: flex; align-items: center; gap: 8px; padding: 6px 0; border-top: 1px solid {palette.border};",
+                        span { style: "flex: 1;", "{action.label()}" }
+                        code {
+                            style: "min-width: 90px; color: {binding_color};",
+                            title: "{binding_title}",
+                            "{binding}"
+                        }
+                        button {
+                            style: "min-width: 170px; background-color: {palette.background}; color: {palette.foreground}; border: 1px solid {palette.border}; border-radius: 3px; cursor: pointer;",
+                            onclick: move |_| {
+                                capturing.set(Some(action));
+                                message.set(None);
+                            },
+                            "{button_label}"
+                        }
+                    }
+                }
+            })}
+            div {
+                style: "display: flex; justify-content: flex-end; gap: 8px; margin-top: 10px;",
+                button {
+                    style: "background: none; color: {palette.muted}; border: 1px solid {palette.border}; border-radius: 3px; padding: 4px 10px; cursor: pointer;",
+                    onclick: reset_to_defaults,
+                    {t!("shortcuts-reset")}
+                }
+                button {
+                    style: "background-color: {palette.primary}; color: white; border: none; border-radius: 3px; padding: 4px 10px; cursor: pointer;",
+                    onclick: move |_| {
+                        capturing.set(None);
+                        message.set(None);
+                        app_state.write().shortcut_editor_visible = false;
+                    },
+                    {t!("dialog-close")}
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+// Status bar
+// One line at the bottom of the window: the engine connection (from the periodic health checks),
+// the symbol and timeframe on the chart, its candle count, range and change, and when candles were last updated.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+use shared::models::to_f64;
+use shared::utils::brazilian_format::{format_decimal, format_signed_decimal};
+
+use crate::state::app_state::{AppState, ConnectionState};
+
+#[component]
+pub fn StatusBar() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let app_state_reader = app_state.read();
+    let palette = app_state_reader.palette();
+    let (dot_color, connection) = match app_state_reader.connection {
+        ConnectionState::Connecting => (palette.muted.clone(), t!("status-connecting")),
+        ConnectionState::Connected => (palette.chart_bullish.clone(), t!("status-connected")),
+        ConnectionState::Reconnecting { attempt, retry_in_secs } => (palette.accent.clone(), t!("status-reconnecting", attempt = attempt, seconds = retry_in_secs)),
+        ConnectionState::Offline { retry_in_secs } => (palette.chart_bearish.clone(), t!("status-offline", seconds = retry_in_secs)),
+    };
+    let chart = app_state_reader.current_symbol_display.as_ref().map(|symbol| {
+        let market_data = app_state_reader.all_market_data.get(symbol);
+        let timeframe = market_data.map_or("-".to_string(), |market_data| market_data.timeframe.to_string());
+        let candles = market_data.map_or(0, |market_data| market_data.candles.len());
+        let chart = t!("status-chart", symbol = symbol, timeframe = timeframe, candles = candles);
+        match market_data.and_then(|market_data| market_data.stats()) {
+            Some(stats) => format!("{} · {}", chart, t!(
+                "status-range",
+                low = format_decimal(to_f64(stats.low), 2), high = format_decimal(to_f64(stats.high), 2), change = format!("{}%", format_signed_decimal(stats.change_pct, 2))
+            )),
+            None => chart,
+        }
+    });
+    let last_update = app_state_reader.last_data_update
+        .map(|updated_at| updated_at.with_timezone(&chrono::Local).format("%H:%M:%S").to_string());
+    drop(app_state_reader);
+
+    rsx! {
+        div {
+            class: "status-bar",
+            style: "display: flex; gap: 16px; align-items: center; margin-top: 12px; padding: 4px 10px; font-size: 1.2rem; color: {palette.muted}; background-color: {palette.surface}; border-top: 1px solid {palette.border};",
+            span {
+                style: "cursor: pointer;",
+                title: t!("connection-edit-hint"),
+                onclick: move |_| app_state.write().connection_dialog_visible = true,
+                span { style: "display: inline-block; width: 8px; height: 8px; border-radius: 50%; margin-right: 6px; background-color: {dot_color};" }
+                "{connection}"
+            }
+            {chart.map(|chart| rsx! { span { "{chart}" } })}
+            {last_update.map(|time| rsx! { span { style: "margin-left: auto;", {t!("status-updated", time = time)} } })}
+        }
+    }
+}
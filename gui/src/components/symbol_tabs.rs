@@ -0,0 +1,75 @@
+// Symbol tab bar
+// One tab per symbol loaded in the GUI or the engine; clicking a tab switches the chart to it,
+// fetching its candles from the engine the first time only.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+
+use crate::services::engine_client::EngineClient;
+use crate::services::symbols::open_symbol;
+use crate::state::app_state::AppState;
+
+#[component]
+pub fn SymbolTabs() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let engine_client = use_shared_state::<Option<EngineClient>>().unwrap();
+
+    let app_state_reader = app_state.read();
+    let symbols = app_state_reader.available_symbols();
+    let current_symbol = app_state_reader.current_symbol_display.clone();
+    let palette = app_state_reader.palette();
+    drop(app_state_reader);
+
+    // Switches the chart to `symbol`, fetching its candles if they are not loaded here yet.
+    // Wrapped in Rc so every tab's onclick can share it.
+    let select_symbol = std::rc::Rc::new(move |symbol: String| {
+        if app_state.read().all_market_data.contains_key(&symbol) {
+            app_state.write().set_display_data(&symbol);
+            return;
+        }
+        match engine_client.read().as_ref().cloned() {
+            Some(client) => open_symbol(app_state.clone(), client, symbol, true),
+            None => app_state.write().error_message = Some(t!("error-no-engine")),
+        }
+    });
+
+    // Picks up symbols loaded in the engine since connecting (e.g. by another client).
+    let refresh_symbols = move |_| {
+        let Some(mut client) = engine_client.read().as_ref().cloned() else { return };
+        spawn(async move {
+            match client.list_symbols().await {
+                Ok(symbols) => app_state.write().engine_symbols = symbols,
+                Err(e) => tracing::warn!("Failed to list the engine's symbols: {}", e),
+            }
+        });
+    };
+
+    rsx! {
+        div {
+            class: "symbol-tabs",
+            style: "display: flex; gap: 4px; align-items: center; width: 800px; overflow-x: auto; margin-bottom: 6px;",
+            if symbols.is_empty() {
+                span { style: "color: {palette.muted}; font-size: 1.2rem;", {t!("symbols-empty")} }
+            }
+            {symbols.into_iter().map(|symbol| {
+                let active = current_symbol.as_deref() == Some(symbol.as_str());
+                let (background, color) = if active { (palette.primary.as_str(), "white") } else { (palette.surface.as_str(), palette.foreground.as_str()) };
+                let symbol_for_click = symbol.clone();
+                let select_for_click = select_symbol.clone();
+                rsx! {
+                    button {
+                        key: "{symbol}",
+                        style: "padding: 4px 12px; background-color: {background}; color: {color}; border: 1px solid {palette.border}; border-radius: 4px 4px 0 0; cursor: pointer; white-space: nowrap;",
+                        onclick: move |_| select_for_click(symbol_for_click.clone()),
+                        "{symbol}"
+                    }
+                }
+            })}
+            button {
+                title: t!("symbols-refresh"),
+                style: "margin-left: auto; padding: 4px 8px; background: none; color: {palette.muted}; border: 1px solid {palette.border}; border-radius: 4px; cursor: pointer;",
+                onclick: refresh_symbols,
+                "⟳"
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+// Watchlist sidebar
+// Lists the chosen symbols with their latest close, daily change and a sparkline of recent closes.
+// Their candles are fetched once and then kept current by live updates (SubscribeCandles), so the
+// quotes refresh as candles arrive. Clicking a symbol shows it on the chart.
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+
+use crate::config::AppConfig;
+use crate::services::engine_client::EngineClient;
+use crate::services::symbols::open_symbol;
+use crate::state::app_state::AppState;
+use crate::state::preferences::save_watchlist;
+
+const SPARKLINE_WIDTH: f64 = 60.0;
+const SPARKLINE_HEIGHT: f64 = 18.0;
+
+/// SVG polyline points of `closes` scaled into the sparkline box.
+fn sparkline_points(closes: &[f64]) -> String {
+    let (low, high) = closes.iter().fold((f64::MAX, f64::MIN), |(low, high), &close| (low.min(close), high.max(close)));
+    let range = (high - low).max(f64::EPSILON);
+    let step = SPARKLINE_WIDTH / (closes.len().max(2) - 1) as f64;
+    closes.iter().enumerate()
+        .map(|(i, close)| format!("{:.1},{:.1}", i as f64 * step, SPARKLINE_HEIGHT - (close - low) / range * SPARKLINE_HEIGHT))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[component]
+pub fn Watchlist() -> Element {
+    let app_state = use_shared_state::<AppState>().unwrap();
+    let app_config = use_shared_state::<AppConfig>().unwrap();
+    let engine_client = use_shared_state::<Option<EngineClient>>().unwrap();
+    let new_symbol = use_state(String::new);
+
+    let app_state_reader = app_state.read();
+    let watchlist = app_state_reader.watchlist.clone();
+    let quotes: Vec<_> = watchlist.iter().map(|symbol| (symbol.clone(), app_state_reader.watch_quote(symbol))).collect();
+    let candidates: Vec<String> = app_state_reader.available_symbols().into_iter().filter(|symbol| !watchlist.contains(symbol)).collect();
+    let current_symbol = app_state_reader.current_symbol_display.clone();
+    let palette = app_state_reader.palette();
+    drop(app_state_reader);
+
+    // Watched symbols are fetched (and followed) once the engine is connected
+    let connected = engine_client.read().is_some();
+    use_effect((connected, watchlist.clone()), move |(_connected, watchlist)| {
+        let Some(client) = engine_client.read().as_ref().cloned() else { return };
+        for symbol in watchlist {
+            if !app_state.read().all_market_data.contains_key(&symbol) {
+                open_symbol(app_state.clone(), client.clone(), symbol, false);
+            }
+        }
+    });
+
+    let add_symbol = move |_| {
+        let symbol = new_symbol.get().trim().to_uppercase();
+        if symbol.is_empty() {
+            return;
+        }
+        new_symbol.set(String::new());
+        let mut app_state_writer = app_state.write();
+        if app_state_writer.add_to_watchlist(&symbol) {
+            save_watchlist(&app_config.read().app.preferences_file, &app_state_writer.watchlist);
+        }
+    };
+    // Shared by every row's buttons
+    let select_symbol = std::rc::Rc::new(move |symbol: String| match engine_client.read().as_ref().cloned() {
+        Some(client) => open_symbol(app_state.clone(), client, symbol, true),
+        None => app_state.write().set_display_data(&symbol),
+    });
+    let remove_symbol = std::rc::Rc::new(move |symbol: String| {
+        let mut app_state_writer = app_state.write();
+        app_state_writer.remove_from_watchlist(&symbol);
+        save_watchlist(&app_config.read().app.preferences_file, &app_state_writer.watchlist);
+    });
+
+    rsx! {
+        div {
+            class: "watchlist",
+            style: "width: 240px; border: 1px solid {palette.border}; border-radius: 4px; padding: 8px; font-size: 1.2rem; color: {palette.foreground};",
+            h4 { style: "margin: 0 0 8px 0;", {t!("watchlist-title")} }
+            div {
+                style: "display: flex; gap: 4px; margin-bottom: 6px;",
+                input {
+                    r#type: "text",
+                    list: "watchlist-candidates",
+                    placeholder: t!("watchlist-symbol-placeholder"),
+                    value: "{new_symbol}",
+                    style: "flex: 1; background-color: {palette.surface}; color: {palette.foreground}; border: 1px solid {palette.border};",
+                    oninput: move |evt: FormEvent| new_symbol.set(evt.value.clone()),
+                }
+                datalist {
+                    id: "watchlist-candidates",
+                    {candidates.into_iter().map(|symbol| rsx! { option { key: "{symbol}", value: "{symbol}" } })}
+                }
+                button {
+                    style: "background-color: {palette.primary}; color: white; border: none; border-radius: 3px; cursor: pointer;",
+                    onclick: add_symbol,
+                    {t!("watchlist-add")}
+                }
+            }
+            if quotes.is_empty() {
+                p { style: "color: {palette.muted};", {t!("watchlist-empty")} }
+            }
+            {quotes.into_iter().map(|(symbol, quote)| {
+                let active = current_symbol.as_deref() == Some(symbol.as_str());
+                let weight = if active { "bold" } else { "normal" };
+                let (select_for_click, remove_for_click) = (select_symbol.clone(), remove_symbol.clone());
+                let (symbol_for_select, symbol_for_remove) = (symbol.clone(), symbol.clone());
+                rsx! {
+                    div {
+                        key: "{symbol}",
+                        style: "display: flex; align-items: center; gap: 6px; border-top: 1px solid {palette.border}; padding: 4px 0;",
+                        span {
+                            style: "width: 60px; cursor: pointer; font-weight: {weight};",
+                            onclick: move |_| select_for_click(symbol_for_select.clone()),
+                            "{symbol}"
+                        }
+                        {match quote {
+                            Some(quote) => {
+                                let color = if quote.change >= 0.0 { &palette.chart_bullish } else { &palette.chart_bearish };
+                                let points = sparkline_points(&quote.sparkline);
+                                let last = format!("{:.*}", app_config.read().price_decimals(&symbol), quote.last);
+                                rsx! {
+                                    span { style: "width: 56px; text-align: right;", "{last}" }
+                                    span { style: "width: 48px; text-align: right; color: {color};", "{quote.change_pct:+.2}%" }
+                                    svg {
+                                        width: "{SPARKLINE_WIDTH}",
+                                        height: "{SPARKLINE_HEIGHT}",
+                                        polyline { points: "{points}", fill: "none", stroke: "{color}", stroke_width: "1" }
+                                    }
+                                }
+                            }
+                            None => rsx! { span { style: "flex: 1; color: {palette.muted};", {t!("watchlist-loading")} } },
+                        }}
+                        button {
+                            title: t!("watchlist-remove"),
+                            style: "margin-left: auto; background: none; border: none; color: {palette.muted}; cursor: pointer;",
+                            onclick: move |_| remove_for_click(symbol_for_remove.clone()),
+                            "✕"
+                        }
+                    }
+                }
+            })}
+        }
+    }
+}
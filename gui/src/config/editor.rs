@@ -0,0 +1,148 @@
+// Fields of the settings dialog: each reads one `AppConfig` setting as text and parses an edit back
+// into it, so the dialog is a list of inputs and a config is only replaced once all of them parse.
+// Sections and fields are named by their messages in the i18n catalogs (e.g. "settings-theme").
+use engine::config::settings::Compression;
+
+use super::AppConfig;
+
+/// How a field is edited. Choices are shown by their "settings-option-{value}" messages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    Text,
+    Number,
+    Color,
+    Toggle,
+    Choice(&'static [&'static str]),
+}
+
+pub struct SettingsField {
+    pub section: &'static str,
+    pub id: &'static str,
+    pub kind: FieldKind,
+    pub get: fn(&AppConfig) -> String,
+    pub set: fn(&mut AppConfig, &str) -> Result<(), String>,
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.trim().parse().map_err(|_| t!("settings-invalid-number", value = value))
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    value.parse().map_err(|_| t!("settings-invalid-toggle", value = value))
+}
+
+/// Comma-separated values, e.g. "20, 50, 200".
+fn parse_list<T: std::str::FromStr>(value: &str) -> Result<Vec<T>, String> {
+    value.split(',').map(str::trim).filter(|item| !item.is_empty())
+        .map(|item| item.parse().map_err(|_| t!("settings-invalid-list-item", value = item)))
+        .collect()
+}
+
+fn join_list<T: ToString>(items: &[T]) -> String {
+    items.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Empty text means "not set".
+fn optional(value: &str) -> Option<String> {
+    Some(value.trim().to_string()).filter(|value| !value.is_empty())
+}
+
+fn compression_name(compression: Compression) -> &'static str {
+    match compression {
+        Compression::None => "none",
+        Compression::Gzip => "gzip",
+        Compression::Zstd => "zstd",
+    }
+}
+
+macro_rules! field {
+    ($section:literal, $id:literal, $kind:expr, |$config:ident| $get:expr, |$target:ident, $value:ident| $set:expr) => {
+        SettingsField {
+            section: $section,
+            id: $id,
+            kind: $kind,
+            get: |$config: &AppConfig| $get,
+            set: |$target: &mut AppConfig, $value: &str| {
+                $set;
+                Ok(())
+            },
+        }
+    };
+}
+
+/// Every editable setting, grouped by section in display order. Shortcuts have their own editor.
+pub fn settings_fields() -> Vec<SettingsField> {
+    use FieldKind::*;
+    vec![
+        field!("settings-section-general", "settings-theme", Choice(&["dark", "light"]), |c| c.app.theme.clone(), |c, v| c.app.theme = v.to_string()),
+        field!("settings-section-general", "settings-language", Choice(&["pt-BR", "en-US"]), |c| c.app.language.clone(), |c, v| c.app.language = v.to_string()),
+        field!("settings-section-general", "settings-auto-save", Toggle, |c| c.app.auto_save.to_string(), |c, v| c.app.auto_save = parse_bool(v)?),
+        field!("settings-section-general", "settings-auto-save-interval", Number, |c| c.app.auto_save_interval.to_string(), |c, v| c.app.auto_save_interval = parse_number(v)?),
+        field!("settings-section-general", "settings-preferences-file", Text, |c| c.app.preferences_file.clone(), |c, v| c.app.preferences_file = v.trim().to_string()),
+        field!("settings-section-general", "settings-project-file", Text, |c| c.app.project_file.clone(), |c, v| c.app.project_file = v.trim().to_string()),
+        field!("settings-section-general", "settings-ui-scale", Number, |c| c.app.ui_scale.to_string(), |c, v| c.app.ui_scale = parse_number(v)?),
+        field!("settings-section-general", "settings-font-size", Number, |c| c.app.font_size.to_string(), |c, v| c.app.font_size = parse_number(v)?),
+        field!("settings-section-engine", "settings-engine-embedded", Toggle, |c| c.engine.embedded.to_string(), |c, v| c.engine.embedded = parse_bool(v)?),
+        field!("settings-section-engine", "settings-engine-spawn", Toggle, |c| c.engine.spawn.to_string(), |c, v| c.engine.spawn = parse_bool(v)?),
+        field!("settings-section-engine", "settings-engine-executable", Text, |c| c.engine.executable.clone(), |c, v| c.engine.executable = v.trim().to_string()),
+        field!("settings-section-engine", "settings-engine-host", Text, |c| c.engine.host.clone(), |c, v| c.engine.host = v.trim().to_string()),
+        field!("settings-section-engine", "settings-engine-port", Number, |c| c.engine.port.to_string(), |c, v| c.engine.port = parse_number(v)?),
+        field!("settings-section-engine", "settings-engine-unix-socket", Text, |c| c.engine.unix_socket.clone().unwrap_or_default(), |c, v| c.engine.unix_socket = optional(v)),
+        field!("settings-section-engine", "settings-engine-namespace", Text, |c| c.engine.namespace.clone().unwrap_or_default(), |c, v| c.engine.namespace = optional(v)),
+        field!("settings-section-engine", "settings-engine-compression", Choice(&["none", "gzip", "zstd"]), |c| compression_name(c.engine.compression).to_string(),
+            |c, v| c.engine.compression = serde_json::from_value(serde_json::Value::String(v.to_string())).map_err(|e| e.to_string())?),
+        field!("settings-section-engine", "settings-engine-health-check", Number, |c| c.engine.health_check_interval_secs.to_string(), |c, v| c.engine.health_check_interval_secs = parse_number(v)?),
+        field!("settings-section-chart", "settings-chart-type", Choice(&["candlestick", "bar", "line", "area", "heikin-ashi"]), |c| c.chart.chart_type.clone(), |c, v| c.chart.chart_type = v.to_string()),
+        field!("settings-section-chart", "settings-bullish-color", Color, |c| c.chart.candle.bullish_color.clone(), |c, v| c.chart.candle.bullish_color = v.to_string()),
+        field!("settings-section-chart", "settings-bearish-color", Color, |c| c.chart.candle.bearish_color.clone(), |c, v| c.chart.candle.bearish_color = v.to_string()),
+        field!("settings-section-chart", "settings-candle-border", Number, |c| c.chart.candle.border_width.to_string(), |c, v| c.chart.candle.border_width = parse_number(v)?),
+        field!("settings-section-chart", "settings-wick-width", Number, |c| c.chart.candle.wick_width.to_string(), |c, v| c.chart.candle.wick_width = parse_number(v)?),
+        field!("settings-section-chart", "settings-background", Color, |c| c.chart.background.clone(), |c, v| c.chart.background = v.to_string()),
+        field!("settings-section-chart", "settings-grid", Toggle, |c| c.chart.grid.enabled.to_string(), |c, v| c.chart.grid.enabled = parse_bool(v)?),
+        field!("settings-section-chart", "settings-grid-color", Color, |c| c.chart.grid.color.clone(), |c, v| c.chart.grid.color = v.to_string()),
+        field!("settings-section-chart", "settings-grid-style", Choice(&["dashed", "solid"]), |c| c.chart.grid.style.clone(), |c, v| c.chart.grid.style = v.to_string()),
+        field!("settings-section-chart", "settings-sessions", Toggle, |c| c.chart.sessions.to_string(), |c, v| c.chart.sessions = parse_bool(v)?),
+        field!("settings-section-chart", "settings-crosshair", Toggle, |c| c.chart.crosshair.enabled.to_string(), |c, v| c.chart.crosshair.enabled = parse_bool(v)?),
+        field!("settings-section-chart", "settings-crosshair-color", Color, |c| c.chart.crosshair.color.clone(), |c, v| c.chart.crosshair.color = v.to_string()),
+        field!("settings-section-chart", "settings-scale-color", Color, |c| c.chart.price_scale.color.clone(), |c, v| {
+            c.chart.price_scale.color = v.to_string();
+            c.chart.time_scale.color = v.to_string()
+        }),
+        field!("settings-section-chart", "settings-scale-border", Color, |c| c.chart.price_scale.border_color.clone(), |c, v| {
+            c.chart.price_scale.border_color = v.to_string();
+            c.chart.time_scale.border_color = v.to_string()
+        }),
+        field!("settings-section-indicators", "settings-sma-periods", Text, |c| join_list(&c.indicators.sma.periods), |c, v| c.indicators.sma.periods = parse_list(v)?),
+        field!("settings-section-indicators", "settings-sma-colors", Text, |c| join_list(&c.indicators.sma.colors), |c, v| c.indicators.sma.colors = parse_list(v)?),
+        field!("settings-section-indicators", "settings-sma-line-width", Number, |c| c.indicators.sma.line_width.to_string(), |c, v| c.indicators.sma.line_width = parse_number(v)?),
+        field!("settings-section-indicators", "settings-ema-periods", Text, |c| join_list(&c.indicators.ema.periods), |c, v| c.indicators.ema.periods = parse_list(v)?),
+        field!("settings-section-indicators", "settings-ema-colors", Text, |c| join_list(&c.indicators.ema.colors), |c, v| c.indicators.ema.colors = parse_list(v)?),
+        field!("settings-section-indicators", "settings-ema-line-width", Number, |c| c.indicators.ema.line_width.to_string(), |c, v| c.indicators.ema.line_width = parse_number(v)?),
+        field!("settings-section-indicators", "settings-rsi-period", Number, |c| c.indicators.rsi.period.to_string(), |c, v| c.indicators.rsi.period = parse_number(v)?),
+        field!("settings-section-indicators", "settings-rsi-overbought", Number, |c| c.indicators.rsi.overbought.to_string(), |c, v| c.indicators.rsi.overbought = parse_number(v)?),
+        field!("settings-section-indicators", "settings-rsi-oversold", Number, |c| c.indicators.rsi.oversold.to_string(), |c, v| c.indicators.rsi.oversold = parse_number(v)?),
+        field!("settings-section-indicators", "settings-rsi-color", Color, |c| c.indicators.rsi.color.clone(), |c, v| c.indicators.rsi.color = v.to_string()),
+        field!("settings-section-data", "settings-csv-delimiter", Text, |c| c.data.csv_delimiter.clone(), |c, v| c.data.csv_delimiter = v.to_string()),
+        field!("settings-section-data", "settings-decimal-separator", Text, |c| c.data.decimal_separator.clone(), |c, v| c.data.decimal_separator = v.to_string()),
+        field!("settings-section-data", "settings-thousand-separator", Text, |c| c.data.thousand_separator.clone(), |c, v| c.data.thousand_separator = v.to_string()),
+        field!("settings-section-data", "settings-date-format", Text, |c| c.data.date_format.clone(), |c, v| c.data.date_format = v.to_string()),
+        field!("settings-section-data", "settings-time-format", Text, |c| c.data.time_format.clone(), |c, v| c.data.time_format = v.to_string()),
+    ]
+}
+
+/// `base` with the `edits` (field id, text) applied, checked by `AppConfig::validate`;
+/// otherwise every problem found, one message each.
+pub fn apply_edits<'a>(base: &AppConfig, edits: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<AppConfig, Vec<String>> {
+    let fields = settings_fields();
+    let mut config = base.clone();
+    let mut problems = Vec::new();
+    for (id, value) in edits {
+        if let Some(field) = fields.iter().find(|field| field.id == id) {
+            if let Err(e) = (field.set)(&mut config, value) {
+                problems.push(t!("settings-field-error", field = t!(field.id), error = e));
+            }
+        }
+    }
+    problems.extend(config.validate());
+    if problems.is_empty() { Ok(config) } else { Err(problems) }
+}
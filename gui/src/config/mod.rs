@@ -1,13 +1,21 @@
 // GUI configuration module
 pub mod theme; // For theme-specific configurations (colors, fonts, etc.)
+pub mod shortcuts; // Key combinations of the `shortcuts` section and their actions
+pub mod editor; // Fields of the settings dialog
 // Potentially app_config.rs for the main application config structure (mapping to default.json)
 
 // Example: Structure for the entire application configuration loaded from JSON
 // This would mirror the structure of assets/config/default.json
-use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use engine::config::settings::is_toml;
+use engine::config::symbols::{lookup, SymbolSettings};
+use shortcuts::{KeyCombo, ShortcutAction};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 // use super::state::app_state::Theme; // If theme enum is used here
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppConfig {
     pub version: String,
     pub app: AppSettings,
@@ -16,25 +24,90 @@ pub struct AppConfig {
     pub indicators: IndicatorDefaults, // Consider nesting further if complex
     pub data: DataSettings,
     pub shortcuts: Shortcuts,
+    #[serde(default)]
+    pub symbols: HashMap<String, SymbolSettings>, // Tick size, price decimals, timeframe... of a symbol or futures root
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// Decimals prices are shown with when the `symbols` section sets none.
+pub const DEFAULT_PRICE_DECIMALS: usize = 2;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppSettings {
     pub theme: String, // "dark" or "light" - could map to Theme enum
     pub language: String,
     pub auto_save: bool,
     pub auto_save_interval: u32,
+    #[serde(default = "default_preferences_file")]
+    pub preferences_file: String, // Where choices like the theme are saved between runs
+    #[serde(default = "default_project_file")]
+    pub project_file: String, // Project saved and opened by the Save/Open Project commands
+    #[serde(default = "default_ui_scale")]
+    pub ui_scale: f64, // Zoom of the whole window, e.g. 2.0 on 4K displays
+    #[serde(default = "default_font_size")]
+    pub font_size: f64, // Base size of chart text, axis labels and panels, in pixels at scale 1.0
+}
+
+impl AppSettings {
+    /// Size of the page's root font: sizes in the GUI are in rem, 1.2rem being 12px at the
+    /// default `font_size` of 16px.
+    pub fn root_font_size(&self) -> f64 {
+        10.0 * self.font_size / default_font_size()
+    }
+}
+
+fn default_ui_scale() -> f64 {
+    1.0
+}
+
+fn default_font_size() -> f64 {
+    16.0
+}
+
+fn default_project_file() -> String {
+    "data/project.json".to_string()
+}
+
+fn default_preferences_file() -> String {
+    "data/gui_preferences.json".to_string()
+}
+
+fn default_health_check_interval_secs() -> u64 {
+    5
 }
 
-#[derive(Debug, Deserialize, Clone)]
+fn default_engine_executable() -> String {
+    "engine".to_string()
+}
+
+fn default_sessions() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct EngineConnSettings {
     pub host: String,
     pub port: u16,
+    #[serde(default)]
+    pub compression: engine::config::settings::Compression, // Same setting as the engine's: compresses requests
+    #[serde(default)]
+    pub unix_socket: Option<String>, // Same setting as the engine's: when set, connect through it instead of host:port
+    #[serde(default)]
+    pub namespace: Option<String>, // Engine namespace this workspace works in (x-namespace); None for the default one
+    #[serde(default)]
+    pub auth_token: Option<String>, // Same setting as the engine's: sent with every call when the engine requires it
+    #[serde(default = "default_health_check_interval_secs")]
+    pub health_check_interval_secs: u64, // How often the connection status is checked (grpc.health.v1)
+    #[serde(default)]
+    pub embedded: bool, // Run the engine inside the GUI (see `engine_settings`) instead of connecting to one
+    #[serde(default)]
+    pub spawn: bool, // Start the engine binary as a child process of the GUI, and stop it on exit
+    #[serde(default = "default_engine_executable")]
+    pub executable: String, // The engine binary started with `spawn`; a relative path is looked up next to the GUI's first
     // max_connections and thread_pool_size are engine's internal config, GUI might not need them directly
     // but they are in the example JSON.
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChartConfig {
     #[serde(rename = "type")]
     pub chart_type: String, // "candlestick"
@@ -42,11 +115,13 @@ pub struct ChartConfig {
     pub background: String,
     pub grid: GridStyle,
     pub crosshair: CrosshairStyle,
+    #[serde(default = "default_sessions")]
+    pub sessions: bool, // Shade time outside the B3 session and separate the days on intraday charts
     pub time_scale: ScaleStyle,
     pub price_scale: ScaleStyle,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CandleStyle {
     pub bullish_color: String,
     pub bearish_color: String,
@@ -54,21 +129,21 @@ pub struct CandleStyle {
     pub wick_width: u32,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GridStyle {
     pub color: String,
     pub enabled: bool,
     pub style: String, // "dashed", "solid"
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct CrosshairStyle {
     pub enabled: bool,
     pub color: String,
     pub style: String, // "solid", "dashed"
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ScaleStyle {
     pub visible: bool,
     pub color: String,
@@ -78,14 +153,14 @@ pub struct ScaleStyle {
     pub mode: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct IndicatorDefaults {
     pub sma: IndicatorSetting,
     pub ema: IndicatorSetting,
     pub rsi: RsiSetting,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct IndicatorSetting {
     pub enabled: bool,
     pub periods: Vec<u32>,
@@ -93,7 +168,7 @@ pub struct IndicatorSetting {
     pub line_width: u32,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RsiSetting {
     pub enabled: bool,
     pub period: u32,
@@ -102,7 +177,7 @@ pub struct RsiSetting {
     pub color: String,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DataSettings {
     pub csv_delimiter: String, // Should be char, but JSON string is easier
     pub decimal_separator: String, // Should be char
@@ -111,7 +186,7 @@ pub struct DataSettings {
     pub time_format: String,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Shortcuts {
     pub command_palette: String,
     pub load_csv: String,
@@ -120,6 +195,24 @@ pub struct Shortcuts {
     pub zoom_in: String,
     pub zoom_out: String,
     pub reset_zoom: String,
+    #[serde(default = "default_rerun_last_command")]
+    pub rerun_last_command: String, // Runs the command palette's most recent command again
+    #[serde(default = "default_undo")]
+    pub undo: String, // Undoes the last price level or indicator edit
+    #[serde(default = "default_redo")]
+    pub redo: String,
+}
+
+fn default_rerun_last_command() -> String {
+    "Ctrl+.".to_string()
+}
+
+fn default_undo() -> String {
+    "Ctrl+Z".to_string()
+}
+
+fn default_redo() -> String {
+    "Ctrl+Y".to_string()
 }
 
 impl AppConfig {
@@ -136,4 +229,188 @@ impl AppConfig {
         let config: AppConfig = serde_json::from_str(config_str)?;
         Ok(config)
     }
+
+    /// The default config with the user's settings (saved by the settings dialog) laid over it,
+    /// failing on an unreadable or invalid user config file, so a file saved half-edited does not
+    /// reset the settings in effect.
+    pub fn reload() -> Result<Self, anyhow::Error> {
+        let mut config = serde_json::from_str::<serde_json::Value>(include_str!("../../assets/config/default.json"))?;
+        if let Some(path) = user_config_path().filter(|path| path.exists()) {
+            merge_json(&mut config, read_config_file(&path)?);
+        }
+        Ok(serde_json::from_value(config)?)
+    }
+
+    /// The config to start with, and what is wrong with it, one message each, for the user to fix.
+    /// A user config file that cannot be read is reported and the defaults are used instead; one with
+    /// invalid settings is used as it is, with each invalid setting reported.
+    pub fn load_checked() -> Result<(Self, Vec<String>), anyhow::Error> {
+        match Self::reload() {
+            Ok(config) => {
+                let problems = config.validate();
+                Ok((config, problems))
+            }
+            Err(e) => {
+                let path = user_config_path().unwrap_or_default();
+                Ok((Self::load_default()?, vec![t!("invalid-user-config", path = path.display(), error = e)]))
+            }
+        }
+    }
+
+    /// What an embedded engine runs with: the engine's own settings (account file, risk limits...)
+    /// from the same config files, which the engine binary reads as well.
+    pub fn engine_settings() -> Result<engine::config::settings::AppSettings, anyhow::Error> {
+        load_merged()
+    }
+
+    /// Writes this config to the user config file, read over the defaults by `load` on the next runs.
+    pub fn save_user(&self) -> Result<PathBuf, anyhow::Error> {
+        let path = user_config_path().ok_or_else(|| anyhow::anyhow!("No config directory on this platform"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let contents = if is_toml(&path) { toml::to_string_pretty(self)? } else { serde_json::to_string_pretty(self)? };
+        std::fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    /// The `symbols` section's settings of `symbol`, or of its futures root.
+    pub fn symbol_settings(&self, symbol: &str) -> Option<&SymbolSettings> {
+        lookup(&self.symbols, symbol)
+    }
+
+    /// Decimals the prices of `symbol` are shown with.
+    pub fn price_decimals(&self, symbol: &str) -> usize {
+        self.symbol_settings(symbol).and_then(|settings| settings.decimals).map_or(DEFAULT_PRICE_DECIMALS, |decimals| decimals as usize)
+    }
+
+    /// Problems that would make this config misbehave, one message each; empty when it is fine.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, problem: String| {
+            if !ok {
+                problems.push(problem);
+            }
+        };
+        check(!self.engine.host.trim().is_empty(), t!("invalid-engine-host"));
+        check(self.engine.port != 0, t!("invalid-engine-port"));
+        check(self.engine.health_check_interval_secs > 0, t!("invalid-health-check"));
+        check(!(self.engine.spawn && self.engine.embedded), t!("invalid-engine-spawn-embedded"));
+        check(!self.engine.spawn || !self.engine.executable.trim().is_empty(), t!("invalid-engine-executable"));
+        check(self.app.auto_save_interval > 0, t!("invalid-auto-save"));
+        check(!self.app.preferences_file.trim().is_empty(), t!("invalid-preferences-file"));
+        check(!self.app.project_file.trim().is_empty(), t!("invalid-project-file"));
+        check((0.5..=3.0).contains(&self.app.ui_scale), t!("invalid-ui-scale"));
+        check((8.0..=32.0).contains(&self.app.font_size), t!("invalid-font-size"));
+
+        // Named after their fields in the settings dialog
+        let chart = &self.chart;
+        for (color, field) in [
+            (&chart.candle.bullish_color, "settings-bullish-color"),
+            (&chart.candle.bearish_color, "settings-bearish-color"),
+            (&chart.background, "settings-background"),
+            (&chart.grid.color, "settings-grid-color"),
+            (&chart.crosshair.color, "settings-crosshair-color"),
+            (&chart.time_scale.color, "settings-scale-color"),
+            (&chart.time_scale.border_color, "settings-scale-border"),
+            (&chart.price_scale.color, "settings-scale-color"),
+            (&chart.price_scale.border_color, "settings-scale-border"),
+            (&self.indicators.rsi.color, "settings-rsi-color"),
+        ] {
+            check(is_hex_color(color), t!("invalid-color", name = t!(field), color = color));
+        }
+
+        for (setting, indicator) in [(&self.indicators.sma, "SMA"), (&self.indicators.ema, "EMA")] {
+            check(!setting.periods.is_empty() && setting.periods.iter().all(|period| *period > 0), t!("invalid-periods", indicator = indicator));
+            check(setting.colors.len() >= setting.periods.len(), t!("invalid-period-colors", indicator = indicator));
+            check(setting.colors.iter().all(|color| is_hex_color(color)), t!("invalid-indicator-colors", indicator = indicator));
+            check(setting.line_width > 0, t!("invalid-line-width", indicator = indicator));
+        }
+        let rsi = &self.indicators.rsi;
+        check(rsi.period > 0, t!("invalid-rsi-period"));
+        check(rsi.oversold < rsi.overbought && rsi.overbought <= 100, t!("invalid-rsi-levels"));
+
+        for action in ShortcutAction::ALL {
+            let binding = action.binding(&self.shortcuts);
+            let valid = KeyCombo::parse(binding).is_some_and(|combo| !combo.is_modifier_only());
+            check(valid, t!("invalid-shortcut", action = action.label(), binding = binding));
+        }
+
+        let data = &self.data;
+        for (separator, field) in [(&data.csv_delimiter, "settings-csv-delimiter"), (&data.decimal_separator, "settings-decimal-separator"), (&data.thousand_separator, "settings-thousand-separator")] {
+            check(separator.chars().count() == 1, t!("invalid-separator", name = t!(field)));
+        }
+        check(data.decimal_separator != data.thousand_separator, t!("invalid-separators-equal"));
+        for (format, problem) in [(&data.date_format, "invalid-date-format"), (&data.time_format, "invalid-time-format")] {
+            let valid = !format.is_empty() && chrono::format::StrftimeItems::new(format).all(|item| item != chrono::format::Item::Error);
+            check(valid, t!(problem, format = format));
+        }
+        // Shared with the engine, which reports them the same way
+        let mut symbols: Vec<_> = self.symbols.iter().collect();
+        symbols.sort_by_key(|(name, _)| *name);
+        for (name, settings) in symbols {
+            problems.extend(settings.validate(name));
+        }
+        problems
+    }
+}
+
+/// The user config file, in the platform's config directory (e.g. ~/.config/home-trader/config.json).
+/// A config.toml there is used instead, and kept in TOML when the settings dialog saves it.
+pub fn user_config_path() -> Option<PathBuf> {
+    let dir = dirs::config_dir()?.join("home-trader");
+    let toml = dir.join("config.toml");
+    Some(if toml.exists() { toml } else { dir.join("config.json") })
+}
+
+/// Reads a config file as TOML or JSON, by its extension (see `engine::config::settings::is_toml`).
+fn read_config_file(path: &Path) -> Result<serde_json::Value, anyhow::Error> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(if is_toml(path) { toml::from_str(&contents)? } else { serde_json::from_str(&contents)? })
+}
+
+/// The default config with the user config file merged over it, read as `T`. Keys missing from the
+/// user file keep their defaults; an unreadable or invalid user file is ignored.
+fn load_merged<T: DeserializeOwned>() -> Result<T, anyhow::Error> {
+    let mut config = serde_json::from_str::<serde_json::Value>(include_str!("../../assets/config/default.json"))?;
+    let Some(path) = user_config_path().filter(|path| path.exists()) else {
+        return Ok(serde_json::from_value(config)?);
+    };
+    let user_settings = read_config_file(&path);
+    match user_settings {
+        Ok(user_settings) => {
+            let defaults = config.clone();
+            merge_json(&mut config, user_settings);
+            serde_json::from_value(config).or_else(|e| {
+                tracing::warn!("Ignoring invalid user config {}: {}", path.display(), e);
+                Ok(serde_json::from_value(defaults)?)
+            })
+        }
+        Err(e) => {
+            tracing::warn!("Ignoring unreadable user config {}: {}", path.display(), e);
+            Ok(serde_json::from_value(config)?)
+        }
+    }
+}
+
+/// Lays `overrides` over `base`, object by object; other values are replaced.
+fn merge_json(base: &mut serde_json::Value, overrides: serde_json::Value) {
+    match (base, overrides) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+/// "#rgb" or "#rrggbb", the forms the chart and color pickers take.
+fn is_hex_color(color: &str) -> bool {
+    color.strip_prefix('#').is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
 }
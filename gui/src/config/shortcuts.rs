@@ -0,0 +1,162 @@
+// Keyboard shortcuts: the actions of the `shortcuts` config section, key combinations parsed from
+// strings like "Ctrl+P", and conflict detection for the shortcut editor.
+use std::fmt;
+
+use super::Shortcuts;
+
+/// A key with its modifiers, e.g. "Ctrl+Shift+P". Letters are kept uppercase; Shift is dropped for
+/// symbols, as it is part of typing them on some layouts (e.g. "+").
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyCombo {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub key: String,
+}
+
+impl KeyCombo {
+    pub fn new(ctrl: bool, shift: bool, alt: bool, key: &str) -> Self {
+        let mut chars = key.chars();
+        let symbol = matches!((chars.next(), chars.next()), (Some(c), None) if !c.is_alphanumeric());
+        let key = if key.chars().count() == 1 { key.to_uppercase() } else { key.to_string() };
+        KeyCombo { ctrl, shift: shift && !symbol, alt, key }
+    }
+
+    /// Parses a binding such as "Ctrl+P", "Ctrl++" or "Ctrl+Shift+F5"; `None` without a key.
+    pub fn parse(binding: &str) -> Option<Self> {
+        let binding = binding.trim();
+        // The key itself may be "+" (as in "Ctrl++"), so it is whatever follows the last separator
+        let (modifiers, key) = match binding.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None => binding.rsplit_once('+').unwrap_or(("", binding)),
+        };
+        if key.is_empty() {
+            return None;
+        }
+        let (mut ctrl, mut shift, mut alt) = (false, false, false);
+        for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" | "cmd" => ctrl = true,
+                "shift" => shift = true,
+                "alt" | "option" => alt = true,
+                _ => return None,
+            }
+        }
+        Some(KeyCombo::new(ctrl, shift, alt, key))
+    }
+
+    /// A modifier pressed on its own does not make a combination.
+    pub fn is_modifier_only(&self) -> bool {
+        matches!(self.key.as_str(), "Control" | "Shift" | "Alt" | "Meta")
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (pressed, name) in [(self.ctrl, "Ctrl+"), (self.shift, "Shift+"), (self.alt, "Alt+")] {
+            if pressed {
+                f.write_str(name)?;
+            }
+        }
+        f.write_str(&self.key)
+    }
+}
+
+/// What a shortcut does; one per field of the `shortcuts` config section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutAction {
+    CommandPalette,
+    LoadCsv,
+    SaveProject,
+    Exit,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    RerunLastCommand,
+    Undo,
+    Redo,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 10] = [
+        ShortcutAction::CommandPalette,
+        ShortcutAction::LoadCsv,
+        ShortcutAction::SaveProject,
+        ShortcutAction::Exit,
+        ShortcutAction::ZoomIn,
+        ShortcutAction::ZoomOut,
+        ShortcutAction::ResetZoom,
+        ShortcutAction::RerunLastCommand,
+        ShortcutAction::Undo,
+        ShortcutAction::Redo,
+    ];
+
+    /// What the action does, in the current language.
+    pub fn label(self) -> String {
+        t!(match self {
+            ShortcutAction::CommandPalette => "shortcut-command-palette",
+            ShortcutAction::LoadCsv => "shortcut-load-csv",
+            ShortcutAction::SaveProject => "shortcut-save-project",
+            ShortcutAction::Exit => "shortcut-exit",
+            ShortcutAction::ZoomIn => "shortcut-zoom-in",
+            ShortcutAction::ZoomOut => "shortcut-zoom-out",
+            ShortcutAction::ResetZoom => "shortcut-reset-zoom",
+            ShortcutAction::RerunLastCommand => "shortcut-rerun-last-command",
+            ShortcutAction::Undo => "shortcut-undo",
+            ShortcutAction::Redo => "shortcut-redo",
+        })
+    }
+
+    /// The binding of this action in `shortcuts`.
+    pub fn binding(self, shortcuts: &Shortcuts) -> &str {
+        match self {
+            ShortcutAction::CommandPalette => &shortcuts.command_palette,
+            ShortcutAction::LoadCsv => &shortcuts.load_csv,
+            ShortcutAction::SaveProject => &shortcuts.save_project,
+            ShortcutAction::Exit => &shortcuts.exit,
+            ShortcutAction::ZoomIn => &shortcuts.zoom_in,
+            ShortcutAction::ZoomOut => &shortcuts.zoom_out,
+            ShortcutAction::ResetZoom => &shortcuts.reset_zoom,
+            ShortcutAction::RerunLastCommand => &shortcuts.rerun_last_command,
+            ShortcutAction::Undo => &shortcuts.undo,
+            ShortcutAction::Redo => &shortcuts.redo,
+        }
+    }
+
+    pub fn set_binding(self, shortcuts: &mut Shortcuts, binding: String) {
+        let field = match self {
+            ShortcutAction::CommandPalette => &mut shortcuts.command_palette,
+            ShortcutAction::LoadCsv => &mut shortcuts.load_csv,
+            ShortcutAction::SaveProject => &mut shortcuts.save_project,
+            ShortcutAction::Exit => &mut shortcuts.exit,
+            ShortcutAction::ZoomIn => &mut shortcuts.zoom_in,
+            ShortcutAction::ZoomOut => &mut shortcuts.zoom_out,
+            ShortcutAction::ResetZoom => &mut shortcuts.reset_zoom,
+            ShortcutAction::RerunLastCommand => &mut shortcuts.rerun_last_command,
+            ShortcutAction::Undo => &mut shortcuts.undo,
+            ShortcutAction::Redo => &mut shortcuts.redo,
+        };
+        *field = binding;
+    }
+}
+
+impl Shortcuts {
+    /// The action bound to `combo`, if any (the first one, should several share it).
+    pub fn action_for(&self, combo: &KeyCombo) -> Option<ShortcutAction> {
+        ShortcutAction::ALL.into_iter().find(|action| KeyCombo::parse(action.binding(self)).as_ref() == Some(combo))
+    }
+
+    /// Actions other than `action` already bound to `combo`.
+    pub fn conflicts_with(&self, action: ShortcutAction, combo: &KeyCombo) -> Vec<ShortcutAction> {
+        ShortcutAction::ALL.into_iter()
+            .filter(|other| *other != action && KeyCombo::parse(other.binding(self)).as_ref() == Some(combo))
+            .collect()
+    }
+
+    /// Actions sharing their binding with another action, e.g. after editing the config file by hand.
+    pub fn conflicting_actions(&self) -> Vec<ShortcutAction> {
+        ShortcutAction::ALL.into_iter()
+            .filter(|action| KeyCombo::parse(action.binding(self)).is_some_and(|combo| !self.conflicts_with(*action, &combo).is_empty()))
+            .collect()
+    }
+}
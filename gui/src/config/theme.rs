@@ -1,6 +1,8 @@
 // Theme specific configurations (colors, fonts, styles)
 use serde::{Deserialize, Serialize};
 
+use crate::state::app_state::Theme;
+
 // This file would define structs for theme properties,
 // which can then be loaded or selected in the application state.
 // The main AppConfig might point to a specific theme file or embed theme details.
@@ -13,6 +15,9 @@ pub struct ThemePalette {
     pub primary: String,
     pub secondary: String,
     pub accent: String,
+    pub surface: String, // Panels and chart plot areas, drawn over the background
+    pub border: String,
+    pub muted: String, // Secondary text, e.g. labels and hints
     // Chart specific colors might also go here or in ChartConfig
     pub chart_bullish: String,
     pub chart_bearish: String,
@@ -20,6 +25,20 @@ pub struct ThemePalette {
 }
 
 impl ThemePalette {
+    pub fn for_theme(theme: &Theme) -> Self {
+        match theme {
+            Theme::Dark => Self::default_dark(),
+            Theme::Light => Self::default_light(),
+        }
+    }
+
+    /// This palette with the candle colors of the `chart.candle` config section, as set in the settings dialog.
+    pub fn with_candle_colors(mut self, candle: &super::CandleStyle) -> Self {
+        self.chart_bullish = candle.bullish_color.clone();
+        self.chart_bearish = candle.bearish_color.clone();
+        self
+    }
+
     pub fn default_dark() -> Self {
         // Values could come from the AppConfig defaults
         Self {
@@ -28,6 +47,9 @@ impl ThemePalette {
             primary: "#007acc".to_string(),
             secondary: "#565656".to_string(),
             accent: "#26a69a".to_string(),
+            surface: "#2a2a2a".to_string(),
+            border: "#444444".to_string(),
+            muted: "#aaaaaa".to_string(),
             chart_bullish: "#26a69a".to_string(), // from default.json
             chart_bearish: "#ef5350".to_string(), // from default.json
         }
@@ -40,20 +62,15 @@ impl ThemePalette {
             primary: "#007acc".to_string(),
             secondary: "#e0e0e0".to_string(),
             accent: "#009688".to_string(),
+            surface: "#f5f5f5".to_string(),
+            border: "#cccccc".to_string(),
+            muted: "#666666".to_string(),
             chart_bullish: "#4caf50".to_string(),
             chart_bearish: "#f44336".to_string(),
         }
     }
 }
 
-// The AppState.current_theme (Dark/Light enum) could be used to select
-// which ThemePalette is currently active.
-// Components could then refer to this active palette for their styling.
-// E.g., in Dioxus, this could be part of the shared state.
-// let app_state = use_shared_state::<AppState>(cx).unwrap();
-// let current_palette = if app_state.read().current_theme == Theme::Dark {
-//     ThemePalette::default_dark()
-// } else {
-//     ThemePalette::default_light()
-// };
-// ... style: "background-color: {current_palette.background};" ...
+// The active palette follows AppState.current_theme: components read it with
+// `app_state.read().palette()` and style themselves from it, e.g.
+// style: "background-color: {palette.background};"
@@ -0,0 +1,113 @@
+// Translations of the GUI's text (Fluent catalogs in assets/i18n), in en-US and pt-BR.
+// Text is looked up with `t!("message-id")` or `t!("message-id", name = value, ...)` in the current
+// language, which can change at runtime: components re-render through `AppState::set_language`.
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en-US")]
+    EnUs,
+    #[serde(rename = "pt-BR")]
+    PtBr,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::EnUs, Language::PtBr];
+
+    /// The language of a code such as "pt-BR" or "pt"; English for anything else.
+    pub fn from_code(code: &str) -> Self {
+        if code.to_ascii_lowercase().starts_with("pt") { Language::PtBr } else { Language::EnUs }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::EnUs => "en-US",
+            Language::PtBr => "pt-BR",
+        }
+    }
+
+    /// The language's own name, as listed in language pickers.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Language::EnUs => "English (US)",
+            Language::PtBr => "Português (Brasil)",
+        }
+    }
+
+    fn catalog(self) -> &'static str {
+        match self {
+            Language::EnUs => include_str!("../assets/i18n/en-US.ftl"),
+            Language::PtBr => include_str!("../assets/i18n/pt-BR.ftl"),
+        }
+    }
+}
+
+static CURRENT_LANGUAGE: AtomicU8 = AtomicU8::new(Language::EnUs as u8);
+
+pub fn current_language() -> Language {
+    Language::ALL[CURRENT_LANGUAGE.load(Ordering::Relaxed) as usize]
+}
+
+/// Switches the language `t!` translates to; see `AppState::set_language` to re-render the GUI too.
+pub fn set_language(language: Language) {
+    CURRENT_LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+/// One bundle per language, in `Language::ALL` order, built on first use.
+fn bundles() -> &'static [FluentBundle<FluentResource>] {
+    static BUNDLES: OnceLock<Vec<FluentBundle<FluentResource>>> = OnceLock::new();
+    BUNDLES.get_or_init(|| Language::ALL.into_iter().map(|language| {
+        let locale = language.code().parse().expect("language codes are valid locales");
+        let mut bundle = FluentBundle::new_concurrent(vec![locale]);
+        // Text goes into plain strings, so no bidi isolation marks around the arguments
+        bundle.set_use_isolating(false);
+        let resource = FluentResource::try_new(language.catalog().to_string()).unwrap_or_else(|(resource, errors)| {
+            tracing::error!("Errors in the {} catalog: {:?}", language.code(), errors);
+            resource
+        });
+        if let Err(errors) = bundle.add_resource(resource) {
+            tracing::error!("Duplicate messages in the {} catalog: {:?}", language.code(), errors);
+        }
+        bundle
+    }).collect())
+}
+
+fn format(language: Language, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+    let bundle = &bundles()[language as usize];
+    let pattern = bundle.get_message(id)?.value()?;
+    let mut errors = Vec::new();
+    let text = bundle.format_pattern(pattern, args, &mut errors).into_owned();
+    if !errors.is_empty() {
+        tracing::warn!("Errors formatting {} in {}: {:?}", id, language.code(), errors);
+    }
+    Some(text)
+}
+
+/// The message `id` in the current language, falling back to English and then to the id itself.
+pub fn translate(id: &str, args: Option<&FluentArgs>) -> String {
+    let language = current_language();
+    format(language, id, args)
+        .or_else(|| {
+            tracing::warn!("No {} translation for {}", language.code(), id);
+            format(Language::EnUs, id, args)
+        })
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Translates a message of the catalogs: `t!("status-offline")`, or with arguments
+/// `t!("error-load-csv", file = path, error = e)` (any `Display` value).
+macro_rules! t {
+    ($id:expr) => {
+        $crate::i18n::translate(&$id, None)
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value.to_string());)+
+        $crate::i18n::translate(&$id, Some(&args))
+    }};
+}
@@ -1,27 +1,36 @@
 #![allow(non_snake_case)]
 // use dioxus::prelude::*; // Unused import
-use dioxus_desktop::{Config, LogicalSize}; // Import Config and LogicalSize for window setup
+use dioxus_desktop::{Config, LogicalPosition, LogicalSize}; // Import Config and LogicalSize for window setup
 
 // Explicitly declare modules if app.rs is not automatically found as src/app.rs
 // If app.rs is indeed src/app.rs, this line might not be strictly needed
 // but doesn't hurt.
+#[macro_use]
+mod i18n; // First, so its `t!` macro is available to the other modules
 mod app;
 mod components;
 mod config;
 mod services;
 mod state;
 
+use state::session::{session_path, SessionState};
+
 fn main() {
+    // The window opens where it was left
+    let mut window = dioxus_desktop::WindowBuilder::new()
+        .with_title("Home Trader")
+        .with_inner_size(LogicalSize::new(800.0, 600.0));
+    if let Some(geometry) = SessionState::load(&session_path()).window {
+        window = window
+            .with_inner_size(LogicalSize::new(geometry.width, geometry.height))
+            .with_position(LogicalPosition::new(geometry.x, geometry.y));
+    }
     // Use the simplest launch function for Dioxus 0.5+
     // pub fn launch(root: fn() -> Element, platform_event_handlers: Vec<ExternalListener>, cfg: Config)
     // The middle argument seems to be for platform event handlers, which we don't have, so pass vec![].
     dioxus_desktop::launch::launch(
         app::App,
         vec![], // No platform event handlers for now
-        Config::default().with_window(
-            dioxus_desktop::WindowBuilder::new()
-                .with_title("Home Trader")
-                .with_inner_size(LogicalSize::new(800.0, 600.0)),
-        ),
+        Config::default().with_window(window),
     );
 }
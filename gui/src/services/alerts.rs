@@ -0,0 +1,57 @@
+// Engine alerts
+// Alerts set in the engine (a price crossing a level, an indicator crossing a threshold, a percent
+// move) are checked there against every candle loaded or replayed. The GUI lists the ones waiting
+// to fire and follows SubscribeAlerts to report each one as a notice when it fires.
+use dioxus::prelude::*;
+use engine::services::GrpcAlert;
+
+use crate::services::engine_client::EngineClient;
+use crate::state::app_state::AppState;
+
+/// What `alert` waits for, e.g. "RSI(14) above 70.00".
+pub fn alert_description(alert: &GrpcAlert) -> String {
+    match alert.kind.as_str() {
+        "INDICATOR" => {
+            let id = if alert.direction == "BELOW" { "alerts-indicator-below" } else { "alerts-indicator-above" };
+            t!(id, indicator = alert.indicator, period = alert.period.to_string(), level = format!("{:.2}", alert.level))
+        }
+        "PERCENT_MOVE" => t!("alerts-percent-move", percent = format!("{:+.1}", alert.level)),
+        _ => t!("alerts-price-cross", level = format!("{:.2}", alert.level)),
+    }
+}
+
+/// Fetches the alerts waiting to fire, then follows them firing unless that is done already.
+/// Following stops with the stream (e.g. the engine stopped); the next call starts it again,
+/// which the connection's health checks do once the engine answers.
+pub fn follow_alerts(app_state: UseSharedState<AppState>, client: &EngineClient) {
+    if app_state.read().following_alerts {
+        return;
+    }
+    app_state.write().following_alerts = true;
+    let mut client = client.clone();
+    spawn(async move {
+        match client.list_alerts().await {
+            Ok(alerts) => app_state.write().engine_alerts = alerts,
+            Err(e) => tracing::warn!("Failed to list the engine's alerts: {}", e),
+        }
+        match client.subscribe_alerts().await {
+            Ok(mut events) => {
+                tracing::info!("Following engine alerts");
+                while let Ok(Some(event)) = events.message().await {
+                    let Some(alert) = event.alert else { continue };
+                    tracing::info!(symbol = %alert.symbol, alert_id = %alert.alert_id, price = event.price, source = %event.source, "Engine alert fired");
+                    let mut app_state_writer = app_state.write();
+                    app_state_writer.engine_alerts.retain(|waiting| waiting.alert_id != alert.alert_id);
+                    let description = match alert.note.is_empty() {
+                        true => alert_description(&alert),
+                        false => format!("{} · {}", alert_description(&alert), alert.note),
+                    };
+                    app_state_writer.notice = Some(t!("notice-alert-fired", symbol = alert.symbol, alert = description, price = format!("{:.2}", event.price)));
+                }
+                tracing::info!("Engine alert stream ended");
+            }
+            Err(e) => tracing::warn!("Failed to follow engine alerts: {}", e),
+        }
+        app_state.write().following_alerts = false;
+    });
+}
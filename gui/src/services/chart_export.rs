@@ -0,0 +1,151 @@
+// Exporting the chart as an image
+// The chart is an SVG over an optional canvas (see components/chart/canvas.rs), so the webview puts
+// them together: the canvas becomes an embedded image of the SVG, and the legend (HTML) becomes text.
+// PNGs are rasterized there as well; the file is picked with a save dialog and written here.
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use dioxus::prelude::*;
+use std::path::PathBuf;
+
+use crate::state::app_state::AppState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartImageFormat {
+    Png,
+    Svg,
+}
+
+impl ChartImageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ChartImageFormat::Png => "png",
+            ChartImageFormat::Svg => "svg",
+        }
+    }
+}
+
+// Returns the SVG markup, or a PNG data URL, of the chart; null when no chart is shown.
+// The crosshair follows the pointer, so it is left out.
+const EXPORT_SCRIPT: &str = r#"
+    const ns = "http://www.w3.org/2000/svg";
+    const container = document.getElementById("candlestick-chart");
+    const svg = document.getElementById("candlestick-svg");
+    if (!container || !svg) return null;
+    const format = "__FORMAT__";
+    const width = svg.width.baseVal.value;
+    const height = svg.height.baseVal.value;
+    const background = getComputedStyle(container).backgroundColor;
+    const canvas = document.getElementById("candlestick-canvas");
+    const copy = svg.cloneNode(true);
+    copy.removeAttribute("id");
+    copy.removeAttribute("style");
+    copy.setAttribute("xmlns", ns);
+    copy.querySelectorAll(".crosshair").forEach((element) => element.remove());
+    // Text sizes are relative to the page's root font (see AppSettings::font_size), which the file lacks
+    const texts = svg.querySelectorAll("text");
+    copy.querySelectorAll("text").forEach((text, i) => text.setAttribute("font-size", getComputedStyle(texts[i]).fontSize));
+    const legend = container.querySelector(".chart-legend");
+    if (legend) {
+        for (const line of legend.children) {
+            const style = getComputedStyle(line);
+            const y = legend.offsetTop + line.offsetTop + line.offsetHeight * 0.8;
+            const text = document.createElementNS(ns, "text");
+            text.setAttribute("x", legend.offsetLeft + 4);
+            text.setAttribute("y", y);
+            text.setAttribute("fill", style.color);
+            text.setAttribute("font-size", style.fontSize);
+            text.setAttribute("font-weight", style.fontWeight);
+            text.textContent = line.textContent;
+            copy.appendChild(text);
+        }
+    }
+    if (format === "svg") {
+        const layers = [];
+        const rect = document.createElementNS(ns, "rect");
+        rect.setAttribute("width", width);
+        rect.setAttribute("height", height);
+        rect.setAttribute("fill", background);
+        layers.push(rect);
+        if (canvas) {
+            const image = document.createElementNS(ns, "image");
+            image.setAttribute("width", width);
+            image.setAttribute("height", height);
+            image.setAttribute("href", canvas.toDataURL("image/png"));
+            layers.push(image);
+        }
+        copy.prepend(...layers);
+        return new XMLSerializer().serializeToString(copy);
+    }
+    const markup = new XMLSerializer().serializeToString(copy);
+    const url = URL.createObjectURL(new Blob([markup], { type: "image/svg+xml" }));
+    const overlay = new Image();
+    try {
+        await new Promise((resolve, reject) => {
+            overlay.onload = resolve;
+            overlay.onerror = () => reject("the chart could not be rasterized");
+            overlay.src = url;
+        });
+    } finally {
+        URL.revokeObjectURL(url);
+    }
+    const ratio = window.devicePixelRatio || 1;
+    const output = document.createElement("canvas");
+    output.width = width * ratio;
+    output.height = height * ratio;
+    const ctx = output.getContext("2d");
+    ctx.scale(ratio, ratio);
+    ctx.fillStyle = background;
+    ctx.fillRect(0, 0, width, height);
+    if (canvas) ctx.drawImage(canvas, 0, 0, width, height);
+    ctx.drawImage(overlay, 0, 0, width, height);
+    return output.toDataURL("image/png");
+"#;
+
+/// The image file contents of the chart on screen.
+async fn render_chart(format: ChartImageFormat) -> Result<Vec<u8>> {
+    let script = EXPORT_SCRIPT.replace("__FORMAT__", format.extension());
+    let value = eval(&script).join().await.map_err(|e| anyhow!("{:?}", e))?;
+    let Some(image) = value.as_str() else { bail!(t!("error-no-chart")) };
+    match format {
+        ChartImageFormat::Svg => Ok(image.as_bytes().to_vec()),
+        ChartImageFormat::Png => {
+            let data = image.strip_prefix("data:image/png;base64,").context("The webview did not return a PNG")?;
+            Ok(base64::engine::general_purpose::STANDARD.decode(data)?)
+        }
+    }
+}
+
+/// Asks where to save the chart, then writes it there; `None` if the dialog was cancelled.
+async fn save_chart(format: ChartImageFormat, suggested_name: String) -> Result<Option<PathBuf>> {
+    let extension = format.extension();
+    let dialog = rfd::AsyncFileDialog::new()
+        .add_filter(extension.to_uppercase(), &[extension])
+        .set_file_name(format!("{}.{}", suggested_name, extension));
+    let Some(file) = dialog.save_file().await else { return Ok(None) };
+    let image = render_chart(format).await?;
+    let path = file.path().to_path_buf();
+    std::fs::write(&path, image).with_context(|| t!("error-write-file", path = path.display()))?;
+    Ok(Some(path))
+}
+
+/// Exports the current chart to an image file picked by the user, reporting the outcome as a notice or error.
+pub fn export_chart(app_state: UseSharedState<AppState>, format: ChartImageFormat) {
+    let Some(symbol) = app_state.read().current_symbol_display.clone() else {
+        app_state.write().error_message = Some(t!("error-no-chart"));
+        return;
+    };
+    spawn(async move {
+        match save_chart(format, symbol).await {
+            Ok(Some(path)) => {
+                tracing::info!("Exported the chart to {}", path.display());
+                app_state.write().notice = Some(t!("notice-chart-exported", path = path.display()));
+            }
+            Ok(None) => tracing::debug!("Chart export cancelled"),
+            Err(e) => {
+                let err_msg = t!("error-export-chart", error = format!("{:#}", e));
+                tracing::error!("{}", err_msg);
+                app_state.write().error_message = Some(err_msg);
+            }
+        }
+    });
+}
@@ -0,0 +1,97 @@
+// Config reloading
+// A new config, from the settings dialog or the user config file saved in an editor, is put into
+// effect at once as far as it can: the theme, language, chart colors, indicator defaults, text size
+// and engine connection. Where the preferences are kept is only read at launch, so changing it is
+// reported as needing a restart.
+use dioxus::prelude::*;
+use engine::config::watch::{next_change, watch_file};
+use std::path::Path;
+use tokio::sync::watch;
+
+use crate::components::chart::style::ChartType;
+use crate::config::{user_config_path, AppConfig};
+use crate::i18n::Language;
+use crate::services::connection::reconnect_now;
+use crate::state::app_state::{AppState, Theme};
+use crate::state::preferences::{save_language, save_theme, UserPreferences};
+
+/// Replaces the config in effect with `new_config`; the changed settings that only take effect after
+/// a restart are returned, named as in the config file.
+pub fn apply_config(app_state: UseSharedState<AppState>, app_config: UseSharedState<AppConfig>, new_config: AppConfig) -> Vec<&'static str> {
+    let current = app_config.read().clone();
+    if new_config.app.theme != current.app.theme {
+        let theme = Theme::from_name(&new_config.app.theme);
+        app_state.write().set_theme(theme.clone());
+        save_theme(&new_config.app.preferences_file, theme);
+    }
+    if new_config.app.language != current.app.language {
+        let language = Language::from_code(&new_config.app.language);
+        app_state.write().set_language(language);
+        save_language(&new_config.app.preferences_file, language);
+    }
+    if new_config.chart.chart_type != current.chart.chart_type {
+        app_state.write().chart_type = ChartType::from_name(&new_config.chart.chart_type);
+    }
+    let mut restart_required = Vec::new();
+    if new_config.app.preferences_file != current.app.preferences_file {
+        restart_required.push("app.preferences_file");
+    }
+    let engine_changed = new_config.engine != current.engine;
+    *app_config.write() = new_config;
+    if engine_changed {
+        reconnect_now();
+    }
+    restart_required
+}
+
+/// Reloads the user config file whenever it is saved, reporting the outcome as a notice or error.
+/// Runs for as long as the GUI does.
+pub async fn watch_user_config(app_state: UseSharedState<AppState>, app_config: UseSharedState<AppConfig>) {
+    let Some(path) = user_config_path() else { return };
+    // The directory is created by the first save of the settings dialog, which may not have happened yet
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let (changed, mut changes) = watch::channel(());
+    let _watcher = match watch_file(&path, changed) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            tracing::warn!("Cannot watch the user config file {}: {}", path.display(), e);
+            return;
+        }
+    };
+    while next_change(&mut changes).await.is_some() {
+        reload_user_config(app_state.clone(), app_config.clone(), &path);
+    }
+}
+
+fn reload_user_config(app_state: UseSharedState<AppState>, app_config: UseSharedState<AppConfig>, path: &Path) {
+    let mut new_config = match AppConfig::reload() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::warn!("Not reloading {}: {}", path.display(), e);
+            app_state.write().error_message = Some(t!("error-settings-reload", path = path.display(), error = e));
+            return;
+        }
+    };
+    let problems = new_config.validate();
+    if !problems.is_empty() {
+        app_state.write().error_message = Some(t!("error-settings-reload", path = path.display(), error = problems.join(" ")));
+        return;
+    }
+    // Shortcuts rebound in the shortcut editor still win over the configured ones, as at launch
+    if let Some(shortcuts) = UserPreferences::load(&new_config.app.preferences_file).shortcuts {
+        new_config.shortcuts = shortcuts;
+    }
+    // Saving from the settings dialog lands here too, with the config already in effect
+    if serde_json::to_value(&new_config).ok() == serde_json::to_value(&*app_config.read()).ok() {
+        return;
+    }
+    tracing::info!("Reloaded the settings from {}", path.display());
+    let restart_required = apply_config(app_state.clone(), app_config, new_config);
+    app_state.write().notice = Some(if restart_required.is_empty() {
+        t!("notice-settings-reloaded", path = path.display())
+    } else {
+        t!("notice-settings-reloaded-restart", path = path.display(), settings = restart_required.join(", "))
+    });
+}
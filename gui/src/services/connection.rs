@@ -0,0 +1,232 @@
+// Engine connection
+// Connects to the engine, then health-checks it every `engine.health_check_interval_secs`, so the
+// status bar tells whether it is connected, reconnecting or offline instead of calls failing silently.
+// Lost connections are retried with exponential backoff, and live candle streams are resumed.
+// Changing the engine settings (in the settings dialog) reconnects with the new ones. With
+// `engine.embedded` the engine runs inside the GUI instead, and is connected to in memory; with
+// `engine.spawn` it is started as a child process (see `engine_process`) and waited for.
+use dioxus::prelude::*;
+use engine::services::embedded::EmbeddedEngine;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+use crate::config::{AppConfig, EngineConnSettings};
+use crate::services::alerts::follow_alerts;
+use crate::services::engine_client::EngineClient;
+use crate::services::engine_process;
+use crate::services::live_updates::resubscribe_live_candles;
+use crate::services::session::restore_last_chart;
+use crate::state::app_state::{AppState, ConnectionState};
+
+/// Failed health checks of a connected engine reported as reconnecting; after them the client is
+/// dropped and connections are attempted from scratch, reported as offline.
+const RECONNECT_ATTEMPTS: u32 = 3;
+
+/// Wait before retrying after the first failure; it doubles with every failure in a row, up to `MAX_RETRY_DELAY`.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Exponential backoff: the wait before retrying after `failures` failures in a row.
+fn retry_delay(failures: u32) -> Duration {
+    INITIAL_RETRY_DELAY.saturating_mul(2u32.saturating_pow(failures.saturating_sub(1))).min(MAX_RETRY_DELAY)
+}
+
+/// How long a spawned engine gets to start serving, and how often it is checked meanwhile.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+const STARTUP_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Connects to the engine at `settings`' address or Unix socket.
+async fn connect_remote(settings: &EngineConnSettings) -> anyhow::Result<EngineClient> {
+    let compression = settings.compression;
+    match settings.unix_socket.clone() {
+        #[cfg(unix)]
+        Some(path) => EngineClient::new_unix(path, compression).await,
+        _ => EngineClient::new(format!("http://{}:{}", settings.host, settings.port), compression).await,
+    }.and_then(|client| client.with_namespace(settings.namespace.clone()))
+        .and_then(|client| client.with_auth_token(settings.auth_token.clone()))
+}
+
+/// Connects to an engine just spawned once it serves; fails if it exits or takes over `STARTUP_TIMEOUT`.
+async fn wait_until_serving(settings: &EngineConnSettings) -> anyhow::Result<EngineClient> {
+    let started = Instant::now();
+    loop {
+        engine_process::check_running()?;
+        if let Ok(mut client) = connect_remote(settings).await {
+            if client.is_ready().await.unwrap_or(false) {
+                tracing::info!(elapsed = ?started.elapsed(), "The spawned engine is serving.");
+                return Ok(client);
+            }
+        }
+        if started.elapsed() >= STARTUP_TIMEOUT {
+            anyhow::bail!(t!("engine-process-timed-out", seconds = STARTUP_TIMEOUT.as_secs()));
+        }
+        tokio::time::sleep(STARTUP_CHECK_INTERVAL).await;
+    }
+}
+
+/// Connects as `settings` say; an embedded engine is started in `embedded` the first time it is
+/// needed, and a spawned one whenever it is not running.
+async fn connect(settings: &EngineConnSettings, embedded: &mut Option<EmbeddedEngine>) -> anyhow::Result<EngineClient> {
+    if settings.embedded {
+        if embedded.is_none() {
+            *embedded = Some(EmbeddedEngine::start(AppConfig::engine_settings()?).await?);
+        }
+        let engine = embedded.as_ref().expect("the embedded engine was just started");
+        return EngineClient::embedded(engine).with_namespace(settings.namespace.clone());
+    }
+    if settings.spawn && engine_process::ensure_running(&settings.executable)? {
+        return wait_until_serving(settings).await;
+    }
+    connect_remote(settings).await
+}
+
+/// How long a connection test waits for the engine to answer.
+const TEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Connects with `settings` and health-checks the engine once, without touching the current connection.
+pub async fn test_connection(settings: &EngineConnSettings) -> anyhow::Result<()> {
+    // Nothing to reach: the embedded engine runs in the GUI, and a second one would share its account file
+    if settings.embedded {
+        return Ok(());
+    }
+    let check = async {
+        // A spawned engine is only started by `maintain_connection`
+        let mut client = connect_remote(settings).await?;
+        if !client.is_ready().await? {
+            anyhow::bail!(t!("connection-not-serving"));
+        }
+        Ok(())
+    };
+    tokio::time::timeout(TEST_TIMEOUT, check).await
+        .map_err(|_| anyhow::anyhow!(t!("connection-timed-out", seconds = TEST_TIMEOUT.as_secs())))?
+}
+
+/// Wakes `maintain_connection` up, e.g. after the engine settings changed.
+fn wake_up() -> &'static Notify {
+    static WAKE_UP: OnceLock<Notify> = OnceLock::new();
+    WAKE_UP.get_or_init(Notify::new)
+}
+
+/// Checks the connection right away instead of at the next check or retry, reconnecting if the
+/// engine settings changed.
+pub fn reconnect_now() {
+    wake_up().notify_one();
+}
+
+/// What the GUI asks a newly (re)connected engine: its info, checked against our API version, and its symbols.
+async fn on_connected(app_state: UseSharedState<AppState>, client: &mut EngineClient) {
+    match client.engine_info().await {
+        Ok(info) => {
+            tracing::info!(version = %info.version, api_version = info.api_version, "Trading engine info received.");
+            if info.api_version != engine::services::API_VERSION {
+                app_state.write().error_message = Some(t!(
+                    "error-api-version",
+                    version = info.version, api_version = info.api_version, expected = engine::services::API_VERSION
+                ));
+            }
+            app_state.write().engine_info = Some(info);
+        }
+        Err(e) => tracing::warn!("Failed to get trading engine info: {}", e),
+    }
+    match client.list_symbols().await {
+        Ok(symbols) => app_state.write().engine_symbols = symbols,
+        Err(e) => tracing::warn!("Failed to list the engine's symbols: {}", e),
+    }
+}
+
+/// Connects to the engine and keeps `AppState::connection` current for as long as the app runs.
+/// A healthy engine is checked every `health_check_interval_secs`; failed connections and checks are
+/// retried with exponential backoff instead. Once the engine answers again, the live candle streams
+/// (and the alert stream) that ended meanwhile are subscribed again.
+pub async fn maintain_connection(app_state: UseSharedState<AppState>, engine_client: UseSharedState<Option<EngineClient>>, app_config: UseSharedState<AppConfig>) {
+    let mut settings = app_config.read().engine.clone();
+    let mut embedded = None;
+    let mut failures = 0;
+    loop {
+        let current_settings = app_config.read().engine.clone();
+        if current_settings != settings {
+            tracing::info!(host = %current_settings.host, port = current_settings.port, embedded = current_settings.embedded, "Engine settings changed; reconnecting.");
+            if (current_settings.spawn, &current_settings.executable) != (settings.spawn, &settings.executable) {
+                // Stops the spawned engine, if there was one; it is started again below when still wanted
+                let _ = tokio::task::spawn_blocking(engine_process::stop).await;
+            }
+            settings = current_settings;
+            if !settings.embedded {
+                // Stops the embedded engine, if there was one
+                embedded = None;
+            }
+            failures = 0;
+            *engine_client.write() = None;
+            app_state.write().connection = ConnectionState::Connecting;
+        }
+        let interval = Duration::from_secs(settings.health_check_interval_secs.max(1));
+        let client = engine_client.read().as_ref().cloned();
+        let had_client = client.is_some();
+        let healthy = match client {
+            None => match connect(&settings, &mut embedded).await {
+                Ok(mut client) => {
+                    tracing::info!("Successfully connected to trading engine.");
+                    on_connected(app_state.clone(), &mut client).await;
+                    restore_last_chart(app_state.clone(), &client);
+                    *engine_client.write() = Some(client);
+                    failures = 0;
+                    // Readiness is checked right away below, on the next pass
+                    continue;
+                }
+                Err(e) => {
+                    if app_state.read().connection == ConnectionState::Connecting {
+                        let error_msg = t!("error-connect", error = e);
+                        tracing::error!("{}", error_msg);
+                        app_state.write().error_message = Some(error_msg);
+                    } else {
+                        tracing::warn!(attempt = failures + 1, "Failed to connect to trading engine: {}", e);
+                    }
+                    false
+                }
+            },
+            Some(mut client) => match client.is_ready().await {
+                Ok(true) => {
+                    if app_state.read().connection != ConnectionState::Connected && failures > 0 {
+                        tracing::info!("Trading engine is back.");
+                        on_connected(app_state.clone(), &mut client).await;
+                    }
+                    resubscribe_live_candles(app_state.clone(), &client);
+                    follow_alerts(app_state.clone(), &client);
+                    true
+                }
+                // A reachable engine may still be starting up or shutting down
+                Ok(false) => {
+                    tracing::warn!("Trading engine is reachable but not serving.");
+                    false
+                }
+                Err(e) => {
+                    tracing::warn!("Trading engine health check failed: {}", e);
+                    false
+                }
+            },
+        };
+        let (state, wait) = if healthy {
+            failures = 0;
+            (ConnectionState::Connected, interval)
+        } else {
+            failures += 1;
+            let delay = retry_delay(failures);
+            let retry_in_secs = delay.as_secs();
+            if had_client && failures <= RECONNECT_ATTEMPTS {
+                (ConnectionState::Reconnecting { attempt: failures, retry_in_secs }, delay)
+            } else {
+                // Give up on the channel; a new one is connected at the next attempt
+                *engine_client.write() = None;
+                (ConnectionState::Offline { retry_in_secs }, delay)
+            }
+        };
+        if app_state.read().connection != state {
+            app_state.write().connection = state;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = wake_up().notified() => {}
+        }
+    }
+}
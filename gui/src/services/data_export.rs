@@ -0,0 +1,80 @@
+// Exporting the data on the chart
+// The engine writes the visible candles and the values of the chart's indicators as CSV or JSON
+// (ExportData); the file is picked with a save dialog and written here.
+use anyhow::{Context, Result};
+use dioxus::prelude::*;
+use engine::services::{ExportDataRequest, ExportedIndicator};
+use std::path::PathBuf;
+
+use crate::components::chart::indicators::indicator_type;
+use crate::services::engine_client::EngineClient;
+use crate::state::app_state::AppState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Csv,
+    Json,
+}
+
+impl DataFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            DataFormat::Csv => "csv",
+            DataFormat::Json => "json",
+        }
+    }
+}
+
+/// The export of the chart of `symbol` as shown: its visible window, or all its candles until the
+/// chart is panned or zoomed, with its indicators.
+fn visible_data_request(app_state: &AppState, symbol: &str, format: DataFormat) -> ExportDataRequest {
+    let view = app_state.visible_view.as_ref().filter(|view| view.symbol == symbol);
+    let indicators = app_state.current_indicators_display.iter()
+        .map(|indicator| ExportedIndicator { indicator_type: indicator_type(&indicator.name), parameters: indicator.parameters.to_string() })
+        .collect();
+    ExportDataRequest {
+        symbol: symbol.to_string(),
+        from_timestamp: view.map(|view| view.first.timestamp_millis()),
+        to_timestamp: view.map(|view| view.last.timestamp_millis()),
+        indicators,
+        format: format.extension().to_uppercase(),
+        ..Default::default()
+    }
+}
+
+/// Asks where to save the data, then has the engine export it there; `None` if the dialog was cancelled.
+async fn save_data(mut client: EngineClient, request: ExportDataRequest, format: DataFormat) -> Result<Option<(PathBuf, u32)>> {
+    let extension = format.extension();
+    let dialog = rfd::AsyncFileDialog::new()
+        .add_filter(extension.to_uppercase(), &[extension])
+        .set_file_name(format!("{}.{}", request.symbol, extension));
+    let Some(file) = dialog.save_file().await else { return Ok(None) };
+    let response = client.export_data(request).await?;
+    let path = file.path().to_path_buf();
+    std::fs::write(&path, response.contents).with_context(|| t!("error-write-file", path = path.display()))?;
+    Ok(Some((path, response.candles_exported)))
+}
+
+/// Exports the candles and indicators visible on the chart to a file picked by the user, reporting
+/// the outcome as a notice or error.
+pub fn export_visible_data(app_state: UseSharedState<AppState>, client: EngineClient, format: DataFormat) {
+    let Some(symbol) = app_state.read().current_symbol_display.clone() else {
+        app_state.write().error_message = Some(t!("error-no-chart"));
+        return;
+    };
+    let request = visible_data_request(&app_state.read(), &symbol, format);
+    spawn(async move {
+        match save_data(client, request, format).await {
+            Ok(Some((path, candles))) => {
+                tracing::info!("Exported {} candles of {} to {}", candles, symbol, path.display());
+                app_state.write().notice = Some(t!("notice-data-exported", count = candles, path = path.display()));
+            }
+            Ok(None) => tracing::debug!("Data export cancelled"),
+            Err(e) => {
+                let err_msg = t!("error-export-data", symbol = symbol, error = format!("{:#}", e));
+                tracing::error!("{}", err_msg);
+                app_state.write().error_message = Some(err_msg);
+            }
+        }
+    });
+}
@@ -12,10 +12,87 @@ use anyhow::Result;
 use engine::services::{
     TradingEngineClient,
     LoadCsvRequest, MarketDataRequest, IndicatorRequest, // ProtoCandle has been aliased
+    SubscribeCandlesRequest, GrpcCandleUpdate, ExportDataRequest, ExportDataResponse,
+    EngineInfoRequest, EngineInfo, ListSymbolsRequest, MarketDataResponse, ProtoCandle, GrpcTimeFrame,
+    StartReplayRequest, ReplayControlRequest, StepReplayRequest, ReplaySpeedRequest, ReplayStatus,
+    SubmitJobRequest, JobRequest,
+    TradeHistoryRequest, GrpcExecution, BacktestRequest, BacktestResponse, GrpcTradeRecord,
+    TradeRequest, TradeResponse,
+    CreateAlertRequest, AlertRequest, ListAlertsRequest, SubscribeAlertsRequest, GrpcAlert, GrpcAlertEvent,
     // MarketDataResponse, LoadCsvResponse, IndicatorResponse, // Response types might be needed for full implementation
 };
-use shared::models::Candle as SharedCandle; // Alias to avoid confusion if ProtoCandle is brought in without alias
+use shared::models::{to_price, Candle as SharedCandle, Price, OrderSide, TimeFrame, Trade, TradeDirection}; // Alias to avoid confusion if ProtoCandle is brought in without alias
+use engine::config::settings::Compression;
+use engine::services::embedded::EmbeddedEngine;
+use engine::services::error_details::ErrorDetails;
+use shared::errors::ErrorCode;
+use engine::services::request_id::REQUEST_ID_HEADER;
+use engine::services::trading_service::NAMESPACE_HEADER;
+use engine::services::auth::AUTHORIZATION_HEADER;
+use engine::services::generated::{job_result::Result as JobOutput, submit_job_request::Job};
+use tonic::codec::CompressionEncoding;
 use tonic::transport::Channel;
+#[cfg(unix)]
+use tonic::transport::{Endpoint, Uri};
+use tonic_health::pb::{health_check_response::ServingStatus, health_client::HealthClient, HealthCheckRequest};
+
+/// A failed engine call: the engine's message plus the structured details it sent, e.g. the
+/// invalid request fields or the bad line of a CSV file. Displayed one fact per line.
+#[derive(Debug)]
+pub struct EngineCallError {
+    pub code: tonic::Code,
+    pub message: String,
+    /// Finds the call in the engine log.
+    pub request_id: Option<String>,
+    pub details: Option<ErrorDetails>,
+}
+
+impl std::fmt::Display for EngineCallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.code, self.message)?;
+        if let Some(id) = &self.request_id {
+            write!(f, " (request {})", id)?;
+        }
+        let Some(details) = &self.details else { return Ok(()) };
+        for violation in &details.field_violations {
+            write!(f, "\n{}: {}", violation.field, violation.description)?;
+        }
+        if let (Some(line), Some(column)) = (details.metadata.get("line"), details.metadata.get("column")) {
+            write!(f, "\nCheck line {} of the file, column '{}'", line, column)?;
+        }
+        if let Some(delay) = details.retry_after {
+            write!(f, "\nTry again in {:.1} s", delay.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for EngineCallError {}
+
+impl EngineCallError {
+    /// What went wrong, when the engine said; `None` for errors from older engines or the transport.
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        self.details.as_ref().and_then(ErrorDetails::code)
+    }
+}
+
+/// The engine's error code for a failed call, if `error` is one.
+pub fn engine_error_code(error: &anyhow::Error) -> Option<ErrorCode> {
+    error.downcast_ref::<EngineCallError>().and_then(EngineCallError::error_code)
+}
+
+/// Turns an engine error into an `EngineCallError` naming the failed call's request ID.
+/// Errors from a stream's trailers carry no metadata: pass the ID from the response headers
+/// as `request_id`.
+fn rpc_error(status: tonic::Status, request_id: Option<&str>) -> anyhow::Error {
+    let request_id = status.metadata().get(REQUEST_ID_HEADER).and_then(|id| id.to_str().ok()).or(request_id);
+    anyhow::Error::new(EngineCallError {
+        code: status.code(),
+        message: status.message().to_string(),
+        request_id: request_id.map(str::to_string),
+        details: ErrorDetails::from_status(&status),
+    })
+}
 
 // For now, let's define a struct and placeholder methods.
 // The actual gRPC client setup will be more involved.
@@ -23,54 +100,144 @@ use tonic::transport::Channel;
 #[derive(Clone)] // Add Clone derive
 pub struct EngineClient {
     client: TradingEngineClient<Channel>,
+    health: HealthClient<Channel>, // grpc.health.v1 on the same connection
+    namespace: Option<tonic::metadata::MetadataValue<tonic::metadata::Ascii>>, // Sent as x-namespace with every trading call
+    authorization: Option<tonic::metadata::MetadataValue<tonic::metadata::Ascii>>, // "Bearer <token>", for an engine with `auth_token` set
     // endpoint: String, // No longer needed if client is stored directly
 }
 
 impl EngineClient {
-    pub async fn new(endpoint: String) -> Result<Self> {
+    pub async fn new(endpoint: String, compression: Compression) -> Result<Self> {
         // Establish gRPC connection by first creating a channel
         let channel = Channel::from_shared(endpoint)
-            .map_err(|e| anyhow::anyhow!("Failed to create URI for gRPC channel: {}", e))?
+            .map_err(|e| anyhow::anyhow!(t!("error-engine-uri", error = e)))?
             .connect()
             .await?;
-        let client = TradingEngineClient::new(channel);
-        Ok(Self { client })
+        Ok(Self::from_channel(channel, compression))
+    }
+
+    /// Connects through the engine's Unix domain socket (its `unix_socket` setting) instead of TCP.
+    #[cfg(unix)]
+    pub async fn new_unix(path: String, compression: Compression) -> Result<Self> {
+        // Tonic needs a URI, but every connection goes to the socket.
+        let channel = Endpoint::from_static("http://[::]:50051")
+            .connect_with_connector(tower::service_fn(move |_: Uri| tokio::net::UnixStream::connect(path.clone())))
+            .await?;
+        Ok(Self::from_channel(channel, compression))
+    }
+
+    /// Talks to an engine running inside the GUI (the `engine.embedded` setting).
+    pub fn embedded(engine: &EmbeddedEngine) -> Self {
+        // In-memory pipes gain nothing from compression
+        Self::from_channel(engine.channel(), Compression::None)
+    }
+
+    fn from_channel(channel: Channel, compression: Compression) -> Self {
+        let mut client = TradingEngineClient::new(channel.clone())
+            .accept_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Zstd);
+        if let Some(encoding) = compression.encoding() {
+            client = client.send_compressed(encoding);
+        }
+        let health = HealthClient::new(channel);
+        Self { client, health, namespace: None, authorization: None }
+    }
+
+    /// Works in the engine namespace `namespace` (see `NAMESPACE_HEADER`) instead of the default one.
+    pub fn with_namespace(self, namespace: Option<String>) -> Result<Self> {
+        let namespace = namespace
+            .filter(|name| !name.trim().is_empty())
+            .map(|name| name.trim().parse())
+            .transpose()
+            .map_err(|e| anyhow::anyhow!(t!("error-engine-namespace", error = e)))?;
+        Ok(Self { namespace, ..self })
+    }
+
+    /// Sends `token` with every trading call, for an engine requiring one (its `auth_token` setting).
+    pub fn with_auth_token(self, token: Option<String>) -> Result<Self> {
+        let authorization = token
+            .filter(|token| !token.trim().is_empty())
+            .map(|token| format!("Bearer {}", token.trim()).parse())
+            .transpose()
+            .map_err(|e| anyhow::anyhow!(t!("error-engine-auth-token", error = e)))?;
+        Ok(Self { authorization, ..self })
+    }
+
+    /// A request carrying this client's namespace and auth token.
+    fn request<T>(&self, message: T) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        if let Some(namespace) = &self.namespace {
+            request.metadata_mut().insert(NAMESPACE_HEADER, namespace.clone());
+        }
+        if let Some(authorization) = &self.authorization {
+            request.metadata_mut().insert(AUTHORIZATION_HEADER, authorization.clone());
+        }
+        request
+    }
+
+    /// Whether the engine reports its trading service as ready (SERVING) to take requests.
+    pub async fn is_ready(&mut self) -> Result<bool> {
+        let request = tonic::Request::new(HealthCheckRequest { service: "trading.TradingEngine".to_string() });
+        let response = self.health.check(request).await?.into_inner();
+        Ok(response.status == ServingStatus::Serving as i32)
+    }
+
+    /// Engine version, uptime, loaded series and features; compare `api_version` with `engine::services::API_VERSION`.
+    pub async fn engine_info(&mut self) -> Result<EngineInfo> {
+        Ok(self.client.get_engine_info(self.request(EngineInfoRequest {})).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+
+    /// Symbols with candles loaded in the engine, sorted.
+    pub async fn list_symbols(&mut self) -> Result<Vec<String>> {
+        let response = self.client.list_symbols(self.request(ListSymbolsRequest {})).await.map_err(|e| rpc_error(e, None))?.into_inner();
+        Ok(response.symbols.into_iter().map(|s| s.symbol).collect())
+    }
+
+    /// Submits `job` and waits for it to finish, passing each progress update (share done, 0 to 1,
+    /// and what the engine is doing) to `on_progress`.
+    async fn run_job(&mut self, job: Job, mut on_progress: impl FnMut(f64, &str)) -> Result<Option<JobOutput>> {
+        let request = self.request(SubmitJobRequest { job: Some(job) });
+        let job = JobRequest { job_id: self.client.submit_job(request).await.map_err(|e| rpc_error(e, None))?.into_inner().job_id };
+
+        let mut updates = self.client.watch_job(self.request(job.clone())).await.map_err(|e| rpc_error(e, None))?.into_inner();
+        while let Some(status) = updates.message().await.map_err(|e| rpc_error(e, None))? {
+            tracing::debug!(job_id = %status.job_id, progress = status.progress, "{}", status.message);
+            on_progress(status.progress, &status.message);
+        }
+        Ok(self.client.get_job_result(self.request(job)).await.map_err(|e| rpc_error(e, None))?.into_inner().result)
     }
 
     // Placeholder methods mirroring the gRPC service
-    pub async fn load_csv(&mut self, file_path: String, symbol: String) -> Result<String> {
-        let request = tonic::Request::new(LoadCsvRequest { file_path, symbol });
-        let response = self.client.load_csv_data(request).await?.into_inner();
-        Ok(response.message)
+    /// Imports a CSV file into `symbol`'s `timeframe` series as an engine job, so a large file does not
+    /// hold a call open; waits for it to finish.
+    pub async fn load_csv(&mut self, file_path: String, symbol: String, timeframe: TimeFrame, on_progress: impl FnMut(f64, &str)) -> Result<String> {
+        let load = LoadCsvRequest { file_path, symbol, timeframe: GrpcTimeFrame::from(timeframe) as i32, ..Default::default() };
+        match self.run_job(Job::LoadCsv(load), on_progress).await? {
+            Some(JobOutput::LoadCsv(response)) => Ok(response.message),
+            _ => Err(anyhow::anyhow!(t!("error-import-no-result"))),
+        }
         // tracing::info!("[GUI Client STUB] Load CSV: {} for {}", file_path, symbol);
         // Ok(format!("Successfully loaded {} for {} (stubbed)", file_path, symbol))
     }
 
-    pub async fn get_market_data(&mut self, symbol: String /*, from: i64, to: i64*/) -> Result<Vec<SharedCandle>> {
+    /// Every candle of `symbol`'s `timeframe` series.
+    pub async fn get_market_data(&mut self, symbol: String, timeframe: TimeFrame /*, from: i64, to: i64*/) -> Result<Vec<SharedCandle>> {
         // For now, let's assume `from` and `to` are not used or handled by default in the engine for simplicity
         // In a real scenario, these would be important parameters.
-        let request = tonic::Request::new(MarketDataRequest {
+        let request = self.request(MarketDataRequest {
             symbol: symbol.clone(), // Clone symbol for the request
             from_timestamp: 0, // Placeholder, needs proper values
             to_timestamp: chrono::Utc::now().timestamp_millis(), // Placeholder, needs proper values
+            timeframe: GrpcTimeFrame::from(timeframe) as i32,
+            follow_replay: false,
+            ..Default::default()
         });
-        let mut stream = self.client.get_market_data(request).await?.into_inner();
+        let response = self.client.get_market_data(request).await.map_err(|e| rpc_error(e, None))?;
+        let request_id = response.metadata().get(REQUEST_ID_HEADER).and_then(|id| id.to_str().ok()).map(str::to_string);
+        let mut stream = response.into_inner();
         let mut candles = Vec::new();
-        while let Some(response_part) = stream.message().await? {
-            candles.extend(response_part.candles.into_iter().map(|proto_c| {
-                // Convert engine::services::ProtoCandle to shared::models::Candle
-                SharedCandle {
-                    symbol: proto_c.symbol,
-                    timestamp: chrono::DateTime::from_timestamp_millis(proto_c.timestamp)
-                        .unwrap_or_else(|| chrono::Utc::now()), // Or handle error better
-                    open: proto_c.open,
-                    high: proto_c.high,
-                    low: proto_c.low,
-                    close: proto_c.close,
-                    volume: proto_c.volume,
-                    trades: proto_c.trades as u32, // Ensure type matches
-                }
-            }));
+        while let Some(response_part) = stream.message().await.map_err(|e| rpc_error(e, request_id.as_deref()))? {
+            candles.extend(response_part.candles.into_iter().map(to_shared_candle));
         }
         Ok(candles)
         // tracing::info!("[GUI Client STUB] Get Market Data for {}", symbol);
@@ -86,12 +253,13 @@ impl EngineClient {
     }
 
     pub async fn calculate_indicator(&mut self, symbol: String, indicator_type: String, parameters_json: String) -> Result<Option<shared::models::Indicator>> {
-        let request = tonic::Request::new(IndicatorRequest {
+        let request = self.request(IndicatorRequest {
             symbol: symbol.clone(),
             indicator_type: indicator_type.clone(),
-            parameters: parameters_json,
+            parameters: parameters_json.clone(),
+            ..Default::default()
         });
-        let response = self.client.calculate_indicator(request).await?.into_inner();
+        let response = self.client.calculate_indicator(request).await.map_err(|e| rpc_error(e, None))?.into_inner();
 
         // Convert engine::services::IndicatorResponse to shared::models::Indicator
         // Assuming IndicatorResponse has fields like name, values, and parameters (which might need parsing if it's a string)
@@ -109,7 +277,141 @@ impl EngineClient {
         }
     }
 
-    // Add other client methods for SimulateTrade etc.
+    /// Candles of `symbol`'s `timeframe` series pushed by the engine as they are loaded or replayed; the stream ends when dropped.
+    pub async fn subscribe_candles(&mut self, symbol: String, timeframe: TimeFrame) -> Result<tonic::Streaming<GrpcCandleUpdate>> {
+        let request = self.request(SubscribeCandlesRequest { symbol, timeframe: GrpcTimeFrame::from(timeframe) as i32 });
+        Ok(self.client.subscribe_candles(request).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+
+    /// Starts replaying `symbol`'s daily candles at `speed` bars per second, paused if asked.
+    pub async fn start_replay(&mut self, symbol: String, speed: f64, start_paused: bool) -> Result<ReplayStatus> {
+        let request = self.request(StartReplayRequest { symbol, speed, start_paused, ..Default::default() });
+        Ok(self.client.start_replay(request).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+
+    pub async fn pause_replay(&mut self, symbol: String) -> Result<ReplayStatus> {
+        Ok(self.client.pause_replay(self.request(ReplayControlRequest { symbol })).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+
+    pub async fn resume_replay(&mut self, symbol: String) -> Result<ReplayStatus> {
+        Ok(self.client.resume_replay(self.request(ReplayControlRequest { symbol })).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+
+    pub async fn step_replay(&mut self, symbol: String, steps: u32) -> Result<ReplayStatus> {
+        Ok(self.client.step_replay(self.request(StepReplayRequest { symbol, steps })).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+
+    pub async fn set_replay_speed(&mut self, symbol: String, speed: f64) -> Result<ReplayStatus> {
+        Ok(self.client.set_replay_speed(self.request(ReplaySpeedRequest { symbol, speed })).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+
+    pub async fn stop_replay(&mut self, symbol: String) -> Result<ReplayStatus> {
+        Ok(self.client.stop_replay(self.request(ReplayControlRequest { symbol })).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+
+    /// The candles a running replay of `symbol` revealed so far, then each one it reveals; ends with the replay.
+    pub async fn follow_replay(&mut self, symbol: String) -> Result<tonic::Streaming<MarketDataResponse>> {
+        let request = self.request(MarketDataRequest {
+            symbol,
+            to_timestamp: chrono::Utc::now().timestamp_millis(),
+            follow_replay: true,
+            ..Default::default()
+        });
+        Ok(self.client.get_market_data(request).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+
+    /// The contents of a CSV or JSON file of candles and indicator values, as the engine writes them (ExportData).
+    pub async fn export_data(&mut self, export: ExportDataRequest) -> Result<ExportDataResponse> {
+        Ok(self.client.export_data(self.request(export)).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+
+    /// `symbol`'s simulated fills, oldest first.
+    pub async fn trade_history(&mut self, symbol: String) -> Result<Vec<GrpcExecution>> {
+        let request = self.request(TradeHistoryRequest { symbol, ..Default::default() });
+        Ok(self.client.get_trade_history(request).await.map_err(|e| rpc_error(e, None))?.into_inner().executions)
+    }
+
+    /// Backtests `strategy` on `symbol` with its default parameters and capital (RunBacktest, as an
+    /// engine job reporting its progress); returns its report, metrics and benchmark comparison.
+    pub async fn run_backtest(&mut self, symbol: String, strategy: String, on_progress: impl FnMut(f64, &str)) -> Result<BacktestResponse> {
+        let backtest = BacktestRequest { symbol, strategy, ..Default::default() };
+        match self.run_job(Job::Backtest(backtest), on_progress).await? {
+            Some(JobOutput::Backtest(response)) => Ok(response),
+            _ => Err(anyhow::anyhow!(t!("error-backtest-no-result"))),
+        }
+    }
+
+    /// Places a simulated day limit order on `symbol`; it fills at once if the latest candle already
+    /// reached `price`, otherwise it rests as a working order.
+    pub async fn place_limit_order(&mut self, symbol: String, side: OrderSide, quantity: f64, price: f64) -> Result<TradeResponse> {
+        let request = self.request(TradeRequest {
+            symbol,
+            action: side.as_str().to_string(),
+            quantity,
+            price: Some(to_price(price).to_string()),
+            order_type: "LIMIT".to_string(),
+            ..Default::default()
+        });
+        Ok(self.client.simulate_trade(request).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+
+    /// Sets an alert the engine checks against every candle loaded or replayed for its series.
+    pub async fn create_alert(&mut self, alert: CreateAlertRequest) -> Result<GrpcAlert> {
+        Ok(self.client.create_alert(self.request(alert)).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+
+    pub async fn delete_alert(&mut self, alert_id: String) -> Result<GrpcAlert> {
+        Ok(self.client.delete_alert(self.request(AlertRequest { alert_id })).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+
+    /// Alerts of every symbol waiting to fire, oldest first.
+    pub async fn list_alerts(&mut self) -> Result<Vec<GrpcAlert>> {
+        Ok(self.client.list_alerts(self.request(ListAlertsRequest::default())).await.map_err(|e| rpc_error(e, None))?.into_inner().alerts)
+    }
+
+    /// Alerts of every symbol as they fire; the stream ends when dropped.
+    pub async fn subscribe_alerts(&mut self) -> Result<tonic::Streaming<GrpcAlertEvent>> {
+        let request = self.request(SubscribeAlertsRequest::default());
+        Ok(self.client.subscribe_alerts(request).await.map_err(|e| rpc_error(e, None))?.into_inner())
+    }
+}
+
+/// A price as the engine sends it, a decimal string; the engine sends no other, so anything else reads as zero.
+fn from_proto_price(value: &str) -> Price {
+    value.parse().unwrap_or_default()
+}
+
+/// Converts an engine candle to the GUI's (shared) candle.
+pub fn to_shared_candle(proto_c: ProtoCandle) -> SharedCandle {
+    SharedCandle {
+        symbol: proto_c.symbol,
+        timestamp: chrono::DateTime::from_timestamp_millis(proto_c.timestamp)
+            .unwrap_or_else(|| chrono::Utc::now()), // Or handle error better
+        open: from_proto_price(&proto_c.open),
+        high: from_proto_price(&proto_c.high),
+        low: from_proto_price(&proto_c.low),
+        close: from_proto_price(&proto_c.close),
+        volume: proto_c.volume,
+        trades: proto_c.trades as u32, // Ensure type matches
+    }
+}
+
+/// Converts a backtest trade record from the engine to the shared trade model.
+pub fn to_shared_trade(record: GrpcTradeRecord) -> Trade {
+    let time = |millis: i64| chrono::DateTime::from_timestamp_millis(millis).unwrap_or_default();
+    Trade {
+        direction: TradeDirection::parse(&record.direction).unwrap_or(TradeDirection::Short),
+        entry_time: time(record.entry_timestamp),
+        exit_time: time(record.exit_timestamp),
+        symbol: record.symbol,
+        entry_price: from_proto_price(&record.entry_price),
+        exit_price: from_proto_price(&record.exit_price),
+        quantity: record.quantity,
+        pnl: from_proto_price(&record.pnl),
+        return_pct: record.return_pct,
+        mae: from_proto_price(&record.mae),
+        mfe: from_proto_price(&record.mfe),
+    }
 }
 
 // Note: The actual gRPC client generation and connection is a significant piece of work.
@@ -120,3 +422,28 @@ impl EngineClient {
 // 2. The `.proto` file is moved to the `shared` crate (or a new `protos` crate), and both `engine`
 //    and `gui` generate their respective server/client code from it. This is a cleaner approach for larger projects.
 //    The plan currently has `trading.proto` in `engine/proto/`.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engine::config::settings::AppSettings;
+    use std::io::Write;
+
+    #[tokio::test]
+    async fn test_intraday_files_load_into_their_timeframe() {
+        let mut file = tempfile::Builder::new().suffix(".csv").tempfile().unwrap();
+        writeln!(file, "Ativo;Data;Hora;Abertura;Máximo;Mínimo;Fechamento;Volume;Quantidade").unwrap();
+        writeln!(file, "PETR4;02/01/2024;10:00:00;30,00;30,10;29,90;30,05;1.000,00;10").unwrap();
+        writeln!(file, "PETR4;02/01/2024;10:05:00;30,05;30,20;30,00;30,15;1.000,00;12").unwrap();
+        file.flush().unwrap();
+        let engine = EmbeddedEngine::start(AppSettings::default()).await.unwrap();
+        let mut client = EngineClient::embedded(&engine);
+
+        // As a dropped PETR4_M5.csv is loaded
+        let path = file.path().to_str().unwrap().to_string();
+        client.load_csv(path, "PETR4".to_string(), TimeFrame::Minute5, |_, _| {}).await.unwrap();
+
+        assert_eq!(client.get_market_data("PETR4".to_string(), TimeFrame::Minute5).await.unwrap().len(), 2);
+        assert_eq!(engine_error_code(&client.get_market_data("PETR4".to_string(), TimeFrame::Day1).await.unwrap_err()), Some(ErrorCode::MarketDataNotFound));
+    }
+}
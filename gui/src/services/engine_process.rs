@@ -0,0 +1,96 @@
+// Engine process
+// With `engine.spawn`, the GUI starts the engine binary as a child process, so users only launch one
+// executable, and stops it when the GUI exits. It is asked to shut down as with Ctrl+C, so it saves
+// its account, and killed if it is still running after `STOP_TIMEOUT`.
+use anyhow::{anyhow, bail, Result};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a spawned engine gets to shut down before it is killed.
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The engine started by the GUI, if it is running.
+static SPAWNED: Mutex<Option<Child>> = Mutex::new(None);
+
+/// `executable` as configured; a relative path is taken from the GUI's directory when the engine is
+/// installed there, and from the working directory or PATH otherwise.
+fn resolve(executable: &str) -> PathBuf {
+    let path = PathBuf::from(executable);
+    if path.is_relative() {
+        let beside_gui = std::env::current_exe().ok()
+            .and_then(|gui| Some(gui.parent()?.join(&path)))
+            .filter(|candidate| candidate.is_file());
+        if let Some(candidate) = beside_gui {
+            return candidate;
+        }
+    }
+    path
+}
+
+/// Starts the engine from `executable` unless the one started before is still running; whether it
+/// was started now. An engine that exited on its own is reported, then started again.
+pub fn ensure_running(executable: &str) -> Result<bool> {
+    let mut spawned = SPAWNED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(child) = spawned.as_mut() {
+        match child.try_wait()? {
+            None => return Ok(false),
+            Some(status) => tracing::warn!(pid = child.id(), %status, "The engine process exited; starting it again."),
+        }
+    }
+    let path = resolve(executable);
+    let child = Command::new(&path)
+        .stdin(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow!(t!("error-spawn-engine", path = path.display(), error = e)))?;
+    tracing::info!(pid = child.id(), path = %path.display(), "Started the engine process.");
+    *spawned = Some(child);
+    Ok(true)
+}
+
+/// Fails with the engine's exit status if the engine started by the GUI has exited.
+pub fn check_running() -> Result<()> {
+    let mut spawned = SPAWNED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(status) = spawned.as_mut().map(Child::try_wait).transpose()?.flatten() {
+        bail!(t!("engine-process-exited", status = status));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn ask_to_stop(child: &Child) -> bool {
+    // SAFETY: kill(2) only sends a signal; the process is our child and has not been waited for
+    unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) == 0 }
+}
+
+#[cfg(not(unix))]
+fn ask_to_stop(_child: &Child) -> bool {
+    // There is no Ctrl+C to send to another process here
+    false
+}
+
+/// Stops the engine started by the GUI, if any, waiting up to `STOP_TIMEOUT` for it to shut down
+/// before killing it. Blocks meanwhile.
+pub fn stop() {
+    let Some(mut child) = SPAWNED.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+        return;
+    };
+    if let Ok(Some(_)) = child.try_wait() {
+        return;
+    }
+    if ask_to_stop(&child) {
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        while Instant::now() < deadline {
+            if let Ok(Some(status)) = child.try_wait() {
+                tracing::info!(pid = child.id(), %status, "The engine process shut down.");
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        tracing::warn!(pid = child.id(), "The engine process did not shut down in {:?}; killing it.", STOP_TIMEOUT);
+    }
+    if let Err(e) = child.kill().and_then(|()| child.wait().map(drop)) {
+        tracing::warn!(pid = child.id(), "Failed to kill the engine process: {}", e);
+    }
+}
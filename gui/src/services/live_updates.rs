@@ -0,0 +1,60 @@
+// Live chart updates
+// Follows the engine's SubscribeCandles stream for each symbol shown, so candles loaded into the
+// engine appear on the chart as they arrive: a new candle is appended, and an update of the last
+// one (a bar still forming) redraws it in place. Crossed price alerts are reported as they happen.
+use dioxus::prelude::*;
+use shared::models::TimeFrame;
+
+use crate::services::engine_client::{to_shared_candle, EngineClient};
+use crate::state::app_state::AppState;
+
+/// Starts following the candles of `symbol`'s `timeframe` series unless they are followed already. The subscription ends
+/// with the stream (e.g. the engine stopped); the next call for the symbol then subscribes again,
+/// which `resubscribe_live_candles` does once the engine answers.
+pub fn follow_live_candles(app_state: UseSharedState<AppState>, mut client: EngineClient, symbol: String, timeframe: TimeFrame) {
+    if !app_state.write().live_symbols.insert(symbol.clone()) {
+        return;
+    }
+    spawn(async move {
+        match client.subscribe_candles(symbol.clone(), timeframe).await {
+            Ok(mut updates) => {
+                tracing::info!(%symbol, %timeframe, "Following live candles");
+                while let Ok(Some(update)) = updates.message().await {
+                    // Replays are followed by the replay controls themselves
+                    let (Some(candle), false) = (update.candle, update.source == "REPLAY") else { continue };
+                    let candle = to_shared_candle(candle);
+                    let mut app_state_writer = app_state.write();
+                    let previous_close = app_state_writer.all_market_data.get(&symbol)
+                        .and_then(|market_data| market_data.candles.last())
+                        .map(|last| last.close_f64());
+                    let close = candle.close_f64();
+                    app_state_writer.upsert_candle(&symbol, candle);
+                    if let Some(previous_close) = previous_close {
+                        let crossed = app_state_writer.crossed_price_alerts(&symbol, previous_close, close);
+                        if let Some(level) = crossed.first() {
+                            tracing::info!(%symbol, price = level.price, close, "Price alert triggered");
+                            app_state_writer.notice = Some(t!("notice-level-crossed", symbol = symbol, level = format!("{:.2}", level.price), price = format!("{:.2}", close)));
+                        }
+                    }
+                }
+                tracing::info!(%symbol, "Live candle stream ended");
+            }
+            Err(e) => tracing::warn!(%symbol, "Failed to follow live candles: {}", e),
+        }
+        app_state.write().live_symbols.remove(&symbol);
+    });
+}
+
+/// Follows again the candles of every symbol on the GUI whose stream ended, e.g. while the engine was away.
+pub fn resubscribe_live_candles(app_state: UseSharedState<AppState>, client: &EngineClient) {
+    let app_state_reader = app_state.read();
+    let unfollowed: Vec<(String, TimeFrame)> = app_state_reader.all_market_data.iter()
+        .filter(|(symbol, _)| !app_state_reader.live_symbols.contains(*symbol))
+        .map(|(symbol, market_data)| (symbol.clone(), market_data.timeframe))
+        .collect();
+    drop(app_state_reader);
+    for (symbol, timeframe) in unfollowed {
+        tracing::info!(%symbol, "Subscribing to live candles again");
+        follow_live_candles(app_state.clone(), client.clone(), symbol, timeframe);
+    }
+}
@@ -1,6 +1,17 @@
 // GUI services module
 // This will primarily include the gRPC client for communicating with the engine.
 pub mod engine_client;
+pub mod connection; // Connecting to the engine and health-checking it
+pub mod engine_process; // The engine started as a child process of the GUI
+pub mod live_updates; // Candles pushed by the engine, applied to the chart as they arrive
+pub mod alerts; // Alerts checked by the engine, reported as they fire
+pub mod trades; // Simulated and backtested trades for the chart
+pub mod symbols; // Candles of a symbol fetched on demand
+pub mod project; // Projects opened by fetching their symbols again
+pub mod session; // Where the GUI was left, saved as it changes and restored at launch
+pub mod config_reload; // Config changes put into effect, and the user config file reloaded when saved
+pub mod chart_export; // The chart saved as a PNG or SVG file
+pub mod data_export; // The visible candles and indicators saved as CSV or JSON by the engine
 
 // Re-export for easier access
 // pub use engine_client::EngineClientService;
@@ -0,0 +1,67 @@
+// Opening projects
+// After a project's layout is applied, each of its symbols is fetched from the engine again:
+// loaded from its CSV file first if the engine no longer has it (e.g. after a restart), then its
+// indicators are recalculated with the saved parameters.
+use dioxus::prelude::*;
+use shared::models::{MarketData, TimeFrame};
+
+use crate::services::engine_client::EngineClient;
+use crate::services::live_updates::follow_live_candles;
+use crate::services::trades::show_simulated_trades;
+use crate::state::app_state::AppState;
+use crate::state::project::{Project, ProjectSymbol};
+
+/// Fetches `entry`'s candles and indicators into the GUI; returns why it failed, if it did.
+async fn restore_symbol(app_state: UseSharedState<AppState>, client: &mut EngineClient, entry: &ProjectSymbol) -> Result<(), String> {
+    let symbol = entry.symbol.clone();
+    let in_engine = app_state.read().engine_symbols.contains(&symbol);
+    if let (false, Some(file)) = (in_engine, &entry.source_file) {
+        client.load_csv(file.clone(), symbol.clone(), TimeFrame::Day1, |progress, message| app_state.write().update_loading(progress, message)).await
+            .map_err(|e| t!("error-load-symbol", symbol = symbol, file = file, error = e))?;
+    }
+    let candles = client.get_market_data(symbol.clone(), TimeFrame::Day1).await.map_err(|e| t!("error-market-data", symbol = symbol, error = e))?;
+    app_state.write().add_market_data(MarketData { symbol: symbol.clone(), candles, timeframe: TimeFrame::Day1 });
+    for indicator in &entry.indicators {
+        match client.calculate_indicator(symbol.clone(), indicator.indicator_type.clone(), indicator.parameters.to_string()).await {
+            Ok(Some(indicator)) => app_state.write().add_indicator_to_symbol(&symbol, indicator),
+            Ok(None) => tracing::warn!(%symbol, indicator = %indicator.indicator_type, "Indicator returned no data"),
+            Err(e) => return Err(t!("error-calculate-indicator", indicator = indicator.indicator_type, symbol = symbol, error = e)),
+        }
+    }
+    Ok(())
+}
+
+/// Applies `project` to the workspace, then fetches its symbols from the engine one by one.
+pub fn open_project(app_state: UseSharedState<AppState>, mut client: EngineClient, project: Project) {
+    let mut app_state_writer = app_state.write();
+    project.apply_layout(&mut app_state_writer);
+    app_state_writer.start_loading(t!("loading-project"));
+    app_state_writer.error_message = None;
+    drop(app_state_writer);
+    spawn(async move {
+        let mut errors = Vec::new();
+        for entry in &project.symbols {
+            match restore_symbol(app_state.clone(), &mut client, entry).await {
+                Ok(()) => {
+                    show_simulated_trades(app_state.clone(), client.clone(), entry.symbol.clone());
+                    follow_live_candles(app_state.clone(), client.clone(), entry.symbol.clone(), TimeFrame::Day1);
+                }
+                Err(e) => {
+                    tracing::error!("{}", e);
+                    errors.push(e);
+                }
+            }
+        }
+        let mut app_state_writer = app_state.write();
+        app_state_writer.finish_loading();
+        let current_symbol = project.current_symbol.clone().or_else(|| project.symbols.first().map(|entry| entry.symbol.clone()));
+        if let Some(symbol) = current_symbol.filter(|symbol| app_state_writer.all_market_data.contains_key(symbol)) {
+            app_state_writer.set_display_data(&symbol);
+        }
+        // Set after the display, which clears errors
+        if !errors.is_empty() {
+            app_state_writer.error_message = Some(errors.join("\n"));
+        }
+        tracing::info!(symbols = project.symbols.len(), failed = errors.len(), "Project opened");
+    });
+}
@@ -0,0 +1,90 @@
+// Keeping the session (see `state::session`): it is saved whenever the window or what it shows
+// changes, checked every few seconds so a closed or killed GUI loses little, and its chart is
+// fetched again once the engine answers after a launch.
+use dioxus::prelude::*;
+use dioxus_desktop::DesktopContext;
+use shared::models::MarketData;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::services::engine_client::EngineClient;
+use crate::services::live_updates::follow_live_candles;
+use crate::services::trades::show_simulated_trades;
+use crate::state::app_state::AppState;
+use crate::state::session::{LastChart, SessionState, WindowGeometry};
+
+/// How often the session is compared with the one saved.
+const SESSION_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+fn window_geometry(window: &DesktopContext) -> Option<WindowGeometry> {
+    let scale = window.scale_factor();
+    let size = window.inner_size().to_logical::<f64>(scale);
+    let position = window.outer_position().ok()?.to_logical::<f64>(scale);
+    // Minimized windows report no size
+    (size.width > 0.0 && size.height > 0.0).then_some(WindowGeometry { width: size.width, height: size.height, x: position.x, y: position.y })
+}
+
+/// Saves the session to `path` whenever it differs from `saved`, for as long as the app runs.
+/// Failures are only logged, and retried at the next change.
+pub async fn keep_session(app_state: UseSharedState<AppState>, window: DesktopContext, path: PathBuf, mut saved: SessionState) {
+    loop {
+        tokio::time::sleep(SESSION_CHECK_INTERVAL).await;
+        let geometry = window_geometry(&window).or(saved.window);
+        let session = SessionState::from_state(&app_state.read(), geometry);
+        if session == saved {
+            continue;
+        }
+        match session.save(&path) {
+            Ok(()) => tracing::debug!("Saved the session to {}", path.display()),
+            Err(e) => tracing::warn!("Failed to save the session to {}: {}", path.display(), e),
+        }
+        saved = session;
+    }
+}
+
+async fn fetch_chart(app_state: UseSharedState<AppState>, client: &mut EngineClient, chart: &LastChart) -> Result<(), String> {
+    let symbol = chart.symbol.clone();
+    let in_engine = app_state.read().engine_symbols.contains(&symbol);
+    if let (false, Some(file)) = (in_engine, &chart.source_file) {
+        client.load_csv(file.clone(), symbol.clone(), chart.timeframe, |progress, message| app_state.write().update_loading(progress, message)).await
+            .map_err(|e| t!("error-load-symbol", symbol = symbol, file = file, error = e))?;
+    }
+    let candles = client.get_market_data(symbol.clone(), chart.timeframe).await.map_err(|e| t!("error-market-data", symbol = symbol, error = e))?;
+    let mut app_state_writer = app_state.write();
+    app_state_writer.add_market_data(MarketData { symbol: symbol.clone(), candles, timeframe: chart.timeframe });
+    if let Some(file) = &chart.source_file {
+        app_state_writer.symbol_sources.insert(symbol.clone(), file.clone());
+    }
+    // Unless another chart was opened meanwhile
+    if app_state_writer.current_symbol_display.is_none() {
+        app_state_writer.set_display_data(&symbol);
+    }
+    Ok(())
+}
+
+/// Shows the chart of the last session, once: called when the engine is connected.
+pub fn restore_last_chart(app_state: UseSharedState<AppState>, client: &EngineClient) {
+    let Some(chart) = app_state.write().pending_chart.take() else {
+        return;
+    };
+    if app_state.read().current_symbol_display.is_some() {
+        return;
+    }
+    tracing::info!(symbol = %chart.symbol, timeframe = %chart.timeframe, "Restoring the last session's chart");
+    app_state.write().start_loading(t!("loading-fetching", symbol = chart.symbol));
+    let mut client = client.clone();
+    spawn(async move {
+        let result = fetch_chart(app_state.clone(), &mut client, &chart).await;
+        app_state.write().finish_loading();
+        match result {
+            Ok(()) => {
+                show_simulated_trades(app_state.clone(), client.clone(), chart.symbol.clone());
+                follow_live_candles(app_state, client, chart.symbol, chart.timeframe);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to restore the last session's chart: {}", e);
+                app_state.write().error_message = Some(e);
+            }
+        }
+    });
+}
@@ -0,0 +1,119 @@
+// Fetching a symbol's candles from the engine into the GUI
+// Used by the symbol tabs and the watchlist: the candles are fetched once, then kept current by
+// following the symbol's live updates. CSV files (from the command palette or dropped on the window)
+// are loaded into the engine first.
+use dioxus::prelude::*;
+use shared::errors::ErrorCode;
+use shared::models::{MarketData, TimeFrame};
+
+use crate::services::engine_client::{engine_error_code, EngineClient};
+use crate::services::live_updates::follow_live_candles;
+use crate::services::trades::show_simulated_trades;
+use crate::state::app_state::AppState;
+use crate::state::session::RecentFileKind;
+
+/// Fetches `symbol`'s daily candles unless they are loaded already, then shows them on the chart if `display`.
+pub fn open_symbol(app_state: UseSharedState<AppState>, mut client: EngineClient, symbol: String, display: bool) {
+    if app_state.read().all_market_data.contains_key(&symbol) {
+        if display {
+            app_state.write().set_display_data(&symbol);
+        }
+        return;
+    }
+    app_state.write().start_loading(t!("loading-fetching", symbol = symbol));
+    spawn(async move {
+        let data_result = client.get_market_data(symbol.clone(), TimeFrame::Day1).await;
+        let mut app_state_writer = app_state.write();
+        app_state_writer.finish_loading();
+        match data_result {
+            Ok(candles) => {
+                app_state_writer.add_market_data(MarketData {
+                    symbol: symbol.clone(),
+                    candles,
+                    timeframe: TimeFrame::Day1,
+                });
+                if display {
+                    app_state_writer.set_display_data(&symbol);
+                }
+                drop(app_state_writer);
+                show_simulated_trades(app_state.clone(), client.clone(), symbol.clone());
+                follow_live_candles(app_state, client, symbol, TimeFrame::Day1);
+            }
+            Err(e) => {
+                let err_msg = market_data_error(&symbol, &e);
+                tracing::error!("{}", err_msg);
+                app_state_writer.error_message = Some(err_msg);
+            }
+        }
+    });
+}
+
+/// What to tell the user when `symbol`'s candles could not be fetched.
+fn market_data_error(symbol: &str, error: &anyhow::Error) -> String {
+    match engine_error_code(error) {
+        Some(ErrorCode::MarketDataNotFound) => t!("error-no-market-data", symbol = symbol),
+        _ => t!("error-market-data", symbol = symbol, error = error),
+    }
+}
+
+/// What to tell the user when the CSV file at `path` could not be loaded.
+fn load_csv_error(path: &str, error: &anyhow::Error) -> String {
+    match engine_error_code(error) {
+        Some(ErrorCode::FileNotFound) => t!("error-csv-not-found", file = path),
+        Some(code) if code.is_bad_file() => t!("error-csv-format", file = path, error = error),
+        _ => t!("error-load-csv", file = path, error = error),
+    }
+}
+
+/// Loads the CSV file at `path` into the engine as `symbol`'s `timeframe` series, then shows its
+/// candles on the chart and follows that series.
+pub fn load_csv_file(app_state: UseSharedState<AppState>, mut client: EngineClient, path: String, symbol: String, timeframe: TimeFrame) {
+    let mut app_state_writer = app_state.write();
+    app_state_writer.start_loading(t!("loading-csv", file = path));
+    app_state_writer.error_message = None;
+    app_state_writer.clear_indicators_for_symbol(&symbol);
+    drop(app_state_writer); // Release lock before await
+
+    spawn(async move {
+        // The import job reports how much of the file it read
+        let load_result = client.load_csv(path.clone(), symbol.clone(), timeframe, |progress, message| {
+            app_state.write().update_loading(progress, message);
+        }).await;
+        let mut app_state_writer;
+        match load_result {
+            Ok(load_msg) => {
+                tracing::info!("[COMMAND ACTION] Load CSV: {}", load_msg);
+                let data_result = client.get_market_data(symbol.clone(), timeframe).await;
+                app_state_writer = app_state.write();
+                app_state_writer.symbol_sources.insert(symbol.clone(), path.clone());
+                app_state_writer.add_recent_file(&path, RecentFileKind::Csv);
+
+                match data_result {
+                    Ok(candles) => {
+                        app_state_writer.add_market_data(MarketData { symbol: symbol.clone(), candles, timeframe });
+                        app_state_writer.set_display_data(&symbol);
+                        app_state_writer.error_message = None;
+                    }
+                    Err(e) => {
+                        let err_msg = market_data_error(&symbol, &e);
+                        tracing::error!("{}", err_msg);
+                        app_state_writer.error_message = Some(err_msg);
+                    }
+                }
+            }
+            Err(e) => {
+                let err_msg = load_csv_error(&path, &e);
+                tracing::error!("{}", err_msg);
+                app_state_writer = app_state.write();
+                app_state_writer.error_message = Some(err_msg);
+            }
+        }
+        app_state_writer.finish_loading();
+        let loaded = app_state_writer.all_market_data.contains_key(&symbol);
+        drop(app_state_writer);
+        if loaded {
+            show_simulated_trades(app_state.clone(), client.clone(), symbol.clone());
+            follow_live_candles(app_state, client, symbol, timeframe);
+        }
+    });
+}
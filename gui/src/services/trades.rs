@@ -0,0 +1,53 @@
+// Trades shown on the chart
+// Fetches a symbol's simulated fills (GetTradeHistory) when its chart is opened, or the trades of a
+// backtest run from the command palette, into `AppState::trades`. Backtest results are kept for the
+// results view, which opens once the backtest is done.
+use dioxus::prelude::*;
+
+use crate::services::engine_client::{to_shared_trade, EngineClient};
+use crate::state::app_state::AppState;
+use crate::state::backtest::BacktestResults;
+use crate::state::trades::ChartTrades;
+
+/// Replaces `symbol`'s chart trades with its simulated fills, if it has any.
+pub fn show_simulated_trades(app_state: UseSharedState<AppState>, mut client: EngineClient, symbol: String) {
+    spawn(async move {
+        match client.trade_history(symbol.clone()).await {
+            Ok(executions) if !executions.is_empty() => {
+                tracing::info!(%symbol, fills = executions.len(), "Showing simulated trades");
+                app_state.write().set_trades(&symbol, ChartTrades::from_executions(&executions));
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!(%symbol, "Failed to fetch the trade history: {}", e),
+        }
+    });
+}
+
+/// Backtests `strategy` on `symbol`, shows its trades on the chart and opens its results.
+pub fn show_backtest_trades(app_state: UseSharedState<AppState>, mut client: EngineClient, symbol: String, strategy: String) {
+    app_state.write().start_loading(t!("loading-backtest", strategy = strategy, symbol = symbol));
+    spawn(async move {
+        let result = client.run_backtest(symbol.clone(), strategy.clone(), |progress, message| {
+            app_state.write().update_loading(progress, message);
+        }).await;
+        let mut app_state_writer = app_state.write();
+        app_state_writer.finish_loading();
+        match result {
+            Ok(response) => {
+                let trades = response.report.as_ref().map_or_else(Vec::new, |report| report.trades.iter().cloned().map(to_shared_trade).collect());
+                let results = BacktestResults { symbol: symbol.clone(), strategy: strategy.clone(), trades, response };
+                let records = &results.trades;
+                tracing::info!(%symbol, %strategy, trades = records.len(), "Showing backtest trades");
+                app_state_writer.notice = Some(t!("notice-backtest", strategy = strategy, symbol = symbol, trades = records.len()));
+                app_state_writer.set_trades(&symbol, ChartTrades::from_trades(records));
+                app_state_writer.backtest_results = Some(results);
+                app_state_writer.backtest_results_visible = true;
+            }
+            Err(e) => {
+                let err_msg = t!("error-backtest", strategy = strategy, symbol = symbol, error = e);
+                tracing::error!("{}", err_msg);
+                app_state_writer.error_message = Some(err_msg);
+            }
+        }
+    });
+}
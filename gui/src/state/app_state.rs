@@ -5,27 +5,153 @@
 // that needs to be managed outside of component lifecycle (e.g., by services).
 
 use serde::{Deserialize, Serialize};
-use shared::models::{MarketData, Indicator}; // Using shared models
-use std::collections::HashMap;
+use shared::models::{Candle, MarketData, Indicator, TimeFrame}; // Using shared models
+use std::collections::{HashMap, HashSet};
+
+use crate::components::chart::context_menu::ChartPoint;
+use crate::components::chart::indicators::{default_line_style, LineDash, LineStyle};
+use crate::components::chart::viewport::{LinkedView, ZoomStep};
+use crate::components::chart::style::ChartType;
+use crate::config::theme::ThemePalette;
+use crate::config::IndicatorDefaults;
+use crate::i18n::{self, Language};
+use crate::state::backtest::BacktestResults;
+use crate::state::csv_drop::DroppedCsv;
+use crate::state::history::{ChartAnnotations, EditHistory};
+use crate::state::session::{LastChart, RecentFile, RecentFileKind, MAX_RECENT_FILES};
+use crate::state::trades::ChartTrades;
+use crate::state::watchlist::WatchQuote;
 
-// Example theme enum
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Theme {
     Dark,
     Light,
 }
 
+impl Theme {
+    /// The theme named in the config's `app.theme` ("dark" or "light"); dark if unknown.
+    pub fn from_name(name: &str) -> Self {
+        if name.eq_ignore_ascii_case("light") { Theme::Light } else { Theme::Dark }
+    }
+}
+
+/// How an indicator is drawn, chosen in the indicator panel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndicatorStyle {
+    pub visible: bool,
+    /// Line color, e.g. "#ffc107"; `None` uses the indicator's default color.
+    pub color: Option<String>,
+    /// Line width in pixels; `None` uses the indicator's default width.
+    #[serde(default)]
+    pub line_width: Option<f64>,
+    #[serde(default)]
+    pub dash: LineDash,
+}
+
+impl Default for IndicatorStyle {
+    fn default() -> Self {
+        Self { visible: true, color: None, line_width: None, dash: LineDash::Solid }
+    }
+}
+
+impl IndicatorStyle {
+    /// The line drawn: these choices over `default` (see `default_line_style`).
+    pub fn line_style(&self, default: LineStyle) -> LineStyle {
+        LineStyle {
+            color: self.color.clone().unwrap_or(default.color),
+            width: self.line_width.unwrap_or(default.width),
+            dash: self.dash,
+        }
+    }
+}
+
+/// A horizontal support/resistance line placed on a symbol's chart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceLevel {
+    pub id: u64, // Unique per symbol
+    pub price: f64,
+    /// Also a price alert: reported by `AppState::crossed_price_alerts` when the price crosses it.
+    pub alert: bool,
+}
+
+/// Panels around the chart that can be shown or hidden from the command palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Panel {
+    Watchlist,
+    Indicators,
+    PriceLevels,
+    Alerts,
+    Replay,
+}
+
+impl Panel {
+    pub const ALL: [Panel; 5] = [Panel::Watchlist, Panel::Indicators, Panel::PriceLevels, Panel::Alerts, Panel::Replay];
+
+    /// Stable name, e.g. in the `command-panel-{name}` messages.
+    pub fn name(self) -> &'static str {
+        match self {
+            Panel::Watchlist => "watchlist",
+            Panel::Indicators => "indicators",
+            Panel::PriceLevels => "price-levels",
+            Panel::Alerts => "alerts",
+            Panel::Replay => "replay",
+        }
+    }
+}
+
+/// Commands kept in `AppState::command_history`.
+pub const MAX_COMMAND_HISTORY: usize = 20;
+
+/// A long operation in progress (CSV import, backtest...), shown as a progress overlay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoadingProgress {
+    pub label: String, // e.g. "Loading sample.csv"
+    /// Share of the work done, 0 to 1; `None` until the engine reports progress (indeterminate bar).
+    pub progress: Option<f64>,
+    pub message: String, // What the engine is doing, e.g. "Read 40% of the file"
+}
+
+/// Engine connection as last health-checked (see `services::connection`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Not connected yet since the GUI started.
+    #[default]
+    Connecting,
+    Connected,
+    /// Was connected, but the engine stopped answering or serving: the `attempt`th failed check in a row,
+    /// retried in `retry_in_secs`.
+    Reconnecting { attempt: u32, retry_in_secs: u64 },
+    /// Unreachable; connecting again in `retry_in_secs`, backing off after each failure.
+    Offline { retry_in_secs: u64 },
+}
+
 // Example structure for application state
 // This can be provided via Dioxus' shared state context if needed.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppState {
     pub current_theme: Theme,
-    pub language: String, // e.g., "pt-BR", "en-US"
+    pub language: Language, // Language of the GUI text, see `set_language`
 
     // --- Data related state ---
     // Storage for all loaded data, keyed by symbol
     pub all_market_data: HashMap<String, MarketData>,
     pub all_indicators: HashMap<String, Vec<Indicator>>, // Stores calculated indicators per symbol
+    // CSV file each symbol was loaded from through the GUI, saved in projects to load it again
+    pub symbol_sources: HashMap<String, String>,
+    // Indicator panel choices, per symbol then indicator name; indicators without one use the default style
+    pub indicator_styles: HashMap<String, HashMap<String, IndicatorStyle>>,
+    // Horizontal price levels per symbol, placed by clicking the chart's price axis
+    pub price_levels: HashMap<String, Vec<PriceLevel>>,
+    // Price level and indicator edits that can be undone (see `record_edit`)
+    #[serde(skip)]
+    pub edit_history: EditHistory,
+    // Simulated fills (GetTradeHistory) or backtest trades drawn on each symbol's chart
+    #[serde(skip)]
+    pub trades: HashMap<String, ChartTrades>,
+    // Symbols followed in the watchlist sidebar, in the order they were added
+    pub watchlist: Vec<String>,
+    // Symbols the engine has candles for (ListSymbols), fetched into `all_market_data` when opened
+    pub engine_symbols: Vec<String>,
 
     // Data for the currently active chart/symbol
     pub current_symbol_display: Option<String>,
@@ -33,11 +159,70 @@ pub struct AppState {
     pub current_indicators_display: Vec<shared::models::Indicator>,
 
     // UI feedback for data operations
-    pub is_loading: bool,
+    pub loading: Option<LoadingProgress>,
     pub error_message: Option<String>,
+    pub notice: Option<String>, // Informational message, e.g. a triggered price alert
+
+    // When candles were last loaded or updated, shown in the status bar
+    #[serde(skip)]
+    pub last_data_update: Option<chrono::DateTime<chrono::Utc>>,
+
+    // Engine connection, kept current by periodic health checks
+    #[serde(skip)]
+    pub connection: ConnectionState,
+    // Version, uptime and loaded series reported by the engine once connected
+    #[serde(skip)]
+    pub engine_info: Option<engine::services::EngineInfo>,
+    // Status of the current symbol's replay, while one runs
+    #[serde(skip)]
+    pub replay_status: Option<engine::services::ReplayStatus>,
+    // Symbols whose live candle stream (SubscribeCandles) is followed
+    #[serde(skip)]
+    pub live_symbols: HashSet<String>,
+    // Alerts set in the engine that have not fired yet, every symbol's, and whether the GUI
+    // follows them firing (SubscribeAlerts)
+    #[serde(skip)]
+    pub engine_alerts: Vec<engine::services::GrpcAlert>,
+    #[serde(skip)]
+    pub following_alerts: bool,
 
     // --- UI specific state ---
     pub command_palette_visible: bool,
+    // Keys of the commands run, most recent first (see `record_command`)
+    pub command_history: Vec<String>,
+    pub shortcut_editor_visible: bool,
+    pub settings_visible: bool,
+    pub connection_dialog_visible: bool,
+    // CSV files dropped on the window, asked about one at a time before loading
+    #[serde(skip)]
+    pub dropped_csvs: Vec<DroppedCsv>,
+    // Latest zoom asked for by a shortcut, numbered so each request is applied once by the chart
+    #[serde(skip)]
+    pub chart_zoom_request: Option<(u64, ZoomStep)>,
+    // How the chart draws candles; starts as the config's `chart.type`
+    pub chart_type: ChartType,
+    // Whether switching symbols keeps the chart on the same times (visible window and crosshair),
+    // to compare symbols bar by bar; `linked_view` is the view last seen while linked
+    pub charts_linked: bool,
+    #[serde(skip)]
+    pub linked_view: Option<LinkedView>,
+    // The chart's visible window (without crosshair) as last reported, for exporting what is shown;
+    // until the chart is panned or zoomed, it shows all the candles
+    #[serde(skip)]
+    pub visible_view: Option<LinkedView>,
+    // Where the chart's context menu is open, if it is
+    #[serde(skip)]
+    pub chart_context_menu: Option<ChartPoint>,
+    // The last backtest run, and whether its results view is open
+    #[serde(skip)]
+    pub backtest_results: Option<BacktestResults>,
+    pub backtest_results_visible: bool,
+    // Panels shown around the chart, and the files opened recently (most recent first); both are
+    // kept in the session, as is the chart, fetched once the engine answers after a launch
+    pub open_panels: Vec<Panel>,
+    pub recent_files: Vec<RecentFile>,
+    #[serde(skip)]
+    pub pending_chart: Option<LastChart>,
     // pub active_timeframe: Option<shared::models::TimeFrame>, // Future use
 
     // Configuration loaded from default.json or user settings
@@ -48,19 +233,52 @@ impl Default for AppState {
     fn default() -> Self {
         Self {
             current_theme: Theme::Dark,
-            language: "pt-BR".to_string(),
+            language: Language::default(),
 
             all_market_data: HashMap::new(),
             all_indicators: HashMap::new(),
+            symbol_sources: HashMap::new(),
+            indicator_styles: HashMap::new(),
+            price_levels: HashMap::new(),
+            edit_history: EditHistory::default(),
+            trades: HashMap::new(),
+            watchlist: Vec::new(),
+            engine_symbols: Vec::new(),
 
             current_symbol_display: None,
             current_candles_display: None,
             current_indicators_display: Vec::new(),
 
-            is_loading: false,
+            loading: None,
             error_message: None,
+            notice: None,
+
+            last_data_update: None,
+
+            connection: ConnectionState::default(),
+            engine_info: None,
+            replay_status: None,
+            live_symbols: HashSet::new(),
+            engine_alerts: Vec::new(),
+            following_alerts: false,
 
             command_palette_visible: false,
+            command_history: Vec::new(),
+            shortcut_editor_visible: false,
+            settings_visible: false,
+            connection_dialog_visible: false,
+            dropped_csvs: Vec::new(),
+            chart_type: ChartType::default(),
+            charts_linked: false,
+            linked_view: None,
+            visible_view: None,
+            chart_context_menu: None,
+            chart_zoom_request: None,
+            backtest_results: None,
+            backtest_results_visible: false,
+            open_panels: Panel::ALL.to_vec(),
+            recent_files: Vec::new(),
+            pending_chart: None,
             // active_timeframe: None,
             // config: AppConfig::default(), // Assuming AppConfig has a default
         }
@@ -73,6 +291,24 @@ impl AppState {
         self.current_theme = theme;
     }
 
+    /// Switches the GUI text to `language`; components reading the state re-render in it.
+    pub fn set_language(&mut self, language: Language) {
+        i18n::set_language(language);
+        self.language = language;
+    }
+
+    /// Switches between the dark and light themes; returns the new one.
+    pub fn toggle_theme(&mut self) -> Theme {
+        let next = if self.current_theme == Theme::Dark { Theme::Light } else { Theme::Dark };
+        self.set_theme(next.clone());
+        next
+    }
+
+    /// Colors of the current theme.
+    pub fn palette(&self) -> ThemePalette {
+        ThemePalette::for_theme(&self.current_theme)
+    }
+
     // Method to update display data after loading/changing symbol
     pub fn set_display_data(&mut self, symbol: &str) {
         self.current_symbol_display = Some(symbol.to_string());
@@ -83,21 +319,55 @@ impl AppState {
             self.current_candles_display = None;
         }
 
-        if let Some(indicators_for_symbol) = self.all_indicators.get(symbol) {
-            self.current_indicators_display = indicators_for_symbol.clone();
-        } else {
-            self.current_indicators_display = Vec::new();
-        }
+        self.refresh_indicators_display();
         self.error_message = None; // Clear previous error on new data load
     }
 
+    /// Symbols the chart can switch to: those loaded here or in the engine, sorted.
+    pub fn available_symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.all_market_data.keys().chain(self.engine_symbols.iter()).cloned().collect();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+
     pub fn add_market_data(&mut self, data: MarketData) {
         let symbol = data.symbol.clone();
         self.all_market_data.insert(symbol.clone(), data);
+        self.last_data_update = Some(chrono::Utc::now());
         // Optionally, directly set this as the display data
         // self.set_display_data(&symbol);
     }
 
+    /// Replaces `symbol`'s candles, e.g. with those a replay revealed so far, keeping its timeframe.
+    pub fn set_candles(&mut self, symbol: &str, candles: Vec<Candle>) {
+        match self.all_market_data.get_mut(symbol) {
+            Some(market_data) => market_data.candles = candles,
+            None => self.add_market_data(MarketData { symbol: symbol.to_string(), timeframe: TimeFrame::Day1, candles }),
+        }
+        self.last_data_update = Some(chrono::Utc::now());
+        self.refresh_candles_display(symbol);
+    }
+
+    /// Adds a candle after `symbol`'s last one, or updates the last one if it has the same timestamp
+    /// (a bar still forming). Older candles are ignored.
+    pub fn upsert_candle(&mut self, symbol: &str, candle: Candle) {
+        let Some(market_data) = self.all_market_data.get_mut(symbol) else { return };
+        match market_data.candles.last_mut() {
+            Some(last) if last.timestamp == candle.timestamp => *last = candle,
+            Some(last) if last.timestamp > candle.timestamp => return,
+            _ => market_data.candles.push(candle),
+        }
+        self.last_data_update = Some(chrono::Utc::now());
+        self.refresh_candles_display(symbol);
+    }
+
+    fn refresh_candles_display(&mut self, symbol: &str) {
+        if self.current_symbol_display.as_deref() == Some(symbol) {
+            self.current_candles_display = self.all_market_data.get(symbol).map(|market_data| market_data.candles.clone());
+        }
+    }
+
     pub fn add_indicator_to_symbol(&mut self, symbol: &str, indicator: Indicator) {
         self.all_indicators.entry(symbol.to_string())
             .or_default()
@@ -105,19 +375,240 @@ impl AppState {
 
         // If this symbol is currently displayed, update its display indicators
         if self.current_symbol_display.as_deref() == Some(symbol) {
-            if let Some(indicators_for_symbol) = self.all_indicators.get(symbol) {
-                self.current_indicators_display = indicators_for_symbol.clone();
-            }
+            self.refresh_indicators_display();
+        }
+    }
+
+    /// `symbol`'s price levels and indicators as they are now.
+    pub fn annotations(&self, symbol: &str) -> ChartAnnotations {
+        ChartAnnotations {
+            symbol: symbol.to_string(),
+            price_levels: self.price_levels.get(symbol).cloned().unwrap_or_default(),
+            indicators: self.all_indicators.get(symbol).cloned().unwrap_or_default(),
+            indicator_styles: self.indicator_styles.get(symbol).cloned().unwrap_or_default(),
+        }
+    }
+
+    /// Keeps `symbol`'s annotations as they are, to undo the edit about to be made to them.
+    pub fn record_edit(&mut self, symbol: &str) {
+        let before = self.annotations(symbol);
+        self.edit_history.record(before);
+    }
+
+    /// Undoes the last price level or indicator edit, showing its symbol; false if there was none.
+    pub fn undo(&mut self) -> bool {
+        let mut history = std::mem::take(&mut self.edit_history);
+        let restored = history.undo(|symbol| self.annotations(symbol));
+        self.edit_history = history;
+        restored.map(|annotations| self.restore_annotations(annotations)).is_some()
+    }
+
+    /// Redoes the last edit undone; false if there was none.
+    pub fn redo(&mut self) -> bool {
+        let mut history = std::mem::take(&mut self.edit_history);
+        let restored = history.redo(|symbol| self.annotations(symbol));
+        self.edit_history = history;
+        restored.map(|annotations| self.restore_annotations(annotations)).is_some()
+    }
+
+    fn restore_annotations(&mut self, annotations: ChartAnnotations) {
+        let ChartAnnotations { symbol, price_levels, indicators, indicator_styles } = annotations;
+        self.price_levels.insert(symbol.clone(), price_levels);
+        self.all_indicators.insert(symbol.clone(), indicators);
+        self.indicator_styles.insert(symbol.clone(), indicator_styles);
+        match self.current_symbol_display.as_deref() == Some(symbol.as_str()) {
+            true => self.refresh_indicators_display(),
+            false if self.all_market_data.contains_key(&symbol) => self.set_display_data(&symbol),
+            false => {}
         }
     }
 
     pub fn clear_indicators_for_symbol(&mut self, symbol: &str) {
         self.all_indicators.remove(symbol);
+        self.indicator_styles.remove(symbol);
         if self.current_symbol_display.as_deref() == Some(symbol) {
             self.current_indicators_display = Vec::new();
         }
     }
 
+    pub fn remove_indicator(&mut self, symbol: &str, name: &str) {
+        if let Some(indicators) = self.all_indicators.get_mut(symbol) {
+            indicators.retain(|indicator| indicator.name != name);
+        }
+        if let Some(styles) = self.indicator_styles.get_mut(symbol) {
+            styles.remove(name);
+        }
+        self.refresh_indicators_display();
+    }
+
+    /// Swaps `old_name` for `indicator` (e.g. recalculated with new parameters), keeping its place and style.
+    pub fn replace_indicator(&mut self, symbol: &str, old_name: &str, indicator: Indicator) {
+        let style = self.indicator_styles.get_mut(symbol).and_then(|styles| styles.remove(old_name));
+        if let Some(style) = style {
+            self.indicator_styles.entry(symbol.to_string()).or_default().insert(indicator.name.clone(), style);
+        }
+        let indicators = self.all_indicators.entry(symbol.to_string()).or_default();
+        match indicators.iter_mut().find(|existing| existing.name == old_name) {
+            Some(existing) => *existing = indicator,
+            None => indicators.push(indicator),
+        }
+        self.refresh_indicators_display();
+    }
+
+    pub fn indicator_style(&self, symbol: &str, name: &str) -> IndicatorStyle {
+        self.indicator_styles.get(symbol).and_then(|styles| styles.get(name)).cloned().unwrap_or_default()
+    }
+
+    pub fn set_indicator_style(&mut self, symbol: &str, name: &str, style: IndicatorStyle) {
+        self.indicator_styles.entry(symbol.to_string()).or_default().insert(name.to_string(), style);
+        self.refresh_indicators_display();
+    }
+
+    /// Lines of the displayed symbol's indicators, by name: their chosen style over the defaults of their type.
+    pub fn current_indicator_lines(&self, defaults: &IndicatorDefaults) -> HashMap<String, LineStyle> {
+        let Some(symbol) = &self.current_symbol_display else { return HashMap::new() };
+        self.all_indicators.get(symbol).into_iter().flatten()
+            .map(|indicator| {
+                let style = self.indicator_style(symbol, &indicator.name);
+                (indicator.name.clone(), style.line_style(default_line_style(&indicator.name, defaults)))
+            })
+            .collect()
+    }
+
+    pub fn add_price_level(&mut self, symbol: &str, price: f64) -> u64 {
+        let levels = self.price_levels.entry(symbol.to_string()).or_default();
+        let id = levels.iter().map(|level| level.id + 1).max().unwrap_or(1);
+        levels.push(PriceLevel { id, price, alert: false });
+        id
+    }
+
+    /// Replaces the level with `level.id`, e.g. after its price was edited or it became an alert.
+    pub fn update_price_level(&mut self, symbol: &str, level: PriceLevel) {
+        if let Some(existing) = self.price_levels.get_mut(symbol).into_iter().flatten().find(|existing| existing.id == level.id) {
+            *existing = level;
+        }
+    }
+
+    pub fn remove_price_level(&mut self, symbol: &str, id: u64) {
+        if let Some(levels) = self.price_levels.get_mut(symbol) {
+            levels.retain(|level| level.id != id);
+        }
+    }
+
+    pub fn current_price_levels(&self) -> Vec<PriceLevel> {
+        self.current_symbol_display.as_ref().and_then(|symbol| self.price_levels.get(symbol)).cloned().unwrap_or_default()
+    }
+
+    /// The current symbol's engine alerts waiting to fire, oldest first.
+    pub fn current_engine_alerts(&self) -> Vec<engine::services::GrpcAlert> {
+        let Some(symbol) = &self.current_symbol_display else { return Vec::new() };
+        self.engine_alerts.iter().filter(|alert| &alert.symbol == symbol).cloned().collect()
+    }
+
+    pub fn set_trades(&mut self, symbol: &str, trades: ChartTrades) {
+        self.trades.insert(symbol.to_string(), trades);
+    }
+
+    pub fn current_trades(&self) -> ChartTrades {
+        self.current_symbol_display.as_ref().and_then(|symbol| self.trades.get(symbol)).cloned().unwrap_or_default()
+    }
+
+    /// Puts the command with the key `key` first in the history, keeping the last `MAX_COMMAND_HISTORY` commands.
+    pub fn record_command(&mut self, key: &str) {
+        self.command_history.retain(|used| used != key);
+        self.command_history.insert(0, key.to_string());
+        self.command_history.truncate(MAX_COMMAND_HISTORY);
+    }
+
+    /// Puts `path` first in the recent files, keeping the last `MAX_RECENT_FILES`.
+    pub fn add_recent_file(&mut self, path: &str, kind: RecentFileKind) {
+        self.recent_files.retain(|recent| recent.path != path);
+        self.recent_files.insert(0, RecentFile { path: path.to_string(), kind });
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+
+    pub fn panel_open(&self, panel: Panel) -> bool {
+        self.open_panels.contains(&panel)
+    }
+
+    pub fn toggle_panel(&mut self, panel: Panel) {
+        match self.panel_open(panel) {
+            true => self.open_panels.retain(|open| *open != panel),
+            false => self.open_panels.push(panel),
+        }
+    }
+
+    pub fn request_chart_zoom(&mut self, step: ZoomStep) {
+        let number = self.chart_zoom_request.map_or(1, |(number, _)| number + 1);
+        self.chart_zoom_request = Some((number, step));
+    }
+
+    /// Opens the chart of `trade`'s symbol on the time it was held. Symbols not loaded in the GUI
+    /// are not opened; returns whether the chart shows the trade.
+    pub fn show_trade(&mut self, trade: &shared::models::Trade) -> bool {
+        if !self.all_market_data.contains_key(&trade.symbol) {
+            return false;
+        }
+        if self.current_symbol_display.as_deref() != Some(trade.symbol.as_str()) {
+            self.set_display_data(&trade.symbol);
+        }
+        self.request_chart_zoom(ZoomStep::Span { first: trade.entry_time, last: trade.exit_time });
+        true
+    }
+
+    pub fn start_loading(&mut self, label: impl Into<String>) {
+        self.loading = Some(LoadingProgress { label: label.into(), progress: None, message: String::new() });
+    }
+
+    /// Progress the engine reported for the operation started with `start_loading`.
+    pub fn update_loading(&mut self, progress: f64, message: &str) {
+        if let Some(loading) = self.loading.as_mut() {
+            loading.progress = Some(progress.clamp(0.0, 1.0));
+            loading.message = message.to_string();
+        }
+    }
+
+    pub fn finish_loading(&mut self) {
+        self.loading = None;
+    }
+
+    /// Adds `symbol` to the watchlist; false if it was there already.
+    pub fn add_to_watchlist(&mut self, symbol: &str) -> bool {
+        if self.watchlist.iter().any(|watched| watched == symbol) {
+            return false;
+        }
+        self.watchlist.push(symbol.to_string());
+        true
+    }
+
+    pub fn remove_from_watchlist(&mut self, symbol: &str) {
+        self.watchlist.retain(|watched| watched != symbol);
+    }
+
+    /// Latest close, daily change and sparkline of `symbol`, once its candles are loaded.
+    pub fn watch_quote(&self, symbol: &str) -> Option<WatchQuote> {
+        self.all_market_data.get(symbol).and_then(|market_data| WatchQuote::from_candles(&market_data.candles))
+    }
+
+    /// Alert hook: the symbol's alert levels the price crossed moving from `previous_price` to `price`
+    /// (reaching a level counts, leaving it does not). Callers decide how to notify.
+    pub fn crossed_price_alerts(&self, symbol: &str, previous_price: f64, price: f64) -> Vec<PriceLevel> {
+        let (low, high) = (previous_price.min(price), previous_price.max(price));
+        self.price_levels.get(symbol).into_iter().flatten()
+            .filter(|level| level.alert && level.price != previous_price && (low..=high).contains(&level.price))
+            .cloned()
+            .collect()
+    }
+
+    // The displayed indicators are the current symbol's visible ones.
+    fn refresh_indicators_display(&mut self) {
+        let Some(symbol) = self.current_symbol_display.clone() else { return };
+        self.current_indicators_display = self.all_indicators.get(&symbol).into_iter().flatten()
+            .filter(|indicator| self.indicator_style(&symbol, &indicator.name).visible)
+            .cloned()
+            .collect();
+    }
+
     // More methods as needed...
 }
 
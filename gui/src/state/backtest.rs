@@ -0,0 +1,42 @@
+// Results of the last backtest run from the GUI, shown in the backtest results view: the engine's
+// report (trades and equity curve), metrics and benchmark comparison.
+use engine::services::{BacktestResponse, GrpcEquityPoint, GrpcPerformanceMetrics};
+use shared::models::Trade;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacktestResults {
+    pub symbol: String,
+    pub strategy: String,
+    /// The report's closed trades, converted from the response.
+    pub trades: Vec<Trade>,
+    pub response: BacktestResponse,
+}
+
+impl BacktestResults {
+
+    pub fn equity_curve(&self) -> &[GrpcEquityPoint] {
+        self.response.report.as_ref().map_or(&[], |report| &report.equity_curve)
+    }
+
+    pub fn metrics(&self) -> GrpcPerformanceMetrics {
+        self.response.metrics.clone().unwrap_or_default()
+    }
+
+    /// Return on the initial capital, in percent; `None` without a report.
+    pub fn total_return_pct(&self) -> Option<f64> {
+        let report = self.response.report.as_ref().filter(|report| report.initial_capital > 0.0)?;
+        Some((report.final_equity / report.initial_capital - 1.0) * 100.0)
+    }
+}
+
+/// Drawdown at each point of `equity_curve`: how far below its highest value so far the equity
+/// was, in percent (0 at a new high, negative below it).
+pub fn drawdown_curve(equity_curve: &[GrpcEquityPoint]) -> Vec<f64> {
+    let mut peak = f64::MIN;
+    equity_curve.iter()
+        .map(|point| {
+            peak = peak.max(point.equity);
+            if peak > 0.0 { (point.equity / peak - 1.0) * 100.0 } else { 0.0 }
+        })
+        .collect()
+}
@@ -0,0 +1,83 @@
+// CSV files dropped on the window, waiting for the user to confirm how to load them. The symbol is
+// guessed from the file's first row (its "Ativo" column in B3 exports) or else from the file name,
+// and the timeframe from the file name (e.g. PETR4_M5.csv) or else the symbol's settings; both can
+// be changed before loading.
+use shared::models::TimeFrame;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::config::AppConfig;
+
+/// Header names of the column holding the symbol.
+const SYMBOL_COLUMNS: [&str; 3] = ["ativo", "symbol", "ticker"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DroppedCsv {
+    pub path: String,
+    pub symbol: String,
+    pub timeframe: TimeFrame,
+}
+
+impl DroppedCsv {
+    /// The file at `path` with its symbol and timeframe guessed; a timeframe the file name does not
+    /// give is the one `config` sets for the symbol, or daily.
+    pub fn inspect(path: &str, config: &AppConfig) -> Self {
+        let symbol = symbol_from_rows(path).unwrap_or_else(|| symbol_from_name(path));
+        let timeframe = timeframe_from_name(path)
+            .or_else(|| config.symbol_settings(&symbol).and_then(|settings| settings.timeframe))
+            .unwrap_or(TimeFrame::Day1);
+        DroppedCsv { path: path.to_string(), symbol, timeframe }
+    }
+
+    /// Whether `path` names a CSV file, whatever the case of its extension.
+    pub fn is_csv(path: &str) -> bool {
+        Path::new(path).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv"))
+    }
+}
+
+/// The symbol in the first data row, if the header has a symbol column.
+fn symbol_from_rows(path: &str) -> Option<String> {
+    let mut lines = BufReader::new(File::open(path).ok()?).lines();
+    let header = lines.next()?.ok()?;
+    let delimiter = if header.contains(';') { ';' } else { ',' };
+    let column = header.split(delimiter).position(|name| {
+        let name = name.trim().trim_start_matches('\u{feff}').trim_matches('"').to_lowercase();
+        SYMBOL_COLUMNS.contains(&name.as_str())
+    })?;
+    let row = lines.next()?.ok()?;
+    let symbol = row.split(delimiter).nth(column)?.trim().trim_matches('"').to_uppercase();
+    (!symbol.is_empty()).then_some(symbol)
+}
+
+/// Lowercase words of the file name, without its extension.
+fn name_words(path: &str) -> Vec<String> {
+    let stem = Path::new(path).file_stem().map(|stem| stem.to_string_lossy().to_lowercase()).unwrap_or_default();
+    stem.split(|c: char| !c.is_ascii_alphanumeric()).filter(|word| !word.is_empty()).map(str::to_string).collect()
+}
+
+/// The first word of the file name, e.g. PETR4 for petr4_m5.csv.
+fn symbol_from_name(path: &str) -> String {
+    name_words(path).first().map_or_else(|| "CSV".to_string(), |word| word.to_uppercase())
+}
+
+/// The timeframe named in the file name, if any.
+fn timeframe_from_name(path: &str) -> Option<TimeFrame> {
+    let words = name_words(path);
+    let named = |names: &[&str]| words.iter().any(|word| names.contains(&word.as_str()));
+    if named(&["m1", "1m", "1min"]) {
+        Some(TimeFrame::Minute1)
+    } else if named(&["m5", "5m", "5min"]) {
+        Some(TimeFrame::Minute5)
+    } else if named(&["m15", "15m", "15min"]) {
+        Some(TimeFrame::Minute15)
+    } else if named(&["m30", "30m", "30min"]) {
+        Some(TimeFrame::Minute30)
+    } else if named(&["h1", "1h", "60m", "60min"]) {
+        Some(TimeFrame::Hour1)
+    } else if named(&["d1", "1d", "daily", "diario"]) {
+        Some(TimeFrame::Day1)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,57 @@
+// Undo and redo of chart annotations: the price levels drawn on a symbol's chart and the
+// indicators added to or removed from it. Before each such edit, the symbol's annotations are
+// kept as they were; undoing puts them back, keeping the edited ones for redoing.
+use shared::models::Indicator;
+use std::collections::HashMap;
+
+use super::app_state::{IndicatorStyle, PriceLevel};
+
+/// Edits that can be undone, oldest dropped first.
+pub const MAX_UNDO_STEPS: usize = 50;
+
+/// A symbol's annotations at some point; indicators keep their values, so restoring one needs
+/// no recalculation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartAnnotations {
+    pub symbol: String,
+    pub price_levels: Vec<PriceLevel>,
+    pub indicators: Vec<Indicator>,
+    pub indicator_styles: HashMap<String, IndicatorStyle>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EditHistory {
+    undo: Vec<ChartAnnotations>,
+    redo: Vec<ChartAnnotations>,
+}
+
+impl EditHistory {
+    /// Keeps `before` to undo the edit about to be made; edits undone so far can no longer be redone.
+    pub fn record(&mut self, before: ChartAnnotations) {
+        if self.undo.len() == MAX_UNDO_STEPS {
+            self.undo.remove(0);
+        }
+        self.undo.push(before);
+        self.redo.clear();
+    }
+
+    /// The annotations to restore to undo the last edit, keeping `current` (those of the same
+    /// symbol, as `current` gives them) to redo it.
+    pub fn undo(&mut self, current: impl FnOnce(&str) -> ChartAnnotations) -> Option<ChartAnnotations> {
+        let before = self.undo.pop()?;
+        self.redo.push(current(&before.symbol));
+        Some(before)
+    }
+
+    /// The annotations to restore to redo the last edit undone; the reverse of `undo`.
+    pub fn redo(&mut self, current: impl FnOnce(&str) -> ChartAnnotations) -> Option<ChartAnnotations> {
+        let after = self.redo.pop()?;
+        self.undo.push(current(&after.symbol));
+        Some(after)
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
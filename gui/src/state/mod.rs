@@ -1,5 +1,13 @@
 // Application state management module
 pub mod app_state;
+pub mod backtest; // Results of the last backtest, for the results view
+pub mod csv_drop; // CSV files dropped on the window, before they are loaded
+pub mod history; // Undo and redo of the chart's price levels and indicators
+pub mod preferences; // Choices saved between runs, e.g. the theme
+pub mod project; // Workspaces saved to and opened from project files
+pub mod session; // Where the GUI was left (window, chart, panels, recent files), restored at launch
+pub mod trades; // Fills and positions drawn on the chart
+pub mod watchlist; // Quotes of the watched symbols
 
 // Re-export for easier access
 // pub use app_state::AppState;
@@ -0,0 +1,78 @@
+// User preferences kept between runs (theme, language, watchlist, keyboard shortcuts), in a small JSON file
+// whose path is `app.preferences_file` in the GUI config.
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::app_state::Theme;
+use crate::config::Shortcuts;
+use crate::i18n::Language;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserPreferences {
+    /// Theme last picked; `None` uses the config's `app.theme`.
+    pub theme: Option<Theme>,
+    /// Language last picked; `None` uses the config's `app.language`.
+    pub language: Option<Language>,
+    /// Symbols in the watchlist, in the order they were added.
+    pub watchlist: Vec<String>,
+    /// Shortcuts rebound in the shortcut editor; `None` uses the config's `shortcuts` section.
+    pub shortcuts: Option<Shortcuts>,
+}
+
+impl UserPreferences {
+    /// The saved preferences, or the defaults if none were saved yet or the file is unreadable.
+    pub fn load(path: &str) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable preferences file {}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> anyhow::Result<()> {
+        if let Some(dir) = Path::new(path).parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Remembers `theme` for the next runs; failures are only logged, the theme still applies now.
+pub fn save_theme(path: &str, theme: Theme) {
+    let mut preferences = UserPreferences::load(path);
+    preferences.theme = Some(theme);
+    if let Err(e) = preferences.save(path) {
+        tracing::warn!("Failed to save the theme to {}: {}", path, e);
+    }
+}
+
+/// Remembers `language` for the next runs; failures are only logged, the language still applies now.
+pub fn save_language(path: &str, language: Language) {
+    let mut preferences = UserPreferences::load(path);
+    preferences.language = Some(language);
+    if let Err(e) = preferences.save(path) {
+        tracing::warn!("Failed to save the language to {}: {}", path, e);
+    }
+}
+
+/// Remembers the watchlist for the next runs; failures are only logged.
+pub fn save_watchlist(path: &str, watchlist: &[String]) {
+    let mut preferences = UserPreferences::load(path);
+    preferences.watchlist = watchlist.to_vec();
+    if let Err(e) = preferences.save(path) {
+        tracing::warn!("Failed to save the watchlist to {}: {}", path, e);
+    }
+}
+
+/// Remembers the keyboard shortcuts for the next runs; failures are only logged.
+pub fn save_shortcuts(path: &str, shortcuts: &Shortcuts) {
+    let mut preferences = UserPreferences::load(path);
+    preferences.shortcuts = Some(shortcuts.clone());
+    if let Err(e) = preferences.save(path) {
+        tracing::warn!("Failed to save the keyboard shortcuts to {}: {}", path, e);
+    }
+}
@@ -0,0 +1,106 @@
+// Projects: the workspace saved to a JSON file and restored later. Candles and indicator values
+// are not saved; on opening, the files are loaded into the engine again if it lacks the symbols
+// and the indicators are recalculated from their parameters (see `services::project`).
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::app_state::{AppState, IndicatorStyle, PriceLevel, Theme};
+use crate::components::chart::indicators::indicator_type;
+
+/// Format version written to new project files.
+pub const PROJECT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectIndicator {
+    pub indicator_type: String, // e.g. "SMA"
+    pub parameters: serde_json::Value, // e.g. {"period": 20}
+}
+
+/// A symbol of the workspace with what was added to its chart.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectSymbol {
+    pub symbol: String,
+    /// CSV file the symbol was loaded from, to load it again; `None` if it came from the engine.
+    pub source_file: Option<String>,
+    pub indicators: Vec<ProjectIndicator>,
+    /// Indicator panel choices, by indicator name.
+    pub indicator_styles: HashMap<String, IndicatorStyle>,
+    pub price_levels: Vec<PriceLevel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Project {
+    pub version: u32,
+    pub symbols: Vec<ProjectSymbol>,
+    // Layout
+    pub current_symbol: Option<String>,
+    pub watchlist: Vec<String>,
+    pub theme: Theme,
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self { version: PROJECT_VERSION, symbols: Vec::new(), current_symbol: None, watchlist: Vec::new(), theme: Theme::Dark }
+    }
+}
+
+impl Project {
+    /// The workspace in `app_state`: every symbol with candles in the GUI, sorted.
+    pub fn from_state(app_state: &AppState) -> Self {
+        let mut symbols: Vec<&String> = app_state.all_market_data.keys().collect();
+        symbols.sort();
+        Project {
+            version: PROJECT_VERSION,
+            symbols: symbols.into_iter().map(|symbol| ProjectSymbol {
+                symbol: symbol.clone(),
+                source_file: app_state.symbol_sources.get(symbol).cloned(),
+                indicators: app_state.all_indicators.get(symbol).into_iter().flatten()
+                    .map(|indicator| ProjectIndicator { indicator_type: indicator_type(&indicator.name), parameters: indicator.parameters.clone() })
+                    .collect(),
+                indicator_styles: app_state.indicator_styles.get(symbol).cloned().unwrap_or_default(),
+                price_levels: app_state.price_levels.get(symbol).cloned().unwrap_or_default(),
+            }).collect(),
+            current_symbol: app_state.current_symbol_display.clone(),
+            watchlist: app_state.watchlist.clone(),
+            theme: app_state.current_theme.clone(),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> anyhow::Result<()> {
+        if let Some(dir) = Path::new(path).parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let project: Project = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if project.version > PROJECT_VERSION {
+            anyhow::bail!(t!("error-project-newer", path = path, version = project.version, supported = PROJECT_VERSION));
+        }
+        Ok(project)
+    }
+
+    /// Replaces the workspace in `app_state` with this project's layout, drawings and styles; the
+    /// symbols' candles and indicators are fetched afterwards.
+    pub fn apply_layout(&self, app_state: &mut AppState) {
+        app_state.all_market_data.clear();
+        app_state.all_indicators.clear();
+        app_state.trades.clear();
+        app_state.current_symbol_display = None;
+        app_state.current_candles_display = None;
+        app_state.current_indicators_display = Vec::new();
+        app_state.edit_history.clear();
+        app_state.indicator_styles = self.symbols.iter().map(|symbol| (symbol.symbol.clone(), symbol.indicator_styles.clone())).collect();
+        app_state.price_levels = self.symbols.iter().map(|symbol| (symbol.symbol.clone(), symbol.price_levels.clone())).collect();
+        app_state.symbol_sources = self.symbols.iter()
+            .filter_map(|symbol| symbol.source_file.clone().map(|file| (symbol.symbol.clone(), file)))
+            .collect();
+        app_state.watchlist = self.watchlist.clone();
+        app_state.set_theme(self.theme.clone());
+    }
+}
@@ -0,0 +1,109 @@
+// The session: where the GUI was left, restored on the next launch. The window's size and position,
+// the chart shown, which panels are open and the files opened recently are kept in a JSON file in
+// the platform's data directory (see `session_path`) and saved as they change (see
+// `services::session`). Choices made on purpose, like the theme, are preferences instead.
+use serde::{Deserialize, Serialize};
+use shared::models::TimeFrame;
+use std::path::{Path, PathBuf};
+
+use super::app_state::{AppState, Panel};
+
+/// Files kept in `AppState::recent_files`.
+pub const MAX_RECENT_FILES: usize = 8;
+
+/// The window's inner size and outer position, in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub width: f64,
+    pub height: f64,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// The chart last shown: fetched again from the engine on the next launch, loaded from its CSV
+/// file first if the engine no longer has the symbol.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LastChart {
+    pub symbol: String,
+    pub timeframe: TimeFrame,
+    pub source_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecentFileKind {
+    Csv,
+    Project,
+}
+
+/// A file opened through the command palette, offered again by its "Open Recent" commands.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub path: String,
+    pub kind: RecentFileKind,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    pub window: Option<WindowGeometry>,
+    pub last_chart: Option<LastChart>,
+    /// Panels shown; `None` shows them all.
+    pub open_panels: Option<Vec<Panel>>,
+    /// Most recent first.
+    pub recent_files: Vec<RecentFile>,
+}
+
+impl SessionState {
+    /// The saved session, or an empty one if none was saved yet or the file is unreadable.
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable session file {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The session of `app_state` with its window at `window`.
+    pub fn from_state(app_state: &AppState, window: Option<WindowGeometry>) -> Self {
+        let last_chart = app_state.current_symbol_display.as_ref().and_then(|symbol| {
+            let market_data = app_state.all_market_data.get(symbol)?;
+            Some(LastChart { symbol: symbol.clone(), timeframe: market_data.timeframe, source_file: app_state.symbol_sources.get(symbol).cloned() })
+        });
+        SessionState {
+            window,
+            last_chart,
+            open_panels: Some(app_state.open_panels.clone()),
+            recent_files: app_state.recent_files.clone(),
+        }
+    }
+
+    /// Puts the session's panels and recent files in `app_state`; its chart is fetched once the
+    /// engine is connected (see `AppState::pending_chart`).
+    pub fn apply(&self, app_state: &mut AppState) {
+        if let Some(panels) = &self.open_panels {
+            app_state.open_panels = panels.clone();
+        }
+        app_state.recent_files = self.recent_files.clone();
+        app_state.recent_files.truncate(MAX_RECENT_FILES);
+        app_state.pending_chart = self.last_chart.clone();
+    }
+}
+
+/// The session file, in the platform's data directory (e.g. ~/.local/share/home-trader/session.json);
+/// under `data/` where there is none.
+pub fn session_path() -> PathBuf {
+    match dirs::data_dir() {
+        Some(dir) => dir.join("home-trader").join("session.json"),
+        None => PathBuf::from("data/session.json"),
+    }
+}
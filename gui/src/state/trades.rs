@@ -0,0 +1,93 @@
+// Trades drawn on the chart: fills as buy/sell markers, and positions as entry-to-exit spans.
+// Built from the engine's trade history (simulated fills) or from backtest trade records.
+use chrono::{DateTime, Utc};
+use engine::services::GrpcExecution;
+use serde::{Deserialize, Serialize};
+use shared::models::{to_f64, OrderSide, Price, Trade, TradeDirection};
+
+/// One fill: an arrow at its candle, with its details in a tooltip.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeMarker {
+    pub timestamp: DateTime<Utc>,
+    pub side: OrderSide,
+    pub price: f64,
+    pub quantity: f64,
+    /// P&L the fill realized, if it closed (part of) a position.
+    pub realized_pnl: Option<f64>,
+}
+
+/// A position from entry to exit; `exit` is `None` while it is still open.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionSpan {
+    pub entry: (DateTime<Utc>, f64),
+    pub exit: Option<(DateTime<Utc>, f64)>,
+    pub long: bool,
+    pub pnl: f64,
+}
+
+/// A symbol's trades as the chart draws them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChartTrades {
+    pub markers: Vec<TradeMarker>,
+    pub spans: Vec<PositionSpan>,
+}
+
+fn timestamp(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
+
+impl ChartTrades {
+    /// From a symbol's fills, oldest first: a span opens when the position leaves zero and
+    /// closes when it returns to zero (or flips, which also opens the next one).
+    pub fn from_executions(executions: &[GrpcExecution]) -> Self {
+        let mut trades = ChartTrades::default();
+        let mut position = 0.0;
+        let mut open_span: Option<PositionSpan> = None;
+        for execution in executions {
+            let side = OrderSide::parse(&execution.side).unwrap_or(OrderSide::Sell);
+            let filled_at = timestamp(execution.timestamp);
+            // Prices come as decimal strings; the chart draws them as floats.
+            let price = execution.price.parse::<Price>().map(to_f64).unwrap_or_default();
+            let realized_pnl = execution.realized_pnl.parse::<Price>().map(to_f64).unwrap_or_default();
+            trades.markers.push(TradeMarker {
+                timestamp: filled_at,
+                side,
+                price,
+                quantity: execution.quantity,
+                realized_pnl: (realized_pnl != 0.0).then_some(realized_pnl),
+            });
+            if let Some(span) = open_span.as_mut() {
+                span.pnl += realized_pnl;
+            }
+            let position_after = execution.position_after;
+            let closed = position != 0.0 && (position_after == 0.0 || position_after.signum() != position.signum());
+            if closed {
+                if let Some(mut span) = open_span.take() {
+                    span.exit = Some((filled_at, price));
+                    trades.spans.push(span);
+                }
+            }
+            if position_after != 0.0 && (position == 0.0 || closed) {
+                open_span = Some(PositionSpan { entry: (filled_at, price), exit: None, long: position_after > 0.0, pnl: 0.0 });
+            }
+            position = position_after;
+        }
+        trades.spans.extend(open_span);
+        trades
+    }
+
+    /// From backtest trades: an entry and an exit marker and a closed span per trade.
+    pub fn from_trades(records: &[Trade]) -> Self {
+        let mut trades = ChartTrades::default();
+        for record in records {
+            let entry_side = record.direction.entry_side();
+            let (entered_at, exited_at) = (record.entry_time, record.exit_time);
+            let (entry_price, exit_price, pnl) = (to_f64(record.entry_price), to_f64(record.exit_price), to_f64(record.pnl));
+            trades.markers.push(TradeMarker { timestamp: entered_at, side: entry_side, price: entry_price, quantity: record.quantity, realized_pnl: None });
+            trades.markers.push(TradeMarker { timestamp: exited_at, side: entry_side.opposite(), price: exit_price, quantity: record.quantity, realized_pnl: Some(pnl) });
+            let long = record.direction == TradeDirection::Long;
+            trades.spans.push(PositionSpan { entry: (entered_at, entry_price), exit: Some((exited_at, exit_price)), long, pnl });
+        }
+        trades
+    }
+}
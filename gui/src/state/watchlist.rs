@@ -0,0 +1,32 @@
+// Watchlist quotes: the latest close of a symbol, its change over the day and a sparkline of its
+// recent closes, computed from the candles the GUI holds (kept current by live updates).
+use shared::models::Candle;
+
+/// Number of closes drawn in a watchlist sparkline.
+pub const SPARKLINE_CANDLES: usize = 30;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchQuote {
+    pub last: f64,
+    /// Change from the previous day's last close (from the day's open on the first day).
+    pub change: f64,
+    pub change_pct: f64,
+    /// The latest closes, oldest first.
+    pub sparkline: Vec<f64>,
+}
+
+impl WatchQuote {
+    /// The quote of a symbol's candles (oldest first); `None` without candles.
+    pub fn from_candles(candles: &[Candle]) -> Option<Self> {
+        let last = candles.last()?;
+        let day = last.timestamp.date_naive();
+        let reference = candles.iter().rev()
+            .find(|candle| candle.timestamp.date_naive() < day)
+            .map(|candle| candle.close_f64())
+            .unwrap_or_else(|| candles.iter().find(|candle| candle.timestamp.date_naive() == day).map_or(last.open_f64(), |first| first.open_f64()));
+        let change = last.close_f64() - reference;
+        let change_pct = if reference != 0.0 { change / reference * 100.0 } else { 0.0 };
+        let sparkline = candles[candles.len().saturating_sub(SPARKLINE_CANDLES)..].iter().map(|candle| candle.close_f64()).collect();
+        Some(WatchQuote { last: last.close_f64(), change, change_pct, sparkline })
+    }
+}
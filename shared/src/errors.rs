@@ -0,0 +1,102 @@
+// Error codes shared by the engine and its clients.
+// The engine sends one with every failed call (as the reason of its error details), so a client can
+// tell a missing file from a malformed one or from a symbol with no data without reading the message.
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    Config,
+    /// The file could not be opened.
+    FileNotFound,
+    Io,
+    /// The CSV reader failed, e.g. on a record with too many fields.
+    CsvFile,
+    /// A value in the file could not be read; the details name the line and column when known.
+    CsvDataFormat,
+    /// No candles for the symbol, timeframe or range asked for.
+    MarketDataNotFound,
+    MarketData,
+    Indicator,
+    /// No order, position or alert with the given ID.
+    SimulationNotFound,
+    Simulation,
+    InvalidRequest,
+    DeadlineExceeded,
+    RateLimited,
+    /// The engine requires an auth token and the call had none, or a wrong one.
+    Unauthenticated,
+    Processing,
+    Internal,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 16] = [
+        ErrorCode::Config, ErrorCode::FileNotFound, ErrorCode::Io, ErrorCode::CsvFile, ErrorCode::CsvDataFormat,
+        ErrorCode::MarketDataNotFound, ErrorCode::MarketData, ErrorCode::Indicator, ErrorCode::SimulationNotFound,
+        ErrorCode::Simulation, ErrorCode::InvalidRequest, ErrorCode::DeadlineExceeded, ErrorCode::RateLimited,
+        ErrorCode::Unauthenticated, ErrorCode::Processing, ErrorCode::Internal,
+    ];
+
+    /// The name sent on the wire, e.g. "CSV_DATA_FORMAT".
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::Config => "CONFIG",
+            ErrorCode::FileNotFound => "FILE_NOT_FOUND",
+            ErrorCode::Io => "IO",
+            ErrorCode::CsvFile => "CSV_FILE",
+            ErrorCode::CsvDataFormat => "CSV_DATA_FORMAT",
+            ErrorCode::MarketDataNotFound => "MARKET_DATA_NOT_FOUND",
+            ErrorCode::MarketData => "MARKET_DATA",
+            ErrorCode::Indicator => "INDICATOR",
+            ErrorCode::SimulationNotFound => "SIMULATION_NOT_FOUND",
+            ErrorCode::Simulation => "SIMULATION",
+            ErrorCode::InvalidRequest => "INVALID_REQUEST",
+            ErrorCode::DeadlineExceeded => "DEADLINE_EXCEEDED",
+            ErrorCode::RateLimited => "RATE_LIMITED",
+            ErrorCode::Unauthenticated => "UNAUTHENTICATED",
+            ErrorCode::Processing => "PROCESSING",
+            ErrorCode::Internal => "INTERNAL",
+        }
+    }
+
+    /// Whether the file given was unreadable or malformed, rather than the engine failing.
+    pub fn is_bad_file(&self) -> bool {
+        matches!(self, ErrorCode::FileNotFound | ErrorCode::CsvFile | ErrorCode::CsvDataFormat)
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ErrorCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ErrorCode::ALL.into_iter()
+            .find(|code| code.as_str() == s)
+            .ok_or_else(|| format!("Unknown error code '{}'", s))
+    }
+}
+
+impl From<ErrorCode> for String {
+    fn from(code: ErrorCode) -> Self {
+        code.as_str().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_parse_what_they_print() {
+        for code in ErrorCode::ALL {
+            assert_eq!(code.to_string().parse::<ErrorCode>(), Ok(code));
+        }
+        assert!("csv_data_format".parse::<ErrorCode>().is_err());
+    }
+}
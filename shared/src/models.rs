@@ -1,18 +1,300 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+
+/// Prices are exact decimals, so fills, average prices and P&L add up without floating-point drift.
+/// Math that needs floats (indicators, statistics) converts with `to_f64` and back with `to_price`.
+pub type Price = Decimal;
+
+/// Decimal places kept when a price is made from an `f64`.
+pub const PRICE_SCALE: u32 = 8;
+
+/// `value` as a price, rounded to `PRICE_SCALE` places; zero if it is not finite.
+pub fn to_price(value: f64) -> Price {
+    Decimal::from_f64_retain(value).unwrap_or_default().round_dp(PRICE_SCALE).normalize()
+}
+
+/// `price` as an `f64`, for math that needs floats.
+pub fn to_f64(price: Price) -> f64 {
+    price.to_f64().unwrap_or_default()
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)] // Added PartialEq
 pub struct Candle {
     pub symbol: String,
     pub timestamp: DateTime<Utc>, // DateTime<Utc> implements PartialEq
-    pub open: f64,
-    pub high: f64,
-    pub low: f64,
-    pub close: f64,
+    pub open: Price,
+    pub high: Price,
+    pub low: Price,
+    pub close: Price,
     pub volume: f64,
     pub trades: u32,
 }
 
+impl Candle {
+    pub fn open_f64(&self) -> f64 {
+        to_f64(self.open)
+    }
+
+    pub fn high_f64(&self) -> f64 {
+        to_f64(self.high)
+    }
+
+    pub fn low_f64(&self) -> f64 {
+        to_f64(self.low)
+    }
+
+    pub fn close_f64(&self) -> f64 {
+        to_f64(self.close)
+    }
+
+    /// Whether the prices are consistent (open and close within [low, high]) and the volume is
+    /// a non-negative number.
+    pub fn is_valid(&self) -> bool {
+        self.low <= self.high
+            && (self.low..=self.high).contains(&self.open)
+            && (self.low..=self.high).contains(&self.close)
+            && self.volume.is_finite()
+            && self.volume >= 0.0
+    }
+
+    /// Closed at or above the open; a doji counts as bullish, as charts usually color it.
+    pub fn is_bullish(&self) -> bool {
+        self.close >= self.open
+    }
+
+    /// Extends this bar with `later`, the next bar of the same period: the high and low widen,
+    /// the close, volume and trade count carry on. The open and timestamp stay.
+    pub fn merge(&mut self, later: &Candle) {
+        self.high = self.high.max(later.high);
+        self.low = self.low.min(later.low);
+        self.close = later.close;
+        self.volume += later.volume;
+        self.trades += later.trades;
+    }
+}
+
+/// Candles of one series, sorted by timestamp with no two at the same instant.
+/// The candles are shared: clones and `slice`s point into the same immutable buffer, so handing a
+/// series (or any range of it) to another task copies no candles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "Vec<Candle>", into = "Vec<Candle>")]
+pub struct CandleSeries {
+    candles: Arc<[Candle]>,
+    /// `candles` as columns, extracted on first use and shared like the candles.
+    columns: Arc<OnceLock<CandleColumns>>,
+    /// The part of `candles` this series covers.
+    span: Range<usize>,
+}
+
+impl CandleSeries {
+    /// Sorts `candles`; of candles sharing a timestamp, the last one given is kept.
+    pub fn new(candles: Vec<Candle>) -> Self {
+        let mut series = Self::default();
+        series.merge(candles);
+        series
+    }
+
+    /// Adds `newer` candles; one with the timestamp of a stored candle replaces it (e.g. the
+    /// still-forming last bar), and the last of several at the same timestamp wins.
+    /// Other holders of the series keep seeing the candles as they were.
+    pub fn merge(&mut self, newer: Vec<Candle>) {
+        let mut merged = newer;
+        merged.reverse();
+        merged.extend_from_slice(self);
+        merged.sort_by_key(|c| c.timestamp); // Stable: the newest candle stays ahead of those it replaces
+        merged.dedup_by_key(|c| c.timestamp);
+        self.span = 0..merged.len();
+        self.candles = merged.into();
+        self.columns = Arc::default();
+    }
+
+    /// Candles in [from, to], found by binary search.
+    pub fn range(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> &[Candle] {
+        let span = self.span_of(from, to);
+        &self[span]
+    }
+
+    /// Index of the candle at `timestamp` exactly.
+    pub fn index_of(&self, timestamp: DateTime<Utc>) -> Option<usize> {
+        self.binary_search_by_key(&timestamp, |c| c.timestamp).ok()
+    }
+
+    /// Number of candles before `timestamp`: the index the candle at `timestamp` has or would have.
+    pub fn position(&self, timestamp: DateTime<Utc>) -> usize {
+        self.partition_point(|c| c.timestamp < timestamp)
+    }
+
+    /// The latest candle at or before `timestamp`: the bar that was current then.
+    pub fn at_or_before(&self, timestamp: DateTime<Utc>) -> Option<&Candle> {
+        self.partition_point(|c| c.timestamp <= timestamp).checked_sub(1).map(|i| &self[i])
+    }
+
+    /// The candles in [from, to] as a series of their own, sharing this one's candles.
+    pub fn slice(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> CandleSeries {
+        let span = self.span_of(from, to);
+        self.sub_series(span)
+    }
+
+    /// The first `len` candles, sharing this series' candles.
+    pub fn prefix(&self, len: usize) -> CandleSeries {
+        self.sub_series(0..len.min(self.len()))
+    }
+
+    /// The candles' prices and volumes as columns. Converted from decimals once per set of candles
+    /// and shared by every clone and slice, so indicators run on plain `f64` arrays.
+    pub fn columns(&self) -> ColumnSlices<'_> {
+        self.columns.get_or_init(|| CandleColumns::from_candles(&self.candles)).slice(self.span.clone())
+    }
+
+    /// Copies the candles out.
+    pub fn into_vec(self) -> Vec<Candle> {
+        self.to_vec()
+    }
+
+    /// Indices of the candles in [from, to].
+    fn span_of(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Range<usize> {
+        let start = from.map_or(0, |from| self.position(from));
+        let end = to.map_or(self.len(), |to| self.partition_point(|c| c.timestamp <= to));
+        start..end.max(start)
+    }
+
+    fn sub_series(&self, span: Range<usize>) -> CandleSeries {
+        let offset = self.span.start;
+        CandleSeries { candles: Arc::clone(&self.candles), columns: Arc::clone(&self.columns), span: offset + span.start..offset + span.end }
+    }
+}
+
+impl Default for CandleSeries {
+    fn default() -> Self {
+        CandleSeries { candles: Arc::new([]), columns: Arc::default(), span: 0..0 }
+    }
+}
+
+impl PartialEq for CandleSeries {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl From<Vec<Candle>> for CandleSeries {
+    fn from(candles: Vec<Candle>) -> Self {
+        Self::new(candles)
+    }
+}
+
+impl From<CandleSeries> for Vec<Candle> {
+    fn from(series: CandleSeries) -> Self {
+        series.into_vec()
+    }
+}
+
+impl IntoIterator for CandleSeries {
+    type Item = Candle;
+    type IntoIter = std::vec::IntoIter<Candle>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a CandleSeries {
+    type Item = &'a Candle;
+    type IntoIter = std::slice::Iter<'a, Candle>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl std::ops::Deref for CandleSeries {
+    type Target = [Candle];
+
+    fn deref(&self) -> &[Candle] {
+        &self.candles[self.span.clone()]
+    }
+}
+
+/// Candles as one array per field, prices as `f64`: what number crunching (indicators, statistics) reads.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CandleColumns {
+    pub timestamps: Vec<DateTime<Utc>>,
+    pub open: Vec<f64>,
+    pub high: Vec<f64>,
+    pub low: Vec<f64>,
+    pub close: Vec<f64>,
+    pub volume: Vec<f64>,
+}
+
+impl CandleColumns {
+    pub fn from_candles(candles: &[Candle]) -> Self {
+        let mut columns = CandleColumns {
+            timestamps: Vec::with_capacity(candles.len()),
+            open: Vec::with_capacity(candles.len()),
+            high: Vec::with_capacity(candles.len()),
+            low: Vec::with_capacity(candles.len()),
+            close: Vec::with_capacity(candles.len()),
+            volume: Vec::with_capacity(candles.len()),
+        };
+        for candle in candles {
+            columns.timestamps.push(candle.timestamp);
+            columns.open.push(candle.open_f64());
+            columns.high.push(candle.high_f64());
+            columns.low.push(candle.low_f64());
+            columns.close.push(candle.close_f64());
+            columns.volume.push(candle.volume);
+        }
+        columns
+    }
+
+    pub fn view(&self) -> ColumnSlices<'_> {
+        self.slice(0..self.close.len())
+    }
+
+    /// The rows in `rows`.
+    pub fn slice(&self, rows: Range<usize>) -> ColumnSlices<'_> {
+        ColumnSlices {
+            timestamps: &self.timestamps[rows.clone()],
+            open: &self.open[rows.clone()],
+            high: &self.high[rows.clone()],
+            low: &self.low[rows.clone()],
+            close: &self.close[rows.clone()],
+            volume: &self.volume[rows],
+        }
+    }
+}
+
+/// Borrowed columns of a run of candles, all of the same length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnSlices<'a> {
+    pub timestamps: &'a [DateTime<Utc>],
+    pub open: &'a [f64],
+    pub high: &'a [f64],
+    pub low: &'a [f64],
+    pub close: &'a [f64],
+    pub volume: &'a [f64],
+}
+
+impl ColumnSlices<'_> {
+    pub fn len(&self) -> usize {
+        self.close.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.close.is_empty()
+    }
+
+    /// Average of the high, low and close of bar `i`.
+    pub fn typical_price(&self, i: usize) -> f64 {
+        (self.high[i] + self.low[i] + self.close[i]) / 3.0
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketData {
     pub symbol: String,
@@ -20,6 +302,81 @@ pub struct MarketData {
     pub timeframe: TimeFrame,
 }
 
+/// Summary statistics of a series of candles.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketStats {
+    pub candles: usize,
+    pub first: DateTime<Utc>,
+    pub last: DateTime<Utc>,
+    pub open: Price,
+    pub close: Price,
+    pub high: Price,
+    pub low: Price,
+    /// From the first open to the last close, in percent.
+    pub change_pct: f64,
+    pub average_volume: f64,
+    /// Standard deviation of the close-to-close returns, in percent.
+    pub volatility_pct: f64,
+}
+
+// Candles are kept oldest first.
+impl MarketData {
+    /// The candles in [from, to].
+    pub fn between(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> MarketData {
+        let start = from.map_or(0, |from| self.candles.partition_point(|c| c.timestamp < from));
+        let end = to.map_or(self.candles.len(), |to| self.candles.partition_point(|c| c.timestamp <= to));
+        MarketData {
+            symbol: self.symbol.clone(),
+            candles: self.candles[start..end.max(start)].to_vec(),
+            timeframe: self.timeframe,
+        }
+    }
+
+    /// Close-to-close returns as fractions (0.01 is 1%), one per candle after the first.
+    /// A return from a zero close counts as 0.
+    pub fn returns(&self) -> Vec<f64> {
+        self.candles.windows(2)
+            .map(|pair| {
+                let (previous, current) = (pair[0].close_f64(), pair[1].close_f64());
+                if previous != 0.0 { current / previous - 1.0 } else { 0.0 }
+            })
+            .collect()
+    }
+
+    pub fn high(&self) -> Option<Price> {
+        self.candles.iter().map(|c| c.high).max()
+    }
+
+    pub fn low(&self) -> Option<Price> {
+        self.candles.iter().map(|c| c.low).min()
+    }
+
+    pub fn average_volume(&self) -> Option<f64> {
+        (!self.candles.is_empty()).then(|| self.candles.iter().map(|c| c.volume).sum::<f64>() / self.candles.len() as f64)
+    }
+
+    /// Statistics of the whole series; `None` without candles.
+    pub fn stats(&self) -> Option<MarketStats> {
+        let (first, last) = (self.candles.first()?, self.candles.last()?);
+        let returns = self.returns();
+        let mean = returns.iter().sum::<f64>() / returns.len().max(1) as f64;
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len().saturating_sub(1).max(1) as f64;
+        let open = first.open_f64();
+        Some(MarketStats {
+            candles: self.candles.len(),
+            first: first.timestamp,
+            last: last.timestamp,
+            open: first.open,
+            close: last.close,
+            high: self.high()?,
+            low: self.low()?,
+            change_pct: if open != 0.0 { (last.close_f64() / open - 1.0) * 100.0 } else { 0.0 },
+            average_volume: self.average_volume()?,
+            volatility_pct: variance.sqrt() * 100.0,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum TimeFrame {
     Minute1,
@@ -30,9 +387,499 @@ pub enum TimeFrame {
     Day1,
 }
 
+impl TimeFrame {
+    /// Every timeframe, shortest first.
+    pub const ALL: [TimeFrame; 6] = [
+        TimeFrame::Minute1,
+        TimeFrame::Minute5,
+        TimeFrame::Minute15,
+        TimeFrame::Minute30,
+        TimeFrame::Hour1,
+        TimeFrame::Day1,
+    ];
+
+    /// Short name, as in "5m" or "1d".
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeFrame::Minute1 => "1m",
+            TimeFrame::Minute5 => "5m",
+            TimeFrame::Minute15 => "15m",
+            TimeFrame::Minute30 => "30m",
+            TimeFrame::Hour1 => "1h",
+            TimeFrame::Day1 => "1d",
+        }
+    }
+
+    /// Length of one bar; a daily bar counts as a whole calendar day.
+    pub fn duration(&self) -> Duration {
+        match self {
+            TimeFrame::Minute1 => Duration::minutes(1),
+            TimeFrame::Minute5 => Duration::minutes(5),
+            TimeFrame::Minute15 => Duration::minutes(15),
+            TimeFrame::Minute30 => Duration::minutes(30),
+            TimeFrame::Hour1 => Duration::hours(1),
+            TimeFrame::Day1 => Duration::days(1),
+        }
+    }
+
+    /// Start of the bar `timestamp` falls in, counting bars from the Unix epoch (so daily bars start
+    /// at midnight UTC). Does not know about trading sessions.
+    pub fn align(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        let seconds = self.duration().num_seconds();
+        let start = timestamp.timestamp().div_euclid(seconds) * seconds;
+        DateTime::from_timestamp(start, 0).unwrap_or(timestamp)
+    }
+}
+
+impl fmt::Display for TimeFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TimeFrame {
+    type Err = String;
+
+    /// Parses a short name such as "5m", "1h" or "1d" (case-insensitive).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        TimeFrame::ALL.into_iter()
+            .find(|timeframe| timeframe.as_str() == name)
+            .ok_or_else(|| format!("Unknown timeframe '{}'. Use one of 1m, 5m, 15m, 30m, 1h or 1d.", s.trim()))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)] // Added PartialEq
 pub struct Indicator {
     pub name: String,
     pub parameters: serde_json::Value, // serde_json::Value implements PartialEq
     pub values: Vec<f64>,
 }
+
+/// Residual quantities smaller than this (floating-point noise from fractional fills) count as flat.
+pub const QUANTITY_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl OrderSide {
+    /// Parses an order action ("BUY"/"SELL", case-insensitive).
+    pub fn parse(action: &str) -> Option<Self> {
+        match action.to_uppercase().as_str() {
+            "BUY" => Some(OrderSide::Buy),
+            "SELL" => Some(OrderSide::Sell),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        }
+    }
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeInForce {
+    /// Valid until the end of the trading day (calendar date) it was placed on.
+    Day,
+    /// Good 'til cancelled.
+    Gtc,
+    /// Good 'til date: expires once simulated time passes the given instant.
+    Gtd(DateTime<Utc>),
+}
+
+impl TimeInForce {
+    /// Parses a time in force ("DAY", "GTC" or "GTD", case-insensitive).
+    /// An empty string defaults to `DAY`; `GTD` requires an expiry timestamp (ms).
+    pub fn parse(tif: &str, expire_at_ms: Option<i64>) -> Result<Self, String> {
+        match tif.trim().to_uppercase().as_str() {
+            "" | "DAY" => Ok(TimeInForce::Day),
+            "GTC" => Ok(TimeInForce::Gtc),
+            "GTD" => {
+                let ms = expire_at_ms.ok_or_else(|| "GTD orders require an expire_at timestamp.".to_string())?;
+                let expire_at = DateTime::from_timestamp_millis(ms)
+                    .ok_or_else(|| format!("Invalid expire_at timestamp: {}", ms))?;
+                Ok(TimeInForce::Gtd(expire_at))
+            }
+            other => Err(format!("Unsupported time in force: '{}'. Use 'DAY', 'GTC' or 'GTD'.", other)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeInForce::Day => "DAY",
+            TimeInForce::Gtc => "GTC",
+            TimeInForce::Gtd(_) => "GTD",
+        }
+    }
+
+    /// Returns true if an order placed at `placed_at` is no longer valid at `now`.
+    pub fn is_expired(&self, placed_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        match self {
+            TimeInForce::Day => now.date_naive() > placed_at.date_naive(),
+            TimeInForce::Gtc => false,
+            TimeInForce::Gtd(expire_at) => now > *expire_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    Working,
+    Filled,
+    Expired,
+    Cancelled,
+    /// Never entered the book (validation failure); only reported back to the caller.
+    Rejected,
+}
+
+impl OrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Working => "WORKING",
+            OrderStatus::Filled => "FILLED",
+            OrderStatus::Expired => "EXPIRED",
+            OrderStatus::Cancelled => "CANCELLED",
+            OrderStatus::Rejected => "REJECTED",
+        }
+    }
+}
+
+/// A limit order, from placement until it fills, expires or is cancelled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Order {
+    pub id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub quantity: f64,
+    pub limit_price: Price,
+    pub time_in_force: TimeInForce,
+    /// Simulated time at which the order was placed (timestamp of the latest candle seen).
+    pub placed_at: DateTime<Utc>,
+    pub status: OrderStatus,
+    pub filled_price: Option<Price>,
+    pub filled_at: Option<DateTime<Utc>>,
+}
+
+impl Order {
+    /// A limit order fills when the candle's range reaches the limit price.
+    pub fn is_marketable(&self, candle: &Candle) -> bool {
+        match self.side {
+            OrderSide::Buy => candle.low <= self.limit_price,
+            OrderSide::Sell => candle.high >= self.limit_price,
+        }
+    }
+
+    /// Price a marketable order fills at when the market trades at `market`: the limit, or the
+    /// market price when that is better for the order (a gap through the limit, or a limit
+    /// moved past the market).
+    pub fn fill_price(&self, market: Price) -> Price {
+        match self.side {
+            OrderSide::Buy => self.limit_price.min(market),
+            OrderSide::Sell => self.limit_price.max(market),
+        }
+    }
+}
+
+/// An execution of (part of) an order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fill {
+    pub order_id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: Price,
+    pub quantity: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Holdings in one symbol, with average-cost accounting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub symbol: String,
+    pub quantity: f64, // Signed: > 0 long, < 0 short
+    pub average_price: Price,
+    pub realized_pnl: Price, // In currency
+    /// Currency value of a one-unit price move for one share/contract.
+    #[serde(skip, default = "unit_point_value")] // Looked up again from the symbol when loaded
+    pub point_value: f64,
+}
+
+fn unit_point_value() -> f64 {
+    1.0
+}
+
+impl Position {
+    pub fn new(symbol: &str, point_value: f64) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            quantity: 0.0,
+            average_price: Price::ZERO,
+            realized_pnl: Price::ZERO,
+            point_value,
+        }
+    }
+
+    pub fn is_flat(&self) -> bool {
+        self.quantity == 0.0
+    }
+
+    pub fn unrealized_pnl(&self, mark_price: f64) -> f64 {
+        (mark_price - to_f64(self.average_price)) * self.quantity * self.point_value
+    }
+
+    /// Applies a fill and returns the P&L it realized in currency (non-zero only when it reduces the position).
+    /// A fill larger than an opposite position closes it and opens the remainder the other way at the fill price.
+    pub fn apply(&mut self, side: OrderSide, quantity: f64, price: Price) -> Price {
+        if !quantity.is_finite() || to_price(quantity).is_zero() {
+            return Price::ZERO; // Nothing a position can hold: leave it as it is
+        }
+        let signed_qty = match side {
+            OrderSide::Buy => quantity,
+            OrderSide::Sell => -quantity,
+        };
+        let mut realized = Price::ZERO;
+        if self.quantity == 0.0 || self.quantity.signum() == signed_qty.signum() {
+            // Opening or adding: blend the average price.
+            let new_qty = self.quantity + signed_qty;
+            let held = self.average_price * to_price(self.quantity.abs()) + price * to_price(signed_qty.abs());
+            self.average_price = held / to_price(new_qty.abs());
+            self.quantity = new_qty;
+        } else {
+            // Reducing, closing or flipping.
+            let closing = to_price(signed_qty.abs().min(self.quantity.abs()));
+            let direction = to_price(self.quantity.signum());
+            realized = closing * (price - self.average_price) * direction * to_price(self.point_value);
+            let mut new_qty = self.quantity + signed_qty;
+            if new_qty.abs() < QUANTITY_EPSILON {
+                new_qty = 0.0;
+                self.average_price = Price::ZERO;
+            } else if new_qty.signum() != self.quantity.signum() {
+                self.average_price = price; // Flipped: the remainder opens at the fill price
+            }
+            self.quantity = new_qty;
+        }
+        self.realized_pnl += realized;
+        realized
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TradeDirection {
+    Long,
+    Short,
+}
+
+impl TradeDirection {
+    /// The direction a position opened by `side` takes.
+    pub fn of(side: OrderSide) -> Self {
+        match side {
+            OrderSide::Buy => TradeDirection::Long,
+            OrderSide::Sell => TradeDirection::Short,
+        }
+    }
+
+    /// Parses "LONG"/"SHORT" (case-insensitive).
+    pub fn parse(direction: &str) -> Option<Self> {
+        match direction.to_uppercase().as_str() {
+            "LONG" => Some(TradeDirection::Long),
+            "SHORT" => Some(TradeDirection::Short),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TradeDirection::Long => "LONG",
+            TradeDirection::Short => "SHORT",
+        }
+    }
+
+    /// Side of the fills that open a trade in this direction.
+    pub fn entry_side(&self) -> OrderSide {
+        match self {
+            TradeDirection::Long => OrderSide::Buy,
+            TradeDirection::Short => OrderSide::Sell,
+        }
+    }
+}
+
+/// One round trip: from flat to flat (or until the position flips direction).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trade {
+    pub symbol: String,
+    pub direction: TradeDirection,
+    pub entry_time: DateTime<Utc>,
+    pub exit_time: DateTime<Utc>,
+    pub entry_price: Price, // Average entry price
+    pub exit_price: Price,  // Average exit price
+    pub quantity: f64,
+    pub pnl: Price, // In currency
+    pub return_pct: f64,
+    /// Maximum adverse excursion: worst open loss while the trade was on (<= 0), in currency.
+    pub mae: Price,
+    /// Maximum favorable excursion: best open profit while the trade was on (>= 0), in currency.
+    pub mfe: Price,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_prices_made_from_floats_drop_the_float_noise() {
+        assert_eq!(to_price(0.1 + 0.2), Price::new(3, 1));
+        assert_eq!(to_price(123_950.0).to_string(), "123950");
+        assert_eq!(to_price(f64::NAN), Price::ZERO);
+        assert_eq!(to_f64(Price::new(3_845, 2)), 38.45);
+    }
+
+    fn candle(minute: u32, open: i64, high: i64, low: i64, close: i64) -> Candle {
+        Candle {
+            symbol: "TEST".to_string(),
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 5, 10, minute, 0).unwrap(),
+            open: Price::from(open),
+            high: Price::from(high),
+            low: Price::from(low),
+            close: Price::from(close),
+            volume: 10.0,
+            trades: 2,
+        }
+    }
+
+    #[test]
+    fn test_candles_derive_their_shape() {
+        let bar = candle(0, 10, 14, 8, 11);
+        assert!(bar.is_valid());
+        assert_eq!(CandleColumns::from_candles(std::slice::from_ref(&bar)).view().typical_price(0), 11.0);
+        assert!(bar.is_bullish());
+        assert!(!candle(0, 10, 9, 8, 9).is_valid()); // Opens above the high
+        assert!(!Candle { volume: f64::NAN, ..bar.clone() }.is_valid());
+
+        let mut merged = bar;
+        merged.merge(&candle(1, 12, 15, 9, 9));
+        assert_eq!(merged, Candle { high: Price::from(15), close: Price::from(9), volume: 20.0, trades: 4, ..candle(0, 10, 14, 8, 11) });
+        assert!(!merged.is_bullish());
+    }
+
+    #[test]
+    fn test_candle_series_stay_sorted_and_unique() {
+        let mut series = CandleSeries::new(vec![candle(2, 1, 1, 1, 1), candle(0, 1, 1, 1, 1), candle(2, 2, 2, 2, 2)]);
+        assert_eq!(series.iter().map(|c| c.close).collect::<Vec<_>>(), [Price::from(1), Price::from(2)]);
+        series.merge(vec![candle(1, 3, 3, 3, 3), candle(2, 4, 4, 4, 4)]);
+        assert_eq!(series.iter().map(|c| c.close).collect::<Vec<_>>(), [Price::from(1), Price::from(3), Price::from(4)]);
+
+        let at = |minute| Some(Utc.with_ymd_and_hms(2024, 3, 5, 10, minute, 0).unwrap());
+        assert_eq!(series.range(at(1), None).len(), 2);
+        assert!(series.range(at(2), at(1)).is_empty());
+        assert_eq!(&series.slice(at(1), None)[..], series.range(at(1), None));
+        assert_eq!((series.prefix(2).len(), series.prefix(9).len()), (2, 3));
+        // Slices share the candles, and a merge leaves earlier holders' view as it was.
+        let tail = series.slice(at(1), None);
+        assert!(std::ptr::eq(&tail[0], &series[1]));
+        assert_eq!(tail.slice(None, at(1)).len(), 1);
+        series.merge(vec![candle(1, 5, 5, 5, 5)]);
+        assert_eq!((tail[0].close, series[1].close), (Price::from(3), Price::from(5)));
+        assert_eq!(tail.columns().close, [3.0, 4.0]);
+        assert_eq!((series.index_of(at(1).unwrap()), series.index_of(at(3).unwrap())), (Some(1), None));
+        assert_eq!((series.position(at(1).unwrap()), series.position(at(9).unwrap())), (1, 3));
+        let half_past = Utc.with_ymd_and_hms(2024, 3, 5, 10, 1, 30).unwrap();
+        assert_eq!(series.at_or_before(half_past).map(|c| c.timestamp), at(1));
+        assert!(series.at_or_before(at(0).unwrap() - Duration::seconds(1)).is_none());
+        assert_eq!(series.slice(at(1), at(1)).columns(), CandleColumns::from_candles(&series[1..2]).view());
+
+        let json = serde_json::to_string(&vec![candle(1, 1, 1, 1, 1), candle(0, 1, 1, 1, 1)]).unwrap();
+        let loaded: CandleSeries = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded[0].timestamp, at(0).unwrap());
+    }
+
+    #[test]
+    fn test_market_data_summarizes_its_candles() {
+        let candles = vec![candle(0, 100, 105, 95, 100), candle(1, 100, 112, 99, 110), candle(2, 110, 111, 90, 99)];
+        let data = MarketData { symbol: "TEST".to_string(), candles, timeframe: TimeFrame::Minute1 };
+        let returns = data.returns();
+        assert_eq!(returns.len(), 2);
+        assert!((returns[0] - 0.1).abs() < 1e-12 && (returns[1] + 0.1).abs() < 1e-12);
+
+        let stats = data.stats().unwrap();
+        assert_eq!((stats.candles, stats.high, stats.low, stats.close), (3, Price::from(112), Price::from(90), Price::from(99)));
+        assert!((stats.change_pct + 1.0).abs() < 1e-9);
+        assert_eq!(stats.average_volume, 10.0);
+        assert!((stats.volatility_pct - 0.02_f64.sqrt() * 100.0).abs() < 1e-9);
+
+        let at = |minute| Some(Utc.with_ymd_and_hms(2024, 3, 5, 10, minute, 0).unwrap());
+        let tail = data.between(at(1), None);
+        assert_eq!(tail.candles.len(), 2);
+        assert_eq!(tail.low(), Some(Price::from(90)));
+        assert!(data.between(at(3), None).stats().is_none());
+    }
+
+    #[test]
+    fn test_timeframes_parse_what_they_print() {
+        for timeframe in TimeFrame::ALL {
+            assert_eq!(timeframe.to_string().parse::<TimeFrame>(), Ok(timeframe));
+        }
+        assert_eq!(" 5M ".parse::<TimeFrame>(), Ok(TimeFrame::Minute5));
+        assert!("2m".parse::<TimeFrame>().is_err());
+        assert_eq!(TimeFrame::Hour1.duration(), Duration::minutes(60));
+    }
+
+    #[test]
+    fn test_timestamps_align_to_the_start_of_their_bar() {
+        let at = |h, m, s| Utc.with_ymd_and_hms(2024, 3, 5, h, m, s).unwrap();
+        assert_eq!(TimeFrame::Minute15.align(at(10, 44, 59)), at(10, 30, 0));
+        assert_eq!(TimeFrame::Minute5.align(at(10, 45, 0)), at(10, 45, 0));
+        assert_eq!(TimeFrame::Day1.align(at(17, 55, 0)), at(0, 0, 0));
+        let before_epoch = Utc.with_ymd_and_hms(1969, 12, 31, 23, 59, 0).unwrap();
+        assert_eq!(TimeFrame::Hour1.align(before_epoch), Utc.with_ymd_and_hms(1969, 12, 31, 23, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_order_enums_parse_what_they_print() {
+        for side in [OrderSide::Buy, OrderSide::Sell] {
+            assert_eq!(OrderSide::parse(side.as_str()), Some(side));
+        }
+        for direction in [TradeDirection::Long, TradeDirection::Short] {
+            assert_eq!(TradeDirection::parse(direction.as_str()), Some(direction));
+        }
+        assert_eq!(OrderSide::parse("hold"), None);
+        assert_eq!(TimeInForce::parse("", None), Ok(TimeInForce::Day));
+        assert_eq!(TimeInForce::parse("gtc", None), Ok(TimeInForce::Gtc));
+        assert!(TimeInForce::parse("GTD", None).is_err());
+        assert!(matches!(TimeInForce::parse("GTD", Some(0)), Ok(TimeInForce::Gtd(_))));
+        assert!(TimeInForce::parse("IOC", None).is_err());
+    }
+
+    #[test]
+    fn test_positions_keep_their_point_value_out_of_json() {
+        let mut position = Position::new("WINFUT", 0.2);
+        position.apply(OrderSide::Buy, 2.0, Price::from(100));
+        assert_eq!(position.apply(OrderSide::Sell, 2.0, Price::from(110)), Price::from(4));
+        let json = serde_json::to_string(&position).unwrap();
+        assert!(!json.contains("point_value"));
+        let loaded: Position = serde_json::from_str(&json).unwrap();
+        assert_eq!((loaded.realized_pnl, loaded.point_value), (Price::from(4), 1.0));
+    }
+
+    #[test]
+    fn test_positions_ignore_fills_too_small_to_hold() {
+        let mut position = Position::new("PETR4", 1.0);
+        for quantity in [0.0, 1e-9, f64::NAN, f64::INFINITY] {
+            assert_eq!(position.apply(OrderSide::Buy, quantity, Price::from(100)), Price::ZERO);
+        }
+        assert!(position.is_flat());
+        assert_eq!(position.average_price, Price::ZERO);
+    }
+}
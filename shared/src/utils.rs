@@ -1,38 +1,135 @@
-// Initially empty, to be populated with utility functions as needed.
-// For example, functions for date/time manipulation, common calculations, etc.
-// that are shared across the engine and GUI.
+// Utility functions shared across the engine and GUI.
 
-// Placeholder for potential future brazilian_format module if it's decided to move it to shared.
-// For now, it's planned for engine/src/data/csv_parser.rs as per spec section 7.1.
-/*
+/// Numbers and dates the way B3 writes them (spec section 7.1): "1.234,56", "30/12/2024", "18:20:00".
+/// The GUI shows prices, volumes and times this way, and the engine's CSV exports are written with it,
+/// so a value reads the same on the chart, in an exported file and in the B3 file it came from.
 pub mod brazilian_format {
-    use std::str::FromStr;
-    use anyhow::{Result, anyhow};
+    use anyhow::{anyhow, Result};
+    use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 
+    use crate::models::Price;
+
+    pub const DATE_FORMAT: &str = "%d/%m/%Y";
+    pub const TIME_FORMAT: &str = "%H:%M:%S";
+
+    /// `value` with `decimals` decimal places: 1234.5 with 2 is "1.234,50", 124080.0 with 0 is "124.080".
+    pub fn format_decimal(value: f64, decimals: usize) -> String {
+        localize(&format!("{:.*}", decimals, value))
+    }
+
+    /// A change, always with its sign: "+1.234,50", "-0,25".
+    pub fn format_signed_decimal(value: f64, decimals: usize) -> String {
+        let formatted = format_decimal(value, decimals);
+        if formatted.starts_with('-') || value.is_nan() { formatted } else { format!("+{}", formatted) }
+    }
+
+    /// Every digit of `price`, e.g. "124.080" or "23,5".
+    pub fn format_price(price: Price) -> String {
+        localize(&price.to_string())
+    }
+
+    /// A volume with its cents, e.g. "600.822.115,84".
+    pub fn format_volume(volume: f64) -> String {
+        format_decimal(volume, 2)
+    }
+
+    pub fn format_date(timestamp: DateTime<Utc>) -> String {
+        timestamp.format(DATE_FORMAT).to_string()
+    }
+
+    pub fn format_time(timestamp: DateTime<Utc>) -> String {
+        timestamp.format(TIME_FORMAT).to_string()
+    }
+
+    /// Date and time, e.g. "30/12/2024 18:20:00".
+    pub fn format_datetime(timestamp: DateTime<Utc>) -> String {
+        format!("{} {}", format_date(timestamp), format_time(timestamp))
+    }
+
+    // Parses decimals like "1.234,56" or "123,45" into f64
     pub fn parse_decimal(s: &str) -> Result<f64> {
-        let normalized = s.trim()
-            .replace('.', "")  // Remove thousand separators
-            .replace(',', "."); // Replace decimal separator
+        normalize(s).parse::<f64>()
+            .map_err(|e| anyhow!("Failed to parse decimal '{}': {}", s, e))
+    }
 
-        f64::from_str(&normalized)
+    // Parses prices like "1.234,56" exactly, without going through f64
+    pub fn parse_price(s: &str) -> Result<Price> {
+        normalize(s).parse::<Price>()
+            .map(|price| price.normalize())
             .map_err(|e| anyhow!("Failed to parse decimal '{}': {}", s, e))
     }
 
-    pub fn format_decimal(value: f64, decimals: usize) -> String {
-        let formatted = format!("{:.decimals$}", value, decimals = decimals);
-        formatted.replace('.', ",")
+    pub fn parse_volume(s: &str) -> Result<f64> {
+        parse_decimal(s)
+    }
+
+    // Parses date "dd/mm/yyyy" and time "HH:MM:SS", the CSV's `Data` and `Hora` columns, into DateTime<Utc>
+    pub fn parse_datetime(date_str: &str, time_str: &str) -> Result<DateTime<Utc>> {
+        let date = NaiveDate::parse_from_str(date_str, DATE_FORMAT)
+            .map_err(|e| anyhow!("Failed to parse date '{}': {}", date_str, e))?;
+        let time = NaiveTime::parse_from_str(time_str, TIME_FORMAT)
+            .map_err(|e| anyhow!("Failed to parse time '{}': {}", time_str, e))?;
+        Ok(DateTime::from_naive_utc_and_offset(date.and_time(time), Utc))
+    }
+
+    // "1.234,56" as "1234.56"
+    fn normalize(s: &str) -> String {
+        s.trim().chars()
+            .filter(|c| *c != '.')
+            .map(|c| if c == ',' { '.' } else { c })
+            .collect()
+    }
+
+    // "-1234.56" as "-1.234,56"; anything else (NaN, inf) as it is
+    fn localize(plain: &str) -> String {
+        let (sign, unsigned) = plain.strip_prefix('-').map_or(("", plain), |rest| ("-", rest));
+        let (integer, fraction) = unsigned.split_once('.').map_or((unsigned, None), |(integer, fraction)| (integer, Some(fraction)));
+        if integer.is_empty() || !integer.bytes().all(|b| b.is_ascii_digit()) {
+            return plain.to_string();
+        }
+        let mut localized = String::with_capacity(plain.len() + integer.len() / 3);
+        localized.push_str(sign);
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                localized.push('.');
+            }
+            localized.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            localized.push(',');
+            localized.push_str(fraction);
+        }
+        localized
     }
 
     #[cfg(test)]
     mod tests {
         use super::*;
+        use chrono::TimeZone;
 
         #[test]
         fn test_parse_decimal() {
             assert_eq!(parse_decimal("123,45").unwrap(), 123.45);
             assert_eq!(parse_decimal("1.234,56").unwrap(), 1234.56);
             assert_eq!(parse_decimal("600.822.115,84").unwrap(), 600822115.84);
+            assert!(parse_decimal("12a").is_err());
+        }
+
+        #[test]
+        fn test_formatting_reads_back() {
+            assert_eq!(format_decimal(1234.5, 2), "1.234,50");
+            assert_eq!(format_decimal(124080.0, 0), "124.080");
+            assert_eq!(format_decimal(-0.5, 1), "-0,5");
+            assert_eq!(format_decimal(f64::NAN, 2), "NaN");
+            assert_eq!((format_signed_decimal(1234.5, 2), format_signed_decimal(-0.25, 2)), ("+1.234,50".to_string(), "-0,25".to_string()));
+            assert_eq!(format_volume(600822115.84), "600.822.115,84");
+            assert_eq!(format_price(Price::new(2350, 2)), "23,50");
+            assert_eq!(parse_price(&format_price(Price::from(124080))).unwrap(), Price::from(124080));
+
+            let timestamp = Utc.with_ymd_and_hms(2024, 12, 30, 18, 20, 0).unwrap();
+            assert_eq!(format_datetime(timestamp), "30/12/2024 18:20:00");
+            assert_eq!(parse_datetime(&format_date(timestamp), &format_time(timestamp)).unwrap(), timestamp);
+            assert!(parse_datetime("2024/12/30", "18:20:00").is_err());
         }
     }
 }
-*/